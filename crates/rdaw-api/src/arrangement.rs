@@ -1,16 +1,30 @@
+use rdaw_core::path::Utf8PathBuf;
+use rdaw_core::time::RealTime;
+use serde::{Deserialize, Serialize};
+
 use crate::document::DocumentId;
 use crate::tempo_map::TempoMapId;
-use crate::track::TrackId;
-use crate::{BackendProtocol, BoxStream, Result};
+use crate::time::Time;
+use crate::track::{TrackHierarchy, TrackId, TrackItemId, TrackSummary, TrackViewItem};
+use crate::{BackendProtocol, BoxStream, Page, PageRequest, Result};
 
 slotmap::new_key_type! {
     pub struct ArrangementId;
+
+    pub struct MarkerId;
 }
 
 #[rdaw_rpc::operations(protocol = BackendProtocol)]
 pub trait ArrangementOperations {
     async fn create_arrangement(&self, document_id: DocumentId) -> Result<ArrangementId>;
 
+    #[role(ReadOnly)]
+    async fn list_arrangements(
+        &self,
+        document_id: DocumentId,
+        page: PageRequest,
+    ) -> Result<Page<ArrangementId>>;
+
     #[sub]
     async fn subscribe_arrangement_name(&self, id: ArrangementId) -> Result<BoxStream<String>>;
 
@@ -20,5 +34,93 @@ pub trait ArrangementOperations {
 
     async fn get_arrangement_main_track(&self, id: ArrangementId) -> Result<TrackId>;
 
+    /// Returns the arrangement with all of its tracks and their items, to avoid issuing a
+    /// request per track when opening a big project.
+    async fn get_arrangement_snapshot(&self, id: ArrangementId) -> Result<ArrangementSnapshot>;
+
     async fn get_arrangement_tempo_map(&self, id: ArrangementId) -> Result<TempoMapId>;
+
+    async fn get_arrangement_tempo(&self, id: ArrangementId) -> Result<f32>;
+
+    async fn set_arrangement_tempo(&self, id: ArrangementId, beats_per_minute: f32) -> Result<()>;
+
+    async fn add_arrangement_marker(&self, id: ArrangementId, marker: Marker) -> Result<MarkerId>;
+
+    async fn get_arrangement_marker(
+        &self,
+        id: ArrangementId,
+        marker_id: MarkerId,
+    ) -> Result<Marker>;
+
+    async fn get_arrangement_markers(&self, id: ArrangementId) -> Result<Vec<(MarkerId, Marker)>>;
+
+    async fn remove_arrangement_marker(
+        &self,
+        id: ArrangementId,
+        marker_id: MarkerId,
+    ) -> Result<()>;
+
+    #[role(Admin)]
+    async fn export_arrangement_cue(
+        &self,
+        id: ArrangementId,
+        audio_path: Utf8PathBuf,
+        path: Utf8PathBuf,
+    ) -> Result<()>;
+
+    /// Returns every marker, in order, in a format understood by other tools.
+    #[role(ReadOnly)]
+    async fn export_markers(&self, id: ArrangementId, format: MarkerFormat) -> Result<String>;
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArrangementSnapshot {
+    pub name: String,
+    pub main_track: TrackId,
+    /// Every track reachable from the main track, once, in depth-first order.
+    pub tracks: Vec<ArrangementTrack>,
+}
+
+impl ArrangementSnapshot {
+    pub fn hierarchy(&self) -> TrackHierarchy {
+        let mut hierarchy = TrackHierarchy::new(self.main_track);
+
+        for track in &self.tracks {
+            hierarchy.set_children(track.summary.id, track.summary.children.clone());
+        }
+
+        hierarchy
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArrangementTrack {
+    pub summary: TrackSummary,
+    /// Items as seen from the arrangement, sorted by their start.
+    pub items: Vec<(TrackItemId, TrackViewItem)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Marker {
+    pub position: Time,
+    pub name: String,
+    pub kind: MarkerKind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MarkerKind {
+    Generic,
+    CdTrack { pregap: RealTime },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MarkerFormat {
+    /// `name,start,kind` with a header row, start in seconds.
+    Csv,
+    /// Cue sheet with a track for every marker, not only the CD track ones.
+    Cue { audio_path: Utf8PathBuf },
+    /// `HH:MM:SS.mmm name` lines, as used for podcast chapters.
+    Chapters,
+    /// Tab separated `start end name` lines, as imported by Audacity.
+    AudacityLabels,
 }
@@ -3,7 +3,7 @@ use rdaw_core::path::Utf8PathBuf;
 use serde::{Deserialize, Serialize};
 
 use crate::document::DocumentId;
-use crate::{BackendProtocol, Result};
+use crate::{BackendProtocol, BoxStream, Result};
 
 slotmap::new_key_type! {
     pub struct AssetId;
@@ -11,6 +11,7 @@ slotmap::new_key_type! {
 
 #[rdaw_rpc::operations(protocol = BackendProtocol)]
 pub trait AssetOperations {
+    #[role(Admin)]
     async fn create_external_asset(
         &self,
         document_id: DocumentId,
@@ -23,7 +24,40 @@ pub trait AssetOperations {
         data: Vec<u8>,
     ) -> Result<AssetId>;
 
+    /// Decodes an audio file and embeds it into the document as WAV, so that the document
+    /// doesn't depend on the file, nor on its format being supported later. Import progress
+    /// subscribers of the document are told how far decoding got.
+    #[role(Admin)]
+    async fn import_audio_asset(
+        &self,
+        document_id: DocumentId,
+        path: Utf8PathBuf,
+    ) -> Result<AssetId>;
+
+    #[sub]
+    async fn subscribe_asset_import_progress(
+        &self,
+        document_id: DocumentId,
+    ) -> Result<BoxStream<AssetImportProgress>>;
+
     async fn get_asset_metadata(&self, id: AssetId) -> Result<AssetMetadata>;
+
+    async fn get_asset_path_variables(&self) -> Result<Vec<(String, Utf8PathBuf)>>;
+
+    #[role(Admin)]
+    async fn set_asset_path_variable(
+        &self,
+        name: String,
+        path: Option<Utf8PathBuf>,
+    ) -> Result<()>;
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AssetImportProgress {
+    pub path: Utf8PathBuf,
+    pub frames_decoded: u64,
+    /// Fraction of the file decoded so far, from 0 to 1.
+    pub progress: f32,
 }
 
 #[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
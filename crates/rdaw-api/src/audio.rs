@@ -10,6 +10,26 @@ pub struct AudioMetadata {
     pub duration: RealTime,
 }
 
+/// Parameters of a running stream, as negotiated with the audio system.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StreamInfo {
+    pub sample_rate: u32,
+    /// Number of frames processed in one callback.
+    pub quantum: usize,
+    /// Latency reported by the device on top of the quantum, in frames.
+    pub device_latency: usize,
+}
+
+impl StreamInfo {
+    pub fn latency_frames(&self) -> usize {
+        self.quantum + self.device_latency
+    }
+
+    pub fn latency(&self) -> RealTime {
+        RealTime::from_secs_f64(self.latency_frames() as f64 / f64::from(self.sample_rate.max(1)))
+    }
+}
+
 #[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
 #[non_exhaustive]
 pub enum AudioChannel {
@@ -65,6 +85,16 @@ pub enum SampleFormat {
     Other,
 }
 
+/// Trade-off between quality and speed when a source is converted to the sample rate of the
+/// engine.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash)]
+pub enum ResampleQuality {
+    Fast,
+    #[default]
+    Balanced,
+    Best,
+}
+
 pub trait AudioInputStream<'media> {
     fn metadata(&self) -> &AudioMetadata;
 
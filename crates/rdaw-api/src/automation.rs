@@ -0,0 +1,153 @@
+use serde::{Deserialize, Serialize};
+
+use crate::node::{NodeId, NodeParam};
+use crate::time::Time;
+use crate::track::TrackId;
+use crate::{BackendProtocol, BoxStream, Result};
+
+slotmap::new_key_type! {
+    pub struct AutomationLaneId;
+
+    pub struct AutomationPointId;
+}
+
+#[rdaw_rpc::operations(protocol = BackendProtocol)]
+pub trait AutomationOperations {
+    /// Creates an empty lane driving a parameter of one of the track's nodes and appends it to
+    /// the track.
+    async fn create_automation_lane(
+        &self,
+        track_id: TrackId,
+        target: AutomationTarget,
+    ) -> Result<AutomationLaneId>;
+
+    async fn get_track_automation_lanes(&self, track_id: TrackId) -> Result<Vec<AutomationLaneId>>;
+
+    async fn remove_track_automation_lane(
+        &self,
+        track_id: TrackId,
+        id: AutomationLaneId,
+    ) -> Result<()>;
+
+    async fn get_automation_lane_target(&self, id: AutomationLaneId) -> Result<AutomationTarget>;
+
+    #[sub]
+    async fn subscribe_automation_lane(
+        &self,
+        id: AutomationLaneId,
+    ) -> Result<BoxStream<AutomationEvent>>;
+
+    /// Returns points sorted by their position.
+    async fn get_automation_points(
+        &self,
+        id: AutomationLaneId,
+    ) -> Result<Vec<(AutomationPointId, AutomationPoint)>>;
+
+    async fn add_automation_point(
+        &self,
+        id: AutomationLaneId,
+        point: AutomationPoint,
+    ) -> Result<AutomationPointId>;
+
+    /// Changes the position and the value of a point, keeping its curve.
+    async fn move_automation_point(
+        &self,
+        id: AutomationLaneId,
+        point_id: AutomationPointId,
+        position: Time,
+        value: f32,
+    ) -> Result<()>;
+
+    async fn set_automation_point_curve(
+        &self,
+        id: AutomationLaneId,
+        point_id: AutomationPointId,
+        curve: CurveShape,
+    ) -> Result<()>;
+
+    async fn remove_automation_point(
+        &self,
+        id: AutomationLaneId,
+        point_id: AutomationPointId,
+    ) -> Result<()>;
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum AutomationEvent {
+    Added {
+        id: AutomationPointId,
+        point: AutomationPoint,
+    },
+    Changed {
+        id: AutomationPointId,
+        point: AutomationPoint,
+    },
+    Removed {
+        id: AutomationPointId,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AutomationTarget {
+    pub node_id: NodeId,
+    pub param: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AutomationPoint {
+    pub position: Time,
+    /// Fraction of the parameter's range, from 0 to 1.
+    pub value: f32,
+    /// Shape of the segment from this point to the next one.
+    pub curve: CurveShape,
+}
+
+impl AutomationPoint {
+    pub fn new(position: Time, value: f32) -> AutomationPoint {
+        AutomationPoint {
+            position,
+            value,
+            curve: CurveShape::Linear,
+        }
+    }
+
+    pub fn is_valid(&self) -> bool {
+        (0.0..=1.0).contains(&self.value) && self.curve.is_valid()
+    }
+
+    /// Value of the parameter at this point.
+    pub fn param_value(&self, param: &NodeParam) -> f32 {
+        param.clamp(param.min + self.value * (param.max - param.min))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum CurveShape {
+    /// Stays at the value of the point until the next one.
+    Hold,
+    Linear,
+    /// Starts and ends slowly, like half a period of a cosine.
+    Smooth,
+    /// Bends the line, from -1 to 1. Positive tension changes the value slowly at first and
+    /// quickly at the end, negative tension does the opposite.
+    Tension(f32),
+}
+
+impl CurveShape {
+    pub fn is_valid(self) -> bool {
+        match self {
+            CurveShape::Tension(tension) => (-1.0..=1.0).contains(&tension),
+            _ => true,
+        }
+    }
+
+    /// Progress from 0 to 1 at the fraction `t` from 0 to 1 of the segment.
+    pub fn value(self, t: f32) -> f32 {
+        match self {
+            CurveShape::Hold => 0.0,
+            CurveShape::Linear => t,
+            CurveShape::Smooth => 0.5 - 0.5 * (t * std::f32::consts::PI).cos(),
+            CurveShape::Tension(tension) => t.powf(4f32.powf(tension)),
+        }
+    }
+}
@@ -0,0 +1,103 @@
+use serde::{Deserialize, Serialize};
+
+use crate::arrangement::ArrangementId;
+use crate::track::{TrackId, TrackMix};
+use crate::{BackendProtocol, BoxStream, Result};
+
+slotmap::new_key_type! {
+    pub struct BusId;
+
+    pub struct SendId;
+}
+
+/// Buses of an arrangement sum what tracks send to them and are mixed into its main track, e.g.
+/// for a shared reverb.
+#[rdaw_rpc::operations(protocol = BackendProtocol)]
+pub trait BusOperations {
+    /// Creates a bus and appends it to the arrangement.
+    async fn create_bus(&self, arrangement_id: ArrangementId, name: String) -> Result<BusId>;
+
+    async fn get_arrangement_buses(&self, arrangement_id: ArrangementId) -> Result<Vec<BusId>>;
+
+    /// Removes the bus along with every send to it.
+    async fn remove_arrangement_bus(&self, arrangement_id: ArrangementId, id: BusId) -> Result<()>;
+
+    async fn get_bus_name(&self, id: BusId) -> Result<String>;
+
+    async fn set_bus_name(&self, id: BusId, new_name: String) -> Result<()>;
+
+    #[sub]
+    async fn subscribe_bus_mix(&self, id: BusId) -> Result<BoxStream<TrackMix>>;
+
+    async fn get_bus_mix(&self, id: BusId) -> Result<TrackMix>;
+
+    /// Linear gain, 1.0 leaves the bus as is.
+    async fn set_bus_volume(&self, id: BusId, volume: f32) -> Result<()>;
+
+    /// From -1.0 (left) to 1.0 (right).
+    async fn set_bus_pan(&self, id: BusId, pan: f32) -> Result<()>;
+
+    #[sub]
+    async fn subscribe_track_sends(&self, track_id: TrackId) -> Result<BoxStream<SendEvent>>;
+
+    async fn get_track_sends(&self, track_id: TrackId) -> Result<Vec<(SendId, TrackSend)>>;
+
+    /// Sends the output of the track to a bus of the same document. Main tracks can't send to
+    /// the buses of their arrangement, since the buses are mixed into them.
+    async fn add_track_send(&self, track_id: TrackId, send: TrackSend) -> Result<SendId>;
+
+    async fn set_track_send_level(
+        &self,
+        track_id: TrackId,
+        send_id: SendId,
+        level: f32,
+    ) -> Result<()>;
+
+    async fn set_track_send_position(
+        &self,
+        track_id: TrackId,
+        send_id: SendId,
+        position: SendPosition,
+    ) -> Result<()>;
+
+    async fn remove_track_send(&self, track_id: TrackId, send_id: SendId) -> Result<()>;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TrackSend {
+    pub bus_id: BusId,
+    /// Linear gain applied to the sent signal.
+    pub level: f32,
+    pub position: SendPosition,
+}
+
+impl TrackSend {
+    pub fn new(bus_id: BusId) -> TrackSend {
+        TrackSend {
+            bus_id,
+            level: 1.0,
+            position: SendPosition::default(),
+        }
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.level.is_finite() && self.level >= 0.0
+    }
+}
+
+/// Where the signal of the track is taken from.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum SendPosition {
+    /// Before the volume and pan of the track.
+    PreFader,
+    /// After the volume and pan of the track.
+    #[default]
+    PostFader,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SendEvent {
+    Added { id: SendId, send: TrackSend },
+    Changed { id: SendId, send: TrackSend },
+    Removed { id: SendId },
+}
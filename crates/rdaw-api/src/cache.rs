@@ -0,0 +1,50 @@
+use rdaw_core::path::Utf8PathBuf;
+
+use crate::{BackendProtocol, Result};
+
+/// Files derived from assets which can be recomputed at any time, like waveform peaks and
+/// decoded audio. Nothing is cached on disk until a directory is set.
+#[rdaw_rpc::operations(protocol = BackendProtocol)]
+pub trait CacheOperations {
+    #[role(ReadOnly)]
+    async fn get_cache_dir(&self) -> Result<Option<Utf8PathBuf>>;
+
+    /// Entries in the previous directory are left as they are.
+    #[role(Admin)]
+    async fn set_cache_dir(&self, path: Option<Utf8PathBuf>) -> Result<()>;
+
+    #[role(ReadOnly)]
+    async fn get_cache_budget(&self) -> Result<u64>;
+
+    /// Least recently used entries are removed once the cache is larger than this many bytes.
+    #[role(Admin)]
+    async fn set_cache_budget(&self, budget: u64) -> Result<()>;
+
+    /// Returns the usage of every kind, empty without a cache directory.
+    #[role(ReadOnly)]
+    async fn get_cache_usage(&self) -> Result<Vec<CacheUsage>>;
+
+    /// Removes every entry of the kind, or of every kind if `None`.
+    #[role(Admin)]
+    async fn clear_cache(&self, kind: Option<CacheKind>) -> Result<()>;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CacheKind {
+    /// Waveform peaks of assets, shared between documents.
+    Waveform,
+    /// Audio sources decoded for playback.
+    Decoded,
+}
+
+impl CacheKind {
+    pub const ALL: [CacheKind; 2] = [CacheKind::Waveform, CacheKind::Decoded];
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CacheUsage {
+    pub kind: CacheKind,
+    pub num_entries: usize,
+    /// In bytes.
+    pub size: u64,
+}
@@ -0,0 +1,223 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::arrangement::ArrangementId;
+use crate::time::BeatTime;
+use crate::{BackendProtocol, BoxStream, Result};
+
+slotmap::new_key_type! {
+    pub struct ChordId;
+}
+
+#[rdaw_rpc::operations(protocol = BackendProtocol)]
+pub trait ChordOperations {
+    #[sub]
+    async fn subscribe_arrangement_chords(
+        &self,
+        id: ArrangementId,
+    ) -> Result<BoxStream<ChordEvent>>;
+
+    async fn add_arrangement_chord(&self, id: ArrangementId, chord: Chord) -> Result<ChordId>;
+
+    async fn get_arrangement_chord(&self, id: ArrangementId, chord_id: ChordId) -> Result<Chord>;
+
+    /// Returns chords sorted by their start.
+    async fn get_arrangement_chords(&self, id: ArrangementId) -> Result<Vec<(ChordId, Chord)>>;
+
+    async fn set_arrangement_chord(
+        &self,
+        id: ArrangementId,
+        chord_id: ChordId,
+        chord: Chord,
+    ) -> Result<()>;
+
+    async fn remove_arrangement_chord(&self, id: ArrangementId, chord_id: ChordId) -> Result<()>;
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChordEvent {
+    Added { id: ChordId, chord: Chord },
+    Changed { id: ChordId, chord: Chord },
+    Removed { id: ChordId },
+}
+
+/// A chord symbol spanning a range of beats, along with the scale the music is in at that point.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Chord {
+    pub start: BeatTime,
+    pub duration: BeatTime,
+    pub root: PitchClass,
+    pub quality: ChordQuality,
+    /// Slash chord bass note.
+    pub bass: Option<PitchClass>,
+    pub scale: Scale,
+}
+
+impl Chord {
+    pub fn end(&self) -> BeatTime {
+        self.start + self.duration
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.duration > BeatTime::ZERO
+            && self.root.is_valid()
+            && self.bass.is_none_or(PitchClass::is_valid)
+            && self.scale.root.is_valid()
+    }
+
+    pub fn contains(&self, position: BeatTime) -> bool {
+        self.start <= position && position < self.end()
+    }
+
+    /// Whether the MIDI key is one of the chord tones.
+    pub fn has_key(&self, key: u8) -> bool {
+        let class = PitchClass::from_key(key);
+        self.bass == Some(class)
+            || self
+                .quality
+                .intervals()
+                .iter()
+                .any(|&v| self.root.transpose(v) == class)
+    }
+}
+
+impl fmt::Display for Chord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", self.root, self.quality.suffix())?;
+
+        if let Some(bass) = self.bass {
+            write!(f, "/{bass}")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Pitch class in semitones above C, from 0 to 11.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct PitchClass(pub u8);
+
+impl PitchClass {
+    pub const C: PitchClass = PitchClass(0);
+
+    const NAMES: [&'static str; 12] = [
+        "C", "C#", "D", "D#", "E", "F", "F#", "G", "G#", "A", "A#", "B",
+    ];
+
+    pub fn from_key(key: u8) -> PitchClass {
+        PitchClass(key % 12)
+    }
+
+    pub fn is_valid(self) -> bool {
+        self.0 < 12
+    }
+
+    pub fn transpose(self, semitones: u8) -> PitchClass {
+        PitchClass((self.0 % 12 + semitones % 12) % 12)
+    }
+}
+
+impl fmt::Display for PitchClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match PitchClass::NAMES.get(usize::from(self.0)) {
+            Some(name) => f.write_str(name),
+            None => write!(f, "?{}", self.0),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ChordQuality {
+    Major,
+    Minor,
+    Diminished,
+    Augmented,
+    Sus2,
+    Sus4,
+    Dominant7,
+    Major7,
+    Minor7,
+}
+
+impl ChordQuality {
+    /// Semitones above the root, including the root itself.
+    pub fn intervals(self) -> &'static [u8] {
+        match self {
+            ChordQuality::Major => &[0, 4, 7],
+            ChordQuality::Minor => &[0, 3, 7],
+            ChordQuality::Diminished => &[0, 3, 6],
+            ChordQuality::Augmented => &[0, 4, 8],
+            ChordQuality::Sus2 => &[0, 2, 7],
+            ChordQuality::Sus4 => &[0, 5, 7],
+            ChordQuality::Dominant7 => &[0, 4, 7, 10],
+            ChordQuality::Major7 => &[0, 4, 7, 11],
+            ChordQuality::Minor7 => &[0, 3, 7, 10],
+        }
+    }
+
+    pub fn suffix(self) -> &'static str {
+        match self {
+            ChordQuality::Major => "",
+            ChordQuality::Minor => "m",
+            ChordQuality::Diminished => "dim",
+            ChordQuality::Augmented => "aug",
+            ChordQuality::Sus2 => "sus2",
+            ChordQuality::Sus4 => "sus4",
+            ChordQuality::Dominant7 => "7",
+            ChordQuality::Major7 => "maj7",
+            ChordQuality::Minor7 => "m7",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Scale {
+    pub root: PitchClass,
+    pub mode: ScaleMode,
+}
+
+impl Scale {
+    pub fn new(root: PitchClass, mode: ScaleMode) -> Scale {
+        Scale { root, mode }
+    }
+
+    /// Whether the MIDI key belongs to the scale.
+    pub fn has_key(&self, key: u8) -> bool {
+        self.mode
+            .intervals()
+            .iter()
+            .any(|&v| self.root.transpose(v) == PitchClass::from_key(key))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ScaleMode {
+    Major,
+    Minor,
+    Dorian,
+    Phrygian,
+    Lydian,
+    Mixolydian,
+    Locrian,
+    HarmonicMinor,
+    MelodicMinor,
+    Chromatic,
+}
+
+impl ScaleMode {
+    pub fn intervals(self) -> &'static [u8] {
+        match self {
+            ScaleMode::Major => &[0, 2, 4, 5, 7, 9, 11],
+            ScaleMode::Minor => &[0, 2, 3, 5, 7, 8, 10],
+            ScaleMode::Dorian => &[0, 2, 3, 5, 7, 9, 10],
+            ScaleMode::Phrygian => &[0, 1, 3, 5, 7, 8, 10],
+            ScaleMode::Lydian => &[0, 2, 4, 6, 7, 9, 11],
+            ScaleMode::Mixolydian => &[0, 2, 4, 5, 7, 9, 10],
+            ScaleMode::Locrian => &[0, 1, 3, 5, 6, 8, 10],
+            ScaleMode::HarmonicMinor => &[0, 2, 3, 5, 7, 8, 11],
+            ScaleMode::MelodicMinor => &[0, 2, 3, 5, 7, 9, 11],
+            ScaleMode::Chromatic => &[0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11],
+        }
+    }
+}
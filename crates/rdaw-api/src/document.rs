@@ -1,7 +1,10 @@
+use std::time::{Duration, SystemTime};
+
 use rdaw_core::path::Utf8PathBuf;
+use rdaw_core::Uuid;
 
 use crate::arrangement::ArrangementId;
-use crate::{BackendProtocol, Result};
+use crate::{BackendProtocol, BoxStream, Page, PageRequest, Result};
 
 slotmap::new_key_type! {
     pub struct DocumentId;
@@ -11,11 +14,203 @@ slotmap::new_key_type! {
 pub trait DocumentOperations {
     async fn create_document(&self) -> Result<DocumentId>;
 
+    #[role(Admin)]
     async fn open_document(&self, path: Utf8PathBuf) -> Result<DocumentId>;
 
+    #[role(Admin)]
+    async fn open_encrypted_document(
+        &self,
+        path: Utf8PathBuf,
+        passphrase: String,
+    ) -> Result<DocumentId>;
+
+    /// Opens a document whose last save was interrupted, e.g. by a crash, discarding whatever
+    /// was written after the last complete revision.
+    #[role(Admin)]
+    async fn recover_document(&self, path: Utf8PathBuf) -> Result<DocumentId>;
+
     async fn save_document(&self, id: DocumentId) -> Result<()>;
 
+    #[role(Admin)]
     async fn save_document_as(&self, id: DocumentId, path: Utf8PathBuf) -> Result<()>;
 
+    #[role(Admin)]
+    async fn export_document_json(&self, id: DocumentId, path: Utf8PathBuf) -> Result<()>;
+
+    #[role(Admin)]
+    async fn import_document_json(&self, path: Utf8PathBuf) -> Result<DocumentId>;
+
     async fn get_document_arrangement(&self, id: DocumentId) -> Result<ArrangementId>;
+
+    /// Returns problems found in the document when it was opened, such as references to objects
+    /// missing from the file. Repaired problems stay repaired once the document is saved.
+    #[role(ReadOnly)]
+    async fn get_document_warnings(&self, id: DocumentId) -> Result<Vec<DocumentWarning>>;
+
+    /// Returns the seed of everything generated at random in the document, such as humanization
+    /// and random fades. The same seed gives the same render on every machine.
+    #[role(ReadOnly)]
+    async fn get_document_seed(&self, id: DocumentId) -> Result<u64>;
+
+    /// Changes the seed, so that everything generated afterwards comes out differently.
+    async fn set_document_seed(&self, id: DocumentId, seed: u64) -> Result<()>;
+
+    /// Reports objects of the document being added, removed and modified. Every object is
+    /// reported at most once per request.
+    #[sub]
+    async fn subscribe_document_changes(
+        &self,
+        id: DocumentId,
+    ) -> Result<BoxStream<DocumentChangeEvent>>;
+
+    #[role(ReadOnly)]
+    async fn is_document_encrypted(&self, id: DocumentId) -> Result<bool>;
+
+    #[role(Admin)]
+    async fn set_document_passphrase(
+        &self,
+        id: DocumentId,
+        passphrase: Option<String>,
+    ) -> Result<()>;
+
+    /// Sets the author recorded for objects changed in later saves of any document.
+    #[role(Admin)]
+    async fn set_document_author(&self, author: Option<String>) -> Result<()>;
+
+    /// Sets which backup copies are kept when saving any document over its previous state.
+    #[role(Admin)]
+    async fn set_document_backups(&self, settings: BackupSettings) -> Result<()>;
+
+    /// Returns when and by whom the object was last changed, as of the last save.
+    #[role(ReadOnly)]
+    async fn get_object_modification(
+        &self,
+        id: DocumentId,
+        uuid: Uuid,
+    ) -> Result<ObjectModification>;
+
+    /// Returns up to `limit` objects of the last save, most recently modified first.
+    #[role(ReadOnly)]
+    async fn list_recently_modified_objects(
+        &self,
+        id: DocumentId,
+        limit: usize,
+    ) -> Result<Vec<ObjectModification>>;
+
+    /// Saves the document and gives the new revision a name.
+    async fn create_snapshot(&self, id: DocumentId, name: String) -> Result<SnapshotId>;
+
+    /// Returns snapshots from oldest to newest.
+    #[role(ReadOnly)]
+    async fn list_snapshots(&self, id: DocumentId, page: PageRequest) -> Result<Page<Snapshot>>;
+
+    /// Brings the document back to the state it had in the snapshot, discarding unsaved changes.
+    /// The restored state is saved as a new revision, so later snapshots stay intact.
+    ///
+    /// Objects which are part of the snapshot keep their ids, but their state has to be fetched
+    /// again.
+    async fn restore_snapshot(&self, id: DocumentId, snapshot_id: SnapshotId) -> Result<()>;
+
+    async fn remove_snapshot(&self, id: DocumentId, snapshot_id: SnapshotId) -> Result<()>;
+
+    /// Returns saved revisions from oldest to newest.
+    #[role(ReadOnly)]
+    async fn list_revisions(&self, id: DocumentId, page: PageRequest) -> Result<Page<Revision>>;
+
+    /// Opens a new unsaved document with the state the document had in the revision. The
+    /// document itself isn't changed.
+    async fn open_revision(&self, id: DocumentId, revision_id: RevisionId) -> Result<DocumentId>;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct SnapshotId(pub u64);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct RevisionId(pub u64);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Revision {
+    pub id: RevisionId,
+    pub created_at: SystemTime,
+    /// How long the document had been worked on when it was saved.
+    pub time_spent: Duration,
+}
+
+/// Every save first copies the document to `<file name>.bak1`, shifting older copies to `.bak2`
+/// and so on.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BackupSettings {
+    /// How many copies are kept, none if zero.
+    pub count: usize,
+    /// Where the copies are stored, next to the document if not set.
+    pub dir: Option<Utf8PathBuf>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Snapshot {
+    pub id: SnapshotId,
+    pub name: String,
+    pub created_at: SystemTime,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ObjectModification {
+    pub uuid: Uuid,
+    pub modified_at: SystemTime,
+    pub author: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ObjectType {
+    Arrangement,
+    Asset,
+    AudioItem,
+    AudioSource,
+    AutomationLane,
+    Bus,
+    MidiItem,
+    MidiSource,
+    Node,
+    TempoMap,
+    Track,
+}
+
+/// Problem with an object of a document, found when the document was opened.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DocumentWarning {
+    pub kind: DocumentWarningKind,
+    pub object_type: ObjectType,
+    pub uuid: Uuid,
+    pub message: String,
+    /// Whether the object was fixed, e.g. by removing the reference or resetting the value.
+    pub repaired: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DocumentWarningKind {
+    /// The object refers to an object which is missing from the document or can't be used.
+    DanglingReference,
+    /// A value of the object is invalid, e.g. a negative duration.
+    OutOfRange,
+    /// The object's UUID is used by another object too, or it's listed twice where it has to be
+    /// unique.
+    DuplicateUuid,
+}
+
+/// Objects are identified by their UUID, which stays the same across sessions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DocumentChangeEvent {
+    ObjectAdded {
+        ty: ObjectType,
+        uuid: Uuid,
+    },
+    ObjectRemoved {
+        ty: ObjectType,
+        uuid: Uuid,
+    },
+    /// Sent whenever an object might have changed, even if its state ended up the same.
+    ObjectModified {
+        ty: ObjectType,
+        uuid: Uuid,
+    },
 }
@@ -0,0 +1,38 @@
+use rdaw_core::time::RealTime;
+
+use crate::arrangement::ArrangementId;
+use crate::bus::BusId;
+use crate::track::{TrackId, TrackItemId};
+use crate::{BackendProtocol, BoxStream, Result};
+
+#[rdaw_rpc::operations(protocol = BackendProtocol)]
+pub trait EngineOperations {
+    #[sub]
+    async fn subscribe_engine_status(&self, id: ArrangementId) -> Result<BoxStream<EngineStatus>>;
+
+    async fn get_engine_status(&self, id: ArrangementId) -> Result<EngineStatus>;
+
+    /// Plays a test impulse with the engine bypassed and waits for it to come back through the
+    /// default input, which should be looped back to the output. Returns the round-trip latency,
+    /// which can be applied with `set_recording_latency_compensation`.
+    async fn measure_loopback_latency(&self) -> Result<RealTime>;
+}
+
+/// State of the audio engine rendering an arrangement.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EngineStatus {
+    /// Whether the arrangement is rendered to the output stream.
+    pub is_running: bool,
+    /// Nodes that have panicked and output silence until they are rebuilt, e.g. after the item
+    /// is edited.
+    pub disabled_nodes: Vec<EngineNode>,
+}
+
+/// What a node of the engine's graph belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EngineNode {
+    Track(TrackId),
+    Item(TrackId, TrackItemId),
+    /// The bus itself, or one of the sends to it.
+    Bus(BusId),
+}
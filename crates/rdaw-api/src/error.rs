@@ -10,23 +10,51 @@ pub type Result<T, E = Error> = std::result::Result<T, E>;
 pub enum ErrorKind {
     Other,
 
+    Busy,
+    Cancelled,
+    Conflict,
+    Corrupted,
     Deserialization,
     Disconnected,
     IndexOutOfBounds,
     InvalidId,
+    InvalidPassphrase,
     InvalidType,
     InvalidUtf8,
     InvalidUuid,
     Io,
+    Locked,
     NotFound,
     NotSupported,
     OutOfMemory,
     PermissionDenied,
+    ReadOnly,
     Serialization,
     Sql,
+    Timeout,
     UnknownVersion,
 }
 
+impl ErrorKind {
+    pub fn is_retryable(self) -> bool {
+        matches!(self, ErrorKind::Busy | ErrorKind::Disconnected | ErrorKind::Timeout)
+    }
+
+    pub fn is_user_error(self) -> bool {
+        matches!(
+            self,
+            ErrorKind::Corrupted
+                | ErrorKind::InvalidPassphrase
+                | ErrorKind::Locked
+                | ErrorKind::NotFound
+                | ErrorKind::NotSupported
+                | ErrorKind::PermissionDenied
+                | ErrorKind::ReadOnly
+                | ErrorKind::UnknownVersion
+        )
+    }
+}
+
 impl From<io::ErrorKind> for ErrorKind {
     fn from(value: io::ErrorKind) -> Self {
         match value {
@@ -34,6 +62,9 @@ impl From<io::ErrorKind> for ErrorKind {
             io::ErrorKind::Other => ErrorKind::Other,
             io::ErrorKind::OutOfMemory => ErrorKind::OutOfMemory,
             io::ErrorKind::PermissionDenied => ErrorKind::PermissionDenied,
+            io::ErrorKind::InvalidData => ErrorKind::Corrupted,
+            io::ErrorKind::TimedOut => ErrorKind::Timeout,
+            io::ErrorKind::WouldBlock => ErrorKind::Busy,
             _ => ErrorKind::Io,
         }
     }
@@ -48,6 +79,8 @@ pub struct Error {
 #[derive(Clone, Serialize, Deserialize)]
 struct Repr {
     cause: ErrorEntry,
+    #[serde(default)]
+    operation: Option<Operation>,
     backtrace: Vec<Location>,
 }
 
@@ -61,6 +94,7 @@ impl Error {
                     message: message.to_string(),
                     cause: None,
                 },
+                operation: None,
                 backtrace: capture_backtrace(),
             }),
         }
@@ -79,6 +113,7 @@ impl Error {
                     message: message.to_string(),
                     cause: Some(Box::new(self.repr.cause)),
                 },
+                operation: self.repr.operation,
                 backtrace: self.repr.backtrace,
             }),
         }
@@ -89,6 +124,18 @@ impl Error {
         self.wrap(kind, message)
     }
 
+    pub fn with_operation(mut self, operation: Operation) -> Error {
+        if self.repr.operation.is_none() {
+            self.repr.operation = Some(operation);
+        }
+
+        self
+    }
+
+    pub fn operation(&self) -> Option<&Operation> {
+        self.repr.operation.as_ref()
+    }
+
     pub fn cause(&self) -> &ErrorEntry {
         &self.repr.cause
     }
@@ -97,6 +144,14 @@ impl Error {
         self.repr.cause.kind
     }
 
+    pub fn is_retryable(&self) -> bool {
+        self.kind().is_retryable()
+    }
+
+    pub fn is_user_error(&self) -> bool {
+        self.kind().is_user_error()
+    }
+
     pub fn message(&self) -> &str {
         &self.repr.cause.message
     }
@@ -132,6 +187,11 @@ impl fmt::Debug for Error {
         }
 
         writeln!(f)?;
+
+        if let Some(operation) = self.operation() {
+            writeln!(f, "Operation: {operation}")?;
+        }
+
         writeln!(f, "Backtrace:")?;
 
         let indent = (self.backtrace().count().ilog10() as usize) + 3;
@@ -194,6 +254,22 @@ impl ProtocolError for Error {
     fn is_invalid_type(&self) -> bool {
         self.kind() == ErrorKind::InvalidType
     }
+
+    fn with_operation(
+        self,
+        name: &'static str,
+        objects: &[(&'static str, &dyn fmt::Debug)],
+    ) -> Self {
+        let operation = Operation {
+            name: name.into(),
+            objects: objects
+                .iter()
+                .map(|(name, id)| (name.to_string(), format!("{id:?}")))
+                .collect(),
+        };
+
+        Error::with_operation(self, operation)
+    }
 }
 
 #[derive(Clone, Serialize, Deserialize)]
@@ -203,6 +279,25 @@ pub struct ErrorEntry {
     pub cause: Option<Box<ErrorEntry>>,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Operation {
+    pub name: String,
+    pub objects: Vec<(String, String)>,
+}
+
+impl fmt::Display for Operation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name)?;
+
+        for (i, (name, id)) in self.objects.iter().enumerate() {
+            let sep = if i == 0 { " with " } else { ", " };
+            write!(f, "{sep}{name} = {id}")?;
+        }
+
+        Ok(())
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Location {
     pub path: Option<String>,
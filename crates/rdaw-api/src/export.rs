@@ -0,0 +1,112 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{BackendProtocol, Result};
+
+/// Built-in presets can't be changed.
+#[rdaw_rpc::operations(protocol = BackendProtocol)]
+pub trait ExportOperations {
+    /// Built-in presets first, then user presets sorted by name.
+    #[role(ReadOnly)]
+    async fn list_export_presets(&self) -> Result<Vec<ExportPreset>>;
+
+    #[role(ReadOnly)]
+    async fn get_export_preset(&self, name: String) -> Result<ExportPreset>;
+
+    #[role(Admin)]
+    async fn save_export_preset(&self, preset: ExportPreset) -> Result<()>;
+
+    #[role(Admin)]
+    async fn remove_export_preset(&self, name: String) -> Result<()>;
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExportPreset {
+    pub name: String,
+    #[serde(flatten)]
+    pub settings: ExportSettings,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExportSettings {
+    pub format: ExportFormat,
+    pub sample_rate: u32,
+    /// Ignored by Ogg Vorbis.
+    pub sample_format: ExportSampleFormat,
+    pub loudness: Option<LoudnessTarget>,
+    /// Whether to add TPDF dither when reducing to an integer sample format.
+    pub dither: bool,
+    /// Moves dither and quantization noise toward high frequencies, where it's less audible, at
+    /// the cost of more noise overall. Only used with dither.
+    #[serde(default)]
+    pub noise_shaping: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportFormat {
+    Wav,
+    Flac,
+    Ogg,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportSampleFormat {
+    I16,
+    I24,
+    F32,
+}
+
+impl ExportSampleFormat {
+    /// `None` for floating point.
+    pub fn integer_bits(self) -> Option<u32> {
+        match self {
+            ExportSampleFormat::I16 => Some(16),
+            ExportSampleFormat::I24 => Some(24),
+            ExportSampleFormat::F32 => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct LoudnessTarget {
+    /// LUFS.
+    pub integrated: f32,
+    /// dBTP.
+    pub true_peak: f32,
+}
+
+impl ExportPreset {
+    pub fn built_in() -> Vec<ExportPreset> {
+        use ExportSampleFormat::{I16, I24};
+
+        vec![
+            built_in_preset("Master", 48000, I24, None),
+            built_in_preset("Podcast", 44100, I16, Some((-16.0, -1.0))),
+            built_in_preset("Broadcast (EBU R128)", 48000, I24, Some((-23.0, -1.0))),
+            built_in_preset("Streaming", 44100, I16, Some((-14.0, -1.0))),
+        ]
+    }
+}
+
+fn built_in_preset(
+    name: &str,
+    sample_rate: u32,
+    sample_format: ExportSampleFormat,
+    loudness: Option<(f32, f32)>,
+) -> ExportPreset {
+    ExportPreset {
+        name: name.into(),
+        settings: ExportSettings {
+            format: ExportFormat::Wav,
+            sample_rate,
+            sample_format,
+            loudness: loudness.map(|(integrated, true_peak)| LoudnessTarget {
+                integrated,
+                true_peak,
+            }),
+            dither: sample_format != ExportSampleFormat::F32,
+            noise_shaping: sample_format == ExportSampleFormat::I16,
+        },
+    }
+}
@@ -0,0 +1,54 @@
+use rdaw_core::time::RealTime;
+
+use crate::time::{BeatTime, Time};
+use crate::track::TrackViewId;
+use crate::{BackendProtocol, Result};
+
+#[rdaw_rpc::operations(protocol = BackendProtocol)]
+pub trait GridOperations {
+    /// Moves `time` to the closest multiple of the snap mode's step, at the tempo of the view's
+    /// arrangement. The result has the same time base as `time`.
+    #[role(ReadOnly)]
+    async fn snap_time(&self, view_id: TrackViewId, time: Time, mode: SnapMode) -> Result<Time>;
+
+    /// Returns the musical grid lines from `start` up to `end`, in order. The finest grid with
+    /// at most `max_lines` lines in the range is used: quarter beats, beats, bars, or every 2nd,
+    /// 4th, 8th bar and so on.
+    #[role(ReadOnly)]
+    async fn get_grid_lines(
+        &self,
+        view_id: TrackViewId,
+        start: Time,
+        end: Time,
+        max_lines: usize,
+    ) -> Result<Vec<GridLine>>;
+}
+
+/// Time signatures aren't supported yet, so every bar has four beats.
+pub const BEATS_PER_BAR: i32 = 4;
+
+/// What times snap to, see [`GridOperations::snap_time`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SnapMode {
+    Bar,
+    Beat,
+    QuarterBeat,
+    Second,
+    /// Frames at the sample rate of the engine.
+    Sample,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GridLine {
+    pub beat: BeatTime,
+    pub real: RealTime,
+    pub kind: GridLineKind,
+}
+
+/// The coarsest division a grid line is on, so that bars can be drawn bolder than beats.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum GridLineKind {
+    Subdivision,
+    Beat,
+    Bar,
+}
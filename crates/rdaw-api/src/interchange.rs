@@ -0,0 +1,10 @@
+use rdaw_core::path::Utf8PathBuf;
+
+use crate::document::DocumentId;
+use crate::{BackendProtocol, Result};
+
+#[rdaw_rpc::operations(protocol = BackendProtocol)]
+pub trait InterchangeOperations {
+    #[role(Admin)]
+    async fn import_session(&self, path: Utf8PathBuf) -> Result<DocumentId>;
+}
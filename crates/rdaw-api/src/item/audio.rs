@@ -1,3 +1,255 @@
+use std::f32::consts::FRAC_PI_2;
+
+use rdaw_core::time::RealTime;
+
+use crate::{BackendProtocol, BoxStream, Result};
+
 slotmap::new_key_type! {
     pub struct AudioItemId;
 }
+
+#[rdaw_rpc::operations(protocol = BackendProtocol)]
+pub trait AudioItemOperations {
+    #[sub]
+    async fn subscribe_audio_item_gain_envelope(
+        &self,
+        id: AudioItemId,
+    ) -> Result<BoxStream<GainEnvelope>>;
+
+    async fn get_audio_item_gain_envelope(&self, id: AudioItemId) -> Result<GainEnvelope>;
+
+    async fn set_audio_item_gain_envelope(
+        &self,
+        id: AudioItemId,
+        envelope: GainEnvelope,
+    ) -> Result<()>;
+
+    async fn add_audio_item_gain_point(&self, id: AudioItemId, point: GainPoint) -> Result<usize>;
+
+    async fn remove_audio_item_gain_point(&self, id: AudioItemId, index: usize) -> Result<()>;
+
+    async fn get_audio_item_clip(&self, id: AudioItemId) -> Result<AudioClip>;
+
+    async fn set_audio_item_source_offset(&self, id: AudioItemId, offset: RealTime) -> Result<()>;
+
+    async fn set_audio_item_gain(&self, id: AudioItemId, gain: f32) -> Result<()>;
+
+    async fn set_audio_item_fade_in(&self, id: AudioItemId, fade: Fade) -> Result<()>;
+
+    async fn set_audio_item_fade_out(&self, id: AudioItemId, fade: Fade) -> Result<()>;
+
+    /// Finds onsets of hits and notes in the source of the item, returned as positions in the
+    /// source. `sensitivity` goes from 0.0, where only the sharpest hits are found, to 1.0.
+    #[role(ReadOnly)]
+    async fn detect_audio_item_transients(
+        &self,
+        id: AudioItemId,
+        sensitivity: f32,
+    ) -> Result<Vec<RealTime>>;
+}
+
+/// How an audio item plays its source.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AudioClip {
+    /// Position in the source where the item starts.
+    pub source_offset: RealTime,
+    /// Linear gain, on top of the gain envelope.
+    pub gain: f32,
+    pub fade_in: Fade,
+    pub fade_out: Fade,
+}
+
+impl Default for AudioClip {
+    fn default() -> AudioClip {
+        AudioClip {
+            source_offset: RealTime::ZERO,
+            gain: 1.0,
+            fade_in: Fade::default(),
+            fade_out: Fade::default(),
+        }
+    }
+}
+
+impl AudioClip {
+    /// Gain and fades at `position`, relative to the start of an item of the given duration.
+    /// Fades longer than the item are cut short.
+    pub fn gain_at(&self, position: RealTime, duration: RealTime) -> f32 {
+        let fade_in = self.fade_in.gain_at(position);
+        let fade_out = self.fade_out.gain_at(duration - position);
+        self.gain * fade_in * fade_out
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Fade {
+    /// No fade if zero.
+    pub duration: RealTime,
+    pub curve: FadeCurve,
+}
+
+impl Fade {
+    pub fn is_valid(&self) -> bool {
+        self.duration >= RealTime::ZERO
+    }
+
+    /// Gain of a fade in, `elapsed` since it has started. Fade outs are the same curve played
+    /// backwards.
+    pub fn gain_at(&self, elapsed: RealTime) -> f32 {
+        if elapsed >= self.duration {
+            return 1.0;
+        }
+
+        if elapsed <= RealTime::ZERO {
+            return 0.0;
+        }
+
+        let t = elapsed.as_secs_f64() / self.duration.as_secs_f64();
+        self.curve.gain(t as f32)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum FadeCurve {
+    #[default]
+    Linear,
+    /// Keeps the power constant when crossfading with the opposite fade.
+    EqualPower,
+    /// Slow at both ends.
+    SCurve,
+    /// Slow at the start, close to even steps in decibels.
+    Exponential,
+}
+
+impl FadeCurve {
+    const EXPONENTIAL_STEEPNESS: f32 = 6.0;
+
+    /// `t` goes from 0 to 1.
+    pub fn gain(self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+
+        match self {
+            FadeCurve::Linear => t,
+            FadeCurve::EqualPower => (t * FRAC_PI_2).sin(),
+            FadeCurve::SCurve => t * t * (3.0 - 2.0 * t),
+            FadeCurve::Exponential => {
+                let k = FadeCurve::EXPONENTIAL_STEEPNESS;
+                (k * t).exp_m1() / k.exp_m1()
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GainPoint {
+    /// Relative to the start of the item.
+    pub position: RealTime,
+    /// Linear gain.
+    pub gain: f32,
+}
+
+impl GainPoint {
+    pub fn new(position: RealTime, gain: f32) -> GainPoint {
+        GainPoint { position, gain }
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.position >= RealTime::ZERO && self.gain.is_finite() && self.gain >= 0.0
+    }
+}
+
+/// Piecewise linear gain curve. Before the first point and after the last one the gain is held,
+/// an empty envelope has unity gain.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GainEnvelope {
+    pub points: Vec<GainPoint>,
+}
+
+impl GainEnvelope {
+    pub fn new() -> GainEnvelope {
+        GainEnvelope::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    pub fn is_sorted(&self) -> bool {
+        self.points
+            .windows(2)
+            .all(|w| w[0].position <= w[1].position)
+    }
+
+    /// Inserts the point after all points with the same position, returns its index.
+    pub fn insert(&mut self, point: GainPoint) -> usize {
+        let idx = self
+            .points
+            .partition_point(|v| v.position <= point.position);
+        self.points.insert(idx, point);
+        idx
+    }
+
+    pub fn gain_at(&self, position: RealTime) -> f32 {
+        let idx = self.points.partition_point(|v| v.position <= position);
+        self.segment_gain(idx, position.as_secs_f64())
+    }
+
+    /// Part of the envelope between `start` and `end`, moved to start at zero. The gains at both
+    /// ends are kept as points, so that the part sounds the same on its own.
+    pub fn slice(&self, start: RealTime, end: RealTime) -> GainEnvelope {
+        if self.points.is_empty() {
+            return GainEnvelope::new();
+        }
+
+        let mut points = vec![GainPoint::new(RealTime::ZERO, self.gain_at(start))];
+        points.extend(
+            self.points
+                .iter()
+                .filter(|v| v.position > start && v.position < end)
+                .map(|v| GainPoint::new(v.position - start, v.gain)),
+        );
+        points.push(GainPoint::new(end - start, self.gain_at(end)));
+
+        GainEnvelope { points }
+    }
+
+    /// Multiplies `samples`, the first of which is at `start`, by the envelope.
+    pub fn apply(&self, start: RealTime, sample_rate: u32, samples: &mut [f32]) {
+        if self.points.is_empty() {
+            return;
+        }
+
+        let start_secs = start.as_secs_f64();
+        let sample_duration = 1.0 / f64::from(sample_rate);
+
+        let mut idx = self.points.partition_point(|v| v.position <= start);
+
+        for (i, sample) in samples.iter_mut().enumerate() {
+            let secs = start_secs + i as f64 * sample_duration;
+
+            while idx < self.points.len() && self.points[idx].position.as_secs_f64() <= secs {
+                idx += 1;
+            }
+
+            *sample *= self.segment_gain(idx, secs);
+        }
+    }
+
+    // `idx` is the index of the first point after `secs`
+    fn segment_gain(&self, idx: usize, secs: f64) -> f32 {
+        let (prev, next) = match (idx.checked_sub(1), self.points.get(idx)) {
+            (None, None) => return 1.0,
+            (None, Some(next)) => return next.gain,
+            (Some(prev), None) => return self.points[prev].gain,
+            (Some(prev), Some(next)) => (&self.points[prev], next),
+        };
+
+        let prev_secs = prev.position.as_secs_f64();
+        let len = next.position.as_secs_f64() - prev_secs;
+        if len <= 0.0 {
+            return next.gain;
+        }
+
+        let t = ((secs - prev_secs) / len) as f32;
+        prev.gain + (next.gain - prev.gain) * t
+    }
+}
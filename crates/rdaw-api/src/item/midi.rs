@@ -0,0 +1,23 @@
+use crate::source::MidiSourceId;
+use crate::time::Time;
+use crate::track::{TrackId, TrackItemId};
+use crate::{BackendProtocol, Result};
+
+slotmap::new_key_type! {
+    pub struct MidiItemId;
+}
+
+#[rdaw_rpc::operations(protocol = BackendProtocol)]
+pub trait MidiItemOperations {
+    /// Creates an empty source along with an item playing it, and adds the item to the track.
+    async fn add_midi_clip(
+        &self,
+        track_id: TrackId,
+        start: Time,
+        duration: Time,
+    ) -> Result<TrackItemId>;
+
+    async fn create_midi_item(&self, source_id: MidiSourceId) -> Result<MidiItemId>;
+
+    async fn get_midi_item_source(&self, id: MidiItemId) -> Result<MidiSourceId>;
+}
@@ -1,17 +1,21 @@
 mod audio;
+mod midi;
 
 use serde::{Deserialize, Serialize};
 
-pub use self::audio::AudioItemId;
+pub use self::audio::*;
+pub use self::midi::*;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum ItemKind {
     Audio,
+    Midi,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub enum ItemId {
     Audio(AudioItemId),
+    Midi(MidiItemId),
 }
 
 impl From<AudioItemId> for ItemId {
@@ -19,3 +23,9 @@ impl From<AudioItemId> for ItemId {
         ItemId::Audio(id)
     }
 }
+
+impl From<MidiItemId> for ItemId {
+    fn from(id: MidiItemId) -> ItemId {
+        ItemId::Midi(id)
+    }
+}
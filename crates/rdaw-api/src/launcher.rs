@@ -0,0 +1,109 @@
+use std::collections::BTreeMap;
+
+use crate::arrangement::ArrangementId;
+use crate::item::ItemId;
+use crate::time::BeatTime;
+use crate::track::TrackId;
+use crate::{BackendProtocol, BoxStream, Result};
+
+slotmap::new_key_type! {
+    pub struct SceneId;
+}
+
+/// Clip launcher of an arrangement: a grid of scenes and tracks, where every cell can hold a clip
+/// which is looped once launched, independently of the arrangement's timeline.
+#[rdaw_rpc::operations(protocol = BackendProtocol)]
+pub trait LauncherOperations {
+    #[sub]
+    async fn subscribe_launcher(&self, id: ArrangementId) -> Result<BoxStream<LauncherEvent>>;
+
+    /// Adds a scene after the existing ones.
+    async fn add_scene(&self, id: ArrangementId, scene: Scene) -> Result<SceneId>;
+
+    async fn get_scene(&self, id: ArrangementId, scene_id: SceneId) -> Result<Scene>;
+
+    /// Returns scenes in order.
+    async fn get_scenes(&self, id: ArrangementId) -> Result<Vec<(SceneId, Scene)>>;
+
+    async fn set_scene(&self, id: ArrangementId, scene_id: SceneId, scene: Scene) -> Result<()>;
+
+    /// Removes the scene and stops its clips.
+    async fn remove_scene(&self, id: ArrangementId, scene_id: SceneId) -> Result<()>;
+
+    /// Launches and stops happen at the next multiple of this many beats, or immediately if it's
+    /// zero. One bar of 4/4 by default.
+    async fn get_launch_quantization(&self, id: ArrangementId) -> Result<BeatTime>;
+
+    async fn set_launch_quantization(
+        &self,
+        id: ArrangementId,
+        quantization: BeatTime,
+    ) -> Result<()>;
+
+    /// Replaces the clip playing on the track with the scene's clip. Returns the quantized
+    /// position the clip starts at.
+    async fn launch_clip(
+        &self,
+        id: ArrangementId,
+        scene_id: SceneId,
+        track_id: TrackId,
+    ) -> Result<BeatTime>;
+
+    /// Launches every clip of the scene.
+    async fn launch_scene(&self, id: ArrangementId, scene_id: SceneId) -> Result<BeatTime>;
+
+    /// Returns the quantized position the clip playing on the track stops at.
+    async fn stop_clip(&self, id: ArrangementId, track_id: TrackId) -> Result<BeatTime>;
+
+    async fn get_launched_clips(&self, id: ArrangementId) -> Result<Vec<LaunchedClip>>;
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LauncherEvent {
+    SceneAdded { id: SceneId, scene: Scene },
+    SceneChanged { id: SceneId, scene: Scene },
+    SceneRemoved { id: SceneId },
+    ClipLaunched(LaunchedClip),
+    ClipStopped { track_id: TrackId, at: BeatTime },
+}
+
+/// Row of the clip launcher.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Scene {
+    pub name: String,
+    pub clips: BTreeMap<TrackId, Clip>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Clip {
+    pub item: ItemId,
+    /// The clip loops after this many beats, so that it stays in sync with the tempo map.
+    pub length: BeatTime,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LaunchedClip {
+    pub track_id: TrackId,
+    pub scene_id: SceneId,
+    pub start: BeatTime,
+    /// Where the clip stops, if that's been requested.
+    pub stop: Option<BeatTime>,
+}
+
+impl LaunchedClip {
+    /// Position in the clip at the given arrangement position, `None` if the clip isn't playing
+    /// there.
+    pub fn position(&self, clip: &Clip, at: BeatTime) -> Option<BeatTime> {
+        if at < self.start || self.stop.is_some_and(|stop| at >= stop) {
+            return None;
+        }
+
+        let length = clip.length.as_beats_f64();
+        if length <= 0.0 {
+            return None;
+        }
+
+        let offset = (at - self.start).as_beats_f64();
+        Some(BeatTime::from_beats_f64(offset % length))
+    }
+}
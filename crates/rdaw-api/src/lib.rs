@@ -3,16 +3,38 @@
 pub mod arrangement;
 pub mod asset;
 pub mod audio;
+pub mod automation;
+pub mod bus;
+pub mod cache;
+pub mod chord;
 pub mod document;
+pub mod engine;
 pub mod error;
+pub mod export;
+pub mod grid;
+pub mod interchange;
 pub mod item;
+pub mod launcher;
+pub mod log;
 pub mod media;
+pub mod meter;
+pub mod modulation;
+pub mod node;
+pub mod plugin;
+pub mod recording;
+pub mod render;
+pub mod settings;
 pub mod source;
+pub mod stats;
 pub mod tempo_map;
 #[cfg(test)]
 mod tests;
 pub mod time;
 pub mod track;
+pub mod transaction;
+pub mod transport;
+pub mod video;
+pub mod waveform;
 
 use std::fmt::Debug;
 use std::pin::Pin;
@@ -26,11 +48,91 @@ pub type BoxStream<T> = Pin<Box<dyn Stream<Item = T> + Send + 'static>>;
     operations(
         self::arrangement::ArrangementOperations,
         self::asset::AssetOperations,
+        self::item::AudioItemOperations,
+        self::item::MidiItemOperations,
         self::source::AudioSourceOperations,
+        self::source::MidiSourceOperations,
+        self::automation::AutomationOperations,
+        self::bus::BusOperations,
+        self::cache::CacheOperations,
+        self::chord::ChordOperations,
         self::document::DocumentOperations,
-        self::track::TrackOperations
+        self::engine::EngineOperations,
+        self::export::ExportOperations,
+        self::grid::GridOperations,
+        self::interchange::InterchangeOperations,
+        self::launcher::LauncherOperations,
+        self::log::LogOperations,
+        self::meter::MeterOperations,
+        self::modulation::ModulationOperations,
+        self::node::NodeOperations,
+        self::plugin::PluginOperations,
+        self::recording::RecordingOperations,
+        self::render::RenderOperations,
+        self::settings::SettingsOperations,
+        self::stats::StatsOperations,
+        self::track::TrackOperations,
+        self::transaction::TransactionOperations,
+        self::transport::TransportOperations,
+        self::video::VideoOperations,
+        self::waveform::WaveformOperations
     ),
     error = Error
 )]
 #[derive(Debug, Clone, Copy)]
 pub struct BackendProtocol;
+
+/// Part of a list to return, so that long lists can be fetched in several requests. Items keep
+/// their order as long as the list doesn't change in between.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PageRequest {
+    /// Number of items to skip.
+    pub offset: usize,
+    /// Maximum number of items to return.
+    pub limit: usize,
+}
+
+impl PageRequest {
+    pub const ALL: PageRequest = PageRequest {
+        offset: 0,
+        limit: usize::MAX,
+    };
+
+    pub fn new(offset: usize, limit: usize) -> PageRequest {
+        PageRequest { offset, limit }
+    }
+
+    /// Collects the requested part of `items`, counting all of them.
+    pub fn collect<T>(self, items: impl IntoIterator<Item = T>) -> Page<T> {
+        let mut page = Page {
+            items: Vec::new(),
+            total: 0,
+        };
+
+        for item in items {
+            if page.total >= self.offset && page.items.len() < self.limit {
+                page.items.push(item);
+            }
+
+            page.total += 1;
+        }
+
+        page
+    }
+}
+
+/// Part of a list, see [`PageRequest`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    /// Number of items in the whole list.
+    pub total: usize,
+}
+
+impl<T> Page<T> {
+    /// Request for the part right after this one, if there's any left.
+    pub fn next(&self, request: PageRequest) -> Option<PageRequest> {
+        let offset = request.offset.saturating_add(self.items.len());
+        (offset < self.total && !self.items.is_empty()).then(|| PageRequest { offset, ..request })
+    }
+}
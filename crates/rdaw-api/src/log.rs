@@ -0,0 +1,89 @@
+use std::time::SystemTime;
+
+use crate::{BackendProtocol, Result};
+
+#[rdaw_rpc::operations(protocol = BackendProtocol)]
+pub trait LogOperations {
+    /// Returns the most recent records matching the filter, oldest first.
+    async fn get_log_records(&self, filter: LogFilter) -> Result<Vec<LogRecord>>;
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogRecord {
+    pub timestamp: SystemTime,
+    pub level: LogLevel,
+    pub target: String,
+    /// The message followed by the other fields of the event, as `name=value`.
+    pub message: String,
+}
+
+/// Ordered from the least to the most verbose.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum LogLevel {
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl LogLevel {
+    pub const ALL: [LogLevel; 5] = [
+        LogLevel::Error,
+        LogLevel::Warn,
+        LogLevel::Info,
+        LogLevel::Debug,
+        LogLevel::Trace,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            LogLevel::Error => "ERROR",
+            LogLevel::Warn => "WARN",
+            LogLevel::Info => "INFO",
+            LogLevel::Debug => "DEBUG",
+            LogLevel::Trace => "TRACE",
+        }
+    }
+}
+
+impl From<tracing::Level> for LogLevel {
+    fn from(level: tracing::Level) -> LogLevel {
+        match level {
+            tracing::Level::ERROR => LogLevel::Error,
+            tracing::Level::WARN => LogLevel::Warn,
+            tracing::Level::INFO => LogLevel::Info,
+            tracing::Level::DEBUG => LogLevel::Debug,
+            tracing::Level::TRACE => LogLevel::Trace,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogFilter {
+    /// Records more verbose than this are skipped.
+    pub max_level: LogLevel,
+    /// Only records whose target starts with this are returned.
+    pub target: Option<String>,
+    pub limit: Option<usize>,
+}
+
+impl Default for LogFilter {
+    fn default() -> LogFilter {
+        LogFilter {
+            max_level: LogLevel::Trace,
+            target: None,
+            limit: None,
+        }
+    }
+}
+
+impl LogFilter {
+    pub fn matches(&self, record: &LogRecord) -> bool {
+        record.level <= self.max_level
+            && self
+                .target
+                .as_deref()
+                .is_none_or(|v| record.target.starts_with(v))
+    }
+}
@@ -1,4 +1,5 @@
 use crate::audio::AudioInputStream;
+use crate::video::VideoInputStream;
 use crate::Result;
 
 pub trait OpenMediaInput<R>: Sized {
@@ -10,5 +11,15 @@ pub trait MediaInput {
     where
         Self: 'a;
 
+    type VideoInputStream<'a>: VideoInputStream<'a>
+    where
+        Self: 'a;
+
     fn get_audio_stream(&mut self) -> Result<Option<Self::AudioInputStream<'_>>>;
+
+    fn get_video_stream(
+        &mut self,
+        max_width: u32,
+        max_height: u32,
+    ) -> Result<Option<Self::VideoInputStream<'_>>>;
 }
@@ -0,0 +1,91 @@
+use crate::arrangement::ArrangementId;
+use crate::track::TrackId;
+use crate::{BackendProtocol, BoxStream, Result};
+
+#[rdaw_rpc::operations(protocol = BackendProtocol)]
+pub trait MeterOperations {
+    /// Levels of the track after its volume and pan, while the engine is rendering its
+    /// arrangement. Frames come at most [`METER_RATE`] times per second, and once more with
+    /// silence after playback stops. The main track meters the master output.
+    #[sub]
+    async fn subscribe_track_meter(&self, track_id: TrackId) -> Result<BoxStream<MeterFrame>>;
+
+    /// Every `decimation`th sample of the master output is a point, it must be at least 1.
+    #[sub]
+    async fn subscribe_stereo_meter(
+        &self,
+        arrangement_id: ArrangementId,
+        decimation: u32,
+    ) -> Result<BoxStream<StereoFrame>>;
+
+    /// Levels of the default input while the track is armed, whether or not the transport is
+    /// playing, so that the input gain can be set before recording. Frames come at most
+    /// [`METER_RATE`] times per second, and only while an audio driver is set.
+    #[sub]
+    async fn subscribe_input_meter(&self, track_id: TrackId) -> Result<BoxStream<MeterFrame>>;
+}
+
+/// Maximum number of meter frames per second.
+pub const METER_RATE: u32 = 30;
+
+/// Linear levels of the left and right channels.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct MeterFrame {
+    pub peak: [f32; 2],
+    pub rms: [f32; 2],
+}
+
+impl MeterFrame {
+    pub fn measure(left: &[f32], right: &[f32]) -> MeterFrame {
+        let mut frame = MeterFrame::default();
+
+        for (channel, samples) in [left, right].into_iter().enumerate() {
+            let mut sum = 0.0;
+
+            for &sample in samples {
+                frame.peak[channel] = frame.peak[channel].max(sample.abs());
+                sum += sample * sample;
+            }
+
+            frame.rms[channel] = (sum / samples.len().max(1) as f32).sqrt();
+        }
+
+        frame
+    }
+
+    pub fn is_silent(&self) -> bool {
+        self.peak == [0.0; 2]
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StereoFrame {
+    pub points: Vec<[f32; 2]>,
+    /// Of every sample, not just the points.
+    pub correlation: f32,
+}
+
+impl StereoFrame {
+    pub fn measure(samples: &[[f32; 2]], decimation: usize) -> StereoFrame {
+        let mut products = [0.0f64; 3];
+
+        for &[left, right] in samples {
+            let (left, right) = (f64::from(left), f64::from(right));
+            products[0] += left * right;
+            products[1] += left * left;
+            products[2] += right * right;
+        }
+
+        let energy = (products[1] * products[2]).sqrt();
+        let correlation = if energy > 0.0 {
+            (products[0] / energy).clamp(-1.0, 1.0) as f32
+        } else {
+            0.0
+        };
+
+        StereoFrame {
+            points: samples.iter().step_by(decimation.max(1)).copied().collect(),
+            correlation,
+        }
+    }
+}
@@ -0,0 +1,188 @@
+use std::f32::consts::TAU;
+
+use rdaw_core::time::RealTime;
+use serde::{Deserialize, Serialize};
+
+use crate::arrangement::ArrangementId;
+use crate::node::{NodeId, NodeParam};
+use crate::track::TrackId;
+use crate::{BackendProtocol, BoxStream, Result};
+
+slotmap::new_key_type! {
+    pub struct ModulatorId;
+}
+
+#[rdaw_rpc::operations(protocol = BackendProtocol)]
+pub trait ModulationOperations {
+    #[sub]
+    async fn subscribe_arrangement_modulators(
+        &self,
+        id: ArrangementId,
+    ) -> Result<BoxStream<ModulatorEvent>>;
+
+    async fn add_arrangement_modulator(
+        &self,
+        id: ArrangementId,
+        modulator: Modulator,
+    ) -> Result<ModulatorId>;
+
+    async fn get_arrangement_modulator(
+        &self,
+        id: ArrangementId,
+        modulator_id: ModulatorId,
+    ) -> Result<Modulator>;
+
+    async fn get_arrangement_modulators(
+        &self,
+        id: ArrangementId,
+    ) -> Result<Vec<(ModulatorId, Modulator)>>;
+
+    async fn set_arrangement_modulator(
+        &self,
+        id: ArrangementId,
+        modulator_id: ModulatorId,
+        modulator: Modulator,
+    ) -> Result<()>;
+
+    async fn remove_arrangement_modulator(
+        &self,
+        id: ArrangementId,
+        modulator_id: ModulatorId,
+    ) -> Result<()>;
+
+    /// Fails for modulators that aren't macros.
+    async fn set_arrangement_macro_value(
+        &self,
+        id: ArrangementId,
+        modulator_id: ModulatorId,
+        value: f32,
+    ) -> Result<()>;
+
+    async fn get_modulated_node_params(&self, id: NodeId) -> Result<Vec<NodeParam>>;
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ModulatorEvent {
+    Added { id: ModulatorId, modulator: Modulator },
+    Changed { id: ModulatorId, modulator: Modulator },
+    Removed { id: ModulatorId },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Modulator {
+    pub name: String,
+    pub source: ModulationSource,
+    pub targets: Vec<ModulationTarget>,
+}
+
+impl Modulator {
+    pub fn is_valid(&self) -> bool {
+        self.source.is_valid() && self.targets.iter().all(ModulationTarget::is_valid)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ModulationSource {
+    Lfo(Lfo),
+    EnvelopeFollower(EnvelopeFollower),
+    Macro { value: f32 },
+}
+
+impl ModulationSource {
+    pub fn is_valid(&self) -> bool {
+        match *self {
+            ModulationSource::Lfo(lfo) => lfo.is_valid(),
+            ModulationSource::EnvelopeFollower(follower) => follower.is_valid(),
+            ModulationSource::Macro { value } => (0.0..=1.0).contains(&value),
+        }
+    }
+}
+
+/// Locked to the playhead.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Lfo {
+    pub shape: LfoShape,
+    /// Cycles per second.
+    pub rate: f32,
+    pub phase: f32,
+}
+
+impl Lfo {
+    pub fn is_valid(&self) -> bool {
+        self.rate.is_finite() && self.rate >= 0.0 && (0.0..=1.0).contains(&self.phase)
+    }
+
+    pub fn value_at(&self, position: RealTime) -> f32 {
+        let cycles = position.as_secs_f64() * f64::from(self.rate) + f64::from(self.phase);
+        self.shape.value(cycles.rem_euclid(1.0) as f32)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum LfoShape {
+    Sine,
+    Triangle,
+    Saw,
+    Square,
+}
+
+impl LfoShape {
+    /// Every shape starts at its lowest point.
+    pub fn value(self, phase: f32) -> f32 {
+        match self {
+            LfoShape::Sine => 0.5 - 0.5 * (phase * TAU).cos(),
+            LfoShape::Triangle => 1.0 - (2.0 * phase - 1.0).abs(),
+            LfoShape::Saw => phase,
+            LfoShape::Square => {
+                if phase < 0.5 {
+                    0.0
+                } else {
+                    1.0
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EnvelopeFollower {
+    pub track_id: TrackId,
+    pub attack: RealTime,
+    pub release: RealTime,
+}
+
+impl EnvelopeFollower {
+    pub fn is_valid(&self) -> bool {
+        self.attack >= RealTime::ZERO && self.release >= RealTime::ZERO
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModulationTarget {
+    pub node_id: NodeId,
+    pub param: String,
+    /// Fraction of the parameter's range, negative depths invert the modulation.
+    pub depth: f32,
+    pub polarity: Polarity,
+}
+
+impl ModulationTarget {
+    pub fn is_valid(&self) -> bool {
+        (-1.0..=1.0).contains(&self.depth)
+    }
+
+    pub fn offset(&self, param: &NodeParam, source: f32) -> f32 {
+        let amount = match self.polarity {
+            Polarity::Unipolar => source,
+            Polarity::Bipolar => source * 2.0 - 1.0,
+        };
+
+        amount * self.depth * (param.max - param.min)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Polarity {
+    Unipolar,
+    Bipolar,
+}
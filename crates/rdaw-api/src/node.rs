@@ -0,0 +1,129 @@
+use rdaw_core::path::Utf8PathBuf;
+
+use crate::document::DocumentId;
+use crate::{BackendProtocol, BoxStream, Result};
+
+slotmap::new_key_type! {
+    pub struct NodeId;
+}
+
+#[rdaw_rpc::operations(protocol = BackendProtocol)]
+pub trait NodeOperations {
+    async fn create_node(
+        &self,
+        document_id: DocumentId,
+        kind: String,
+        params: Vec<NodeParam>,
+    ) -> Result<NodeId>;
+
+    #[sub]
+    async fn subscribe_node_params(&self, id: NodeId) -> Result<BoxStream<NodeParamEvent>>;
+
+    async fn get_node_kind(&self, id: NodeId) -> Result<String>;
+
+    async fn get_node_params(&self, id: NodeId) -> Result<Vec<NodeParam>>;
+
+    async fn set_node_param(&self, id: NodeId, name: String, value: f32) -> Result<()>;
+
+    async fn list_node_presets(
+        &self,
+        document_id: DocumentId,
+        kind: String,
+    ) -> Result<Vec<NodePreset>>;
+
+    async fn save_node_preset(
+        &self,
+        id: NodeId,
+        name: String,
+        location: PresetLocation,
+    ) -> Result<()>;
+
+    async fn apply_node_preset(
+        &self,
+        id: NodeId,
+        name: String,
+        location: PresetLocation,
+    ) -> Result<()>;
+
+    async fn remove_node_preset(
+        &self,
+        document_id: DocumentId,
+        kind: String,
+        name: String,
+        location: PresetLocation,
+    ) -> Result<()>;
+
+    async fn get_node_compare_slot(&self, id: NodeId) -> Result<CompareSlot>;
+
+    async fn store_node_compare_slot(&self, id: NodeId, slot: CompareSlot) -> Result<()>;
+
+    async fn copy_node_compare_slot(&self, id: NodeId, from: CompareSlot) -> Result<()>;
+
+    async fn toggle_node_compare(&self, id: NodeId) -> Result<CompareSlot>;
+
+    #[role(Admin)]
+    async fn set_user_preset_dir(&self, path: Option<Utf8PathBuf>) -> Result<()>;
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct NodeParam {
+    pub name: String,
+    pub value: f32,
+    pub min: f32,
+    pub max: f32,
+    pub default: f32,
+}
+
+impl NodeParam {
+    pub fn new(name: impl Into<String>, min: f32, max: f32, default: f32) -> NodeParam {
+        NodeParam {
+            name: name.into(),
+            value: default,
+            min,
+            max,
+            default,
+        }
+    }
+
+    pub fn clamp(&self, value: f32) -> f32 {
+        if value.is_nan() {
+            self.default
+        } else {
+            value.clamp(self.min, self.max)
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct NodeParamEvent {
+    pub name: String,
+    pub value: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum PresetLocation {
+    User,
+    Document,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct NodePreset {
+    pub name: String,
+    pub location: PresetLocation,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum CompareSlot {
+    #[default]
+    A,
+    B,
+}
+
+impl CompareSlot {
+    pub fn other(self) -> CompareSlot {
+        match self {
+            CompareSlot::A => CompareSlot::B,
+            CompareSlot::B => CompareSlot::A,
+        }
+    }
+}
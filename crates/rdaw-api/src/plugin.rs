@@ -0,0 +1,43 @@
+use rdaw_core::path::Utf8PathBuf;
+
+use crate::node::NodeId;
+use crate::track::TrackId;
+use crate::{BackendProtocol, Result};
+
+#[rdaw_rpc::operations(protocol = BackendProtocol)]
+pub trait PluginOperations {
+    /// Nothing is found in safe mode.
+    #[role(Admin)]
+    async fn scan_plugins(&self, extra_paths: Vec<Utf8PathBuf>) -> Result<Vec<PluginDescriptor>>;
+
+    async fn get_plugins(&self) -> Result<Vec<PluginDescriptor>>;
+
+    async fn add_track_plugin(&self, track_id: TrackId, plugin_id: String) -> Result<NodeId>;
+
+    async fn get_plugin_params(&self, node_id: NodeId) -> Result<Vec<PluginParam>>;
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PluginDescriptor {
+    pub id: String,
+    pub name: String,
+    pub vendor: String,
+    pub version: String,
+    pub description: String,
+    pub features: Vec<String>,
+    pub path: Utf8PathBuf,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PluginParam {
+    pub id: u32,
+    pub name: String,
+    pub module: String,
+    pub min: f64,
+    pub max: f64,
+    pub default: f64,
+}
+
+pub fn plugin_node_kind(plugin_id: &str) -> String {
+    format!("clap:{plugin_id}")
+}
@@ -0,0 +1,42 @@
+use rdaw_core::time::RealTime;
+
+use crate::arrangement::ArrangementId;
+use crate::track::{TrackId, TrackItemId};
+use crate::{BackendProtocol, BoxStream, Result};
+
+#[rdaw_rpc::operations(protocol = BackendProtocol)]
+pub trait RecordingOperations {
+    /// Armed tracks receive the recorded audio.
+    async fn arm_track(&self, id: TrackId, armed: bool) -> Result<()>;
+
+    async fn is_track_armed(&self, id: TrackId) -> Result<bool>;
+
+    /// Starts playback and captures the default input, until recording is stopped.
+    async fn start_recording(&self, id: ArrangementId) -> Result<()>;
+
+    /// Stops playback and adds the captured audio to every armed track of the arrangement, at the
+    /// position recording was started from.
+    async fn stop_recording(&self, id: ArrangementId) -> Result<Vec<(TrackId, TrackItemId)>>;
+
+    /// How much earlier the captured audio is placed, to make up for the latency of the device.
+    #[role(ReadOnly)]
+    async fn get_recording_latency_compensation(&self) -> Result<RealTime>;
+
+    #[role(Admin)]
+    async fn set_recording_latency_compensation(&self, latency: RealTime) -> Result<()>;
+
+    #[sub]
+    async fn subscribe_recording_meter(
+        &self,
+        id: ArrangementId,
+    ) -> Result<BoxStream<RecordingMeter>>;
+}
+
+/// Levels of the captured audio since the previous event.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecordingMeter {
+    /// Peak absolute sample value of each channel.
+    pub peaks: Vec<f32>,
+    /// How much has been recorded so far.
+    pub duration: RealTime,
+}
@@ -0,0 +1,56 @@
+use rdaw_core::path::Utf8PathBuf;
+
+use crate::arrangement::ArrangementId;
+use crate::export::ExportSettings;
+use crate::time::Time;
+use crate::{BackendProtocol, BoxStream, Result};
+
+#[rdaw_rpc::operations(protocol = BackendProtocol)]
+pub trait RenderOperations {
+    /// A render that didn't finish doesn't leave a file behind.
+    #[role(Admin)]
+    async fn render_arrangement(
+        &self,
+        arrangement_id: ArrangementId,
+        range: RenderRange,
+        settings: ExportSettings,
+        path: Utf8PathBuf,
+    ) -> Result<()>;
+
+    /// Renders the arrangement from its start to `end` as a CD image for replication: a cue sheet
+    /// at `path` with a track for every CD track marker, next to a `.bin` file with the audio as
+    /// raw 16-bit 44.1 kHz stereo. Progress and cancellation work as with `render_arrangement`.
+    #[role(Admin)]
+    async fn render_arrangement_cd(
+        &self,
+        arrangement_id: ArrangementId,
+        end: Time,
+        path: Utf8PathBuf,
+    ) -> Result<()>;
+
+    #[sub]
+    async fn subscribe_render_progress(
+        &self,
+        arrangement_id: ArrangementId,
+    ) -> Result<BoxStream<RenderProgress>>;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RenderRange {
+    pub start: Time,
+    pub end: Time,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RenderProgress {
+    pub path: Utf8PathBuf,
+    pub stage: RenderStage,
+    pub progress: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RenderStage {
+    Decoding,
+    Rendering,
+    Encoding,
+}
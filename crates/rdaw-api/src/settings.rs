@@ -0,0 +1,45 @@
+use std::time::Duration;
+
+use rdaw_core::path::Utf8PathBuf;
+
+use crate::{BackendProtocol, Result};
+
+#[rdaw_rpc::operations(protocol = BackendProtocol)]
+pub trait SettingsOperations {
+    #[role(ReadOnly)]
+    async fn get_autosave_interval(&self) -> Result<Option<Duration>>;
+
+    /// Documents which have a path and unsaved changes are saved this often, never if `None`.
+    #[role(Admin)]
+    async fn set_autosave_interval(&self, interval: Option<Duration>) -> Result<()>;
+
+    /// Settings kept between sessions, like asset path variables, are loaded from this file right
+    /// away and written to it whenever they change. Nothing is kept if `None`.
+    #[role(Admin)]
+    async fn set_settings_path(&self, path: Option<Utf8PathBuf>) -> Result<()>;
+
+    #[role(ReadOnly)]
+    async fn get_monitoring_limiter(&self) -> Result<MonitoringLimiter>;
+
+    /// Limits what the engine sends to the audio driver, to protect ears and speakers from
+    /// accidents while editing. Offline renders are never limited.
+    #[role(Admin)]
+    async fn set_monitoring_limiter(&self, limiter: MonitoringLimiter) -> Result<()>;
+}
+
+/// Brickwall true-peak limiter right before the audio driver.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MonitoringLimiter {
+    pub enabled: bool,
+    /// Highest true peak let through in dBTP, at most 0.
+    pub ceiling: f32,
+}
+
+impl Default for MonitoringLimiter {
+    fn default() -> MonitoringLimiter {
+        MonitoringLimiter {
+            enabled: true,
+            ceiling: -1.0,
+        }
+    }
+}
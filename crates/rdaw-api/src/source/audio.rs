@@ -1,6 +1,7 @@
 use crate::asset::AssetId;
-use crate::audio::AudioMetadata;
-use crate::{BackendProtocol, BoxStream, Result};
+use crate::audio::{AudioMetadata, ResampleQuality};
+use crate::document::DocumentId;
+use crate::{BackendProtocol, BoxStream, Page, PageRequest, Result};
 
 slotmap::new_key_type! {
     pub struct AudioSourceId;
@@ -10,6 +11,13 @@ slotmap::new_key_type! {
 pub trait AudioSourceOperations {
     async fn create_audio_source(&self, asset_id: AssetId) -> Result<AudioSourceId>;
 
+    #[role(ReadOnly)]
+    async fn list_audio_sources(
+        &self,
+        document_id: DocumentId,
+        page: PageRequest,
+    ) -> Result<Page<AudioSourceId>>;
+
     #[sub]
     async fn subscribe_audio_source_name(&self, id: AudioSourceId) -> Result<BoxStream<String>>;
 
@@ -18,4 +26,14 @@ pub trait AudioSourceOperations {
     async fn set_audio_source_name(&self, id: AudioSourceId, new_name: String) -> Result<()>;
 
     async fn get_audio_source_metadata(&self, id: AudioSourceId) -> Result<AudioMetadata>;
+
+    async fn get_audio_source_resample_quality(&self, id: AudioSourceId)
+        -> Result<ResampleQuality>;
+
+    /// Only matters if the sample rate of the source differs from the one of the engine.
+    async fn set_audio_source_resample_quality(
+        &self,
+        id: AudioSourceId,
+        quality: ResampleQuality,
+    ) -> Result<()>;
 }
@@ -0,0 +1,102 @@
+use crate::document::DocumentId;
+use crate::time::BeatTime;
+use crate::{BackendProtocol, BoxStream, Page, PageRequest, Result};
+
+slotmap::new_key_type! {
+    pub struct MidiSourceId;
+
+    pub struct MidiNoteId;
+}
+
+#[rdaw_rpc::operations(protocol = BackendProtocol)]
+pub trait MidiSourceOperations {
+    async fn create_midi_source(&self, document_id: DocumentId) -> Result<MidiSourceId>;
+
+    #[role(ReadOnly)]
+    async fn list_midi_sources(
+        &self,
+        document_id: DocumentId,
+        page: PageRequest,
+    ) -> Result<Page<MidiSourceId>>;
+
+    #[sub]
+    async fn subscribe_midi_source_notes(
+        &self,
+        id: MidiSourceId,
+    ) -> Result<BoxStream<MidiNoteEvent>>;
+
+    #[sub]
+    async fn subscribe_midi_source_ccs(&self, id: MidiSourceId) -> Result<BoxStream<Vec<MidiCc>>>;
+
+    async fn add_midi_source_note(&self, id: MidiSourceId, note: MidiNote) -> Result<MidiNoteId>;
+
+    async fn get_midi_source_note(
+        &self,
+        id: MidiSourceId,
+        note_id: MidiNoteId,
+    ) -> Result<MidiNote>;
+
+    /// Returns notes sorted by their start.
+    async fn get_midi_source_notes(&self, id: MidiSourceId) -> Result<Vec<(MidiNoteId, MidiNote)>>;
+
+    async fn set_midi_source_note(
+        &self,
+        id: MidiSourceId,
+        note_id: MidiNoteId,
+        note: MidiNote,
+    ) -> Result<()>;
+
+    async fn remove_midi_source_note(&self, id: MidiSourceId, note_id: MidiNoteId) -> Result<()>;
+
+    /// Returns controller events sorted by their position.
+    async fn get_midi_source_ccs(&self, id: MidiSourceId) -> Result<Vec<MidiCc>>;
+
+    /// Inserts the event after all events with the same position, returns its index.
+    async fn add_midi_source_cc(&self, id: MidiSourceId, cc: MidiCc) -> Result<usize>;
+
+    async fn remove_midi_source_cc(&self, id: MidiSourceId, index: usize) -> Result<()>;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MidiNoteEvent {
+    Added { id: MidiNoteId, note: MidiNote },
+    Changed { id: MidiNoteId, note: MidiNote },
+    Removed { id: MidiNoteId },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MidiNote {
+    /// Relative to the start of the source.
+    pub start: BeatTime,
+    pub duration: BeatTime,
+    pub key: u8,
+    pub velocity: u8,
+}
+
+impl MidiNote {
+    pub fn end(&self) -> BeatTime {
+        self.start + self.duration
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.start >= BeatTime::ZERO
+            && self.duration > BeatTime::ZERO
+            && self.key < 128
+            && (1..128).contains(&self.velocity)
+    }
+}
+
+/// Control change event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MidiCc {
+    /// Relative to the start of the source.
+    pub position: BeatTime,
+    pub controller: u8,
+    pub value: u8,
+}
+
+impl MidiCc {
+    pub fn is_valid(&self) -> bool {
+        self.position >= BeatTime::ZERO && self.controller < 128 && self.value < 128
+    }
+}
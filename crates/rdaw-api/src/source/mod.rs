@@ -1,10 +1,13 @@
 mod audio;
+mod midi;
 
 pub use self::audio::*;
+pub use self::midi::*;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub enum SourceId {
     Audio(AudioSourceId),
+    Midi(MidiSourceId),
 }
 
 impl From<AudioSourceId> for SourceId {
@@ -12,3 +15,9 @@ impl From<AudioSourceId> for SourceId {
         SourceId::Audio(id)
     }
 }
+
+impl From<MidiSourceId> for SourceId {
+    fn from(id: MidiSourceId) -> SourceId {
+        SourceId::Midi(id)
+    }
+}
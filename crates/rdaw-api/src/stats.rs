@@ -0,0 +1,113 @@
+use std::time::Duration;
+
+use crate::document::DocumentId;
+use crate::{BackendProtocol, Result};
+
+#[rdaw_rpc::operations(protocol = BackendProtocol)]
+pub trait StatsOperations {
+    /// Returns stats of every handler that was called at least once, slowest in total first.
+    async fn get_handler_stats(&self) -> Result<Vec<HandlerStats>>;
+
+    #[role(Admin)]
+    async fn reset_handler_stats(&self) -> Result<()>;
+
+    /// Requests taking longer than the threshold are logged along with their parameters.
+    #[role(Admin)]
+    async fn set_slow_request_threshold(&self, threshold: Option<Duration>) -> Result<()>;
+
+    async fn get_document_stats(&self, id: DocumentId) -> Result<DocumentStats>;
+
+    async fn get_task_queue_stats(&self) -> Result<Vec<TaskQueueStats>>;
+
+    async fn get_track_view_cache_stats(&self) -> Result<TrackViewCacheStats>;
+
+    /// Views with subscribers are kept even if there are more of them than the capacity.
+    #[role(Admin)]
+    async fn set_track_view_cache_capacity(&self, capacity: usize) -> Result<()>;
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HandlerStats {
+    pub name: String,
+    pub count: u64,
+    pub total: Duration,
+    pub max: Duration,
+    /// Percentiles are computed over the most recent calls only.
+    pub p50: Duration,
+    pub p90: Duration,
+    pub p99: Duration,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DocumentStats {
+    /// Per object type, in memory.
+    pub objects: Vec<MemoryStats>,
+    /// Per cache, in memory.
+    pub caches: Vec<MemoryStats>,
+    pub blob_count: u64,
+    /// Uncompressed size of all blobs in the document file.
+    pub blob_bytes: u64,
+    pub file_size: u64,
+}
+
+impl DocumentStats {
+    pub fn estimated_bytes(&self) -> usize {
+        self.objects
+            .iter()
+            .chain(&self.caches)
+            .map(|v| v.estimated_bytes)
+            .sum()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MemoryStats {
+    pub name: String,
+    pub count: usize,
+    pub estimated_bytes: usize,
+}
+
+/// Background work is split into queues, so that one kind of work can't starve another.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TaskQueue {
+    /// Reading and hashing files, writing blobs.
+    Io,
+    /// Decoding audio and video.
+    Decode,
+    /// Computing things from decoded media, like waveforms.
+    Analysis,
+    /// Rendering arrangements to files.
+    Render,
+}
+
+impl TaskQueue {
+    pub const ALL: [TaskQueue; 4] = [
+        TaskQueue::Io,
+        TaskQueue::Decode,
+        TaskQueue::Analysis,
+        TaskQueue::Render,
+    ];
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TaskQueueStats {
+    pub queue: TaskQueue,
+    /// How many tasks of the queue may run at the same time.
+    pub concurrency: usize,
+    pub queued: usize,
+    pub running: usize,
+    pub completed: u64,
+    pub failed: u64,
+    /// Time spent running tasks, summed over all threads.
+    pub busy: Duration,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TrackViewCacheStats {
+    pub capacity: usize,
+    /// Cached views, across all documents.
+    pub len: usize,
+    pub hits: u64,
+    pub misses: u64,
+    pub evictions: u64,
+}
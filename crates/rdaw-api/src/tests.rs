@@ -1,27 +1,60 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use futures::channel::oneshot;
 use futures::executor::LocalPool;
 use futures::task::SpawnExt;
-use futures::FutureExt;
-use rdaw_rpc::transport::{self, ServerTransport};
-use rdaw_rpc::{handler, operations, protocol, Client, ClientMessage};
+use futures::{FutureExt, StreamExt};
+use rdaw_rpc::transport::{self, BatchingTransport, ServerTransport};
+use rdaw_rpc::{
+    handler, operations, protocol, Client, ClientMessage, Role, ServerMessage, StreamId,
+};
 
-use crate::{Error, ErrorKind, Result};
+use crate::{BoxStream, Error, ErrorKind, Result};
 
 #[operations(protocol = TestProtocol)]
 trait FooOperations {
     async fn get_foo(&self) -> Result<i32>;
+
+    async fn set_foo(&self, foo: i32) -> Result<()>;
+
+    #[role(Admin)]
+    async fn reset_foo(&self) -> Result<()>;
+
+    #[sub]
+    async fn subscribe_foo(&self) -> Result<BoxStream<i32>>;
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[protocol(operations(FooOperations), error = Error)]
 struct TestProtocol;
 
-struct TestBackend;
+struct TestBackend {
+    foo: i32,
+}
 
 #[handler(protocol = TestProtocol, operations = FooOperations)]
 impl TestBackend {
     #[handler]
     fn get_foo(&self) -> Result<i32> {
-        Ok(1)
+        Ok(self.foo)
+    }
+
+    #[handler]
+    fn set_foo(&mut self, foo: i32) -> Result<()> {
+        self.foo = foo;
+        Ok(())
+    }
+
+    #[handler]
+    fn reset_foo(&mut self) -> Result<()> {
+        self.foo = 0;
+        Ok(())
+    }
+
+    #[handler]
+    fn subscribe_foo(&mut self) -> Result<StreamId> {
+        Ok(StreamId(0))
     }
 }
 
@@ -35,7 +68,18 @@ impl TestBackend {
             ClientMessage::Request { id, payload } => match payload {
                 TestRequest::Foo(req) => self.handle_foo_request(transport, id, req).await,
             },
-            ClientMessage::CloseStream { .. } => todo!(),
+            ClientMessage::Batch { requests } => {
+                let transport = BatchingTransport::new(transport);
+                for (id, payload) in requests {
+                    match payload {
+                        TestRequest::Foo(req) => {
+                            self.handle_foo_request(transport.clone(), id, req).await?
+                        }
+                    }
+                }
+                transport.finish().await
+            }
+            ClientMessage::CancelRequest { .. } | ClientMessage::CloseStream { .. } => todo!(),
         }
     }
 
@@ -58,7 +102,7 @@ fn local_client() -> Result<()> {
     let (client_transport, server_transport) = transport::local(None);
 
     let client = Client::<TestProtocol, _>::new(client_transport);
-    let mut server = TestBackend;
+    let mut server = TestBackend { foo: 1 };
 
     spawner
         .spawn(client.clone().handle().map(|v| v.unwrap()))
@@ -75,3 +119,363 @@ fn local_client() -> Result<()> {
         Ok(())
     })
 }
+
+#[test]
+fn batch() -> Result<()> {
+    let mut executor = LocalPool::new();
+    let spawner = executor.spawner();
+
+    let (client_transport, server_transport) = transport::local(None);
+
+    let client = Client::<TestProtocol, _>::new(client_transport);
+
+    spawner
+        .spawn(client.clone().handle().map(|v| v.unwrap()))
+        .unwrap();
+
+    // the whole batch arrives as one message, and is answered with one
+    spawner
+        .spawn(async move {
+            let mut server = TestBackend { foo: 1 };
+            let msg = server_transport.recv().await.unwrap();
+            assert!(matches!(msg, ClientMessage::Batch { ref requests } if requests.len() == 3));
+            server
+                .handle_message(server_transport.clone(), msg)
+                .await
+                .unwrap();
+        })
+        .unwrap();
+
+    executor.run_until(async move {
+        let batch = client.batch();
+        let first = batch.get_foo();
+        let set = batch.set_foo(2);
+        let second = batch.get_foo();
+        assert_eq!(batch.len(), 3);
+
+        batch.send().await?;
+        assert!(batch.is_empty());
+
+        assert_eq!(first.await?, 1);
+        set.await?;
+        assert_eq!(second.await?, 2);
+
+        Ok(())
+    })
+}
+
+#[test]
+fn cancel_request() -> Result<()> {
+    let mut executor = LocalPool::new();
+    let spawner = executor.spawner();
+
+    let (client_transport, server_transport) = transport::local(None);
+
+    let client = Client::<TestProtocol, _>::new(client_transport);
+    let (responded_sender, responded_receiver) = oneshot::channel();
+
+    spawner
+        .spawn(client.clone().handle().map(|v| v.unwrap()))
+        .unwrap();
+
+    // the response to the cancelled request comes after the cancellation
+    spawner
+        .spawn(async move {
+            let mut server = TestBackend { foo: 1 };
+
+            let ClientMessage::Request { id, payload } = server_transport.recv().await.unwrap()
+            else {
+                panic!("expected a request");
+            };
+
+            let msg = server_transport.recv().await.unwrap();
+            assert!(
+                matches!(msg, ClientMessage::CancelRequest { id: cancelled } if cancelled == id)
+            );
+
+            let msg = ClientMessage::Request { id, payload };
+            server
+                .handle_message(server_transport.clone(), msg)
+                .await
+                .unwrap();
+            responded_sender.send(()).unwrap();
+
+            server.handle(server_transport).await.unwrap();
+        })
+        .unwrap();
+
+    executor.run_until(async move {
+        let req = client
+            .request_with_cancel(FooRequest::GetFoo {}.into())
+            .await?;
+        req.cancel();
+        responded_receiver.await.unwrap();
+
+        // the late response didn't get mixed up with this one
+        assert_eq!(client.get_foo().await?, 1);
+
+        Ok(())
+    })
+}
+
+#[test]
+fn reconnect() -> Result<()> {
+    let mut executor = LocalPool::new();
+    let spawner = executor.spawner();
+
+    let (client_transport, server_transport) = transport::local(None);
+    let (next_client_transport, next_server_transport) = transport::local(None);
+
+    let client = Client::<TestProtocol, _>::new(client_transport);
+    let mut resync = client.subscribe_resync();
+
+    // the first attempt to connect again fails
+    let mut next_transport = Some(next_client_transport);
+    let mut failures = 1;
+    let connect = move || {
+        let transport = if failures > 0 {
+            failures -= 1;
+            None
+        } else {
+            next_transport.take()
+        };
+        async move { transport.ok_or_else(|| Error::new(ErrorKind::Disconnected, "no server")) }
+    };
+
+    let delays = Arc::new(Mutex::new(Vec::new()));
+    let sleep = {
+        let delays = delays.clone();
+        move |delay| {
+            delays.lock().unwrap().push(delay);
+            async {}
+        }
+    };
+
+    spawner
+        .spawn(
+            client
+                .clone()
+                .handle_reconnecting(connect, sleep)
+                .map(|v| v.unwrap()),
+        )
+        .unwrap();
+
+    // the first server goes away after a single request
+    spawner
+        .spawn(async move {
+            let mut server = TestBackend { foo: 1 };
+            let msg = server_transport.recv().await.unwrap();
+            server
+                .handle_message(server_transport.clone(), msg)
+                .await
+                .unwrap();
+        })
+        .unwrap();
+
+    spawner
+        .spawn(async move {
+            let mut server = TestBackend { foo: 2 };
+            server.handle(next_server_transport).await.unwrap()
+        })
+        .unwrap();
+
+    executor.run_until(async move {
+        assert_eq!(client.get_foo().await?, 1);
+
+        resync.next().await;
+
+        assert_eq!(client.get_foo().await?, 2);
+        assert_eq!(*delays.lock().unwrap(), [Duration::from_millis(100)]);
+
+        Ok(())
+    })
+}
+
+#[test]
+fn reconnect_resubscribes() -> Result<()> {
+    let mut executor = LocalPool::new();
+    let spawner = executor.spawner();
+
+    let (client_transport, server_transport) = transport::local(None);
+    let (next_client_transport, next_server_transport) = transport::local(None);
+
+    let client = Client::<TestProtocol, _>::new(client_transport);
+    let mut resync = client.subscribe_resync();
+
+    let mut next_transport = Some(next_client_transport);
+    let connect = move || {
+        let transport = next_transport.take();
+        async move { transport.ok_or_else(|| Error::new(ErrorKind::Disconnected, "no server")) }
+    };
+
+    spawner
+        .spawn(
+            client
+                .clone()
+                .handle_reconnecting(connect, |_| async {})
+                .map(|v| v.unwrap()),
+        )
+        .unwrap();
+
+    // the first server goes away right after the subscription
+    spawner
+        .spawn(async move {
+            let mut server = TestBackend { foo: 1 };
+            let msg = server_transport.recv().await.unwrap();
+            server
+                .handle_message(server_transport.clone(), msg)
+                .await
+                .unwrap();
+        })
+        .unwrap();
+
+    // the next one gets the replayed subscription, and sends the change made after it
+    spawner
+        .spawn(async move {
+            let mut server = TestBackend { foo: 2 };
+
+            for _ in 0..2 {
+                let msg = next_server_transport.recv().await.unwrap();
+                server
+                    .handle_message(next_server_transport.clone(), msg)
+                    .await
+                    .unwrap();
+            }
+
+            let msg = ServerMessage::Event {
+                id: StreamId(0),
+                seq: 0,
+                payload: FooEvents::SubscribeFoo(server.foo).into(),
+            };
+            next_server_transport.send(msg).await.unwrap();
+
+            server.handle(next_server_transport).await.unwrap()
+        })
+        .unwrap();
+
+    executor.run_until(async move {
+        let mut stream = client.subscribe_foo().await?;
+
+        resync.next().await;
+
+        client.set_foo(3).await?;
+        assert_eq!(stream.next().await, Some(3));
+
+        Ok(())
+    })
+}
+
+#[test]
+fn disconnect_fails_requests() -> Result<()> {
+    let mut executor = LocalPool::new();
+    let spawner = executor.spawner();
+
+    let (client_transport, server_transport) = transport::local(None);
+
+    let client = Client::<TestProtocol, _>::new(client_transport);
+
+    // the server goes away without responding
+    spawner
+        .spawn(async move {
+            server_transport.recv().await.unwrap();
+        })
+        .unwrap();
+
+    executor.run_until(async move {
+        // not polled before the disconnect
+        let req = client
+            .request_with_cancel(FooRequest::GetFoo {}.into())
+            .await?;
+
+        client.clone().handle().await?;
+
+        let err = req.await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Disconnected);
+
+        Ok(())
+    })
+}
+
+#[test]
+fn event_gap() -> Result<()> {
+    let mut executor = LocalPool::new();
+    let spawner = executor.spawner();
+
+    let (client_transport, server_transport) = transport::local(None);
+
+    let client = Client::<TestProtocol, _>::new(client_transport);
+    let mut resync = client.subscribe_resync();
+
+    spawner
+        .spawn(client.clone().handle().map(|v| v.unwrap()))
+        .unwrap();
+
+    spawner
+        .spawn(async move {
+            let mut server = TestBackend { foo: 1 };
+            let msg = server_transport.recv().await.unwrap();
+            server
+                .handle_message(server_transport.clone(), msg)
+                .await
+                .unwrap();
+
+            // the event with seq 2 is lost
+            for seq in [0, 1, 3] {
+                let payload = FooEvents::SubscribeFoo(seq as i32).into();
+                let msg = ServerMessage::Event {
+                    id: StreamId(0),
+                    seq,
+                    payload,
+                };
+                server_transport.send(msg).await.unwrap();
+            }
+        })
+        .unwrap();
+
+    executor.run_until(async move {
+        let mut stream = client.subscribe_foo().await?;
+
+        assert_eq!(stream.next().await, Some(0));
+        assert_eq!(stream.next().await, Some(1));
+        assert_eq!(stream.next().await, Some(3));
+        assert_eq!(resync.next().now_or_never(), Some(Some(())));
+
+        Ok(())
+    })
+}
+
+#[test]
+fn required_role() {
+    let req = TestRequest::from(FooRequest::GetFoo {});
+    assert_eq!(req.required_role(), Role::ReadOnly);
+
+    let req = TestRequest::from(FooRequest::SetFoo { foo: 2 });
+    assert_eq!(req.required_role(), Role::Editor);
+
+    let req = TestRequest::from(FooRequest::ResetFoo {});
+    assert_eq!(req.required_role(), Role::Admin);
+}
+
+#[test]
+fn request_name() {
+    let req = TestRequest::from(FooRequest::SetFoo { foo: 2 });
+    assert_eq!(req.name(), "set_foo");
+}
+
+#[test]
+fn error_classification() {
+    let error = Error::new(ErrorKind::Busy, "database is locked");
+    assert!(error.is_retryable());
+    assert!(!error.is_user_error());
+
+    let error = Error::new(ErrorKind::InvalidPassphrase, "invalid passphrase");
+    assert!(!error.is_retryable());
+    assert!(error.is_user_error());
+
+    let error = Error::new(ErrorKind::InvalidId, "invalid id");
+    assert!(!error.is_retryable());
+    assert!(!error.is_user_error());
+
+    let error = Error::from(std::io::Error::from(std::io::ErrorKind::TimedOut));
+    assert_eq!(error.kind(), ErrorKind::Timeout);
+}
@@ -10,6 +10,24 @@ pub enum Time {
     Beat(BeatTime),
 }
 
+impl Time {
+    pub fn time_base(self) -> TimeBase {
+        match self {
+            Time::Real(_) => TimeBase::Absolute,
+            Time::Beat(_) => TimeBase::Musical,
+        }
+    }
+}
+
+/// Whether a position follows tempo changes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum TimeBase {
+    /// Positioned in beats, moves when the tempo changes.
+    Musical,
+    /// Positioned in wall-clock time, stays in place when the tempo changes.
+    Absolute,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct BeatTime {
     beats: I32F32,
@@ -1,11 +1,15 @@
-use rdaw_core::collections::{HashMap, ImVec};
+use rdaw_core::collections::{HashMap, HashSet, ImVec};
 use rdaw_core::time::RealTime;
 
 use crate::arrangement::ArrangementId;
 use crate::document::DocumentId;
-use crate::item::ItemId;
-use crate::time::Time;
-use crate::{BackendProtocol, BoxStream, Result};
+use crate::item::{AudioClip, ItemId};
+use crate::node::NodeId;
+use crate::time::{BeatTime, Time, TimeBase};
+use crate::{BackendProtocol, BoxStream, Page, PageRequest, Result};
+
+/// Highest lane an item can be on, so that tracks have at most 256 lanes.
+pub const MAX_TRACK_LANE: u32 = 255;
 
 slotmap::new_key_type! {
     pub struct TrackId;
@@ -23,6 +27,14 @@ pub struct TrackViewId {
 pub trait TrackOperations {
     async fn create_track(&self, document_id: DocumentId) -> Result<TrackId>;
 
+    /// Returns tracks of the document, including ones which aren't part of any arrangement.
+    #[role(ReadOnly)]
+    async fn list_tracks(
+        &self,
+        document_id: DocumentId,
+        page: PageRequest,
+    ) -> Result<Page<TrackId>>;
+
     #[sub]
     async fn subscribe_track_name(&self, id: TrackId) -> Result<BoxStream<String>>;
 
@@ -33,14 +45,53 @@ pub trait TrackOperations {
     ) -> Result<BoxStream<TrackHierarchyEvent>>;
 
     #[sub]
-    async fn subscribe_track_view(&self, id: TrackViewId) -> Result<BoxStream<TrackViewEvent>>;
+    async fn subscribe_track_locked(&self, id: TrackId) -> Result<BoxStream<bool>>;
+
+    #[sub]
+    async fn subscribe_track_status(&self, id: TrackId) -> Result<BoxStream<TrackStatus>>;
+
+    #[sub]
+    async fn subscribe_track_mix(&self, id: TrackId) -> Result<BoxStream<TrackMix>>;
+
+    #[sub]
+    async fn subscribe_track_view(
+        &self,
+        id: TrackViewId,
+        filter: TrackViewFilter,
+    ) -> Result<BoxStream<TrackViewEvent>>;
 
     async fn get_track_name(&self, id: TrackId) -> Result<String>;
 
     async fn set_track_name(&self, id: TrackId, new_name: String) -> Result<()>;
 
+    async fn get_track_locked(&self, id: TrackId) -> Result<bool>;
+
+    async fn set_track_locked(&self, id: TrackId, locked: bool) -> Result<()>;
+
+    async fn get_track_status(&self, id: TrackId) -> Result<TrackStatus>;
+
+    async fn set_track_mute(&self, id: TrackId, muted: bool) -> Result<()>;
+
+    async fn set_track_solo(&self, id: TrackId, soloed: bool) -> Result<()>;
+
+    async fn get_track_mix(&self, id: TrackId) -> Result<TrackMix>;
+
+    /// Linear gain, 1.0 leaves the track as is.
+    async fn set_track_volume(&self, id: TrackId, volume: f32) -> Result<()>;
+
+    /// From -1.0 (left) to 1.0 (right).
+    async fn set_track_pan(&self, id: TrackId, pan: f32) -> Result<()>;
+
+    /// Returns tracks of the arrangement which can't be heard, see
+    /// [`TrackHierarchy::effectively_muted`].
+    #[role(ReadOnly)]
+    async fn get_muted_tracks(&self, arrangement_id: ArrangementId) -> Result<Vec<TrackId>>;
+
     async fn get_track_children(&self, id: TrackId) -> Result<Vec<TrackId>>;
 
+    /// Returns summaries in the same order as `ids`.
+    async fn get_tracks_summary(&self, ids: Vec<TrackId>) -> Result<Vec<TrackSummary>>;
+
     async fn get_track_hierarchy(&self, id: TrackId) -> Result<TrackHierarchy>;
 
     async fn append_track_child(&self, parent_id: TrackId, child_id: TrackId) -> Result<()>;
@@ -66,13 +117,19 @@ pub trait TrackOperations {
 
     async fn get_track_item(&self, track_id: TrackId, item_id: TrackItemId) -> Result<TrackItem>;
 
-    async fn remove_track_item(&self, track_id: TrackId, item_id: TrackItemId) -> Result<()>;
+    async fn remove_track_item(
+        &self,
+        track_id: TrackId,
+        item_id: TrackItemId,
+        force: bool,
+    ) -> Result<()>;
 
     async fn move_track_item(
         &self,
         track_id: TrackId,
         item_id: TrackItemId,
         new_start: Time,
+        force: bool,
     ) -> Result<()>;
 
     async fn resize_track_item(
@@ -80,6 +137,159 @@ pub trait TrackOperations {
         track_id: TrackId,
         item_id: TrackItemId,
         new_duration: Time,
+        force: bool,
+    ) -> Result<()>;
+
+    /// Moves an item along with the items after it, as a single edit.
+    async fn ripple_move_track_item(
+        &self,
+        view_id: TrackViewId,
+        item_id: TrackItemId,
+        new_start: Time,
+        mode: RippleMode,
+        force: bool,
+    ) -> Result<()>;
+
+    /// Resizes an item and shifts the items after its end by the change in duration.
+    async fn ripple_resize_track_item(
+        &self,
+        view_id: TrackViewId,
+        item_id: TrackItemId,
+        new_duration: Time,
+        mode: RippleMode,
+        force: bool,
+    ) -> Result<()>;
+
+    /// Moves every selected item by `delta` as a single edit, so either all of them move or
+    /// none. A beat delta keeps items positioned in beats on the same grid, and moves the other
+    /// items by the real time the beats take at their position. A real delta works the other way
+    /// around.
+    async fn nudge_track_items(
+        &self,
+        arrangement_id: ArrangementId,
+        selection: Vec<(TrackId, TrackItemId)>,
+        delta: Time,
+        force: bool,
+    ) -> Result<()>;
+
+    /// Moves every selected item as a single edit, so that dragging a selection takes one
+    /// request. Items are shifted by `delta` like [`TrackOperations::nudge_track_items`] does,
+    /// and by `track_delta` tracks of the arrangement, so the selection keeps its shape across
+    /// tracks. Either all of the items move or none. Items get new ids on other tracks, so the
+    /// new ids are returned in the order of the selection.
+    async fn move_track_items(
+        &self,
+        arrangement_id: ArrangementId,
+        selection: Vec<(TrackId, TrackItemId)>,
+        delta: Time,
+        track_delta: i32,
+        force: bool,
+    ) -> Result<Vec<(TrackId, TrackItemId)>>;
+
+    /// Removes every selected item as a single edit, so either all of them are removed or none.
+    async fn delete_track_items(
+        &self,
+        selection: Vec<(TrackId, TrackItemId)>,
+        force: bool,
+    ) -> Result<()>;
+
+    /// Copies the selected items to the clipboard, replacing whatever it held. Their audio and
+    /// MIDI items are copied too, so the clipboard stays valid after the originals change.
+    async fn copy_track_items(
+        &self,
+        arrangement_id: ArrangementId,
+        selection: Vec<(TrackId, TrackItemId)>,
+    ) -> Result<()>;
+
+    /// Adds the clipboard items to the arrangement. The earliest item starts at the target
+    /// offset, and the others keep their distance to it. Items from the topmost copied track go
+    /// to the target track, items from the other tracks go to the tracks just as far below it.
+    /// Returns the new items in the order they were copied.
+    async fn paste_track_items(
+        &self,
+        arrangement_id: ArrangementId,
+        target: PasteTarget,
+        force: bool,
+    ) -> Result<Vec<(TrackId, TrackItemId)>>;
+
+    /// Copies the selected items to the same tracks, right after the end of the selection. The
+    /// clipboard is left alone.
+    async fn duplicate_track_items(
+        &self,
+        arrangement_id: ArrangementId,
+        selection: Vec<(TrackId, TrackItemId)>,
+        force: bool,
+    ) -> Result<Vec<(TrackId, TrackItemId)>>;
+
+    /// Converts the start and duration of an item, so that it either follows tempo changes of
+    /// the arrangement or stays at its wall-clock position.
+    async fn set_track_item_time_base(
+        &self,
+        view_id: TrackViewId,
+        item_id: TrackItemId,
+        time_base: TimeBase,
+        force: bool,
+    ) -> Result<()>;
+
+    /// Converts every item of the track, see [`TrackOperations::set_track_item_time_base`].
+    async fn set_track_time_base(
+        &self,
+        view_id: TrackViewId,
+        time_base: TimeBase,
+        force: bool,
+    ) -> Result<()>;
+
+    /// Splits an item in two at `at`, which has to be inside the item. The item is shortened to
+    /// the part before `at`, and the rest becomes a new item, whose id is returned. For audio
+    /// items, the new item continues at the same position in the source, the gain envelope is
+    /// split between the parts, and the fades stay at the outer ends. Only audio items can be
+    /// split for now.
+    async fn split_track_item(
+        &self,
+        view_id: TrackViewId,
+        item_id: TrackItemId,
+        at: Time,
+        force: bool,
+    ) -> Result<TrackItemId>;
+
+    /// Moves the edges of an item to `new_start` and `new_end` as a single edit, keeping the
+    /// audio of audio items where it is on the timeline. The start of an audio item can't go
+    /// before the start of its source, and the start of a MIDI item can't be changed for now.
+    async fn trim_track_item(
+        &self,
+        view_id: TrackViewId,
+        item_id: TrackItemId,
+        new_start: Time,
+        new_end: Time,
+        force: bool,
+    ) -> Result<()>;
+
+    /// Splits an audio item at the transients of its source, as detected by
+    /// `detect_audio_item_transients`. The item is shortened to the first slice, every other
+    /// slice gets an item of its own. Returns the items of all slices in order, starting with
+    /// the original one.
+    async fn slice_item_at_transients(
+        &self,
+        view_id: TrackViewId,
+        item_id: TrackItemId,
+        sensitivity: f32,
+        force: bool,
+    ) -> Result<Vec<TrackItemId>>;
+
+    /// Lanes above [`MAX_TRACK_LANE`] aren't supported.
+    async fn move_track_item_to_lane(
+        &self,
+        track_id: TrackId,
+        item_id: TrackItemId,
+        new_lane: u32,
+        force: bool,
+    ) -> Result<()>;
+
+    async fn set_track_item_locked(
+        &self,
+        track_id: TrackId,
+        item_id: TrackItemId,
+        locked: bool,
     ) -> Result<()>;
 
     async fn get_track_view_item(
@@ -88,12 +298,59 @@ pub trait TrackOperations {
         item_id: TrackItemId,
     ) -> Result<TrackViewItem>;
 
+    async fn get_track_view_lane_count(&self, view_id: TrackViewId) -> Result<u32>;
+
     async fn get_track_view_range(
         &self,
         view_id: TrackViewId,
         start: Option<Time>,
         end: Option<Time>,
     ) -> Result<Vec<(TrackItemId, TrackViewItem)>>;
+
+    async fn get_track_nodes(&self, id: TrackId) -> Result<Vec<NodeId>>;
+
+    async fn insert_track_node(&self, id: TrackId, node_id: NodeId, index: usize) -> Result<()>;
+
+    async fn remove_track_node(&self, id: TrackId, index: usize) -> Result<()>;
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrackSummary {
+    pub id: TrackId,
+    pub name: String,
+    pub locked: bool,
+    pub children: Vec<TrackId>,
+    pub nodes: Vec<NodeId>,
+}
+
+/// Everything the track header shows a badge for.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TrackStatus {
+    /// Tracks can't be frozen yet, so this is always false.
+    pub frozen: bool,
+    pub locked: bool,
+    pub muted: bool,
+    pub soloed: bool,
+    /// Receives the recorded audio.
+    pub armed: bool,
+    /// Armed, and the input is currently being recorded.
+    pub monitoring: bool,
+}
+
+/// Applied to the output of a track, after its children and items are mixed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TrackMix {
+    pub volume: f32,
+    pub pan: f32,
+}
+
+impl Default for TrackMix {
+    fn default() -> TrackMix {
+        TrackMix {
+            volume: 1.0,
+            pan: 0.0,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
@@ -101,27 +358,91 @@ pub struct TrackItem {
     pub inner: ItemId,
     pub start: Time,
     pub duration: Time,
+    pub lane: u32,
+    pub locked: bool,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+/// Track item with its position precomputed in every time base, so that the engine and the UI
+/// agree on rounding.
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct TrackViewItem {
     pub inner: ItemId,
     pub start: Time,
     pub duration: Time,
     pub real_start: RealTime,
     pub real_end: RealTime,
+    pub beat_start: BeatTime,
+    pub beat_end: BeatTime,
+    /// Frames at the sample rate of the engine.
+    pub frame_start: i64,
+    pub frame_end: i64,
+    pub lane: u32,
+    pub locked: bool,
+    /// Offset, gain and fades of audio items, so that the timeline can draw them.
+    pub clip: Option<AudioClip>,
 }
 
 impl TrackViewItem {
     pub fn real_duration(&self) -> RealTime {
         self.real_end - self.real_start
     }
+
+    pub fn beat_duration(&self) -> BeatTime {
+        self.beat_end - self.beat_start
+    }
+
+    pub fn frame_duration(&self) -> i64 {
+        self.frame_end - self.frame_start
+    }
+
+    pub fn track_item(&self) -> TrackItem {
+        TrackItem {
+            inner: self.inner,
+            start: self.start,
+            duration: self.duration,
+            lane: self.lane,
+            locked: self.locked,
+        }
+    }
+}
+
+/// Which items follow an edited item in ripple edits.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum RippleMode {
+    #[default]
+    Off,
+    /// Items after the edited one on the same track.
+    Track,
+    /// Items after the edited one on every track of the arrangement.
+    AllTracks,
+}
+
+/// Where clipboard items go, see [`TrackOperations::paste_track_items`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PasteTarget {
+    pub track_id: TrackId,
+    pub offset: Time,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TrackViewFilter {
+    pub start: Option<Time>,
+    pub end: Option<Time>,
+}
+
+impl TrackViewFilter {
+    pub fn range(start: Time, end: Time) -> TrackViewFilter {
+        TrackViewFilter {
+            start: Some(start),
+            end: Some(end),
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct TrackHierarchy {
     root: TrackId,
-    children: HashMap<TrackId, Vec<TrackId>>,
+    children: HashMap<TrackId, ImVec<TrackId>>,
 }
 
 impl TrackHierarchy {
@@ -173,7 +494,78 @@ impl TrackHierarchy {
     }
 
     pub fn set_children(&mut self, id: TrackId, new_children: Vec<TrackId>) {
-        self.children.insert(id, new_children);
+        self.children.insert(id, new_children.into());
+    }
+
+    pub fn apply_event(&mut self, event: TrackHierarchyEvent) {
+        event.apply(self.children.entry(event.id()).or_default());
+    }
+
+    /// Returns tracks which can't be heard, given the status of every track.
+    ///
+    /// A track is silent if it or any of its ancestors is muted. If anything is soloed, tracks
+    /// which aren't soloed themselves, inside a soloed track, or on the way to one are silent too.
+    /// Tracks with several parents are heard if any of their occurrences is.
+    pub fn effectively_muted(
+        &self,
+        mut status: impl FnMut(TrackId) -> TrackStatus,
+    ) -> HashSet<TrackId> {
+        struct Occurrence {
+            id: TrackId,
+            parent: Option<usize>,
+            muted: bool,
+            in_solo: bool,
+            leads_to_solo: bool,
+        }
+
+        let mut occurrences = Vec::<Occurrence>::new();
+        let mut path = Vec::new();
+
+        self.dfs(self.root, |node| {
+            path.truncate(node.level);
+            let parent = path.last().copied();
+            let (parent_muted, parent_in_solo) = parent
+                .map(|i: usize| (occurrences[i].muted, occurrences[i].in_solo))
+                .unwrap_or_default();
+            let status = status(node.id);
+
+            occurrences.push(Occurrence {
+                id: node.id,
+                parent,
+                muted: status.muted || parent_muted,
+                in_solo: status.soloed || parent_in_solo,
+                leads_to_solo: status.soloed,
+            });
+
+            path.push(occurrences.len() - 1);
+        });
+
+        // children come after their parents, so this reaches the root from every soloed track
+        for i in (0..occurrences.len()).rev() {
+            if let Some(parent) = occurrences[i].parent {
+                if occurrences[i].leads_to_solo {
+                    occurrences[parent].leads_to_solo = true;
+                }
+            }
+        }
+
+        let any_soloed = occurrences.iter().any(|v| v.in_solo);
+        let mut heard = HashSet::default();
+        let mut muted = HashSet::default();
+
+        for occurrence in &occurrences {
+            let silent = occurrence.muted
+                || (any_soloed && !occurrence.in_solo && !occurrence.leads_to_solo);
+
+            if silent {
+                muted.insert(occurrence.id);
+            } else {
+                heard.insert(occurrence.id);
+            }
+        }
+
+        muted.retain(|id| !heard.contains(id));
+        muted
     }
 }
 
@@ -185,14 +577,63 @@ pub struct TrackNode {
     pub parent: Option<TrackId>,
 }
 
-#[derive(Debug, Clone, Eq, PartialEq)]
+/// A change to the direct children of track `id`.
+///
+/// Indices refer to the children list right before the change. Moves within a single parent
+/// remove the child at `from` first, then insert it at `to`.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
 pub enum TrackHierarchyEvent {
-    ChildrenChanged {
+    ChildInserted {
+        id: TrackId,
+        index: usize,
+        child: TrackId,
+    },
+    ChildRemoved {
+        id: TrackId,
+        index: usize,
+    },
+    ChildMoved {
         id: TrackId,
-        new_children: ImVec<TrackId>,
+        from: usize,
+        to: usize,
     },
 }
 
+impl TrackHierarchyEvent {
+    /// The track whose children changed.
+    pub fn id(&self) -> TrackId {
+        match *self {
+            TrackHierarchyEvent::ChildInserted { id, .. }
+            | TrackHierarchyEvent::ChildRemoved { id, .. }
+            | TrackHierarchyEvent::ChildMoved { id, .. } => id,
+        }
+    }
+
+    /// Applies the event to the children of [`TrackHierarchyEvent::id`].
+    ///
+    /// Events that don't fit the list (e.g. if it's out of date) are ignored.
+    pub fn apply(&self, children: &mut ImVec<TrackId>) {
+        match *self {
+            TrackHierarchyEvent::ChildInserted { index, child, .. } => {
+                if index <= children.len() {
+                    children.insert(index, child);
+                }
+            }
+            TrackHierarchyEvent::ChildRemoved { index, .. } => {
+                if index < children.len() {
+                    children.remove(index);
+                }
+            }
+            TrackHierarchyEvent::ChildMoved { from, to, .. } => {
+                if from < children.len() && to < children.len() {
+                    let child = children.remove(from);
+                    children.insert(to, child);
+                }
+            }
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum TrackViewEvent {
     ItemAdded {
@@ -212,4 +653,16 @@ pub enum TrackViewEvent {
         new_duration: Time,
         new_real_duration: RealTime,
     },
+    ItemLaneChanged {
+        id: TrackItemId,
+        new_lane: u32,
+    },
+    ItemLocked {
+        id: TrackItemId,
+        locked: bool,
+    },
+    ItemClipChanged {
+        id: TrackItemId,
+        clip: AudioClip,
+    },
 }
@@ -0,0 +1,58 @@
+use rdaw_rpc::transport::ClientTransport;
+use rdaw_rpc::Client;
+
+use crate::{BackendProtocol, Result};
+
+/// Groups several operations so that they either all apply, or none of them do.
+///
+/// While a transaction is open, events are held back and delivered in a single burst once it's
+/// committed. Document operations other than queries are rejected until then, and so are changes
+/// made by other clients. The transaction is rolled back if its client disconnects.
+#[rdaw_rpc::operations(protocol = BackendProtocol)]
+pub trait TransactionOperations {
+    async fn begin_transaction(&self) -> Result<()>;
+
+    async fn commit_transaction(&self) -> Result<()>;
+
+    /// Reverts every change made since the transaction began, discarding its events.
+    async fn rollback_transaction(&self) -> Result<()>;
+}
+
+/// Open transaction which is rolled back when dropped without being committed, e.g. when an
+/// error is returned early.
+pub struct Transaction<T: ClientTransport<BackendProtocol>> {
+    client: Client<BackendProtocol, T>,
+    is_done: bool,
+}
+
+impl<T: ClientTransport<BackendProtocol>> Transaction<T> {
+    pub async fn begin(client: &Client<BackendProtocol, T>) -> Result<Transaction<T>> {
+        client.begin_transaction().await?;
+
+        Ok(Transaction {
+            client: client.clone(),
+            is_done: false,
+        })
+    }
+
+    pub async fn commit(mut self) -> Result<()> {
+        let res = self.client.commit_transaction().await;
+        self.is_done = true;
+        res
+    }
+
+    pub async fn rollback(mut self) -> Result<()> {
+        let res = self.client.rollback_transaction().await;
+        self.is_done = true;
+        res
+    }
+}
+
+impl<T: ClientTransport<BackendProtocol>> Drop for Transaction<T> {
+    fn drop(&mut self) {
+        if !self.is_done {
+            let req = TransactionRequest::RollbackTransaction {};
+            self.client.request_detached(req.into());
+        }
+    }
+}
@@ -0,0 +1,76 @@
+use rdaw_core::time::RealTime;
+
+use crate::arrangement::ArrangementId;
+use crate::time::Time;
+use crate::{BackendProtocol, BoxStream, Result};
+
+#[rdaw_rpc::operations(protocol = BackendProtocol)]
+pub trait TransportOperations {
+    #[sub]
+    async fn subscribe_playhead(&self, id: ArrangementId) -> Result<BoxStream<PlayheadEvent>>;
+
+    async fn get_transport_state(&self, id: ArrangementId) -> Result<TransportState>;
+
+    async fn play(&self, id: ArrangementId) -> Result<()>;
+
+    async fn pause(&self, id: ArrangementId) -> Result<()>;
+
+    /// Stops playback and moves the playhead back to where playback was started from.
+    async fn stop(&self, id: ArrangementId) -> Result<()>;
+
+    async fn seek(&self, id: ArrangementId, position: Time) -> Result<()>;
+
+    /// Starts playback at `position`, stopping brings the playhead back there.
+    async fn play_from(&self, id: ArrangementId, position: Time) -> Result<()>;
+
+    /// Plays from `start` and stops at `end`, or when stopped earlier. The playhead then goes to
+    /// where `after` says.
+    async fn play_range(
+        &self,
+        id: ArrangementId,
+        start: Time,
+        end: Time,
+        after: RangeEnd,
+    ) -> Result<()>;
+
+    /// Once the playhead reaches the end of the loop region, it jumps back to its start. Playback
+    /// started after the region doesn't loop.
+    async fn set_loop_region(&self, id: ArrangementId, region: Option<LoopRegion>) -> Result<()>;
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum PlaybackState {
+    #[default]
+    Stopped,
+    Playing,
+    Paused,
+}
+
+/// Where the playhead goes after playing a range.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum RangeEnd {
+    /// Back to the start of the range.
+    #[default]
+    Start,
+    /// Back to where it was before the range started playing.
+    PreviousPosition,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TransportState {
+    pub playback: PlaybackState,
+    pub position: RealTime,
+    pub loop_region: Option<LoopRegion>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LoopRegion {
+    pub start: Time,
+    pub end: Time,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PlayheadEvent {
+    pub playback: PlaybackState,
+    pub position: RealTime,
+}
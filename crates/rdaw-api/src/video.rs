@@ -0,0 +1,106 @@
+use rdaw_core::time::RealTime;
+
+use crate::arrangement::ArrangementId;
+use crate::asset::AssetId;
+use crate::track::{TrackId, TrackItemId};
+use crate::{BackendProtocol, Result};
+
+#[rdaw_rpc::operations(protocol = BackendProtocol)]
+pub trait VideoOperations {
+    async fn attach_arrangement_video(
+        &self,
+        id: ArrangementId,
+        asset_id: AssetId,
+        offset: RealTime,
+    ) -> Result<VideoMetadata>;
+
+    async fn detach_arrangement_video(&self, id: ArrangementId) -> Result<()>;
+
+    async fn get_arrangement_video(&self, id: ArrangementId) -> Result<Option<ArrangementVideo>>;
+
+    async fn get_arrangement_video_frame(
+        &self,
+        id: ArrangementId,
+        position: RealTime,
+        max_width: u32,
+        max_height: u32,
+    ) -> Result<Option<VideoFrame>>;
+
+    async fn extract_arrangement_video_audio(
+        &self,
+        id: ArrangementId,
+        track_id: TrackId,
+    ) -> Result<TrackItemId>;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ArrangementVideo {
+    pub asset_id: AssetId,
+    pub offset: RealTime,
+    pub metadata: VideoMetadata,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VideoMetadata {
+    pub width: u32,
+    pub height: u32,
+    pub frame_rate: FrameRate,
+    pub duration: RealTime,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FrameRate {
+    pub num: u32,
+    pub den: u32,
+}
+
+impl FrameRate {
+    pub fn frame_duration(self) -> RealTime {
+        if self.num == 0 {
+            return RealTime::ZERO;
+        }
+
+        RealTime::from_nanos(i64::from(self.den) * 1_000_000_000 / i64::from(self.num))
+    }
+}
+
+/// Decoded video frame in RGBA8 format.
+#[derive(Clone, PartialEq, Eq)]
+pub struct VideoFrame {
+    pub position: RealTime,
+    pub width: u32,
+    pub height: u32,
+    pub data: Vec<u8>,
+}
+
+impl std::fmt::Debug for VideoFrame {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("VideoFrame")
+            .field("position", &self.position)
+            .field("width", &self.width)
+            .field("height", &self.height)
+            .finish_non_exhaustive()
+    }
+}
+
+pub trait VideoInputStream<'media> {
+    fn metadata(&self) -> &VideoMetadata;
+
+    fn seek(&mut self, position: RealTime) -> Result<()>;
+
+    fn next_frame(&mut self) -> Result<Option<VideoFrame>>;
+
+    fn frame_at(&mut self, position: RealTime) -> Result<Option<VideoFrame>> {
+        self.seek(position)?;
+
+        let frame_duration = self.metadata().frame_rate.frame_duration();
+
+        while let Some(frame) = self.next_frame()? {
+            if frame.position + frame_duration > position {
+                return Ok(Some(frame));
+            }
+        }
+
+        Ok(None)
+    }
+}
@@ -0,0 +1,86 @@
+use std::sync::Arc;
+
+use crate::source::AudioSourceId;
+use crate::track::{TrackItemId, TrackViewId};
+use crate::{BackendProtocol, BoxStream, Result};
+
+#[rdaw_rpc::operations(protocol = BackendProtocol)]
+pub trait WaveformOperations {
+    /// Returns waveforms of the audio items in the view, each at the coarsest level which has at
+    /// most `resolution` frames per peak. Items with peaks that aren't loaded are left out.
+    async fn get_waveform(
+        &self,
+        view_id: TrackViewId,
+        resolution: u32,
+    ) -> Result<Vec<ItemWaveform>>;
+
+    /// Loads or computes the peaks of the audio items in the view in the background, progress
+    /// subscribers of the view are told when they can be fetched. Peaks which failed before are
+    /// computed from the asset again. Computing stops if the client goes away, unless another
+    /// request is waiting for the same peaks.
+    async fn load_waveform(&self, view_id: TrackViewId) -> Result<()>;
+
+    #[sub]
+    async fn subscribe_waveform_progress(
+        &self,
+        view_id: TrackViewId,
+    ) -> Result<BoxStream<WaveformEvent>>;
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ItemWaveform {
+    pub item_id: TrackItemId,
+    pub source_id: AudioSourceId,
+    pub waveform: Waveform,
+}
+
+/// Peaks of a whole audio source at one resolution.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Waveform {
+    pub sample_rate: u32,
+    /// Number of source frames summarized by one peak.
+    pub frames_per_peak: u32,
+    pub num_channels: usize,
+    /// Peaks of all channels, interleaved.
+    pub peaks: Arc<[Peak]>,
+}
+
+impl Waveform {
+    pub fn num_peaks(&self) -> usize {
+        self.peaks.len() / self.num_channels.max(1)
+    }
+
+    pub fn channel_peak(&self, idx: usize, channel: usize) -> Option<Peak> {
+        self.peaks.get(idx * self.num_channels + channel).copied()
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Peak {
+    pub min: f32,
+    pub max: f32,
+}
+
+impl Peak {
+    pub fn merge(self, other: Peak) -> Peak {
+        Peak {
+            min: self.min.min(other.min),
+            max: self.max.max(other.max),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WaveformEvent {
+    /// Fraction of the source analyzed so far, from 0 to 1.
+    Progress {
+        source_id: AudioSourceId,
+        progress: f32,
+    },
+    Ready {
+        source_id: AudioSourceId,
+    },
+    Failed {
+        source_id: AudioSourceId,
+    },
+}
@@ -0,0 +1,55 @@
+use std::ops::Deref;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventKind {
+    NoteOn { key: u8, velocity: u8 },
+    NoteOff { key: u8 },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Event {
+    /// Offset from the start of the block, in frames.
+    pub frame: u32,
+    pub kind: EventKind,
+}
+
+/// Events of one block, sorted by frame. The capacity is fixed, so that pushing never allocates
+/// on the realtime thread.
+#[derive(Debug, Clone)]
+pub struct EventBuffer {
+    events: Vec<Event>,
+}
+
+impl EventBuffer {
+    pub fn new(capacity: usize) -> EventBuffer {
+        EventBuffer {
+            events: Vec::with_capacity(capacity),
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.events.capacity()
+    }
+
+    /// Returns `false` and drops the event if the buffer is full.
+    pub fn push(&mut self, event: Event) -> bool {
+        if self.events.len() == self.events.capacity() {
+            return false;
+        }
+
+        self.events.push(event);
+        true
+    }
+
+    pub fn clear(&mut self) {
+        self.events.clear();
+    }
+}
+
+impl Deref for EventBuffer {
+    type Target = [Event];
+
+    fn deref(&self) -> &Self::Target {
+        &self.events
+    }
+}
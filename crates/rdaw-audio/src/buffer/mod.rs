@@ -1,3 +1,5 @@
 mod audio;
+mod event;
 
 pub use self::audio::{AudioBuffer, SilentHint};
+pub use self::event::{Event, EventBuffer, EventKind};
@@ -0,0 +1,92 @@
+//! Conversion of float samples to integers of a lower bit depth.
+//!
+//! With TPDF dither the quantization error is noise of a constant power of a quarter of the least
+//! significant bit squared, independent of the signal, instead of distortion. Noise shaping feeds
+//! the error back through a second order filter, so that the noise at low frequencies is traded
+//! for more of it close to the Nyquist frequency.
+
+#[cfg(test)]
+mod tests;
+
+/// Fixed, so that rendering the same thing twice gives the same file.
+const SEED: u64 = 0x9e37_79b9_7f4a_7c15;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Dither {
+    /// Rounds to the nearest value.
+    Off,
+    Tpdf,
+    /// TPDF dither, the error being shaped by `(1 - z^-1)^2`.
+    ShapedTpdf,
+}
+
+#[derive(Debug, Clone)]
+pub struct Quantizer {
+    scale: f32,
+    min: i32,
+    max: i32,
+    dither: Dither,
+    rng: u64,
+    /// Last two errors of each channel, the most recent one first.
+    errors: Vec<[f32; 2]>,
+}
+
+impl Quantizer {
+    pub fn new(bits: u32, num_channels: usize, dither: Dither) -> Quantizer {
+        assert!((2..=32).contains(&bits), "can't quantize to {bits} bits");
+
+        let max = (1i64 << (bits - 1)) - 1;
+
+        Quantizer {
+            scale: (max + 1) as f32,
+            min: (-max - 1) as i32,
+            max: max as i32,
+            dither,
+            rng: SEED,
+            errors: vec![[0.0; 2]; num_channels],
+        }
+    }
+
+    /// Converts a sample from -1.0 to 1.0 to an integer, clipping it if it doesn't fit.
+    pub fn quantize(&mut self, channel: usize, sample: f32) -> i32 {
+        let value = sample * self.scale;
+
+        let value = match self.dither {
+            Dither::ShapedTpdf => {
+                let [e1, e2] = self.errors[channel];
+                value - (2.0 * e1 - e2)
+            }
+            _ => value,
+        };
+
+        let dither = match self.dither {
+            Dither::Off => 0.0,
+            Dither::Tpdf | Dither::ShapedTpdf => self.uniform() - self.uniform(),
+        };
+
+        let quantized = (value + dither).round();
+
+        // the error is taken before clipping, which would make the feedback loop blow up
+        let errors = &mut self.errors[channel];
+        *errors = [quantized - value, errors[0]];
+
+        (quantized as i64).clamp(self.min.into(), self.max.into()) as i32
+    }
+
+    pub fn quantize_all(&mut self, channels: &[Vec<f32>]) -> Vec<Vec<i32>> {
+        channels
+            .iter()
+            .enumerate()
+            .map(|(channel, samples)| samples.iter().map(|&v| self.quantize(channel, v)).collect())
+            .collect()
+    }
+
+    /// From 0 to 1, using xorshift64*.
+    fn uniform(&mut self) -> f32 {
+        self.rng ^= self.rng >> 12;
+        self.rng ^= self.rng << 25;
+        self.rng ^= self.rng >> 27;
+        let bits = self.rng.wrapping_mul(0x2545_f491_4f6c_dd1d) >> 40;
+        bits as f32 / (1u64 << 24) as f32
+    }
+}
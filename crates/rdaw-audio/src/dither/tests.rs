@@ -0,0 +1,74 @@
+use super::{Dither, Quantizer};
+
+const LEN: usize = 100_000;
+const LSB: f32 = 1.0 / 32768.0;
+
+/// Quantization errors in least significant bits.
+fn errors(dither: Dither, signal: impl Fn(usize) -> f32) -> Vec<f32> {
+    let mut quantizer = Quantizer::new(16, 1, dither);
+
+    (0..LEN)
+        .map(|i| {
+            let sample = signal(i);
+            quantizer.quantize(0, sample) as f32 - sample / LSB
+        })
+        .collect()
+}
+
+fn mean(values: &[f32]) -> f32 {
+    values.iter().sum::<f32>() / values.len() as f32
+}
+
+fn power(values: &[f32]) -> f32 {
+    values.iter().map(|v| v * v).sum::<f32>() / values.len() as f32
+}
+
+/// Power of the error below roughly a sixteenth of the sample rate.
+fn low_frequency_power(errors: &[f32]) -> f32 {
+    let averages = errors.chunks_exact(16).map(mean).collect::<Vec<_>>();
+    power(&averages)
+}
+
+#[test]
+fn rounding() {
+    let mut quantizer = Quantizer::new(16, 1, Dither::Off);
+    assert_eq!(quantizer.quantize(0, 0.5), 16384);
+    assert_eq!(quantizer.quantize(0, 2.4 * LSB), 2);
+    assert_eq!(quantizer.quantize(0, 1.0), 32767);
+    assert_eq!(quantizer.quantize(0, -1.0), -32768);
+    assert_eq!(quantizer.quantize(0, -3.0), -32768);
+
+    let mut quantizer = Quantizer::new(24, 1, Dither::Off);
+    assert_eq!(quantizer.quantize(0, 1.0), 8388607);
+}
+
+#[test]
+fn tpdf_noise_floor() {
+    // without dither the error depends on the signal, with it the error is always the same noise
+    for fraction in [0.0, 0.25, 0.5] {
+        let constant = (100.0 + fraction) * LSB;
+
+        let undithered = errors(Dither::Off, |_| constant);
+        assert!(undithered.iter().all(|&v| v == undithered[0]));
+
+        let dithered = errors(Dither::Tpdf, |_| constant);
+        assert!(mean(&dithered).abs() < 0.01, "{fraction}");
+        assert!((power(&dithered) - 0.25).abs() < 0.01, "{fraction}");
+    }
+}
+
+#[test]
+fn noise_shaping() {
+    let signal = |i: usize| 100.0 * LSB * (i as f32 * 0.01).sin();
+
+    let flat = errors(Dither::Tpdf, signal);
+    let shaped = errors(Dither::ShapedTpdf, signal);
+
+    // more noise overall, but much less of it at low frequencies
+    assert!(power(&shaped) > power(&flat));
+    assert!(low_frequency_power(&shaped) < low_frequency_power(&flat) / 2.0);
+
+    // clipping doesn't make the feedback run away
+    let clipped = errors(Dither::ShapedTpdf, |i| if i % 2 == 0 { 2.0 } else { -2.0 });
+    assert!(clipped.iter().all(|v| v.abs() < 32768.0 + 4.0));
+}
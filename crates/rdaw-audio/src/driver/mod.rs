@@ -0,0 +1,84 @@
+mod null;
+mod offline;
+#[cfg(test)]
+mod tests;
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use rdaw_api::audio::{AudioChannel, StreamInfo};
+use rdaw_core::sync::spsc;
+
+pub use self::null::{NullDriver, NullInStream, NullOutStream};
+pub use self::offline::{OfflineDriver, OfflineInStream, OfflineOutStream};
+
+pub trait Driver: Send + Sync + 'static {
+    type Error: Send + Sync + 'static;
+    type OutStream: OutStream;
+    type InStream: InStream;
+
+    fn create_out_stream(&self, desc: OutStreamDesc) -> Result<Self::OutStream, Self::Error>;
+
+    fn create_in_stream(&self, desc: InStreamDesc) -> Result<Self::InStream, Self::Error>;
+}
+
+pub struct OutStreamDesc {
+    pub name: String,
+    pub sample_rate: u32,
+    pub buffer_size: usize,
+    pub channels: Vec<AudioChannel>,
+    pub callback: Box<dyn FnMut(OutCallbackData<'_>) + Send + 'static>,
+}
+
+pub struct OutCallbackData<'a> {
+    pub num_channels: usize,
+    pub num_frames: usize,
+    pub samples: &'a mut [f32],
+}
+
+pub trait OutStream: Send + Sync + 'static {
+    type Error: Send + Sync + 'static;
+
+    fn is_active(&self) -> Result<bool, Self::Error>;
+
+    fn set_active(&self, active: bool) -> Result<(), Self::Error>;
+
+    /// Drivers may only know the real values once the stream has started running.
+    fn info(&self) -> Result<StreamInfo, Self::Error>;
+}
+
+pub struct InStreamDesc {
+    pub name: String,
+    pub sample_rate: u32,
+    pub buffer_size: usize,
+    pub channels: Vec<AudioChannel>,
+    /// Receives captured samples, interleaved. The capturing thread never waits for the
+    /// receiver, periods which don't fit into the ring are dropped.
+    pub sender: spsc::Sender<f32>,
+}
+
+pub trait InStream: Send + Sync + 'static {
+    type Error: Send + Sync + 'static;
+
+    fn is_active(&self) -> Result<bool, Self::Error>;
+
+    fn set_active(&self, active: bool) -> Result<(), Self::Error>;
+
+    /// Drivers may only know the real values once the stream has started running.
+    fn info(&self) -> Result<StreamInfo, Self::Error>;
+
+    /// Number of frames which were dropped because the ring was full.
+    fn num_dropped_frames(&self) -> Result<u64, Self::Error>;
+}
+
+/// Sends a captured period, counting its frames as dropped if it doesn't fit.
+pub fn send_captured(
+    sender: &mut spsc::Sender<f32>,
+    samples: &[f32],
+    num_channels: usize,
+    dropped_frames: &AtomicU64,
+) {
+    if sender.try_send_slice(samples).is_err() {
+        let num_frames = samples.len() / num_channels.max(1);
+        dropped_frames.fetch_add(num_frames as u64, Ordering::Relaxed);
+    }
+}
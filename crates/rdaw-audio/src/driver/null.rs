@@ -0,0 +1,207 @@
+use std::io;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use rdaw_api::audio::StreamInfo;
+
+use super::{
+    send_captured, Driver, InStream, InStreamDesc, OutCallbackData, OutStream, OutStreamDesc,
+};
+
+/// Driver for machines without an audio system. Streams are run from a timer at the rate a
+/// real device would consume them, and their output is thrown away. Input streams capture
+/// silence.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct NullDriver;
+
+impl NullDriver {
+    pub fn new() -> NullDriver {
+        NullDriver
+    }
+}
+
+impl Driver for NullDriver {
+    type Error = io::Error;
+    type OutStream = NullOutStream;
+    type InStream = NullInStream;
+
+    fn create_out_stream(&self, desc: OutStreamDesc) -> io::Result<NullOutStream> {
+        let info = stream_info(desc.sample_rate, desc.buffer_size)?;
+        let shared = Arc::new(Shared::default());
+
+        let thread = thread::Builder::new()
+            .name(format!("null-driver-{}", desc.name))
+            .spawn({
+                let shared = shared.clone();
+                move || run_out_stream(desc, &shared)
+            })?;
+
+        Ok(NullOutStream {
+            info,
+            shared,
+            thread: Some(thread),
+        })
+    }
+
+    fn create_in_stream(&self, desc: InStreamDesc) -> io::Result<NullInStream> {
+        let info = stream_info(desc.sample_rate, desc.buffer_size)?;
+        let shared = Arc::new(Shared::default());
+
+        let thread = thread::Builder::new()
+            .name(format!("null-driver-{}", desc.name))
+            .spawn({
+                let shared = shared.clone();
+                move || run_in_stream(desc, &shared)
+            })?;
+
+        Ok(NullInStream {
+            info,
+            shared,
+            thread: Some(thread),
+        })
+    }
+}
+
+fn stream_info(sample_rate: u32, buffer_size: usize) -> io::Result<StreamInfo> {
+    if sample_rate == 0 || buffer_size == 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "sample rate and buffer size must be positive",
+        ));
+    }
+
+    Ok(StreamInfo {
+        sample_rate,
+        quantum: buffer_size,
+        device_latency: 0,
+    })
+}
+
+fn run_out_stream(mut desc: OutStreamDesc, shared: &Shared) {
+    let num_channels = desc.channels.len();
+    let mut samples = vec![0.0; desc.buffer_size * num_channels];
+
+    run_timer(desc.sample_rate, desc.buffer_size, shared, || {
+        samples.fill(0.0);
+        (desc.callback)(OutCallbackData {
+            num_channels,
+            num_frames: desc.buffer_size,
+            samples: &mut samples,
+        });
+    });
+}
+
+fn run_in_stream(mut desc: InStreamDesc, shared: &Shared) {
+    let num_channels = desc.channels.len();
+    let samples = vec![0.0; desc.buffer_size * num_channels];
+
+    run_timer(desc.sample_rate, desc.buffer_size, shared, || {
+        send_captured(
+            &mut desc.sender,
+            &samples,
+            num_channels,
+            &shared.dropped_frames,
+        );
+    });
+}
+
+/// Calls `period_fn` once every period while the stream is active, until it's terminated.
+fn run_timer(sample_rate: u32, buffer_size: usize, shared: &Shared, mut period_fn: impl FnMut()) {
+    let period = Duration::from_secs_f64(buffer_size as f64 / f64::from(sample_rate));
+    let mut deadline = Instant::now();
+
+    while !shared.terminated.load(Ordering::Relaxed) {
+        if shared.active.load(Ordering::Relaxed) {
+            period_fn();
+        }
+
+        deadline += period;
+
+        let now = Instant::now();
+        if now > deadline + period {
+            // the callback is too slow, drop the missed periods instead of catching up
+            deadline = now;
+        }
+
+        thread::sleep(deadline.saturating_duration_since(now));
+    }
+}
+
+#[derive(Default)]
+struct Shared {
+    active: AtomicBool,
+    terminated: AtomicBool,
+    dropped_frames: AtomicU64,
+}
+
+pub struct NullOutStream {
+    info: StreamInfo,
+    shared: Arc<Shared>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl OutStream for NullOutStream {
+    type Error = io::Error;
+
+    fn is_active(&self) -> io::Result<bool> {
+        Ok(self.shared.active.load(Ordering::Relaxed))
+    }
+
+    fn set_active(&self, active: bool) -> io::Result<()> {
+        self.shared.active.store(active, Ordering::Relaxed);
+        Ok(())
+    }
+
+    fn info(&self) -> io::Result<StreamInfo> {
+        Ok(self.info)
+    }
+}
+
+impl Drop for NullOutStream {
+    fn drop(&mut self) {
+        self.shared.terminated.store(true, Ordering::Relaxed);
+
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+pub struct NullInStream {
+    info: StreamInfo,
+    shared: Arc<Shared>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl InStream for NullInStream {
+    type Error = io::Error;
+
+    fn is_active(&self) -> io::Result<bool> {
+        Ok(self.shared.active.load(Ordering::Relaxed))
+    }
+
+    fn set_active(&self, active: bool) -> io::Result<()> {
+        self.shared.active.store(active, Ordering::Relaxed);
+        Ok(())
+    }
+
+    fn info(&self) -> io::Result<StreamInfo> {
+        Ok(self.info)
+    }
+
+    fn num_dropped_frames(&self) -> io::Result<u64> {
+        Ok(self.shared.dropped_frames.load(Ordering::Relaxed))
+    }
+}
+
+impl Drop for NullInStream {
+    fn drop(&mut self) {
+        self.shared.terminated.store(true, Ordering::Relaxed);
+
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
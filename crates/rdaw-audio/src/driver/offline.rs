@@ -0,0 +1,187 @@
+use std::convert::Infallible;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use rdaw_api::audio::{AudioChannel, StreamInfo};
+use rdaw_core::sync::spsc;
+
+use super::{
+    send_captured, Driver, InStream, InStreamDesc, OutCallbackData, OutStream, OutStreamDesc,
+};
+
+/// Driver without an audio system behind it. Streams only run when they are pumped, so the
+/// output only depends on the callback and the number of rendered frames. Input streams capture
+/// whatever they are fed.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct OfflineDriver;
+
+impl OfflineDriver {
+    pub fn new() -> OfflineDriver {
+        OfflineDriver
+    }
+}
+
+impl Driver for OfflineDriver {
+    type Error = Infallible;
+    type OutStream = OfflineOutStream;
+    type InStream = OfflineInStream;
+
+    fn create_out_stream(&self, desc: OutStreamDesc) -> Result<OfflineOutStream, Infallible> {
+        Ok(OfflineOutStream {
+            sample_rate: desc.sample_rate,
+            buffer_size: desc.buffer_size,
+            channels: desc.channels,
+            callback: Mutex::new(desc.callback),
+            active: AtomicBool::new(false),
+            position: AtomicU64::new(0),
+        })
+    }
+
+    fn create_in_stream(&self, desc: InStreamDesc) -> Result<OfflineInStream, Infallible> {
+        Ok(OfflineInStream {
+            sample_rate: desc.sample_rate,
+            buffer_size: desc.buffer_size,
+            channels: desc.channels,
+            sender: Mutex::new(desc.sender),
+            active: AtomicBool::new(false),
+            dropped_frames: AtomicU64::new(0),
+        })
+    }
+}
+
+pub struct OfflineOutStream {
+    sample_rate: u32,
+    buffer_size: usize,
+    channels: Vec<AudioChannel>,
+    callback: Mutex<Box<dyn FnMut(OutCallbackData<'_>) + Send + 'static>>,
+    active: AtomicBool,
+    position: AtomicU64,
+}
+
+impl OfflineOutStream {
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    pub fn buffer_size(&self) -> usize {
+        self.buffer_size
+    }
+
+    pub fn channels(&self) -> &[AudioChannel] {
+        &self.channels
+    }
+
+    /// Number of frames rendered so far, including the ones rendered while warming up.
+    pub fn position(&self) -> u64 {
+        self.position.load(Ordering::Relaxed)
+    }
+
+    /// Runs the callback for `num_periods` periods of `buffer_size` frames and returns the
+    /// interleaved samples. An inactive stream renders silence without running the callback.
+    pub fn render(&self, num_periods: usize) -> Vec<f32> {
+        let num_channels = self.channels.len();
+        let period_len = self.buffer_size * num_channels;
+        let mut samples = vec![0.0; period_len * num_periods];
+
+        if !self.active.load(Ordering::Relaxed) {
+            return samples;
+        }
+
+        let mut callback = self.callback.lock().unwrap();
+
+        for period in samples.chunks_exact_mut(period_len.max(1)) {
+            callback(OutCallbackData {
+                num_channels,
+                num_frames: self.buffer_size,
+                samples: period,
+            });
+
+            self.position
+                .fetch_add(self.buffer_size as u64, Ordering::Relaxed);
+        }
+
+        samples
+    }
+
+    /// Runs the callback and throws the output away, e.g. to let nodes with internal state
+    /// settle before the output is compared.
+    pub fn warm_up(&self, num_periods: usize) {
+        self.render(num_periods);
+    }
+}
+
+impl OutStream for OfflineOutStream {
+    type Error = Infallible;
+
+    fn is_active(&self) -> Result<bool, Infallible> {
+        Ok(self.active.load(Ordering::Relaxed))
+    }
+
+    fn set_active(&self, active: bool) -> Result<(), Infallible> {
+        self.active.store(active, Ordering::Relaxed);
+        Ok(())
+    }
+
+    fn info(&self) -> Result<StreamInfo, Infallible> {
+        Ok(StreamInfo {
+            sample_rate: self.sample_rate,
+            quantum: self.buffer_size,
+            device_latency: 0,
+        })
+    }
+}
+
+pub struct OfflineInStream {
+    sample_rate: u32,
+    buffer_size: usize,
+    channels: Vec<AudioChannel>,
+    sender: Mutex<spsc::Sender<f32>>,
+    active: AtomicBool,
+    dropped_frames: AtomicU64,
+}
+
+impl OfflineInStream {
+    pub fn channels(&self) -> &[AudioChannel] {
+        &self.channels
+    }
+
+    /// Captures interleaved samples, in periods of `buffer_size` frames. An inactive stream
+    /// ignores them.
+    pub fn feed(&self, samples: &[f32]) {
+        if !self.active.load(Ordering::Relaxed) {
+            return;
+        }
+
+        let num_channels = self.channels.len();
+        let mut sender = self.sender.lock().unwrap();
+
+        for period in samples.chunks((self.buffer_size * num_channels).max(1)) {
+            send_captured(&mut sender, period, num_channels, &self.dropped_frames);
+        }
+    }
+}
+
+impl InStream for OfflineInStream {
+    type Error = Infallible;
+
+    fn is_active(&self) -> Result<bool, Infallible> {
+        Ok(self.active.load(Ordering::Relaxed))
+    }
+
+    fn set_active(&self, active: bool) -> Result<(), Infallible> {
+        self.active.store(active, Ordering::Relaxed);
+        Ok(())
+    }
+
+    fn info(&self) -> Result<StreamInfo, Infallible> {
+        Ok(StreamInfo {
+            sample_rate: self.sample_rate,
+            quantum: self.buffer_size,
+            device_latency: 0,
+        })
+    }
+
+    fn num_dropped_frames(&self) -> Result<u64, Infallible> {
+        Ok(self.dropped_frames.load(Ordering::Relaxed))
+    }
+}
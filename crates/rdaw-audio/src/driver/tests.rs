@@ -0,0 +1,684 @@
+use std::sync::{mpsc, Arc};
+use std::time::Duration;
+
+use rdaw_api::audio::AudioChannel;
+use rdaw_api::item::{AudioClip, Fade, FadeCurve, GainEnvelope, GainPoint};
+use rdaw_api::modulation::{EnvelopeFollower, Lfo, LfoShape, ModulationSource};
+use rdaw_api::track::TrackId;
+use rdaw_core::sync::spsc;
+use rdaw_core::time::RealTime;
+
+use super::{
+    Driver, InStream, InStreamDesc, NullDriver, OfflineDriver, OfflineOutStream, OutStream,
+    OutStreamDesc,
+};
+use crate::buffer::{Event, EventKind, SilentHint};
+use crate::graph::{CompiledNode, CycleError, Graph, GraphParams, Inputs, Node, Outputs, Port};
+use crate::nodes::{
+    pan_gains, GainEnvelopeNode, GainPanNode, GainPanParams, MixNode, ModulationValue,
+    ModulatorNode, SampleNode,
+};
+use crate::playhead::Playhead;
+
+const PARAMS: GraphParams = GraphParams {
+    sample_rate: 4,
+    buffer_size: 4,
+};
+
+/// Outputs the index of each frame.
+struct RampNode;
+
+impl Node for RampNode {
+    fn num_audio_inputs(&self) -> usize {
+        0
+    }
+
+    fn num_audio_outputs(&self) -> usize {
+        1
+    }
+
+    fn compile(&self, _params: &GraphParams) -> Box<dyn CompiledNode> {
+        Box::new(CompiledRampNode { position: 0 })
+    }
+}
+
+struct CompiledRampNode {
+    position: u64,
+}
+
+impl CompiledNode for CompiledRampNode {
+    fn process(&mut self, _params: &GraphParams, _inputs: Inputs<'_>, outputs: Outputs<'_>) {
+        let output = &mut *outputs.audio[0];
+        output.silent_hint = SilentHint::NotSilent;
+
+        for sample in output.iter_mut() {
+            *sample = self.position as f32;
+            self.position += 1;
+        }
+    }
+}
+
+/// Plays a note on the second frame of every block.
+struct NoteNode;
+
+impl Node for NoteNode {
+    fn num_audio_inputs(&self) -> usize {
+        0
+    }
+
+    fn num_audio_outputs(&self) -> usize {
+        0
+    }
+
+    fn num_event_outputs(&self) -> usize {
+        1
+    }
+
+    fn compile(&self, _params: &GraphParams) -> Box<dyn CompiledNode> {
+        Box::new(CompiledNoteNode)
+    }
+}
+
+struct CompiledNoteNode;
+
+impl CompiledNode for CompiledNoteNode {
+    fn process(&mut self, _params: &GraphParams, _inputs: Inputs<'_>, outputs: Outputs<'_>) {
+        outputs.events[0].push(Event {
+            frame: 1,
+            kind: EventKind::NoteOn {
+                key: 60,
+                velocity: 100,
+            },
+        });
+    }
+}
+
+/// Outputs velocities of received notes at their frames.
+struct VelocityNode;
+
+impl Node for VelocityNode {
+    fn num_audio_inputs(&self) -> usize {
+        0
+    }
+
+    fn num_audio_outputs(&self) -> usize {
+        1
+    }
+
+    fn num_event_inputs(&self) -> usize {
+        1
+    }
+
+    fn compile(&self, _params: &GraphParams) -> Box<dyn CompiledNode> {
+        Box::new(CompiledVelocityNode)
+    }
+}
+
+struct CompiledVelocityNode;
+
+impl CompiledNode for CompiledVelocityNode {
+    fn process(&mut self, _params: &GraphParams, inputs: Inputs<'_>, outputs: Outputs<'_>) {
+        let output = &mut *outputs.audio[0];
+        output.clear();
+
+        for event in inputs.events[0].iter() {
+            if let EventKind::NoteOn { velocity, .. } = event.kind {
+                output[event.frame as usize] = f32::from(velocity);
+                output.silent_hint = SilentHint::NotSilent;
+            }
+        }
+    }
+}
+
+fn ramp_stream() -> OfflineOutStream {
+    let mut graph = Graph::new(PARAMS);
+    let ramp = graph.add_node(RampNode);
+    let gain = graph.add_node(GainEnvelopeNode {
+        envelope: GainEnvelope {
+            points: vec![
+                GainPoint::new(RealTime::from_secs(1), 1.0),
+                GainPoint::new(RealTime::from_secs(1), 0.5),
+            ],
+        },
+        start: RealTime::ZERO,
+    });
+    graph
+        .connect((ramp, Port::Audio(0)), (gain, Port::Audio(0)))
+        .unwrap();
+
+    let mut compiled = graph.compile();
+
+    let stream = OfflineDriver::new()
+        .create_out_stream(OutStreamDesc {
+            name: "test".into(),
+            sample_rate: PARAMS.sample_rate,
+            buffer_size: PARAMS.buffer_size,
+            channels: vec![AudioChannel::FrontLeft, AudioChannel::FrontRight],
+            callback: Box::new(move |data| {
+                compiled.process();
+                let output = compiled.audio_output(gain, 0).unwrap();
+
+                for (frame, &sample) in data.samples.chunks_exact_mut(2).zip(output.iter()) {
+                    frame.fill(sample);
+                }
+            }),
+        })
+        .unwrap();
+
+    stream.set_active(true).unwrap();
+    stream
+}
+
+#[test]
+fn offline_render() {
+    let stream = ramp_stream();
+
+    assert_eq!(
+        stream.render(2),
+        [
+            0.0, 0.0, 1.0, 1.0, 2.0, 2.0, 3.0, 3.0, //
+            2.0, 2.0, 2.5, 2.5, 3.0, 3.0, 3.5, 3.5,
+        ]
+    );
+    assert_eq!(stream.position(), 8);
+
+    stream.set_active(false).unwrap();
+    assert_eq!(stream.render(1), [0.0; 8]);
+    assert_eq!(stream.position(), 8);
+}
+
+#[test]
+fn offline_render_is_deterministic() {
+    let warm = ramp_stream();
+    warm.warm_up(3);
+
+    let cold = ramp_stream();
+    cold.render(3);
+
+    assert_eq!(warm.render(5), cold.render(5));
+    assert_eq!(warm.position(), cold.position());
+}
+
+#[test]
+fn sample_playback() {
+    let playhead = Playhead::new();
+
+    let mut graph = Graph::new(PARAMS);
+    let first = graph.add_node(SampleNode {
+        samples: Arc::new([1.0, 2.0, 3.0, 4.0, 5.0, 6.0]),
+        sample_rate: PARAMS.sample_rate,
+        start: 2,
+        duration: 4,
+        loop_length: None,
+        clip: AudioClip::default(),
+        playhead: playhead.clone(),
+    });
+    let second = graph.add_node(SampleNode {
+        samples: Arc::new([10.0; 2]),
+        sample_rate: PARAMS.sample_rate,
+        start: 0,
+        duration: 2,
+        loop_length: None,
+        clip: AudioClip::default(),
+        playhead: playhead.clone(),
+    });
+    let mix = graph.add_node(MixNode { num_inputs: 2 });
+    graph
+        .connect((first, Port::Audio(0)), (mix, Port::Audio(0)))
+        .unwrap();
+    graph
+        .connect((second, Port::Audio(0)), (mix, Port::Audio(1)))
+        .unwrap();
+
+    let mut compiled = graph.compile();
+    let mut render = || {
+        compiled.process();
+        let output = compiled.audio_output(mix, 0).unwrap().to_vec();
+        playhead.advance(output.len());
+        output
+    };
+
+    assert_eq!(render(), [10.0, 10.0, 1.0, 2.0]);
+    assert_eq!(render(), [3.0, 4.0, 0.0, 0.0]);
+
+    playhead.seek(0);
+    playhead.set_loop(Some((2, 4)));
+    render();
+    assert_eq!(playhead.frame(), 2);
+
+    // removing a node disconnects it
+    graph.remove_node(second);
+    let mut compiled = graph.compile();
+    compiled.process();
+    assert_eq!(
+        compiled.audio_output(mix, 0).unwrap()[..],
+        [1.0, 2.0, 3.0, 4.0]
+    );
+}
+
+#[test]
+fn sample_clip() {
+    let playhead = Playhead::new();
+
+    let mut graph = Graph::new(PARAMS);
+    let node = graph.add_node(SampleNode {
+        samples: Arc::new([1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0]),
+        sample_rate: PARAMS.sample_rate,
+        start: 0,
+        duration: 4,
+        loop_length: None,
+        clip: AudioClip {
+            // two frames at the sample rate of 4
+            source_offset: RealTime::from_secs_f64(0.5),
+            gain: 2.0,
+            fade_in: Fade {
+                duration: RealTime::from_secs_f64(0.5),
+                curve: FadeCurve::Linear,
+            },
+            fade_out: Fade::default(),
+        },
+        playhead,
+    });
+
+    let mut compiled = graph.compile();
+    compiled.process();
+    assert_eq!(
+        compiled.audio_output(node, 0).unwrap()[..],
+        [0.0, 4.0, 10.0, 12.0]
+    );
+}
+
+#[test]
+fn sample_loop() {
+    let playhead = Playhead::new();
+
+    let mut graph = Graph::new(PARAMS);
+    let node = graph.add_node(SampleNode {
+        samples: Arc::new([1.0, 2.0, 3.0, 4.0]),
+        sample_rate: PARAMS.sample_rate,
+        start: 1,
+        duration: 6,
+        loop_length: Some(3),
+        clip: AudioClip::default(),
+        playhead: playhead.clone(),
+    });
+
+    let mut compiled = graph.compile();
+    let mut render = || {
+        compiled.process();
+        let output = compiled.audio_output(node, 0).unwrap().to_vec();
+        playhead.advance(output.len());
+        output
+    };
+
+    assert_eq!(render(), [0.0, 1.0, 2.0, 3.0]);
+    assert_eq!(render(), [1.0, 2.0, 3.0, 0.0]);
+}
+
+/// Panics on the second period.
+struct PanicNode;
+
+impl Node for PanicNode {
+    fn num_audio_inputs(&self) -> usize {
+        0
+    }
+
+    fn num_audio_outputs(&self) -> usize {
+        1
+    }
+
+    fn compile(&self, _params: &GraphParams) -> Box<dyn CompiledNode> {
+        Box::new(CompiledPanicNode { num_periods: 0 })
+    }
+}
+
+struct CompiledPanicNode {
+    num_periods: usize,
+}
+
+impl CompiledNode for CompiledPanicNode {
+    fn process(&mut self, _params: &GraphParams, _inputs: Inputs<'_>, outputs: Outputs<'_>) {
+        let output = &mut *outputs.audio[0];
+        output.silent_hint = SilentHint::NotSilent;
+        output.fill(100.0);
+
+        self.num_periods += 1;
+        if self.num_periods == 2 {
+            panic!("node failed");
+        }
+    }
+}
+
+#[test]
+fn panicking_node() {
+    let mut graph = Graph::new(PARAMS);
+    let ramp = graph.add_node(RampNode);
+    let panic = graph.add_node(PanicNode);
+    let mix = graph.add_node(MixNode { num_inputs: 2 });
+    graph
+        .connect((ramp, Port::Audio(0)), (mix, Port::Audio(0)))
+        .unwrap();
+    graph
+        .connect((panic, Port::Audio(0)), (mix, Port::Audio(1)))
+        .unwrap();
+
+    let mut compiled = graph.compile();
+    let mut render = || {
+        compiled.process();
+        compiled.audio_output(mix, 0).unwrap().to_vec()
+    };
+
+    assert_eq!(render(), [100.0, 101.0, 102.0, 103.0]);
+    assert_eq!(render(), [4.0, 5.0, 6.0, 7.0]);
+    assert_eq!(render(), [8.0, 9.0, 10.0, 11.0]);
+    assert_eq!(compiled.disabled_nodes().collect::<Vec<_>>(), [panic]);
+
+    let mut compiled = graph.compile();
+    compiled.disable_node(ramp);
+    compiled.process();
+    assert_eq!(compiled.audio_output(mix, 0).unwrap()[..], [100.0; 4]);
+}
+
+#[test]
+fn gain_pan() {
+    assert_eq!(pan_gains(1.0, 0.0), [1.0, 1.0]);
+    assert_eq!(pan_gains(0.5, -1.0), [0.5, 0.0]);
+    assert_eq!(pan_gains(1.0, 2.0), [0.0, 1.0]);
+
+    let params = GainPanParams::new(0.5, 0.5);
+    assert_eq!(params.get(), (0.5, 0.5));
+
+    let mut graph = Graph::new(PARAMS);
+    let ramp = graph.add_node(RampNode);
+    let gain_pan = graph.add_node(GainPanNode {
+        params: params.clone(),
+    });
+    graph
+        .connect((ramp, Port::Audio(0)), (gain_pan, Port::Audio(0)))
+        .unwrap();
+    graph
+        .connect((ramp, Port::Audio(0)), (gain_pan, Port::Audio(1)))
+        .unwrap();
+
+    let mut compiled = graph.compile();
+    let mut render = || {
+        compiled.process();
+        [0, 1].map(|port| compiled.audio_output(gain_pan, port).unwrap().to_vec())
+    };
+
+    assert_eq!(
+        render(),
+        [vec![0.0, 0.25, 0.5, 0.75], vec![0.0, 0.5, 1.0, 1.5]]
+    );
+
+    // changes are ramped over the next block
+    params.set(1.0, 0.0);
+    assert_eq!(
+        render(),
+        [vec![1.75, 3.125, 4.875, 7.0], vec![2.5, 3.75, 5.25, 7.0]]
+    );
+    assert_eq!(
+        render(),
+        [vec![8.0, 9.0, 10.0, 11.0], vec![8.0, 9.0, 10.0, 11.0]]
+    );
+}
+
+#[test]
+fn modulators() {
+    let playhead = Playhead::new();
+    let lfo_value = ModulationValue::new();
+    let follower_value = ModulationValue::new();
+
+    let mut graph = Graph::new(PARAMS);
+    graph.add_node(ModulatorNode {
+        source: ModulationSource::Lfo(Lfo {
+            shape: LfoShape::Saw,
+            rate: 0.5,
+            phase: 0.25,
+        }),
+        playhead: playhead.clone(),
+        value: lfo_value.clone(),
+    });
+
+    let ramp = graph.add_node(RampNode);
+    let follower = graph.add_node(ModulatorNode {
+        source: ModulationSource::EnvelopeFollower(EnvelopeFollower {
+            track_id: TrackId::default(),
+            attack: RealTime::ZERO,
+            release: RealTime::from_secs(1),
+        }),
+        playhead: playhead.clone(),
+        value: follower_value.clone(),
+    });
+    graph
+        .connect((ramp, Port::Audio(0)), (follower, Port::Audio(0)))
+        .unwrap();
+
+    let mut compiled = graph.compile();
+    compiled.process();
+    assert_eq!(lfo_value.get(), 0.25);
+    assert_eq!(follower_value.get(), 1.0);
+
+    // the lfo is locked to the playhead
+    playhead.seek(4);
+    compiled.process();
+    assert_eq!(lfo_value.get(), 0.75);
+}
+
+#[test]
+fn cycles() {
+    let mut graph = Graph::new(PARAMS);
+    let first = graph.add_node(MixNode { num_inputs: 1 });
+    let second = graph.add_node(MixNode { num_inputs: 1 });
+    let third = graph.add_node(MixNode { num_inputs: 1 });
+
+    graph
+        .connect((first, Port::Audio(0)), (second, Port::Audio(0)))
+        .unwrap();
+    graph
+        .connect((second, Port::Audio(0)), (third, Port::Audio(0)))
+        .unwrap();
+
+    let res = graph.connect((third, Port::Audio(0)), (first, Port::Audio(0)));
+    assert_eq!(
+        res,
+        Err(CycleError {
+            src: third,
+            dst: first,
+        })
+    );
+
+    let res = graph.connect((first, Port::Audio(0)), (first, Port::Audio(0)));
+    assert!(res.is_err());
+
+    // the rejected connections are left out
+    let mut compiled = graph.compile();
+    compiled.process();
+    assert!(compiled.audio_output(third, 0).is_some());
+}
+
+#[test]
+fn disconnect() {
+    let mut graph = Graph::new(PARAMS);
+    let ramp = graph.add_node(RampNode);
+    let mix = graph.add_node(MixNode { num_inputs: 2 });
+    let out = graph.add_node(MixNode { num_inputs: 1 });
+
+    graph
+        .connect((ramp, Port::Audio(0)), (mix, Port::Audio(0)))
+        .unwrap();
+    graph
+        .connect((ramp, Port::Audio(0)), (mix, Port::Audio(1)))
+        .unwrap();
+    graph
+        .connect((mix, Port::Audio(0)), (out, Port::Audio(0)))
+        .unwrap();
+
+    let render = |graph: &Graph| {
+        let mut compiled = graph.compile();
+        compiled.process();
+        compiled.audio_output(out, 0).unwrap().to_vec()
+    };
+
+    assert_eq!(render(&graph), [0.0, 2.0, 4.0, 6.0]);
+
+    // the other port still depends on the ramp
+    graph.disconnect((ramp, Port::Audio(0)), (mix, Port::Audio(1)));
+    assert_eq!(render(&graph), [0.0, 1.0, 2.0, 3.0]);
+    assert!(graph
+        .connect((out, Port::Audio(0)), (mix, Port::Audio(1)))
+        .is_err());
+
+    graph.disconnect_all(mix);
+    assert_eq!(render(&graph), [0.0; 4]);
+
+    // the output doesn't depend on the mix anymore
+    graph
+        .connect((out, Port::Audio(0)), (mix, Port::Audio(1)))
+        .unwrap();
+}
+
+#[test]
+fn event_ports() {
+    let mut graph = Graph::new(PARAMS);
+    let notes = graph.add_node(NoteNode);
+    let velocity = graph.add_node(VelocityNode);
+
+    let mut compiled = graph.compile();
+    compiled.process();
+    assert_eq!(
+        compiled.audio_output(velocity, 0).unwrap().to_vec(),
+        [0.0; 4]
+    );
+
+    graph
+        .connect((notes, Port::Event(0)), (velocity, Port::Event(0)))
+        .unwrap();
+
+    // event outputs are cleared every block, so notes don't pile up
+    let mut compiled = graph.compile();
+    for _ in 0..2 {
+        compiled.process();
+        assert_eq!(compiled.event_output(notes, 0).unwrap().len(), 1);
+        assert_eq!(
+            compiled.audio_output(velocity, 0).unwrap().to_vec(),
+            [0.0, 100.0, 0.0, 0.0]
+        );
+    }
+
+    graph.disconnect((notes, Port::Event(0)), (velocity, Port::Event(0)));
+    let mut compiled = graph.compile();
+    compiled.process();
+    assert_eq!(
+        compiled.audio_output(velocity, 0).unwrap().to_vec(),
+        [0.0; 4]
+    );
+}
+
+#[test]
+#[should_panic]
+fn mismatched_ports() {
+    let mut graph = Graph::new(PARAMS);
+    let notes = graph.add_node(NoteNode);
+    let mix = graph.add_node(MixNode { num_inputs: 1 });
+    let _ = graph.connect((notes, Port::Event(0)), (mix, Port::Audio(0)));
+}
+
+#[test]
+fn null_driver() {
+    let (sender, receiver) = mpsc::channel();
+
+    let stream = NullDriver::new()
+        .create_out_stream(OutStreamDesc {
+            name: "test".into(),
+            sample_rate: 48000,
+            buffer_size: 64,
+            channels: vec![AudioChannel::Mono],
+            callback: Box::new(move |data| {
+                let _ = sender.send((data.num_frames, data.samples.len()));
+            }),
+        })
+        .unwrap();
+
+    let info = stream.info().unwrap();
+    assert_eq!(info.sample_rate, 48000);
+    assert_eq!(info.latency_frames(), 64);
+    assert!(info.latency().approx_eq(
+        RealTime::from_secs_f64(64.0 / 48000.0),
+        RealTime::from_nanos(1)
+    ));
+
+    assert!(!stream.is_active().unwrap());
+    stream.set_active(true).unwrap();
+
+    for _ in 0..2 {
+        let received = receiver.recv_timeout(Duration::from_secs(5));
+        assert_eq!(received, Ok((64, 64)));
+    }
+
+    drop(stream);
+
+    // the timer thread is joined on drop, so the callback is gone too
+    while receiver.try_recv().is_ok() {}
+    assert_eq!(
+        receiver.recv_timeout(Duration::from_millis(10)),
+        Err(mpsc::RecvTimeoutError::Disconnected)
+    );
+}
+
+#[test]
+fn offline_in_stream() {
+    let (sender, mut receiver) = spsc::channel(8);
+
+    let stream = OfflineDriver::new()
+        .create_in_stream(InStreamDesc {
+            name: "test".into(),
+            sample_rate: PARAMS.sample_rate,
+            buffer_size: 2,
+            channels: vec![AudioChannel::FrontLeft, AudioChannel::FrontRight],
+            sender,
+        })
+        .unwrap();
+
+    stream.feed(&[1.0; 4]);
+    assert!(receiver.try_recv().is_err());
+
+    stream.set_active(true).unwrap();
+    stream.feed(&[0.0, 1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0]);
+
+    let mut samples = [0.0; 8];
+    receiver.try_recv_slice(&mut samples).unwrap();
+    assert_eq!(samples, [0.0, 1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0]);
+
+    // the last two periods don't fit into the ring
+    stream.feed(&[1.0; 16]);
+    assert_eq!(stream.num_dropped_frames().unwrap(), 4);
+}
+
+#[test]
+fn null_in_stream() {
+    let (sender, mut receiver) = spsc::channel(1024);
+
+    let stream = NullDriver::new()
+        .create_in_stream(InStreamDesc {
+            name: "test".into(),
+            sample_rate: 48000,
+            buffer_size: 64,
+            channels: vec![AudioChannel::Mono],
+            sender,
+        })
+        .unwrap();
+
+    assert_eq!(stream.info().unwrap().quantum, 64);
+    stream.set_active(true).unwrap();
+
+    for _ in 0..2 {
+        assert_eq!(receiver.recv_timeout(Duration::from_secs(5)), Ok(0.0));
+    }
+
+    drop(stream);
+
+    // the sender is dropped along with the timer thread
+    while receiver.try_recv().is_ok() {}
+    assert!(receiver.recv_timeout(Duration::from_millis(10)).is_err());
+}
@@ -1,14 +1,19 @@
 use std::cell::UnsafeCell;
 use std::collections::VecDeque;
+use std::mem;
+use std::panic::{self, AssertUnwindSafe};
 
 use bumpalo::Bump;
 use rdaw_core::collections::{HashMap, HashSet};
 use slotmap::SlotMap;
 use smallvec::SmallVec;
 
-use crate::buffer::AudioBuffer;
+use crate::buffer::{AudioBuffer, EventBuffer};
 
-#[derive(Debug, Clone, Copy)]
+/// How many events fit into an event buffer during one block.
+pub const MAX_EVENTS_PER_BLOCK: usize = 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct GraphParams {
     pub sample_rate: u32,
     pub buffer_size: usize,
@@ -23,6 +28,14 @@ pub trait Node: Send + Sync + 'static {
 
     fn num_audio_outputs(&self) -> usize;
 
+    fn num_event_inputs(&self) -> usize {
+        0
+    }
+
+    fn num_event_outputs(&self) -> usize {
+        0
+    }
+
     fn compile(&self, params: &GraphParams) -> Box<dyn CompiledNode>;
 }
 
@@ -33,24 +46,89 @@ pub trait CompiledNode: Send + 'static {
 #[derive(Debug)]
 pub struct Inputs<'a> {
     pub audio: &'a [&'a AudioBuffer],
+    pub events: &'a [&'a EventBuffer],
 }
 
 #[derive(Debug)]
 pub struct Outputs<'a> {
     pub audio: &'a mut [&'a mut AudioBuffer],
+    /// Cleared before the node is processed.
+    pub events: &'a mut [&'a mut EventBuffer],
 }
 
 #[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash)]
 pub enum Port {
     Audio(usize),
+    Event(usize),
+}
+
+impl Port {
+    fn index(self) -> usize {
+        match self {
+            Port::Audio(idx) | Port::Event(idx) => idx,
+        }
+    }
+
+    fn with_index(self, idx: usize) -> Port {
+        match self {
+            Port::Audio(_) => Port::Audio(idx),
+            Port::Event(_) => Port::Event(idx),
+        }
+    }
+
+    fn is_same_kind(self, other: Port) -> bool {
+        matches!(
+            (self, other),
+            (Port::Audio(_), Port::Audio(_)) | (Port::Event(_), Port::Event(_))
+        )
+    }
+}
+
+/// Returned by [`Graph::connect`] when the destination already feeds into the source.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, thiserror::Error)]
+#[error("connecting {src:?} to {dst:?} would create a cycle")]
+pub struct CycleError {
+    pub src: NodeId,
+    pub dst: NodeId,
+}
+
+/// Connections of ports of one kind.
+struct Ports {
+    inputs: Vec<Option<(NodeId, usize)>>,
+    outputs: Vec<Vec<(NodeId, usize)>>,
+}
+
+impl Ports {
+    fn new(num_inputs: usize, num_outputs: usize) -> Ports {
+        Ports {
+            inputs: vec![None; num_inputs],
+            outputs: vec![vec![]; num_outputs],
+        }
+    }
 }
 
 struct NodeEntry {
     node: Box<dyn Node>,
     deps: HashSet<NodeId>,
     rev_deps: HashSet<NodeId>,
-    audio_inputs: Vec<Option<(NodeId, usize)>>,
-    audio_outputs: Vec<Vec<(NodeId, usize)>>,
+    audio: Ports,
+    events: Ports,
+}
+
+impl NodeEntry {
+    fn ports(&self, port: Port) -> &Ports {
+        match port {
+            Port::Audio(_) => &self.audio,
+            Port::Event(_) => &self.events,
+        }
+    }
+
+    fn ports_mut(&mut self, port: Port) -> &mut Ports {
+        match port {
+            Port::Audio(_) => &mut self.audio,
+            Port::Event(_) => &mut self.events,
+        }
+    }
 }
 
 pub struct Graph {
@@ -92,13 +170,30 @@ impl Graph {
             }
         }
 
-        if order.len() != self.nodes.len() {
-            panic!("cycle detected"); // TODO
-        }
+        // `connect` never lets a cycle in
+        debug_assert_eq!(order.len(), self.nodes.len());
 
         order
     }
 
+    /// Whether `to` can be reached from `from` by following connections downstream.
+    fn is_reachable(&self, from: NodeId, to: NodeId) -> bool {
+        let mut visited = HashSet::default();
+        let mut stack = vec![from];
+
+        while let Some(node) = stack.pop() {
+            if node == to {
+                return true;
+            }
+
+            if visited.insert(node) {
+                stack.extend(self.nodes[node].rev_deps.iter().copied());
+            }
+        }
+
+        false
+    }
+
     pub fn set_params(&mut self, params: GraphParams) {
         self.params = params;
     }
@@ -107,8 +202,8 @@ impl Graph {
         self.nodes.insert(NodeEntry {
             deps: HashSet::default(),
             rev_deps: HashSet::default(),
-            audio_inputs: vec![None; node.num_audio_inputs()],
-            audio_outputs: vec![vec![]; node.num_audio_outputs()],
+            audio: Ports::new(node.num_audio_inputs(), node.num_audio_outputs()),
+            events: Ports::new(node.num_event_inputs(), node.num_event_outputs()),
 
             node: Box::new(node),
         })
@@ -119,89 +214,267 @@ impl Graph {
     }
 
     pub fn remove_node(&mut self, id: NodeId) {
-        self.nodes.remove(id);
+        let Some(entry) = self.nodes.remove(id) else {
+            return;
+        };
+
+        for dep in entry.deps {
+            if let Some(dep) = self.nodes.get_mut(dep) {
+                dep.rev_deps.remove(&id);
+                for dsts in dep.audio.outputs.iter_mut().chain(&mut dep.events.outputs) {
+                    dsts.retain(|&(node, _)| node != id);
+                }
+            }
+        }
+
+        for rev_dep in entry.rev_deps {
+            if let Some(rev_dep) = self.nodes.get_mut(rev_dep) {
+                rev_dep.deps.remove(&id);
+                for src in rev_dep
+                    .audio
+                    .inputs
+                    .iter_mut()
+                    .chain(&mut rev_dep.events.inputs)
+                {
+                    if src.is_some_and(|(node, _)| node == id) {
+                        *src = None;
+                    }
+                }
+            }
+        }
     }
 
     pub fn connect(
         &mut self,
         (src_node, src_port): (NodeId, Port),
         (dst_node, dst_port): (NodeId, Port),
-    ) {
-        match (src_port, dst_port) {
-            (Port::Audio(src_port), Port::Audio(dst_port)) => {
-                self.nodes[src_node].audio_outputs[src_port].push((dst_node, dst_port));
-                self.nodes[dst_node].audio_inputs[dst_port] = Some((src_node, src_port))
-            }
+    ) -> Result<(), CycleError> {
+        if self.is_reachable(dst_node, src_node) {
+            return Err(CycleError {
+                src: src_node,
+                dst: dst_node,
+            });
+        }
+
+        if !src_port.is_same_kind(dst_port) {
+            panic!("{src_port:?} can't be connected to {dst_port:?}");
+        }
+
+        // an input has at most one source, the old one is replaced
+        let dst_idx = dst_port.index();
+        if let Some((old_node, old_idx)) = self.nodes[dst_node].ports(dst_port).inputs[dst_idx] {
+            self.disconnect(
+                (old_node, src_port.with_index(old_idx)),
+                (dst_node, dst_port),
+            );
         }
 
+        let src_idx = src_port.index();
+        self.nodes[src_node].ports_mut(src_port).outputs[src_idx].push((dst_node, dst_idx));
+        self.nodes[dst_node].ports_mut(dst_port).inputs[dst_idx] = Some((src_node, src_idx));
+
         self.nodes[dst_node].deps.insert(src_node);
         self.nodes[src_node].rev_deps.insert(dst_node);
+
+        Ok(())
+    }
+
+    /// Removes a connection made with [`Graph::connect`]. Does nothing if there's no such
+    /// connection.
+    pub fn disconnect(
+        &mut self,
+        (src_node, src_port): (NodeId, Port),
+        (dst_node, dst_port): (NodeId, Port),
+    ) {
+        let (src_idx, dst_idx) = (src_port.index(), dst_port.index());
+
+        let Some(dst) = self.nodes.get_mut(dst_node) else {
+            return;
+        };
+
+        let Some(input) = dst.ports_mut(dst_port).inputs.get_mut(dst_idx) else {
+            return;
+        };
+
+        if !src_port.is_same_kind(dst_port) || *input != Some((src_node, src_idx)) {
+            return;
+        }
+
+        *input = None;
+
+        let src = self.nodes[src_node].ports_mut(src_port);
+        if let Some(dsts) = src.outputs.get_mut(src_idx) {
+            dsts.retain(|&v| v != (dst_node, dst_idx));
+        }
+
+        self.remove_unused_dep(src_node, dst_node);
+    }
+
+    /// Removes all connections of the node, keeping the node itself.
+    pub fn disconnect_all(&mut self, id: NodeId) {
+        let Some(entry) = self.nodes.get(id) else {
+            return;
+        };
+
+        let mut connections = Vec::new();
+
+        for kind in [Port::Audio(0), Port::Event(0)] {
+            let ports = entry.ports(kind);
+
+            for (idx, src) in ports.inputs.iter().enumerate() {
+                if let Some((src_node, src_idx)) = *src {
+                    connections.push((
+                        (src_node, kind.with_index(src_idx)),
+                        (id, kind.with_index(idx)),
+                    ));
+                }
+            }
+
+            for (idx, dsts) in ports.outputs.iter().enumerate() {
+                for &(dst_node, dst_idx) in dsts {
+                    connections.push((
+                        (id, kind.with_index(idx)),
+                        (dst_node, kind.with_index(dst_idx)),
+                    ));
+                }
+            }
+        }
+
+        for (src, dst) in connections {
+            self.disconnect(src, dst);
+        }
+    }
+
+    /// Nodes stay dependent on each other while at least one of their ports is connected.
+    fn remove_unused_dep(&mut self, src_node: NodeId, dst_node: NodeId) {
+        let dst = &self.nodes[dst_node];
+        let is_connected = dst
+            .audio
+            .inputs
+            .iter()
+            .chain(&dst.events.inputs)
+            .any(|src| src.is_some_and(|(node, _)| node == src_node));
+
+        if !is_connected {
+            self.nodes[dst_node].deps.remove(&src_node);
+            self.nodes[src_node].rev_deps.remove(&dst_node);
+        }
     }
 
     pub fn compile(&self) -> CompiledGraph {
-        let mut num_buffers = 1;
-        let mut out_buffers =
-            HashMap::with_capacity_and_hasher(self.nodes.len(), Default::default());
+        let mut audio = BufferAllocator::new(self.nodes.len());
+        let mut events = BufferAllocator::new(self.nodes.len());
         let mut nodes = Vec::with_capacity(self.nodes.len());
 
         for node_id in self.toposort() {
             let node = &self.nodes[node_id];
 
-            let audio_inputs = node
-                .audio_inputs
-                .iter()
-                .map(|src| match src {
-                    Some(src) => *out_buffers.get(src).unwrap(),
-                    None => 0,
-                })
-                .collect();
-
-            let mut audio_outputs = SmallVec::new();
-            for (idx, _dsts) in node.audio_outputs.iter().enumerate() {
-                let buffer_idx = num_buffers;
-                num_buffers += 1;
-
-                out_buffers.insert((node_id, idx), buffer_idx);
-                audio_outputs.push(buffer_idx);
-            }
-
             nodes.push(CompiledNodeEntry {
+                id: node_id,
                 node: node.node.compile(&self.params),
-                audio_inputs,
-                audio_outputs,
+                is_disabled: false,
+                audio_inputs: audio.inputs(&node.audio),
+                audio_outputs: audio.outputs(node_id, &node.audio),
+                event_inputs: events.inputs(&node.events),
+                event_outputs: events.outputs(node_id, &node.events),
             });
         }
 
-        let bump = Bump::with_capacity(1024);
+        // nodes get their inputs and outputs as slices allocated from the arena, which must not
+        // grow on the realtime thread
+        let num_ports = nodes
+            .iter()
+            .map(|v| {
+                v.audio_inputs.len()
+                    + v.audio_outputs.len()
+                    + v.event_inputs.len()
+                    + v.event_outputs.len()
+            })
+            .sum::<usize>();
+        let bump = Bump::with_capacity(num_ports * mem::size_of::<&AudioBuffer>());
         bump.set_allocation_limit(Some(0));
 
-        let audio_buffers = (0..num_buffers)
+        let audio_buffers = (0..audio.num_buffers)
             .map(|_| UnsafeCell::new(AudioBuffer::new(self.params.buffer_size)))
             .collect();
 
+        let event_buffers = (0..events.num_buffers)
+            .map(|_| UnsafeCell::new(EventBuffer::new(MAX_EVENTS_PER_BLOCK)))
+            .collect();
+
         CompiledGraph {
             state: State {
                 params: self.params,
                 bump,
                 audio_buffers,
+                event_buffers,
             },
             nodes,
+            out_buffers: audio.out_buffers,
+            out_event_buffers: events.out_buffers,
         }
     }
 }
 
+/// Assigns a buffer to every output. The first buffer is shared by unconnected inputs and is
+/// never written to.
+struct BufferAllocator {
+    num_buffers: usize,
+    out_buffers: HashMap<(NodeId, usize), usize>,
+}
+
+impl BufferAllocator {
+    fn new(num_nodes: usize) -> BufferAllocator {
+        BufferAllocator {
+            num_buffers: 1,
+            out_buffers: HashMap::with_capacity_and_hasher(num_nodes, Default::default()),
+        }
+    }
+
+    /// Nodes are visited in topological order, so sources already have their buffers.
+    fn inputs(&self, ports: &Ports) -> SmallVec<[usize; 4]> {
+        ports
+            .inputs
+            .iter()
+            .map(|src| match src {
+                Some(src) => *self.out_buffers.get(src).unwrap(),
+                None => 0,
+            })
+            .collect()
+    }
+
+    fn outputs(&mut self, node_id: NodeId, ports: &Ports) -> SmallVec<[usize; 4]> {
+        let mut outputs = SmallVec::new();
+
+        for idx in 0..ports.outputs.len() {
+            let buffer_idx = self.num_buffers;
+            self.num_buffers += 1;
+
+            self.out_buffers.insert((node_id, idx), buffer_idx);
+            outputs.push(buffer_idx);
+        }
+
+        outputs
+    }
+}
+
 struct State {
     params: GraphParams,
     bump: Bump,
     audio_buffers: Vec<UnsafeCell<AudioBuffer>>,
+    event_buffers: Vec<UnsafeCell<EventBuffer>>,
 }
 
 pub struct CompiledGraph {
     state: State,
     nodes: Vec<CompiledNodeEntry>,
+    out_buffers: HashMap<(NodeId, usize), usize>,
+    out_event_buffers: HashMap<(NodeId, usize), usize>,
 }
 
 impl CompiledGraph {
+    /// A node that panics is disabled and outputs silence from then on, the rest of the graph
+    /// keeps running.
     pub fn process(&mut self) {
         self.state.bump.reset();
 
@@ -209,22 +482,65 @@ impl CompiledGraph {
             node.process(&mut self.state);
         }
     }
+
+    /// Nodes that have panicked or were disabled with [`CompiledGraph::disable_node`].
+    pub fn disabled_nodes(&self) -> impl Iterator<Item = NodeId> + '_ {
+        self.nodes.iter().filter(|v| v.is_disabled).map(|v| v.id)
+    }
+
+    /// Makes the node output silence, e.g. because it has panicked in a previous compilation.
+    pub fn disable_node(&mut self, node: NodeId) {
+        if let Some(entry) = self.nodes.iter_mut().find(|v| v.id == node) {
+            entry.is_disabled = true;
+        }
+    }
+
+    /// Returns what the node wrote to the audio output during the last [`CompiledGraph::process`].
+    pub fn audio_output(&self, node: NodeId, port: usize) -> Option<&AudioBuffer> {
+        let &idx = self.out_buffers.get(&(node, port))?;
+        // buffers are only written to in `process`, which needs a mutable reference
+        Some(unsafe { &*self.state.audio_buffers[idx].get() })
+    }
+
+    /// Returns what the node wrote to the event output during the last [`CompiledGraph::process`].
+    pub fn event_output(&self, node: NodeId, port: usize) -> Option<&EventBuffer> {
+        let &idx = self.out_event_buffers.get(&(node, port))?;
+        Some(unsafe { &*self.state.event_buffers[idx].get() })
+    }
 }
 
 struct CompiledNodeEntry {
+    id: NodeId,
     node: Box<dyn CompiledNode>,
+    is_disabled: bool,
     audio_inputs: SmallVec<[usize; 4]>,
     audio_outputs: SmallVec<[usize; 4]>,
+    event_inputs: SmallVec<[usize; 4]>,
+    event_outputs: SmallVec<[usize; 4]>,
 }
 
 impl CompiledNodeEntry {
     fn process(&mut self, state: &mut State) {
+        if self.is_disabled {
+            self.clear_outputs(state);
+            return;
+        }
+
+        for &idx in &self.event_outputs {
+            state.event_buffers[idx].get_mut().clear();
+        }
+
         let inputs = Inputs {
             audio: state.bump.alloc_slice_fill_iter(
                 self.audio_inputs
                     .iter()
                     .map(|&idx| unsafe { &*state.audio_buffers[idx].get() }),
             ),
+            events: state.bump.alloc_slice_fill_iter(
+                self.event_inputs
+                    .iter()
+                    .map(|&idx| unsafe { &*state.event_buffers[idx].get() }),
+            ),
         };
 
         let outputs = Outputs {
@@ -233,8 +549,32 @@ impl CompiledNodeEntry {
                     .iter()
                     .map(|&idx| unsafe { &mut *state.audio_buffers[idx].get() }),
             ),
+            events: state.bump.alloc_slice_fill_iter(
+                self.event_outputs
+                    .iter()
+                    .map(|&idx| unsafe { &mut *state.event_buffers[idx].get() }),
+            ),
         };
 
-        self.node.process(&state.params, inputs, outputs);
+        // a node that has panicked is never run again and its outputs are cleared, so whatever
+        // state it has left behind can't be observed
+        let res = panic::catch_unwind(AssertUnwindSafe(|| {
+            self.node.process(&state.params, inputs, outputs)
+        }));
+
+        if res.is_err() {
+            self.is_disabled = true;
+            self.clear_outputs(state);
+        }
+    }
+
+    fn clear_outputs(&self, state: &mut State) {
+        for &idx in &self.audio_outputs {
+            state.audio_buffers[idx].get_mut().clear();
+        }
+
+        for &idx in &self.event_outputs {
+            state.event_buffers[idx].get_mut().clear();
+        }
     }
 }
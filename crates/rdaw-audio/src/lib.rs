@@ -1,3 +1,8 @@
 pub mod buffer;
+pub mod dither;
 pub mod driver;
 pub mod graph;
+pub mod loudness;
+pub mod nodes;
+pub mod playhead;
+pub mod transients;
@@ -0,0 +1,395 @@
+//! Loudness as measured by ITU-R BS.1770. All channels are weighted equally, which is what
+//! BS.1770 does for mono and stereo.
+
+#[cfg(test)]
+mod tests;
+
+use std::collections::VecDeque;
+use std::f64::consts::PI;
+
+use rdaw_api::export::LoudnessTarget;
+
+const STEP_SECS: f64 = 0.1;
+/// Gating blocks are 400ms long and overlap by 75%.
+const STEPS_PER_BLOCK: usize = 4;
+const ABSOLUTE_GATE: f64 = -70.0;
+const RELATIVE_GATE: f64 = -10.0;
+
+const OVERSAMPLING: usize = 4;
+const INTERPOLATION_TAPS: usize = 12;
+
+const LIMITER_LOOKAHEAD_SECS: f64 = 0.0015;
+const LIMITER_RELEASE_SECS: f64 = 0.05;
+
+pub fn db_to_gain(db: f32) -> f32 {
+    10f32.powf(db / 20.0)
+}
+
+pub fn gain_to_db(gain: f32) -> f32 {
+    20.0 * gain.log10()
+}
+
+/// `None` if there's less than a single block or everything is gated away as silence.
+pub fn integrated_loudness<C: AsRef<[f32]>>(channels: &[C], sample_rate: u32) -> Option<f32> {
+    let step = (STEP_SECS * f64::from(sample_rate)).round() as usize;
+    let block = step * STEPS_PER_BLOCK;
+    let len = num_frames(channels);
+
+    if step == 0 || len < block {
+        return None;
+    }
+
+    // energy of the k-weighted signal of every 100ms step, summed over channels
+    let mut steps = vec![0.0; len / step];
+
+    for channel in channels {
+        let mut filters = k_weighting(sample_rate);
+
+        for (i, &sample) in channel.as_ref()[..steps.len() * step].iter().enumerate() {
+            let value = filters
+                .iter_mut()
+                .fold(f64::from(sample), |v, filter| filter.process(v));
+            steps[i / step] += value * value;
+        }
+    }
+
+    let powers = steps
+        .windows(STEPS_PER_BLOCK)
+        .map(|v| v.iter().sum::<f64>() / block as f64)
+        .filter(|&v| loudness(v) > ABSOLUTE_GATE)
+        .collect::<Vec<_>>();
+
+    if powers.is_empty() {
+        return None;
+    }
+
+    let threshold = loudness(mean(&powers)) + RELATIVE_GATE;
+    let gated = powers
+        .into_iter()
+        .filter(|&v| loudness(v) > threshold)
+        .collect::<Vec<_>>();
+
+    Some(loudness(mean(&gated)) as f32)
+}
+
+pub fn true_peak<C: AsRef<[f32]>>(channels: &[C]) -> f32 {
+    frame_peaks(channels).into_iter().fold(0.0, f32::max)
+}
+
+/// Returns the loudness measured before, silent renders are left as they are.
+pub fn normalize(
+    channels: &mut [Vec<f32>],
+    sample_rate: u32,
+    target: LoudnessTarget,
+) -> Option<f32> {
+    let loudness = integrated_loudness(channels, sample_rate)?;
+    let gain = db_to_gain(target.integrated - loudness);
+
+    for sample in channels.iter_mut().flatten() {
+        *sample *= gain;
+    }
+
+    limit_true_peak(channels, sample_rate, db_to_gain(target.true_peak));
+
+    Some(loudness)
+}
+
+/// The gain is the same for all channels, so that the stereo image doesn't shift.
+pub fn limit_true_peak(channels: &mut [Vec<f32>], sample_rate: u32, ceiling: f32) {
+    let len = num_frames(channels);
+    let lookahead = ((LIMITER_LOOKAHEAD_SECS * f64::from(sample_rate)).round() as usize).max(1);
+    let radius = INTERPOLATION_TAPS / 2;
+
+    let required = frame_peaks(channels)
+        .into_iter()
+        .map(|peak| if peak > ceiling { ceiling / peak } else { 1.0 })
+        .collect::<Vec<_>>();
+
+    // every frame within the interpolator's reach of a peak is attenuated at least as much as
+    // the peak itself, so that the reconstructed signal stays under the ceiling too
+    let held = sliding_min(&required, lookahead + 2 * radius);
+
+    // fading in over the lookahead instead of jumping avoids distortion, and since every
+    // averaged value is low enough for the frame, so is their mean
+    let mut sum = held[radius..radius + lookahead]
+        .iter()
+        .map(|&v| f64::from(v))
+        .sum::<f64>();
+
+    let release = 1.0 - (-1.0 / (LIMITER_RELEASE_SECS * f64::from(sample_rate))).exp();
+    let mut gain = 1.0;
+
+    for i in 0..len {
+        let target = sum / lookahead as f64;
+        sum += f64::from(held[i + radius + lookahead]) - f64::from(held[i + radius]);
+
+        gain = if target < gain {
+            target
+        } else {
+            gain + (target - gain) * release
+        };
+
+        for channel in channels.iter_mut() {
+            channel[i] *= gain as f32;
+        }
+    }
+}
+
+/// Streaming version of [`limit_true_peak`] for stereo, delaying the signal by the lookahead and
+/// the reach of the interpolator. Allocates only when created.
+#[derive(Debug, Clone)]
+pub struct TruePeakLimiter {
+    kernel: [[f64; INTERPOLATION_TAPS]; OVERSAMPLING - 1],
+    release: f64,
+    /// Last input frames, from the ones being output to the ones being interpolated.
+    frames: Vec<[f32; 2]>,
+    /// Where the next input frame goes, which is the oldest one.
+    frame_pos: usize,
+    num_frames: u64,
+    /// Peak between the frame being measured and the one before.
+    previous: f32,
+    /// Required gains of the window, increasing, with the number of their frames.
+    mins: VecDeque<(u64, f32)>,
+    /// Held gains averaged over the lookahead.
+    held: Vec<f32>,
+    held_pos: usize,
+    held_sum: f64,
+    gain: f64,
+}
+
+impl TruePeakLimiter {
+    pub fn new(sample_rate: u32) -> TruePeakLimiter {
+        let lookahead = ((LIMITER_LOOKAHEAD_SECS * f64::from(sample_rate)).round() as usize).max(1);
+        let window = lookahead + INTERPOLATION_TAPS;
+
+        TruePeakLimiter {
+            kernel: interpolation_kernel(),
+            release: 1.0 - (-1.0 / (LIMITER_RELEASE_SECS * f64::from(sample_rate))).exp(),
+            frames: vec![[0.0; 2]; INTERPOLATION_TAPS + lookahead],
+            frame_pos: 0,
+            num_frames: 0,
+            previous: 0.0,
+            mins: VecDeque::with_capacity(window + 1),
+            held: vec![1.0; lookahead],
+            held_pos: 0,
+            held_sum: lookahead as f64,
+            gain: 1.0,
+        }
+    }
+
+    /// Frames an input frame takes to come out.
+    pub fn latency(&self) -> usize {
+        self.frames.len() - 1
+    }
+
+    /// Limits the frame `latency` frames before this one, keeping its true peak under `ceiling`
+    /// (linear).
+    pub fn process(&mut self, frame: [f32; 2], ceiling: f32) -> [f32; 2] {
+        let len = self.frames.len();
+        self.frames[self.frame_pos] = frame;
+        self.frame_pos = (self.frame_pos + 1) % len;
+
+        // `ago` frames before the one just pushed
+        let frames = &self.frames;
+        let frame_pos = self.frame_pos;
+        let get = |ago: usize| frames[(frame_pos + len - 1 - ago) % len];
+
+        // the frame being measured is in the middle of the last taps, and the peak after it is
+        // interpolated from all of them
+        let radius = INTERPOLATION_TAPS / 2;
+        let mut between = 0.0f64;
+
+        for phase in &self.kernel {
+            for channel in 0..2 {
+                let value = phase
+                    .iter()
+                    .enumerate()
+                    .map(|(k, &coef)| f64::from(get(INTERPOLATION_TAPS - 1 - k)[channel]) * coef)
+                    .sum::<f64>();
+                between = between.max(value.abs());
+            }
+        }
+
+        let between = between as f32;
+        let [left, right] = get(radius);
+        let peak = left.abs().max(right.abs()).max(between).max(self.previous);
+        self.previous = between;
+
+        let required = if peak > ceiling { ceiling / peak } else { 1.0 };
+
+        // same windows as the whole render version, just ending at the measured frame
+        let index = self.num_frames;
+        self.num_frames += 1;
+
+        while self.mins.back().is_some_and(|&(_, v)| v >= required) {
+            self.mins.pop_back();
+        }
+        self.mins.push_back((index, required));
+
+        let window = (self.held.len() + INTERPOLATION_TAPS) as u64;
+        while self.mins.front().is_some_and(|&(i, _)| i + window <= index) {
+            self.mins.pop_front();
+        }
+
+        let held = self.mins.front().map_or(1.0, |&(_, v)| v);
+        self.held_sum += f64::from(held) - f64::from(self.held[self.held_pos]);
+        self.held[self.held_pos] = held;
+        self.held_pos = (self.held_pos + 1) % self.held.len();
+
+        let target = self.held_sum / self.held.len() as f64;
+        self.gain = if target < self.gain {
+            target
+        } else {
+            self.gain + (target - self.gain) * self.release
+        };
+
+        let [left, right] = get(len - 1);
+        let gain = self.gain as f32;
+        [left * gain, right * gain]
+    }
+
+    /// Forgets the signal, as if the limiter was just created.
+    pub fn reset(&mut self) {
+        self.frames.fill([0.0; 2]);
+        self.previous = 0.0;
+        self.mins.clear();
+        self.held.fill(1.0);
+        self.held_sum = self.held.len() as f64;
+        self.gain = 1.0;
+    }
+}
+
+fn num_frames<C: AsRef<[f32]>>(channels: &[C]) -> usize {
+    channels.iter().map(|v| v.as_ref().len()).min().unwrap_or(0)
+}
+
+fn loudness(power: f64) -> f64 {
+    -0.691 + 10.0 * power.log10()
+}
+
+fn mean(values: &[f64]) -> f64 {
+    values.iter().sum::<f64>() / values.len() as f64
+}
+
+fn frame_peaks<C: AsRef<[f32]>>(channels: &[C]) -> Vec<f32> {
+    let len = num_frames(channels);
+    let kernel = interpolation_kernel();
+    let mut peaks = vec![0.0f32; len];
+
+    for channel in channels {
+        let channel = &channel.as_ref()[..len];
+        let mut previous = 0.0f32;
+
+        for (i, peak) in peaks.iter_mut().enumerate() {
+            let mut between = 0.0f64;
+
+            for phase in &kernel {
+                let value = phase
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(k, &coef)| {
+                        let j = (i + k).checked_sub(INTERPOLATION_TAPS / 2 - 1)?;
+                        Some(f64::from(*channel.get(j)?) * coef)
+                    })
+                    .sum::<f64>();
+                between = between.max(value.abs());
+            }
+
+            let between = between as f32;
+            *peak = peak.max(channel[i].abs()).max(between).max(previous);
+            previous = between;
+        }
+    }
+
+    peaks
+}
+
+fn interpolation_kernel() -> [[f64; INTERPOLATION_TAPS]; OVERSAMPLING - 1] {
+    let mut kernel = [[0.0; INTERPOLATION_TAPS]; OVERSAMPLING - 1];
+    let half = (INTERPOLATION_TAPS / 2) as f64;
+
+    for (phase, coefs) in kernel.iter_mut().enumerate() {
+        let fraction = (phase + 1) as f64 / OVERSAMPLING as f64;
+
+        for (k, coef) in coefs.iter_mut().enumerate() {
+            let distance = fraction + half - 1.0 - k as f64;
+            let sinc = (PI * distance).sin() / (PI * distance);
+            let window = 0.5 * (1.0 + (PI * distance / half).cos());
+            *coef = sinc * window;
+        }
+    }
+
+    kernel
+}
+
+/// Missing values count as 1.
+fn sliding_min(values: &[f32], window: usize) -> Vec<f32> {
+    let mut queue = VecDeque::<usize>::new();
+    let mut mins = Vec::with_capacity(values.len() + window);
+
+    for end in 0..values.len() + window {
+        if end < values.len() {
+            while queue.back().is_some_and(|&i| values[i] >= values[end]) {
+                queue.pop_back();
+            }
+
+            queue.push_back(end);
+        }
+
+        while queue.front().is_some_and(|&i| i + window <= end) {
+            queue.pop_front();
+        }
+
+        mins.push(queue.front().map_or(1.0, |&i| values[i]));
+    }
+
+    mins
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Biquad {
+    b: [f64; 3],
+    a: [f64; 2],
+    state: [f64; 2],
+}
+
+impl Biquad {
+    fn process(&mut self, x: f64) -> f64 {
+        let y = self.b[0] * x + self.state[0];
+        self.state[0] = self.b[1] * x - self.a[0] * y + self.state[1];
+        self.state[1] = self.b[2] * x - self.a[1] * y;
+        y
+    }
+}
+
+fn k_weighting(sample_rate: u32) -> [Biquad; 2] {
+    let sample_rate = f64::from(sample_rate);
+
+    let k = (PI * 1681.974450955533 / sample_rate).tan();
+    let q = 0.7071752369554196;
+    let vh = 10f64.powf(3.999843853973347 / 20.0);
+    let vb = vh.powf(0.4996667741545416);
+    let a0 = 1.0 + k / q + k * k;
+
+    let shelf = Biquad {
+        b: [
+            (vh + vb * k / q + k * k) / a0,
+            2.0 * (k * k - vh) / a0,
+            (vh - vb * k / q + k * k) / a0,
+        ],
+        a: [2.0 * (k * k - 1.0) / a0, (1.0 - k / q + k * k) / a0],
+        state: [0.0; 2],
+    };
+
+    let k = (PI * 38.13547087602444 / sample_rate).tan();
+    let q = 0.5003270373238773;
+    let a0 = 1.0 + k / q + k * k;
+
+    let high_pass = Biquad {
+        b: [1.0, -2.0, 1.0],
+        a: [2.0 * (k * k - 1.0) / a0, (1.0 - k / q + k * k) / a0],
+        state: [0.0; 2],
+    };
+
+    [shelf, high_pass]
+}
@@ -0,0 +1,127 @@
+use std::f32::consts::PI;
+
+use rdaw_api::export::LoudnessTarget;
+
+use super::{db_to_gain, gain_to_db, integrated_loudness, normalize, true_peak, TruePeakLimiter};
+
+const SAMPLE_RATE: u32 = 48000;
+
+fn sine(frequency: f32, level: f32, secs: f32) -> Vec<f32> {
+    let amplitude = db_to_gain(level);
+    let len = (secs * SAMPLE_RATE as f32) as usize;
+
+    (0..len)
+        .map(|i| amplitude * (2.0 * PI * frequency * i as f32 / SAMPLE_RATE as f32).sin())
+        .collect()
+}
+
+fn assert_close(actual: f32, expected: f32, tolerance: f32) {
+    assert!(
+        (actual - expected).abs() <= tolerance,
+        "{actual} isn't within {tolerance} of {expected}"
+    );
+}
+
+#[test]
+fn integrated() {
+    // EBU Tech 3341, the first case: a 1 kHz sine at -23 dBFS in both channels
+    let signal = sine(997.0, -23.0, 2.0);
+
+    let stereo = integrated_loudness(&[&signal, &signal], SAMPLE_RATE).unwrap();
+    assert_close(stereo, -23.0, 0.1);
+
+    let mono = integrated_loudness(&[&signal], SAMPLE_RATE).unwrap();
+    assert_close(mono, -26.0, 0.1);
+
+    assert_eq!(integrated_loudness(&[vec![0.0; 48000]], SAMPLE_RATE), None);
+    assert_eq!(integrated_loudness(&[&signal[..1000]], SAMPLE_RATE), None);
+}
+
+#[test]
+fn inter_sample_peak() {
+    // a quarter of the sample rate shifted by 45 degrees is never sampled at its peak
+    let signal = (0..64)
+        .map(|i| 0.5 * (PI / 2.0 * i as f32 + PI / 4.0).sin())
+        .collect::<Vec<_>>();
+
+    assert_close(signal.iter().fold(0.0, |a, b| b.abs().max(a)), 0.354, 0.001);
+    assert_close(true_peak(&[signal]), 0.5, 0.02);
+}
+
+#[test]
+fn normalization() {
+    let target = LoudnessTarget {
+        integrated: -16.0,
+        true_peak: -1.0,
+    };
+
+    let signal = sine(997.0, -30.0, 2.0);
+    let mut channels = vec![signal.clone(), signal];
+
+    let before = normalize(&mut channels, SAMPLE_RATE, target).unwrap();
+    assert_close(before, -30.0, 0.1);
+    assert_close(
+        integrated_loudness(&channels, SAMPLE_RATE).unwrap(),
+        -16.0,
+        0.1,
+    );
+
+    let mut silence = vec![vec![0.0; 48000]];
+    assert_eq!(normalize(&mut silence, SAMPLE_RATE, target), None);
+    assert!(silence[0].iter().all(|&v| v == 0.0));
+}
+
+#[test]
+fn true_peak_limiting() {
+    let target = LoudnessTarget {
+        integrated: -16.0,
+        true_peak: -1.0,
+    };
+
+    // a click which ends up way over the ceiling once the rest is made louder
+    let signal = sine(997.0, -30.0, 2.0);
+    let mut channels = vec![signal.clone(), signal];
+    channels[0][48000] = 1.0;
+
+    normalize(&mut channels, SAMPLE_RATE, target).unwrap();
+
+    assert!(gain_to_db(true_peak(&channels)) <= -0.95);
+    assert_close(
+        integrated_loudness(&channels, SAMPLE_RATE).unwrap(),
+        -16.0,
+        0.5,
+    );
+}
+
+#[test]
+fn streaming_limiter() {
+    let mut limiter = TruePeakLimiter::new(SAMPLE_RATE);
+    let latency = limiter.latency();
+    let ceiling = db_to_gain(-1.0);
+
+    // quiet enough to come out as is, just later
+    let quiet = sine(997.0, -12.0, 0.1);
+    let mut output = quiet
+        .iter()
+        .map(|&v| limiter.process([v, -v], ceiling))
+        .collect::<Vec<_>>();
+    assert!(output[..latency].iter().all(|&v| v == [0.0; 2]));
+    for (&[left, right], &v) in output[latency..].iter().zip(&quiet) {
+        assert_close(left, v, 1e-6);
+        assert_close(right, -v, 1e-6);
+    }
+
+    // way too loud, with a click on top
+    let mut loud = sine(997.0, 6.0, 1.0);
+    loud[24000] = 4.0;
+    output = loud
+        .iter()
+        .map(|&v| limiter.process([v, v * 0.5], ceiling))
+        .collect();
+
+    let channels = [0, 1].map(|c| output.iter().map(|v| v[c]).collect::<Vec<_>>());
+    assert!(gain_to_db(true_peak(&channels)) <= -0.95);
+
+    limiter.reset();
+    assert_eq!(limiter.process([0.5; 2], ceiling), [0.0; 2]);
+}
@@ -0,0 +1,57 @@
+use rdaw_api::item::GainEnvelope;
+use rdaw_core::time::RealTime;
+
+use crate::buffer::SilentHint;
+use crate::graph::{CompiledNode, GraphParams, Inputs, Node, Outputs};
+
+#[derive(Debug, Clone)]
+pub struct GainEnvelopeNode {
+    pub envelope: GainEnvelope,
+    /// Item-relative position of the first processed sample.
+    pub start: RealTime,
+}
+
+impl Node for GainEnvelopeNode {
+    fn num_audio_inputs(&self) -> usize {
+        1
+    }
+
+    fn num_audio_outputs(&self) -> usize {
+        1
+    }
+
+    fn compile(&self, _params: &GraphParams) -> Box<dyn CompiledNode> {
+        Box::new(CompiledGainEnvelopeNode {
+            envelope: self.envelope.clone(),
+            start: self.start,
+            num_frames: 0,
+        })
+    }
+}
+
+struct CompiledGainEnvelopeNode {
+    envelope: GainEnvelope,
+    start: RealTime,
+    num_frames: u64,
+}
+
+impl CompiledNode for CompiledGainEnvelopeNode {
+    fn process(&mut self, params: &GraphParams, inputs: Inputs<'_>, outputs: Outputs<'_>) {
+        let input = inputs.audio[0];
+        let output = &mut *outputs.audio[0];
+
+        let offset = self.num_frames as f64 / f64::from(params.sample_rate);
+        let position = self.start + RealTime::from_secs_f64(offset);
+        self.num_frames += input.len() as u64;
+
+        if input.silent_hint == SilentHint::Silent {
+            output.clear();
+            return;
+        }
+
+        output.copy_from_slice(input);
+        output.silent_hint = input.silent_hint;
+        self.envelope
+            .apply(position, params.sample_rate, &mut output.data);
+    }
+}
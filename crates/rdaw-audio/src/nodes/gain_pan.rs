@@ -0,0 +1,121 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use crate::buffer::SilentHint;
+use crate::graph::{CompiledNode, GraphParams, Inputs, Node, Outputs};
+
+/// Volume and pan shared with the realtime thread, so that they can be changed without
+/// recompiling the graph.
+#[derive(Debug, Clone)]
+pub struct GainPanParams {
+    // both values in one atomic, so that they're never seen half-updated
+    bits: Arc<AtomicU64>,
+}
+
+impl GainPanParams {
+    pub fn new(volume: f32, pan: f32) -> GainPanParams {
+        let params = GainPanParams {
+            bits: Arc::new(AtomicU64::new(0)),
+        };
+        params.set(volume, pan);
+        params
+    }
+
+    /// Linear gain, 1.0 leaves the signal as is.
+    pub fn volume(&self) -> f32 {
+        self.get().0
+    }
+
+    /// From -1.0 (left) to 1.0 (right).
+    pub fn pan(&self) -> f32 {
+        self.get().1
+    }
+
+    pub fn get(&self) -> (f32, f32) {
+        let bits = self.bits.load(Ordering::Relaxed);
+        (
+            f32::from_bits((bits >> 32) as u32),
+            f32::from_bits(bits as u32),
+        )
+    }
+
+    pub fn set(&self, volume: f32, pan: f32) {
+        let bits = (u64::from(volume.to_bits()) << 32) | u64::from(pan.to_bits());
+        self.bits.store(bits, Ordering::Relaxed);
+    }
+}
+
+impl Default for GainPanParams {
+    fn default() -> GainPanParams {
+        GainPanParams::new(1.0, 0.0)
+    }
+}
+
+/// Applies volume and pan to a stereo signal, the first input and output being the left channel.
+///
+/// Panning attenuates the opposite channel only, so that a centered signal is left as is. Gains
+/// are ramped over a block when they change, to avoid clicks.
+#[derive(Debug, Clone, Default)]
+pub struct GainPanNode {
+    pub params: GainPanParams,
+}
+
+impl Node for GainPanNode {
+    fn num_audio_inputs(&self) -> usize {
+        2
+    }
+
+    fn num_audio_outputs(&self) -> usize {
+        2
+    }
+
+    fn compile(&self, _params: &GraphParams) -> Box<dyn CompiledNode> {
+        Box::new(CompiledGainPanNode {
+            params: self.params.clone(),
+            gains: None,
+        })
+    }
+}
+
+/// Gains of the left and right channels.
+pub fn pan_gains(volume: f32, pan: f32) -> [f32; 2] {
+    let pan = pan.clamp(-1.0, 1.0);
+    [volume * (1.0 - pan).min(1.0), volume * (1.0 + pan).min(1.0)]
+}
+
+struct CompiledGainPanNode {
+    params: GainPanParams,
+    /// Gains at the end of the last block.
+    gains: Option<[f32; 2]>,
+}
+
+impl CompiledNode for CompiledGainPanNode {
+    fn process(&mut self, _params: &GraphParams, inputs: Inputs<'_>, outputs: Outputs<'_>) {
+        let (volume, pan) = self.params.get();
+        let new_gains = pan_gains(volume, pan);
+        let old_gains = self.gains.replace(new_gains).unwrap_or(new_gains);
+
+        for (channel, input) in inputs.audio.iter().enumerate() {
+            let output = &mut *outputs.audio[channel];
+
+            if input.silent_hint == SilentHint::Silent {
+                output.clear();
+                continue;
+            }
+
+            output.copy_from_slice(input);
+            output.silent_hint = input.silent_hint;
+
+            let (old, new) = (old_gains[channel], new_gains[channel]);
+            if old == new {
+                output.iter_mut().for_each(|v| *v *= new);
+                continue;
+            }
+
+            let step = (new - old) / output.len() as f32;
+            for (i, sample) in output.iter_mut().enumerate() {
+                *sample *= old + step * (i + 1) as f32;
+            }
+        }
+    }
+}
@@ -0,0 +1,137 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use crate::buffer::SilentHint;
+use crate::graph::{CompiledNode, GraphParams, Inputs, Node, Outputs};
+use crate::loudness::{db_to_gain, TruePeakLimiter};
+
+/// Whether the limiter is enabled and its ceiling, shared with the realtime thread.
+#[derive(Debug, Clone)]
+pub struct LimiterParams {
+    // both values in one atomic, so that they're never seen half-updated
+    bits: Arc<AtomicU64>,
+}
+
+impl LimiterParams {
+    pub fn new(enabled: bool, ceiling: f32) -> LimiterParams {
+        let params = LimiterParams {
+            bits: Arc::new(AtomicU64::new(0)),
+        };
+        params.set(enabled, ceiling);
+        params
+    }
+
+    /// Whether the limiter is enabled and its ceiling in dBTP.
+    pub fn get(&self) -> (bool, f32) {
+        let bits = self.bits.load(Ordering::Relaxed);
+        ((bits >> 32) != 0, f32::from_bits(bits as u32))
+    }
+
+    pub fn set(&self, enabled: bool, ceiling: f32) {
+        let bits = (u64::from(enabled) << 32) | u64::from(ceiling.to_bits());
+        self.bits.store(bits, Ordering::Relaxed);
+    }
+}
+
+/// Delayed signal and gain of a limiter, carried over when the graph is recompiled.
+#[derive(Debug, Clone, Default)]
+pub struct LimiterState {
+    // only locked by the realtime thread, so it's never contended
+    inner: Arc<Mutex<LimiterStateInner>>,
+}
+
+#[derive(Debug, Default)]
+struct LimiterStateInner {
+    limiter: Option<TruePeakLimiter>,
+    was_enabled: bool,
+}
+
+/// Brickwall true-peak limiter of a stereo signal, the first input and output being the left
+/// channel. The signal is delayed by [`TruePeakLimiter::latency`] while enabled, and passed as is
+/// while disabled.
+#[derive(Debug, Clone)]
+pub struct LimiterNode {
+    pub params: LimiterParams,
+    pub state: LimiterState,
+}
+
+impl Node for LimiterNode {
+    fn num_audio_inputs(&self) -> usize {
+        2
+    }
+
+    fn num_audio_outputs(&self) -> usize {
+        2
+    }
+
+    fn compile(&self, params: &GraphParams) -> Box<dyn CompiledNode> {
+        Box::new(CompiledLimiterNode {
+            params: self.params.clone(),
+            state: self.state.clone(),
+            spare: Some(TruePeakLimiter::new(params.sample_rate)),
+        })
+    }
+}
+
+struct CompiledLimiterNode {
+    params: LimiterParams,
+    state: LimiterState,
+    /// Taken by the state if it doesn't have a limiter yet, so that the realtime thread doesn't
+    /// allocate.
+    spare: Option<TruePeakLimiter>,
+}
+
+impl CompiledNode for CompiledLimiterNode {
+    fn process(&mut self, _params: &GraphParams, inputs: Inputs<'_>, outputs: Outputs<'_>) {
+        let (enabled, ceiling) = self.params.get();
+
+        let Ok(mut state) = self.state.inner.try_lock() else {
+            pass_through(inputs, outputs);
+            return;
+        };
+        let state = &mut *state;
+
+        if state.limiter.is_none() {
+            state.limiter = self.spare.take();
+        }
+
+        let Some(limiter) = state.limiter.as_mut().filter(|_| enabled) else {
+            state.was_enabled = false;
+            pass_through(inputs, outputs);
+            return;
+        };
+
+        // whatever was delayed before it was disabled is gone
+        if !state.was_enabled {
+            limiter.reset();
+            state.was_enabled = true;
+        }
+
+        let ceiling = db_to_gain(ceiling);
+        let [left_in, right_in] = [inputs.audio[0], inputs.audio[1]]
+            .map(|v| (v.silent_hint != SilentHint::Silent).then_some(&**v));
+
+        let [left_out, right_out] = outputs.audio else {
+            return;
+        };
+
+        for i in 0..left_out.len() {
+            let frame = [
+                left_in.map_or(0.0, |v| v[i]),
+                right_in.map_or(0.0, |v| v[i]),
+            ];
+            [left_out[i], right_out[i]] = limiter.process(frame, ceiling);
+        }
+
+        // the delayed signal is still coming out after the input goes silent
+        left_out.silent_hint = SilentHint::Unspecified;
+        right_out.silent_hint = SilentHint::Unspecified;
+    }
+}
+
+fn pass_through(inputs: Inputs<'_>, outputs: Outputs<'_>) {
+    for (input, output) in inputs.audio.iter().zip(outputs.audio.iter_mut()) {
+        output.copy_from_slice(input);
+        output.silent_hint = input.silent_hint;
+    }
+}
@@ -0,0 +1,56 @@
+use std::sync::{Arc, Mutex};
+
+use rdaw_api::meter::MeterFrame;
+use rdaw_core::sync::spsc::Sender;
+
+use crate::buffer::SilentHint;
+use crate::graph::{CompiledNode, GraphParams, Inputs, Node, Outputs};
+
+/// Sending half of the ring meter frames are published to, shared by all meters of a graph. Only
+/// the realtime thread sends, so the lock is never contended.
+pub type MeterSender<K> = Arc<Mutex<Sender<(K, MeterFrame)>>>;
+
+/// Measures the levels of a stereo signal once per block and sends them along with `key`. Frames
+/// are dropped when the ring is full, since meters can do without a few.
+#[derive(Debug, Clone)]
+pub struct MeterNode<K> {
+    pub key: K,
+    pub sender: MeterSender<K>,
+}
+
+impl<K: Copy + Send + Sync + 'static> Node for MeterNode<K> {
+    fn num_audio_inputs(&self) -> usize {
+        2
+    }
+
+    fn num_audio_outputs(&self) -> usize {
+        0
+    }
+
+    fn compile(&self, _params: &GraphParams) -> Box<dyn CompiledNode> {
+        Box::new(CompiledMeterNode { node: self.clone() })
+    }
+}
+
+struct CompiledMeterNode<K> {
+    node: MeterNode<K>,
+}
+
+impl<K: Copy + Send + 'static> CompiledNode for CompiledMeterNode<K> {
+    fn process(&mut self, _params: &GraphParams, inputs: Inputs<'_>, _outputs: Outputs<'_>) {
+        let is_silent = inputs
+            .audio
+            .iter()
+            .all(|v| v.silent_hint == SilentHint::Silent);
+
+        let frame = if is_silent {
+            MeterFrame::default()
+        } else {
+            MeterFrame::measure(inputs.audio[0], inputs.audio[1])
+        };
+
+        if let Ok(mut sender) = self.node.sender.try_lock() {
+            let _ = sender.try_send((self.node.key, frame));
+        }
+    }
+}
@@ -0,0 +1,42 @@
+use crate::buffer::SilentHint;
+use crate::graph::{CompiledNode, GraphParams, Inputs, Node, Outputs};
+
+#[derive(Debug, Clone)]
+pub struct MixNode {
+    pub num_inputs: usize,
+}
+
+impl Node for MixNode {
+    fn num_audio_inputs(&self) -> usize {
+        self.num_inputs
+    }
+
+    fn num_audio_outputs(&self) -> usize {
+        1
+    }
+
+    fn compile(&self, _params: &GraphParams) -> Box<dyn CompiledNode> {
+        Box::new(CompiledMixNode)
+    }
+}
+
+struct CompiledMixNode;
+
+impl CompiledNode for CompiledMixNode {
+    fn process(&mut self, _params: &GraphParams, inputs: Inputs<'_>, outputs: Outputs<'_>) {
+        let output = &mut *outputs.audio[0];
+        output.clear();
+
+        for input in inputs.audio {
+            if input.silent_hint == SilentHint::Silent {
+                continue;
+            }
+
+            for (out, &sample) in output.iter_mut().zip(input.iter()) {
+                *out += sample;
+            }
+
+            output.silent_hint = SilentHint::Unspecified;
+        }
+    }
+}
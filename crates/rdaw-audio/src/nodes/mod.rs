@@ -0,0 +1,17 @@
+mod gain_envelope;
+mod gain_pan;
+mod limiter;
+mod meter;
+mod mix;
+mod modulator;
+mod sample;
+mod stereo_meter;
+
+pub use self::gain_envelope::GainEnvelopeNode;
+pub use self::gain_pan::{pan_gains, GainPanNode, GainPanParams};
+pub use self::limiter::{LimiterNode, LimiterParams, LimiterState};
+pub use self::meter::{MeterNode, MeterSender};
+pub use self::mix::MixNode;
+pub use self::modulator::{ModulationValue, ModulatorNode};
+pub use self::sample::SampleNode;
+pub use self::stereo_meter::{StereoMeterNode, StereoMeterSender};
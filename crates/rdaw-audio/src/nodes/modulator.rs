@@ -0,0 +1,106 @@
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+
+use rdaw_api::modulation::{EnvelopeFollower, ModulationSource};
+use rdaw_core::time::RealTime;
+
+use crate::buffer::SilentHint;
+use crate::graph::{CompiledNode, GraphParams, Inputs, Node, Outputs};
+use crate::playhead::Playhead;
+
+#[derive(Debug, Clone, Default)]
+pub struct ModulationValue {
+    bits: Arc<AtomicU32>,
+}
+
+impl ModulationValue {
+    pub fn new() -> ModulationValue {
+        ModulationValue::default()
+    }
+
+    pub fn get(&self) -> f32 {
+        f32::from_bits(self.bits.load(Ordering::Relaxed))
+    }
+
+    pub fn set(&self, value: f32) {
+        self.bits.store(value.to_bits(), Ordering::Relaxed);
+    }
+}
+
+/// Envelope followers listen to the only input, other sources don't have any.
+#[derive(Debug, Clone)]
+pub struct ModulatorNode {
+    pub source: ModulationSource,
+    pub playhead: Playhead,
+    pub value: ModulationValue,
+}
+
+impl Node for ModulatorNode {
+    fn num_audio_inputs(&self) -> usize {
+        match self.source {
+            ModulationSource::EnvelopeFollower(_) => 1,
+            _ => 0,
+        }
+    }
+
+    fn num_audio_outputs(&self) -> usize {
+        0
+    }
+
+    fn compile(&self, _params: &GraphParams) -> Box<dyn CompiledNode> {
+        Box::new(CompiledModulatorNode {
+            node: self.clone(),
+            envelope: 0.0,
+        })
+    }
+}
+
+struct CompiledModulatorNode {
+    node: ModulatorNode,
+    envelope: f32,
+}
+
+impl CompiledModulatorNode {
+    fn follow(&mut self, follower: &EnvelopeFollower, params: &GraphParams, inputs: Inputs<'_>) {
+        let input = inputs.audio[0];
+
+        let peak = if input.silent_hint == SilentHint::Silent {
+            0.0
+        } else {
+            input.iter().fold(0.0f32, |acc, v| acc.max(v.abs()))
+        };
+
+        let time = if peak > self.envelope {
+            follower.attack
+        } else {
+            follower.release
+        };
+
+        let block = input.len() as f32 / params.sample_rate as f32;
+        let coeff = if time > RealTime::ZERO {
+            1.0 - (-block / time.as_secs_f32()).exp()
+        } else {
+            1.0
+        };
+
+        self.envelope += (peak - self.envelope) * coeff;
+    }
+}
+
+impl CompiledNode for CompiledModulatorNode {
+    fn process(&mut self, params: &GraphParams, inputs: Inputs<'_>, _outputs: Outputs<'_>) {
+        let value = match self.node.source {
+            ModulationSource::Lfo(lfo) => {
+                let secs = self.node.playhead.frame() as f64 / f64::from(params.sample_rate);
+                lfo.value_at(RealTime::from_secs_f64(secs))
+            }
+            ModulationSource::EnvelopeFollower(follower) => {
+                self.follow(&follower, params, inputs);
+                self.envelope.min(1.0)
+            }
+            ModulationSource::Macro { value } => value,
+        };
+
+        self.node.value.set(value);
+    }
+}
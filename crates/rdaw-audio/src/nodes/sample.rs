@@ -0,0 +1,85 @@
+use std::sync::Arc;
+
+use rdaw_api::item::AudioClip;
+use rdaw_core::time::RealTime;
+
+use crate::buffer::SilentHint;
+use crate::graph::{CompiledNode, GraphParams, Inputs, Node, Outputs};
+use crate::playhead::Playhead;
+
+#[derive(Debug, Clone)]
+pub struct SampleNode {
+    pub samples: Arc<[f32]>,
+    pub sample_rate: u32,
+    /// Timeline frame where the item starts.
+    pub start: i64,
+    pub duration: i64,
+    /// The item starts over after this many timeline frames, until its duration is over.
+    pub loop_length: Option<i64>,
+    /// Where to start in the samples, and the gain and fades to apply.
+    pub clip: AudioClip,
+    pub playhead: Playhead,
+}
+
+impl Node for SampleNode {
+    fn num_audio_inputs(&self) -> usize {
+        0
+    }
+
+    fn num_audio_outputs(&self) -> usize {
+        1
+    }
+
+    fn compile(&self, params: &GraphParams) -> Box<dyn CompiledNode> {
+        let sample_rate = f64::from(params.sample_rate);
+        let offset = (self.clip.source_offset.as_secs_f64() * sample_rate).round() as i64;
+
+        Box::new(CompiledSampleNode {
+            node: self.clone(),
+            offset,
+        })
+    }
+}
+
+struct CompiledSampleNode {
+    node: SampleNode,
+    /// Source offset in timeline frames.
+    offset: i64,
+}
+
+impl CompiledNode for CompiledSampleNode {
+    fn process(&mut self, params: &GraphParams, _inputs: Inputs<'_>, outputs: Outputs<'_>) {
+        let node = &self.node;
+        let output = &mut *outputs.audio[0];
+
+        let period_start = node.playhead.frame() - node.start;
+        let period_end = period_start + output.len() as i64;
+
+        if period_end <= 0 || period_start >= node.duration {
+            output.clear();
+            return;
+        }
+
+        // sources aren't resampled yet, samples are picked at the nearest position instead
+        let ratio = f64::from(node.sample_rate) / f64::from(params.sample_rate);
+        let frame_secs = 1.0 / f64::from(params.sample_rate);
+        // fades are applied to every repetition
+        let loop_length = node.loop_length.unwrap_or(node.duration).max(1);
+        let duration = RealTime::from_secs_f64(loop_length as f64 * frame_secs);
+
+        for (i, out) in output.iter_mut().enumerate() {
+            let frame = period_start + i as i64;
+            *out = if (0..node.duration).contains(&frame) {
+                let frame = frame % loop_length;
+                let idx = ((frame + self.offset) as f64 * ratio) as usize;
+                let sample = node.samples.get(idx).copied().unwrap_or(0.0);
+                let position = RealTime::from_secs_f64(frame as f64 * frame_secs);
+                sample * node.clip.gain_at(position, duration)
+            } else {
+                0.0
+            };
+        }
+
+        output.silent_hint = SilentHint::Unspecified;
+    }
+}
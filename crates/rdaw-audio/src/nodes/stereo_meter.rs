@@ -0,0 +1,60 @@
+use std::sync::{Arc, Mutex};
+
+use rdaw_core::sync::spsc::Sender;
+
+use crate::buffer::SilentHint;
+use crate::graph::{CompiledNode, GraphParams, Inputs, Node, Outputs};
+
+/// Only the realtime thread sends, so the lock is never contended.
+pub type StereoMeterSender = Arc<Mutex<Sender<[f32; 2]>>>;
+
+/// Blocks are dropped whole when the ring is full.
+#[derive(Debug, Clone)]
+pub struct StereoMeterNode {
+    pub sender: StereoMeterSender,
+}
+
+impl Node for StereoMeterNode {
+    fn num_audio_inputs(&self) -> usize {
+        2
+    }
+
+    fn num_audio_outputs(&self) -> usize {
+        0
+    }
+
+    fn compile(&self, params: &GraphParams) -> Box<dyn CompiledNode> {
+        Box::new(CompiledStereoMeterNode {
+            sender: self.sender.clone(),
+            samples: Vec::with_capacity(params.buffer_size),
+        })
+    }
+}
+
+struct CompiledStereoMeterNode {
+    sender: StereoMeterSender,
+    samples: Vec<[f32; 2]>,
+}
+
+impl CompiledNode for CompiledStereoMeterNode {
+    fn process(&mut self, params: &GraphParams, inputs: Inputs<'_>, _outputs: Outputs<'_>) {
+        let is_silent = inputs
+            .audio
+            .iter()
+            .all(|v| v.silent_hint == SilentHint::Silent);
+
+        self.samples.clear();
+
+        if is_silent {
+            self.samples.resize(params.buffer_size, [0.0; 2]);
+        } else {
+            let frames = inputs.audio[0].iter().zip(inputs.audio[1].iter());
+            self.samples
+                .extend(frames.map(|(&left, &right)| [left, right]));
+        }
+
+        if let Ok(mut sender) = self.sender.try_lock() {
+            let _ = sender.try_send_slice(&self.samples);
+        }
+    }
+}
@@ -0,0 +1,63 @@
+use std::sync::atomic::{AtomicBool, AtomicI64, Ordering};
+use std::sync::Arc;
+
+#[derive(Debug, Clone, Default)]
+pub struct Playhead {
+    inner: Arc<PlayheadInner>,
+}
+
+#[derive(Debug, Default)]
+struct PlayheadInner {
+    playing: AtomicBool,
+    frame: AtomicI64,
+    loop_start: AtomicI64,
+    /// Zero or less if there's no loop.
+    loop_end: AtomicI64,
+}
+
+impl Playhead {
+    pub fn new() -> Playhead {
+        Playhead::default()
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.inner.playing.load(Ordering::Acquire)
+    }
+
+    pub fn set_playing(&self, playing: bool) {
+        self.inner.playing.store(playing, Ordering::Release);
+    }
+
+    pub fn frame(&self) -> i64 {
+        self.inner.frame.load(Ordering::Acquire)
+    }
+
+    pub fn seek(&self, frame: i64) {
+        self.inner.frame.store(frame, Ordering::Release);
+    }
+
+    pub fn set_loop(&self, range: Option<(i64, i64)>) {
+        let (start, end) = range.unwrap_or((0, 0));
+        self.inner.loop_start.store(start, Ordering::Release);
+        self.inner.loop_end.store(end, Ordering::Release);
+    }
+
+    /// Jumps back to the start of the loop if the period has crossed its end.
+    pub fn advance(&self, num_frames: usize) {
+        let old = self.frame();
+        let mut new = old + num_frames as i64;
+
+        let start = self.inner.loop_start.load(Ordering::Acquire);
+        let end = self.inner.loop_end.load(Ordering::Acquire);
+
+        if end > start && old < end && new >= end {
+            new = start + (new - end) % (end - start);
+        }
+
+        // a seek from another thread wins over the advance
+        let _ = self
+            .inner
+            .frame
+            .compare_exchange(old, new, Ordering::AcqRel, Ordering::Acquire);
+    }
+}
@@ -0,0 +1,116 @@
+//! Detection of transients, the onsets of hits and notes, for slicing audio at them.
+//!
+//! An onset is where the energy of a short hop, summed over channels, rises sharply above the
+//! average of the hops before it. Its position is then refined to the first frame of the hop
+//! which reaches half of the hop's peak.
+
+#[cfg(test)]
+mod tests;
+
+use std::collections::VecDeque;
+
+const HOP_SECS: f64 = 0.005;
+/// Number of previous hops an onset has to rise above.
+const HISTORY_HOPS: usize = 4;
+/// Rise in dB needed for an onset at the lowest and the highest sensitivity.
+const LEAST_SENSITIVE_RISE: f32 = 24.0;
+const MOST_SENSITIVE_RISE: f32 = 3.0;
+/// Hops quieter than this never start an onset, and so is anything before the first hop.
+const SILENCE_DB: f32 = -60.0;
+const FLOOR_DB: f32 = -120.0;
+/// Onsets closer to the previous one are ignored, so that a single hit isn't sliced twice.
+const MIN_GAP_SECS: f64 = 0.05;
+
+/// Finds onsets in interleaved frames pushed in any number of chunks.
+#[derive(Debug)]
+pub struct TransientDetector {
+    num_channels: usize,
+    hop: usize,
+    min_gap: u64,
+    rise: f32,
+    /// Frames of the current hop.
+    current: Vec<f32>,
+    /// Frame the current hop starts at.
+    position: u64,
+    history: VecDeque<f32>,
+    onsets: Vec<u64>,
+}
+
+impl TransientDetector {
+    /// `sensitivity` goes from 0.0, where only the sharpest hits are found, to 1.0.
+    pub fn new(num_channels: usize, sample_rate: u32, sensitivity: f32) -> TransientDetector {
+        let sensitivity = sensitivity.clamp(0.0, 1.0);
+        let rise =
+            LEAST_SENSITIVE_RISE + (MOST_SENSITIVE_RISE - LEAST_SENSITIVE_RISE) * sensitivity;
+        let hop = (HOP_SECS * f64::from(sample_rate)).round().max(1.0) as usize;
+
+        TransientDetector {
+            num_channels: num_channels.max(1),
+            hop,
+            min_gap: (MIN_GAP_SECS * f64::from(sample_rate)).round() as u64,
+            rise,
+            current: Vec::with_capacity(hop * num_channels.max(1)),
+            position: 0,
+            history: VecDeque::with_capacity(HISTORY_HOPS + 1),
+            onsets: Vec::new(),
+        }
+    }
+
+    pub fn push(&mut self, samples: &[f32]) {
+        for frame in samples.chunks_exact(self.num_channels) {
+            self.current.extend_from_slice(frame);
+
+            if self.current.len() == self.hop * self.num_channels {
+                self.process_hop();
+            }
+        }
+    }
+
+    /// Returns frames at which onsets start, in ascending order.
+    pub fn finish(mut self) -> Vec<u64> {
+        if !self.current.is_empty() {
+            self.process_hop();
+        }
+
+        self.onsets
+    }
+
+    fn process_hop(&mut self) {
+        let num_frames = self.current.len() / self.num_channels;
+        let energy = self.current.iter().map(|v| v * v).sum::<f32>() / num_frames as f32;
+        let level = 10.0 * energy.max(1e-12).log10();
+
+        let reference = if self.history.is_empty() {
+            FLOOR_DB
+        } else {
+            self.history.iter().sum::<f32>() / self.history.len() as f32
+        };
+
+        let is_spaced = match self.onsets.last() {
+            Some(&last) => self.position - last >= self.min_gap,
+            None => true,
+        };
+
+        if level > SILENCE_DB && level - reference >= self.rise && is_spaced {
+            self.onsets.push(self.position + self.attack_frame() as u64);
+        }
+
+        self.history.push_back(level);
+        if self.history.len() > HISTORY_HOPS {
+            self.history.pop_front();
+        }
+
+        self.position += num_frames as u64;
+        self.current.clear();
+    }
+
+    /// First frame of the current hop reaching half of its peak.
+    fn attack_frame(&self) -> usize {
+        let peak = self.current.iter().fold(0.0f32, |acc, v| acc.max(v.abs()));
+
+        self.current
+            .iter()
+            .position(|v| v.abs() >= peak * 0.5)
+            .map_or(0, |i| i / self.num_channels)
+    }
+}
@@ -0,0 +1,75 @@
+use std::f32::consts::PI;
+
+use super::TransientDetector;
+
+const SAMPLE_RATE: u32 = 48000;
+
+/// Adds a decaying tone starting at `start`.
+fn add_hit(signal: &mut [f32], start: usize, amplitude: f32, frequency: f32) {
+    for (i, sample) in signal[start..].iter_mut().enumerate() {
+        let t = i as f32 / SAMPLE_RATE as f32;
+        *sample += amplitude * (-t / 0.02).exp() * (2.0 * PI * frequency * t).sin();
+    }
+}
+
+fn detect(signal: &[f32], sensitivity: f32) -> Vec<u64> {
+    let mut detector = TransientDetector::new(1, SAMPLE_RATE, sensitivity);
+    detector.push(signal);
+    detector.finish()
+}
+
+fn assert_onsets(actual: &[u64], expected: &[u64]) {
+    // within a millisecond
+    let is_close = actual.len() == expected.len()
+        && actual
+            .iter()
+            .zip(expected)
+            .all(|(a, e)| a.abs_diff(*e) <= 48);
+    assert!(is_close, "{actual:?} isn't close to {expected:?}");
+}
+
+#[test]
+fn hits() {
+    let mut signal = vec![0.0; SAMPLE_RATE as usize];
+    for start in [4800, 19200, 33600] {
+        add_hit(&mut signal, start, 0.8, 1000.0);
+    }
+
+    assert_onsets(&detect(&signal, 0.5), &[4800, 19200, 33600]);
+
+    // chunks don't have to line up with hops
+    let mut detector = TransientDetector::new(1, SAMPLE_RATE, 0.5);
+    for chunk in signal.chunks(1000) {
+        detector.push(chunk);
+    }
+    assert_eq!(detector.finish(), detect(&signal, 0.5));
+
+    assert!(detect(&vec![0.0; 48000], 1.0).is_empty());
+}
+
+#[test]
+fn sensitivity() {
+    // a quiet tone, which starts at the very beginning, with a soft and a loud hit on top
+    let mut signal = (0..SAMPLE_RATE)
+        .map(|i| 0.03 * (2.0 * PI * 440.0 * i as f32 / SAMPLE_RATE as f32).sin())
+        .collect::<Vec<_>>();
+    add_hit(&mut signal, 9600, 0.1, 1500.0);
+    add_hit(&mut signal, 28800, 1.0, 1000.0);
+
+    assert_onsets(&detect(&signal, 0.0), &[0, 28800]);
+    assert_onsets(&detect(&signal, 0.5), &[0, 28800]);
+    assert_onsets(&detect(&signal, 1.0), &[0, 9600, 28800]);
+}
+
+#[test]
+fn stereo() {
+    let mut left = vec![0.0; 24000];
+    add_hit(&mut left, 12000, 0.8, 1000.0);
+
+    // the hit is only in the left channel
+    let interleaved = left.iter().flat_map(|&v| [v, 0.0]).collect::<Vec<_>>();
+
+    let mut detector = TransientDetector::new(2, SAMPLE_RATE, 0.5);
+    detector.push(&interleaved);
+    assert_onsets(&detector.finish(), &[12000]);
+}
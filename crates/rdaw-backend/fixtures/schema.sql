@@ -0,0 +1,12 @@
+PRAGMA user_version = 5;
+CREATE INDEX blob_dependencies_parent_idx ON blob_dependencies (parent_id);
+CREATE UNIQUE INDEX blobs_hash_idx ON blobs (hash) WHERE hash IS NOT NULL;
+CREATE INDEX objects_blob_idx ON objects (blob_id);
+CREATE TABLE blob_chunks ( blob_id INTEGER REFERENCES blobs (id) ON DELETE CASCADE, offset INTEGER NOT NULL, len INTEGER NOT NULL, data BLOB NOT NULL, PRIMARY KEY (blob_id, offset) );
+CREATE TABLE blob_dependencies ( parent_id INTEGER NOT NULL REFERENCES blobs (id) ON DELETE CASCADE, child_id INTEGER NOT NULL REFERENCES blobs (id), PRIMARY KEY (parent_id, child_id) );
+CREATE TABLE blobs ( id INTEGER PRIMARY KEY ASC, hash BLOB, total_len INTEGER NOT NULL, compression INTEGER NOT NULL );
+CREATE TABLE encryption ( id INTEGER PRIMARY KEY CHECK (id = 0), salt BLOB NOT NULL, wrapped_key BLOB NOT NULL );
+CREATE TABLE objects ( uuid BLOB NOT NULL, revision_id INTEGER NOT NULL, blob_id INTEGER NOT NULL REFERENCES blobs (id), modified_at TEXT, author TEXT, PRIMARY KEY (uuid, revision_id) );
+CREATE TABLE revisions ( id INTEGER PRIMARY KEY ASC, created_at TEXT NOT NULL, time_spent INTEGER NOT NULL, arrangement_uuid BLOB NOT NULL );
+CREATE TABLE snapshots ( id INTEGER PRIMARY KEY ASC, revision_id INTEGER NOT NULL REFERENCES revisions (id), name TEXT NOT NULL );
+CREATE TABLE waveform_peaks ( asset_hash BLOB PRIMARY KEY, blob_id INTEGER NOT NULL REFERENCES blobs (id) ON DELETE CASCADE );
@@ -0,0 +1,99 @@
+use std::fmt::Write;
+
+use rdaw_api::{bail, ErrorKind, Result};
+use rdaw_core::path::Utf8Path;
+use rdaw_core::time::RealTime;
+
+const FRAMES_PER_SEC: i64 = 75;
+const MAX_TRACKS: usize = 99;
+const MAX_CD_TEXT_LEN: usize = 80;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CueTrack<'a> {
+    pub title: &'a str,
+    pub start: RealTime,
+    pub pregap: RealTime,
+}
+
+pub fn write_cue_sheet(title: &str, audio_path: &Utf8Path, tracks: &[CueTrack]) -> Result<String> {
+    if tracks.is_empty() {
+        bail!(ErrorKind::NotFound, "no cd track markers");
+    }
+
+    if tracks.len() > MAX_TRACKS {
+        bail!(
+            ErrorKind::NotSupported,
+            "too many cd tracks ({} > {MAX_TRACKS})",
+            tracks.len(),
+        );
+    }
+
+    let mut out = String::new();
+    let _ = writeln!(out, "REM GENERATOR \"rdaw\"");
+    let _ = writeln!(out, "TITLE \"{}\"", cd_text(title));
+    let _ = writeln!(
+        out,
+        "FILE \"{}\" {}",
+        audio_path.as_str().replace('"', "'"),
+        file_type(audio_path)
+    );
+
+    let mut prev_start = 0;
+
+    for (i, track) in tracks.iter().enumerate() {
+        let start = to_frames(track.start);
+        let pregap = to_frames(track.pregap);
+
+        // the gap can't extend into the previous track, and the first track can only use the
+        // audio before it. whatever doesn't fit is left to the burner as generated silence
+        let gap_start = (start - pregap).max(prev_start);
+        let missing_gap = if i == 0 { pregap - (start - gap_start) } else { 0 };
+
+        let _ = writeln!(out, "  TRACK {:02} AUDIO", i + 1);
+        let _ = writeln!(out, "    TITLE \"{}\"", cd_text(track.title));
+
+        if missing_gap > 0 {
+            let _ = writeln!(out, "    PREGAP {}", msf(missing_gap));
+        }
+
+        if gap_start < start {
+            let _ = writeln!(out, "    INDEX 00 {}", msf(gap_start));
+        }
+
+        let _ = writeln!(out, "    INDEX 01 {}", msf(start));
+
+        prev_start = start;
+    }
+
+    Ok(out)
+}
+
+fn to_frames(time: RealTime) -> i64 {
+    let frames = time.as_nanos() as i128 * FRAMES_PER_SEC as i128 / 1_000_000_000;
+    frames.max(0) as i64
+}
+
+fn msf(frames: i64) -> String {
+    let secs = frames / FRAMES_PER_SEC;
+    format!("{:02}:{:02}:{:02}", secs / 60, secs % 60, frames % FRAMES_PER_SEC)
+}
+
+fn cd_text(text: &str) -> String {
+    text.chars()
+        .map(|c| match c {
+            '"' => '\'',
+            c if c.is_control() => ' ',
+            c => c,
+        })
+        .take(MAX_CD_TEXT_LEN)
+        .collect()
+}
+
+fn file_type(path: &Utf8Path) -> &'static str {
+    match path.extension().map(|v| v.to_ascii_lowercase()).as_deref() {
+        Some("wav") => "WAVE",
+        Some("aif" | "aiff") => "AIFF",
+        Some("mp3") => "MP3",
+        _ => "BINARY",
+    }
+}
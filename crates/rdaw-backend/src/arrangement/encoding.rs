@@ -1,51 +1,730 @@
-use rdaw_api::Result;
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+
+use rdaw_api::arrangement::{Marker, MarkerKind};
+use rdaw_api::chord::{Chord, ChordQuality, PitchClass, Scale, ScaleMode};
+use rdaw_api::item::{ItemId, ItemKind};
+use rdaw_api::launcher::{Clip, Scene};
+use rdaw_api::modulation::{
+    EnvelopeFollower, Lfo, LfoShape, ModulationSource, ModulationTarget, Modulator, Polarity,
+};
+use rdaw_api::time::{BeatTime, Time};
+use rdaw_api::video::{ArrangementVideo, FrameRate, VideoMetadata};
+use rdaw_api::{bail, ErrorKind, Result};
+use rdaw_core::time::RealTime;
 use serde::{Deserialize, Serialize};
+use slotmap::SlotMap;
 
 use super::Arrangement;
 use crate::define_version_enum;
 use crate::document::encoding;
+use crate::node::presets::Preset;
 use crate::object::{DeserializationContext, SerializationContext, Uuid};
 
 pub fn serialize(ctx: &mut SerializationContext<'_>, arrangement: &Arrangement) -> Result<Vec<u8>> {
     let tempo_map_uuid = ctx.add_dep(arrangement.tempo_map_id)?;
     let main_track_uuid = ctx.add_dep(arrangement.main_track_id)?;
 
+    let markers = arrangement
+        .markers
+        .values()
+        .map(|marker| MarkerLatest {
+            position: marker.position,
+            name: Cow::Borrowed(&marker.name),
+            kind: marker.kind,
+        })
+        .collect();
+
+    let video = match &arrangement.video {
+        Some(video) => Some(ArrangementVideoLatest {
+            asset_uuid: ctx.add_dep(video.asset_id)?,
+            offset: video.offset,
+            width: video.metadata.width,
+            height: video.metadata.height,
+            frame_rate_num: video.metadata.frame_rate.num,
+            frame_rate_den: video.metadata.frame_rate.den,
+            duration: video.metadata.duration,
+        }),
+        None => None,
+    };
+
+    let presets = arrangement
+        .presets
+        .iter()
+        .map(|preset| PresetLatest {
+            kind: Cow::Borrowed(&preset.kind),
+            name: Cow::Borrowed(&preset.name),
+            params: preset
+                .params
+                .iter()
+                .map(|(name, &value)| (Cow::Borrowed(name.as_str()), value))
+                .collect(),
+        })
+        .collect();
+
+    let chords = arrangement
+        .chords
+        .values()
+        .map(|chord| ChordLatest {
+            start: chord.start,
+            duration: chord.duration,
+            root: chord.root,
+            quality: chord.quality,
+            bass: chord.bass,
+            scale_root: chord.scale.root,
+            scale_mode: chord.scale.mode,
+        })
+        .collect();
+
+    let mut modulators = Vec::with_capacity(arrangement.modulators.len());
+
+    for modulator in arrangement.modulators.values() {
+        let source = match modulator.source {
+            ModulationSource::Lfo(lfo) => ModulationSourceLatest::Lfo {
+                shape: lfo.shape,
+                rate: lfo.rate,
+                phase: lfo.phase,
+            },
+            ModulationSource::EnvelopeFollower(follower) => {
+                ModulationSourceLatest::EnvelopeFollower {
+                    track_uuid: ctx.add_dep(follower.track_id)?,
+                    attack: follower.attack,
+                    release: follower.release,
+                }
+            }
+            ModulationSource::Macro { value } => ModulationSourceLatest::Macro { value },
+        };
+
+        let mut targets = Vec::with_capacity(modulator.targets.len());
+
+        for target in &modulator.targets {
+            targets.push(ModulationTargetLatest {
+                node_uuid: ctx.add_dep(target.node_id)?,
+                param: Cow::Borrowed(&target.param),
+                depth: target.depth,
+                polarity: target.polarity,
+            });
+        }
+
+        modulators.push(ModulatorLatest {
+            name: Cow::Borrowed(&modulator.name),
+            source,
+            targets,
+        });
+    }
+
+    let mut scenes = Vec::with_capacity(arrangement.scene_order.len());
+
+    for scene in arrangement
+        .scene_order
+        .iter()
+        .filter_map(|&id| arrangement.scenes.get(id))
+    {
+        let mut clips = Vec::with_capacity(scene.clips.len());
+
+        for (&track_id, clip) in &scene.clips {
+            let (item_kind, item_uuid) = match clip.item {
+                ItemId::Audio(id) => (ItemKind::Audio, ctx.add_dep(id)?),
+                ItemId::Midi(id) => (ItemKind::Midi, ctx.add_dep(id)?),
+            };
+
+            clips.push(ClipLatest {
+                track_uuid: ctx.add_dep(track_id)?,
+                item_kind,
+                item_uuid,
+                length: clip.length,
+            });
+        }
+
+        scenes.push(SceneLatest {
+            name: Cow::Borrowed(&scene.name),
+            clips,
+        });
+    }
+
+    let buses = arrangement
+        .buses
+        .iter()
+        .map(|&id| ctx.add_dep(id))
+        .collect::<Result<Vec<_>>>()?;
+
     let raw = ArrangementLatest {
         tempo_map_uuid,
         main_track_uuid,
-        name: &arrangement.name,
+        name: Cow::Borrowed(&arrangement.name),
+        markers,
+        video,
+        presets,
+        chords,
+        modulators,
+        scenes,
+        buses,
+        seed: arrangement.seed,
     };
 
-    encoding::serialize(Version::LATEST.as_u32(), &raw)
+    encoding::serialize(ctx.format(), Version::LATEST.as_u32(), &raw)
 }
 
 pub fn deserialize(ctx: &mut DeserializationContext<'_>, data: &[u8]) -> Result<Arrangement> {
-    let (version, data) = encoding::extract_version(data)?;
+    let (version, data) = encoding::extract_version(ctx.format(), data)?;
     let raw = match Version::from_u32(version)? {
-        Version::V1 => encoding::deserialize::<ArrangementV1>(data)?,
+        Version::V1 => {
+            let raw = encoding::deserialize::<ArrangementV1>(ctx.format(), data)?;
+            let raw = ArrangementV4::from(ArrangementV3::from(ArrangementV2::from(raw)));
+            let raw = ArrangementV7::from(ArrangementV6::from(ArrangementV5::from(raw)));
+            ArrangementV8::from(raw).into()
+        }
+        Version::V2 => {
+            let raw = encoding::deserialize::<ArrangementV2>(ctx.format(), data)?;
+            let raw = ArrangementV5::from(ArrangementV4::from(ArrangementV3::from(raw)));
+            ArrangementV8::from(ArrangementV7::from(ArrangementV6::from(raw))).into()
+        }
+        Version::V3 => {
+            let raw = encoding::deserialize::<ArrangementV3>(ctx.format(), data)?;
+            let raw = ArrangementV6::from(ArrangementV5::from(ArrangementV4::from(raw)));
+            ArrangementV8::from(ArrangementV7::from(raw)).into()
+        }
+        Version::V4 => {
+            let raw = encoding::deserialize::<ArrangementV4>(ctx.format(), data)?;
+            let raw = ArrangementV7::from(ArrangementV6::from(ArrangementV5::from(raw)));
+            ArrangementV8::from(raw).into()
+        }
+        Version::V5 => {
+            let raw = encoding::deserialize::<ArrangementV5>(ctx.format(), data)?;
+            ArrangementV8::from(ArrangementV7::from(ArrangementV6::from(raw))).into()
+        }
+        Version::V6 => {
+            let raw = encoding::deserialize::<ArrangementV6>(ctx.format(), data)?;
+            ArrangementV8::from(ArrangementV7::from(raw)).into()
+        }
+        Version::V7 => {
+            ArrangementV8::from(encoding::deserialize::<ArrangementV7>(ctx.format(), data)?).into()
+        }
+        Version::V8 => encoding::deserialize::<ArrangementV8>(ctx.format(), data)?.into(),
+        Version::V9 => encoding::deserialize::<ArrangementV9>(ctx.format(), data)?,
     };
 
     let tempo_map_id = ctx.add_dep(raw.tempo_map_uuid)?;
     let main_track_id = ctx.add_dep(raw.main_track_uuid)?;
 
+    let mut markers = SlotMap::with_capacity_and_key(raw.markers.len());
+
+    for marker in raw.markers {
+        markers.insert(Marker {
+            position: marker.position,
+            name: marker.name.into_owned(),
+            kind: marker.kind,
+        });
+    }
+
+    let video = match raw.video {
+        Some(video) => Some(ArrangementVideo {
+            asset_id: ctx.add_dep(video.asset_uuid)?,
+            offset: video.offset,
+            metadata: VideoMetadata {
+                width: video.width,
+                height: video.height,
+                frame_rate: FrameRate {
+                    num: video.frame_rate_num,
+                    den: video.frame_rate_den,
+                },
+                duration: video.duration,
+            },
+        }),
+        None => None,
+    };
+
+    let presets = raw
+        .presets
+        .into_iter()
+        .map(|preset| Preset {
+            kind: preset.kind.into_owned(),
+            name: preset.name.into_owned(),
+            params: preset
+                .params
+                .into_iter()
+                .map(|(name, value)| (name.into_owned(), value))
+                .collect(),
+        })
+        .collect();
+
+    let mut chords = SlotMap::with_capacity_and_key(raw.chords.len());
+
+    for chord in raw.chords {
+        let chord = Chord {
+            start: chord.start,
+            duration: chord.duration,
+            root: chord.root,
+            quality: chord.quality,
+            bass: chord.bass,
+            scale: Scale::new(chord.scale_root, chord.scale_mode),
+        };
+
+        if !chord.is_valid() {
+            bail!(ErrorKind::Deserialization, "invalid chord {chord:?}");
+        }
+
+        chords.insert(chord);
+    }
+
+    let mut modulators = SlotMap::with_capacity_and_key(raw.modulators.len());
+
+    for modulator in raw.modulators {
+        let source = match modulator.source {
+            ModulationSourceV6::Lfo { shape, rate, phase } => {
+                ModulationSource::Lfo(Lfo { shape, rate, phase })
+            }
+            ModulationSourceV6::EnvelopeFollower {
+                track_uuid,
+                attack,
+                release,
+            } => ModulationSource::EnvelopeFollower(EnvelopeFollower {
+                track_id: ctx.add_dep(track_uuid)?,
+                attack,
+                release,
+            }),
+            ModulationSourceV6::Macro { value } => ModulationSource::Macro { value },
+        };
+
+        let mut targets = Vec::with_capacity(modulator.targets.len());
+
+        for target in modulator.targets {
+            targets.push(ModulationTarget {
+                node_id: ctx.add_dep(target.node_uuid)?,
+                param: target.param.into_owned(),
+                depth: target.depth,
+                polarity: target.polarity,
+            });
+        }
+
+        let modulator = Modulator {
+            name: modulator.name.into_owned(),
+            source,
+            targets,
+        };
+
+        if !modulator.is_valid() {
+            bail!(
+                ErrorKind::Deserialization,
+                "invalid modulator {modulator:?}"
+            );
+        }
+
+        modulators.insert(modulator);
+    }
+
+    let mut scenes = SlotMap::with_capacity_and_key(raw.scenes.len());
+    let mut scene_order = Vec::with_capacity(raw.scenes.len());
+
+    for scene in raw.scenes {
+        let mut clips = BTreeMap::new();
+
+        for clip in scene.clips {
+            let item = match clip.item_kind {
+                ItemKind::Audio => ItemId::Audio(ctx.add_dep(clip.item_uuid)?),
+                ItemKind::Midi => ItemId::Midi(ctx.add_dep(clip.item_uuid)?),
+            };
+
+            if clip.length <= BeatTime::ZERO {
+                bail!(ErrorKind::Deserialization, "clip has a non-positive length");
+            }
+
+            let track_id = ctx.add_dep(clip.track_uuid)?;
+            clips.insert(
+                track_id,
+                Clip {
+                    item,
+                    length: clip.length,
+                },
+            );
+        }
+
+        scene_order.push(scenes.insert(Scene {
+            name: scene.name.into_owned(),
+            clips,
+        }));
+    }
+
+    let buses = raw
+        .buses
+        .into_iter()
+        .map(|uuid| ctx.add_dep(uuid))
+        .collect::<Result<Vec<_>>>()?;
+
     Ok(Arrangement {
         tempo_map_id,
         main_track_id,
-        name: raw.name.to_owned(),
+        name: raw.name.into_owned(),
+        markers,
+        video,
+        presets,
+        chords,
+        modulators,
+        scenes,
+        scene_order,
+        buses,
+        seed: raw.seed,
     })
 }
 
 define_version_enum! {
     enum Version {
         V1 = 1,
+        V2 = 2,
+        V3 = 3,
+        V4 = 4,
+        V5 = 5,
+        V6 = 6,
+        V7 = 7,
+        V8 = 8,
+        V9 = 9,
     }
 }
 
-type ArrangementLatest<'a> = ArrangementV1<'a>;
+type ArrangementLatest<'a> = ArrangementV9<'a>;
+type MarkerLatest<'a> = MarkerV2<'a>;
+type ArrangementVideoLatest = ArrangementVideoV3;
+type PresetLatest<'a> = PresetV4<'a>;
+type ChordLatest = ChordV5;
+type ModulatorLatest<'a> = ModulatorV6<'a>;
+type ModulationSourceLatest = ModulationSourceV6;
+type ModulationTargetLatest<'a> = ModulationTargetV6<'a>;
+type SceneLatest<'a> = SceneV7<'a>;
+type ClipLatest = ClipV7;
 
 #[derive(Debug, Serialize, Deserialize)]
 struct ArrangementV1<'a> {
     tempo_map_uuid: Uuid,
     main_track_uuid: Uuid,
-    name: &'a str,
+    #[serde(borrow)]
+    name: Cow<'a, str>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ArrangementV2<'a> {
+    tempo_map_uuid: Uuid,
+    main_track_uuid: Uuid,
+    #[serde(borrow)]
+    name: Cow<'a, str>,
+    #[serde(borrow)]
+    markers: Vec<MarkerV2<'a>>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct MarkerV2<'a> {
+    position: Time,
+    #[serde(borrow)]
+    name: Cow<'a, str>,
+    kind: MarkerKind,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ArrangementV3<'a> {
+    tempo_map_uuid: Uuid,
+    main_track_uuid: Uuid,
+    #[serde(borrow)]
+    name: Cow<'a, str>,
+    #[serde(borrow)]
+    markers: Vec<MarkerV2<'a>>,
+    video: Option<ArrangementVideoV3>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ArrangementVideoV3 {
+    asset_uuid: Uuid,
+    offset: RealTime,
+    width: u32,
+    height: u32,
+    frame_rate_num: u32,
+    frame_rate_den: u32,
+    duration: RealTime,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ArrangementV4<'a> {
+    tempo_map_uuid: Uuid,
+    main_track_uuid: Uuid,
+    #[serde(borrow)]
+    name: Cow<'a, str>,
+    #[serde(borrow)]
+    markers: Vec<MarkerV2<'a>>,
+    video: Option<ArrangementVideoV3>,
+    #[serde(borrow)]
+    presets: Vec<PresetV4<'a>>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct PresetV4<'a> {
+    #[serde(borrow)]
+    kind: Cow<'a, str>,
+    #[serde(borrow)]
+    name: Cow<'a, str>,
+    #[serde(borrow)]
+    params: BTreeMap<Cow<'a, str>, f32>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ArrangementV5<'a> {
+    tempo_map_uuid: Uuid,
+    main_track_uuid: Uuid,
+    #[serde(borrow)]
+    name: Cow<'a, str>,
+    #[serde(borrow)]
+    markers: Vec<MarkerV2<'a>>,
+    video: Option<ArrangementVideoV3>,
+    #[serde(borrow)]
+    presets: Vec<PresetV4<'a>>,
+    chords: Vec<ChordV5>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ChordV5 {
+    start: BeatTime,
+    duration: BeatTime,
+    root: PitchClass,
+    quality: ChordQuality,
+    bass: Option<PitchClass>,
+    scale_root: PitchClass,
+    scale_mode: ScaleMode,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ArrangementV6<'a> {
+    tempo_map_uuid: Uuid,
+    main_track_uuid: Uuid,
+    #[serde(borrow)]
+    name: Cow<'a, str>,
+    #[serde(borrow)]
+    markers: Vec<MarkerV2<'a>>,
+    video: Option<ArrangementVideoV3>,
+    #[serde(borrow)]
+    presets: Vec<PresetV4<'a>>,
+    chords: Vec<ChordV5>,
+    #[serde(borrow)]
+    modulators: Vec<ModulatorV6<'a>>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ModulatorV6<'a> {
+    #[serde(borrow)]
+    name: Cow<'a, str>,
+    source: ModulationSourceV6,
+    #[serde(borrow)]
+    targets: Vec<ModulationTargetV6<'a>>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+enum ModulationSourceV6 {
+    Lfo {
+        shape: LfoShape,
+        rate: f32,
+        phase: f32,
+    },
+    EnvelopeFollower {
+        track_uuid: Uuid,
+        attack: RealTime,
+        release: RealTime,
+    },
+    Macro {
+        value: f32,
+    },
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ModulationTargetV6<'a> {
+    node_uuid: Uuid,
+    #[serde(borrow)]
+    param: Cow<'a, str>,
+    depth: f32,
+    polarity: Polarity,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ArrangementV7<'a> {
+    tempo_map_uuid: Uuid,
+    main_track_uuid: Uuid,
+    #[serde(borrow)]
+    name: Cow<'a, str>,
+    #[serde(borrow)]
+    markers: Vec<MarkerV2<'a>>,
+    video: Option<ArrangementVideoV3>,
+    #[serde(borrow)]
+    presets: Vec<PresetV4<'a>>,
+    chords: Vec<ChordV5>,
+    #[serde(borrow)]
+    modulators: Vec<ModulatorV6<'a>>,
+    #[serde(borrow)]
+    scenes: Vec<SceneV7<'a>>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SceneV7<'a> {
+    #[serde(borrow)]
+    name: Cow<'a, str>,
+    clips: Vec<ClipV7>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ClipV7 {
+    track_uuid: Uuid,
+    item_kind: ItemKind,
+    item_uuid: Uuid,
+    length: BeatTime,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ArrangementV8<'a> {
+    tempo_map_uuid: Uuid,
+    main_track_uuid: Uuid,
+    #[serde(borrow)]
+    name: Cow<'a, str>,
+    #[serde(borrow)]
+    markers: Vec<MarkerV2<'a>>,
+    video: Option<ArrangementVideoV3>,
+    #[serde(borrow)]
+    presets: Vec<PresetV4<'a>>,
+    chords: Vec<ChordV5>,
+    #[serde(borrow)]
+    modulators: Vec<ModulatorV6<'a>>,
+    #[serde(borrow)]
+    scenes: Vec<SceneV7<'a>>,
+    buses: Vec<Uuid>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ArrangementV9<'a> {
+    tempo_map_uuid: Uuid,
+    main_track_uuid: Uuid,
+    #[serde(borrow)]
+    name: Cow<'a, str>,
+    #[serde(borrow)]
+    markers: Vec<MarkerV2<'a>>,
+    video: Option<ArrangementVideoV3>,
+    #[serde(borrow)]
+    presets: Vec<PresetV4<'a>>,
+    chords: Vec<ChordV5>,
+    #[serde(borrow)]
+    modulators: Vec<ModulatorV6<'a>>,
+    #[serde(borrow)]
+    scenes: Vec<SceneV7<'a>>,
+    buses: Vec<Uuid>,
+    seed: u64,
+}
+
+impl<'a> From<ArrangementV1<'a>> for ArrangementV2<'a> {
+    fn from(v: ArrangementV1<'a>) -> ArrangementV2<'a> {
+        ArrangementV2 {
+            tempo_map_uuid: v.tempo_map_uuid,
+            main_track_uuid: v.main_track_uuid,
+            name: v.name,
+            markers: Vec::new(),
+        }
+    }
+}
+
+impl<'a> From<ArrangementV2<'a>> for ArrangementV3<'a> {
+    fn from(v: ArrangementV2<'a>) -> ArrangementV3<'a> {
+        ArrangementV3 {
+            tempo_map_uuid: v.tempo_map_uuid,
+            main_track_uuid: v.main_track_uuid,
+            name: v.name,
+            markers: v.markers,
+            video: None,
+        }
+    }
+}
+
+impl<'a> From<ArrangementV3<'a>> for ArrangementV4<'a> {
+    fn from(v: ArrangementV3<'a>) -> ArrangementV4<'a> {
+        ArrangementV4 {
+            tempo_map_uuid: v.tempo_map_uuid,
+            main_track_uuid: v.main_track_uuid,
+            name: v.name,
+            markers: v.markers,
+            video: v.video,
+            presets: Vec::new(),
+        }
+    }
+}
+
+impl<'a> From<ArrangementV4<'a>> for ArrangementV5<'a> {
+    fn from(v: ArrangementV4<'a>) -> ArrangementV5<'a> {
+        ArrangementV5 {
+            tempo_map_uuid: v.tempo_map_uuid,
+            main_track_uuid: v.main_track_uuid,
+            name: v.name,
+            markers: v.markers,
+            video: v.video,
+            presets: v.presets,
+            chords: Vec::new(),
+        }
+    }
+}
+
+impl<'a> From<ArrangementV5<'a>> for ArrangementV6<'a> {
+    fn from(v: ArrangementV5<'a>) -> ArrangementV6<'a> {
+        ArrangementV6 {
+            tempo_map_uuid: v.tempo_map_uuid,
+            main_track_uuid: v.main_track_uuid,
+            name: v.name,
+            markers: v.markers,
+            video: v.video,
+            presets: v.presets,
+            chords: v.chords,
+            modulators: Vec::new(),
+        }
+    }
+}
+
+impl<'a> From<ArrangementV6<'a>> for ArrangementV7<'a> {
+    fn from(v: ArrangementV6<'a>) -> ArrangementV7<'a> {
+        ArrangementV7 {
+            tempo_map_uuid: v.tempo_map_uuid,
+            main_track_uuid: v.main_track_uuid,
+            name: v.name,
+            markers: v.markers,
+            video: v.video,
+            presets: v.presets,
+            chords: v.chords,
+            modulators: v.modulators,
+            scenes: Vec::new(),
+        }
+    }
+}
+
+impl<'a> From<ArrangementV7<'a>> for ArrangementV8<'a> {
+    fn from(v: ArrangementV7<'a>) -> ArrangementV8<'a> {
+        ArrangementV8 {
+            tempo_map_uuid: v.tempo_map_uuid,
+            main_track_uuid: v.main_track_uuid,
+            name: v.name,
+            markers: v.markers,
+            video: v.video,
+            presets: v.presets,
+            chords: v.chords,
+            modulators: v.modulators,
+            scenes: v.scenes,
+            buses: Vec::new(),
+        }
+    }
+}
+
+impl<'a> From<ArrangementV8<'a>> for ArrangementV9<'a> {
+    fn from(v: ArrangementV8<'a>) -> ArrangementV9<'a> {
+        ArrangementV9 {
+            tempo_map_uuid: v.tempo_map_uuid,
+            main_track_uuid: v.main_track_uuid,
+            name: v.name,
+            markers: v.markers,
+            video: v.video,
+            presets: v.presets,
+            chords: v.chords,
+            modulators: v.modulators,
+            scenes: v.scenes,
+            buses: v.buses,
+            // documents made before seeds existed all get the same one, so that they still render
+            // the same everywhere
+            seed: 0,
+        }
+    }
 }
@@ -0,0 +1,88 @@
+use std::fmt::Write;
+
+use rdaw_api::arrangement::MarkerKind;
+use rdaw_core::time::RealTime;
+
+const NANOS_PER_MILLI: i64 = 1_000_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ListedMarker<'a> {
+    pub name: &'a str,
+    pub start: RealTime,
+    pub kind: MarkerKind,
+}
+
+pub fn write_csv(markers: &[ListedMarker]) -> String {
+    let mut out = String::from("name,start,kind\n");
+
+    for marker in markers {
+        let kind = match marker.kind {
+            MarkerKind::Generic => "generic",
+            MarkerKind::CdTrack { .. } => "cd_track",
+        };
+
+        let _ = writeln!(
+            out,
+            "{},{},{kind}",
+            csv_field(marker.name),
+            secs(marker.start)
+        );
+    }
+
+    out
+}
+
+pub fn write_chapters(markers: &[ListedMarker]) -> String {
+    let mut out = String::new();
+
+    for marker in markers {
+        let millis = millis(marker.start);
+        let secs = millis / 1000;
+        let _ = writeln!(
+            out,
+            "{:02}:{:02}:{:02}.{:03} {}",
+            secs / 3600,
+            secs / 60 % 60,
+            secs % 60,
+            millis % 1000,
+            single_line(marker.name),
+        );
+    }
+
+    out
+}
+
+pub fn write_audacity_labels(markers: &[ListedMarker]) -> String {
+    let mut out = String::new();
+
+    for marker in markers {
+        // point labels, markers don't have an end
+        let start = secs(marker.start);
+        let _ = writeln!(out, "{start}\t{start}\t{}", single_line(marker.name));
+    }
+
+    out
+}
+
+fn millis(time: RealTime) -> i64 {
+    time.as_nanos().max(0) / NANOS_PER_MILLI
+}
+
+fn secs(time: RealTime) -> String {
+    let millis = millis(time);
+    format!("{}.{:03}", millis / 1000, millis % 1000)
+}
+
+fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.into()
+    }
+}
+
+fn single_line(text: &str) -> String {
+    text.chars()
+        .map(|c| if c.is_control() { ' ' } else { c })
+        .collect()
+}
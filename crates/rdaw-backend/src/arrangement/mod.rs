@@ -1,11 +1,24 @@
+pub mod cue;
 mod encoding;
+pub mod marker_list;
 mod ops;
+#[cfg(test)]
+mod tests;
 
-use rdaw_api::arrangement::ArrangementId;
+use std::mem;
+
+use rdaw_api::arrangement::{ArrangementId, Marker, MarkerId};
+use rdaw_api::bus::BusId;
+use rdaw_api::chord::{Chord, ChordId};
+use rdaw_api::launcher::{Clip, Scene, SceneId};
+use rdaw_api::modulation::{ModulationTarget, Modulator, ModulatorId};
 use rdaw_api::tempo_map::TempoMapId;
 use rdaw_api::track::TrackId;
+use rdaw_api::video::ArrangementVideo;
 use rdaw_api::Result;
+use slotmap::SlotMap;
 
+use crate::node::presets::Preset;
 use crate::object::{DeserializationContext, Object, ObjectId, ObjectType, SerializationContext};
 
 impl ObjectId for ArrangementId {
@@ -17,6 +30,18 @@ pub struct Arrangement {
     pub tempo_map_id: TempoMapId,
     pub main_track_id: TrackId,
     pub name: String,
+    pub markers: SlotMap<MarkerId, Marker>,
+    pub video: Option<ArrangementVideo>,
+    pub presets: Vec<Preset>,
+    pub chords: SlotMap<ChordId, Chord>,
+    pub modulators: SlotMap<ModulatorId, Modulator>,
+    pub scenes: SlotMap<SceneId, Scene>,
+    /// Order of the clip launcher's scenes.
+    pub scene_order: Vec<SceneId>,
+    pub buses: Vec<BusId>,
+    /// Seed of everything generated at random in the document, e.g. humanization, so that it's
+    /// the same on every machine. Only the seed of the document's root arrangement is used.
+    pub seed: u64,
 }
 
 impl Object for Arrangement {
@@ -31,4 +56,61 @@ impl Object for Arrangement {
     fn deserialize(ctx: &mut DeserializationContext<'_>, data: &[u8]) -> Result<Self> {
         self::encoding::deserialize(ctx, data)
     }
+
+    fn heap_size(&self) -> usize {
+        let markers = self
+            .markers
+            .values()
+            .map(|marker| marker.name.capacity())
+            .sum::<usize>();
+
+        let presets = self
+            .presets
+            .iter()
+            .map(|preset| {
+                let params = preset
+                    .params
+                    .keys()
+                    .map(|name| name.capacity() + mem::size_of::<(String, f32)>())
+                    .sum::<usize>();
+
+                preset.kind.capacity() + preset.name.capacity() + params
+            })
+            .sum::<usize>();
+
+        let modulators = self
+            .modulators
+            .values()
+            .map(|modulator| {
+                let targets = modulator
+                    .targets
+                    .iter()
+                    .map(|target| target.param.capacity())
+                    .sum::<usize>();
+
+                modulator.name.capacity()
+                    + modulator.targets.capacity() * mem::size_of::<ModulationTarget>()
+                    + targets
+            })
+            .sum::<usize>();
+
+        let scenes = self
+            .scenes
+            .values()
+            .map(|scene| scene.name.capacity() + scene.clips.len() * mem::size_of::<Clip>())
+            .sum::<usize>();
+
+        self.name.capacity()
+            + self.markers.capacity() * mem::size_of::<Marker>()
+            + markers
+            + self.presets.capacity() * mem::size_of::<Preset>()
+            + presets
+            + self.chords.capacity() * mem::size_of::<Chord>()
+            + self.modulators.capacity() * mem::size_of::<Modulator>()
+            + modulators
+            + self.scenes.capacity() * mem::size_of::<Scene>()
+            + scenes
+            + self.scene_order.capacity() * mem::size_of::<SceneId>()
+            + self.buses.capacity() * mem::size_of::<BusId>()
+    }
 }
@@ -1,14 +1,19 @@
 use rdaw_api::arrangement::{
     ArrangementId, ArrangementOperations, ArrangementRequest, ArrangementResponse,
+    ArrangementSnapshot, ArrangementTrack, Marker, MarkerFormat, MarkerId, MarkerKind,
 };
 use rdaw_api::document::DocumentId;
 use rdaw_api::tempo_map::TempoMapId;
-use rdaw_api::track::TrackId;
-use rdaw_api::{BackendProtocol, Result};
+use rdaw_api::track::{TrackId, TrackViewId};
+use rdaw_api::{bail, format_err, BackendProtocol, ErrorKind, Page, PageRequest, Result};
+use rdaw_core::path::{Utf8Path, Utf8PathBuf};
+use rdaw_core::time::RealTime;
 use rdaw_rpc::StreamId;
-use slotmap::Key;
+use slotmap::{Key, SlotMap};
 use tracing::instrument;
 
+use super::cue::{self, CueTrack};
+use super::marker_list::{self, ListedMarker};
 use super::Arrangement;
 use crate::object::ObjectKey;
 use crate::tempo_map::TempoMap;
@@ -36,6 +41,15 @@ impl Backend {
             tempo_map_id,
             main_track_id,
             name: String::new(),
+            markers: SlotMap::default(),
+            video: None,
+            presets: Vec::new(),
+            chords: SlotMap::default(),
+            modulators: SlotMap::default(),
+            scenes: SlotMap::default(),
+            scene_order: Vec::new(),
+            buses: Vec::new(),
+            seed: rand::random(),
         };
 
         let arrangement_id = self
@@ -53,6 +67,17 @@ impl Backend {
         Ok(arrangement_id)
     }
 
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn list_arrangements(
+        &self,
+        document_id: DocumentId,
+        page: PageRequest,
+    ) -> Result<Page<ArrangementId>> {
+        self.documents.ensure_has(document_id)?;
+        Ok(self.hub.arrangements.page_in_document(document_id, page))
+    }
+
     #[instrument(level = "trace", skip_all, err)]
     #[handler]
     pub fn subscribe_arrangement_name(&mut self, id: ArrangementId) -> Result<StreamId> {
@@ -83,10 +108,198 @@ impl Backend {
         Ok(arrangement.main_track_id)
     }
 
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn get_arrangement_snapshot(&mut self, id: ArrangementId) -> Result<ArrangementSnapshot> {
+        let arrangement = self.hub.arrangements.get_or_err(id)?;
+        let name = arrangement.name.clone();
+        let main_track = arrangement.main_track_id;
+        let tempo_map = &self.hub.tempo_maps[arrangement.tempo_map_id];
+
+        let mut tracks = Vec::new();
+
+        for track_id in self.arrangement_tracks(id) {
+            let summary = self.track_summary(track_id)?;
+
+            let view_id = TrackViewId {
+                track_id,
+                arrangement_id: id,
+            };
+
+            let view = self.track_view_cache.get_or_insert(&self.hub, &self.subscribers, view_id);
+            let mut items = view
+                .get_range(tempo_map, None, None)
+                .map(|(id, v)| (id, *v))
+                .collect::<Vec<_>>();
+            items.sort_by_key(|(_, v)| v.real_start);
+
+            tracks.push(ArrangementTrack { summary, items });
+        }
+
+        Ok(ArrangementSnapshot {
+            name,
+            main_track,
+            tracks,
+        })
+    }
+
     #[instrument(level = "trace", skip_all, err)]
     #[handler]
     pub fn get_arrangement_tempo_map(&self, id: ArrangementId) -> Result<TempoMapId> {
         let arrangement = self.hub.arrangements.get_or_err(id)?;
         Ok(arrangement.tempo_map_id)
     }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn get_arrangement_tempo(&self, id: ArrangementId) -> Result<f32> {
+        let arrangement = self.hub.arrangements.get_or_err(id)?;
+        Ok(self.hub.tempo_maps[arrangement.tempo_map_id].beats_per_minute())
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn set_arrangement_tempo(
+        &mut self,
+        id: ArrangementId,
+        beats_per_minute: f32,
+    ) -> Result<()> {
+        if !beats_per_minute.is_finite() || beats_per_minute <= 0.0 {
+            bail!(ErrorKind::NotSupported, "invalid tempo {beats_per_minute}");
+        }
+
+        let arrangement = self.hub.arrangements.get_or_err(id)?;
+        self.hub.tempo_maps[arrangement.tempo_map_id] = TempoMap::new(beats_per_minute);
+        self.track_view_cache.recompute_arrangement(&self.hub, id);
+
+        Ok(())
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn add_arrangement_marker(
+        &mut self,
+        id: ArrangementId,
+        marker: Marker,
+    ) -> Result<MarkerId> {
+        let arrangement = self.hub.arrangements.get_mut_or_err(id)?;
+        Ok(arrangement.markers.insert(marker))
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn get_arrangement_marker(&self, id: ArrangementId, marker_id: MarkerId) -> Result<Marker> {
+        let arrangement = self.hub.arrangements.get_or_err(id)?;
+        arrangement.markers.get(marker_id).cloned().ok_or_else(|| {
+            format_err!(ErrorKind::InvalidId, "{marker_id:?} doesn't exist in {id:?}")
+        })
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn get_arrangement_markers(&self, id: ArrangementId) -> Result<Vec<(MarkerId, Marker)>> {
+        let arrangement = self.hub.arrangements.get_or_err(id)?;
+        let tempo_map = &self.hub.tempo_maps[arrangement.tempo_map_id];
+
+        let mut markers = arrangement
+            .markers
+            .iter()
+            .map(|(marker_id, marker)| (marker_id, marker.clone()))
+            .collect::<Vec<_>>();
+        markers.sort_by_key(|(marker_id, marker)| (tempo_map.to_real(marker.position), *marker_id));
+
+        Ok(markers)
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn remove_arrangement_marker(
+        &mut self,
+        id: ArrangementId,
+        marker_id: MarkerId,
+    ) -> Result<()> {
+        let arrangement = self.hub.arrangements.get_mut_or_err(id)?;
+        arrangement.markers.remove(marker_id);
+        Ok(())
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn export_arrangement_cue(
+        &mut self,
+        id: ArrangementId,
+        audio_path: Utf8PathBuf,
+        path: Utf8PathBuf,
+    ) -> Result<()> {
+        let audio_path = path
+            .parent()
+            .and_then(|dir| audio_path.strip_prefix(dir).ok())
+            .unwrap_or(&audio_path);
+
+        let sheet = self.cd_cue_sheet(id, audio_path)?;
+        std::fs::write(&path, sheet)?;
+
+        Ok(())
+    }
+
+    /// Cue sheet with a track for every CD track marker of the arrangement.
+    pub fn cd_cue_sheet(&self, id: ArrangementId, audio_path: &Utf8Path) -> Result<String> {
+        let markers = self.get_arrangement_markers(id)?;
+        let arrangement = &self.hub.arrangements[id];
+        let tempo_map = &self.hub.tempo_maps[arrangement.tempo_map_id];
+
+        let tracks = markers
+            .iter()
+            .filter_map(|(_, marker)| match marker.kind {
+                MarkerKind::CdTrack { pregap } => Some(CueTrack {
+                    title: &marker.name,
+                    start: tempo_map.to_real(marker.position),
+                    pregap,
+                }),
+                MarkerKind::Generic => None,
+            })
+            .collect::<Vec<_>>();
+
+        cue::write_cue_sheet(&arrangement.name, audio_path, &tracks)
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn export_markers(&self, id: ArrangementId, format: MarkerFormat) -> Result<String> {
+        let markers = self.get_arrangement_markers(id)?;
+        let arrangement = &self.hub.arrangements[id];
+        let tempo_map = &self.hub.tempo_maps[arrangement.tempo_map_id];
+
+        let markers = markers
+            .iter()
+            .map(|(_, marker)| ListedMarker {
+                name: &marker.name,
+                start: tempo_map.to_real(marker.position),
+                kind: marker.kind,
+            })
+            .collect::<Vec<_>>();
+
+        let text = match format {
+            MarkerFormat::Csv => marker_list::write_csv(&markers),
+            MarkerFormat::Chapters => marker_list::write_chapters(&markers),
+            MarkerFormat::AudacityLabels => marker_list::write_audacity_labels(&markers),
+            MarkerFormat::Cue { audio_path } => {
+                let tracks = markers
+                    .iter()
+                    .map(|marker| CueTrack {
+                        title: marker.name,
+                        start: marker.start,
+                        pregap: match marker.kind {
+                            MarkerKind::CdTrack { pregap } => pregap,
+                            MarkerKind::Generic => RealTime::ZERO,
+                        },
+                    })
+                    .collect::<Vec<_>>();
+
+                cue::write_cue_sheet(&arrangement.name, &audio_path, &tracks)?
+            }
+        };
+
+        Ok(text)
+    }
 }
@@ -0,0 +1,295 @@
+use rdaw_api::arrangement::{ArrangementOperations, Marker, MarkerFormat, MarkerKind};
+use rdaw_api::document::DocumentOperations;
+use rdaw_api::time::Time;
+use rdaw_api::track::TrackOperations;
+use rdaw_api::{assert_err, ErrorKind, PageRequest, Result};
+use rdaw_core::path::Utf8Path;
+use rdaw_core::time::RealTime;
+
+use super::cue::{write_cue_sheet, CueTrack};
+use crate::tests::{beats, run_test, ProjectBuilder};
+
+fn cd_track(name: &str, secs: f64, pregap: f64) -> Marker {
+    Marker {
+        position: Time::Real(RealTime::from_secs_f64(secs)),
+        name: name.into(),
+        kind: MarkerKind::CdTrack {
+            pregap: RealTime::from_secs_f64(pregap),
+        },
+    }
+}
+
+#[test]
+fn list_arrangements() -> Result<()> {
+    run_test(|client| async move {
+        let document_id = client.create_document().await?;
+        let first_id = client.get_document_arrangement(document_id).await?;
+        let second_id = client.create_arrangement(document_id).await?;
+        let other_document_id = client.create_document().await?;
+
+        let mut arrangements = client
+            .list_arrangements(document_id, PageRequest::ALL)
+            .await?
+            .items;
+        arrangements.sort();
+        let mut expected = vec![first_id, second_id];
+        expected.sort();
+        assert_eq!(arrangements, expected);
+
+        let other = client
+            .list_arrangements(other_document_id, PageRequest::ALL)
+            .await?;
+        assert_eq!(other.total, 1);
+
+        Ok(())
+    })
+}
+
+#[test]
+fn markers() -> Result<()> {
+    run_test(|client| async move {
+        let document_id = client.create_document().await?;
+        let arrangement_id = client.get_document_arrangement(document_id).await?;
+
+        let second = cd_track("Second", 10.0, 0.0);
+        let first = cd_track("First", 0.0, 2.0);
+
+        let second_id = client
+            .add_arrangement_marker(arrangement_id, second.clone())
+            .await?;
+        let first_id = client
+            .add_arrangement_marker(arrangement_id, first.clone())
+            .await?;
+
+        assert_eq!(
+            client.get_arrangement_marker(arrangement_id, first_id).await?,
+            first
+        );
+        assert_eq!(
+            client.get_arrangement_markers(arrangement_id).await?,
+            vec![(first_id, first.clone()), (second_id, second)]
+        );
+
+        client
+            .remove_arrangement_marker(arrangement_id, second_id)
+            .await?;
+        assert_eq!(
+            client.get_arrangement_markers(arrangement_id).await?,
+            vec![(first_id, first)]
+        );
+        assert_err!(
+            client.get_arrangement_marker(arrangement_id, second_id).await,
+            ErrorKind::InvalidId
+        );
+
+        Ok(())
+    })
+}
+
+#[test]
+fn export_cue() -> Result<()> {
+    run_test(|client| async move {
+        let document_id = client.create_document().await?;
+        let arrangement_id = client.get_document_arrangement(document_id).await?;
+        client
+            .set_arrangement_name(arrangement_id, "Album".into())
+            .await?;
+
+        let dir = tempfile::tempdir()?;
+        let dir = Utf8Path::from_path(dir.path()).unwrap();
+        let path = dir.join("album.cue");
+
+        let res = client
+            .export_arrangement_cue(arrangement_id, dir.join("album.wav"), path.clone())
+            .await;
+        assert_err!(res, ErrorKind::NotFound);
+
+        let generic = Marker {
+            position: Time::Real(RealTime::from_secs(5)),
+            name: "Note".into(),
+            kind: MarkerKind::Generic,
+        };
+
+        for marker in [cd_track("Intro", 0.0, 2.0), generic, cd_track("Outro", 61.0, 1.0)] {
+            client.add_arrangement_marker(arrangement_id, marker).await?;
+        }
+
+        client
+            .export_arrangement_cue(arrangement_id, dir.join("album.wav"), path.clone())
+            .await?;
+
+        let sheet = std::fs::read_to_string(&path)?;
+        assert_eq!(
+            sheet,
+            "REM GENERATOR \"rdaw\"\n\
+             TITLE \"Album\"\n\
+             FILE \"album.wav\" WAVE\n  \
+               TRACK 01 AUDIO\n    \
+                 TITLE \"Intro\"\n    \
+                 PREGAP 00:02:00\n    \
+                 INDEX 01 00:00:00\n  \
+               TRACK 02 AUDIO\n    \
+                 TITLE \"Outro\"\n    \
+                 INDEX 00 01:00:00\n    \
+                 INDEX 01 01:01:00\n"
+        );
+
+        Ok(())
+    })
+}
+
+#[test]
+fn cue_gaps() -> Result<()> {
+    let tracks = [
+        CueTrack {
+            title: "A \"quoted\" title",
+            start: RealTime::from_secs(1),
+            pregap: RealTime::from_secs(3),
+        },
+        CueTrack {
+            title: "B",
+            start: RealTime::from_secs_f64(2.5),
+            pregap: RealTime::from_secs(5),
+        },
+    ];
+
+    let sheet = write_cue_sheet("Disc", Utf8Path::new("disc.bin"), &tracks)?;
+    let lines = sheet.lines().map(str::trim).collect::<Vec<_>>();
+
+    assert_eq!(
+        lines,
+        [
+            "REM GENERATOR \"rdaw\"",
+            "TITLE \"Disc\"",
+            "FILE \"disc.bin\" BINARY",
+            "TRACK 01 AUDIO",
+            "TITLE \"A 'quoted' title\"",
+            "PREGAP 00:02:00",
+            "INDEX 00 00:00:00",
+            "INDEX 01 00:01:00",
+            "TRACK 02 AUDIO",
+            "TITLE \"B\"",
+            "INDEX 00 00:01:00",
+            "INDEX 01 00:02:37",
+        ]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn export_markers() -> Result<()> {
+    run_test(|client| async move {
+        let document_id = client.create_document().await?;
+        let arrangement_id = client.get_document_arrangement(document_id).await?;
+
+        let generic = Marker {
+            position: Time::Real(RealTime::from_secs(5)),
+            name: "Note, \"quoted\"".into(),
+            kind: MarkerKind::Generic,
+        };
+
+        for marker in [cd_track("Outro", 3661.5, 1.0), generic, cd_track("Intro", 0.0, 0.0)] {
+            client.add_arrangement_marker(arrangement_id, marker).await?;
+        }
+
+        let csv = client
+            .export_markers(arrangement_id, MarkerFormat::Csv)
+            .await?;
+        assert_eq!(
+            csv,
+            "name,start,kind\n\
+             Intro,0.000,cd_track\n\
+             \"Note, \"\"quoted\"\"\",5.000,generic\n\
+             Outro,3661.500,cd_track\n"
+        );
+
+        let chapters = client
+            .export_markers(arrangement_id, MarkerFormat::Chapters)
+            .await?;
+        assert_eq!(
+            chapters,
+            "00:00:00.000 Intro\n\
+             00:00:05.000 Note, \"quoted\"\n\
+             01:01:01.500 Outro\n"
+        );
+
+        let labels = client
+            .export_markers(arrangement_id, MarkerFormat::AudacityLabels)
+            .await?;
+        assert_eq!(
+            labels,
+            "0.000\t0.000\tIntro\n\
+             5.000\t5.000\tNote, \"quoted\"\n\
+             3661.500\t3661.500\tOutro\n"
+        );
+
+        // generic markers become tracks too
+        let format = MarkerFormat::Cue {
+            audio_path: "show.wav".into(),
+        };
+        let sheet = client.export_markers(arrangement_id, format).await?;
+        assert!(sheet.contains("TRACK 02 AUDIO\n    TITLE \"Note, 'quoted'\"\n"));
+        assert!(sheet.contains("TRACK 03 AUDIO"));
+
+        Ok(())
+    })
+}
+
+#[test]
+fn arrangement_snapshot() -> Result<()> {
+    run_test(|client| async move {
+        let project = ProjectBuilder::new()
+            .track("Drums", |t| t.item(beats(4), beats(2)).item(beats(0), beats(4)))
+            .track("Group", |t| t.child("Bass", |t| t.locked()))
+            .build(&client)
+            .await?;
+
+        client
+            .set_arrangement_name(project.arrangement_id, "Song".into())
+            .await?;
+
+        let snapshot = client
+            .get_arrangement_snapshot(project.arrangement_id)
+            .await?;
+
+        assert_eq!(snapshot.name, "Song");
+        assert_eq!(snapshot.main_track, project.main_track_id);
+
+        let track_ids = snapshot
+            .tracks
+            .iter()
+            .map(|v| v.summary.id)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            track_ids,
+            vec![
+                project.main_track_id,
+                project.track("Drums"),
+                project.track("Group"),
+                project.track("Bass"),
+            ]
+        );
+
+        let drums = &snapshot.tracks[1];
+        assert_eq!(drums.summary.name, "Drums");
+        assert_eq!(
+            drums.items.iter().map(|(id, _)| *id).collect::<Vec<_>>(),
+            vec![project.items("Drums")[1], project.items("Drums")[0]]
+        );
+
+        let view_range = client
+            .get_track_view_range(project.view("Drums"), None, None)
+            .await?;
+        assert_eq!(drums.items.len(), view_range.len());
+
+        assert!(snapshot.tracks[3].summary.locked);
+        let hierarchy = snapshot.hierarchy();
+        assert_eq!(
+            hierarchy.children(project.track("Group")).collect::<Vec<_>>(),
+            vec![project.track("Bass")]
+        );
+
+        Ok(())
+    })
+}
@@ -1,3 +1,5 @@
+use std::borrow::Cow;
+
 use blake3::Hash;
 use rdaw_api::Result;
 use rdaw_core::path::Utf8Path;
@@ -8,35 +10,45 @@ use crate::define_version_enum;
 use crate::document::encoding;
 use crate::object::{DeserializationContext, SerializationContext};
 
-pub fn serialize(_ctx: &mut SerializationContext<'_>, asset: &Asset) -> Result<Vec<u8>> {
+pub fn serialize(ctx: &mut SerializationContext<'_>, asset: &Asset) -> Result<Vec<u8>> {
+    let path;
     let raw = match asset {
-        Asset::External(asset) => AssetLatest::External {
-            path: &asset.path,
-            hash: asset.hash,
-            size: asset.size,
-        },
+        Asset::External(asset) => {
+            path = ctx.portable_path(&asset.path);
+            AssetLatest::External {
+                path: Cow::Borrowed(&path),
+                hash: asset.hash,
+                size: asset.size,
+            }
+        }
         Asset::Embedded(asset) => AssetLatest::Embedded {
             hash: asset.hash,
             size: asset.size,
         },
     };
 
-    encoding::serialize(Version::LATEST.as_u32(), &raw)
+    encoding::serialize(ctx.format(), Version::LATEST.as_u32(), &raw)
 }
 
-pub fn deserialize(_ctx: &mut DeserializationContext<'_>, data: &[u8]) -> Result<Asset> {
-    let (version, data) = encoding::extract_version(data)?;
-    let raw = match Version::from_u32(version)? {
-        Version::V1 => encoding::deserialize::<AssetLatest>(data)?,
-    };
-
-    let asset = match raw {
-        AssetV1::External { path, hash, size } => Asset::External(ExternalAsset {
-            path: path.into(),
-            hash,
-            size,
-        }),
-        AssetV1::Embedded { hash, size } => Asset::Embedded(EmbeddedAsset { hash, size }),
+pub fn deserialize(ctx: &mut DeserializationContext<'_>, data: &[u8]) -> Result<Asset> {
+    let (version, data) = encoding::extract_version(ctx.format(), data)?;
+    let asset = match Version::from_u32(version)? {
+        Version::V1 => match encoding::deserialize::<AssetV1>(ctx.format(), data)? {
+            AssetV1::External { path, hash, size } => Asset::External(ExternalAsset {
+                path: path.into(),
+                hash,
+                size,
+            }),
+            AssetV1::Embedded { hash, size } => Asset::Embedded(EmbeddedAsset { hash, size }),
+        },
+        Version::V2 => match encoding::deserialize::<AssetV2>(ctx.format(), data)? {
+            AssetV2::External { path, hash, size } => Asset::External(ExternalAsset {
+                path: ctx.resolve_path(&path)?,
+                hash,
+                size,
+            }),
+            AssetV2::Embedded { hash, size } => Asset::Embedded(EmbeddedAsset { hash, size }),
+        },
     };
 
     Ok(asset)
@@ -45,10 +57,11 @@ pub fn deserialize(_ctx: &mut DeserializationContext<'_>, data: &[u8]) -> Result
 define_version_enum! {
     enum Version {
         V1 = 1,
+        V2 = 2,
     }
 }
 
-type AssetLatest<'a> = AssetV1<'a>;
+type AssetLatest<'a> = AssetV2<'a>;
 
 #[derive(Debug, Serialize, Deserialize)]
 enum AssetV1<'a> {
@@ -63,3 +76,17 @@ enum AssetV1<'a> {
         size: u64,
     },
 }
+
+#[derive(Debug, Serialize, Deserialize)]
+enum AssetV2<'a> {
+    External {
+        #[serde(borrow)]
+        path: Cow<'a, str>,
+        hash: Hash,
+        size: u64,
+    },
+    Embedded {
+        hash: Hash,
+        size: u64,
+    },
+}
@@ -0,0 +1,51 @@
+use rdaw_api::audio::AudioInputStream as _;
+use rdaw_api::media::{MediaInput as _, OpenMediaInput as _};
+use rdaw_api::{bail, ErrorKind, Result};
+use rdaw_ffmpeg::MediaInput;
+use rdaw_rpc::CancellationToken;
+
+use super::AssetReader;
+use crate::ensure_not_cancelled;
+use crate::recording::encode_wav;
+
+/// Decodes the audio stream of a file into a WAV file. Progress is reported with the number of
+/// frames decoded and their fraction of the stream, whenever the fraction grows by a percent.
+pub fn decode_to_wav(
+    reader: AssetReader,
+    cancel: &CancellationToken,
+    mut progress: impl FnMut(u64, f32),
+) -> Result<Vec<u8>> {
+    let mut media = MediaInput::open(reader)?;
+    let Some(mut stream) = media.get_audio_stream()? else {
+        bail!(ErrorKind::NotFound, "file doesn't have an audio stream");
+    };
+
+    let num_channels = stream.metadata().channels.len().max(1);
+    let sample_rate = stream.metadata().sample_rate;
+    let total_frames = stream.metadata().duration.as_secs_f64() * f64::from(sample_rate);
+
+    let mut samples = Vec::new();
+    let mut reported = 0.0;
+
+    loop {
+        ensure_not_cancelled(cancel)?;
+
+        let frame = stream.next_frame()?;
+        if frame.is_empty() {
+            break;
+        }
+
+        samples.extend_from_slice(frame);
+        let num_frames = (samples.len() / num_channels) as u64;
+
+        if total_frames > 0.0 {
+            let fraction = (num_frames as f64 / total_frames).min(1.0) as f32;
+            if fraction - reported >= 0.01 {
+                reported = fraction;
+                progress(num_frames, fraction);
+            }
+        }
+    }
+
+    encode_wav(&samples, num_channels as u16, sample_rate)
+}
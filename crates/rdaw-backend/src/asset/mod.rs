@@ -1,5 +1,7 @@
 mod encoding;
+mod import;
 mod ops;
+mod path;
 mod reader;
 #[cfg(test)]
 mod tests;
@@ -9,6 +11,7 @@ use rdaw_api::asset::AssetId;
 use rdaw_api::Result;
 use rdaw_core::path::{Utf8Path, Utf8PathBuf};
 
+pub use self::path::PathVariables;
 pub use self::reader::AssetReader;
 use crate::object::{DeserializationContext, Object, ObjectId, ObjectType, SerializationContext};
 
@@ -70,4 +73,11 @@ impl Object for Asset {
     fn deserialize(ctx: &mut DeserializationContext<'_>, data: &[u8]) -> Result<Self> {
         self::encoding::deserialize(ctx, data)
     }
+
+    fn heap_size(&self) -> usize {
+        match self {
+            Asset::External(v) => v.path.capacity(),
+            Asset::Embedded(_) => 0,
+        }
+    }
 }
@@ -2,14 +2,18 @@ use std::fs::File;
 use std::io::Write;
 
 use blake3::Hasher;
-use rdaw_api::asset::{AssetId, AssetMetadata, AssetOperations, AssetRequest, AssetResponse};
+use rdaw_api::asset::{
+    AssetId, AssetImportProgress, AssetMetadata, AssetOperations, AssetRequest, AssetResponse,
+};
 use rdaw_api::document::DocumentId;
 use rdaw_api::error::ResultExt;
+use rdaw_api::stats::TaskQueue;
 use rdaw_api::{bail, BackendProtocol, Error, ErrorKind, Result};
 use rdaw_core::path::Utf8PathBuf;
-use rdaw_rpc::Responder;
+use rdaw_rpc::{CancellationToken, Responder, StreamId};
 use tracing::instrument;
 
+use super::import::decode_to_wav;
 use super::{Asset, AssetReader, EmbeddedAsset, ExternalAsset};
 use crate::document::Compression;
 use crate::object::ObjectKey;
@@ -30,7 +34,7 @@ impl Backend {
         let file = File::open(&path).with_context(|| format!("failed to open `{path}`]"))?;
 
         let queue = self.queue.clone();
-        self.spawn(async move {
+        self.spawn(TaskQueue::Io, async move {
             let mut hasher = Hasher::new();
 
             hasher
@@ -69,7 +73,7 @@ impl Backend {
         let mut blob = document.create_blob(Compression::Zstd)?;
 
         let queue = self.queue.clone();
-        self.spawn(async move {
+        self.spawn(TaskQueue::Io, async move {
             blob.write_all(&data)?;
             let hash = blob.save()?;
             let size = data.len() as u64;
@@ -91,6 +95,72 @@ impl Backend {
         Ok(())
     }
 
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn import_audio_asset(
+        &mut self,
+        responder: impl Responder<AssetId, Error>,
+        cancel: CancellationToken,
+        document_id: DocumentId,
+        path: Utf8PathBuf,
+    ) -> Result<()> {
+        let document = self.documents.get_or_err(document_id)?;
+        let mut blob = document.create_blob(Compression::Zstd)?;
+
+        let file = File::open(&path).with_context(|| format!("failed to open `{path}`"))?;
+        let reader = AssetReader::from_file(file);
+
+        let queue = self.queue.clone();
+        self.spawn(TaskQueue::Decode, async move {
+            let progress_queue = queue.clone();
+            let progress_path = path.clone();
+            let progress = move |frames_decoded, progress| {
+                let path = progress_path.clone();
+                progress_queue.defer(move |this: &mut Backend| {
+                    this.subscribers.asset_import_progress.notify(
+                        document_id,
+                        AssetImportProgress {
+                            path,
+                            frames_decoded,
+                            progress,
+                        },
+                    );
+                    std::future::ready(Ok(()))
+                });
+            };
+
+            let res = decode_to_wav(reader, &cancel, progress)
+                .with_context(|| format!("failed to import `{path}`"))
+                .and_then(|data| {
+                    blob.write_all(&data)?;
+                    let hash = blob.save()?;
+                    let size = data.len() as u64;
+                    Ok(Asset::Embedded(EmbeddedAsset { hash, size }))
+                });
+
+            queue.defer(move |this: &mut Backend| {
+                let res = res.map(|asset| {
+                    this.hub
+                        .assets
+                        .insert(ObjectKey::new_random(document_id), asset)
+                });
+
+                responder.respond(res)
+            });
+
+            Ok(())
+        });
+
+        Ok(())
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn subscribe_asset_import_progress(&mut self, document_id: DocumentId) -> Result<StreamId> {
+        self.documents.ensure_has(document_id)?;
+        Ok(self.subscribers.asset_import_progress.subscribe(document_id))
+    }
+
     #[instrument(level = "trace", skip_all, err)]
     #[handler]
     pub fn get_asset_metadata(&self, id: AssetId) -> Result<AssetMetadata> {
@@ -102,6 +172,32 @@ impl Backend {
         })
     }
 
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn get_asset_path_variables(&self) -> Result<Vec<(String, Utf8PathBuf)>> {
+        let vars = self
+            .path_variables
+            .iter()
+            .map(|(name, path)| (name.to_owned(), path.to_path_buf()))
+            .collect();
+        Ok(vars)
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn set_asset_path_variable(
+        &mut self,
+        name: String,
+        path: Option<Utf8PathBuf>,
+    ) -> Result<()> {
+        match path {
+            Some(path) => self.path_variables.set(name, path)?,
+            None => self.path_variables.remove(&name),
+        }
+
+        self.store_settings()
+    }
+
     pub fn open_asset(&self, id: AssetId) -> Result<AssetReader> {
         let asset = self.hub.assets.get_or_err(id)?;
 
@@ -0,0 +1,88 @@
+use std::collections::BTreeMap;
+
+use rdaw_api::{bail, format_err, ErrorKind, Result};
+use rdaw_core::path::{Utf8Path, Utf8PathBuf};
+
+#[derive(Debug, Clone, Default)]
+pub struct PathVariables {
+    vars: BTreeMap<String, Utf8PathBuf>,
+}
+
+impl PathVariables {
+    pub fn new() -> PathVariables {
+        PathVariables::default()
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Utf8Path> {
+        self.vars.get(name).map(|v| v.as_path())
+    }
+
+    pub fn set(&mut self, name: String, path: Utf8PathBuf) -> Result<()> {
+        if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            bail!(ErrorKind::NotSupported, "invalid path variable name `{name}`");
+        }
+
+        if !path.is_absolute() {
+            bail!(
+                ErrorKind::NotSupported,
+                "path variable `{name}` must be absolute, got `{path}`"
+            );
+        }
+
+        self.vars.insert(name, path);
+        Ok(())
+    }
+
+    pub fn remove(&mut self, name: &str) {
+        self.vars.remove(name);
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Utf8Path)> + '_ {
+        self.vars.iter().map(|(k, v)| (k.as_str(), v.as_path()))
+    }
+
+    pub fn expand(&self, base_dir: Option<&Utf8Path>, path: &str) -> Result<Utf8PathBuf> {
+        if let Some(rest) = path.strip_prefix("${") {
+            let Some((name, rest)) = rest.split_once('}') else {
+                bail!(ErrorKind::Deserialization, "unterminated path variable in `{path}`");
+            };
+
+            let var = self.get(name).ok_or_else(|| {
+                format_err!(ErrorKind::NotFound, "undefined path variable `{name}`")
+            })?;
+
+            return Ok(var.join(rest.trim_start_matches('/')));
+        }
+
+        let path = Utf8Path::new(path);
+
+        match base_dir {
+            Some(base_dir) if path.is_relative() => Ok(base_dir.join(path)),
+            _ => Ok(path.to_path_buf()),
+        }
+    }
+
+    pub fn shorten(&self, base_dir: Option<&Utf8Path>, path: &Utf8Path) -> String {
+        let var = self
+            .vars
+            .iter()
+            .filter_map(|(name, var)| Some((name, path.strip_prefix(var).ok()?)))
+            .min_by_key(|(_, rest)| rest.components().count());
+
+        if let Some((name, rest)) = var {
+            if rest.as_str().is_empty() {
+                return format!("${{{name}}}");
+            }
+
+            return format!("${{{name}}}/{rest}");
+        }
+
+        if let Some(rest) = base_dir.and_then(|base_dir| path.strip_prefix(base_dir).ok()) {
+            if !rest.as_str().is_empty() {
+                return rest.to_string();
+            }
+        }
+
+        path.to_string()
+    }
+}
@@ -1,5 +1,5 @@
 use std::fs::File;
-use std::io::Read;
+use std::io::{Read, Seek, SeekFrom};
 
 use crate::document::BlobReader;
 
@@ -36,3 +36,12 @@ impl Read for AssetReader {
         }
     }
 }
+
+impl Seek for AssetReader {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        match &mut self.inner {
+            Inner::File(v) => v.seek(pos),
+            Inner::Blob(v) => v.seek(pos),
+        }
+    }
+}
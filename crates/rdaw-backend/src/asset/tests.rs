@@ -1,11 +1,13 @@
 use std::io::Write;
 
+use futures::StreamExt;
 use rdaw_api::asset::{AssetMetadata, AssetOperations};
 use rdaw_api::document::DocumentOperations;
-use rdaw_api::Result;
-use rdaw_core::path::Utf8PathBuf;
+use rdaw_api::{assert_err, ErrorKind, Result};
+use rdaw_core::path::{Utf8Path, Utf8PathBuf};
 use tempfile::NamedTempFile;
 
+use super::PathVariables;
 use crate::tests::run_test;
 
 #[test]
@@ -65,3 +67,117 @@ fn create_embedded_asset() -> Result<()> {
         Ok(())
     })
 }
+
+#[test]
+fn import_audio_asset() -> Result<()> {
+    run_test(|client| async move {
+        let path = Utf8Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("../rdaw-ffmpeg/tests/samples/220_Hz_sine_wave.ogg");
+
+        let document_id = client.create_document().await?;
+        let mut stream = client.subscribe_asset_import_progress(document_id).await?;
+
+        let asset_id = client.import_audio_asset(document_id, path.clone()).await?;
+
+        let metadata = client.get_asset_metadata(asset_id).await?;
+        assert_eq!(metadata.path, None);
+        assert!(metadata.size > 44);
+
+        let progress = stream.next().await.unwrap();
+        assert_eq!(progress.path, path);
+        assert!(progress.frames_decoded > 0);
+        assert!(progress.progress > 0.0 && progress.progress <= 1.0);
+
+        assert_err!(
+            client
+                .import_audio_asset(document_id, "/nonexistent.wav".into())
+                .await,
+            ErrorKind::NotFound,
+        );
+
+        Ok(())
+    })
+}
+
+#[test]
+fn expand_path_variables() -> Result<()> {
+    let mut vars = PathVariables::new();
+    vars.set("SAMPLES".into(), "/mnt/samples".into())?;
+
+    let base_dir = Some(Utf8Path::new("/home/user/project"));
+
+    assert_eq!(
+        vars.expand(base_dir, "${SAMPLES}/drums/kick.wav")?,
+        "/mnt/samples/drums/kick.wav"
+    );
+    assert_eq!(
+        vars.expand(base_dir, "audio/take1.wav")?,
+        "/home/user/project/audio/take1.wav"
+    );
+    assert_eq!(vars.expand(base_dir, "/tmp/a.wav")?, "/tmp/a.wav");
+    assert_eq!(vars.expand(None, "audio/take1.wav")?, "audio/take1.wav");
+
+    assert_err!(vars.expand(base_dir, "${LOOPS}/a.wav"), ErrorKind::NotFound);
+    assert_err!(vars.expand(base_dir, "${SAMPLES"), ErrorKind::Deserialization);
+
+    Ok(())
+}
+
+#[test]
+fn shorten_path_variables() -> Result<()> {
+    let mut vars = PathVariables::new();
+    vars.set("SAMPLES".into(), "/mnt/samples".into())?;
+    vars.set("DRUMS".into(), "/mnt/samples/drums".into())?;
+
+    let base_dir = Some(Utf8Path::new("/home/user/project"));
+
+    assert_eq!(
+        vars.shorten(base_dir, "/mnt/samples/drums/kick.wav".into()),
+        "${DRUMS}/kick.wav"
+    );
+    assert_eq!(
+        vars.shorten(base_dir, "/mnt/samples/bass.wav".into()),
+        "${SAMPLES}/bass.wav"
+    );
+    assert_eq!(
+        vars.shorten(base_dir, "/home/user/project/audio/take1.wav".into()),
+        "audio/take1.wav"
+    );
+    assert_eq!(vars.shorten(base_dir, "/tmp/a.wav".into()), "/tmp/a.wav");
+    assert_eq!(vars.shorten(None, "/tmp/a.wav".into()), "/tmp/a.wav");
+
+    assert_err!(
+        vars.set("NOT VALID".into(), "/tmp".into()),
+        ErrorKind::NotSupported
+    );
+    assert_err!(
+        vars.set("RELATIVE".into(), "tmp".into()),
+        ErrorKind::NotSupported
+    );
+
+    Ok(())
+}
+
+#[test]
+fn get_set_asset_path_variables() -> Result<()> {
+    run_test(|client| async move {
+        assert!(client.get_asset_path_variables().await?.is_empty());
+
+        client
+            .set_asset_path_variable("SAMPLES".into(), Some("/mnt/samples".into()))
+            .await?;
+
+        assert_eq!(
+            client.get_asset_path_variables().await?,
+            vec![(String::from("SAMPLES"), Utf8PathBuf::from("/mnt/samples"))]
+        );
+
+        client
+            .set_asset_path_variable("SAMPLES".into(), None)
+            .await?;
+
+        assert!(client.get_asset_path_variables().await?.is_empty());
+
+        Ok(())
+    })
+}
@@ -0,0 +1,91 @@
+use std::borrow::Cow;
+
+use rdaw_api::automation::{AutomationPoint, AutomationTarget, CurveShape};
+use rdaw_api::time::Time;
+use rdaw_api::{bail, ErrorKind, Result};
+use serde::{Deserialize, Serialize};
+use slotmap::SlotMap;
+
+use super::AutomationLane;
+use crate::define_version_enum;
+use crate::document::encoding;
+use crate::object::{DeserializationContext, SerializationContext, Uuid};
+
+pub fn serialize(ctx: &mut SerializationContext<'_>, lane: &AutomationLane) -> Result<Vec<u8>> {
+    let node = ctx.add_dep(lane.target.node_id)?;
+
+    let points = lane
+        .points
+        .values()
+        .map(|point| AutomationPointLatest {
+            position: point.position,
+            value: point.value,
+            curve: point.curve,
+        })
+        .collect();
+
+    let raw = AutomationLaneLatest {
+        node,
+        param: Cow::Borrowed(&lane.target.param),
+        points,
+    };
+
+    encoding::serialize(ctx.format(), Version::LATEST.as_u32(), &raw)
+}
+
+pub fn deserialize(ctx: &mut DeserializationContext<'_>, data: &[u8]) -> Result<AutomationLane> {
+    let (version, data) = encoding::extract_version(ctx.format(), data)?;
+    let raw = match Version::from_u32(version)? {
+        Version::V1 => encoding::deserialize::<AutomationLaneV1>(ctx.format(), data)?,
+    };
+
+    let target = AutomationTarget {
+        node_id: ctx.add_dep(raw.node)?,
+        param: raw.param.into_owned(),
+    };
+
+    let mut points = SlotMap::with_capacity_and_key(raw.points.len());
+
+    for point in raw.points {
+        let point = AutomationPoint {
+            position: point.position,
+            value: point.value,
+            curve: point.curve,
+        };
+
+        if !point.is_valid() {
+            bail!(
+                ErrorKind::Deserialization,
+                "invalid automation point {point:?}"
+            );
+        }
+
+        points.insert(point);
+    }
+
+    Ok(AutomationLane { target, points })
+}
+
+define_version_enum! {
+    enum Version {
+        V1 = 1,
+    }
+}
+
+type AutomationLaneLatest<'a> = AutomationLaneV1<'a>;
+type AutomationPointLatest = AutomationPointV1;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct AutomationLaneV1<'a> {
+    node: Uuid,
+    #[serde(borrow)]
+    param: Cow<'a, str>,
+    points: Vec<AutomationPointV1>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct AutomationPointV1 {
+    position: Time,
+    value: f32,
+    curve: CurveShape,
+}
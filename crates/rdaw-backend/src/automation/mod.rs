@@ -0,0 +1,139 @@
+mod encoding;
+mod ops;
+#[cfg(test)]
+mod tests;
+
+use std::mem;
+
+use rdaw_api::automation::{
+    AutomationLaneId, AutomationPoint, AutomationPointId, AutomationTarget,
+};
+use rdaw_api::Result;
+use rdaw_core::time::RealTime;
+use slotmap::SlotMap;
+
+use crate::object::{DeserializationContext, Object, ObjectId, ObjectType, SerializationContext};
+use crate::tempo_map::TempoMap;
+
+impl ObjectId for AutomationLaneId {
+    type Object = AutomationLane;
+}
+
+#[derive(Debug, Clone)]
+pub struct AutomationLane {
+    pub target: AutomationTarget,
+    pub points: SlotMap<AutomationPointId, AutomationPoint>,
+}
+
+impl AutomationLane {
+    pub fn new(target: AutomationTarget) -> AutomationLane {
+        AutomationLane {
+            target,
+            points: SlotMap::default(),
+        }
+    }
+
+    /// Points sorted by their position, points at the same position are kept in the order of
+    /// their ids.
+    pub fn sorted_points(&self, tempo_map: &TempoMap) -> Vec<(AutomationPointId, AutomationPoint)> {
+        let mut points = self
+            .points
+            .iter()
+            .map(|(id, point)| (id, *point))
+            .collect::<Vec<_>>();
+        points.sort_by_key(|(id, point)| (tempo_map.to_real(point.position), *id));
+        points
+    }
+}
+
+impl Object for AutomationLane {
+    type Id = AutomationLaneId;
+
+    const TYPE: ObjectType = ObjectType::AutomationLane;
+
+    fn serialize(&self, ctx: &mut SerializationContext<'_>) -> Result<Vec<u8>> {
+        self::encoding::serialize(ctx, self)
+    }
+
+    fn deserialize(ctx: &mut DeserializationContext<'_>, data: &[u8]) -> Result<Self> {
+        self::encoding::deserialize(ctx, data)
+    }
+
+    fn heap_size(&self) -> usize {
+        self.target.param.capacity() + self.points.capacity() * mem::size_of::<AutomationPoint>()
+    }
+}
+
+/// Points of a lane positioned in real time, ready to be sampled by the engine.
+#[derive(Debug, Clone, Default)]
+pub struct AutomationCurve {
+    points: Vec<(RealTime, AutomationPoint)>,
+}
+
+impl AutomationCurve {
+    pub fn new(lane: &AutomationLane, tempo_map: &TempoMap) -> AutomationCurve {
+        let points = lane
+            .sorted_points(tempo_map)
+            .into_iter()
+            .map(|(_, point)| (tempo_map.to_real(point.position), point))
+            .collect();
+
+        AutomationCurve { points }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    /// Value from 0 to 1. Before the first point and after the last one the value is held, an
+    /// empty curve has no value and leaves the parameter alone.
+    pub fn value_at(&self, position: RealTime) -> Option<f32> {
+        let idx = self.points.partition_point(|(v, _)| *v <= position);
+        self.segment_value(idx, position.as_secs_f64())
+    }
+
+    /// Writes values at `start` and every sample after it to `values`, returns `false` without
+    /// touching them if the curve is empty.
+    pub fn sample(&self, start: RealTime, sample_rate: u32, values: &mut [f32]) -> bool {
+        if self.points.is_empty() {
+            return false;
+        }
+
+        let start_secs = start.as_secs_f64();
+        let sample_duration = 1.0 / f64::from(sample_rate);
+
+        let mut idx = self.points.partition_point(|(v, _)| *v <= start);
+
+        for (i, value) in values.iter_mut().enumerate() {
+            let secs = start_secs + i as f64 * sample_duration;
+
+            while idx < self.points.len() && self.points[idx].0.as_secs_f64() <= secs {
+                idx += 1;
+            }
+
+            *value = self.segment_value(idx, secs).unwrap_or_default();
+        }
+
+        true
+    }
+
+    // `idx` is the index of the first point after `secs`
+    fn segment_value(&self, idx: usize, secs: f64) -> Option<f32> {
+        let ((prev_pos, prev), (next_pos, next)) = match (idx.checked_sub(1), self.points.get(idx))
+        {
+            (None, None) => return None,
+            (None, Some((_, next))) => return Some(next.value),
+            (Some(prev), None) => return Some(self.points[prev].1.value),
+            (Some(prev), Some(next)) => (&self.points[prev], next),
+        };
+
+        let prev_secs = prev_pos.as_secs_f64();
+        let len = next_pos.as_secs_f64() - prev_secs;
+        if len <= 0.0 {
+            return Some(next.value);
+        }
+
+        let t = ((secs - prev_secs) / len) as f32;
+        Some(prev.value + (next.value - prev.value) * prev.curve.value(t))
+    }
+}
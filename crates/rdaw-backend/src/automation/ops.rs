@@ -0,0 +1,247 @@
+use rdaw_api::automation::{
+    AutomationEvent, AutomationLaneId, AutomationOperations, AutomationPoint, AutomationPointId,
+    AutomationRequest, AutomationResponse, AutomationTarget, CurveShape,
+};
+use rdaw_api::time::Time;
+use rdaw_api::track::TrackId;
+use rdaw_api::{bail, format_err, BackendProtocol, ErrorKind, Result};
+use rdaw_rpc::StreamId;
+use tracing::instrument;
+
+use super::AutomationLane;
+use crate::object::ObjectKey;
+use crate::tempo_map::TempoMap;
+use crate::Backend;
+
+#[rdaw_rpc::handler(protocol = BackendProtocol, operations = AutomationOperations)]
+impl Backend {
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn create_automation_lane(
+        &mut self,
+        track_id: TrackId,
+        target: AutomationTarget,
+    ) -> Result<AutomationLaneId> {
+        let track = self.hub.tracks.get_or_err(track_id)?;
+        let document_id = self.hub.tracks.get_key_or_err(track_id)?.document_id;
+
+        if !track.nodes.contains(&target.node_id) {
+            bail!(
+                ErrorKind::NotFound,
+                "{:?} isn't on {track_id:?}",
+                target.node_id
+            );
+        }
+
+        let node = self.hub.nodes.get_or_err(target.node_id)?;
+        if node.param(&target.param).is_none() {
+            bail!(
+                ErrorKind::NotFound,
+                "{:?} has no parameter `{}`",
+                target.node_id,
+                target.param
+            );
+        }
+
+        let is_automated = track
+            .automation_lanes
+            .iter()
+            .any(|&id| self.hub.automation_lanes[id].target == target);
+
+        if is_automated {
+            bail!(
+                ErrorKind::Conflict,
+                "parameter `{}` of {:?} is already automated",
+                target.param,
+                target.node_id
+            );
+        }
+
+        let id = self.hub.automation_lanes.insert(
+            ObjectKey::new_random(document_id),
+            AutomationLane::new(target),
+        );
+
+        self.hub.tracks[track_id].automation_lanes.push(id);
+
+        Ok(id)
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn get_track_automation_lanes(&self, track_id: TrackId) -> Result<Vec<AutomationLaneId>> {
+        let track = self.hub.tracks.get_or_err(track_id)?;
+        Ok(track.automation_lanes.clone())
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn remove_track_automation_lane(
+        &mut self,
+        track_id: TrackId,
+        id: AutomationLaneId,
+    ) -> Result<()> {
+        let track = self.hub.tracks.get_mut_or_err(track_id)?;
+
+        let Some(idx) = track.automation_lanes.iter().position(|&v| v == id) else {
+            bail!(ErrorKind::NotFound, "{id:?} isn't on {track_id:?}");
+        };
+
+        track.automation_lanes.remove(idx);
+        self.hub.automation_lanes.remove(id);
+        self.subscribers.automation_lane.close_all(id);
+
+        Ok(())
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn get_automation_lane_target(&self, id: AutomationLaneId) -> Result<AutomationTarget> {
+        let lane = self.hub.automation_lanes.get_or_err(id)?;
+        Ok(lane.target.clone())
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn subscribe_automation_lane(&mut self, id: AutomationLaneId) -> Result<StreamId> {
+        self.hub.automation_lanes.ensure_has(id)?;
+        Ok(self.subscribers.automation_lane.subscribe(id))
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn get_automation_points(
+        &self,
+        id: AutomationLaneId,
+    ) -> Result<Vec<(AutomationPointId, AutomationPoint)>> {
+        let lane = self.hub.automation_lanes.get_or_err(id)?;
+        let tempo_map = self.automation_tempo_map(id)?;
+        Ok(lane.sorted_points(tempo_map))
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn add_automation_point(
+        &mut self,
+        id: AutomationLaneId,
+        point: AutomationPoint,
+    ) -> Result<AutomationPointId> {
+        if !point.is_valid() {
+            bail!(
+                ErrorKind::NotSupported,
+                "invalid automation point {point:?}"
+            );
+        }
+
+        let lane = self.hub.automation_lanes.get_mut_or_err(id)?;
+        let point_id = lane.points.insert(point);
+
+        self.subscribers.automation_lane.notify(
+            id,
+            AutomationEvent::Added {
+                id: point_id,
+                point,
+            },
+        );
+
+        Ok(point_id)
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn move_automation_point(
+        &mut self,
+        id: AutomationLaneId,
+        point_id: AutomationPointId,
+        position: Time,
+        value: f32,
+    ) -> Result<()> {
+        let point = self.get_automation_point(id, point_id)?;
+
+        self.set_automation_point(
+            id,
+            point_id,
+            AutomationPoint {
+                position,
+                value,
+                ..point
+            },
+        )
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn set_automation_point_curve(
+        &mut self,
+        id: AutomationLaneId,
+        point_id: AutomationPointId,
+        curve: CurveShape,
+    ) -> Result<()> {
+        let point = self.get_automation_point(id, point_id)?;
+        self.set_automation_point(id, point_id, AutomationPoint { curve, ..point })
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn remove_automation_point(
+        &mut self,
+        id: AutomationLaneId,
+        point_id: AutomationPointId,
+    ) -> Result<()> {
+        let lane = self.hub.automation_lanes.get_mut_or_err(id)?;
+
+        if lane.points.remove(point_id).is_some() {
+            self.subscribers
+                .automation_lane
+                .notify(id, AutomationEvent::Removed { id: point_id });
+        }
+
+        Ok(())
+    }
+
+    fn get_automation_point(
+        &self,
+        id: AutomationLaneId,
+        point_id: AutomationPointId,
+    ) -> Result<AutomationPoint> {
+        let lane = self.hub.automation_lanes.get_or_err(id)?;
+        lane.points.get(point_id).copied().ok_or_else(|| {
+            format_err!(ErrorKind::InvalidId, "{point_id:?} doesn't exist in {id:?}")
+        })
+    }
+
+    fn set_automation_point(
+        &mut self,
+        id: AutomationLaneId,
+        point_id: AutomationPointId,
+        point: AutomationPoint,
+    ) -> Result<()> {
+        if !point.is_valid() {
+            bail!(
+                ErrorKind::NotSupported,
+                "invalid automation point {point:?}"
+            );
+        }
+
+        let lane = self.hub.automation_lanes.get_mut_or_err(id)?;
+        lane.points[point_id] = point;
+
+        self.subscribers.automation_lane.notify(
+            id,
+            AutomationEvent::Changed {
+                id: point_id,
+                point,
+            },
+        );
+
+        Ok(())
+    }
+
+    /// Automation follows the tempo map of the document's arrangement.
+    fn automation_tempo_map(&self, id: AutomationLaneId) -> Result<&TempoMap> {
+        let document_id = self.hub.automation_lanes.get_key_or_err(id)?.document_id;
+        let arrangement_id = self.get_document_arrangement(document_id)?;
+        let arrangement = &self.hub.arrangements[arrangement_id];
+        Ok(&self.hub.tempo_maps[arrangement.tempo_map_id])
+    }
+}
@@ -0,0 +1,295 @@
+use futures::StreamExt;
+use rdaw_api::arrangement::ArrangementOperations;
+use rdaw_api::automation::{
+    AutomationEvent, AutomationOperations, AutomationPoint, AutomationTarget, CurveShape,
+};
+use rdaw_api::document::{DocumentId, DocumentOperations};
+use rdaw_api::node::{NodeId, NodeOperations, NodeParam};
+use rdaw_api::time::{BeatTime, Time};
+use rdaw_api::track::{TrackId, TrackOperations};
+use rdaw_api::{assert_err, ErrorKind, Result};
+use rdaw_core::path::Utf8Path;
+use rdaw_core::time::RealTime;
+use tempfile::NamedTempFile;
+
+use super::{AutomationCurve, AutomationLane};
+use crate::tempo_map::TempoMap;
+use crate::tests::{run_test, TestClient};
+
+fn beats(beats: i32) -> Time {
+    Time::Beat(BeatTime::from_beats(beats))
+}
+
+fn secs(secs: f64) -> RealTime {
+    RealTime::from_secs_f64(secs)
+}
+
+fn target(node_id: NodeId) -> AutomationTarget {
+    AutomationTarget {
+        node_id,
+        param: "cutoff".into(),
+    }
+}
+
+async fn create_filter_track(
+    client: &TestClient,
+    document_id: DocumentId,
+) -> Result<(TrackId, NodeId)> {
+    let track_id = client.create_track(document_id).await?;
+    let node_id = client
+        .create_node(
+            document_id,
+            "filter".into(),
+            vec![NodeParam::new("cutoff", 0.0, 100.0, 50.0)],
+        )
+        .await?;
+    client.insert_track_node(track_id, node_id, 0).await?;
+    Ok((track_id, node_id))
+}
+
+#[test]
+fn lanes() -> Result<()> {
+    run_test(|client| async move {
+        let document_id = client.create_document().await?;
+        let (track_id, node_id) = create_filter_track(&client, document_id).await?;
+
+        let lane_id = client
+            .create_automation_lane(track_id, target(node_id))
+            .await?;
+        assert_eq!(
+            client.get_track_automation_lanes(track_id).await?,
+            vec![lane_id]
+        );
+        assert_eq!(
+            client.get_automation_lane_target(lane_id).await?,
+            target(node_id)
+        );
+
+        assert_err!(
+            client
+                .create_automation_lane(track_id, target(node_id))
+                .await,
+            ErrorKind::Conflict
+        );
+        assert_err!(
+            client
+                .create_automation_lane(
+                    track_id,
+                    AutomationTarget {
+                        node_id,
+                        param: "resonance".into(),
+                    },
+                )
+                .await,
+            ErrorKind::NotFound
+        );
+
+        let other_track_id = client.create_track(document_id).await?;
+        assert_err!(
+            client
+                .create_automation_lane(other_track_id, target(node_id))
+                .await,
+            ErrorKind::NotFound
+        );
+
+        client
+            .remove_track_automation_lane(track_id, lane_id)
+            .await?;
+        assert!(client
+            .get_track_automation_lanes(track_id)
+            .await?
+            .is_empty());
+        assert_err!(
+            client.get_automation_points(lane_id).await,
+            ErrorKind::InvalidId
+        );
+
+        Ok(())
+    })
+}
+
+#[test]
+fn points() -> Result<()> {
+    run_test(|client| async move {
+        let document_id = client.create_document().await?;
+        let (track_id, node_id) = create_filter_track(&client, document_id).await?;
+        let lane_id = client
+            .create_automation_lane(track_id, target(node_id))
+            .await?;
+
+        let mut stream = client.subscribe_automation_lane(lane_id).await?;
+
+        let late = AutomationPoint::new(beats(4), 1.0);
+        let late_id = client.add_automation_point(lane_id, late).await?;
+        assert_eq!(
+            stream.next().await,
+            Some(AutomationEvent::Added {
+                id: late_id,
+                point: late,
+            })
+        );
+
+        // real time positions are ordered against beat positions by the tempo map
+        let early = AutomationPoint::new(Time::Real(secs(1.0)), 0.0);
+        let early_id = client.add_automation_point(lane_id, early).await?;
+        stream.next().await;
+
+        assert_eq!(
+            client.get_automation_points(lane_id).await?,
+            vec![(early_id, early), (late_id, late)]
+        );
+
+        client
+            .move_automation_point(lane_id, late_id, beats(1), 0.5)
+            .await?;
+        let moved = AutomationPoint::new(beats(1), 0.5);
+        assert_eq!(
+            stream.next().await,
+            Some(AutomationEvent::Changed {
+                id: late_id,
+                point: moved,
+            })
+        );
+
+        client
+            .set_automation_point_curve(lane_id, late_id, CurveShape::Smooth)
+            .await?;
+        assert_eq!(
+            stream.next().await,
+            Some(AutomationEvent::Changed {
+                id: late_id,
+                point: AutomationPoint {
+                    curve: CurveShape::Smooth,
+                    ..moved
+                },
+            })
+        );
+
+        assert_eq!(client.get_automation_points(lane_id).await?[0].0, late_id);
+
+        assert_err!(
+            client
+                .add_automation_point(lane_id, AutomationPoint::new(beats(0), 1.5))
+                .await,
+            ErrorKind::NotSupported
+        );
+        assert_err!(
+            client
+                .set_automation_point_curve(lane_id, late_id, CurveShape::Tension(2.0))
+                .await,
+            ErrorKind::NotSupported
+        );
+
+        client.remove_automation_point(lane_id, early_id).await?;
+        assert_eq!(
+            stream.next().await,
+            Some(AutomationEvent::Removed { id: early_id })
+        );
+        assert_err!(
+            client
+                .move_automation_point(lane_id, early_id, beats(0), 0.0)
+                .await,
+            ErrorKind::InvalidId
+        );
+
+        Ok(())
+    })
+}
+
+#[test]
+fn json_roundtrip() -> Result<()> {
+    run_test(|client| async move {
+        let document_id = client.create_document().await?;
+        let arrangement_id = client.get_document_arrangement(document_id).await?;
+        let main_track_id = client.get_arrangement_main_track(arrangement_id).await?;
+
+        let (track_id, node_id) = create_filter_track(&client, document_id).await?;
+        client.append_track_child(main_track_id, track_id).await?;
+
+        let lane_id = client
+            .create_automation_lane(track_id, target(node_id))
+            .await?;
+        let point = AutomationPoint {
+            curve: CurveShape::Tension(-0.5),
+            ..AutomationPoint::new(beats(2), 0.25)
+        };
+        client.add_automation_point(lane_id, point).await?;
+
+        let temp_file = NamedTempFile::with_prefix(".rdaw-test-")?;
+        let path = Utf8Path::from_path(temp_file.path()).unwrap();
+        client
+            .export_document_json(document_id, path.to_path_buf())
+            .await?;
+
+        let document_id = client.import_document_json(path.to_path_buf()).await?;
+        let arrangement_id = client.get_document_arrangement(document_id).await?;
+        let main_track_id = client.get_arrangement_main_track(arrangement_id).await?;
+        let track_id = client.get_track_children(main_track_id).await?[0];
+
+        let lanes = client.get_track_automation_lanes(track_id).await?;
+        assert_eq!(lanes.len(), 1);
+
+        let target = client.get_automation_lane_target(lanes[0]).await?;
+        assert_eq!(target.param, "cutoff");
+        assert_eq!(
+            client.get_track_nodes(track_id).await?,
+            vec![target.node_id]
+        );
+
+        let points = client.get_automation_points(lanes[0]).await?;
+        assert_eq!(
+            points.into_iter().map(|(_, v)| v).collect::<Vec<_>>(),
+            vec![point]
+        );
+
+        Ok(())
+    })
+}
+
+#[test]
+fn curve() {
+    let tempo_map = TempoMap::new(120.0);
+    let mut lane = AutomationLane::new(target(NodeId::default()));
+
+    let curve = AutomationCurve::new(&lane, &tempo_map);
+    assert_eq!(curve.value_at(secs(1.0)), None);
+    assert!(!curve.sample(secs(0.0), 4, &mut [0.0; 4]));
+
+    lane.points
+        .insert(AutomationPoint::new(Time::Real(secs(1.0)), 1.0));
+    lane.points.insert(AutomationPoint {
+        curve: CurveShape::Hold,
+        ..AutomationPoint::new(Time::Real(secs(2.0)), 0.5)
+    });
+    lane.points
+        .insert(AutomationPoint::new(Time::Real(secs(3.0)), 0.0));
+
+    let curve = AutomationCurve::new(&lane, &tempo_map);
+    assert_eq!(curve.value_at(secs(0.0)), Some(1.0));
+    assert_eq!(curve.value_at(secs(1.5)), Some(0.75));
+    assert_eq!(curve.value_at(secs(2.5)), Some(0.5));
+    assert_eq!(curve.value_at(secs(3.0)), Some(0.0));
+    assert_eq!(curve.value_at(secs(10.0)), Some(0.0));
+
+    let mut values = [0.0; 8];
+    assert!(curve.sample(secs(0.5), 2, &mut values));
+    assert_eq!(values, [1.0, 1.0, 0.75, 0.5, 0.5, 0.0, 0.0, 0.0]);
+}
+
+#[test]
+fn curve_shapes() {
+    for shape in [
+        CurveShape::Linear,
+        CurveShape::Smooth,
+        CurveShape::Tension(-1.0),
+        CurveShape::Tension(1.0),
+    ] {
+        assert_eq!(shape.value(0.0), 0.0);
+        assert!((shape.value(1.0) - 1.0).abs() < 1e-6);
+    }
+
+    assert_eq!(CurveShape::Hold.value(0.9), 0.0);
+    assert!((CurveShape::Smooth.value(0.5) - 0.5).abs() < 1e-6);
+    assert!(CurveShape::Tension(0.5).value(0.5) < 0.5);
+    assert!(CurveShape::Tension(-0.5).value(0.5) > 0.5);
+}
@@ -0,0 +1,57 @@
+use std::borrow::Cow;
+
+use rdaw_api::track::TrackMix;
+use rdaw_api::{bail, ErrorKind, Result};
+use serde::{Deserialize, Serialize};
+
+use super::Bus;
+use crate::define_version_enum;
+use crate::document::encoding;
+use crate::object::{DeserializationContext, SerializationContext};
+
+pub fn serialize(ctx: &mut SerializationContext<'_>, bus: &Bus) -> Result<Vec<u8>> {
+    let raw = BusLatest {
+        name: Cow::Borrowed(&bus.name),
+        volume: bus.mix.volume,
+        pan: bus.mix.pan,
+    };
+
+    encoding::serialize(ctx.format(), Version::LATEST.as_u32(), &raw)
+}
+
+pub fn deserialize(ctx: &mut DeserializationContext<'_>, data: &[u8]) -> Result<Bus> {
+    let (version, data) = encoding::extract_version(ctx.format(), data)?;
+    let raw = match Version::from_u32(version)? {
+        Version::V1 => encoding::deserialize::<BusV1>(ctx.format(), data)?,
+    };
+
+    let mix = TrackMix {
+        volume: raw.volume,
+        pan: raw.pan,
+    };
+
+    if !mix.volume.is_finite() || mix.volume < 0.0 || !(-1.0..=1.0).contains(&mix.pan) {
+        bail!(ErrorKind::Deserialization, "invalid bus mix {mix:?}");
+    }
+
+    Ok(Bus {
+        name: raw.name.into_owned(),
+        mix,
+    })
+}
+
+define_version_enum! {
+    enum Version {
+        V1 = 1,
+    }
+}
+
+type BusLatest<'a> = BusV1<'a>;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct BusV1<'a> {
+    #[serde(borrow)]
+    name: Cow<'a, str>,
+    volume: f32,
+    pan: f32,
+}
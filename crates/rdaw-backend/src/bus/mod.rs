@@ -0,0 +1,47 @@
+mod encoding;
+mod ops;
+#[cfg(test)]
+mod tests;
+
+use rdaw_api::bus::BusId;
+use rdaw_api::track::TrackMix;
+use rdaw_api::Result;
+
+use crate::object::{DeserializationContext, Object, ObjectId, ObjectType, SerializationContext};
+
+impl ObjectId for BusId {
+    type Object = Bus;
+}
+
+#[derive(Debug, Clone)]
+pub struct Bus {
+    pub name: String,
+    pub mix: TrackMix,
+}
+
+impl Bus {
+    pub fn new(name: String) -> Bus {
+        Bus {
+            name,
+            mix: TrackMix::default(),
+        }
+    }
+}
+
+impl Object for Bus {
+    type Id = BusId;
+
+    const TYPE: ObjectType = ObjectType::Bus;
+
+    fn serialize(&self, ctx: &mut SerializationContext<'_>) -> Result<Vec<u8>> {
+        self::encoding::serialize(ctx, self)
+    }
+
+    fn deserialize(ctx: &mut DeserializationContext<'_>, data: &[u8]) -> Result<Self> {
+        self::encoding::deserialize(ctx, data)
+    }
+
+    fn heap_size(&self) -> usize {
+        self.name.capacity()
+    }
+}
@@ -0,0 +1,264 @@
+use rdaw_api::arrangement::ArrangementId;
+use rdaw_api::bus::{
+    BusId, BusOperations, BusRequest, BusResponse, SendEvent, SendId, SendPosition, TrackSend,
+};
+use rdaw_api::track::{TrackId, TrackMix};
+use rdaw_api::{bail, format_err, BackendProtocol, ErrorKind, Result};
+use rdaw_rpc::StreamId;
+use tracing::instrument;
+
+use super::Bus;
+use crate::object::ObjectKey;
+use crate::Backend;
+
+#[rdaw_rpc::handler(protocol = BackendProtocol, operations = BusOperations)]
+impl Backend {
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn create_bus(&mut self, arrangement_id: ArrangementId, name: String) -> Result<BusId> {
+        let document_id = self
+            .hub
+            .arrangements
+            .get_key_or_err(arrangement_id)?
+            .document_id;
+
+        let id = self
+            .hub
+            .buses
+            .insert(ObjectKey::new_random(document_id), Bus::new(name));
+
+        self.hub.arrangements[arrangement_id].buses.push(id);
+
+        Ok(id)
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn get_arrangement_buses(&self, arrangement_id: ArrangementId) -> Result<Vec<BusId>> {
+        let arrangement = self.hub.arrangements.get_or_err(arrangement_id)?;
+        Ok(arrangement.buses.clone())
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn remove_arrangement_bus(
+        &mut self,
+        arrangement_id: ArrangementId,
+        id: BusId,
+    ) -> Result<()> {
+        let document_id = self.hub.buses.get_key_or_err(id)?.document_id;
+        let arrangement = self.hub.arrangements.get_mut_or_err(arrangement_id)?;
+
+        let Some(idx) = arrangement.buses.iter().position(|&v| v == id) else {
+            bail!(ErrorKind::NotFound, "{id:?} isn't on {arrangement_id:?}");
+        };
+
+        arrangement.buses.remove(idx);
+
+        for track_id in self.hub.tracks.ids_in_document(document_id) {
+            let track = &mut self.hub.tracks[track_id];
+            let removed = track
+                .sends
+                .iter()
+                .filter(|(_, send)| send.bus_id == id)
+                .map(|(send_id, _)| send_id)
+                .collect::<Vec<_>>();
+
+            for send_id in removed {
+                track.sends.remove(send_id);
+                self.subscribers
+                    .track_sends
+                    .notify(track_id, SendEvent::Removed { id: send_id });
+            }
+        }
+
+        self.hub.buses.remove(id);
+        self.subscribers.bus_mix.close_all(id);
+
+        Ok(())
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn get_bus_name(&self, id: BusId) -> Result<String> {
+        let bus = self.hub.buses.get_or_err(id)?;
+        Ok(bus.name.clone())
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn set_bus_name(&mut self, id: BusId, new_name: String) -> Result<()> {
+        let bus = self.hub.buses.get_mut_or_err(id)?;
+        bus.name = new_name;
+        Ok(())
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn subscribe_bus_mix(&mut self, id: BusId) -> Result<StreamId> {
+        self.hub.buses.ensure_has(id)?;
+        Ok(self.subscribers.bus_mix.subscribe(id))
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn get_bus_mix(&self, id: BusId) -> Result<TrackMix> {
+        let bus = self.hub.buses.get_or_err(id)?;
+        Ok(bus.mix)
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn set_bus_volume(&mut self, id: BusId, volume: f32) -> Result<()> {
+        if !volume.is_finite() || volume < 0.0 {
+            bail!(ErrorKind::NotSupported, "invalid volume {volume}");
+        }
+
+        let bus = self.hub.buses.get_mut_or_err(id)?;
+        bus.mix.volume = volume;
+        self.subscribers.bus_mix.notify(id, bus.mix);
+        Ok(())
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn set_bus_pan(&mut self, id: BusId, pan: f32) -> Result<()> {
+        if !(-1.0..=1.0).contains(&pan) {
+            bail!(ErrorKind::NotSupported, "invalid pan {pan}");
+        }
+
+        let bus = self.hub.buses.get_mut_or_err(id)?;
+        bus.mix.pan = pan;
+        self.subscribers.bus_mix.notify(id, bus.mix);
+        Ok(())
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn subscribe_track_sends(&mut self, track_id: TrackId) -> Result<StreamId> {
+        self.hub.tracks.ensure_has(track_id)?;
+        Ok(self.subscribers.track_sends.subscribe(track_id))
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn get_track_sends(&self, track_id: TrackId) -> Result<Vec<(SendId, TrackSend)>> {
+        let track = self.hub.tracks.get_or_err(track_id)?;
+        Ok(track.sends.iter().map(|(id, send)| (id, *send)).collect())
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn add_track_send(&mut self, track_id: TrackId, send: TrackSend) -> Result<SendId> {
+        if !send.is_valid() {
+            bail!(ErrorKind::NotSupported, "invalid send {send:?}");
+        }
+
+        let track_document_id = self.hub.tracks.get_key_or_err(track_id)?.document_id;
+        let bus_document_id = self.hub.buses.get_key_or_err(send.bus_id)?.document_id;
+
+        if track_document_id != bus_document_id {
+            bail!(
+                ErrorKind::NotSupported,
+                "{track_id:?} and {:?} are in different documents",
+                send.bus_id
+            );
+        }
+
+        let arrangement_id = self.bus_arrangement(send.bus_id)?;
+        if self.hub.arrangements[arrangement_id].main_track_id == track_id {
+            bail!(
+                ErrorKind::NotSupported,
+                "main track can't send to {:?}, which is mixed into it",
+                send.bus_id
+            );
+        }
+
+        let track = &mut self.hub.tracks[track_id];
+        let id = track.sends.insert(send);
+
+        self.subscribers
+            .track_sends
+            .notify(track_id, SendEvent::Added { id, send });
+
+        Ok(id)
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn set_track_send_level(
+        &mut self,
+        track_id: TrackId,
+        send_id: SendId,
+        level: f32,
+    ) -> Result<()> {
+        let send = self.get_track_send(track_id, send_id)?;
+        self.set_track_send(track_id, send_id, TrackSend { level, ..send })
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn set_track_send_position(
+        &mut self,
+        track_id: TrackId,
+        send_id: SendId,
+        position: SendPosition,
+    ) -> Result<()> {
+        let send = self.get_track_send(track_id, send_id)?;
+        self.set_track_send(track_id, send_id, TrackSend { position, ..send })
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn remove_track_send(&mut self, track_id: TrackId, send_id: SendId) -> Result<()> {
+        let track = self.hub.tracks.get_mut_or_err(track_id)?;
+
+        if track.sends.remove(send_id).is_some() {
+            self.subscribers
+                .track_sends
+                .notify(track_id, SendEvent::Removed { id: send_id });
+        }
+
+        Ok(())
+    }
+
+    fn get_track_send(&self, track_id: TrackId, send_id: SendId) -> Result<TrackSend> {
+        let track = self.hub.tracks.get_or_err(track_id)?;
+        track.sends.get(send_id).copied().ok_or_else(|| {
+            format_err!(
+                ErrorKind::InvalidId,
+                "{send_id:?} doesn't exist in {track_id:?}"
+            )
+        })
+    }
+
+    fn set_track_send(
+        &mut self,
+        track_id: TrackId,
+        send_id: SendId,
+        send: TrackSend,
+    ) -> Result<()> {
+        if !send.is_valid() {
+            bail!(ErrorKind::NotSupported, "invalid send {send:?}");
+        }
+
+        let track = self.hub.tracks.get_mut_or_err(track_id)?;
+        track.sends[send_id] = send;
+
+        self.subscribers
+            .track_sends
+            .notify(track_id, SendEvent::Changed { id: send_id, send });
+
+        Ok(())
+    }
+
+    /// Arrangement the bus is mixed into.
+    fn bus_arrangement(&self, id: BusId) -> Result<ArrangementId> {
+        self.hub
+            .arrangements
+            .iter()
+            .find(|(_, _, arrangement)| arrangement.buses.contains(&id))
+            .map(|(arrangement_id, _, _)| arrangement_id)
+            .ok_or_else(|| format_err!(ErrorKind::NotFound, "{id:?} isn't on any arrangement"))
+    }
+}
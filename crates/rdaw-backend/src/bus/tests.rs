@@ -0,0 +1,194 @@
+use futures::StreamExt;
+use rdaw_api::arrangement::ArrangementOperations;
+use rdaw_api::bus::{BusOperations, SendEvent, SendPosition, TrackSend};
+use rdaw_api::document::DocumentOperations;
+use rdaw_api::track::{TrackMix, TrackOperations};
+use rdaw_api::{assert_err, ErrorKind, Result};
+use rdaw_core::path::Utf8Path;
+use tempfile::NamedTempFile;
+
+use crate::tests::run_test;
+
+#[test]
+fn buses() -> Result<()> {
+    run_test(|client| async move {
+        let document_id = client.create_document().await?;
+        let arrangement_id = client.get_document_arrangement(document_id).await?;
+
+        let bus_id = client.create_bus(arrangement_id, "Reverb".into()).await?;
+        assert_eq!(
+            client.get_arrangement_buses(arrangement_id).await?,
+            vec![bus_id]
+        );
+        assert_eq!(client.get_bus_name(bus_id).await?, "Reverb");
+
+        let mut stream = client.subscribe_bus_mix(bus_id).await?;
+
+        client.set_bus_volume(bus_id, 0.5).await?;
+        let quieter = TrackMix {
+            volume: 0.5,
+            ..TrackMix::default()
+        };
+        assert_eq!(stream.next().await, Some(quieter));
+
+        client.set_bus_pan(bus_id, -1.0).await?;
+        let mix = TrackMix {
+            pan: -1.0,
+            ..quieter
+        };
+        assert_eq!(stream.next().await, Some(mix));
+        assert_eq!(client.get_bus_mix(bus_id).await?, mix);
+
+        assert_err!(
+            client.set_bus_volume(bus_id, f32::NAN).await,
+            ErrorKind::NotSupported
+        );
+        assert_err!(
+            client.set_bus_pan(bus_id, 2.0).await,
+            ErrorKind::NotSupported
+        );
+
+        let track_id = client.create_track(document_id).await?;
+        client
+            .add_track_send(track_id, TrackSend::new(bus_id))
+            .await?;
+        let mut sends = client.subscribe_track_sends(track_id).await?;
+
+        client
+            .remove_arrangement_bus(arrangement_id, bus_id)
+            .await?;
+        assert!(client
+            .get_arrangement_buses(arrangement_id)
+            .await?
+            .is_empty());
+        assert!(matches!(
+            sends.next().await,
+            Some(SendEvent::Removed { .. })
+        ));
+        assert!(client.get_track_sends(track_id).await?.is_empty());
+        assert_eq!(stream.next().await, None);
+        assert_err!(client.get_bus_name(bus_id).await, ErrorKind::InvalidId);
+
+        Ok(())
+    })
+}
+
+#[test]
+fn sends() -> Result<()> {
+    run_test(|client| async move {
+        let document_id = client.create_document().await?;
+        let arrangement_id = client.get_document_arrangement(document_id).await?;
+        let main_track_id = client.get_arrangement_main_track(arrangement_id).await?;
+        let bus_id = client.create_bus(arrangement_id, "Reverb".into()).await?;
+
+        let track_id = client.create_track(document_id).await?;
+        let mut stream = client.subscribe_track_sends(track_id).await?;
+
+        let send = TrackSend::new(bus_id);
+        let send_id = client.add_track_send(track_id, send).await?;
+        assert_eq!(
+            stream.next().await,
+            Some(SendEvent::Added { id: send_id, send })
+        );
+
+        client.set_track_send_level(track_id, send_id, 0.25).await?;
+        client
+            .set_track_send_position(track_id, send_id, SendPosition::PreFader)
+            .await?;
+        stream.next().await;
+        let changed = TrackSend {
+            level: 0.25,
+            position: SendPosition::PreFader,
+            ..send
+        };
+        assert_eq!(
+            stream.next().await,
+            Some(SendEvent::Changed {
+                id: send_id,
+                send: changed,
+            })
+        );
+        assert_eq!(
+            client.get_track_sends(track_id).await?,
+            vec![(send_id, changed)]
+        );
+
+        assert_err!(
+            client.set_track_send_level(track_id, send_id, -1.0).await,
+            ErrorKind::NotSupported
+        );
+
+        // buses are mixed into the main track, so it can't send to them
+        assert_err!(
+            client.add_track_send(main_track_id, send).await,
+            ErrorKind::NotSupported
+        );
+
+        let other_document_id = client.create_document().await?;
+        let other_track_id = client.create_track(other_document_id).await?;
+        assert_err!(
+            client.add_track_send(other_track_id, send).await,
+            ErrorKind::NotSupported
+        );
+
+        client.remove_track_send(track_id, send_id).await?;
+        assert_eq!(
+            stream.next().await,
+            Some(SendEvent::Removed { id: send_id })
+        );
+        assert_err!(
+            client.set_track_send_level(track_id, send_id, 1.0).await,
+            ErrorKind::InvalidId
+        );
+
+        Ok(())
+    })
+}
+
+#[test]
+fn json_roundtrip() -> Result<()> {
+    run_test(|client| async move {
+        let document_id = client.create_document().await?;
+        let arrangement_id = client.get_document_arrangement(document_id).await?;
+        let main_track_id = client.get_arrangement_main_track(arrangement_id).await?;
+
+        let bus_id = client.create_bus(arrangement_id, "Reverb".into()).await?;
+        client.set_bus_volume(bus_id, 0.5).await?;
+
+        let track_id = client.create_track(document_id).await?;
+        client.append_track_child(main_track_id, track_id).await?;
+        let send = TrackSend {
+            level: 0.75,
+            position: SendPosition::PreFader,
+            ..TrackSend::new(bus_id)
+        };
+        client.add_track_send(track_id, send).await?;
+
+        let temp_file = NamedTempFile::with_prefix(".rdaw-test-")?;
+        let path = Utf8Path::from_path(temp_file.path()).unwrap();
+        client
+            .export_document_json(document_id, path.to_path_buf())
+            .await?;
+
+        let document_id = client.import_document_json(path.to_path_buf()).await?;
+        let arrangement_id = client.get_document_arrangement(document_id).await?;
+        let main_track_id = client.get_arrangement_main_track(arrangement_id).await?;
+        let track_id = client.get_track_children(main_track_id).await?[0];
+
+        let buses = client.get_arrangement_buses(arrangement_id).await?;
+        assert_eq!(buses.len(), 1);
+        assert_eq!(client.get_bus_name(buses[0]).await?, "Reverb");
+        assert_eq!(client.get_bus_mix(buses[0]).await?.volume, 0.5);
+
+        let sends = client.get_track_sends(track_id).await?;
+        assert_eq!(
+            sends.into_iter().map(|(_, v)| v).collect::<Vec<_>>(),
+            vec![TrackSend {
+                bus_id: buses[0],
+                ..send
+            }]
+        );
+
+        Ok(())
+    })
+}
@@ -0,0 +1,182 @@
+mod ops;
+#[cfg(test)]
+mod tests;
+
+use std::fs::{self, File};
+use std::io;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+use blake3::Hash;
+use rdaw_api::cache::{CacheKind, CacheUsage};
+use rdaw_api::Result;
+use rdaw_core::path::{Utf8Path, Utf8PathBuf};
+
+pub const DEFAULT_BUDGET: u64 = 4 << 30;
+
+/// Directory of files keyed by the hash of what they were derived from, one subdirectory per
+/// kind. Entries can be read and written from any thread. Once the cache grows past its budget,
+/// the least recently used entries are removed.
+#[derive(Debug)]
+pub struct FileCache {
+    dir: Utf8PathBuf,
+    budget: AtomicU64,
+    // concurrent cleanups would remove more than needed
+    cleanup: Mutex<()>,
+}
+
+#[derive(Debug)]
+struct Entry {
+    kind: CacheKind,
+    path: Utf8PathBuf,
+    size: u64,
+    used_at: SystemTime,
+}
+
+impl FileCache {
+    pub fn new(dir: Utf8PathBuf, budget: u64) -> FileCache {
+        FileCache {
+            dir,
+            budget: AtomicU64::new(budget),
+            cleanup: Mutex::new(()),
+        }
+    }
+
+    pub fn dir(&self) -> &Utf8Path {
+        &self.dir
+    }
+
+    pub fn set_budget(&self, budget: u64) -> Result<()> {
+        self.budget.store(budget, Ordering::Relaxed);
+        self.cleanup()
+    }
+
+    pub fn read(&self, kind: CacheKind, key: Hash) -> Result<Option<Vec<u8>>> {
+        let path = self.entry_path(kind, key);
+
+        let data = match fs::read(&path) {
+            Ok(v) => v,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+
+        // the modification time doubles as the time of the last use
+        File::options()
+            .write(true)
+            .open(&path)
+            .and_then(|file| file.set_modified(SystemTime::now()))?;
+
+        Ok(Some(data))
+    }
+
+    /// Replaces the entry atomically, then removes old entries if the cache is over budget.
+    pub fn write(&self, kind: CacheKind, key: Hash, data: &[u8]) -> Result<()> {
+        let path = self.entry_path(kind, key);
+        fs::create_dir_all(self.dir.join(kind_dir(kind)))?;
+
+        let tmp_path = path.with_extension("tmp");
+        fs::write(&tmp_path, data)?;
+        fs::rename(&tmp_path, &path)?;
+
+        self.cleanup()
+    }
+
+    pub fn usage(&self) -> Result<Vec<CacheUsage>> {
+        let entries = self.entries()?;
+
+        let usage = CacheKind::ALL.map(|kind| {
+            let entries = entries.iter().filter(|v| v.kind == kind);
+            CacheUsage {
+                kind,
+                num_entries: entries.clone().count(),
+                size: entries.map(|v| v.size).sum(),
+            }
+        });
+
+        Ok(usage.into())
+    }
+
+    pub fn clear(&self, kind: Option<CacheKind>) -> Result<()> {
+        for entry in self.entries()? {
+            if kind.is_none_or(|kind| kind == entry.kind) {
+                remove_entry(&entry.path)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Removes the least recently used entries until the cache is within its budget.
+    pub fn cleanup(&self) -> Result<()> {
+        let _guard = self.cleanup.lock().unwrap();
+
+        let budget = self.budget.load(Ordering::Relaxed);
+        let mut entries = self.entries()?;
+        let mut size = entries.iter().map(|v| v.size).sum::<u64>();
+
+        entries.sort_by_key(|v| v.used_at);
+
+        for entry in entries {
+            if size <= budget {
+                break;
+            }
+
+            remove_entry(&entry.path)?;
+            size -= entry.size;
+        }
+
+        Ok(())
+    }
+
+    fn entries(&self) -> Result<Vec<Entry>> {
+        let mut entries = Vec::new();
+
+        for kind in CacheKind::ALL {
+            let dir = self.dir.join(kind_dir(kind));
+
+            let read_dir = match dir.read_dir_utf8() {
+                Ok(v) => v,
+                Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+                Err(e) => return Err(e.into()),
+            };
+
+            for dir_entry in read_dir {
+                let dir_entry = dir_entry?;
+
+                // unfinished writes
+                if dir_entry.path().extension().is_some() {
+                    continue;
+                }
+
+                let metadata = dir_entry.metadata()?;
+                entries.push(Entry {
+                    kind,
+                    path: dir_entry.into_path(),
+                    size: metadata.len(),
+                    used_at: metadata.modified()?,
+                });
+            }
+        }
+
+        Ok(entries)
+    }
+
+    fn entry_path(&self, kind: CacheKind, key: Hash) -> Utf8PathBuf {
+        self.dir.join(kind_dir(kind)).join(key.to_hex().as_str())
+    }
+}
+
+fn kind_dir(kind: CacheKind) -> &'static str {
+    match kind {
+        CacheKind::Waveform => "waveform",
+        CacheKind::Decoded => "decoded",
+    }
+}
+
+fn remove_entry(path: &Utf8Path) -> Result<()> {
+    match fs::remove_file(path) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e.into()),
+        _ => Ok(()),
+    }
+}
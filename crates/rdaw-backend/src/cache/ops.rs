@@ -0,0 +1,75 @@
+use std::fs;
+use std::sync::Arc;
+
+use rdaw_api::cache::{CacheKind, CacheOperations, CacheRequest, CacheResponse, CacheUsage};
+use rdaw_api::error::ResultExt;
+use rdaw_api::{BackendProtocol, Result};
+use rdaw_core::path::Utf8PathBuf;
+use tracing::instrument;
+
+use super::FileCache;
+use crate::Backend;
+
+#[rdaw_rpc::handler(protocol = BackendProtocol, operations = CacheOperations)]
+impl Backend {
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn get_cache_dir(&self) -> Result<Option<Utf8PathBuf>> {
+        Ok(self.cache.as_ref().map(|v| v.dir().to_owned()))
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn set_cache_dir(&mut self, path: Option<Utf8PathBuf>) -> Result<()> {
+        let Some(path) = path else {
+            self.cache = None;
+            return Ok(());
+        };
+
+        fs::create_dir_all(&path)
+            .with_context(|| format!("failed to create cache directory `{path}`"))?;
+
+        let cache = FileCache::new(path, self.cache_budget);
+        // the directory could already hold more than the budget
+        cache.cleanup()?;
+        self.cache = Some(Arc::new(cache));
+
+        Ok(())
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn get_cache_budget(&self) -> Result<u64> {
+        Ok(self.cache_budget)
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn set_cache_budget(&mut self, budget: u64) -> Result<()> {
+        self.cache_budget = budget;
+
+        if let Some(cache) = &self.cache {
+            cache.set_budget(budget)?;
+        }
+
+        Ok(())
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn get_cache_usage(&self) -> Result<Vec<CacheUsage>> {
+        match &self.cache {
+            Some(cache) => cache.usage(),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn clear_cache(&mut self, kind: Option<CacheKind>) -> Result<()> {
+        match &self.cache {
+            Some(cache) => cache.clear(kind),
+            None => Ok(()),
+        }
+    }
+}
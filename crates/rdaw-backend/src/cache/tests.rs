@@ -0,0 +1,107 @@
+use std::thread;
+use std::time::Duration;
+
+use rdaw_api::cache::{CacheKind, CacheOperations, CacheUsage};
+use rdaw_api::Result;
+use rdaw_core::path::Utf8Path;
+
+use super::FileCache;
+use crate::tests::run_test;
+
+fn key(i: u8) -> blake3::Hash {
+    blake3::hash(&[i])
+}
+
+fn num_entries(usage: &[CacheUsage], kind: CacheKind) -> usize {
+    usage
+        .iter()
+        .find(|v| v.kind == kind)
+        .map_or(0, |v| v.num_entries)
+}
+
+#[test]
+fn least_recently_used() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    let dir = Utf8Path::from_path(dir.path()).unwrap();
+    let cache = FileCache::new(dir.to_owned(), 300);
+
+    for i in 0..3 {
+        cache.write(CacheKind::Decoded, key(i), &[i; 100])?;
+        // modification times have to differ
+        thread::sleep(Duration::from_millis(10));
+    }
+
+    assert_eq!(cache.read(CacheKind::Decoded, key(0))?, Some(vec![0; 100]));
+    thread::sleep(Duration::from_millis(10));
+
+    // the first entry was used last, so the second one goes
+    cache.write(CacheKind::Waveform, key(3), &[3; 100])?;
+    assert_eq!(cache.read(CacheKind::Decoded, key(1))?, None);
+    assert!(cache.read(CacheKind::Decoded, key(0))?.is_some());
+    assert!(cache.read(CacheKind::Decoded, key(2))?.is_some());
+
+    let usage = cache.usage()?;
+    assert_eq!(num_entries(&usage, CacheKind::Decoded), 2);
+    assert_eq!(num_entries(&usage, CacheKind::Waveform), 1);
+    assert_eq!(usage.iter().map(|v| v.size).sum::<u64>(), 300);
+
+    cache.set_budget(100)?;
+    assert_eq!(cache.usage()?.iter().map(|v| v.size).sum::<u64>(), 100);
+
+    Ok(())
+}
+
+#[test]
+fn clear() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    let dir = Utf8Path::from_path(dir.path()).unwrap();
+    let cache = FileCache::new(dir.to_owned(), 1000);
+
+    cache.write(CacheKind::Decoded, key(0), &[0; 10])?;
+    cache.write(CacheKind::Waveform, key(1), &[1; 10])?;
+
+    cache.clear(Some(CacheKind::Waveform))?;
+    assert_eq!(cache.read(CacheKind::Waveform, key(1))?, None);
+    assert!(cache.read(CacheKind::Decoded, key(0))?.is_some());
+
+    cache.clear(None)?;
+    assert_eq!(cache.read(CacheKind::Decoded, key(0))?, None);
+
+    Ok(())
+}
+
+#[test]
+fn cache_dir() -> Result<()> {
+    run_test(|client| async move {
+        let dir = tempfile::tempdir()?;
+        let dir = Utf8Path::from_path(dir.path()).unwrap().join("cache");
+
+        assert_eq!(client.get_cache_dir().await?, None);
+        assert!(client.get_cache_usage().await?.is_empty());
+
+        // budgets apply to caches set later
+        client.set_cache_budget(1000).await?;
+        assert_eq!(client.get_cache_budget().await?, 1000);
+
+        let cache = FileCache::new(dir.clone(), u64::MAX);
+        cache.write(CacheKind::Decoded, key(0), &[0; 800])?;
+        thread::sleep(Duration::from_millis(10));
+        cache.write(CacheKind::Decoded, key(1), &[1; 800])?;
+
+        client.set_cache_dir(Some(dir.clone())).await?;
+        assert_eq!(client.get_cache_dir().await?, Some(dir.clone()));
+
+        let usage = client.get_cache_usage().await?;
+        assert_eq!(num_entries(&usage, CacheKind::Decoded), 1);
+        assert_eq!(cache.read(CacheKind::Decoded, key(1))?, Some(vec![1; 800]));
+
+        client.clear_cache(None).await?;
+        let usage = client.get_cache_usage().await?;
+        assert_eq!(num_entries(&usage, CacheKind::Decoded), 0);
+
+        client.set_cache_dir(None).await?;
+        assert_eq!(client.get_cache_dir().await?, None);
+
+        Ok(())
+    })
+}
@@ -0,0 +1,3 @@
+mod ops;
+#[cfg(test)]
+mod tests;
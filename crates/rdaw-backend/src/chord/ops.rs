@@ -0,0 +1,123 @@
+use rdaw_api::arrangement::ArrangementId;
+use rdaw_api::chord::{Chord, ChordEvent, ChordId, ChordOperations, ChordRequest, ChordResponse};
+use rdaw_api::{bail, format_err, BackendProtocol, ErrorKind, Result};
+use rdaw_rpc::StreamId;
+use tracing::instrument;
+
+use crate::Backend;
+
+#[rdaw_rpc::handler(protocol = BackendProtocol, operations = ChordOperations)]
+impl Backend {
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn subscribe_arrangement_chords(&mut self, id: ArrangementId) -> Result<StreamId> {
+        self.hub.arrangements.ensure_has(id)?;
+        Ok(self.subscribers.arrangement_chords.subscribe(id))
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn add_arrangement_chord(&mut self, id: ArrangementId, chord: Chord) -> Result<ChordId> {
+        self.ensure_chord_fits(id, None, &chord)?;
+
+        let arrangement = self.hub.arrangements.get_mut_or_err(id)?;
+        let chord_id = arrangement.chords.insert(chord);
+
+        self.subscribers.arrangement_chords.notify(
+            id,
+            ChordEvent::Added {
+                id: chord_id,
+                chord,
+            },
+        );
+
+        Ok(chord_id)
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn get_arrangement_chord(&self, id: ArrangementId, chord_id: ChordId) -> Result<Chord> {
+        let arrangement = self.hub.arrangements.get_or_err(id)?;
+        arrangement.chords.get(chord_id).copied().ok_or_else(|| {
+            format_err!(ErrorKind::InvalidId, "{chord_id:?} doesn't exist in {id:?}")
+        })
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn get_arrangement_chords(&self, id: ArrangementId) -> Result<Vec<(ChordId, Chord)>> {
+        let arrangement = self.hub.arrangements.get_or_err(id)?;
+
+        let mut chords = arrangement
+            .chords
+            .iter()
+            .map(|(chord_id, chord)| (chord_id, *chord))
+            .collect::<Vec<_>>();
+        chords.sort_by_key(|(chord_id, chord)| (chord.start, *chord_id));
+
+        Ok(chords)
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn set_arrangement_chord(
+        &mut self,
+        id: ArrangementId,
+        chord_id: ChordId,
+        chord: Chord,
+    ) -> Result<()> {
+        self.get_arrangement_chord(id, chord_id)?;
+        self.ensure_chord_fits(id, Some(chord_id), &chord)?;
+
+        let arrangement = self.hub.arrangements.get_mut_or_err(id)?;
+        arrangement.chords[chord_id] = chord;
+
+        self.subscribers.arrangement_chords.notify(
+            id,
+            ChordEvent::Changed {
+                id: chord_id,
+                chord,
+            },
+        );
+
+        Ok(())
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn remove_arrangement_chord(&mut self, id: ArrangementId, chord_id: ChordId) -> Result<()> {
+        let arrangement = self.hub.arrangements.get_mut_or_err(id)?;
+
+        if arrangement.chords.remove(chord_id).is_some() {
+            self.subscribers
+                .arrangement_chords
+                .notify(id, ChordEvent::Removed { id: chord_id });
+        }
+
+        Ok(())
+    }
+
+    fn ensure_chord_fits(
+        &self,
+        id: ArrangementId,
+        chord_id: Option<ChordId>,
+        chord: &Chord,
+    ) -> Result<()> {
+        if !chord.is_valid() {
+            bail!(ErrorKind::NotSupported, "invalid chord {chord:?}");
+        }
+
+        let arrangement = self.hub.arrangements.get_or_err(id)?;
+
+        // chords can't overlap, so that there is at most one chord and scale at any position
+        let overlapping = arrangement.chords.iter().find(|&(other_id, other)| {
+            Some(other_id) != chord_id && other.start < chord.end() && chord.start < other.end()
+        });
+
+        if let Some((other_id, _)) = overlapping {
+            bail!(ErrorKind::NotSupported, "chord overlaps with {other_id:?}");
+        }
+
+        Ok(())
+    }
+}
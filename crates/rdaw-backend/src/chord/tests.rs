@@ -0,0 +1,144 @@
+use futures::StreamExt;
+use rdaw_api::chord::{
+    Chord, ChordEvent, ChordOperations, ChordQuality, PitchClass, Scale, ScaleMode,
+};
+use rdaw_api::document::DocumentOperations;
+use rdaw_api::time::BeatTime;
+use rdaw_api::{assert_err, ErrorKind, Result};
+
+use crate::tests::run_test;
+
+fn chord(start: i32, duration: i32, root: u8, quality: ChordQuality) -> Chord {
+    Chord {
+        start: BeatTime::from_beats(start),
+        duration: BeatTime::from_beats(duration),
+        root: PitchClass(root),
+        quality,
+        bass: None,
+        scale: Scale::new(PitchClass::C, ScaleMode::Major),
+    }
+}
+
+#[test]
+fn chords() -> Result<()> {
+    run_test(|client| async move {
+        let document_id = client.create_document().await?;
+        let arrangement_id = client.get_document_arrangement(document_id).await?;
+
+        let mut stream = client.subscribe_arrangement_chords(arrangement_id).await?;
+
+        let second = chord(4, 4, 7, ChordQuality::Major);
+        let first = chord(0, 4, 0, ChordQuality::Major);
+
+        let second_id = client.add_arrangement_chord(arrangement_id, second).await?;
+        let first_id = client.add_arrangement_chord(arrangement_id, first).await?;
+
+        assert_eq!(
+            stream.next().await,
+            Some(ChordEvent::Added {
+                id: second_id,
+                chord: second,
+            })
+        );
+        assert_eq!(
+            stream.next().await,
+            Some(ChordEvent::Added {
+                id: first_id,
+                chord: first,
+            })
+        );
+
+        assert_eq!(
+            client.get_arrangement_chords(arrangement_id).await?,
+            vec![(first_id, first), (second_id, second)]
+        );
+
+        let minor = chord(4, 2, 9, ChordQuality::Minor);
+        client
+            .set_arrangement_chord(arrangement_id, second_id, minor)
+            .await?;
+        assert_eq!(
+            client
+                .get_arrangement_chord(arrangement_id, second_id)
+                .await?,
+            minor
+        );
+        assert_eq!(
+            stream.next().await,
+            Some(ChordEvent::Changed {
+                id: second_id,
+                chord: minor,
+            })
+        );
+
+        client
+            .remove_arrangement_chord(arrangement_id, first_id)
+            .await?;
+        assert_eq!(
+            stream.next().await,
+            Some(ChordEvent::Removed { id: first_id })
+        );
+        assert_err!(
+            client.get_arrangement_chord(arrangement_id, first_id).await,
+            ErrorKind::InvalidId
+        );
+
+        Ok(())
+    })
+}
+
+#[test]
+fn invalid_chords() -> Result<()> {
+    run_test(|client| async move {
+        let document_id = client.create_document().await?;
+        let arrangement_id = client.get_document_arrangement(document_id).await?;
+
+        let first = chord(0, 4, 0, ChordQuality::Major);
+        let first_id = client.add_arrangement_chord(arrangement_id, first).await?;
+
+        assert_err!(
+            client
+                .add_arrangement_chord(arrangement_id, chord(2, 4, 7, ChordQuality::Major))
+                .await,
+            ErrorKind::NotSupported
+        );
+        assert_err!(
+            client
+                .add_arrangement_chord(arrangement_id, chord(4, 0, 7, ChordQuality::Major))
+                .await,
+            ErrorKind::NotSupported
+        );
+        assert_err!(
+            client
+                .add_arrangement_chord(arrangement_id, chord(4, 4, 12, ChordQuality::Major))
+                .await,
+            ErrorKind::NotSupported
+        );
+
+        // a chord doesn't overlap with itself
+        let longer = chord(0, 8, 0, ChordQuality::Sus4);
+        client
+            .set_arrangement_chord(arrangement_id, first_id, longer)
+            .await?;
+
+        Ok(())
+    })
+}
+
+#[test]
+fn chord_tones() {
+    let mut chord = chord(0, 4, 9, ChordQuality::Minor7);
+    chord.bass = Some(PitchClass(7));
+    assert_eq!(chord.to_string(), "Am7/G");
+
+    let keys = (60..72)
+        .filter(|&key| chord.has_key(key))
+        .collect::<Vec<_>>();
+    assert_eq!(keys, vec![60, 64, 67, 69]);
+
+    let scale = Scale::new(PitchClass(9), ScaleMode::Minor);
+    let keys = (60..72)
+        .filter(|&key| scale.has_key(key))
+        .collect::<Vec<_>>();
+    assert_eq!(keys, vec![60, 62, 64, 65, 67, 69, 71]);
+}
@@ -0,0 +1,51 @@
+use std::fs;
+use std::io::ErrorKind as IoErrorKind;
+
+use rdaw_api::document::BackupSettings;
+use rdaw_api::Result;
+use rdaw_core::path::{Utf8Path, Utf8PathBuf};
+
+/// Path of a backup of the document, counting from 1 for the newest one.
+pub fn path(document_path: &Utf8Path, settings: &BackupSettings, index: usize) -> Utf8PathBuf {
+    let file_name = document_path.file_name().unwrap_or("document");
+    let dir = match &settings.dir {
+        Some(dir) => dir.as_path(),
+        None => document_path.parent().unwrap_or(Utf8Path::new("")),
+    };
+
+    dir.join(format!("{file_name}.bak{index}"))
+}
+
+/// Drops the oldest backup and shifts the rest by one. Returns the now free path for the newest
+/// backup, or `None` if backups are disabled.
+pub fn rotate(document_path: &Utf8Path, settings: &BackupSettings) -> Result<Option<Utf8PathBuf>> {
+    if settings.count == 0 {
+        return Ok(None);
+    }
+
+    if let Some(dir) = &settings.dir {
+        fs::create_dir_all(dir)?;
+    }
+
+    remove_if_exists(&path(document_path, settings, settings.count))?;
+
+    for index in (1..settings.count).rev() {
+        let from = path(document_path, settings, index);
+        let to = path(document_path, settings, index + 1);
+
+        if let Err(e) = fs::rename(from, to) {
+            if e.kind() != IoErrorKind::NotFound {
+                return Err(e.into());
+            }
+        }
+    }
+
+    Ok(Some(path(document_path, settings, 1)))
+}
+
+fn remove_if_exists(path: &Utf8Path) -> Result<()> {
+    match fs::remove_file(path) {
+        Err(e) if e.kind() != IoErrorKind::NotFound => Err(e.into()),
+        _ => Ok(()),
+    }
+}
@@ -187,3 +187,55 @@ impl io::Read for BlobReader {
         Ok(existing + extra)
     }
 }
+
+impl io::Seek for BlobReader {
+    fn seek(&mut self, pos: io::SeekFrom) -> io::Result<u64> {
+        let current = self.offset - self.buffer.len() as u64;
+
+        let target = match pos {
+            io::SeekFrom::Start(v) => Some(v),
+            io::SeekFrom::End(v) => self.blob.total_len.checked_add_signed(v),
+            io::SeekFrom::Current(v) => current.checked_add_signed(v),
+        };
+
+        let Some(target) = target else {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "invalid seek to a negative or overflowing position",
+            ));
+        };
+
+        if target == current {
+            return Ok(target);
+        }
+
+        let chunk_offset = target - target % CHUNK_SIZE as u64;
+
+        self.buffer.clear();
+        self.offset = chunk_offset;
+
+        if chunk_offset < self.blob.total_len {
+            let chunk = self
+                .db
+                .lock()
+                .unwrap()
+                .read_blob_chunk(self.id, chunk_offset)
+                .map_err(io::Error::other)?;
+
+            if let Some(chunk) = chunk {
+                let data = self
+                    .blob
+                    .compression
+                    .decompress(chunk.len as usize, &chunk.data)?;
+
+                let skip = ((target - chunk_offset) as usize).min(data.len());
+                self.buffer.extend_from_slice(&data[skip..]);
+                self.offset += chunk.len;
+            }
+        } else {
+            self.offset = target;
+        }
+
+        Ok(target)
+    }
+}
@@ -1,16 +1,26 @@
 use blake3::Hash;
+use chrono::{DateTime, Utc};
+use rdaw_api::document::{RevisionId, SnapshotId};
 use rdaw_api::{bail, format_err, ErrorKind};
 use rdaw_core::path::{Utf8Path, Utf8PathBuf};
 use rdaw_core::Uuid;
-use rusqlite::{Connection, OpenFlags};
+use rusqlite::{Connection, ErrorCode, OpenFlags};
 use tempfile::{NamedTempFile, TempPath};
 
-use super::{Blob, BlobChunk, BlobId, Compression, DocumentRevision, ObjectRevision, RevisionId};
+use super::encryption::{Cipher, SALT_LEN};
+use super::{
+    Blob, BlobChunk, BlobId, Compression, DocumentRevision, DocumentSnapshot, ObjectModification,
+    ObjectRevision,
+};
 use crate::define_version_enum;
 
 define_version_enum! {
     enum Version {
         V1 = 1,
+        V2 = 2,
+        V3 = 3,
+        V4 = 4,
+        V5 = 5,
     }
 }
 
@@ -19,6 +29,8 @@ pub struct Database {
     db: Connection,
     _temp_path: Option<TempPath>,
     next_revision: RevisionId,
+    cipher: Option<Cipher>,
+    author: Option<String>,
 }
 
 impl Database {
@@ -34,6 +46,8 @@ impl Database {
             )?,
             _temp_path: Some(temp_path),
             next_revision: RevisionId(0),
+            cipher: None,
+            author: None,
         };
 
         db.configure()?;
@@ -42,7 +56,13 @@ impl Database {
         Ok(db)
     }
 
-    pub fn open(path: &Utf8Path) -> Result<Database> {
+    pub fn open(path: &Utf8Path, passphrase: Option<&str>) -> Result<Database> {
+        let mut db = Database::open_locked(path)?;
+        db.cipher = db.unlock(passphrase)?;
+        Ok(db)
+    }
+
+    fn open_locked(path: &Utf8Path) -> Result<Database> {
         let mut db = Database {
             db: Connection::open_with_flags(
                 path,
@@ -50,6 +70,8 @@ impl Database {
             )?,
             _temp_path: None,
             next_revision: RevisionId(0),
+            cipher: None,
+            author: None,
         };
 
         db.configure()?;
@@ -59,7 +81,23 @@ impl Database {
         };
 
         match version {
-            Version::V1 => {}
+            Version::V1 => {
+                db.migrate_v1()?;
+                db.migrate_v2()?;
+                db.migrate_v3()?;
+                db.migrate_v4()?;
+            }
+            Version::V2 => {
+                db.migrate_v2()?;
+                db.migrate_v3()?;
+                db.migrate_v4()?;
+            }
+            Version::V3 => {
+                db.migrate_v3()?;
+                db.migrate_v4()?;
+            }
+            Version::V4 => db.migrate_v4()?,
+            Version::V5 => {}
         }
 
         db.next_revision = db.read_next_revision()?;
@@ -67,6 +105,72 @@ impl Database {
         Ok(db)
     }
 
+    fn migrate_v1(&mut self) -> Result<()> {
+        self.db.execute_batch(
+            "
+            CREATE TABLE encryption (
+                id INTEGER PRIMARY KEY CHECK (id = 0),
+                salt BLOB NOT NULL,
+                wrapped_key BLOB NOT NULL
+            );
+            ",
+        )?;
+        self.write_version(Version::V2)?;
+        Ok(())
+    }
+
+    fn migrate_v2(&mut self) -> Result<()> {
+        self.db.execute_batch(
+            "
+            CREATE TABLE snapshots (
+                id INTEGER PRIMARY KEY ASC,
+                revision_id INTEGER NOT NULL REFERENCES revisions (id),
+                name TEXT NOT NULL
+            );
+            ",
+        )?;
+        self.write_version(Version::V3)?;
+        Ok(())
+    }
+
+    fn migrate_v3(&mut self) -> Result<()> {
+        // objects are written before their revision is saved, so an object belongs to the
+        // revision after the one in its row. Without history the best guess for the time of a
+        // change is the revision in which the object got its current blob.
+        self.db.execute_batch(
+            "
+            ALTER TABLE objects ADD COLUMN modified_at TEXT;
+            ALTER TABLE objects ADD COLUMN author TEXT;
+
+            UPDATE objects SET modified_at = (
+                SELECT r.created_at
+                FROM objects f
+                JOIN revisions r ON r.id = f.revision_id + 1
+                WHERE f.uuid = objects.uuid
+                    AND f.blob_id = objects.blob_id
+                    AND f.revision_id <= objects.revision_id
+                ORDER BY f.revision_id
+                LIMIT 1
+            );
+            ",
+        )?;
+        self.write_version(Version::V4)?;
+        Ok(())
+    }
+
+    fn migrate_v4(&mut self) -> Result<()> {
+        self.db.execute_batch(
+            "
+            CREATE TABLE waveform_peaks (
+                asset_hash BLOB PRIMARY KEY,
+                blob_id INTEGER NOT NULL REFERENCES blobs (id) ON DELETE CASCADE
+            );
+            ",
+        )?;
+        self.write_version(Version::V5)?;
+        Ok(())
+    }
+
     fn configure(&mut self) -> Result<()> {
         self.db.execute_batch(
             "
@@ -124,10 +228,29 @@ impl Database {
                 uuid BLOB NOT NULL,
                 revision_id INTEGER NOT NULL,
                 blob_id INTEGER NOT NULL REFERENCES blobs (id),
+                modified_at TEXT,
+                author TEXT,
                 PRIMARY KEY (uuid, revision_id)
             );
 
             CREATE INDEX objects_blob_idx ON objects (blob_id);
+
+            CREATE TABLE encryption (
+                id INTEGER PRIMARY KEY CHECK (id = 0),
+                salt BLOB NOT NULL,
+                wrapped_key BLOB NOT NULL
+            );
+
+            CREATE TABLE snapshots (
+                id INTEGER PRIMARY KEY ASC,
+                revision_id INTEGER NOT NULL REFERENCES revisions (id),
+                name TEXT NOT NULL
+            );
+
+            CREATE TABLE waveform_peaks (
+                asset_hash BLOB PRIMARY KEY,
+                blob_id INTEGER NOT NULL REFERENCES blobs (id) ON DELETE CASCADE
+            );
             ",
         )?;
         Ok(())
@@ -152,12 +275,41 @@ impl Database {
         Ok(())
     }
 
+    pub fn schema(&self) -> Result<String> {
+        let mut schema = String::new();
+
+        let version: u32 = self
+            .db
+            .query_row("PRAGMA user_version", [], |row| row.get(0))?;
+        schema.push_str(&format!("PRAGMA user_version = {version};\n"));
+
+        let mut stmt = self.db.prepare_cached(
+            "SELECT sql FROM sqlite_master WHERE sql IS NOT NULL ORDER BY type, name",
+        )?;
+
+        let mut rows = stmt.query([])?;
+        while let Some(row) = rows.next()? {
+            let sql: String = row.get(0)?;
+            let sql = sql.split_whitespace().collect::<Vec<_>>().join(" ");
+            schema.push_str(&sql);
+            schema.push_str(";\n");
+        }
+
+        Ok(schema)
+    }
+
     pub fn save(&mut self, revision: DocumentRevision) -> Result<()> {
         self.save_revision(revision)?;
         self.db.execute_batch("PRAGMA wal_checkpoint(FULL)")?;
         Ok(())
     }
 
+    /// Writes a copy of the database to a file which doesn't exist yet.
+    pub fn backup_into(&self, path: &Utf8Path) -> Result<()> {
+        self.db.execute("VACUUM INTO ?1", [path.as_str()])?;
+        Ok(())
+    }
+
     pub fn save_as(&self, path: &Utf8Path, revision: DocumentRevision) -> Result<Database> {
         let target_dir = path.parent().map(|v| Ok(v.to_owned())).unwrap_or_else(|| {
             Utf8PathBuf::from_path_buf(std::env::temp_dir()).map_err(|path| {
@@ -179,13 +331,106 @@ impl Database {
         self.db
             .execute("VACUUM INTO ?1", [temp_file_path.as_str()])?;
 
-        let mut new_db = Database::open(temp_file_path)?;
+        let mut new_db = Database::open_locked(temp_file_path)?;
+        new_db.cipher.clone_from(&self.cipher);
         new_db.save(revision)?;
         drop(new_db);
 
         temp_file.persist(path).map_err(|e| Error::from(e.error))?;
 
-        Database::open(path)
+        let mut db = Database::open_locked(path)?;
+        db.cipher.clone_from(&self.cipher);
+        Ok(db)
+    }
+
+    pub fn is_encrypted(&self) -> bool {
+        self.cipher.is_some()
+    }
+
+    /// Author recorded for objects changed from now on.
+    pub fn set_author(&mut self, author: Option<String>) {
+        self.author = author;
+    }
+
+    fn read_encryption(&self) -> Result<Option<(Vec<u8>, Vec<u8>)>> {
+        let mut stmt = self
+            .db
+            .prepare_cached("SELECT salt, wrapped_key FROM encryption WHERE id = 0")?;
+
+        stmt.query([])
+            .map_err(Error::from)
+            .and_then(|mut rows| {
+                let Some(row) = rows.next()? else {
+                    return Ok(None);
+                };
+
+                Ok(Some((row.get(0)?, row.get(1)?)))
+            })
+    }
+
+    fn unlock(&self, passphrase: Option<&str>) -> Result<Option<Cipher>> {
+        let Some((salt, wrapped_key)) = self.read_encryption()? else {
+            return Ok(None);
+        };
+
+        let Some(passphrase) = passphrase else {
+            bail!(ErrorKind::InvalidPassphrase, "document is encrypted");
+        };
+
+        let key_cipher = Cipher::from_passphrase(passphrase, &salt)?;
+        let cipher = key_cipher.unwrap_key(&wrapped_key)?;
+
+        Ok(Some(cipher))
+    }
+
+    pub fn set_passphrase(&mut self, passphrase: Option<&str>) -> Result<()> {
+        let old_cipher = self.cipher.clone();
+
+        // pages freed below are zeroed, so the old key and chunks aren't left in the file
+        self.db.execute_batch("PRAGMA secure_delete = ON")?;
+
+        // the key and the chunks have to change together, or the document can't be opened
+        let tx = self.db.transaction()?;
+
+        let new_cipher = match passphrase {
+            Some(passphrase) => {
+                let cipher = old_cipher.clone().unwrap_or_else(Cipher::new_random);
+
+                let salt: [u8; SALT_LEN] = rand::random();
+                let key_cipher = Cipher::from_passphrase(passphrase, &salt)?;
+                let wrapped_key = key_cipher.wrap_key(&cipher)?;
+
+                let mut stmt = tx.prepare_cached(
+                    "INSERT OR REPLACE INTO encryption (id, salt, wrapped_key) VALUES (0, ?1, ?2)",
+                )?;
+                stmt.execute(rusqlite::params![&salt[..], wrapped_key])?;
+
+                Some(cipher)
+            }
+            None => {
+                tx.execute("DELETE FROM encryption", [])?;
+                None
+            }
+        };
+
+        if old_cipher.is_some() != new_cipher.is_some() {
+            reencrypt_chunks(&tx, old_cipher.as_ref(), new_cipher.as_ref())?;
+        }
+
+        tx.commit()?;
+        self.cipher = new_cipher;
+
+        // the old pages are also in the WAL, and the file is rebuilt without any free pages
+        self.db.execute_batch(
+            "
+            PRAGMA wal_checkpoint(TRUNCATE);
+            VACUUM;
+            PRAGMA wal_checkpoint(TRUNCATE);
+            PRAGMA secure_delete = OFF;
+            ",
+        )?;
+
+        Ok(())
     }
 
     pub fn revisions(&self) -> Result<Vec<(RevisionId, DocumentRevision)>> {
@@ -210,6 +455,22 @@ impl Database {
         self.next_revision
     }
 
+    pub fn blob_stats(&self) -> Result<(u64, u64)> {
+        let mut stmt = self
+            .db
+            .prepare_cached("SELECT COUNT(*), COALESCE(SUM(total_len), 0) FROM blobs")?;
+        let stats = stmt.query_row([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        Ok(stats)
+    }
+
+    pub fn file_size(&self) -> Result<u64> {
+        let mut stmt = self.db.prepare_cached(
+            "SELECT page_count * page_size FROM pragma_page_count(), pragma_page_size()",
+        )?;
+        let size = stmt.query_row([], |row| row.get(0))?;
+        Ok(size)
+    }
+
     fn read_next_revision(&self) -> Result<RevisionId> {
         let mut stmt = self.db.prepare_cached("SELECT COUNT(*) FROM revisions")?;
         let id = stmt.query_row([], |row| row.get(0).map(RevisionId))?;
@@ -232,6 +493,144 @@ impl Database {
         Ok(())
     }
 
+    /// Marks the last saved revision with a name.
+    pub fn create_snapshot(&self, name: &str) -> Result<SnapshotId> {
+        let mut stmt = self.db.prepare_cached(
+            "
+            INSERT INTO snapshots (revision_id, name)
+            VALUES ((SELECT MAX(id) FROM revisions), ?1)
+            RETURNING id
+            ",
+        )?;
+
+        let id = stmt.query_row([name], |row| row.get(0).map(SnapshotId))?;
+        Ok(id)
+    }
+
+    pub fn snapshots(&self) -> Result<Vec<(SnapshotId, DocumentSnapshot)>> {
+        let mut stmt = self.db.prepare_cached(
+            "
+            SELECT s.id, s.name, s.revision_id, r.created_at, r.time_spent, r.arrangement_uuid
+            FROM snapshots s
+            JOIN revisions r ON r.id = s.revision_id
+            ORDER BY s.id
+            ",
+        )?;
+
+        let iter = stmt.query_and_then([], |row| {
+            let id = SnapshotId(row.get(0)?);
+            let snapshot = DocumentSnapshot {
+                name: row.get(1)?,
+                revision_id: RevisionId(row.get(2)?),
+                revision: DocumentRevision {
+                    created_at: row.get(3)?,
+                    time_spent_secs: row.get(4)?,
+                    arrangement_uuid: row.get(5)?,
+                },
+            };
+            Ok((id, snapshot))
+        })?;
+
+        iter.collect()
+    }
+
+    pub fn remove_snapshot(&self, id: SnapshotId) -> Result<bool> {
+        let mut stmt = self
+            .db
+            .prepare_cached("DELETE FROM snapshots WHERE id = ?1")?;
+        let count = stmt.execute([id.0])?;
+        Ok(count > 0)
+    }
+
+    /// Removes objects and blobs written by a save which didn't finish. Returns the number of
+    /// removed objects.
+    pub fn discard_incomplete_revision(&mut self) -> Result<usize> {
+        let tx = self.db.transaction()?;
+
+        // objects are written before their revision is saved, so they can't be newer than it
+        let num_objects = tx.execute(
+            "DELETE FROM objects WHERE revision_id >= ?1",
+            [self.next_revision.0],
+        )?;
+        tx.execute("DELETE FROM blobs WHERE hash IS NULL", [])?;
+
+        tx.commit()?;
+        Ok(num_objects)
+    }
+
+    /// Saves a new revision in which every object is the same as it was in `target`.
+    pub fn restore(&mut self, target: RevisionId, revision: DocumentRevision) -> Result<()> {
+        // objects are written before their revision is saved, so the ones belonging to `target`
+        // have smaller revision ids
+        let mut stmt = self.db.prepare_cached(
+            "
+            INSERT OR REPLACE INTO objects (uuid, revision_id, blob_id, modified_at, author)
+            SELECT o.uuid, ?2, o.blob_id, o.modified_at, o.author
+            FROM objects o
+            WHERE o.revision_id = (
+                SELECT MAX(revision_id) FROM objects WHERE uuid = o.uuid AND revision_id < ?1
+            )
+            ",
+        )?;
+
+        stmt.execute(rusqlite::params![target.0, self.next_revision.0])?;
+        drop(stmt);
+
+        self.save(revision)
+    }
+
+    /// Copies the database into a new temporary one and restores `target` there.
+    pub fn checkout(&self, target: RevisionId, revision: DocumentRevision) -> Result<Database> {
+        let temp_path = NamedTempFile::with_prefix(".rdaw-unsaved-")?.into_temp_path();
+        let path = Utf8Path::from_path(&temp_path)
+            .ok_or_else(|| format_err!(ErrorKind::InvalidUtf8, "invalid utf-8 in temp path"))?;
+
+        // the temp file is still empty, which VACUUM INTO accepts
+        self.db.execute("VACUUM INTO ?1", [path.as_str()])?;
+
+        let mut db = Database::open_locked(path)?;
+        db._temp_path = Some(temp_path);
+        db.cipher.clone_from(&self.cipher);
+        db.author.clone_from(&self.author);
+        db.restore(target, revision)?;
+
+        Ok(db)
+    }
+
+    /// Returns the hash of the blob with cached peaks of an asset.
+    pub fn find_waveform_peaks(&self, asset_hash: Hash) -> Result<Option<Hash>> {
+        let mut stmt = self.db.prepare_cached(
+            "
+            SELECT b.hash
+            FROM waveform_peaks p
+            JOIN blobs b ON b.id = p.blob_id
+            WHERE p.asset_hash = ?1
+            ",
+        )?;
+
+        stmt.query([asset_hash.as_bytes()])
+            .map_err(Error::from)
+            .and_then(|mut rows| {
+                let Some(row) = rows.next()? else {
+                    return Ok(None);
+                };
+
+                Ok(Some(Hash::from_bytes(row.get(0)?)))
+            })
+    }
+
+    pub fn write_waveform_peaks(&self, asset_hash: Hash, hash: Hash) -> Result<()> {
+        let mut stmt = self.db.prepare_cached(
+            "
+            INSERT OR REPLACE INTO waveform_peaks (asset_hash, blob_id)
+            VALUES (?1, (SELECT id FROM blobs WHERE hash = ?2))
+            ",
+        )?;
+
+        stmt.execute(rusqlite::params![asset_hash.as_bytes(), hash.as_bytes()])?;
+        Ok(())
+    }
+
     pub fn create_blob(&self, blob: Blob) -> Result<BlobId> {
         let mut stmt = self.db.prepare_cached(
             "INSERT INTO blobs (hash, total_len, compression) VALUES (?1, ?2, ?3) RETURNING id",
@@ -335,11 +734,16 @@ impl Database {
             "INSERT INTO blob_chunks (blob_id, offset, len, data) VALUES (?1, ?2, ?3, ?4)",
         )?;
 
+        let data = match &self.cipher {
+            Some(cipher) => cipher.encrypt(&chunk.data)?.into(),
+            None => chunk.data,
+        };
+
         stmt.execute(rusqlite::params![
             chunk.blob_id.0,
             chunk.offset,
             chunk.len,
-            chunk.data
+            data
         ])?;
 
         Ok(())
@@ -361,11 +765,17 @@ impl Database {
                     return Ok(None);
                 };
 
+                let mut data = row.get::<_, Vec<u8>>(1)?;
+
+                if let Some(cipher) = &self.cipher {
+                    data = cipher.decrypt(&data)?;
+                }
+
                 let chunk = BlobChunk {
                     blob_id,
                     offset,
                     len: row.get(0)?,
-                    data: row.get::<_, Vec<u8>>(1)?.into(),
+                    data: data.into(),
                 };
 
                 Ok(Some(chunk))
@@ -380,9 +790,46 @@ impl Database {
             let blob_id = stmt.query_row([hash.as_bytes()], |row| row.get(0).map(BlobId))?;
 
             let mut stmt = tx.prepare_cached(
-                "INSERT INTO objects (uuid, revision_id, blob_id) VALUES (?1, ?2, ?3)",
+                "
+                SELECT blob_id, modified_at, author
+                FROM objects
+                WHERE uuid = ?1
+                ORDER BY revision_id DESC
+                LIMIT 1
+                ",
+            )?;
+
+            let previous = stmt.query([uuid]).map_err(Error::from).and_then(|mut rows| {
+                let Some(row) = rows.next()? else {
+                    return Ok(None);
+                };
+
+                let modified_at: Option<DateTime<Utc>> = row.get(1)?;
+                let author: Option<String> = row.get(2)?;
+                Ok(Some((BlobId(row.get(0)?), modified_at, author)))
+            })?;
+
+            // every object is written on save, but only the changed ones get new metadata
+            let (modified_at, author) = match previous {
+                Some((previous_blob_id, modified_at, author)) if previous_blob_id == blob_id => {
+                    (modified_at, author)
+                }
+                _ => (Some(Utc::now()), self.author.clone()),
+            };
+
+            let mut stmt = tx.prepare_cached(
+                "
+                INSERT INTO objects (uuid, revision_id, blob_id, modified_at, author)
+                VALUES (?1, ?2, ?3, ?4, ?5)
+                ",
             )?;
-            stmt.execute(rusqlite::params![uuid, self.next_revision.0, blob_id.0])?;
+            stmt.execute(rusqlite::params![
+                uuid,
+                self.next_revision.0,
+                blob_id.0,
+                modified_at,
+                author
+            ])?;
         }
 
         tx.commit()?;
@@ -415,6 +862,93 @@ impl Database {
                 }))
             })
     }
+
+    pub fn object_modification(&self, uuid: Uuid) -> Result<Option<ObjectModification>> {
+        let mut stmt = self.db.prepare_cached(
+            "
+            SELECT modified_at, author
+            FROM objects
+            WHERE uuid = ?1
+            ORDER BY revision_id DESC
+            LIMIT 1
+            ",
+        )?;
+
+        stmt.query([uuid])
+            .map_err(Error::from)
+            .and_then(|mut rows| {
+                let Some(row) = rows.next()? else {
+                    return Ok(None);
+                };
+
+                let Some(modified_at) = row.get::<_, Option<DateTime<Utc>>>(0)? else {
+                    return Ok(None);
+                };
+
+                Ok(Some(ObjectModification {
+                    uuid,
+                    modified_at,
+                    author: row.get(1)?,
+                }))
+            })
+    }
+
+    /// Objects of the last revision, most recently modified first.
+    pub fn recently_modified_objects(&self, limit: usize) -> Result<Vec<ObjectModification>> {
+        let mut stmt = self.db.prepare_cached(
+            "
+            SELECT o.uuid, o.modified_at, o.author
+            FROM objects o
+            WHERE o.revision_id = (SELECT MAX(revision_id) FROM objects)
+                AND o.modified_at IS NOT NULL
+            ORDER BY o.modified_at DESC, o.uuid
+            LIMIT ?1
+            ",
+        )?;
+
+        let iter = stmt.query_and_then([limit], |row| {
+            Ok(ObjectModification {
+                uuid: row.get(0)?,
+                modified_at: row.get(1)?,
+                author: row.get(2)?,
+            })
+        })?;
+
+        iter.collect()
+    }
+}
+
+fn reencrypt_chunks(
+    tx: &rusqlite::Transaction<'_>,
+    old: Option<&Cipher>,
+    new: Option<&Cipher>,
+) -> Result<()> {
+    let mut stmt = tx.prepare_cached("SELECT blob_id, offset FROM blob_chunks")?;
+    let keys = stmt
+        .query_map([], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, u64>(1)?)))?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut select_stmt =
+        tx.prepare_cached("SELECT data FROM blob_chunks WHERE blob_id = ?1 AND offset = ?2")?;
+    let mut update_stmt =
+        tx.prepare_cached("UPDATE blob_chunks SET data = ?3 WHERE blob_id = ?1 AND offset = ?2")?;
+
+    for (blob_id, offset) in keys {
+        let mut data: Vec<u8> =
+            select_stmt.query_row(rusqlite::params![blob_id, offset], |row| row.get(0))?;
+
+        if let Some(old) = old {
+            data = old.decrypt(&data)?;
+        }
+
+        if let Some(new) = new {
+            data = new.encrypt(&data)?;
+        }
+
+        update_stmt.execute(rusqlite::params![blob_id, offset, data])?;
+    }
+
+    Ok(())
 }
 
 pub type Result<T, E = Error> = std::result::Result<T, E>;
@@ -432,9 +966,22 @@ pub enum Error {
 impl From<Error> for rdaw_api::Error {
     fn from(value: Error) -> Self {
         match value {
-            Error::Sql(v) => rdaw_api::Error::new(ErrorKind::Sql, v),
+            Error::Sql(v) => rdaw_api::Error::new(sql_error_kind(&v), v),
             Error::Io(v) => v.into(),
             Error::Api(v) => v,
         }
     }
 }
+
+fn sql_error_kind(error: &rusqlite::Error) -> ErrorKind {
+    let Some(code) = error.sqlite_error_code() else {
+        return ErrorKind::Sql;
+    };
+
+    match code {
+        ErrorCode::DatabaseBusy | ErrorCode::DatabaseLocked => ErrorKind::Busy,
+        ErrorCode::DatabaseCorrupt | ErrorCode::NotADatabase => ErrorKind::Corrupted,
+        ErrorCode::ReadOnly => ErrorKind::ReadOnly,
+        _ => ErrorKind::Sql,
+    }
+}
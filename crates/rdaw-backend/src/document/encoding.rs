@@ -1,25 +1,65 @@
 use rdaw_api::error::ResultExt;
-use rdaw_api::{format_err, Error, ErrorKind, Result};
+use rdaw_api::{bail, Error, ErrorKind, Result};
 use serde::{Deserialize, Serialize};
+use serde_json::value::RawValue;
 
-pub fn serialize<T: Serialize>(version: u32, value: &T) -> Result<Vec<u8>> {
-    let mut vec = Vec::with_capacity(128);
-    vec.extend(version.to_le_bytes());
-    postcard::to_extend(value, vec).convert_err(ErrorKind::Serialization)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Format {
+    #[default]
+    Binary,
+    Json,
 }
 
-pub fn extract_version(data: &[u8]) -> Result<(u32, &[u8]), Error> {
-    let version = u32::from_le_bytes(
-        data[0..4]
-            .try_into()
-            .map_err(|_| format_err!(ErrorKind::Deserialization, "version field too short"))?,
-    );
+#[derive(Debug, Serialize)]
+struct JsonVersioned<'a, T> {
+    version: u32,
+    data: &'a T,
+}
+
+#[derive(Debug, Deserialize)]
+struct JsonVersionedRaw<'a> {
+    version: u32,
+    #[serde(borrow)]
+    data: &'a RawValue,
+}
 
-    Ok((version, &data[4..]))
+pub fn serialize<T: Serialize>(format: Format, version: u32, value: &T) -> Result<Vec<u8>> {
+    match format {
+        Format::Binary => {
+            let mut vec = Vec::with_capacity(128);
+            vec.extend(version.to_le_bytes());
+            postcard::to_extend(value, vec).convert_err(ErrorKind::Serialization)
+        }
+        Format::Json => serde_json::to_vec(&JsonVersioned {
+            version,
+            data: value,
+        })
+        .convert_err(ErrorKind::Serialization),
+    }
+}
+
+pub fn extract_version(format: Format, data: &[u8]) -> Result<(u32, &[u8]), Error> {
+    match format {
+        Format::Binary => {
+            let Some((version, data)) = data.split_first_chunk::<4>() else {
+                bail!(ErrorKind::Deserialization, "version field too short");
+            };
+
+            Ok((u32::from_le_bytes(*version), data))
+        }
+        Format::Json => {
+            let raw = serde_json::from_slice::<JsonVersionedRaw>(data)
+                .convert_err(ErrorKind::Deserialization)?;
+            Ok((raw.version, raw.data.get().as_bytes()))
+        }
+    }
 }
 
-pub fn deserialize<'de, T: Deserialize<'de>>(data: &'de [u8]) -> Result<T, Error> {
-    postcard::from_bytes(data).convert_err(ErrorKind::Deserialization)
+pub fn deserialize<'de, T: Deserialize<'de>>(format: Format, data: &'de [u8]) -> Result<T, Error> {
+    match format {
+        Format::Binary => postcard::from_bytes(data).convert_err(ErrorKind::Deserialization),
+        Format::Json => serde_json::from_slice(data).convert_err(ErrorKind::Deserialization),
+    }
 }
 
 #[macro_export]
@@ -0,0 +1,86 @@
+use std::fmt;
+
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
+use rdaw_api::{bail, format_err, ErrorKind, Result};
+
+pub const SALT_LEN: usize = 16;
+
+const KEY_LEN: usize = 32;
+const NONCE_LEN: usize = 24;
+
+#[derive(Clone)]
+pub struct Cipher {
+    key: [u8; KEY_LEN],
+}
+
+impl Cipher {
+    pub fn new_random() -> Cipher {
+        Cipher {
+            key: rand::random(),
+        }
+    }
+
+    pub fn from_passphrase(passphrase: &str, salt: &[u8]) -> Result<Cipher> {
+        let mut key = [0; KEY_LEN];
+
+        argon2::Argon2::default()
+            .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+            .map_err(|e| format_err!(ErrorKind::Other, "failed to derive key: {e}"))?;
+
+        Ok(Cipher { key })
+    }
+
+    pub fn encrypt(&self, data: &[u8]) -> Result<Vec<u8>> {
+        let nonce: [u8; NONCE_LEN] = rand::random();
+
+        let ciphertext = self
+            .aead()
+            .encrypt(XNonce::from_slice(&nonce), data)
+            .map_err(|_| format_err!(ErrorKind::Other, "failed to encrypt data"))?;
+
+        let mut out = Vec::with_capacity(NONCE_LEN + ciphertext.len());
+        out.extend_from_slice(&nonce);
+        out.extend_from_slice(&ciphertext);
+
+        Ok(out)
+    }
+
+    pub fn decrypt(&self, data: &[u8]) -> Result<Vec<u8>> {
+        if data.len() < NONCE_LEN {
+            bail!(ErrorKind::Corrupted, "encrypted data is too short");
+        }
+
+        let (nonce, ciphertext) = data.split_at(NONCE_LEN);
+
+        self.aead()
+            .decrypt(XNonce::from_slice(nonce), ciphertext)
+            .map_err(|_| format_err!(ErrorKind::Corrupted, "failed to decrypt data"))
+    }
+
+    pub fn wrap_key(&self, cipher: &Cipher) -> Result<Vec<u8>> {
+        self.encrypt(&cipher.key)
+    }
+
+    pub fn unwrap_key(&self, wrapped_key: &[u8]) -> Result<Cipher> {
+        let key = self
+            .decrypt(wrapped_key)
+            .map_err(|_| format_err!(ErrorKind::InvalidPassphrase, "invalid passphrase"))?;
+
+        let key = key
+            .try_into()
+            .map_err(|_| format_err!(ErrorKind::Deserialization, "invalid key length"))?;
+
+        Ok(Cipher { key })
+    }
+
+    fn aead(&self) -> XChaCha20Poly1305 {
+        XChaCha20Poly1305::new(Key::from_slice(&self.key))
+    }
+}
+
+impl fmt::Debug for Cipher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Cipher").finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,72 @@
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Write};
+
+use rdaw_api::error::ResultExt;
+use rdaw_api::{ErrorKind, Result};
+use rdaw_core::collections::HashMap;
+use rdaw_core::path::Utf8Path;
+use rdaw_core::Uuid;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::define_version_enum;
+
+define_version_enum! {
+    enum Version {
+        V1 = 1,
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct JsonDocument {
+    version: u32,
+    arrangement_uuid: Uuid,
+    objects: BTreeMap<Uuid, Value>,
+}
+
+pub fn write(
+    path: &Utf8Path,
+    arrangement_uuid: Uuid,
+    objects: BTreeMap<Uuid, Vec<u8>>,
+) -> Result<()> {
+    let objects = objects
+        .into_iter()
+        .map(|(uuid, data)| {
+            let value = serde_json::from_slice(&data).convert_err(ErrorKind::Serialization)?;
+            Ok((uuid, value))
+        })
+        .collect::<Result<_>>()?;
+
+    let document = JsonDocument {
+        version: Version::LATEST.as_u32(),
+        arrangement_uuid,
+        objects,
+    };
+
+    let mut writer = BufWriter::new(File::create(path)?);
+    serde_json::to_writer_pretty(&mut writer, &document).convert_err(ErrorKind::Serialization)?;
+    writer.write_all(b"\n")?;
+    writer.flush()?;
+
+    Ok(())
+}
+
+pub fn read(path: &Utf8Path) -> Result<(Uuid, HashMap<Uuid, Vec<u8>>)> {
+    let reader = BufReader::new(File::open(path)?);
+    let document = serde_json::from_reader::<_, JsonDocument>(reader)
+        .convert_err(ErrorKind::Deserialization)?;
+
+    Version::from_u32(document.version)?;
+
+    let objects = document
+        .objects
+        .into_iter()
+        .map(|(uuid, value)| {
+            let data = serde_json::to_vec(&value).convert_err(ErrorKind::Deserialization)?;
+            Ok((uuid, data))
+        })
+        .collect::<Result<_>>()?;
+
+    Ok((document.arrangement_uuid, objects))
+}
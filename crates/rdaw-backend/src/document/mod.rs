@@ -1,7 +1,10 @@
+mod backup;
 mod blob;
 mod compression;
 mod database;
 pub mod encoding;
+mod encryption;
+mod json;
 mod ops;
 mod storage;
 #[cfg(test)]
@@ -11,8 +14,10 @@ use std::sync::{Arc, Mutex};
 
 use blake3::Hash;
 use chrono::{DateTime, Utc};
+use rdaw_api::document::{BackupSettings, DocumentId, RevisionId, SnapshotId};
 use rdaw_api::Result;
 use rdaw_core::path::{Utf8Path, Utf8PathBuf};
+use rdaw_core::random::SeededRng;
 use rdaw_core::Uuid;
 
 use self::blob::{Blob, BlobChunk, BlobId};
@@ -20,6 +25,7 @@ pub use self::blob::{BlobReader, BlobWriter};
 pub use self::compression::Compression;
 use self::database::Database;
 pub use self::storage::DocumentStorage;
+use crate::Backend;
 
 #[derive(Debug)]
 pub struct Document {
@@ -38,7 +44,27 @@ impl Document {
     }
 
     pub fn open(path: &Utf8Path) -> Result<Document> {
-        let db = Database::open(path)?;
+        Document::open_inner(path, None)
+    }
+
+    pub fn open_with_passphrase(path: &Utf8Path, passphrase: &str) -> Result<Document> {
+        Document::open_inner(path, Some(passphrase))
+    }
+
+    /// Opens a document as of its last complete revision.
+    pub fn recover(path: &Utf8Path) -> Result<Document> {
+        let document = Document::open_inner(path, None)?;
+
+        let num_objects = document.db.lock().unwrap().discard_incomplete_revision()?;
+        if num_objects > 0 {
+            tracing::warn!(%path, num_objects, "discarded objects of an interrupted save");
+        }
+
+        Ok(document)
+    }
+
+    fn open_inner(path: &Utf8Path, passphrase: Option<&str>) -> Result<Document> {
+        let db = Database::open(path, passphrase)?;
         let document = Document {
             db: Arc::new(Mutex::new(db)),
             path: Some(path.into()),
@@ -57,6 +83,21 @@ impl Document {
         Ok(())
     }
 
+    /// Rotates the backups of a saved document and copies its current state into the newest one.
+    pub fn backup(&self, settings: &BackupSettings) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+
+        let Some(target) = backup::rotate(path, settings)? else {
+            return Ok(());
+        };
+
+        let db = self.db.lock().unwrap();
+        db.backup_into(&target)?;
+        Ok(())
+    }
+
     pub fn save_as(&self, path: &Utf8Path, revision: DocumentRevision) -> Result<Document> {
         let db = self.db.lock().unwrap();
         let new_db = db.save_as(path, revision)?;
@@ -66,16 +107,84 @@ impl Document {
         })
     }
 
+    pub fn is_encrypted(&self) -> bool {
+        let db = self.db.lock().unwrap();
+        db.is_encrypted()
+    }
+
+    pub fn set_passphrase(&self, passphrase: Option<&str>) -> Result<()> {
+        let mut db = self.db.lock().unwrap();
+        db.set_passphrase(passphrase)?;
+        Ok(())
+    }
+
+    pub fn set_author(&self, author: Option<String>) {
+        let mut db = self.db.lock().unwrap();
+        db.set_author(author);
+    }
+
+    pub fn schema(&self) -> Result<String> {
+        let db = self.db.lock().unwrap();
+        let schema = db.schema()?;
+        Ok(schema)
+    }
+
     pub fn revisions(&self) -> Result<Vec<(RevisionId, DocumentRevision)>> {
         let db = self.db.lock().unwrap();
         let revisions = db.revisions()?;
         Ok(revisions)
     }
 
+    pub fn storage_stats(&self) -> Result<StorageStats> {
+        let db = self.db.lock().unwrap();
+        let (blob_count, blob_bytes) = db.blob_stats()?;
+        let file_size = db.file_size()?;
+
+        Ok(StorageStats {
+            blob_count,
+            blob_bytes,
+            file_size,
+        })
+    }
+
     pub fn last_revision(&self) -> Result<Option<(RevisionId, DocumentRevision)>> {
         self.revisions().map(|mut v| v.pop()) // TODO: don't get all of them
     }
 
+    pub fn create_snapshot(&self, name: &str) -> Result<SnapshotId> {
+        let db = self.db.lock().unwrap();
+        let id = db.create_snapshot(name)?;
+        Ok(id)
+    }
+
+    pub fn snapshots(&self) -> Result<Vec<(SnapshotId, DocumentSnapshot)>> {
+        let db = self.db.lock().unwrap();
+        let snapshots = db.snapshots()?;
+        Ok(snapshots)
+    }
+
+    pub fn remove_snapshot(&self, id: SnapshotId) -> Result<bool> {
+        let db = self.db.lock().unwrap();
+        let removed = db.remove_snapshot(id)?;
+        Ok(removed)
+    }
+
+    pub fn restore(&self, target: RevisionId, revision: DocumentRevision) -> Result<()> {
+        let mut db = self.db.lock().unwrap();
+        db.restore(target, revision)?;
+        Ok(())
+    }
+
+    /// Copies the document into a new unsaved one, then restores `target` in the copy.
+    pub fn checkout(&self, target: RevisionId, revision: DocumentRevision) -> Result<Document> {
+        let db = self.db.lock().unwrap();
+        let new_db = db.checkout(target, revision)?;
+        Ok(Document {
+            db: Arc::new(Mutex::new(new_db)),
+            path: None,
+        })
+    }
+
     pub fn create_blob(&self, compression: Compression) -> Result<BlobWriter> {
         let id = self.db.lock().unwrap().create_blob(Blob {
             hash: None,
@@ -108,6 +217,18 @@ impl Document {
         Ok(())
     }
 
+    pub fn find_waveform_peaks(&self, asset_hash: Hash) -> Result<Option<Hash>> {
+        let db = self.db.lock().unwrap();
+        let hash = db.find_waveform_peaks(asset_hash)?;
+        Ok(hash)
+    }
+
+    pub fn write_waveform_peaks(&self, asset_hash: Hash, hash: Hash) -> Result<()> {
+        let db = self.db.lock().unwrap();
+        db.write_waveform_peaks(asset_hash, hash)?;
+        Ok(())
+    }
+
     pub fn write_object(&self, uuid: Uuid, hash: Hash) -> Result<()> {
         let mut db = self.db.lock().unwrap();
         db.write_object(uuid, hash)?;
@@ -119,10 +240,19 @@ impl Document {
         let obj = db.read_object(uuid)?;
         Ok(obj)
     }
-}
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Ord, PartialOrd)]
-pub struct RevisionId(pub u64);
+    pub fn object_modification(&self, uuid: Uuid) -> Result<Option<ObjectModification>> {
+        let db = self.db.lock().unwrap();
+        let modification = db.object_modification(uuid)?;
+        Ok(modification)
+    }
+
+    pub fn recently_modified_objects(&self, limit: usize) -> Result<Vec<ObjectModification>> {
+        let db = self.db.lock().unwrap();
+        let modifications = db.recently_modified_objects(limit)?;
+        Ok(modifications)
+    }
+}
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct DocumentRevision {
@@ -131,9 +261,41 @@ pub struct DocumentRevision {
     pub arrangement_uuid: Uuid,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DocumentSnapshot {
+    pub name: String,
+    pub revision_id: RevisionId,
+    pub revision: DocumentRevision,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct ObjectRevision {
     pub uuid: Uuid,
     pub revision_id: RevisionId,
     pub hash: Hash,
 }
+
+/// When and by whom an object was last changed, as of its last saved revision.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ObjectModification {
+    pub uuid: Uuid,
+    pub modified_at: DateTime<Utc>,
+    pub author: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StorageStats {
+    pub blob_count: u64,
+    /// Uncompressed size of all blobs.
+    pub blob_bytes: u64,
+    pub file_size: u64,
+}
+
+impl Backend {
+    /// Random generator for one generative feature of the document, such as `"humanize"`. It
+    /// gives the same numbers on every machine for as long as the document's seed stays the same.
+    pub fn document_rng(&self, document_id: DocumentId, key: &str) -> Result<SeededRng> {
+        let seed = self.get_document_seed(document_id)?;
+        Ok(SeededRng::derive(seed, key))
+    }
+}
@@ -1,12 +1,20 @@
+use std::time::Duration;
+
 use chrono::Utc;
 use rdaw_api::arrangement::ArrangementId;
-use rdaw_api::document::{DocumentId, DocumentOperations, DocumentRequest, DocumentResponse};
-use rdaw_api::{format_err, BackendProtocol, ErrorKind, Result};
+use rdaw_api::document::{
+    BackupSettings, DocumentId, DocumentOperations, DocumentRequest, DocumentResponse,
+    DocumentWarning, ObjectModification, Revision, RevisionId, Snapshot, SnapshotId,
+};
+use rdaw_api::{bail, format_err, BackendProtocol, ErrorKind, Page, PageRequest, Result};
 use rdaw_core::path::Utf8PathBuf;
+use rdaw_core::Uuid;
+use rdaw_rpc::StreamId;
 use tracing::instrument;
 
-use super::{Document, DocumentRevision};
-use crate::object::{DeserializationContext, ObjectKey, SerializationContext};
+use super::encoding::Format;
+use super::{json, Document, DocumentRevision};
+use crate::object::{self, DeserializationContext, ObjectKey, SerializationContext};
 use crate::Backend;
 
 #[rdaw_rpc::handler(protocol = BackendProtocol, operations = DocumentOperations)]
@@ -34,20 +42,54 @@ impl Backend {
     #[handler]
     pub fn open_document(&mut self, path: Utf8PathBuf) -> Result<DocumentId> {
         let document = Document::open(path.as_ref())?;
+        self.load_document(document)
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn open_encrypted_document(
+        &mut self,
+        path: Utf8PathBuf,
+        passphrase: String,
+    ) -> Result<DocumentId> {
+        let document = Document::open_with_passphrase(path.as_ref(), &passphrase)?;
+        self.load_document(document)
+    }
 
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn recover_document(&mut self, path: Utf8PathBuf) -> Result<DocumentId> {
+        let document = Document::recover(path.as_ref())?;
+        self.load_document(document)
+    }
+
+    fn load_document(&mut self, document: Document) -> Result<DocumentId> {
         let (_, last_revision) = document
             .last_revision()?
             .ok_or_else(|| format_err!(ErrorKind::Other, "document doesn't have any revisions"))?;
 
         let document_id = self.documents.insert(document);
 
-        DeserializationContext::deserialize::<ArrangementId>(
+        DeserializationContext::deserialize_partial::<ArrangementId>(
             &mut self.hub,
             &self.documents,
+            &self.path_variables,
             document_id,
             last_revision.arrangement_uuid,
         )?;
 
+        let warnings = object::validate(&mut self.hub, document_id);
+        for warning in &warnings {
+            tracing::warn!(
+                object_type = ?warning.object_type,
+                uuid = %warning.uuid,
+                repaired = warning.repaired,
+                "{}",
+                warning.message,
+            );
+        }
+        self.document_warnings.insert(document_id, warnings);
+
         let arrangement_id = self.get_document_arrangement(document_id)?;
         let main_track_id = self.get_arrangement_main_track(arrangement_id)?;
         self.recompute_track_hierarchy(main_track_id);
@@ -55,10 +97,24 @@ impl Backend {
         Ok(document_id)
     }
 
+    /// Forgets a document that was never handed out, e.g. because building it failed halfway.
+    pub fn discard_document(&mut self, id: DocumentId) {
+        self.hub.remove_document(id);
+        self.documents.remove(id);
+        self.unsaved_documents.remove(&id);
+        self.document_warnings.remove(&id);
+    }
+
     #[instrument(level = "trace", skip_all, err)]
     #[handler]
     pub fn save_document(&mut self, id: DocumentId) -> Result<()> {
+        self.write_document(id, true)
+    }
+
+    /// Saves a new revision of the document, backing up the previous one first if asked to.
+    pub fn write_document(&mut self, id: DocumentId, backup: bool) -> Result<()> {
         let document = self.documents.get_or_err(id)?;
+        document.set_author(self.author.clone());
 
         let (_, last_revision) = document
             .last_revision()?
@@ -67,7 +123,22 @@ impl Backend {
         let arrangement_key = ObjectKey::new(id, last_revision.arrangement_uuid);
         let arrangement_id = self.hub.arrangements.get_id_or_err(arrangement_key)?;
 
-        SerializationContext::serialize(&mut self.hub, &self.documents, arrangement_id)?;
+        // a failed backup shouldn't keep the changes from being saved
+        if backup {
+            if let Err(error) = document.backup(&self.backups) {
+                tracing::warn!(?id, ?error, "failed to back up document");
+            }
+        }
+
+        let base_dir = document.path().and_then(|v| v.parent());
+
+        SerializationContext::serialize(
+            &mut self.hub,
+            &self.documents,
+            &self.path_variables,
+            base_dir,
+            arrangement_id,
+        )?;
 
         let document = &self.documents[id];
         document.save(DocumentRevision {
@@ -76,6 +147,7 @@ impl Backend {
             arrangement_uuid: last_revision.arrangement_uuid,
         })?;
 
+        self.unsaved_documents.remove(&id);
         Ok(())
     }
 
@@ -83,6 +155,7 @@ impl Backend {
     #[handler]
     pub fn save_document_as(&mut self, id: DocumentId, path: Utf8PathBuf) -> Result<()> {
         let document = self.documents.get_or_err(id)?;
+        document.set_author(self.author.clone());
 
         let (_, last_revision) = document
             .last_revision()?
@@ -91,7 +164,13 @@ impl Backend {
         let arrangement_key = ObjectKey::new(id, last_revision.arrangement_uuid);
         let arrangement_id = self.hub.arrangements.get_id_or_err(arrangement_key)?;
 
-        SerializationContext::serialize(&mut self.hub, &self.documents, arrangement_id)?;
+        SerializationContext::serialize(
+            &mut self.hub,
+            &self.documents,
+            &self.path_variables,
+            path.parent(),
+            arrangement_id,
+        )?;
 
         let document = &self.documents[id];
         let new_document = document.save_as(
@@ -104,13 +183,63 @@ impl Backend {
         )?;
 
         self.documents[id] = new_document;
+        self.unsaved_documents.remove(&id);
 
         Ok(())
     }
 
     #[instrument(level = "trace", skip_all, err)]
     #[handler]
-    fn get_document_arrangement(&self, id: DocumentId) -> Result<ArrangementId> {
+    pub fn export_document_json(&mut self, id: DocumentId, path: Utf8PathBuf) -> Result<()> {
+        let arrangement_id = self.get_document_arrangement(id)?;
+
+        let (arrangement_uuid, objects) = SerializationContext::export(
+            &mut self.hub,
+            &self.documents,
+            &self.path_variables,
+            path.parent(),
+            arrangement_id,
+            Format::Json,
+        )?;
+
+        json::write(&path, arrangement_uuid, objects)
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn import_document_json(&mut self, path: Utf8PathBuf) -> Result<DocumentId> {
+        let (arrangement_uuid, objects) = json::read(&path)?;
+
+        let document = Document::new()?;
+        let document_id = self.documents.insert(document);
+
+        let arrangement_id = DeserializationContext::import::<ArrangementId>(
+            &mut self.hub,
+            &self.documents,
+            &self.path_variables,
+            path.parent(),
+            document_id,
+            arrangement_uuid,
+            &objects,
+            Format::Json,
+        )?;
+
+        let main_track_id = self.get_arrangement_main_track(arrangement_id)?;
+        self.recompute_track_hierarchy(main_track_id);
+
+        let document = &self.documents[document_id];
+        document.save(DocumentRevision {
+            created_at: Utc::now(),
+            time_spent_secs: 0,
+            arrangement_uuid,
+        })?;
+
+        Ok(document_id)
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn get_document_arrangement(&self, id: DocumentId) -> Result<ArrangementId> {
         let document = self.documents.get_or_err(id)?;
 
         let (_, last_revision) = document
@@ -120,4 +249,225 @@ impl Backend {
         let arrangement_key = ObjectKey::new(id, last_revision.arrangement_uuid);
         self.hub.arrangements.get_id_or_err(arrangement_key)
     }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn get_document_warnings(&self, id: DocumentId) -> Result<Vec<DocumentWarning>> {
+        self.documents.ensure_has(id)?;
+        Ok(self.document_warnings.get(&id).cloned().unwrap_or_default())
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn get_document_seed(&self, id: DocumentId) -> Result<u64> {
+        let arrangement_id = self.get_document_arrangement(id)?;
+        Ok(self.hub.arrangements.get_or_err(arrangement_id)?.seed)
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn set_document_seed(&mut self, id: DocumentId, seed: u64) -> Result<()> {
+        let arrangement_id = self.get_document_arrangement(id)?;
+        self.hub.arrangements.get_mut_or_err(arrangement_id)?.seed = seed;
+        Ok(())
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn subscribe_document_changes(&mut self, id: DocumentId) -> Result<StreamId> {
+        self.documents.ensure_has(id)?;
+        Ok(self.subscribers.document_changes.subscribe(id))
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn is_document_encrypted(&self, id: DocumentId) -> Result<bool> {
+        let document = self.documents.get_or_err(id)?;
+        Ok(document.is_encrypted())
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn set_document_passphrase(
+        &mut self,
+        id: DocumentId,
+        passphrase: Option<String>,
+    ) -> Result<()> {
+        let document = self.documents.get_or_err(id)?;
+        document.set_passphrase(passphrase.as_deref())
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn set_document_author(&mut self, author: Option<String>) -> Result<()> {
+        self.author = author.filter(|v| !v.trim().is_empty());
+        Ok(())
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn set_document_backups(&mut self, settings: BackupSettings) -> Result<()> {
+        self.backups = settings;
+        Ok(())
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn get_object_modification(
+        &self,
+        id: DocumentId,
+        uuid: Uuid,
+    ) -> Result<ObjectModification> {
+        let document = self.documents.get_or_err(id)?;
+
+        let modification = document.object_modification(uuid)?.ok_or_else(|| {
+            format_err!(ErrorKind::NotFound, "object {uuid} has no saved modifications")
+        })?;
+
+        Ok(ObjectModification {
+            uuid: modification.uuid,
+            modified_at: modification.modified_at.into(),
+            author: modification.author,
+        })
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn list_recently_modified_objects(
+        &self,
+        id: DocumentId,
+        limit: usize,
+    ) -> Result<Vec<ObjectModification>> {
+        let document = self.documents.get_or_err(id)?;
+
+        let modifications = document
+            .recently_modified_objects(limit)?
+            .into_iter()
+            .map(|modification| ObjectModification {
+                uuid: modification.uuid,
+                modified_at: modification.modified_at.into(),
+                author: modification.author,
+            })
+            .collect();
+
+        Ok(modifications)
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn create_snapshot(&mut self, id: DocumentId, name: String) -> Result<SnapshotId> {
+        if name.trim().is_empty() {
+            bail!(ErrorKind::NotSupported, "snapshot name can't be empty");
+        }
+
+        self.save_document(id)?;
+
+        let document = &self.documents[id];
+        document.create_snapshot(&name)
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn list_snapshots(&self, id: DocumentId, page: PageRequest) -> Result<Page<Snapshot>> {
+        let document = self.documents.get_or_err(id)?;
+
+        let snapshots = document.snapshots()?.into_iter().map(|(id, snapshot)| Snapshot {
+            id,
+            name: snapshot.name,
+            created_at: snapshot.revision.created_at.into(),
+        });
+
+        Ok(page.collect(snapshots))
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn restore_snapshot(&mut self, id: DocumentId, snapshot_id: SnapshotId) -> Result<()> {
+        let document = self.documents.get_or_err(id)?;
+
+        let (_, snapshot) = document
+            .snapshots()?
+            .into_iter()
+            .find(|(v, _)| *v == snapshot_id)
+            .ok_or_else(|| format_err!(ErrorKind::NotFound, "{snapshot_id:?} doesn't exist"))?;
+
+        let old_arrangement_id = self.get_document_arrangement(id)?;
+        let old_main_track_id = self.get_arrangement_main_track(old_arrangement_id)?;
+
+        let document = &self.documents[id];
+        document.restore(
+            snapshot.revision_id,
+            DocumentRevision {
+                created_at: Utc::now(),
+                time_spent_secs: 0,
+                arrangement_uuid: snapshot.revision.arrangement_uuid,
+            },
+        )?;
+
+        // tracks which aren't part of the snapshot stay loaded, but are detached
+        self.clear_track_hierarchy(old_main_track_id);
+
+        let arrangement_id = DeserializationContext::reload::<ArrangementId>(
+            &mut self.hub,
+            &self.documents,
+            &self.path_variables,
+            id,
+            snapshot.revision.arrangement_uuid,
+        )?;
+
+        let main_track_id = self.get_arrangement_main_track(arrangement_id)?;
+        self.recompute_track_hierarchy(main_track_id);
+        self.track_view_cache.recompute_arrangement(&self.hub, arrangement_id);
+
+        Ok(())
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn remove_snapshot(&mut self, id: DocumentId, snapshot_id: SnapshotId) -> Result<()> {
+        let document = self.documents.get_or_err(id)?;
+
+        if !document.remove_snapshot(snapshot_id)? {
+            bail!(ErrorKind::NotFound, "{snapshot_id:?} doesn't exist");
+        }
+
+        Ok(())
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn list_revisions(&self, id: DocumentId, page: PageRequest) -> Result<Page<Revision>> {
+        let document = self.documents.get_or_err(id)?;
+
+        let revisions = document.revisions()?.into_iter().map(|(id, revision)| Revision {
+            id,
+            created_at: revision.created_at.into(),
+            time_spent: Duration::from_secs(revision.time_spent_secs),
+        });
+
+        Ok(page.collect(revisions))
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn open_revision(&mut self, id: DocumentId, revision_id: RevisionId) -> Result<DocumentId> {
+        let document = self.documents.get_or_err(id)?;
+
+        let (_, revision) = document
+            .revisions()?
+            .into_iter()
+            .find(|(v, _)| *v == revision_id)
+            .ok_or_else(|| format_err!(ErrorKind::NotFound, "{revision_id:?} doesn't exist"))?;
+
+        let copy = document.checkout(
+            revision_id,
+            DocumentRevision {
+                created_at: Utc::now(),
+                time_spent_secs: revision.time_spent_secs,
+                arrangement_uuid: revision.arrangement_uuid,
+            },
+        )?;
+
+        self.load_document(copy)
+    }
 }
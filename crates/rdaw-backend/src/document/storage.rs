@@ -16,6 +16,10 @@ impl DocumentStorage {
         self.map.insert(document)
     }
 
+    pub fn remove(&mut self, id: DocumentId) -> Option<Document> {
+        self.map.remove(id)
+    }
+
     pub fn has(&self, id: DocumentId) -> bool {
         self.map.contains_key(id)
     }
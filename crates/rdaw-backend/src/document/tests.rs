@@ -1,12 +1,25 @@
-use std::io::{Read, Write};
+use std::fs;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::thread;
 
 use chrono::Utc;
+use futures::executor::block_on;
+use futures::StreamExt;
+use rdaw_api::arrangement::ArrangementOperations;
+use rdaw_api::document::{
+    BackupSettings, DocumentChangeEvent, DocumentId, DocumentOperations, ObjectType,
+};
+use rdaw_api::track::TrackOperations;
+use rdaw_api::{assert_err, ErrorKind, PageRequest};
 use rdaw_core::path::Utf8Path;
 use rdaw_core::Uuid;
+use rdaw_rpc::transport::{SocketClientTransport, SocketListener};
+use rdaw_rpc::{Authenticator, Client, Role, SharedSecret};
 use tempfile::NamedTempFile;
 
-use super::{Compression, Document, DocumentRevision, Result, RevisionId};
+use super::{backup, Compression, Document, DocumentRevision, Result, RevisionId};
 use crate::document::ObjectRevision;
+use crate::tests::{assert_golden, run_test, run_test_as, run_test_with};
 
 #[test]
 fn new() -> Result<()> {
@@ -49,6 +62,33 @@ fn save_as() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn rotate_backups() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    let dir = Utf8Path::from_path(dir.path()).unwrap();
+    let path = dir.join("project.rdaw");
+
+    let settings = BackupSettings {
+        count: 2,
+        dir: Some(dir.join("backups")),
+    };
+
+    for i in 0..3 {
+        let target = backup::rotate(&path, &settings)?.unwrap();
+        assert_eq!(target, dir.join("backups/project.rdaw.bak1"));
+        std::fs::write(target, i.to_string())?;
+    }
+
+    let read = |i| std::fs::read_to_string(backup::path(&path, &settings, i)).ok();
+    assert_eq!(read(1).as_deref(), Some("2"));
+    assert_eq!(read(2).as_deref(), Some("1"));
+    assert_eq!(read(3), None);
+
+    assert_eq!(backup::rotate(&path, &BackupSettings::default())?, None);
+
+    Ok(())
+}
+
 #[test]
 fn revisions() -> Result<()> {
     let doc = Document::new()?;
@@ -108,6 +148,39 @@ fn create_blob() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn seek_blob() -> Result<()> {
+    let doc = Document::new()?;
+
+    let data = (0..20000u32).map(|v| v as u8).collect::<Vec<_>>();
+
+    for compression in [Compression::None, Compression::Zstd] {
+        let mut writer = doc.create_blob(compression)?;
+        writer.write_all(&data)?;
+        let hash = writer.save()?;
+
+        let mut reader = doc.open_blob(hash)?.unwrap();
+        let mut buf = [0; 4];
+
+        for pos in [0, 3, 8191, 8192, 12345, 19996, 100] {
+            assert_eq!(reader.seek(SeekFrom::Start(pos))?, pos);
+            reader.read_exact(&mut buf)?;
+            assert_eq!(buf, data[pos as usize..pos as usize + 4]);
+        }
+
+        assert_eq!(reader.seek(SeekFrom::Current(-4))?, 100);
+        assert_eq!(reader.seek(SeekFrom::End(-1))?, 19999);
+
+        let mut rest = Vec::new();
+        reader.read_to_end(&mut rest)?;
+        assert_eq!(rest, [data[19999]]);
+
+        assert!(reader.seek(SeekFrom::Current(-30000)).is_err());
+    }
+
+    Ok(())
+}
+
 #[test]
 fn create_blob_with_deps() -> Result<()> {
     let doc = Document::new()?;
@@ -173,3 +246,511 @@ fn write_object() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn recover() -> Result<()> {
+    let temp_file = NamedTempFile::with_prefix(".rdaw-test-")?;
+    let path = Utf8Path::from_path(temp_file.path()).unwrap();
+
+    let revision = DocumentRevision {
+        created_at: Utc::now(),
+        time_spent_secs: 1,
+        arrangement_uuid: Uuid::new_v4(),
+    };
+    let doc = Document::new()?.save_as(path, revision)?;
+
+    let mut writer = doc.create_blob(Compression::None)?;
+    writer.write_all(&[1])?;
+    let hash = writer.save()?;
+
+    // the save is interrupted before its revision is written
+    let uuid = Uuid::new_v4();
+    doc.write_object(uuid, hash)?;
+    drop(doc);
+
+    let doc = Document::recover(path)?;
+    assert_eq!(doc.read_object(uuid)?, None);
+    assert_eq!(doc.revisions()?, vec![(RevisionId(1), revision)]);
+
+    Ok(())
+}
+
+#[test]
+fn object_modifications() -> Result<()> {
+    let doc = Document::new()?;
+
+    let write_blob = |data: &[u8]| -> Result<blake3::Hash> {
+        let mut writer = doc.create_blob(Compression::None)?;
+        writer.write_all(data)?;
+        Ok(writer.save()?)
+    };
+
+    let revision = DocumentRevision {
+        created_at: Utc::now(),
+        time_spent_secs: 1,
+        arrangement_uuid: Uuid::new_v4(),
+    };
+
+    let unchanged = Uuid::new_v4();
+    let changed = Uuid::new_v4();
+    assert_eq!(doc.object_modification(unchanged)?, None);
+
+    doc.write_object(unchanged, write_blob(&[1])?)?;
+    doc.write_object(changed, write_blob(&[2])?)?;
+    doc.save(revision)?;
+
+    let first = doc.object_modification(unchanged)?.unwrap();
+    assert_eq!(first.author, None);
+
+    doc.set_author(Some("Alice".into()));
+    doc.write_object(unchanged, write_blob(&[1])?)?;
+    doc.write_object(changed, write_blob(&[3])?)?;
+    doc.save(revision)?;
+
+    assert_eq!(doc.object_modification(unchanged)?, Some(first));
+
+    let second = doc.object_modification(changed)?.unwrap();
+    assert_eq!(second.author.as_deref(), Some("Alice"));
+    assert!(second.modified_at >= first.modified_at);
+
+    let recent = doc.recently_modified_objects(1)?;
+    assert_eq!(recent, vec![second]);
+    assert_eq!(doc.recently_modified_objects(10)?.len(), 2);
+
+    Ok(())
+}
+
+#[test]
+fn encryption() -> Result<()> {
+    let doc = Document::new()?;
+    assert!(!doc.is_encrypted());
+
+    let mut writer = doc.create_blob(Compression::Zstd)?;
+    writer.write_all(&[1, 2, 3])?;
+    let hash_1 = writer.save()?;
+
+    doc.set_passphrase(Some("secret"))?;
+    assert!(doc.is_encrypted());
+
+    let mut writer = doc.create_blob(Compression::None)?;
+    writer.write_all(&[4, 5, 6])?;
+    let hash_2 = writer.save()?;
+
+    let revision = DocumentRevision {
+        created_at: Utc::now(),
+        time_spent_secs: 15,
+        arrangement_uuid: Uuid::new_v4(),
+    };
+
+    let temp_file = NamedTempFile::with_prefix(".rdaw-test-")?;
+    let path = Utf8Path::from_path(temp_file.path()).unwrap();
+    doc.save_as(path, revision)?;
+
+    assert_err!(Document::open(path), ErrorKind::InvalidPassphrase);
+    assert_err!(
+        Document::open_with_passphrase(path, "wrong"),
+        ErrorKind::InvalidPassphrase
+    );
+
+    let copy = Document::open_with_passphrase(path, "secret")?;
+
+    for (hash, data) in [(hash_1, [1, 2, 3]), (hash_2, [4, 5, 6])] {
+        let mut reader = copy.open_blob(hash)?.unwrap();
+        let mut buf = Vec::new();
+        reader.read_to_end(&mut buf)?;
+        assert_eq!(buf, data);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn encryption_leaves_no_plaintext() -> Result<()> {
+    const PLAINTEXT: &[u8] = b"nobody should be able to read this";
+
+    let doc = Document::new()?;
+    let mut writer = doc.create_blob(Compression::None)?;
+    writer.write_all(PLAINTEXT)?;
+    writer.save()?;
+
+    let revision = DocumentRevision {
+        created_at: Utc::now(),
+        time_spent_secs: 15,
+        arrangement_uuid: Uuid::new_v4(),
+    };
+
+    let temp_file = NamedTempFile::with_prefix(".rdaw-test-")?;
+    let path = Utf8Path::from_path(temp_file.path()).unwrap();
+    let copy = doc.save_as(path, revision)?;
+    let contains_plaintext = || -> Result<bool> {
+        let data = fs::read(path)?;
+        Ok(data.windows(PLAINTEXT.len()).any(|v| v == PLAINTEXT))
+    };
+
+    assert!(contains_plaintext()?);
+    copy.set_passphrase(Some("secret"))?;
+    assert!(!contains_plaintext()?);
+
+    Ok(())
+}
+
+#[test]
+fn change_passphrase() -> Result<()> {
+    let doc = Document::new()?;
+    doc.set_passphrase(Some("old"))?;
+
+    let mut writer = doc.create_blob(Compression::None)?;
+    writer.write_all(&[1, 2, 3])?;
+    let hash = writer.save()?;
+
+    doc.set_passphrase(Some("new"))?;
+
+    let revision = DocumentRevision {
+        created_at: Utc::now(),
+        time_spent_secs: 15,
+        arrangement_uuid: Uuid::new_v4(),
+    };
+
+    let temp_file = NamedTempFile::with_prefix(".rdaw-test-")?;
+    let path = Utf8Path::from_path(temp_file.path()).unwrap();
+    doc.save_as(path, revision)?;
+
+    assert_err!(
+        Document::open_with_passphrase(path, "old"),
+        ErrorKind::InvalidPassphrase
+    );
+
+    let copy = Document::open_with_passphrase(path, "new")?;
+    copy.set_passphrase(None)?;
+    assert!(!copy.is_encrypted());
+
+    let mut reader = copy.open_blob(hash)?.unwrap();
+    let mut buf = Vec::new();
+    reader.read_to_end(&mut buf)?;
+    assert_eq!(buf, [1, 2, 3]);
+
+    Ok(())
+}
+
+#[test]
+fn schema() -> Result<()> {
+    let doc = Document::new()?;
+    assert_golden("schema.sql", &doc.schema()?);
+    Ok(())
+}
+
+#[test]
+fn json_roundtrip() -> Result<()> {
+    run_test(|client| async move {
+        let document_id = client.create_document().await?;
+        let arrangement_id = client.get_document_arrangement(document_id).await?;
+        let main_track_id = client.get_arrangement_main_track(arrangement_id).await?;
+
+        client
+            .set_arrangement_name(arrangement_id, "Arrangement \"1\"".into())
+            .await?;
+
+        let child_id = client.create_track(document_id).await?;
+        client.set_track_name(child_id, "Drums".into()).await?;
+        client.append_track_child(main_track_id, child_id).await?;
+
+        let temp_file = NamedTempFile::with_prefix(".rdaw-test-")?;
+        let path = Utf8Path::from_path(temp_file.path()).unwrap();
+        client
+            .export_document_json(document_id, path.to_path_buf())
+            .await?;
+
+        let json = std::fs::read_to_string(path)?;
+        assert!(json.contains("\"Drums\""));
+
+        let document_id = client.import_document_json(path.to_path_buf()).await?;
+        let arrangement_id = client.get_document_arrangement(document_id).await?;
+        assert_eq!(
+            client.get_arrangement_name(arrangement_id).await?,
+            "Arrangement \"1\""
+        );
+
+        let main_track_id = client.get_arrangement_main_track(arrangement_id).await?;
+        let children = client.get_track_children(main_track_id).await?;
+        assert_eq!(children.len(), 1);
+        assert_eq!(client.get_track_name(children[0]).await?, "Drums");
+
+        Ok(())
+    })
+}
+
+#[test]
+fn save_backups() -> Result<()> {
+    run_test(|client| async move {
+        let dir = tempfile::tempdir()?;
+        let dir = Utf8Path::from_path(dir.path()).unwrap();
+        let path = dir.join("project.rdaw");
+
+        client
+            .set_document_backups(BackupSettings {
+                count: 2,
+                dir: None,
+            })
+            .await?;
+
+        let document_id = client.create_document().await?;
+        client.save_document_as(document_id, path.clone()).await?;
+        let document_id = client.open_document(path.clone()).await?;
+        assert!(!dir.join("project.rdaw.bak1").exists());
+
+        for _ in 0..3 {
+            client.save_document(document_id).await?;
+        }
+
+        // every backup is one save behind the next one
+        let num_revisions = |name: &str| {
+            Document::open(&dir.join(name))?
+                .revisions()
+                .map(|v| v.len())
+        };
+        let latest = num_revisions("project.rdaw")?;
+        assert_eq!(num_revisions("project.rdaw.bak1")?, latest - 1);
+        assert_eq!(num_revisions("project.rdaw.bak2")?, latest - 2);
+        assert!(!dir.join("project.rdaw.bak3").exists());
+
+        Ok(())
+    })
+}
+
+#[test]
+fn snapshots() -> Result<()> {
+    run_test(|client| async move {
+        let document_id = client.create_document().await?;
+        let arrangement_id = client.get_document_arrangement(document_id).await?;
+        let main_track_id = client.get_arrangement_main_track(arrangement_id).await?;
+
+        let child_id = client.create_track(document_id).await?;
+        client.set_track_name(child_id, "Drums".into()).await?;
+        client.append_track_child(main_track_id, child_id).await?;
+
+        let first = client.create_snapshot(document_id, "mix v1".into()).await?;
+
+        client.set_track_name(child_id, "Bass".into()).await?;
+        let other_id = client.create_track(document_id).await?;
+        client.append_track_child(main_track_id, other_id).await?;
+
+        let second = client
+            .create_snapshot(document_id, "pre-client-notes".into())
+            .await?;
+
+        let snapshots = client.list_snapshots(document_id, PageRequest::ALL).await?.items;
+        assert_eq!(
+            snapshots
+                .iter()
+                .map(|v| (v.id, v.name.as_str()))
+                .collect::<Vec<_>>(),
+            vec![(first, "mix v1"), (second, "pre-client-notes")]
+        );
+        assert!(snapshots[0].created_at <= snapshots[1].created_at);
+
+        client.restore_snapshot(document_id, first).await?;
+        assert_eq!(client.get_track_children(main_track_id).await?, vec![child_id]);
+        assert_eq!(client.get_track_name(child_id).await?, "Drums");
+
+        client.restore_snapshot(document_id, second).await?;
+        assert_eq!(
+            client.get_track_children(main_track_id).await?,
+            vec![child_id, other_id]
+        );
+        assert_eq!(client.get_track_name(child_id).await?, "Bass");
+
+        client.remove_snapshot(document_id, first).await?;
+        assert_eq!(client.list_snapshots(document_id, PageRequest::ALL).await?.total, 1);
+        assert_err!(
+            client.restore_snapshot(document_id, first).await,
+            ErrorKind::NotFound
+        );
+        assert_err!(
+            client.create_snapshot(document_id, " ".into()).await,
+            ErrorKind::NotSupported
+        );
+
+        Ok(())
+    })
+}
+
+#[test]
+fn open_revision() -> Result<()> {
+    run_test(|client| async move {
+        let document_id = client.create_document().await?;
+        let arrangement_id = client.get_document_arrangement(document_id).await?;
+        let main_track_id = client.get_arrangement_main_track(arrangement_id).await?;
+
+        let child_id = client.create_track(document_id).await?;
+        client.set_track_name(child_id, "Drums".into()).await?;
+        client.append_track_child(main_track_id, child_id).await?;
+        client.save_document(document_id).await?;
+
+        client.set_track_name(child_id, "Bass".into()).await?;
+        client.save_document(document_id).await?;
+
+        let revisions = client.list_revisions(document_id, PageRequest::ALL).await?.items;
+        assert_eq!(revisions.len(), 3);
+        assert!(revisions[1].created_at <= revisions[2].created_at);
+
+        let copy_id = client.open_revision(document_id, revisions[1].id).await?;
+        assert_ne!(copy_id, document_id);
+
+        let copy_arrangement_id = client.get_document_arrangement(copy_id).await?;
+        let copy_main_track_id = client
+            .get_arrangement_main_track(copy_arrangement_id)
+            .await?;
+        let copy_children = client.get_track_children(copy_main_track_id).await?;
+        assert_eq!(copy_children.len(), 1);
+        assert_eq!(client.get_track_name(copy_children[0]).await?, "Drums");
+
+        // the document itself is left as it was
+        assert_eq!(client.get_track_name(child_id).await?, "Bass");
+        assert_eq!(client.list_revisions(document_id, PageRequest::ALL).await?.total, 3);
+
+        assert_err!(
+            client.open_revision(document_id, RevisionId(100)).await,
+            ErrorKind::NotFound
+        );
+
+        Ok(())
+    })
+}
+
+#[test]
+fn document_changes() -> Result<()> {
+    run_test(|client| async move {
+        let document_id = client.create_document().await?;
+        let mut stream = client.subscribe_document_changes(document_id).await?;
+
+        client.create_track(document_id).await?;
+        let Some(DocumentChangeEvent::ObjectAdded { ty, uuid }) = stream.next().await else {
+            panic!("expected an added object");
+        };
+        assert_eq!(ty, ObjectType::Track);
+
+        let track_id = client.create_track(document_id).await?;
+        stream.next().await;
+
+        client.set_track_name(track_id, "Drums".into()).await?;
+        let Some(DocumentChangeEvent::ObjectModified { ty, uuid: modified }) = stream.next().await
+        else {
+            panic!("expected a modified object");
+        };
+        assert_eq!(ty, ObjectType::Track);
+        assert_ne!(uuid, modified);
+
+        Ok(())
+    })
+}
+
+#[test]
+fn seed() -> Result<()> {
+    run_test(|client| async move {
+        let dir = tempfile::tempdir()?;
+        let path = Utf8Path::from_path(dir.path()).unwrap().join("project.rdaw");
+
+        let document_id = client.create_document().await?;
+        client.set_document_seed(document_id, 1234).await?;
+        assert_eq!(client.get_document_seed(document_id).await?, 1234);
+
+        client.save_document_as(document_id, path.clone()).await?;
+        let document_id = client.open_document(path).await?;
+        assert_eq!(client.get_document_seed(document_id).await?, 1234);
+
+        Ok(())
+    })
+}
+
+#[test]
+fn warnings() -> Result<()> {
+    run_test(|client| async move {
+        let dir = tempfile::tempdir()?;
+        let path = Utf8Path::from_path(dir.path()).unwrap().join("project.rdaw");
+
+        let document_id = client.create_document().await?;
+        assert!(client.get_document_warnings(document_id).await?.is_empty());
+
+        client.save_document_as(document_id, path.clone()).await?;
+        let document_id = client.open_document(path).await?;
+        assert!(client.get_document_warnings(document_id).await?.is_empty());
+
+        assert_err!(
+            client.get_document_warnings(DocumentId::default()).await,
+            ErrorKind::InvalidId
+        );
+
+        Ok(())
+    })
+}
+
+#[test]
+fn read_only_role() -> Result<()> {
+    run_test_as(Role::ReadOnly, |client| async move {
+        assert_err!(client.create_document().await, ErrorKind::PermissionDenied);
+        assert_err!(
+            client.set_document_seed(DocumentId::default(), 1).await,
+            ErrorKind::PermissionDenied
+        );
+
+        // read-only requests get past the role check
+        assert_err!(
+            client.get_document_seed(DocumentId::default()).await,
+            ErrorKind::InvalidId
+        );
+
+        Ok(())
+    })
+}
+
+#[test]
+fn editor_role() -> Result<()> {
+    run_test_as(Role::Editor, |client| async move {
+        let document_id = client.create_document().await?;
+        client.set_document_seed(document_id, 1).await?;
+
+        // paths on the machine of the backend are only for admins
+        assert_err!(
+            client
+                .save_document_as(document_id, "project.rdaw".into())
+                .await,
+            ErrorKind::PermissionDenied
+        );
+
+        Ok(())
+    })
+}
+
+#[test]
+fn remote_client() -> Result<()> {
+    let secret = SharedSecret::new(b"secret");
+    let mut authenticator = Authenticator::new();
+    authenticator.add_secret(secret.clone(), Role::ReadOnly);
+
+    let listener = SocketListener::bind_tcp("127.0.0.1:0", authenticator)?;
+    let addr = listener.tcp_addr().unwrap();
+
+    run_test_with(
+        |backend| backend.listen(listener),
+        |client| async move {
+            let remote = Client::new(SocketClientTransport::connect_tcp(addr, &secret)?);
+            let remote_clone = remote.clone();
+            thread::spawn(move || block_on(remote_clone.handle()));
+
+            let document_id = client.create_document().await?;
+            let track_id = client.create_track(document_id).await?;
+
+            let mut stream = remote.subscribe_track_name(track_id).await?;
+            assert_err!(
+                remote.set_track_name(track_id, "Kick".into()).await,
+                ErrorKind::PermissionDenied
+            );
+
+            // events go to the client which has subscribed
+            client.set_track_name(track_id, "Snare".into()).await?;
+            assert_eq!(stream.next().await, Some("Snare".into()));
+
+            Ok(())
+        },
+    )
+}
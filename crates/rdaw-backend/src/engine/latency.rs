@@ -0,0 +1,134 @@
+//! Measures the round-trip latency of the audio device, with its output looped back into its
+//! input. An impulse is played once the streams have settled, and the latency is the number of
+//! frames until it's captured. Both streams are started together, so their frame counters are
+//! treated as one clock.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use rdaw_api::{bail, ErrorKind, Result};
+use rdaw_audio::driver::{InStreamDesc, OutCallbackData, OutStreamDesc};
+use rdaw_core::sync::spsc::{self, TryRecvError};
+use rdaw_core::time::RealTime;
+
+use super::{frames_to_time, DynDriver, DynInStream, DynOutStream, BUFFER_SIZE, SAMPLE_RATE};
+use crate::recording::{ring, CHANNELS};
+
+const IMPULSE_LEVEL: f32 = 0.9;
+
+/// Captured samples above this level are taken for the impulse.
+const DETECTION_THRESHOLD: f32 = 0.5;
+
+/// Silence played before the impulse, in frames.
+const PRE_ROLL: u64 = SAMPLE_RATE as u64 / 4;
+
+const TIMEOUT: Duration = Duration::from_secs(1);
+
+const NOT_EMITTED: u64 = u64::MAX;
+
+/// Output and input streams of a running loopback test. The engine has its own output stream,
+/// which shouldn't be running at the same time.
+pub struct LoopbackTest {
+    // dropping the streams stops them
+    _out_stream: Box<dyn DynOutStream>,
+    _in_stream: Box<dyn DynInStream>,
+    receiver: spsc::Receiver<f32>,
+    num_channels: usize,
+    emitted_at: Arc<AtomicU64>,
+}
+
+impl LoopbackTest {
+    pub fn start(driver: &dyn DynDriver) -> Result<LoopbackTest> {
+        let emitted_at = Arc::new(AtomicU64::new(NOT_EMITTED));
+        let callback = {
+            let emitted_at = emitted_at.clone();
+            let mut position = 0u64;
+
+            move |data: OutCallbackData<'_>| {
+                data.samples.fill(0.0);
+
+                if position >= PRE_ROLL && emitted_at.load(Ordering::Relaxed) == NOT_EMITTED {
+                    let num_channels = data.num_channels.min(data.samples.len());
+                    data.samples[..num_channels].fill(IMPULSE_LEVEL);
+                    emitted_at.store(position, Ordering::Relaxed);
+                }
+
+                position += data.num_frames as u64;
+            }
+        };
+
+        let out_stream = driver.create_out_stream(OutStreamDesc {
+            name: "rdaw-loopback".into(),
+            sample_rate: SAMPLE_RATE,
+            buffer_size: BUFFER_SIZE,
+            channels: CHANNELS.to_vec(),
+            callback: Box::new(callback),
+        })?;
+
+        let (sender, receiver) = ring()?;
+        let in_stream = driver.create_in_stream(InStreamDesc {
+            name: "rdaw-loopback".into(),
+            sample_rate: SAMPLE_RATE,
+            buffer_size: BUFFER_SIZE,
+            channels: CHANNELS.to_vec(),
+            sender,
+        })?;
+
+        in_stream.set_active(true)?;
+        out_stream.set_active(true)?;
+
+        Ok(LoopbackTest {
+            _out_stream: out_stream,
+            _in_stream: in_stream,
+            receiver,
+            num_channels: CHANNELS.len(),
+            emitted_at,
+        })
+    }
+
+    /// Waits for the impulse to come back and returns the round-trip latency.
+    pub fn measure(mut self) -> Result<RealTime> {
+        let deadline = Instant::now() + TIMEOUT;
+        let mut captured = Vec::new();
+        let mut num_frames = 0u64;
+
+        while let Some(timeout) = deadline.checked_duration_since(Instant::now()) {
+            match self.receiver.recv_timeout(timeout) {
+                Ok(sample) => captured.push(sample),
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Closed) => bail!(ErrorKind::Disconnected, "input stream closed"),
+            }
+
+            while let Ok(sample) = self.receiver.try_recv() {
+                captured.push(sample);
+            }
+
+            let emitted_at = self.emitted_at.load(Ordering::Relaxed);
+            let offset = num_frames;
+            let num_whole = captured.len() / self.num_channels * self.num_channels;
+            num_frames += (num_whole / self.num_channels) as u64;
+
+            if emitted_at != NOT_EMITTED {
+                if let Some(frame) = find_impulse(&captured[..num_whole], self.num_channels) {
+                    let latency = (offset + frame as u64).saturating_sub(emitted_at);
+                    return Ok(frames_to_time(latency as i64, SAMPLE_RATE));
+                }
+            }
+
+            captured.drain(..num_whole);
+        }
+
+        bail!(
+            ErrorKind::Timeout,
+            "impulse wasn't captured, is the output looped back?"
+        );
+    }
+}
+
+/// Returns the first frame with a sample above the detection threshold.
+pub fn find_impulse(samples: &[f32], num_channels: usize) -> Option<usize> {
+    samples
+        .chunks_exact(num_channels.max(1))
+        .position(|frame| frame.iter().any(|v| v.abs() >= DETECTION_THRESHOLD))
+}
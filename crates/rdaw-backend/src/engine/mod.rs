@@ -0,0 +1,1299 @@
+//! Renders the arrangement that's being played into an output stream. After an edit only the
+//! nodes of changed tracks and items are replaced, then the graph is recompiled and handed over
+//! to the stream. Volume and pan are shared with the nodes, so changing them doesn't need a
+//! recompilation.
+//!
+//! Buses are built like tracks, their mixes summing the sends of other tracks, each send being a
+//! [`GainPanNode`] applying its level. Buses are mixed into the main track, and send levels are
+//! shared with the nodes too.
+//!
+//! Modulators become [`ModulatorNode`]s, which publish their value once per block.
+//!
+//! Every track also has a [`MeterNode`] listening to its output, which sends the levels of each
+//! block through a ring that's drained by [`EngineGraph::poll_meters`]. A [`StereoMeterNode`]
+//! does the same with every sample of the master output.
+//!
+//! The graph of the engine ends with a [`LimiterNode`] after the master output, protecting the
+//! speakers. Graphs rendered offline don't have one.
+
+mod latency;
+mod ops;
+#[cfg(test)]
+mod tests;
+
+use std::fmt::{self, Display};
+use std::slice::ChunksExactMut;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use rdaw_api::arrangement::ArrangementId;
+use rdaw_api::audio::{AudioChannel, ResampleQuality, StreamInfo};
+use rdaw_api::bus::{BusId, SendId, SendPosition};
+use rdaw_api::engine::{EngineNode, EngineStatus};
+use rdaw_api::item::AudioClip;
+use rdaw_api::meter::{MeterFrame, METER_RATE};
+use rdaw_api::modulation::{ModulationSource, ModulatorId};
+use rdaw_api::node::NodeId;
+use rdaw_api::source::AudioSourceId;
+use rdaw_api::track::{TrackId, TrackItemId};
+use rdaw_api::{format_err, ErrorKind, Result};
+use rdaw_audio::driver::{
+    Driver, InStream, InStreamDesc, OutCallbackData, OutStream, OutStreamDesc,
+};
+use rdaw_audio::graph::{
+    CompiledGraph, Graph, GraphParams, Node as _, NodeId as GraphNodeId, Port,
+};
+use rdaw_audio::nodes::{
+    GainPanNode, GainPanParams, LimiterNode, LimiterParams, LimiterState, MeterNode, MeterSender,
+    MixNode, ModulationValue, ModulatorNode, SampleNode, StereoMeterNode, StereoMeterSender,
+};
+use rdaw_audio::playhead::Playhead;
+use rdaw_clap::PluginNode;
+use rdaw_core::collections::{HashMap, HashSet};
+use rdaw_core::sync::spsc::{self, Receiver};
+use rdaw_core::time::RealTime;
+use rdaw_ffmpeg::ResampleNode;
+
+pub use self::ops::decode_cached_source;
+
+/// Frame positions of track views are measured at this rate.
+pub const SAMPLE_RATE: u32 = 48000;
+pub const BUFFER_SIZE: usize = 512;
+
+/// Meter frames the ring can hold between two polls.
+const METER_CAPACITY: usize = 4096;
+
+const STEREO_METER_CAPACITY: usize = 16384;
+
+const NANOS_IN_SEC: i128 = 1_000_000_000;
+
+pub fn time_to_frames(time: RealTime, sample_rate: u32) -> i64 {
+    (i128::from(time.as_nanos()) * i128::from(sample_rate) / NANOS_IN_SEC) as i64
+}
+
+pub fn frames_to_time(frames: i64, sample_rate: u32) -> RealTime {
+    RealTime::from_nanos(
+        (i128::from(frames) * NANOS_IN_SEC / i128::from(sample_rate.max(1))) as i64,
+    )
+}
+
+/// Object-safe [`Driver`].
+pub trait DynDriver: Send + Sync + 'static {
+    fn create_out_stream(&self, desc: OutStreamDesc) -> Result<Box<dyn DynOutStream>>;
+
+    fn create_in_stream(&self, desc: InStreamDesc) -> Result<Box<dyn DynInStream>>;
+}
+
+pub trait DynOutStream: Send + Sync + 'static {
+    fn set_active(&self, active: bool) -> Result<()>;
+
+    fn info(&self) -> Result<StreamInfo>;
+}
+
+pub trait DynInStream: Send + Sync + 'static {
+    fn set_active(&self, active: bool) -> Result<()>;
+
+    fn info(&self) -> Result<StreamInfo>;
+
+    fn num_dropped_frames(&self) -> Result<u64>;
+}
+
+impl fmt::Debug for dyn DynDriver {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DynDriver").finish_non_exhaustive()
+    }
+}
+
+impl<D> DynDriver for D
+where
+    D: Driver,
+    D::Error: Display,
+    <D::OutStream as OutStream>::Error: Display,
+    <D::InStream as InStream>::Error: Display,
+{
+    fn create_out_stream(&self, desc: OutStreamDesc) -> Result<Box<dyn DynOutStream>> {
+        let stream = Driver::create_out_stream(self, desc)
+            .map_err(|e| format_err!(ErrorKind::Other, "failed to create output stream: {e}"))?;
+        Ok(Box::new(stream))
+    }
+
+    fn create_in_stream(&self, desc: InStreamDesc) -> Result<Box<dyn DynInStream>> {
+        let stream = Driver::create_in_stream(self, desc)
+            .map_err(|e| format_err!(ErrorKind::Other, "failed to create input stream: {e}"))?;
+        Ok(Box::new(stream))
+    }
+}
+
+impl<S> DynOutStream for S
+where
+    S: OutStream,
+    S::Error: Display,
+{
+    fn set_active(&self, active: bool) -> Result<()> {
+        OutStream::set_active(self, active).map_err(|e| format_err!(ErrorKind::Other, "{e}"))
+    }
+
+    fn info(&self) -> Result<StreamInfo> {
+        OutStream::info(self).map_err(|e| format_err!(ErrorKind::Other, "{e}"))
+    }
+}
+
+impl<S> DynInStream for S
+where
+    S: InStream,
+    S::Error: Display,
+{
+    fn set_active(&self, active: bool) -> Result<()> {
+        InStream::set_active(self, active).map_err(|e| format_err!(ErrorKind::Other, "{e}"))
+    }
+
+    fn info(&self) -> Result<StreamInfo> {
+        InStream::info(self).map_err(|e| format_err!(ErrorKind::Other, "{e}"))
+    }
+
+    fn num_dropped_frames(&self) -> Result<u64> {
+        InStream::num_dropped_frames(self).map_err(|e| format_err!(ErrorKind::Other, "{e}"))
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct GraphDesc {
+    /// Children come before their parents, the main track is the last one.
+    pub tracks: Vec<TrackDesc>,
+    /// Mixed into the main track.
+    pub buses: Vec<BusDesc>,
+    pub modulators: Vec<ModulatorDesc>,
+}
+
+#[derive(Debug)]
+pub struct TrackDesc {
+    pub id: TrackId,
+    pub children: Vec<TrackId>,
+    pub items: Vec<ItemDesc>,
+    /// In processing order.
+    pub plugins: Vec<PluginDesc>,
+    pub volume: f32,
+    pub pan: f32,
+}
+
+#[derive(Debug, Clone)]
+pub struct PluginDesc {
+    pub id: NodeId,
+    pub node: PluginNode,
+}
+
+/// Identifies an item within its track.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ItemKey {
+    Track(TrackItemId),
+    /// Clip launched on the track, there's at most one.
+    Launched,
+}
+
+#[derive(Debug, Clone)]
+pub struct ItemDesc {
+    pub id: ItemKey,
+    pub source: DecodedSource,
+    /// Timeline frame where the item starts.
+    pub start: i64,
+    pub duration: i64,
+    pub loop_length: Option<i64>,
+    pub clip: AudioClip,
+    pub resample_quality: ResampleQuality,
+}
+
+impl PartialEq for ItemDesc {
+    fn eq(&self, other: &ItemDesc) -> bool {
+        self.id == other.id
+            && self.start == other.start
+            && self.duration == other.duration
+            && self.loop_length == other.loop_length
+            && self.clip == other.clip
+            && self.resample_quality == other.resample_quality
+            && self.source.same(&other.source)
+    }
+}
+
+#[derive(Debug)]
+pub struct BusDesc {
+    pub id: BusId,
+    /// Sends of tracks that aren't rendered are left out.
+    pub sends: Vec<SendDesc>,
+    pub volume: f32,
+    pub pan: f32,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct SendDesc {
+    pub track_id: TrackId,
+    pub id: SendId,
+    pub position: SendPosition,
+    pub level: f32,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ModulatorDesc {
+    pub id: ModulatorId,
+    pub source: ModulationSource,
+}
+
+/// Mixed down to mono.
+#[derive(Debug, Clone)]
+pub struct DecodedSource {
+    pub samples: Arc<[f32]>,
+    pub sample_rate: u32,
+}
+
+impl DecodedSource {
+    fn same(&self, other: &DecodedSource) -> bool {
+        Arc::ptr_eq(&self.samples, &other.samples) && self.sample_rate == other.sample_rate
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum SourceState {
+    Decoding,
+    Ready(DecodedSource),
+    Failed,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Input {
+    Track(TrackId),
+    Item(TrackId, ItemKey),
+    Bus(BusId),
+}
+
+#[derive(Debug)]
+struct TrackNode {
+    /// Gain and pan, with the stereo output of the track.
+    node: GraphNodeId,
+    /// Left and right channels of the inputs.
+    mixes: [GraphNodeId; 2],
+    /// Listens to the output of the track.
+    meter: GraphNodeId,
+    inputs: Vec<Input>,
+    plugins: Vec<NodeId>,
+    plugin_nodes: Vec<GraphNodeId>,
+    /// Left and right channels before the gain and pan, after the plugins.
+    pre_fader: [(GraphNodeId, usize); 2],
+    params: GainPanParams,
+}
+
+impl TrackNode {
+    fn nodes(&self) -> impl Iterator<Item = GraphNodeId> + '_ {
+        [self.node, self.mixes[0], self.mixes[1], self.meter]
+            .into_iter()
+            .chain(self.plugin_nodes.iter().copied())
+    }
+}
+
+#[derive(Debug)]
+struct BusNode {
+    /// Gain and pan, with the stereo output of the bus.
+    node: GraphNodeId,
+    /// Left and right channels of the sends.
+    mixes: [GraphNodeId; 2],
+    sends: Vec<SendNode>,
+    params: GainPanParams,
+}
+
+impl BusNode {
+    fn nodes(&self) -> impl Iterator<Item = GraphNodeId> + '_ {
+        [self.node, self.mixes[0], self.mixes[1]]
+            .into_iter()
+            .chain(self.sends.iter().map(|v| v.node))
+    }
+}
+
+#[derive(Debug)]
+struct SendNode {
+    track_id: TrackId,
+    id: SendId,
+    position: SendPosition,
+    /// Applies the level of the send, stereo.
+    node: GraphNodeId,
+    params: GainPanParams,
+}
+
+#[derive(Debug)]
+struct ItemNode {
+    node: GraphNodeId,
+    desc: ItemDesc,
+}
+
+#[derive(Debug)]
+struct ModulatorNodeState {
+    node: GraphNodeId,
+    source: ModulationSource,
+    /// Track node an envelope follower listens to.
+    input: Option<GraphNodeId>,
+    value: ModulationValue,
+}
+
+pub struct EngineGraph {
+    params: GraphParams,
+    playhead: Playhead,
+    graph: Graph,
+    tracks: HashMap<TrackId, TrackNode>,
+    // item ids are only unique within their track
+    items: HashMap<(TrackId, ItemKey), ItemNode>,
+    buses: HashMap<BusId, BusNode>,
+    modulators: HashMap<ModulatorId, ModulatorNodeState>,
+    master: Option<GraphNodeId>,
+    meter_sender: MeterSender<TrackId>,
+    meter_receiver: Receiver<(TrackId, MeterFrame)>,
+    /// Master node the stereo meter listens to, and the meter.
+    stereo_meter: Option<(GraphNodeId, GraphNodeId)>,
+    stereo_sender: StereoMeterSender,
+    stereo_receiver: Receiver<[f32; 2]>,
+    limiter: Option<LimiterParams>,
+    limiter_state: LimiterState,
+    /// Master node the limiter takes, and the limiter.
+    limiter_node: Option<(GraphNodeId, GraphNodeId)>,
+}
+
+impl EngineGraph {
+    pub fn new(params: GraphParams, playhead: Playhead) -> EngineGraph {
+        let (meter_sender, meter_receiver) = spsc::channel(METER_CAPACITY);
+        let (stereo_sender, stereo_receiver) = spsc::channel(STEREO_METER_CAPACITY);
+
+        EngineGraph {
+            params,
+            playhead,
+            graph: Graph::new(params),
+            tracks: HashMap::default(),
+            items: HashMap::default(),
+            buses: HashMap::default(),
+            modulators: HashMap::default(),
+            master: None,
+            meter_sender: Arc::new(Mutex::new(meter_sender)),
+            meter_receiver,
+            stereo_meter: None,
+            stereo_sender: Arc::new(Mutex::new(stereo_sender)),
+            stereo_receiver,
+            limiter: None,
+            limiter_state: LimiterState::default(),
+            limiter_node: None,
+        }
+    }
+
+    /// Puts a limiter between the master node and the output.
+    pub fn with_limiter(mut self, params: LimiterParams) -> EngineGraph {
+        self.limiter = Some(params);
+        self
+    }
+
+    pub fn params(&self) -> GraphParams {
+        self.params
+    }
+
+    /// Returns whether the graph needs to be recompiled.
+    pub fn update(&mut self, desc: &GraphDesc) -> bool {
+        let mut changed = false;
+
+        let wanted_items = desc
+            .tracks
+            .iter()
+            .flat_map(|track| {
+                track
+                    .items
+                    .iter()
+                    .map(move |item| ((track.id, item.id), item))
+            })
+            .collect::<HashMap<_, _>>();
+
+        let graph = &mut self.graph;
+        self.items.retain(|key, node| {
+            let keep = wanted_items
+                .get(key)
+                .is_some_and(|&desc| *desc == node.desc);
+            if !keep {
+                graph.remove_node(node.node);
+                changed = true;
+            }
+            keep
+        });
+
+        let mut replaced = HashSet::default();
+
+        for (&key, &item) in &wanted_items {
+            if self.items.contains_key(&key) {
+                continue;
+            }
+
+            let node = if item.source.sample_rate == self.params.sample_rate {
+                self.graph.add_node(SampleNode {
+                    samples: item.source.samples.clone(),
+                    sample_rate: item.source.sample_rate,
+                    start: item.start,
+                    duration: item.duration,
+                    loop_length: item.loop_length,
+                    clip: item.clip,
+                    playhead: self.playhead.clone(),
+                })
+            } else {
+                self.graph.add_node(ResampleNode {
+                    samples: item.source.samples.clone(),
+                    sample_rate: item.source.sample_rate,
+                    start: item.start,
+                    duration: item.duration,
+                    loop_length: item.loop_length,
+                    clip: item.clip,
+                    quality: item.resample_quality,
+                    playhead: self.playhead.clone(),
+                })
+            };
+
+            self.items.insert(
+                key,
+                ItemNode {
+                    node,
+                    desc: item.clone(),
+                },
+            );
+
+            replaced.insert(Input::Item(key.0, key.1));
+            changed = true;
+        }
+
+        let wanted_tracks = desc.tracks.iter().map(|v| v.id).collect::<HashSet<_>>();
+
+        let graph = &mut self.graph;
+        self.tracks.retain(|id, node| {
+            let keep = wanted_tracks.contains(id);
+            if !keep {
+                node.nodes().for_each(|v| graph.remove_node(v));
+                changed = true;
+            }
+            keep
+        });
+
+        for (i, track) in desc.tracks.iter().enumerate() {
+            // buses take sends from the other tracks and are mixed into the main one
+            let buses = if i + 1 == desc.tracks.len() {
+                changed |= self.update_buses(&desc.buses, &mut replaced);
+                &desc.buses[..]
+            } else {
+                &[]
+            };
+
+            changed |= self.update_track(track, buses, &mut replaced);
+        }
+
+        self.master = desc
+            .tracks
+            .last()
+            .and_then(|track| self.tracks.get(&track.id))
+            .map(|v| v.node);
+
+        changed |= self.update_stereo_meter();
+        changed |= self.update_limiter();
+        changed |= self.update_modulators(&desc.modulators);
+
+        changed
+    }
+
+    /// Rebuilds the nodes of the track if its inputs have changed, returns whether it did.
+    fn update_track(
+        &mut self,
+        track: &TrackDesc,
+        buses: &[BusDesc],
+        replaced: &mut HashSet<Input>,
+    ) -> bool {
+        let inputs = track
+            .children
+            .iter()
+            .map(|&id| Input::Track(id))
+            .chain(
+                track
+                    .items
+                    .iter()
+                    .map(|item| Input::Item(track.id, item.id)),
+            )
+            .chain(buses.iter().map(|bus| Input::Bus(bus.id)))
+            .collect::<Vec<_>>();
+
+        let plugins = track.plugins.iter().map(|v| v.id).collect::<Vec<_>>();
+
+        let is_stale = match self.tracks.get(&track.id) {
+            Some(node) => {
+                node.inputs != inputs
+                    || node.plugins != plugins
+                    || inputs.iter().any(|v| replaced.contains(v))
+            }
+            None => true,
+        };
+
+        if !is_stale {
+            // takes effect on the next block, without recompiling
+            self.tracks[&track.id].params.set(track.volume, track.pan);
+            return false;
+        }
+
+        let params = match self.tracks.remove(&track.id) {
+            Some(old) => {
+                old.nodes().for_each(|v| self.graph.remove_node(v));
+                old.params
+            }
+            None => GainPanParams::default(),
+        };
+        params.set(track.volume, track.pan);
+
+        let mixes = [(); 2].map(|_| {
+            self.graph.add_node(MixNode {
+                num_inputs: inputs.len(),
+            })
+        });
+        let node = self.graph.add_node(GainPanNode {
+            params: params.clone(),
+        });
+
+        for (port, input) in inputs.iter().enumerate() {
+            // child tracks and buses are stereo, items are mono and go to both channels
+            let src = match *input {
+                Input::Track(id) => self.tracks.get(&id).map(|v| [(v.node, 0), (v.node, 1)]),
+                Input::Item(track_id, item_id) => self
+                    .items
+                    .get(&(track_id, item_id))
+                    .map(|v| [(v.node, 0), (v.node, 0)]),
+                Input::Bus(id) => self.buses.get(&id).map(|v| [(v.node, 0), (v.node, 1)]),
+            };
+
+            let Some(src) = src else {
+                continue;
+            };
+
+            for ((src, src_port), mix) in src.into_iter().zip(mixes) {
+                let res = self
+                    .graph
+                    .connect((src, Port::Audio(src_port)), (mix, Port::Audio(port)));
+                if let Err(error) = res {
+                    tracing::error!(%error, ?track.id, "track input was left disconnected");
+                }
+            }
+        }
+
+        let mut pre_fader = mixes.map(|mix| (mix, 0));
+        let mut plugin_nodes = Vec::with_capacity(track.plugins.len());
+
+        for plugin in &track.plugins {
+            let num_outputs = plugin.node.num_audio_outputs();
+            if num_outputs == 0 {
+                tracing::warn!(?plugin.id, "plugin without audio outputs was bypassed");
+                continue;
+            }
+
+            let plugin_node = self.graph.add_node(plugin.node.clone());
+            plugin_nodes.push(plugin_node);
+
+            // mono plugins only get the left channel
+            let num_inputs = plugin.node.num_audio_inputs();
+            for (port, (src, src_port)) in pre_fader.into_iter().take(num_inputs).enumerate() {
+                let res = self.graph.connect(
+                    (src, Port::Audio(src_port)),
+                    (plugin_node, Port::Audio(port)),
+                );
+                if let Err(error) = res {
+                    tracing::error!(%error, ?plugin.id, "plugin input was left disconnected");
+                }
+            }
+
+            pre_fader = [(plugin_node, 0), (plugin_node, 1.min(num_outputs - 1))];
+        }
+
+        for (port, (src, src_port)) in pre_fader.into_iter().enumerate() {
+            let res = self
+                .graph
+                .connect((src, Port::Audio(src_port)), (node, Port::Audio(port)));
+            if let Err(error) = res {
+                tracing::error!(%error, ?track.id, "track mix was left disconnected");
+            }
+        }
+
+        let meter = self.graph.add_node(MeterNode {
+            key: track.id,
+            sender: self.meter_sender.clone(),
+        });
+
+        for port in 0..2 {
+            let res = self
+                .graph
+                .connect((node, Port::Audio(port)), (meter, Port::Audio(port)));
+            if let Err(error) = res {
+                tracing::error!(%error, ?track.id, "track meter was left disconnected");
+            }
+        }
+
+        self.tracks.insert(
+            track.id,
+            TrackNode {
+                node,
+                mixes,
+                meter,
+                inputs,
+                plugins,
+                plugin_nodes,
+                pre_fader,
+                params,
+            },
+        );
+        replaced.insert(Input::Track(track.id));
+        true
+    }
+
+    /// Returns whether the master node has been replaced.
+    fn update_stereo_meter(&mut self) -> bool {
+        if self.stereo_meter.map(|(master, _)| master) == self.master {
+            return false;
+        }
+
+        if let Some((_, node)) = self.stereo_meter.take() {
+            self.graph.remove_node(node);
+        }
+
+        let Some(master) = self.master else {
+            return true;
+        };
+
+        let node = self.graph.add_node(StereoMeterNode {
+            sender: self.stereo_sender.clone(),
+        });
+
+        for port in 0..2 {
+            let res = self
+                .graph
+                .connect((master, Port::Audio(port)), (node, Port::Audio(port)));
+            if let Err(error) = res {
+                tracing::error!(%error, "stereo meter was left disconnected");
+            }
+        }
+
+        self.stereo_meter = Some((master, node));
+        true
+    }
+
+    /// Moves the limiter to the master node if it has been replaced, returns whether it was.
+    fn update_limiter(&mut self) -> bool {
+        let Some(params) = &self.limiter else {
+            return false;
+        };
+
+        if self.limiter_node.map(|(master, _)| master) == self.master {
+            return false;
+        }
+
+        if let Some((_, node)) = self.limiter_node.take() {
+            self.graph.remove_node(node);
+        }
+
+        let Some(master) = self.master else {
+            return true;
+        };
+
+        let node = self.graph.add_node(LimiterNode {
+            params: params.clone(),
+            state: self.limiter_state.clone(),
+        });
+
+        for port in 0..2 {
+            let res = self
+                .graph
+                .connect((master, Port::Audio(port)), (node, Port::Audio(port)));
+            if let Err(error) = res {
+                tracing::error!(%error, "limiter was left disconnected");
+            }
+        }
+
+        self.limiter_node = Some((master, node));
+        true
+    }
+
+    /// Rebuilds buses whose sends have changed, returns whether any were.
+    fn update_buses(&mut self, buses: &[BusDesc], replaced: &mut HashSet<Input>) -> bool {
+        let mut changed = false;
+
+        let wanted_buses = buses.iter().map(|v| v.id).collect::<HashSet<_>>();
+
+        let graph = &mut self.graph;
+        self.buses.retain(|id, node| {
+            let keep = wanted_buses.contains(id);
+            if !keep {
+                node.nodes().for_each(|v| graph.remove_node(v));
+                changed = true;
+            }
+            keep
+        });
+
+        for bus in buses {
+            let is_stale = match self.buses.get(&bus.id) {
+                Some(node) => {
+                    node.sends.len() != bus.sends.len()
+                        || node.sends.iter().zip(&bus.sends).any(|(node, send)| {
+                            node.track_id != send.track_id
+                                || node.id != send.id
+                                || node.position != send.position
+                                || replaced.contains(&Input::Track(send.track_id))
+                        })
+                }
+                None => true,
+            };
+
+            if !is_stale {
+                // takes effect on the next block, without recompiling
+                let node = &self.buses[&bus.id];
+                node.params.set(bus.volume, bus.pan);
+                for (node, send) in node.sends.iter().zip(&bus.sends) {
+                    node.params.set(send.level, 0.0);
+                }
+                continue;
+            }
+
+            let params = match self.buses.remove(&bus.id) {
+                Some(old) => {
+                    old.nodes().for_each(|v| self.graph.remove_node(v));
+                    old.params
+                }
+                None => GainPanParams::default(),
+            };
+            params.set(bus.volume, bus.pan);
+
+            let mixes = [(); 2].map(|_| {
+                self.graph.add_node(MixNode {
+                    num_inputs: bus.sends.len(),
+                })
+            });
+            let node = self.graph.add_node(GainPanNode {
+                params: params.clone(),
+            });
+
+            let mut sends = Vec::with_capacity(bus.sends.len());
+
+            for (port, send) in bus.sends.iter().enumerate() {
+                let send_params = GainPanParams::new(send.level, 0.0);
+                let send_node = self.graph.add_node(GainPanNode {
+                    params: send_params.clone(),
+                });
+
+                sends.push(SendNode {
+                    track_id: send.track_id,
+                    id: send.id,
+                    position: send.position,
+                    node: send_node,
+                    params: send_params,
+                });
+
+                let Some(track) = self.tracks.get(&send.track_id) else {
+                    continue;
+                };
+
+                let src = match send.position {
+                    SendPosition::PreFader => track.pre_fader,
+                    SendPosition::PostFader => [(track.node, 0), (track.node, 1)],
+                };
+
+                for (channel, ((src, src_port), mix)) in src.into_iter().zip(mixes).enumerate() {
+                    let res = self
+                        .graph
+                        .connect(
+                            (src, Port::Audio(src_port)),
+                            (send_node, Port::Audio(channel)),
+                        )
+                        .and_then(|()| {
+                            self.graph.connect(
+                                (send_node, Port::Audio(channel)),
+                                (mix, Port::Audio(port)),
+                            )
+                        });
+                    if let Err(error) = res {
+                        tracing::error!(%error, ?bus.id, "send was left disconnected");
+                    }
+                }
+            }
+
+            for (port, mix) in mixes.into_iter().enumerate() {
+                let res = self
+                    .graph
+                    .connect((mix, Port::Audio(0)), (node, Port::Audio(port)));
+                if let Err(error) = res {
+                    tracing::error!(%error, ?bus.id, "bus mix was left disconnected");
+                }
+            }
+
+            self.buses.insert(
+                bus.id,
+                BusNode {
+                    node,
+                    mixes,
+                    sends,
+                    params,
+                },
+            );
+            replaced.insert(Input::Bus(bus.id));
+            changed = true;
+        }
+
+        changed
+    }
+
+    fn update_modulators(&mut self, modulators: &[ModulatorDesc]) -> bool {
+        let mut changed = false;
+        let mut old_modulators = std::mem::take(&mut self.modulators);
+
+        for modulator in modulators {
+            let input = match modulator.source {
+                ModulationSource::EnvelopeFollower(follower) => {
+                    self.tracks.get(&follower.track_id).map(|v| v.node)
+                }
+                _ => None,
+            };
+
+            let old = old_modulators.remove(&modulator.id);
+            let value = match old {
+                Some(old) if old.source == modulator.source && old.input == input => {
+                    self.modulators.insert(modulator.id, old);
+                    continue;
+                }
+                Some(old) => {
+                    self.graph.remove_node(old.node);
+                    old.value
+                }
+                None => ModulationValue::new(),
+            };
+
+            let node = self.graph.add_node(ModulatorNode {
+                source: modulator.source,
+                playhead: self.playhead.clone(),
+                value: value.clone(),
+            });
+
+            if let Some(input) = input {
+                let res = self
+                    .graph
+                    .connect((input, Port::Audio(0)), (node, Port::Audio(0)));
+                if let Err(error) = res {
+                    tracing::error!(%error, "modulator input was left disconnected");
+                }
+            }
+
+            self.modulators.insert(
+                modulator.id,
+                ModulatorNodeState {
+                    node,
+                    source: modulator.source,
+                    input,
+                    value,
+                },
+            );
+
+            changed = true;
+        }
+
+        for old in old_modulators.into_values() {
+            self.graph.remove_node(old.node);
+            changed = true;
+        }
+
+        changed
+    }
+
+    pub fn modulation_value(&self, id: ModulatorId) -> Option<f32> {
+        self.modulators.get(&id).map(|v| v.value.get())
+    }
+
+    /// Takes the meter frames rendered since the last poll, merged by track.
+    pub fn poll_meters(&mut self) -> HashMap<TrackId, MeterFrame> {
+        let mut meters = HashMap::<TrackId, (MeterFrame, usize)>::default();
+
+        while let Ok((track_id, frame)) = self.meter_receiver.try_recv() {
+            let (merged, num_frames) = meters.entry(track_id).or_default();
+
+            // blocks have the same length, so their mean square is the mean of the squares
+            for channel in 0..2 {
+                merged.peak[channel] = merged.peak[channel].max(frame.peak[channel]);
+                merged.rms[channel] += frame.rms[channel] * frame.rms[channel];
+            }
+            *num_frames += 1;
+        }
+
+        meters
+            .into_iter()
+            .map(|(track_id, (mut frame, num_frames))| {
+                for rms in &mut frame.rms {
+                    *rms = (*rms / num_frames as f32).sqrt();
+                }
+                (track_id, frame)
+            })
+            .collect()
+    }
+
+    pub fn poll_stereo_meter(&mut self) -> Vec<[f32; 2]> {
+        let mut samples = Vec::new();
+        while let Ok(sample) = self.stereo_receiver.try_recv() {
+            samples.push(sample);
+        }
+        samples
+    }
+
+    /// Returns the compiled graph and the node with the output, the master node or the limiter.
+    pub fn compile(&self) -> Option<(CompiledGraph, GraphNodeId)> {
+        let master = self.master?;
+        let output = self.limiter_node.map_or(master, |(_, node)| node);
+        Some((self.graph.compile(), output))
+    }
+
+    pub fn node_owner(&self, node: GraphNodeId) -> Option<EngineNode> {
+        if let Some((&id, _)) = self
+            .tracks
+            .iter()
+            .find(|(_, v)| v.nodes().any(|v| v == node))
+        {
+            return Some(EngineNode::Track(id));
+        }
+
+        if let Some((&id, _)) = self
+            .buses
+            .iter()
+            .find(|(_, v)| v.nodes().any(|v| v == node))
+        {
+            return Some(EngineNode::Bus(id));
+        }
+
+        let (&(track_id, key), _) = self.items.iter().find(|(_, v)| v.node == node)?;
+        match key {
+            ItemKey::Track(item_id) => Some(EngineNode::Item(track_id, item_id)),
+            // the launched clip isn't an item of the track
+            ItemKey::Launched => Some(EngineNode::Track(track_id)),
+        }
+    }
+}
+
+/// Frames of the last block which didn't fit into the period of the driver.
+struct Leftover {
+    left: Vec<f32>,
+    right: Vec<f32>,
+    /// Index of the first frame which wasn't played yet.
+    pos: usize,
+    /// Playhead frame right after the block. The frames are dropped if it has moved since.
+    end: i64,
+}
+
+impl Leftover {
+    fn new(buffer_size: usize) -> Leftover {
+        Leftover {
+            left: Vec::with_capacity(buffer_size),
+            right: Vec::with_capacity(buffer_size),
+            pos: 0,
+            end: 0,
+        }
+    }
+
+    fn fill(&mut self, left: &[f32], right: &[f32], end: i64) {
+        self.left.clear();
+        self.left.extend_from_slice(left);
+        self.right.clear();
+        self.right.extend_from_slice(right);
+        self.pos = 0;
+        self.end = end;
+    }
+
+    fn play(&mut self, frames: &mut ChunksExactMut<'_, f32>) {
+        let left = &self.left[self.pos..];
+        let right = &self.right[self.pos..];
+
+        for ((&left, &right), frame) in left.iter().zip(right).zip(frames) {
+            // channels past the first two get the left one
+            for (channel, sample) in frame.iter_mut().enumerate() {
+                *sample = if channel == 1 { right } else { left };
+            }
+            self.pos += 1;
+        }
+    }
+
+    fn discard(&mut self) {
+        self.pos = self.left.len();
+    }
+}
+
+/// Fills the period of the driver with blocks of the graph. The driver may ask for periods of a
+/// different size than the graph renders, what's left of the last block is played in the next
+/// period, so that stateful nodes never render the same frames twice.
+fn render_period(
+    graph: &mut CompiledGraph,
+    output: GraphNodeId,
+    playhead: &Playhead,
+    leftover: &mut Leftover,
+    samples: &mut [f32],
+    num_channels: usize,
+) {
+    if !playhead.is_playing() || playhead.frame() != leftover.end {
+        leftover.discard();
+    }
+
+    let mut frames = samples.chunks_exact_mut(num_channels.max(1));
+
+    loop {
+        leftover.play(&mut frames);
+
+        if !playhead.is_playing() || frames.len() == 0 {
+            return;
+        }
+
+        graph.process();
+
+        let (Some(left), Some(right)) =
+            (graph.audio_output(output, 0), graph.audio_output(output, 1))
+        else {
+            return;
+        };
+
+        playhead.advance(left.len());
+        leftover.fill(left, right, playhead.frame());
+    }
+}
+
+type PendingGraph = Arc<Mutex<Option<(CompiledGraph, GraphNodeId)>>>;
+
+/// Dropped on the backend thread, since freeing it could block the realtime one.
+type RetiredGraph = Arc<Mutex<Option<CompiledGraph>>>;
+
+/// Nodes the realtime thread has disabled because they've panicked.
+type DisabledNodes = Arc<Mutex<Vec<GraphNodeId>>>;
+
+pub struct Engine {
+    arrangement_id: ArrangementId,
+    playhead: Playhead,
+    graph: EngineGraph,
+    pending: PendingGraph,
+    retired: RetiredGraph,
+    disabled: DisabledNodes,
+    status: EngineStatus,
+    sources: HashMap<AudioSourceId, SourceState>,
+    stream: Box<dyn DynOutStream>,
+    last_meter_poll: Instant,
+    /// Tracks which weren't silent in the last meter poll.
+    metered_tracks: HashSet<TrackId>,
+    is_stereo_metered: bool,
+}
+
+impl fmt::Debug for Engine {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Engine")
+            .field("arrangement_id", &self.arrangement_id)
+            .field("playhead", &self.playhead)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Engine {
+    pub fn new(
+        driver: &dyn DynDriver,
+        arrangement_id: ArrangementId,
+        limiter: LimiterParams,
+    ) -> Result<Engine> {
+        let params = GraphParams {
+            sample_rate: SAMPLE_RATE,
+            buffer_size: BUFFER_SIZE,
+        };
+
+        let playhead = Playhead::new();
+        let pending = PendingGraph::default();
+        let retired = RetiredGraph::default();
+        let disabled = DisabledNodes::default();
+
+        let channels = vec![AudioChannel::FrontLeft, AudioChannel::FrontRight];
+        let callback = {
+            let playhead = playhead.clone();
+            let pending = pending.clone();
+            let retired = retired.clone();
+            let disabled = disabled.clone();
+            let mut current = None::<(CompiledGraph, GraphNodeId)>;
+            let mut leftover = Leftover::new(params.buffer_size);
+            let mut num_disabled = 0;
+
+            move |data: OutCallbackData<'_>| {
+                // the new graph is only taken once the last replaced one has been collected
+                if let (Ok(mut pending), Ok(mut retired)) = (pending.try_lock(), retired.try_lock())
+                {
+                    if retired.is_none() {
+                        if let Some((mut graph, output)) = pending.take() {
+                            // nodes that have survived recompilation stay disabled
+                            if let Some((old, _)) = &current {
+                                for node in old.disabled_nodes() {
+                                    graph.disable_node(node);
+                                }
+                            }
+
+                            *retired = current.replace((graph, output)).map(|(old, _)| old);
+                            num_disabled = usize::MAX;
+                        }
+                    }
+                }
+
+                let samples = data.samples;
+                samples.fill(0.0);
+
+                let Some((graph, output)) = &mut current else {
+                    return;
+                };
+
+                render_period(
+                    graph,
+                    *output,
+                    &playhead,
+                    &mut leftover,
+                    samples,
+                    data.num_channels,
+                );
+
+                // only allocates when a node has panicked, which is rare enough
+                let new_num_disabled = graph.disabled_nodes().count();
+                if new_num_disabled != num_disabled {
+                    if let Ok(mut disabled) = disabled.try_lock() {
+                        disabled.clear();
+                        disabled.extend(graph.disabled_nodes());
+                        num_disabled = new_num_disabled;
+                    }
+                }
+            }
+        };
+
+        let stream = driver.create_out_stream(OutStreamDesc {
+            name: "rdaw".into(),
+            sample_rate: params.sample_rate,
+            buffer_size: params.buffer_size,
+            channels,
+            callback: Box::new(callback),
+        })?;
+
+        stream.set_active(true)?;
+
+        Ok(Engine {
+            arrangement_id,
+            graph: EngineGraph::new(params, playhead.clone()).with_limiter(limiter),
+            playhead,
+            pending,
+            retired,
+            disabled,
+            status: EngineStatus {
+                is_running: true,
+                disabled_nodes: Vec::new(),
+            },
+            sources: HashMap::default(),
+            stream,
+            last_meter_poll: Instant::now(),
+            metered_tracks: HashSet::default(),
+            is_stereo_metered: false,
+        })
+    }
+
+    pub fn arrangement_id(&self) -> ArrangementId {
+        self.arrangement_id
+    }
+
+    pub fn playhead(&self) -> &Playhead {
+        &self.playhead
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.graph.params().sample_rate
+    }
+
+    pub fn stream_info(&self) -> Result<StreamInfo> {
+        self.stream.info()
+    }
+
+    /// Last status returned by [`Engine::poll_status`].
+    pub fn status(&self) -> &EngineStatus {
+        &self.status
+    }
+
+    /// Returns the new status if it has changed since the last poll.
+    pub fn poll_status(&mut self) -> Option<&EngineStatus> {
+        self.collect_retired();
+
+        let disabled_nodes = self
+            .disabled
+            .lock()
+            .unwrap()
+            .iter()
+            .filter_map(|&node| self.graph.node_owner(node))
+            .collect::<Vec<_>>();
+
+        if disabled_nodes == self.status.disabled_nodes {
+            return None;
+        }
+
+        for node in &disabled_nodes {
+            if !self.status.disabled_nodes.contains(node) {
+                tracing::error!(?node, "node has panicked and was disabled");
+            }
+        }
+
+        self.status.disabled_nodes = disabled_nodes;
+        Some(&self.status)
+    }
+
+    pub fn modulation_value(&self, id: ModulatorId) -> Option<f32> {
+        self.graph.modulation_value(id)
+    }
+
+    /// Returns the levels of tracks rendered since the last poll, throttled to [`METER_RATE`].
+    /// Tracks which had levels in the last poll but not anymore get a silent frame, so that
+    /// meters fall back when playback stops.
+    pub fn poll_meters(&mut self) -> Option<Vec<(TrackId, MeterFrame)>> {
+        let now = Instant::now();
+        if now - self.last_meter_poll < Duration::from_secs(1) / METER_RATE {
+            return None;
+        }
+        self.last_meter_poll = now;
+
+        let meters = self.graph.poll_meters();
+
+        let mut frames = meters
+            .iter()
+            .map(|(&track_id, &frame)| (track_id, frame))
+            .collect::<Vec<_>>();
+
+        for &track_id in &self.metered_tracks {
+            if !meters.contains_key(&track_id) {
+                frames.push((track_id, MeterFrame::default()));
+            }
+        }
+
+        self.metered_tracks = meters
+            .into_iter()
+            .filter(|(_, frame)| !frame.is_silent())
+            .map(|(track_id, _)| track_id)
+            .collect();
+
+        Some(frames)
+    }
+
+    /// An empty poll after one with samples clears goniometers when playback stops.
+    pub fn poll_stereo_meter(&mut self) -> Option<Vec<[f32; 2]>> {
+        let samples = self.graph.poll_stereo_meter();
+        let was_metered = std::mem::replace(&mut self.is_stereo_metered, !samples.is_empty());
+        (was_metered || !samples.is_empty()).then_some(samples)
+    }
+
+    pub fn update(&mut self, desc: &GraphDesc) {
+        if !self.graph.update(desc) {
+            return;
+        }
+
+        self.collect_retired();
+
+        if let Some(compiled) = self.graph.compile() {
+            // a graph which the realtime thread hasn't picked up yet is dropped here
+            let old = self.pending.lock().unwrap().replace(compiled);
+            drop(old);
+        }
+    }
+
+    fn collect_retired(&self) {
+        let old = self.retired.lock().unwrap().take();
+        drop(old);
+        rdaw_clap::collect_garbage();
+    }
+}
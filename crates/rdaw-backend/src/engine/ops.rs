@@ -0,0 +1,538 @@
+use blake3::Hash;
+use rdaw_api::arrangement::ArrangementId;
+use rdaw_api::audio::{AudioInputStream as _, ResampleQuality};
+use rdaw_api::cache::CacheKind;
+use rdaw_api::engine::{EngineOperations, EngineRequest, EngineResponse, EngineStatus};
+use rdaw_api::item::ItemId;
+use rdaw_api::media::{MediaInput as _, OpenMediaInput as _};
+use rdaw_api::source::AudioSourceId;
+use rdaw_api::stats::TaskQueue;
+use rdaw_api::track::TrackId;
+use rdaw_api::transport::PlaybackState;
+use rdaw_api::{bail, BackendProtocol, Error, ErrorKind, Result};
+use rdaw_core::time::RealTime;
+use rdaw_ffmpeg::MediaInput;
+use rdaw_rpc::{Responder, StreamId};
+use tracing::instrument;
+
+use super::latency::LoopbackTest;
+use super::{
+    frames_to_time, time_to_frames, BusDesc, DecodedSource, Engine, GraphDesc, ItemDesc, ItemKey,
+    ModulatorDesc, PluginDesc, SendDesc, SourceState, TrackDesc, SAMPLE_RATE,
+};
+use crate::asset::AssetReader;
+use crate::cache::FileCache;
+use crate::tempo_map::TempoMap;
+use crate::track::view_item;
+use crate::Backend;
+
+/// Duration of launched clips that weren't stopped, long enough to never run out.
+const UNTIL_STOPPED: i64 = i64::MAX / 2;
+
+#[rdaw_rpc::handler(protocol = BackendProtocol, operations = EngineOperations)]
+impl Backend {
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn subscribe_engine_status(&mut self, id: ArrangementId) -> Result<StreamId> {
+        self.hub.arrangements.ensure_has(id)?;
+        Ok(self.subscribers.engine_status.subscribe(id))
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn get_engine_status(&self, id: ArrangementId) -> Result<EngineStatus> {
+        self.hub.arrangements.ensure_has(id)?;
+
+        let status = match &self.engine {
+            Some(engine) if engine.arrangement_id() == id => engine.status().clone(),
+            _ => EngineStatus::default(),
+        };
+
+        Ok(status)
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn measure_loopback_latency(
+        &mut self,
+        responder: impl Responder<RealTime, Error>,
+    ) -> Result<()> {
+        let Some(driver) = self.audio_driver.clone() else {
+            bail!(
+                ErrorKind::NotSupported,
+                "measuring latency needs an audio driver"
+            );
+        };
+
+        let is_playing = self
+            .transports
+            .values()
+            .any(|v| v.playback == PlaybackState::Playing);
+
+        if is_playing || !self.recordings.is_empty() {
+            bail!(ErrorKind::Busy, "can't measure latency during playback");
+        }
+
+        // the engine is bypassed while measuring, it's created again on playback
+        if let Some(engine) = self.engine.take() {
+            self.subscribers
+                .engine_status
+                .notify(engine.arrangement_id(), EngineStatus::default());
+        }
+
+        let test = LoopbackTest::start(&*driver)?;
+
+        let queue = self.queue.clone();
+        self.spawn(TaskQueue::Io, async move {
+            let res = test.measure();
+            queue.defer(move |_: &mut Backend| responder.respond(res));
+            Ok(())
+        });
+
+        Ok(())
+    }
+
+    /// Does nothing if there's no audio driver.
+    pub(crate) fn ensure_engine(&mut self, id: ArrangementId) -> Result<()> {
+        let Some(driver) = &self.audio_driver else {
+            return Ok(());
+        };
+
+        if self
+            .engine
+            .as_ref()
+            .is_some_and(|v| v.arrangement_id() == id)
+        {
+            return Ok(());
+        }
+
+        // only one stream at a time, the old one has to go before the new one is created
+        if let Some(old) = self.engine.take() {
+            let status = EngineStatus::default();
+            self.subscribers
+                .engine_status
+                .notify(old.arrangement_id(), status);
+        }
+
+        let engine = Engine::new(&**driver, id, self.monitoring_limiter.clone())?;
+        self.subscribers
+            .engine_status
+            .notify(id, engine.status().clone());
+
+        self.engine = Some(engine);
+        self.engine_dirty = true;
+        self.sync_engine_playhead(id);
+
+        Ok(())
+    }
+
+    pub(crate) fn update_engine(&mut self) {
+        if !std::mem::take(&mut self.engine_dirty) {
+            return;
+        }
+
+        let Some(mut engine) = self.engine.take() else {
+            return;
+        };
+
+        // the arrangement is gone, so is the engine
+        if self
+            .hub
+            .arrangements
+            .ensure_has(engine.arrangement_id())
+            .is_err()
+        {
+            return;
+        }
+
+        let mut desc = self.build_graph_desc(engine.arrangement_id(), |id| {
+            self.decoded_source(&mut engine, id)
+        });
+        self.add_launched_items(engine.arrangement_id(), &mut desc, |id| {
+            self.decoded_source(&mut engine, id)
+        });
+        engine.update(&desc);
+
+        self.engine = Some(engine);
+    }
+
+    /// Tells subscribers about nodes the realtime thread has disabled.
+    pub(crate) fn poll_engine_status(&mut self) {
+        let Some(engine) = &mut self.engine else {
+            return;
+        };
+
+        let id = engine.arrangement_id();
+        if let Some(status) = engine.poll_status() {
+            self.subscribers.engine_status.notify(id, status.clone());
+        }
+    }
+
+    pub(crate) fn sync_engine_playhead(&self, id: ArrangementId) {
+        let Some(engine) = self.engine.as_ref().filter(|v| v.arrangement_id() == id) else {
+            return;
+        };
+
+        let transport = self.transports.get(&id).cloned().unwrap_or_default();
+        let sample_rate = engine.sample_rate();
+
+        let loop_range = transport
+            .loop_region
+            .and_then(|region| self.resolve_loop_range(id, region).ok())
+            .map(|range| {
+                (
+                    time_to_frames(range.start, sample_rate),
+                    time_to_frames(range.end, sample_rate),
+                )
+            });
+
+        let playhead = engine.playhead();
+        playhead.set_playing(false);
+        playhead.seek(time_to_frames(transport.position, sample_rate));
+        playhead.set_loop(loop_range);
+        playhead.set_playing(transport.playback == PlaybackState::Playing);
+    }
+
+    pub(crate) fn engine_position(&self, id: ArrangementId) -> Option<RealTime> {
+        let engine = self.engine.as_ref().filter(|v| v.arrangement_id() == id)?;
+        Some(frames_to_time(
+            engine.playhead().frame(),
+            engine.sample_rate(),
+        ))
+    }
+
+    /// Items are left out if `source` doesn't return their decoded source.
+    pub(crate) fn build_graph_desc(
+        &self,
+        arrangement_id: ArrangementId,
+        mut source: impl FnMut(AudioSourceId) -> Option<DecodedSource>,
+    ) -> GraphDesc {
+        let mut desc = GraphDesc::default();
+
+        let Some(arrangement) = self.hub.arrangements.get(arrangement_id) else {
+            return desc;
+        };
+
+        let tempo_map = &self.hub.tempo_maps[arrangement.tempo_map_id];
+        self.add_track_desc(&mut source, tempo_map, arrangement.main_track_id, &mut desc);
+
+        for &bus_id in &arrangement.buses {
+            let Some(bus) = self.hub.buses.get(bus_id) else {
+                continue;
+            };
+
+            // the main track is left out, since buses are mixed into it
+            let others = desc.tracks.split_last().map_or(&[][..], |(_, v)| v);
+            let sends = others
+                .iter()
+                .flat_map(|track| {
+                    self.hub.tracks[track.id]
+                        .sends
+                        .iter()
+                        .filter(|(_, send)| send.bus_id == bus_id)
+                        .map(|(id, send)| SendDesc {
+                            track_id: track.id,
+                            id,
+                            position: send.position,
+                            level: send.level,
+                        })
+                })
+                .collect();
+
+            desc.buses.push(BusDesc {
+                id: bus_id,
+                sends,
+                volume: bus.mix.volume,
+                pan: bus.mix.pan,
+            });
+        }
+
+        desc.modulators = arrangement
+            .modulators
+            .iter()
+            .map(|(id, modulator)| ModulatorDesc {
+                id,
+                source: modulator.source,
+            })
+            .collect();
+
+        desc
+    }
+
+    fn add_track_desc(
+        &self,
+        source: &mut impl FnMut(AudioSourceId) -> Option<DecodedSource>,
+        tempo_map: &TempoMap,
+        id: TrackId,
+        desc: &mut GraphDesc,
+    ) {
+        let Some(track) = self.hub.tracks.get(id) else {
+            return;
+        };
+
+        for &child in &track.links.children {
+            self.add_track_desc(source, tempo_map, child, desc);
+        }
+
+        let mut items = Vec::new();
+
+        for (item_id, item) in &track.items {
+            // only audio items are rendered for now
+            let ItemId::Audio(audio_item_id) = item.inner else {
+                continue;
+            };
+
+            let Some(audio_item) = self.hub.audio_items.get(audio_item_id) else {
+                continue;
+            };
+
+            let Some(decoded) = source(audio_item.source_id) else {
+                continue;
+            };
+
+            let resample_quality = self
+                .hub
+                .audio_sources
+                .get(audio_item.source_id)
+                .map_or(ResampleQuality::default(), |v| v.resample_quality);
+
+            // frames of views are at the engine's sample rate
+            let view_item = view_item(tempo_map, item, Some(audio_item.clip));
+
+            items.push(ItemDesc {
+                id: ItemKey::Track(item_id),
+                source: decoded,
+                start: view_item.frame_start,
+                duration: view_item.frame_duration(),
+                loop_length: None,
+                clip: audio_item.clip,
+                resample_quality,
+            });
+        }
+
+        let plugins = track
+            .nodes
+            .iter()
+            .filter_map(|&id| {
+                let node = self.plugins.get_node(id)?.clone();
+                Some(PluginDesc { id, node })
+            })
+            .collect();
+
+        desc.tracks.push(TrackDesc {
+            id,
+            children: track.links.children.clone(),
+            items,
+            plugins,
+            volume: track.mix.volume,
+            pan: track.mix.pan,
+        });
+    }
+
+    /// Launched clips loop from where they start until they're stopped. Renders leave them out,
+    /// since they're not part of the arrangement.
+    fn add_launched_items(
+        &self,
+        arrangement_id: ArrangementId,
+        desc: &mut GraphDesc,
+        mut source: impl FnMut(AudioSourceId) -> Option<DecodedSource>,
+    ) {
+        let (Some(arrangement), Some(launcher)) = (
+            self.hub.arrangements.get(arrangement_id),
+            self.launchers.get(&arrangement_id),
+        ) else {
+            return;
+        };
+
+        let tempo_map = &self.hub.tempo_maps[arrangement.tempo_map_id];
+        let to_frames = |beat| time_to_frames(tempo_map.beat_to_real(beat), SAMPLE_RATE);
+
+        for track in &mut desc.tracks {
+            let Some(launched) = launcher.launched.get(&track.id) else {
+                continue;
+            };
+
+            let Some(clip) = arrangement
+                .scenes
+                .get(launched.scene_id)
+                .and_then(|scene| scene.clips.get(&track.id))
+            else {
+                continue;
+            };
+
+            // only audio items are rendered for now
+            let ItemId::Audio(audio_item_id) = clip.item else {
+                continue;
+            };
+
+            let Some(audio_item) = self.hub.audio_items.get(audio_item_id) else {
+                continue;
+            };
+
+            let Some(decoded) = source(audio_item.source_id) else {
+                continue;
+            };
+
+            let resample_quality = self
+                .hub
+                .audio_sources
+                .get(audio_item.source_id)
+                .map_or(ResampleQuality::default(), |v| v.resample_quality);
+
+            let start = to_frames(launched.start);
+            let duration = match launched.stop {
+                Some(stop) => to_frames(stop) - start,
+                None => UNTIL_STOPPED,
+            };
+
+            track.items.push(ItemDesc {
+                id: ItemKey::Launched,
+                source: decoded,
+                start,
+                duration,
+                loop_length: Some(to_frames(launched.start + clip.length) - start),
+                clip: audio_item.clip,
+                resample_quality,
+            });
+        }
+    }
+
+    /// The engine is updated again once the source is decoded.
+    fn decoded_source(&self, engine: &mut Engine, id: AudioSourceId) -> Option<DecodedSource> {
+        match engine.sources.get(&id) {
+            Some(SourceState::Ready(source)) => return Some(source.clone()),
+            Some(SourceState::Decoding | SourceState::Failed) => return None,
+            None => {}
+        }
+
+        let reader = self.hub.audio_sources.get_or_err(id).and_then(|source| {
+            let hash = self.hub.assets.get_or_err(source.asset_id)?.hash();
+            Ok((self.open_asset(source.asset_id)?, hash))
+        });
+
+        let (reader, asset_hash) = match reader {
+            Ok(v) => v,
+            Err(error) => {
+                tracing::warn!(?id, ?error, "can't play audio source");
+                engine.sources.insert(id, SourceState::Failed);
+                return None;
+            }
+        };
+
+        engine.sources.insert(id, SourceState::Decoding);
+
+        let queue = self.queue.clone();
+        let cache = self.cache.clone();
+        self.spawn(TaskQueue::Decode, async move {
+            let state = match decode_cached_source(reader, cache.as_deref(), asset_hash) {
+                Ok(source) => SourceState::Ready(source),
+                Err(error) => {
+                    tracing::warn!(?id, ?error, "failed to decode audio source");
+                    SourceState::Failed
+                }
+            };
+
+            queue.defer(move |this: &mut Backend| {
+                if let Some(engine) = &mut this.engine {
+                    engine.sources.insert(id, state);
+                    this.engine_dirty = true;
+                }
+
+                std::future::ready(Ok(()))
+            });
+
+            Ok(())
+        });
+
+        None
+    }
+}
+
+/// Decodes the source, or loads it from the cache if it was decoded before.
+pub fn decode_cached_source(
+    reader: AssetReader,
+    cache: Option<&FileCache>,
+    asset_hash: Hash,
+) -> Result<DecodedSource> {
+    let Some(cache) = cache else {
+        return decode_source(reader);
+    };
+
+    match cache.read(CacheKind::Decoded, asset_hash) {
+        Ok(Some(data)) => match parse_decoded_source(&data) {
+            Some(source) => return Ok(source),
+            None => tracing::warn!(?asset_hash, "invalid decoded source in the cache"),
+        },
+        Ok(None) => {}
+        Err(error) => tracing::warn!(?asset_hash, ?error, "failed to read decoded source"),
+    }
+
+    let source = decode_source(reader)?;
+    let data = encode_decoded_source(&source);
+
+    if let Err(error) = cache.write(CacheKind::Decoded, asset_hash, &data) {
+        tracing::warn!(?asset_hash, ?error, "failed to cache decoded source");
+    }
+
+    Ok(source)
+}
+
+/// Sample rate followed by the samples, all little-endian.
+fn encode_decoded_source(source: &DecodedSource) -> Vec<u8> {
+    let mut data = Vec::with_capacity(4 + source.samples.len() * 4);
+    data.extend_from_slice(&source.sample_rate.to_le_bytes());
+
+    for sample in source.samples.iter() {
+        data.extend_from_slice(&sample.to_le_bytes());
+    }
+
+    data
+}
+
+fn parse_decoded_source(data: &[u8]) -> Option<DecodedSource> {
+    let (sample_rate, samples) = data.split_first_chunk::<4>()?;
+    if samples.len() % 4 != 0 {
+        return None;
+    }
+
+    let samples = samples
+        .chunks_exact(4)
+        .map(|v| f32::from_le_bytes([v[0], v[1], v[2], v[3]]))
+        .collect();
+
+    Some(DecodedSource {
+        samples,
+        sample_rate: u32::from_le_bytes(*sample_rate),
+    })
+}
+
+fn decode_source(reader: AssetReader) -> Result<DecodedSource> {
+    let mut media = MediaInput::open(reader)?;
+    let Some(mut stream) = media.get_audio_stream()? else {
+        bail!(ErrorKind::NotFound, "asset doesn't have an audio stream");
+    };
+
+    let num_channels = stream.metadata().channels.len().max(1);
+    let sample_rate = stream.metadata().sample_rate;
+
+    // the graph is mono for now, so channels are averaged
+    let mut samples = Vec::new();
+
+    loop {
+        let frame = stream.next_frame()?;
+        if frame.is_empty() {
+            break;
+        }
+
+        samples.extend(
+            frame
+                .chunks_exact(num_channels)
+                .map(|v| v.iter().sum::<f32>() / num_channels as f32),
+        );
+    }
+
+    Ok(DecodedSource {
+        samples: samples.into(),
+        sample_rate,
+    })
+}
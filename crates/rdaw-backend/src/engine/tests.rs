@@ -0,0 +1,665 @@
+use std::sync::Arc;
+
+use rdaw_api::audio::ResampleQuality;
+use rdaw_api::bus::{BusId, SendId, SendPosition};
+use rdaw_api::document::DocumentOperations;
+use rdaw_api::engine::{EngineNode, EngineOperations, EngineStatus};
+use rdaw_api::item::AudioClip;
+use rdaw_api::meter::StereoFrame;
+use rdaw_api::modulation::{EnvelopeFollower, Lfo, LfoShape, ModulationSource, ModulatorId};
+use rdaw_api::track::{TrackId, TrackItemId};
+use rdaw_api::transport::TransportOperations;
+use rdaw_api::{assert_err, ErrorKind, Result};
+use rdaw_audio::driver::{NullDriver, OfflineDriver};
+use rdaw_audio::graph::GraphParams;
+use rdaw_audio::nodes::LimiterParams;
+use rdaw_audio::playhead::Playhead;
+use rdaw_core::time::RealTime;
+use slotmap::SlotMap;
+
+use super::latency::find_impulse;
+use super::{
+    frames_to_time, render_period, time_to_frames, BusDesc, DecodedSource, EngineGraph, GraphDesc,
+    ItemDesc, ItemKey, Leftover, ModulatorDesc, SendDesc, TrackDesc,
+};
+use crate::tests::{run_test, run_test_with};
+
+const PARAMS: GraphParams = GraphParams {
+    sample_rate: 48000,
+    buffer_size: 4,
+};
+
+fn source(value: f32) -> DecodedSource {
+    DecodedSource {
+        samples: vec![value; 16].into(),
+        sample_rate: 48000,
+    }
+}
+
+fn item(id: TrackItemId, source: &DecodedSource, start: i64) -> ItemDesc {
+    ItemDesc {
+        id: ItemKey::Track(id),
+        source: source.clone(),
+        start,
+        duration: 16,
+        loop_length: None,
+        clip: AudioClip::default(),
+        resample_quality: ResampleQuality::default(),
+    }
+}
+
+struct Ids {
+    main: TrackId,
+    drums: TrackId,
+    bass: TrackId,
+    items: Vec<TrackItemId>,
+}
+
+fn ids() -> Ids {
+    let mut tracks = SlotMap::<TrackId, ()>::with_key();
+    let mut items = SlotMap::<TrackItemId, ()>::with_key();
+
+    Ids {
+        main: tracks.insert(()),
+        drums: tracks.insert(()),
+        bass: tracks.insert(()),
+        items: (0..2).map(|_| items.insert(())).collect(),
+    }
+}
+
+fn desc(ids: &Ids, drums: Vec<ItemDesc>, bass: Vec<ItemDesc>) -> GraphDesc {
+    GraphDesc {
+        tracks: vec![
+            TrackDesc {
+                id: ids.drums,
+                children: Vec::new(),
+                items: drums,
+                plugins: Vec::new(),
+                volume: 1.0,
+                pan: 0.0,
+            },
+            TrackDesc {
+                id: ids.bass,
+                children: Vec::new(),
+                items: bass,
+                plugins: Vec::new(),
+                volume: 1.0,
+                pan: 0.0,
+            },
+            TrackDesc {
+                id: ids.main,
+                children: vec![ids.drums, ids.bass],
+                items: Vec::new(),
+                plugins: Vec::new(),
+                volume: 1.0,
+                pan: 0.0,
+            },
+        ],
+        buses: Vec::new(),
+        modulators: Vec::new(),
+    }
+}
+
+#[test]
+fn time_conversion() {
+    assert_eq!(time_to_frames(RealTime::from_secs(2), 48000), 96000);
+    assert_eq!(frames_to_time(24000, 48000), RealTime::from_secs_f64(0.5));
+    assert_eq!(time_to_frames(RealTime::from_secs(-1), 44100), -44100);
+}
+
+#[test]
+fn incremental_update() {
+    let ids = ids();
+    let kick = source(1.0);
+    let bass = source(0.5);
+
+    let mut graph = EngineGraph::new(PARAMS, Playhead::new());
+    assert!(graph.compile().is_none());
+
+    let first = desc(
+        &ids,
+        vec![item(ids.items[0], &kick, 0)],
+        vec![item(ids.items[0], &bass, 0)],
+    );
+    assert!(graph.update(&first));
+    assert!(!graph.update(&first));
+
+    let drums_node = graph.tracks[&ids.drums].node;
+    let bass_node = graph.tracks[&ids.bass].node;
+    let main_node = graph.tracks[&ids.main].node;
+
+    // moving a bass item only rebuilds the bass track and its parents
+    let moved = desc(
+        &ids,
+        vec![item(ids.items[0], &kick, 0)],
+        vec![item(ids.items[0], &bass, 4)],
+    );
+    assert!(graph.update(&moved));
+    assert_eq!(graph.tracks[&ids.drums].node, drums_node);
+    assert_ne!(graph.tracks[&ids.bass].node, bass_node);
+    assert_ne!(graph.tracks[&ids.main].node, main_node);
+
+    // a source with the same samples, but decoded again, is a different source
+    let redecoded = desc(
+        &ids,
+        vec![item(ids.items[0], &source(1.0), 0)],
+        vec![item(ids.items[0], &bass, 4)],
+    );
+    assert!(graph.update(&redecoded));
+    assert_ne!(graph.tracks[&ids.drums].node, drums_node);
+
+    let removed = desc(&ids, Vec::new(), vec![item(ids.items[0], &bass, 4)]);
+    assert!(graph.update(&removed));
+    assert_eq!(graph.items.len(), 1);
+}
+
+#[test]
+fn render() {
+    let ids = ids();
+    let kick = source(1.0);
+    let bass = source(0.5);
+    let playhead = Playhead::new();
+
+    let mut graph = EngineGraph::new(PARAMS, playhead.clone());
+    graph.update(&desc(
+        &ids,
+        vec![item(ids.items[0], &kick, 0), item(ids.items[1], &kick, 2)],
+        vec![item(ids.items[0], &bass, 1)],
+    ));
+
+    let (mut compiled, master) = graph.compile().unwrap();
+
+    compiled.process();
+    let output = compiled.audio_output(master, 0).unwrap();
+    assert_eq!(&output[..], &[1.0, 1.5, 2.5, 2.5]);
+
+    playhead.seek(16);
+    compiled.process();
+    let output = compiled.audio_output(master, 0).unwrap();
+    assert_eq!(&output[..], &[1.5, 1.0, 0.0, 0.0]);
+}
+
+#[test]
+fn large_graph() {
+    let mut tracks = SlotMap::<TrackId, ()>::with_key();
+    let mut items = SlotMap::<TrackItemId, ()>::with_key();
+    let item_ids = (0..4).map(|_| items.insert(())).collect::<Vec<_>>();
+    let kick = source(1.0);
+
+    let mut desc = GraphDesc {
+        tracks: (0..40)
+            .map(|_| TrackDesc {
+                id: tracks.insert(()),
+                children: Vec::new(),
+                items: item_ids.iter().map(|&id| item(id, &kick, 0)).collect(),
+                plugins: Vec::new(),
+                volume: 1.0,
+                pan: 0.0,
+            })
+            .collect(),
+        buses: Vec::new(),
+        modulators: Vec::new(),
+    };
+
+    desc.tracks.push(TrackDesc {
+        id: tracks.insert(()),
+        children: desc.tracks.iter().map(|v| v.id).collect(),
+        items: Vec::new(),
+        plugins: Vec::new(),
+        volume: 1.0,
+        pan: 0.0,
+    });
+
+    let mut graph = EngineGraph::new(PARAMS, Playhead::new());
+    graph.update(&desc);
+
+    // the ports of all nodes have to fit into the arena of the compiled graph
+    let (mut compiled, master) = graph.compile().unwrap();
+    compiled.process();
+    compiled.process();
+
+    let output = compiled.audio_output(master, 0).unwrap();
+    assert_eq!(&output[..], &[160.0; 4]);
+}
+
+#[test]
+fn uneven_periods() {
+    let ids = ids();
+    let ramp = DecodedSource {
+        samples: (0..16).map(|v| v as f32).collect::<Vec<_>>().into(),
+        sample_rate: 48000,
+    };
+
+    let playhead = Playhead::new();
+    playhead.set_playing(true);
+
+    let mut graph = EngineGraph::new(PARAMS, playhead.clone());
+    graph.update(&desc(&ids, vec![item(ids.items[0], &ramp, 0)], Vec::new()));
+    let (mut compiled, output) = graph.compile().unwrap();
+
+    let mut leftover = Leftover::new(PARAMS.buffer_size);
+    let mut samples = [0.0; 6];
+
+    render_period(&mut compiled, output, &playhead, &mut leftover, &mut samples, 1);
+    assert_eq!(samples, [0.0, 1.0, 2.0, 3.0, 4.0, 5.0]);
+
+    // the second block is rendered once, its rest is played in the next period
+    assert_eq!(playhead.frame(), 8);
+
+    render_period(&mut compiled, output, &playhead, &mut leftover, &mut samples, 1);
+    assert_eq!(samples, [6.0, 7.0, 8.0, 9.0, 10.0, 11.0]);
+    assert_eq!(playhead.frame(), 12);
+}
+
+#[test]
+fn launched_clip() {
+    let ids = ids();
+    let source = DecodedSource {
+        samples: vec![1.0, 2.0, 3.0, 4.0].into(),
+        sample_rate: 48000,
+    };
+    let playhead = Playhead::new();
+
+    let launched = ItemDesc {
+        id: ItemKey::Launched,
+        duration: 6,
+        loop_length: Some(3),
+        ..item(ids.items[0], &source, 1)
+    };
+
+    // plays along with the item of the track which has the same id
+    let mut graph = EngineGraph::new(PARAMS, playhead.clone());
+    graph.update(&desc(
+        &ids,
+        vec![item(ids.items[0], &source, 0), launched],
+        Vec::new(),
+    ));
+
+    let (mut compiled, master) = graph.compile().unwrap();
+
+    compiled.process();
+    let output = compiled.audio_output(master, 0).unwrap();
+    assert_eq!(&output[..], &[1.0, 3.0, 5.0, 7.0]);
+
+    playhead.advance(PARAMS.buffer_size);
+    compiled.process();
+    let output = compiled.audio_output(master, 0).unwrap();
+    assert_eq!(&output[..], &[1.0, 2.0, 3.0, 0.0]);
+}
+
+#[test]
+fn resampled_source() {
+    let ids = ids();
+    let source = DecodedSource {
+        samples: vec![1.0; 512].into(),
+        sample_rate: 24000,
+    };
+    let playhead = Playhead::new();
+
+    let item = ItemDesc {
+        duration: 1024,
+        ..item(ids.items[0], &source, 0)
+    };
+
+    let mut graph = EngineGraph::new(PARAMS, playhead.clone());
+    graph.update(&desc(&ids, vec![item], Vec::new()));
+
+    let (mut compiled, master) = graph.compile().unwrap();
+    let mut render = || {
+        let mut samples = Vec::new();
+        while samples.len() < 1024 {
+            compiled.process();
+            samples.extend_from_slice(&compiled.audio_output(master, 0).unwrap()[..]);
+            playhead.advance(PARAMS.buffer_size);
+        }
+        samples
+    };
+
+    // twice as many frames, away from the edges where the filter rings
+    let samples = render();
+    assert!(samples[64..960].iter().all(|v| (v - 1.0).abs() < 0.05));
+
+    // starts over after a seek
+    playhead.seek(0);
+    assert_eq!(render(), samples);
+}
+
+#[test]
+fn volume_and_pan() {
+    let ids = ids();
+    let kick = source(1.0);
+    let playhead = Playhead::new();
+
+    let mut graph = EngineGraph::new(PARAMS, playhead.clone());
+    let mut desc = desc(&ids, vec![item(ids.items[0], &kick, 0)], Vec::new());
+    graph.update(&desc);
+
+    let (mut compiled, master) = graph.compile().unwrap();
+    let mut render = || {
+        playhead.seek(0);
+        compiled.process();
+        [0, 1].map(|port| compiled.audio_output(master, port).unwrap()[3])
+    };
+
+    assert_eq!(render(), [1.0, 1.0]);
+
+    // doesn't need a recompilation
+    desc.tracks[0].volume = 0.5;
+    desc.tracks[0].pan = 1.0;
+    assert!(!graph.update(&desc));
+    assert_eq!(render()[0], 0.0);
+    assert_eq!(render(), [0.0, 0.5]);
+}
+
+#[test]
+fn modulators() {
+    let ids = ids();
+    let kick = source(1.0);
+
+    let mut modulator_ids = SlotMap::<ModulatorId, ()>::with_key();
+    let lfo_id = modulator_ids.insert(());
+    let follower_id = modulator_ids.insert(());
+
+    let with_modulators = |drums| GraphDesc {
+        modulators: vec![
+            ModulatorDesc {
+                id: lfo_id,
+                source: ModulationSource::Lfo(Lfo {
+                    shape: LfoShape::Saw,
+                    rate: 1.0,
+                    phase: 0.5,
+                }),
+            },
+            ModulatorDesc {
+                id: follower_id,
+                source: ModulationSource::EnvelopeFollower(EnvelopeFollower {
+                    track_id: ids.drums,
+                    attack: RealTime::ZERO,
+                    release: RealTime::ZERO,
+                }),
+            },
+        ],
+        ..desc(&ids, drums, Vec::new())
+    };
+
+    let mut graph = EngineGraph::new(PARAMS, Playhead::new());
+    assert!(graph.update(&with_modulators(vec![item(ids.items[0], &kick, 0)])));
+    assert!(!graph.update(&with_modulators(vec![item(ids.items[0], &kick, 0)])));
+
+    let lfo_node = graph.modulators[&lfo_id].node;
+    let follower_node = graph.modulators[&follower_id].node;
+
+    let (mut compiled, _) = graph.compile().unwrap();
+    compiled.process();
+    assert_eq!(graph.modulation_value(lfo_id), Some(0.5));
+    assert_eq!(graph.modulation_value(follower_id), Some(1.0));
+
+    // the follower is reconnected when its track is rebuilt
+    assert!(graph.update(&with_modulators(Vec::new())));
+    assert_eq!(graph.modulators[&lfo_id].node, lfo_node);
+    assert_ne!(graph.modulators[&follower_id].node, follower_node);
+
+    assert!(graph.update(&desc(&ids, Vec::new(), Vec::new())));
+    assert!(graph.modulators.is_empty());
+    assert_eq!(graph.modulation_value(lfo_id), None);
+}
+
+#[test]
+fn buses() {
+    let ids = ids();
+    let kick = source(1.0);
+    let playhead = Playhead::new();
+
+    let bus_id = SlotMap::<BusId, ()>::with_key().insert(());
+    let send_id = SlotMap::<SendId, ()>::with_key().insert(());
+
+    let with_bus = |position, level| {
+        let mut desc = GraphDesc {
+            buses: vec![BusDesc {
+                id: bus_id,
+                sends: vec![SendDesc {
+                    track_id: ids.drums,
+                    id: send_id,
+                    position,
+                    level,
+                }],
+                volume: 1.0,
+                pan: 0.0,
+            }],
+            ..desc(&ids, vec![item(ids.items[0], &kick, 0)], Vec::new())
+        };
+        desc.tracks[0].volume = 0.5;
+        desc
+    };
+
+    let mut graph = EngineGraph::new(PARAMS, playhead.clone());
+    assert!(graph.update(&with_bus(SendPosition::PostFader, 0.5)));
+
+    let bus_node = graph.buses[&bus_id].node;
+    assert_eq!(graph.node_owner(bus_node), Some(EngineNode::Bus(bus_id)));
+
+    let render = |graph: &EngineGraph| {
+        let (mut compiled, master) = graph.compile().unwrap();
+        playhead.seek(0);
+        compiled.process();
+        compiled.audio_output(master, 0).unwrap()[0]
+    };
+
+    // the track at half volume, plus half of that through the bus
+    assert_eq!(render(&graph), 0.75);
+
+    // send levels don't need a recompilation
+    assert!(!graph.update(&with_bus(SendPosition::PostFader, 1.0)));
+    assert_eq!(graph.buses[&bus_id].node, bus_node);
+    assert_eq!(render(&graph), 1.0);
+
+    // pre-fader sends ignore the volume of the track
+    assert!(graph.update(&with_bus(SendPosition::PreFader, 1.0)));
+    assert_ne!(graph.buses[&bus_id].node, bus_node);
+    assert_eq!(render(&graph), 1.5);
+
+    assert!(graph.update(&desc(&ids, Vec::new(), Vec::new())));
+    assert!(graph.buses.is_empty());
+    assert_eq!(graph.node_owner(bus_node), None);
+}
+
+#[test]
+fn meters() {
+    let ids = ids();
+    let kick = source(1.0);
+    let playhead = Playhead::new();
+
+    let mut graph = EngineGraph::new(PARAMS, playhead.clone());
+    let mut desc = desc(&ids, vec![item(ids.items[0], &kick, 2)], Vec::new());
+    desc.tracks[0].volume = 0.5;
+    graph.update(&desc);
+
+    let (mut compiled, _) = graph.compile().unwrap();
+    compiled.process();
+    playhead.advance(PARAMS.buffer_size);
+    compiled.process();
+
+    let meters = graph.poll_meters();
+    assert_eq!(meters.len(), 3);
+
+    // half of the first block and the whole second one, after the volume
+    let drums = meters[&ids.drums];
+    assert_eq!(drums.peak, [0.5, 0.5]);
+    assert!(drums.rms.iter().all(|v| (v - 0.1875f32.sqrt()).abs() < 1e-6));
+    assert_eq!(meters[&ids.main].peak, [0.5, 0.5]);
+    assert!(meters[&ids.bass].is_silent());
+
+    assert!(graph.poll_meters().is_empty());
+}
+
+#[test]
+fn stereo_meter() {
+    let ids = ids();
+    let kick = source(1.0);
+    let playhead = Playhead::new();
+
+    let mut graph = EngineGraph::new(PARAMS, playhead.clone());
+    graph.update(&desc(&ids, vec![item(ids.items[0], &kick, 2)], Vec::new()));
+
+    let (mut compiled, _) = graph.compile().unwrap();
+    compiled.process();
+
+    let samples = graph.poll_stereo_meter();
+    assert_eq!(samples.len(), PARAMS.buffer_size);
+    assert_eq!(samples[0], [0.0; 2]);
+    assert_eq!(samples[2][0], samples[2][1]);
+    assert!(samples[2][0] > 0.0);
+
+    let frame = StereoFrame::measure(&samples, 2);
+    assert_eq!(frame.points, [samples[0], samples[2]]);
+    assert!((frame.correlation - 1.0).abs() < 1e-6);
+
+    // the meter follows the master track when it's rebuilt
+    let mut desc = desc(&ids, Vec::new(), Vec::new());
+    desc.tracks[2].children.pop();
+    assert!(graph.update(&desc));
+
+    let (mut compiled, _) = graph.compile().unwrap();
+    compiled.process();
+    assert_eq!(graph.poll_stereo_meter(), [[0.0; 2]; 4]);
+}
+
+#[test]
+fn limiter() {
+    let ids = ids();
+    let kick = source(1.0);
+    let playhead = Playhead::new();
+    let limiter = LimiterParams::new(false, -6.0);
+
+    let mut graph = EngineGraph::new(PARAMS, playhead.clone()).with_limiter(limiter.clone());
+    graph.update(&desc(&ids, vec![item(ids.items[0], &kick, 0)], Vec::new()));
+
+    let (mut compiled, output) = graph.compile().unwrap();
+    assert_eq!(graph.node_owner(output), None);
+
+    compiled.process();
+    assert_eq!(compiled.audio_output(output, 0).unwrap()[..], [1.0; 4]);
+
+    // what was played before is forgotten, and the rest is delayed
+    limiter.set(true, -6.0);
+    playhead.advance(PARAMS.buffer_size);
+    compiled.process();
+    assert_eq!(compiled.audio_output(output, 0).unwrap()[..], [0.0; 4]);
+
+    // the delayed signal isn't lost when the graph is recompiled
+    let is_delayed = (0..64).any(|_| {
+        let (mut compiled, output) = graph.compile().unwrap();
+        playhead.advance(PARAMS.buffer_size);
+        compiled.process();
+        compiled.audio_output(output, 0).unwrap().iter().any(|&v| v != 0.0)
+    });
+    assert!(is_delayed);
+
+    // offline renders aren't limited
+    let mut graph = EngineGraph::new(PARAMS, Playhead::new());
+    graph.update(&desc(&ids, vec![item(ids.items[0], &kick, 0)], Vec::new()));
+    let (_, master) = graph.compile().unwrap();
+    assert_eq!(graph.node_owner(master), Some(EngineNode::Track(ids.main)));
+}
+
+#[test]
+fn node_owner() {
+    let ids = ids();
+    let kick = source(1.0);
+
+    let mut graph = EngineGraph::new(PARAMS, Playhead::new());
+    graph.update(&desc(&ids, vec![item(ids.items[0], &kick, 0)], Vec::new()));
+
+    let track_node = graph.tracks[&ids.drums].node;
+    let item_node = graph.items[&(ids.drums, ItemKey::Track(ids.items[0]))].node;
+
+    assert_eq!(
+        graph.node_owner(track_node),
+        Some(EngineNode::Track(ids.drums))
+    );
+    assert_eq!(
+        graph.node_owner(item_node),
+        Some(EngineNode::Item(ids.drums, ids.items[0]))
+    );
+
+    graph.update(&desc(&ids, Vec::new(), Vec::new()));
+    assert_eq!(graph.node_owner(item_node), None);
+}
+
+#[test]
+fn engine_status_without_driver() -> Result<()> {
+    run_test(|client| async move {
+        let document_id = client.create_document().await?;
+        let arrangement_id = client.get_document_arrangement(document_id).await?;
+
+        client.play(arrangement_id).await?;
+        assert_eq!(
+            client.get_engine_status(arrangement_id).await?,
+            EngineStatus::default()
+        );
+
+        Ok(())
+    })
+}
+
+#[test]
+fn engine_status() -> Result<()> {
+    run_test_with(
+        |backend| backend.set_audio_driver(Arc::new(OfflineDriver::new())),
+        |client| async move {
+            let first_doc = client.create_document().await?;
+            let first_id = client.get_document_arrangement(first_doc).await?;
+            let second_doc = client.create_document().await?;
+            let second_id = client.get_document_arrangement(second_doc).await?;
+
+            let running = EngineStatus {
+                is_running: true,
+                disabled_nodes: Vec::new(),
+            };
+
+            client.play(first_id).await?;
+            assert_eq!(client.get_engine_status(first_id).await?, running);
+
+            // only one arrangement is rendered at a time
+            client.play(second_id).await?;
+            assert_eq!(client.get_engine_status(second_id).await?, running);
+            assert_eq!(
+                client.get_engine_status(first_id).await?,
+                EngineStatus::default()
+            );
+
+            Ok(())
+        },
+    )
+}
+
+#[test]
+fn impulse_detection() {
+    assert_eq!(find_impulse(&[0.0, 0.1, -0.2, 0.0], 2), None);
+    assert_eq!(find_impulse(&[0.0, 0.1, 0.0, 0.0, 0.0, -0.8], 2), Some(2));
+}
+
+#[test]
+fn loopback_latency_without_driver() -> Result<()> {
+    run_test(|client| async move {
+        assert_err!(
+            client.measure_loopback_latency().await,
+            ErrorKind::NotSupported
+        );
+
+        Ok(())
+    })
+}
+
+#[test]
+fn loopback_latency_without_loopback() -> Result<()> {
+    run_test_with(
+        |backend| backend.set_audio_driver(Arc::new(NullDriver::new())),
+        |client| async move {
+            // the null driver captures silence, so the impulse never comes back
+            assert_err!(client.measure_loopback_latency().await, ErrorKind::Timeout);
+
+            Ok(())
+        },
+    )
+}
@@ -0,0 +1,168 @@
+//! User export presets are stored as JSON files in `<dir>/export/<name>.json`:
+//!
+//! ```json
+//! { "version": 1, "name": "Radio", "format": "flac", "sample_rate": 48000, ... }
+//! ```
+
+mod ops;
+#[cfg(test)]
+mod tests;
+
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Write};
+
+use rdaw_api::error::ResultExt;
+use rdaw_api::export::{ExportFormat, ExportPreset, ExportSampleFormat, ExportSettings};
+use rdaw_api::{bail, ErrorKind, Result};
+use rdaw_audio::dither::{Dither, Quantizer};
+use rdaw_audio::loudness;
+use rdaw_core::path::{Utf8Path, Utf8PathBuf};
+use serde::{Deserialize, Serialize};
+
+use crate::define_version_enum;
+use crate::node::presets::validate_name;
+
+const SUBDIR: &str = "export";
+const EXTENSION: &str = "json";
+
+define_version_enum! {
+    enum Version {
+        V1 = 1,
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct PresetFile {
+    version: u32,
+    #[serde(flatten)]
+    preset: ExportPreset,
+}
+
+pub fn validate_settings(settings: &ExportSettings) -> Result<()> {
+    if settings.sample_rate == 0 {
+        bail!(ErrorKind::NotSupported, "sample rate must be positive");
+    }
+
+    if settings.format == ExportFormat::Flac && settings.sample_format == ExportSampleFormat::F32 {
+        bail!(ErrorKind::NotSupported, "FLAC can't store floating point samples");
+    }
+
+    if let Some(target) = settings.loudness {
+        if !target.integrated.is_finite() || !target.true_peak.is_finite() {
+            bail!(ErrorKind::NotSupported, "loudness target must be finite");
+        }
+
+        if target.true_peak > 0.0 {
+            bail!(
+                ErrorKind::NotSupported,
+                "true peak ceiling can't be above 0 dBTP"
+            );
+        }
+    }
+
+    Ok(())
+}
+
+pub fn process_render(channels: &mut [Vec<f32>], settings: &ExportSettings) {
+    if let Some(target) = settings.loudness {
+        loudness::normalize(channels, settings.sample_rate, target);
+    }
+}
+
+/// Converts a processed render to the integer sample format of the settings, dithering if they
+/// ask for it. `None` if the format is floating point.
+pub fn quantize_render(channels: &[Vec<f32>], settings: &ExportSettings) -> Option<Vec<Vec<i32>>> {
+    let bits = settings.sample_format.integer_bits()?;
+
+    let dither = match (settings.dither, settings.noise_shaping) {
+        (false, _) => Dither::Off,
+        (true, false) => Dither::Tpdf,
+        (true, true) => Dither::ShapedTpdf,
+    };
+
+    Some(Quantizer::new(bits, channels.len(), dither).quantize_all(channels))
+}
+
+pub fn list_user_presets(dir: &Utf8Path) -> Result<Vec<String>> {
+    let dir = dir.join(SUBDIR);
+
+    let entries = match fs::read_dir(&dir) {
+        Ok(v) => v,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e).with_context(|| format!("failed to read `{dir}`")),
+    };
+
+    let mut names = Vec::new();
+
+    for entry in entries {
+        let path = entry?.path();
+        let Some(path) = Utf8Path::from_path(&path) else {
+            continue;
+        };
+
+        if path.extension() != Some(EXTENSION) {
+            continue;
+        }
+
+        if let Some(name) = path.file_stem() {
+            if validate_name("export preset", name).is_ok() {
+                names.push(name.to_owned());
+            }
+        }
+    }
+
+    names.sort();
+
+    Ok(names)
+}
+
+pub fn read_user_preset(dir: &Utf8Path, name: &str) -> Result<ExportPreset> {
+    let path = preset_path(dir, name);
+
+    let file = File::open(&path).with_context(|| format!("failed to open `{path}`"))?;
+    let raw = serde_json::from_reader::<_, PresetFile>(BufReader::new(file))
+        .convert_err(ErrorKind::Deserialization)?;
+
+    Version::from_u32(raw.version)?;
+
+    Ok(ExportPreset {
+        name: name.to_owned(),
+        ..raw.preset
+    })
+}
+
+pub fn write_user_preset(dir: &Utf8Path, preset: &ExportPreset) -> Result<()> {
+    let path = preset_path(dir, &preset.name);
+    let subdir = dir.join(SUBDIR);
+
+    fs::create_dir_all(&subdir).with_context(|| format!("failed to create `{subdir}`"))?;
+
+    let raw = PresetFile {
+        version: Version::LATEST.as_u32(),
+        preset: preset.clone(),
+    };
+
+    // same as with node presets, a crash never leaves a truncated file behind
+    let tmp_path = subdir.join(format!(".{}.{EXTENSION}.tmp", preset.name));
+
+    let mut writer = BufWriter::new(
+        File::create(&tmp_path).with_context(|| format!("failed to create `{tmp_path}`"))?,
+    );
+    serde_json::to_writer_pretty(&mut writer, &raw).convert_err(ErrorKind::Serialization)?;
+    writer.flush()?;
+    drop(writer);
+
+    fs::rename(&tmp_path, &path).with_context(|| format!("failed to write `{path}`"))?;
+
+    Ok(())
+}
+
+pub fn remove_user_preset(dir: &Utf8Path, name: &str) -> Result<()> {
+    let path = preset_path(dir, name);
+    fs::remove_file(&path).with_context(|| format!("failed to remove `{path}`"))?;
+    Ok(())
+}
+
+fn preset_path(dir: &Utf8Path, name: &str) -> Utf8PathBuf {
+    dir.join(SUBDIR).join(format!("{name}.{EXTENSION}"))
+}
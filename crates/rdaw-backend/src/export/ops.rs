@@ -0,0 +1,88 @@
+use rdaw_api::export::{ExportOperations, ExportPreset, ExportRequest, ExportResponse};
+use rdaw_api::{bail, BackendProtocol, ErrorKind, Result};
+use tracing::instrument;
+
+use crate::node::presets::validate_name;
+use crate::Backend;
+
+#[rdaw_rpc::handler(protocol = BackendProtocol, operations = ExportOperations)]
+impl Backend {
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn list_export_presets(&self) -> Result<Vec<ExportPreset>> {
+        let mut list = ExportPreset::built_in();
+
+        let Some(dir) = &self.user_preset_dir else {
+            return Ok(list);
+        };
+
+        for name in super::list_user_presets(dir)? {
+            if is_built_in(&name) {
+                continue;
+            }
+
+            // one broken file shouldn't hide the rest
+            match super::read_user_preset(dir, &name) {
+                Ok(preset) => list.push(preset),
+                Err(error) => tracing::warn!(?name, ?error, "failed to read export preset"),
+            }
+        }
+
+        Ok(list)
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn get_export_preset(&self, name: String) -> Result<ExportPreset> {
+        validate_name("export preset", &name)?;
+
+        if let Some(preset) = ExportPreset::built_in()
+            .into_iter()
+            .find(|v| v.name == name)
+        {
+            return Ok(preset);
+        }
+
+        let dir = self.user_preset_dir_or_err()?;
+        super::read_user_preset(dir, &name)
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn save_export_preset(&mut self, preset: ExportPreset) -> Result<()> {
+        validate_name("export preset", &preset.name)?;
+
+        if is_built_in(&preset.name) {
+            bail!(
+                ErrorKind::NotSupported,
+                "built-in export preset `{}` can't be changed",
+                preset.name,
+            );
+        }
+
+        super::validate_settings(&preset.settings)?;
+
+        let dir = self.user_preset_dir_or_err()?;
+        super::write_user_preset(dir, &preset)
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn remove_export_preset(&mut self, name: String) -> Result<()> {
+        validate_name("export preset", &name)?;
+
+        if is_built_in(&name) {
+            bail!(
+                ErrorKind::NotSupported,
+                "built-in export preset `{name}` can't be removed"
+            );
+        }
+
+        let dir = self.user_preset_dir_or_err()?;
+        super::remove_user_preset(dir, &name)
+    }
+}
+
+fn is_built_in(name: &str) -> bool {
+    ExportPreset::built_in().iter().any(|v| v.name == name)
+}
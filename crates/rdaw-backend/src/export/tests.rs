@@ -0,0 +1,129 @@
+use rdaw_api::export::{
+    ExportFormat, ExportOperations, ExportPreset, ExportSampleFormat, ExportSettings,
+    LoudnessTarget,
+};
+use rdaw_api::node::NodeOperations;
+use rdaw_api::{assert_err, ErrorKind, Result};
+use rdaw_audio::loudness::integrated_loudness;
+use rdaw_core::path::Utf8Path;
+
+use super::{process_render, quantize_render};
+use crate::tests::run_test;
+
+fn radio() -> ExportPreset {
+    ExportPreset {
+        name: "Radio".into(),
+        settings: ExportSettings {
+            format: ExportFormat::Flac,
+            sample_rate: 48000,
+            sample_format: ExportSampleFormat::I24,
+            loudness: Some(LoudnessTarget {
+                integrated: -23.0,
+                true_peak: -2.0,
+            }),
+            dither: true,
+            noise_shaping: false,
+        },
+    }
+}
+
+#[test]
+fn presets() -> Result<()> {
+    run_test(|client| async move {
+        let built_in = ExportPreset::built_in();
+        assert_eq!(client.list_export_presets().await?, built_in);
+        assert_eq!(
+            client.get_export_preset("Podcast".into()).await?,
+            built_in[1]
+        );
+
+        assert_err!(
+            client.save_export_preset(radio()).await,
+            ErrorKind::NotFound
+        );
+
+        let dir = tempfile::tempdir()?;
+        let dir = Utf8Path::from_path(dir.path()).unwrap();
+        client.set_user_preset_dir(Some(dir.to_owned())).await?;
+
+        client.save_export_preset(radio()).await?;
+        assert!(dir.join("export").join("Radio.json").exists());
+        assert_eq!(client.get_export_preset("Radio".into()).await?, radio());
+
+        // a broken file doesn't hide the other presets
+        std::fs::write(dir.join("export").join("Broken.json"), "{")?;
+
+        let list = client.list_export_presets().await?;
+        assert_eq!(list.len(), built_in.len() + 1);
+        assert_eq!(list.last(), Some(&radio()));
+
+        let mut podcast = radio();
+        podcast.name = "Podcast".into();
+        assert_err!(
+            client.save_export_preset(podcast).await,
+            ErrorKind::NotSupported
+        );
+        assert_err!(
+            client.remove_export_preset("Podcast".into()).await,
+            ErrorKind::NotSupported
+        );
+
+        let mut loud = radio();
+        loud.settings.loudness = Some(LoudnessTarget {
+            integrated: -9.0,
+            true_peak: 1.0,
+        });
+        assert_err!(
+            client.save_export_preset(loud).await,
+            ErrorKind::NotSupported
+        );
+
+        let mut float = radio();
+        float.settings.sample_format = ExportSampleFormat::F32;
+        assert_err!(
+            client.save_export_preset(float).await,
+            ErrorKind::NotSupported
+        );
+
+        client.remove_export_preset("Radio".into()).await?;
+        assert_eq!(client.list_export_presets().await?, built_in);
+
+        Ok(())
+    })
+}
+
+#[test]
+fn render_normalization() {
+    let settings = radio().settings;
+
+    let tone = (0..96000)
+        .map(|i| 0.01 * (i as f32 * 0.1).sin())
+        .collect::<Vec<_>>();
+    let mut channels = vec![tone.clone(), tone];
+
+    process_render(&mut channels, &settings);
+
+    let loudness = integrated_loudness(&channels, settings.sample_rate).unwrap();
+    assert!((loudness + 23.0).abs() < 0.1);
+}
+
+#[test]
+fn render_quantization() {
+    let mut settings = radio().settings;
+    let channels = vec![vec![0.5, -1.0], vec![1.0, 0.0]];
+
+    settings.sample_format = ExportSampleFormat::F32;
+    assert_eq!(quantize_render(&channels, &settings), None);
+
+    settings.sample_format = ExportSampleFormat::I16;
+    settings.dither = false;
+    assert_eq!(
+        quantize_render(&channels, &settings),
+        Some(vec![vec![16384, -32768], vec![32767, 0]])
+    );
+
+    settings.dither = true;
+    settings.noise_shaping = true;
+    let dithered = quantize_render(&channels, &settings).unwrap();
+    assert!((dithered[0][0] - 16384).abs() <= 2);
+}
@@ -0,0 +1,96 @@
+use std::io::Write;
+
+use rdaw_api::arrangement::ArrangementId;
+use rdaw_api::asset::AssetId;
+use rdaw_api::automation::AutomationLaneId;
+use rdaw_api::bus::BusId;
+use rdaw_api::document::DocumentId;
+use rdaw_api::item::{AudioItemId, MidiItemId};
+use rdaw_api::node::NodeId;
+use rdaw_api::source::{AudioSourceId, MidiSourceId};
+use rdaw_api::tempo_map::TempoMapId;
+use rdaw_api::track::TrackId;
+use rdaw_api::Result;
+use rdaw_core::Uuid;
+
+use crate::asset::PathVariables;
+use crate::document::{Compression, Document, DocumentStorage};
+use crate::object::{DeserializationContext, Hub, ObjectId, ObjectType, StorageRef};
+
+pub const OBJECT_TYPES: [ObjectType; 11] = [
+    ObjectType::Arrangement,
+    ObjectType::Asset,
+    ObjectType::AudioItem,
+    ObjectType::AudioSource,
+    ObjectType::AutomationLane,
+    ObjectType::Bus,
+    ObjectType::MidiItem,
+    ObjectType::MidiSource,
+    ObjectType::Node,
+    ObjectType::TempoMap,
+    ObjectType::Track,
+];
+
+#[derive(Debug)]
+pub struct ObjectFuzzer {
+    hub: Hub,
+    documents: DocumentStorage,
+    path_variables: PathVariables,
+    document_id: DocumentId,
+}
+
+impl ObjectFuzzer {
+    pub fn new() -> Result<ObjectFuzzer> {
+        let mut documents = DocumentStorage::default();
+        let document_id = documents.insert(Document::new()?);
+
+        Ok(ObjectFuzzer {
+            hub: Hub::default(),
+            documents,
+            path_variables: PathVariables::new(),
+            document_id,
+        })
+    }
+
+    pub fn deserialize(&mut self, ty: ObjectType, data: &[u8]) -> Result<()> {
+        self.hub = Hub::default();
+
+        let uuid = Uuid::new_v4();
+        let document = &self.documents[self.document_id];
+
+        let mut blob = document.create_blob(Compression::None)?;
+        blob.write_all(data)?;
+        let hash = blob.save()?;
+
+        document.write_object(uuid, hash)?;
+
+        match ty {
+            ObjectType::Arrangement => self.deserialize_obj::<ArrangementId>(uuid),
+            ObjectType::Asset => self.deserialize_obj::<AssetId>(uuid),
+            ObjectType::AudioItem => self.deserialize_obj::<AudioItemId>(uuid),
+            ObjectType::AudioSource => self.deserialize_obj::<AudioSourceId>(uuid),
+            ObjectType::AutomationLane => self.deserialize_obj::<AutomationLaneId>(uuid),
+            ObjectType::Bus => self.deserialize_obj::<BusId>(uuid),
+            ObjectType::MidiItem => self.deserialize_obj::<MidiItemId>(uuid),
+            ObjectType::MidiSource => self.deserialize_obj::<MidiSourceId>(uuid),
+            ObjectType::Node => self.deserialize_obj::<NodeId>(uuid),
+            ObjectType::TempoMap => self.deserialize_obj::<TempoMapId>(uuid),
+            ObjectType::Track => self.deserialize_obj::<TrackId>(uuid),
+        }
+    }
+
+    fn deserialize_obj<I: ObjectId>(&mut self, uuid: Uuid) -> Result<()>
+    where
+        I::Object: StorageRef,
+    {
+        DeserializationContext::deserialize::<I>(
+            &mut self.hub,
+            &self.documents,
+            &self.path_variables,
+            self.document_id,
+            uuid,
+        )?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,88 @@
+mod ops;
+#[cfg(test)]
+mod tests;
+
+use rdaw_api::grid::{GridLine, GridLineKind, SnapMode, BEATS_PER_BAR};
+use rdaw_api::time::{BeatTime, Time};
+use rdaw_core::time::RealTime;
+
+use crate::engine::{frames_to_time, time_to_frames, SAMPLE_RATE};
+use crate::tempo_map::TempoMap;
+
+/// Moves `time` to the closest multiple of the mode's step, keeping its time base.
+pub fn snap_time(tempo_map: &TempoMap, time: Time, mode: SnapMode) -> Time {
+    let snapped = match mode {
+        SnapMode::Bar => snap_beats(tempo_map, time, f64::from(BEATS_PER_BAR)),
+        SnapMode::Beat => snap_beats(tempo_map, time, 1.0),
+        SnapMode::QuarterBeat => snap_beats(tempo_map, time, 0.25),
+        SnapMode::Second => {
+            let secs = tempo_map.to_real(time).as_secs_f64().round();
+            Time::Real(RealTime::from_secs(secs as i64))
+        }
+        SnapMode::Sample => {
+            let half_frame = frames_to_time(1, SAMPLE_RATE).as_nanos() / 2;
+            let real = tempo_map.to_real(time) + RealTime::from_nanos(half_frame);
+            let frames = time_to_frames(real, SAMPLE_RATE);
+            Time::Real(frames_to_time(frames, SAMPLE_RATE))
+        }
+    };
+
+    tempo_map.convert(snapped, time.time_base())
+}
+
+fn snap_beats(tempo_map: &TempoMap, time: Time, step: f64) -> Time {
+    let beats = tempo_map.to_beat(time).as_beats_f64();
+    Time::Beat(BeatTime::from_beats_f64((beats / step).round() * step))
+}
+
+/// Lines of the finest grid which has at most `max_lines` lines from `start` up to `end`.
+pub fn grid_lines(tempo_map: &TempoMap, start: Time, end: Time, max_lines: usize) -> Vec<GridLine> {
+    let start = tempo_map.to_beat(start).as_beats_f64();
+    let end = tempo_map.to_beat(end).as_beats_f64();
+
+    if end <= start || max_lines == 0 {
+        return Vec::new();
+    }
+
+    // steps are counted in quarter beats, so that lines can be told apart exactly
+    let bar = i64::from(BEATS_PER_BAR) * 4;
+    let mut step = 1;
+
+    let (first, last) = loop {
+        let step_beats = step as f64 / 4.0;
+        let first = (start / step_beats).ceil() as i64;
+        // the end isn't part of the range
+        let last = (end / step_beats).ceil() as i64 - 1;
+
+        if last - first < max_lines as i64 {
+            break (first, last);
+        }
+
+        step = match step {
+            1 => 4,
+            4 => bar,
+            _ => step * 2,
+        };
+    };
+
+    (first..=last)
+        .map(|i| {
+            let quarters = i * step;
+            let beat = BeatTime::from_beats_f64(quarters as f64 / 4.0);
+
+            let kind = if quarters.rem_euclid(bar) == 0 {
+                GridLineKind::Bar
+            } else if quarters.rem_euclid(4) == 0 {
+                GridLineKind::Beat
+            } else {
+                GridLineKind::Subdivision
+            };
+
+            GridLine {
+                beat,
+                real: tempo_map.beat_to_real(beat),
+                kind,
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,37 @@
+use rdaw_api::grid::{GridLine, GridOperations, GridRequest, GridResponse, SnapMode};
+use rdaw_api::time::Time;
+use rdaw_api::track::TrackViewId;
+use rdaw_api::{BackendProtocol, Result};
+use tracing::instrument;
+
+use crate::tempo_map::TempoMap;
+use crate::Backend;
+
+#[rdaw_rpc::handler(protocol = BackendProtocol, operations = GridOperations)]
+impl Backend {
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn snap_time(&self, view_id: TrackViewId, time: Time, mode: SnapMode) -> Result<Time> {
+        let tempo_map = self.view_tempo_map(view_id)?;
+        Ok(super::snap_time(tempo_map, time, mode))
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn get_grid_lines(
+        &self,
+        view_id: TrackViewId,
+        start: Time,
+        end: Time,
+        max_lines: usize,
+    ) -> Result<Vec<GridLine>> {
+        let tempo_map = self.view_tempo_map(view_id)?;
+        Ok(super::grid_lines(tempo_map, start, end, max_lines))
+    }
+
+    fn view_tempo_map(&self, view_id: TrackViewId) -> Result<&TempoMap> {
+        self.hub.tracks.ensure_has(view_id.track_id)?;
+        let arrangement = self.hub.arrangements.get_or_err(view_id.arrangement_id)?;
+        self.hub.tempo_maps.get_or_err(arrangement.tempo_map_id)
+    }
+}
@@ -0,0 +1,120 @@
+use rdaw_api::arrangement::ArrangementOperations;
+use rdaw_api::document::DocumentOperations;
+use rdaw_api::grid::{GridLineKind, GridOperations, SnapMode};
+use rdaw_api::time::{BeatTime, Time};
+use rdaw_api::track::TrackViewId;
+use rdaw_api::{assert_err, ErrorKind, Result};
+use rdaw_core::time::RealTime;
+
+use crate::tests::{invalid_track_id, run_test, TestClient};
+
+async fn main_view(client: &TestClient) -> Result<TrackViewId> {
+    let document_id = client.create_document().await?;
+    let arrangement_id = client.get_document_arrangement(document_id).await?;
+    let track_id = client.get_arrangement_main_track(arrangement_id).await?;
+
+    Ok(TrackViewId {
+        track_id,
+        arrangement_id,
+    })
+}
+
+fn beat(beats: f64) -> Time {
+    Time::Beat(BeatTime::from_beats_f64(beats))
+}
+
+fn real(secs: f64) -> Time {
+    Time::Real(RealTime::from_secs_f64(secs))
+}
+
+#[test]
+fn snap_time() -> Result<()> {
+    run_test(|client| async move {
+        // at 120 BPM, so a beat takes half a second
+        let view_id = main_view(&client).await?;
+
+        let cases = [
+            (beat(5.9), SnapMode::Bar, beat(4.0)),
+            (beat(6.1), SnapMode::Bar, beat(8.0)),
+            (real(0.7), SnapMode::Beat, real(0.5)),
+            (beat(1.13), SnapMode::QuarterBeat, beat(1.25)),
+            (real(1.6), SnapMode::Second, real(2.0)),
+            (beat(3.1), SnapMode::Second, beat(4.0)),
+        ];
+
+        for (time, mode, expected) in cases {
+            assert_eq!(client.snap_time(view_id, time, mode).await?, expected);
+        }
+
+        // snapping to samples is stable
+        let sample = client
+            .snap_time(
+                view_id,
+                Time::Real(RealTime::from_nanos(30_000)),
+                SnapMode::Sample,
+            )
+            .await?;
+        assert_eq!(sample, Time::Real(RealTime::from_nanos(20_833)));
+        assert_eq!(
+            client.snap_time(view_id, sample, SnapMode::Sample).await?,
+            sample
+        );
+
+        let invalid_view_id = TrackViewId {
+            track_id: invalid_track_id(),
+            ..view_id
+        };
+        assert_err!(
+            client
+                .snap_time(invalid_view_id, beat(1.0), SnapMode::Beat)
+                .await,
+            ErrorKind::InvalidId
+        );
+
+        Ok(())
+    })
+}
+
+#[test]
+fn grid_lines() -> Result<()> {
+    run_test(|client| async move {
+        let view_id = main_view(&client).await?;
+
+        let lines = client
+            .get_grid_lines(view_id, beat(0.0), beat(8.0), 100)
+            .await?;
+        assert_eq!(lines.len(), 32);
+        assert_eq!(lines[1].beat, BeatTime::from_beats_f64(0.25));
+        assert_eq!(lines[4].real, RealTime::from_secs_f64(0.5));
+
+        let count = |kind| lines.iter().filter(|v| v.kind == kind).count();
+        assert_eq!(count(GridLineKind::Bar), 2);
+        assert_eq!(count(GridLineKind::Beat), 6);
+        assert_eq!(count(GridLineKind::Subdivision), 24);
+
+        // coarser grids as the range gets too dense
+        let lines = client
+            .get_grid_lines(view_id, beat(0.0), beat(8.0), 8)
+            .await?;
+        assert_eq!(lines.len(), 8);
+        assert!(lines.iter().all(|v| v.kind >= GridLineKind::Beat));
+
+        let lines = client
+            .get_grid_lines(view_id, real(0.0), real(4.0), 2)
+            .await?;
+        assert_eq!(lines.len(), 2);
+        assert!(lines.iter().all(|v| v.kind == GridLineKind::Bar));
+
+        let lines = client
+            .get_grid_lines(view_id, beat(0.0), beat(8.0), 1)
+            .await?;
+        assert_eq!(lines.len(), 1);
+
+        assert!(client
+            .get_grid_lines(view_id, beat(8.0), beat(0.0), 100)
+            .await?
+            .is_empty());
+
+        Ok(())
+    })
+}
@@ -0,0 +1,160 @@
+//! Simple JSON session interchange format.
+//!
+//! ```json
+//! {
+//!   "version": 1,
+//!   "name": "Session",
+//!   "tempo": 120.0,
+//!   "sources": [
+//!     { "id": "kick", "path": "audio/kick.wav", "sample_rate": 48000, "channels": 2, "duration": 1.5 }
+//!   ],
+//!   "tracks": [
+//!     {
+//!       "name": "Drums",
+//!       "clips": [{ "source": "kick", "start": 0.0, "duration": 1.5, "lane": 0 }],
+//!       "tracks": []
+//!     }
+//!   ]
+//! }
+//! ```
+//!
+//! Times are in seconds. Source paths are relative to the session file. `name`, `tempo`, `lane`
+//! and nested `tracks` are optional.
+
+mod ops;
+#[cfg(test)]
+mod tests;
+
+use std::fs::File;
+use std::io::BufReader;
+
+use rdaw_api::audio::AudioChannel;
+use rdaw_api::error::ResultExt;
+use rdaw_api::{bail, ErrorKind, Result};
+use rdaw_core::collections::HashSet;
+use rdaw_core::path::{Utf8Path, Utf8PathBuf};
+use serde::Deserialize;
+
+use crate::define_version_enum;
+
+/// More channels than any real source has, so that a broken session can't allocate too much.
+const MAX_CHANNELS: u32 = 64;
+
+define_version_enum! {
+    enum Version {
+        V1 = 1,
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Session {
+    pub version: u32,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub tempo: Option<f32>,
+    #[serde(default)]
+    pub sources: Vec<SessionSource>,
+    #[serde(default)]
+    pub tracks: Vec<SessionTrack>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SessionSource {
+    pub id: String,
+    pub path: Utf8PathBuf,
+    pub sample_rate: u32,
+    pub channels: u32,
+    pub duration: f64,
+}
+
+impl SessionSource {
+    pub fn audio_channels(&self) -> Vec<AudioChannel> {
+        match self.channels {
+            1 => vec![AudioChannel::Mono],
+            2 => vec![AudioChannel::FrontLeft, AudioChannel::FrontRight],
+            n => vec![AudioChannel::Unknown; n as usize],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SessionTrack {
+    pub name: String,
+    #[serde(default)]
+    pub clips: Vec<SessionClip>,
+    #[serde(default)]
+    pub tracks: Vec<SessionTrack>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SessionClip {
+    pub source: String,
+    pub start: f64,
+    pub duration: f64,
+    #[serde(default)]
+    pub lane: u32,
+}
+
+pub fn read(path: &Utf8Path) -> Result<Session> {
+    let file = File::open(path).with_context(|| format!("failed to open `{path}`"))?;
+    let mut session = serde_json::from_reader::<_, Session>(BufReader::new(file))
+        .convert_err(ErrorKind::Deserialization)?;
+
+    Version::from_u32(session.version)?;
+
+    if let Some(tempo) = session.tempo {
+        if !tempo.is_finite() || tempo <= 0.0 {
+            bail!(ErrorKind::Deserialization, "invalid tempo {tempo}");
+        }
+    }
+
+    let base_dir = path.parent().unwrap_or(Utf8Path::new(""));
+    let mut source_ids = HashSet::default();
+
+    for source in &mut session.sources {
+        if !source_ids.insert(source.id.clone()) {
+            bail!(ErrorKind::Deserialization, "duplicate source `{}`", source.id);
+        }
+
+        if source.channels == 0
+            || source.channels > MAX_CHANNELS
+            || source.sample_rate == 0
+            || !is_valid_time(source.duration)
+        {
+            bail!(ErrorKind::Deserialization, "invalid source `{}`", source.id);
+        }
+
+        source.path = base_dir.join(&source.path);
+    }
+
+    validate_tracks(&session.tracks, &source_ids)?;
+
+    Ok(session)
+}
+
+fn validate_tracks(tracks: &[SessionTrack], source_ids: &HashSet<String>) -> Result<()> {
+    for track in tracks {
+        for clip in &track.clips {
+            if !source_ids.contains(&clip.source) {
+                bail!(ErrorKind::Deserialization, "unknown source `{}`", clip.source);
+            }
+
+            if !is_valid_time(clip.start) || !is_valid_time(clip.duration) {
+                bail!(
+                    ErrorKind::Deserialization,
+                    "invalid clip position in track `{}`",
+                    track.name,
+                );
+            }
+        }
+
+        validate_tracks(&track.tracks, source_ids)?;
+    }
+
+    Ok(())
+}
+
+fn is_valid_time(secs: f64) -> bool {
+    secs.is_finite() && secs >= 0.0
+}
@@ -0,0 +1,185 @@
+use std::fs::File;
+
+use blake3::Hasher;
+use chrono::Utc;
+use rdaw_api::audio::{AudioMetadata, ResampleQuality, SampleFormat};
+use rdaw_api::document::DocumentId;
+use rdaw_api::error::ResultExt;
+use rdaw_api::interchange::{InterchangeOperations, InterchangeRequest, InterchangeResponse};
+use rdaw_api::item::ItemId;
+use rdaw_api::source::AudioSourceId;
+use rdaw_api::stats::TaskQueue;
+use rdaw_api::time::Time;
+use rdaw_api::track::{TrackId, TrackItem};
+use rdaw_api::{BackendProtocol, Error, Result};
+use rdaw_core::collections::HashMap;
+use rdaw_core::path::Utf8PathBuf;
+use rdaw_core::time::RealTime;
+use rdaw_rpc::{CancellationToken, Responder};
+use tracing::instrument;
+
+use super::{Session, SessionTrack};
+use crate::asset::{Asset, ExternalAsset};
+use crate::document::DocumentRevision;
+use crate::item::AudioItem;
+use crate::object::ObjectKey;
+use crate::source::AudioSource;
+use crate::tempo_map::TempoMap;
+use crate::{ensure_not_cancelled, Backend};
+
+#[rdaw_rpc::handler(protocol = BackendProtocol, operations = InterchangeOperations)]
+impl Backend {
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn import_session(
+        &mut self,
+        responder: impl Responder<DocumentId, Error>,
+        cancel: CancellationToken,
+        path: Utf8PathBuf,
+    ) -> Result<()> {
+        let session = super::read(&path)?;
+
+        let queue = self.queue.clone();
+        self.spawn(TaskQueue::Io, async move {
+            let assets = session
+                .sources
+                .iter()
+                .map(|source| {
+                    ensure_not_cancelled(&cancel)?;
+                    hash_external_asset(source.path.clone())
+                })
+                .collect::<Result<Vec<_>>>();
+
+            queue.defer(move |this: &mut Backend| {
+                let res = assets.and_then(|assets| this.build_session(session, assets));
+                responder.respond(res)
+            });
+
+            Ok(())
+        });
+
+        Ok(())
+    }
+
+    fn build_session(&mut self, session: Session, assets: Vec<Asset>) -> Result<DocumentId> {
+        let document_id = self.create_document()?;
+
+        let res = self.fill_session(document_id, session, assets);
+        if res.is_err() {
+            self.discard_document(document_id);
+        }
+
+        res.map(|_| document_id)
+    }
+
+    fn fill_session(
+        &mut self,
+        document_id: DocumentId,
+        session: Session,
+        assets: Vec<Asset>,
+    ) -> Result<()> {
+        let arrangement_id = self.get_document_arrangement(document_id)?;
+        let main_track_id = self.get_arrangement_main_track(arrangement_id)?;
+
+        if let Some(name) = session.name {
+            self.set_arrangement_name(arrangement_id, name)?;
+        }
+
+        if let Some(tempo) = session.tempo {
+            let tempo_map_id = self.hub.arrangements[arrangement_id].tempo_map_id;
+            self.hub.tempo_maps[tempo_map_id] = TempoMap::new(tempo);
+        }
+
+        let mut sources = HashMap::default();
+
+        for (source, asset) in session.sources.iter().zip(assets) {
+            let asset_id = self
+                .hub
+                .assets
+                .insert(ObjectKey::new_random(document_id), asset);
+
+            let metadata = AudioMetadata {
+                channels: source.audio_channels(),
+                sample_rate: source.sample_rate,
+                sample_format: SampleFormat::Other,
+                duration: RealTime::from_secs_f64(source.duration),
+            };
+
+            let source_id = self.hub.audio_sources.insert(
+                ObjectKey::new_random(document_id),
+                AudioSource {
+                    asset_id,
+                    metadata,
+                    resample_quality: ResampleQuality::default(),
+                },
+            );
+
+            sources.insert(source.id.as_str(), source_id);
+        }
+
+        for track in &session.tracks {
+            self.build_session_track(document_id, main_track_id, track, &sources)?;
+        }
+
+        let arrangement_uuid = self.hub.arrangements.get_key_or_err(arrangement_id)?.uuid;
+        let document = &self.documents[document_id];
+        document.save(DocumentRevision {
+            created_at: Utc::now(),
+            time_spent_secs: 0,
+            arrangement_uuid,
+        })?;
+
+        Ok(())
+    }
+
+    fn build_session_track(
+        &mut self,
+        document_id: DocumentId,
+        parent_id: TrackId,
+        track: &SessionTrack,
+        sources: &HashMap<&str, AudioSourceId>,
+    ) -> Result<()> {
+        let track_id = self.create_track(document_id)?;
+        self.set_track_name(track_id, track.name.clone())?;
+
+        for clip in &track.clips {
+            let item_id = self.hub.audio_items.insert(
+                ObjectKey::new_random(document_id),
+                AudioItem::new(sources[clip.source.as_str()]),
+            );
+
+            self.add_track_item(
+                track_id,
+                TrackItem {
+                    inner: ItemId::Audio(item_id),
+                    start: Time::Real(RealTime::from_secs_f64(clip.start)),
+                    duration: Time::Real(RealTime::from_secs_f64(clip.duration)),
+                    lane: clip.lane,
+                    locked: false,
+                },
+            )?;
+        }
+
+        self.append_track_child(parent_id, track_id)?;
+
+        for child in &track.tracks {
+            self.build_session_track(document_id, track_id, child, sources)?;
+        }
+
+        Ok(())
+    }
+}
+
+fn hash_external_asset(path: Utf8PathBuf) -> Result<Asset> {
+    let file = File::open(&path).with_context(|| format!("failed to open `{path}`"))?;
+
+    let mut hasher = Hasher::new();
+    hasher
+        .update_reader(file)
+        .with_context(|| format!("failed to read `{path}`"))?;
+
+    let hash = hasher.finalize();
+    let size = hasher.count();
+
+    Ok(Asset::External(ExternalAsset { path, hash, size }))
+}
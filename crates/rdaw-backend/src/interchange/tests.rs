@@ -0,0 +1,136 @@
+use std::fs;
+
+use rdaw_api::arrangement::ArrangementOperations;
+use rdaw_api::document::DocumentOperations;
+use rdaw_api::interchange::InterchangeOperations;
+use rdaw_api::time::Time;
+use rdaw_api::track::{TrackOperations, TrackViewId};
+use rdaw_api::{assert_err, ErrorKind, Result};
+use rdaw_core::path::Utf8Path;
+use rdaw_core::time::RealTime;
+use tempfile::TempDir;
+
+use crate::tests::run_test;
+
+fn write_session(dir: &TempDir, session: &str) -> Result<&Utf8Path> {
+    let dir = Utf8Path::from_path(dir.path()).unwrap();
+    fs::write(dir.join("kick.wav"), [1, 2, 3])?;
+    fs::write(dir.join("session.json"), session)?;
+    Ok(dir)
+}
+
+#[test]
+fn import_session() -> Result<()> {
+    run_test(|client| async move {
+        let dir = tempfile::tempdir()?;
+        let dir_path = write_session(
+            &dir,
+            r#"{
+                "version": 1,
+                "name": "Session",
+                "sources": [
+                    { "id": "kick", "path": "kick.wav", "sample_rate": 48000, "channels": 2, "duration": 1.5 }
+                ],
+                "tracks": [
+                    {
+                        "name": "Drums",
+                        "clips": [
+                            { "source": "kick", "start": 0.0, "duration": 1.5 },
+                            { "source": "kick", "start": 2.0, "duration": 1.0, "lane": 1 }
+                        ],
+                        "tracks": [{ "name": "Kick" }]
+                    },
+                    { "name": "Bass" }
+                ]
+            }"#,
+        )?;
+
+        let document_id = client
+            .import_session(dir_path.join("session.json"))
+            .await?;
+        let arrangement_id = client.get_document_arrangement(document_id).await?;
+        assert_eq!(client.get_arrangement_name(arrangement_id).await?, "Session");
+
+        let main_track_id = client.get_arrangement_main_track(arrangement_id).await?;
+        let children = client.get_track_children(main_track_id).await?;
+        assert_eq!(children.len(), 2);
+        assert_eq!(client.get_track_name(children[0]).await?, "Drums");
+        assert_eq!(client.get_track_name(children[1]).await?, "Bass");
+
+        let drums_children = client.get_track_children(children[0]).await?;
+        assert_eq!(drums_children.len(), 1);
+        assert_eq!(client.get_track_name(drums_children[0]).await?, "Kick");
+
+        let view_id = TrackViewId {
+            track_id: children[0],
+            arrangement_id,
+        };
+        let items = client.get_track_view_range(view_id, None, None).await?;
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].1.start, Time::Real(RealTime::from_secs_f64(0.0)));
+        assert_eq!(items[0].1.lane, 0);
+        assert_eq!(items[1].1.start, Time::Real(RealTime::from_secs_f64(2.0)));
+        assert_eq!(items[1].1.lane, 1);
+        assert_eq!(client.get_track_view_lane_count(view_id).await?, 2);
+
+        Ok(())
+    })
+}
+
+#[test]
+fn import_session_unknown_source() -> Result<()> {
+    run_test(|client| async move {
+        let dir = tempfile::tempdir()?;
+        let dir_path = write_session(
+            &dir,
+            r#"{
+                "version": 1,
+                "tracks": [{ "name": "Drums", "clips": [{ "source": "snare", "start": 0.0, "duration": 1.0 }] }]
+            }"#,
+        )?;
+
+        let res = client.import_session(dir_path.join("session.json")).await;
+        assert_err!(res, ErrorKind::Deserialization);
+
+        Ok(())
+    })
+}
+
+#[test]
+fn import_session_unknown_version() -> Result<()> {
+    run_test(|client| async move {
+        let dir = tempfile::tempdir()?;
+        let dir_path = write_session(&dir, r#"{ "version": 999 }"#)?;
+
+        let res = client.import_session(dir_path.join("session.json")).await;
+        assert_err!(res, ErrorKind::UnknownVersion);
+
+        Ok(())
+    })
+}
+
+#[test]
+fn import_session_out_of_range() -> Result<()> {
+    run_test(|client| async move {
+        let dir = tempfile::tempdir()?;
+        let dir_path = write_session(&dir, r#"{ "version": 1, "tempo": -1.0 }"#)?;
+
+        let res = client.import_session(dir_path.join("session.json")).await;
+        assert_err!(res, ErrorKind::Deserialization);
+
+        let dir_path = write_session(
+            &dir,
+            r#"{
+                "version": 1,
+                "sources": [
+                    { "id": "kick", "path": "kick.wav", "sample_rate": 48000, "channels": 4000000000, "duration": 1.5 }
+                ]
+            }"#,
+        )?;
+
+        let res = client.import_session(dir_path.join("session.json")).await;
+        assert_err!(res, ErrorKind::Deserialization);
+
+        Ok(())
+    })
+}
@@ -0,0 +1,91 @@
+mod ops;
+#[cfg(test)]
+mod tests;
+
+use std::mem;
+
+use rdaw_api::audio::AudioInputStream as _;
+use rdaw_api::item::{AudioClip, AudioItemId, GainEnvelope, GainPoint};
+use rdaw_api::media::{MediaInput as _, OpenMediaInput as _};
+use rdaw_api::source::AudioSourceId;
+use rdaw_api::{bail, ErrorKind, Result};
+use rdaw_audio::transients::TransientDetector;
+use rdaw_core::time::RealTime;
+use rdaw_ffmpeg::MediaInput;
+use rdaw_rpc::CancellationToken;
+
+use crate::asset::AssetReader;
+use crate::ensure_not_cancelled;
+use crate::object::{DeserializationContext, Object, ObjectId, ObjectType, SerializationContext};
+
+impl ObjectId for AudioItemId {
+    type Object = AudioItem;
+}
+
+#[derive(Debug, Clone)]
+pub struct AudioItem {
+    pub source_id: AudioSourceId,
+    pub gain_envelope: GainEnvelope,
+    pub clip: AudioClip,
+}
+
+impl AudioItem {
+    pub fn new(source_id: AudioSourceId) -> AudioItem {
+        AudioItem {
+            source_id,
+            gain_envelope: GainEnvelope::new(),
+            clip: AudioClip::default(),
+        }
+    }
+}
+
+impl Object for AudioItem {
+    type Id = AudioItemId;
+
+    const TYPE: ObjectType = ObjectType::AudioItem;
+
+    fn serialize(&self, _ctx: &mut SerializationContext<'_>) -> Result<Vec<u8>> {
+        bail!(ErrorKind::NotSupported, "audio item serialization is not implemented");
+    }
+
+    fn deserialize(_ctx: &mut DeserializationContext<'_>, _data: &[u8]) -> Result<Self> {
+        bail!(ErrorKind::NotSupported, "audio item deserialization is not implemented");
+    }
+
+    fn heap_size(&self) -> usize {
+        self.gain_envelope.points.capacity() * mem::size_of::<GainPoint>()
+    }
+}
+
+/// Decodes the whole asset and returns positions of its transients.
+pub fn detect_transients(
+    reader: AssetReader,
+    sensitivity: f32,
+    cancel: &CancellationToken,
+) -> Result<Vec<RealTime>> {
+    let mut media = MediaInput::open(reader)?;
+    let Some(mut stream) = media.get_audio_stream()? else {
+        bail!(ErrorKind::NotFound, "asset doesn't have an audio stream");
+    };
+
+    let num_channels = stream.metadata().channels.len().max(1);
+    let sample_rate = stream.metadata().sample_rate;
+    let mut detector = TransientDetector::new(num_channels, sample_rate, sensitivity);
+
+    loop {
+        ensure_not_cancelled(cancel)?;
+
+        let frame = stream.next_frame()?;
+        if frame.is_empty() {
+            break;
+        }
+
+        detector.push(frame);
+    }
+
+    let onsets = detector.finish().into_iter().map(|frame| {
+        RealTime::from_secs_f64(frame as f64 / f64::from(sample_rate))
+    });
+
+    Ok(onsets.collect())
+}
@@ -0,0 +1,218 @@
+use rdaw_api::item::{
+    AudioClip, AudioItemId, AudioItemOperations, AudioItemRequest, AudioItemResponse, Fade,
+    GainEnvelope, GainPoint, ItemId,
+};
+use rdaw_api::stats::TaskQueue;
+use rdaw_api::track::TrackViewEvent;
+use rdaw_api::{bail, BackendProtocol, Error, ErrorKind, Result};
+use rdaw_core::time::RealTime;
+use rdaw_rpc::{CancellationToken, Responder, StreamId};
+use tracing::instrument;
+
+use super::detect_transients;
+use crate::asset::AssetReader;
+use crate::track::filter_intersects;
+use crate::Backend;
+
+#[rdaw_rpc::handler(protocol = BackendProtocol, operations = AudioItemOperations)]
+impl Backend {
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn subscribe_audio_item_gain_envelope(&mut self, id: AudioItemId) -> Result<StreamId> {
+        self.hub.audio_items.ensure_has(id)?;
+        Ok(self.subscribers.audio_item_gain_envelope.subscribe(id))
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn get_audio_item_gain_envelope(&self, id: AudioItemId) -> Result<GainEnvelope> {
+        let item = self.hub.audio_items.get_or_err(id)?;
+        Ok(item.gain_envelope.clone())
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn set_audio_item_gain_envelope(
+        &mut self,
+        id: AudioItemId,
+        mut envelope: GainEnvelope,
+    ) -> Result<()> {
+        if let Some(point) = envelope.points.iter().find(|v| !v.is_valid()) {
+            bail!(ErrorKind::NotSupported, "invalid gain point {point:?}");
+        }
+
+        envelope.points.sort_by_key(|point| point.position);
+
+        let item = self.hub.audio_items.get_mut_or_err(id)?;
+        item.gain_envelope.clone_from(&envelope);
+        self.subscribers
+            .audio_item_gain_envelope
+            .notify(id, envelope);
+
+        Ok(())
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn add_audio_item_gain_point(
+        &mut self,
+        id: AudioItemId,
+        point: GainPoint,
+    ) -> Result<usize> {
+        if !point.is_valid() {
+            bail!(ErrorKind::NotSupported, "invalid gain point {point:?}");
+        }
+
+        let item = self.hub.audio_items.get_mut_or_err(id)?;
+        let index = item.gain_envelope.insert(point);
+        self.notify_audio_item_gain_envelope(id);
+
+        Ok(index)
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn remove_audio_item_gain_point(&mut self, id: AudioItemId, index: usize) -> Result<()> {
+        let item = self.hub.audio_items.get_mut_or_err(id)?;
+
+        if index >= item.gain_envelope.points.len() {
+            bail!(
+                ErrorKind::IndexOutOfBounds,
+                "index out of bounds passed to remove_audio_item_gain_point",
+            );
+        }
+
+        item.gain_envelope.points.remove(index);
+        self.notify_audio_item_gain_envelope(id);
+
+        Ok(())
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn get_audio_item_clip(&self, id: AudioItemId) -> Result<AudioClip> {
+        let item = self.hub.audio_items.get_or_err(id)?;
+        Ok(item.clip)
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn set_audio_item_source_offset(
+        &mut self,
+        id: AudioItemId,
+        offset: RealTime,
+    ) -> Result<()> {
+        if offset < RealTime::ZERO {
+            bail!(ErrorKind::NotSupported, "source offset can't be negative");
+        }
+
+        self.update_audio_item_clip(id, |clip| clip.source_offset = offset)
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn set_audio_item_gain(&mut self, id: AudioItemId, gain: f32) -> Result<()> {
+        if !gain.is_finite() || gain < 0.0 {
+            bail!(ErrorKind::NotSupported, "invalid gain {gain}");
+        }
+
+        self.update_audio_item_clip(id, |clip| clip.gain = gain)
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn set_audio_item_fade_in(&mut self, id: AudioItemId, fade: Fade) -> Result<()> {
+        if !fade.is_valid() {
+            bail!(ErrorKind::NotSupported, "invalid fade {fade:?}");
+        }
+
+        self.update_audio_item_clip(id, |clip| clip.fade_in = fade)
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn set_audio_item_fade_out(&mut self, id: AudioItemId, fade: Fade) -> Result<()> {
+        if !fade.is_valid() {
+            bail!(ErrorKind::NotSupported, "invalid fade {fade:?}");
+        }
+
+        self.update_audio_item_clip(id, |clip| clip.fade_out = fade)
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn detect_audio_item_transients(
+        &mut self,
+        responder: impl Responder<Vec<RealTime>, Error>,
+        cancel: CancellationToken,
+        id: AudioItemId,
+        sensitivity: f32,
+    ) -> Result<()> {
+        if !(0.0..=1.0).contains(&sensitivity) {
+            bail!(ErrorKind::NotSupported, "invalid sensitivity {sensitivity}");
+        }
+
+        let reader = self.open_audio_item_source(id)?;
+
+        let queue = self.queue.clone();
+        self.spawn(TaskQueue::Analysis, async move {
+            let transients = detect_transients(reader, sensitivity, &cancel);
+            queue.defer(move |_: &mut Backend| responder.respond(transients));
+            Ok(())
+        });
+
+        Ok(())
+    }
+
+    pub(crate) fn open_audio_item_source(&self, id: AudioItemId) -> Result<AssetReader> {
+        let item = self.hub.audio_items.get_or_err(id)?;
+        let source = self.hub.audio_sources.get_or_err(item.source_id)?;
+        self.open_asset(source.asset_id)
+    }
+
+    /// Changes the clip and updates views of every track item playing the audio item.
+    pub(crate) fn update_audio_item_clip(
+        &mut self,
+        id: AudioItemId,
+        update: impl FnOnce(&mut AudioClip),
+    ) -> Result<()> {
+        let item = self.hub.audio_items.get_mut_or_err(id)?;
+        update(&mut item.clip);
+        let clip = item.clip;
+
+        let track_items = self
+            .hub
+            .tracks
+            .iter()
+            .flat_map(|(track_id, _, track)| {
+                track
+                    .items
+                    .iter()
+                    .filter(|(_, v)| v.inner == ItemId::Audio(id))
+                    .map(move |(item_id, _)| (track_id, item_id))
+            })
+            .collect::<Vec<_>>();
+
+        for (track_id, item_id) in track_items {
+            for (view_id, view) in self.track_view_cache.iter_mut(track_id) {
+                let arrangement = &self.hub.arrangements[view_id.arrangement_id];
+                let tempo_map = &self.hub.tempo_maps[arrangement.tempo_map_id];
+                view.set_item_clip(item_id, clip);
+                let item = view.get_item(item_id).copied();
+                let event = TrackViewEvent::ItemClipChanged { id: item_id, clip };
+                self.subscribers.track_view.notify_filtered(view_id, event, |filter| {
+                    filter_intersects(tempo_map, filter, &item)
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    fn notify_audio_item_gain_envelope(&mut self, id: AudioItemId) {
+        let envelope = self.hub.audio_items[id].gain_envelope.clone();
+        self.subscribers
+            .audio_item_gain_envelope
+            .notify(id, envelope);
+    }
+}
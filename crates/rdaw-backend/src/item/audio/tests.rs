@@ -0,0 +1,400 @@
+use std::f32::consts::PI;
+use std::fs;
+
+use futures::StreamExt;
+use rdaw_api::arrangement::ArrangementOperations;
+use rdaw_api::document::DocumentOperations;
+use rdaw_api::interchange::InterchangeOperations;
+use rdaw_api::item::{
+    AudioClip, AudioItemId, AudioItemOperations, Fade, FadeCurve, GainEnvelope, GainPoint, ItemId,
+};
+use rdaw_api::time::Time;
+use rdaw_api::track::{TrackOperations, TrackViewEvent, TrackViewFilter, TrackViewId};
+use rdaw_api::{assert_err, ErrorKind, Result};
+use rdaw_core::path::Utf8Path;
+use rdaw_core::time::RealTime;
+
+use crate::recording::encode_wav;
+use crate::tests::{run_test, TestClient};
+
+async fn import_item(client: &TestClient, dir: &Utf8Path) -> Result<AudioItemId> {
+    let (_, item_id) = import_view_item(client, dir).await?;
+    Ok(item_id)
+}
+
+/// Also returns the view of the track the item is on.
+async fn import_view_item(
+    client: &TestClient,
+    dir: &Utf8Path,
+) -> Result<(TrackViewId, AudioItemId)> {
+    fs::write(dir.join("kick.wav"), [1, 2, 3])?;
+    fs::write(
+        dir.join("session.json"),
+        r#"{
+            "version": 1,
+            "sources": [
+                { "id": "kick", "path": "kick.wav", "sample_rate": 48000, "channels": 1, "duration": 1.0 }
+            ],
+            "tracks": [
+                { "name": "Kick", "clips": [{ "source": "kick", "start": 0.0, "duration": 1.0 }] }
+            ]
+        }"#,
+    )?;
+
+    let document_id = client.import_session(dir.join("session.json")).await?;
+    let arrangement_id = client.get_document_arrangement(document_id).await?;
+    let main_track_id = client.get_arrangement_main_track(arrangement_id).await?;
+    let track_id = client.get_track_children(main_track_id).await?[0];
+
+    let view_id = TrackViewId {
+        track_id,
+        arrangement_id,
+    };
+    let items = client.get_track_view_range(view_id, None, None).await?;
+
+    match items[0].1.inner {
+        ItemId::Audio(id) => Ok((view_id, id)),
+        inner => panic!("unexpected item {inner:?}"),
+    }
+}
+
+fn point(secs: f64, gain: f32) -> GainPoint {
+    GainPoint::new(RealTime::from_secs_f64(secs), gain)
+}
+
+#[test]
+fn gain_envelope() -> Result<()> {
+    run_test(|client| async move {
+        let dir = tempfile::tempdir()?;
+        let dir = Utf8Path::from_path(dir.path()).unwrap();
+        let item_id = import_item(&client, dir).await?;
+
+        assert!(client.get_audio_item_gain_envelope(item_id).await?.is_empty());
+
+        let mut stream = client.subscribe_audio_item_gain_envelope(item_id).await?;
+
+        assert_eq!(client.add_audio_item_gain_point(item_id, point(1.0, 0.0)).await?, 0);
+        assert_eq!(client.add_audio_item_gain_point(item_id, point(0.0, 1.0)).await?, 0);
+        assert_eq!(client.add_audio_item_gain_point(item_id, point(0.5, 0.5)).await?, 1);
+
+        let envelope = client.get_audio_item_gain_envelope(item_id).await?;
+        assert_eq!(envelope.points, [point(0.0, 1.0), point(0.5, 0.5), point(1.0, 0.0)]);
+        assert_eq!(stream.next().await, Some(envelope));
+
+        client.remove_audio_item_gain_point(item_id, 1).await?;
+        assert_err!(
+            client.remove_audio_item_gain_point(item_id, 2).await,
+            ErrorKind::IndexOutOfBounds
+        );
+
+        assert_err!(
+            client
+                .add_audio_item_gain_point(item_id, point(-1.0, 1.0))
+                .await,
+            ErrorKind::NotSupported
+        );
+
+        let envelope = GainEnvelope {
+            points: vec![point(2.0, 0.5), point(1.0, f32::NAN)],
+        };
+        assert_err!(
+            client
+                .set_audio_item_gain_envelope(item_id, envelope)
+                .await,
+            ErrorKind::NotSupported
+        );
+
+        let envelope = GainEnvelope {
+            points: vec![point(2.0, 0.5), point(1.0, 2.0)],
+        };
+        client
+            .set_audio_item_gain_envelope(item_id, envelope)
+            .await?;
+        assert_eq!(
+            client.get_audio_item_gain_envelope(item_id).await?.points,
+            [point(1.0, 2.0), point(2.0, 0.5)]
+        );
+
+        Ok(())
+    })
+}
+
+#[test]
+fn clip() -> Result<()> {
+    run_test(|client| async move {
+        let dir = tempfile::tempdir()?;
+        let dir = Utf8Path::from_path(dir.path()).unwrap();
+        let (view_id, item_id) = import_view_item(&client, dir).await?;
+
+        assert_eq!(client.get_audio_item_clip(item_id).await?, AudioClip::default());
+
+        let mut stream = client
+            .subscribe_track_view(view_id, TrackViewFilter::default())
+            .await?;
+
+        let fade = Fade {
+            duration: RealTime::from_secs_f64(0.25),
+            curve: FadeCurve::EqualPower,
+        };
+
+        client
+            .set_audio_item_source_offset(item_id, RealTime::from_secs_f64(0.5))
+            .await?;
+        client.set_audio_item_gain(item_id, 0.5).await?;
+        client.set_audio_item_fade_in(item_id, fade).await?;
+        client.set_audio_item_fade_out(item_id, fade).await?;
+
+        let clip = AudioClip {
+            source_offset: RealTime::from_secs_f64(0.5),
+            gain: 0.5,
+            fade_in: fade,
+            fade_out: fade,
+        };
+        assert_eq!(client.get_audio_item_clip(item_id).await?, clip);
+
+        // events of the same item may be coalesced, the last one has every change
+        loop {
+            match stream.next().await {
+                Some(TrackViewEvent::ItemClipChanged { clip: v, .. }) if v == clip => break,
+                Some(TrackViewEvent::ItemClipChanged { .. }) => continue,
+                event => panic!("unexpected event {event:?}"),
+            }
+        }
+
+        let items = client.get_track_view_range(view_id, None, None).await?;
+        assert_eq!(items[0].1.clip, Some(clip));
+
+        assert_err!(
+            client.set_audio_item_gain(item_id, f32::NAN).await,
+            ErrorKind::NotSupported
+        );
+        assert_err!(
+            client
+                .set_audio_item_source_offset(item_id, RealTime::from_secs(-1))
+                .await,
+            ErrorKind::NotSupported
+        );
+
+        Ok(())
+    })
+}
+
+#[test]
+fn fades() {
+    let fade = |secs: f64, curve| Fade {
+        duration: RealTime::from_secs_f64(secs),
+        curve,
+    };
+
+    let clip = AudioClip {
+        gain: 0.5,
+        fade_in: fade(1.0, FadeCurve::Linear),
+        fade_out: fade(2.0, FadeCurve::SCurve),
+        ..AudioClip::default()
+    };
+
+    let duration = RealTime::from_secs(4);
+    let gain_at = |secs: f64| clip.gain_at(RealTime::from_secs_f64(secs), duration);
+
+    assert_eq!(gain_at(0.0), 0.0);
+    assert_eq!(gain_at(0.5), 0.25);
+    assert_eq!(gain_at(1.5), 0.5);
+    assert_eq!(gain_at(3.0), 0.25);
+    assert_eq!(gain_at(4.0), 0.0);
+
+    assert!((FadeCurve::EqualPower.gain(1.0) - 1.0).abs() < 1e-6);
+    assert_eq!(FadeCurve::Exponential.gain(0.0), 0.0);
+    assert!((FadeCurve::Exponential.gain(1.0) - 1.0).abs() < 1e-6);
+    assert!(FadeCurve::Exponential.gain(0.5) < 0.5);
+    assert_eq!(fade(0.0, FadeCurve::Linear).gain_at(RealTime::ZERO), 1.0);
+}
+
+#[test]
+fn apply_gain_envelope() {
+    let envelope = GainEnvelope {
+        points: vec![point(0.5, 1.0), point(1.0, 0.0), point(1.0, 0.5)],
+    };
+
+    assert_eq!(envelope.gain_at(RealTime::ZERO), 1.0);
+    assert_eq!(envelope.gain_at(RealTime::from_secs_f64(0.75)), 0.5);
+    assert_eq!(envelope.gain_at(RealTime::from_secs(1)), 0.5);
+    assert_eq!(envelope.gain_at(RealTime::from_secs(10)), 0.5);
+    assert_eq!(GainEnvelope::new().gain_at(RealTime::ZERO), 1.0);
+
+    let mut samples = [1.0; 6];
+    envelope.apply(RealTime::from_secs_f64(0.5), 4, &mut samples);
+    assert_eq!(samples, [1.0, 0.5, 0.5, 0.5, 0.5, 0.5]);
+}
+
+#[test]
+fn slice_gain_envelope() {
+    let envelope = GainEnvelope {
+        points: vec![point(0.0, 0.0), point(1.0, 1.0), point(2.0, 0.0)],
+    };
+
+    let slice = envelope.slice(RealTime::from_secs_f64(0.5), RealTime::from_secs_f64(1.5));
+    assert_eq!(slice.points, [point(0.0, 0.5), point(0.5, 1.0), point(1.0, 0.5)]);
+
+    let empty = GainEnvelope::new();
+    assert!(empty.slice(RealTime::ZERO, RealTime::from_secs(1)).is_empty());
+}
+
+/// Imports a second of audio with hits at 0.1, 0.4 and 0.7 seconds.
+async fn import_hits(client: &TestClient, dir: &Utf8Path) -> Result<TrackViewId> {
+    let mut samples = vec![0.0; 48000];
+    for start in [4800, 19200, 33600] {
+        for (i, sample) in samples[start..].iter_mut().enumerate() {
+            let t = i as f32 / 48000.0;
+            *sample += 0.8 * (-t / 0.02).exp() * (2.0 * PI * 1000.0 * t).sin();
+        }
+    }
+
+    fs::write(dir.join("hits.wav"), encode_wav(&samples, 1, 48000)?)?;
+    fs::write(
+        dir.join("session.json"),
+        r#"{
+            "version": 1,
+            "sources": [
+                { "id": "hits", "path": "hits.wav", "sample_rate": 48000, "channels": 1, "duration": 1.0 }
+            ],
+            "tracks": [
+                { "name": "Hits", "clips": [{ "source": "hits", "start": 0.0, "duration": 1.0 }] }
+            ]
+        }"#,
+    )?;
+
+    let document_id = client.import_session(dir.join("session.json")).await?;
+    let arrangement_id = client.get_document_arrangement(document_id).await?;
+    let main_track_id = client.get_arrangement_main_track(arrangement_id).await?;
+    let track_id = client.get_track_children(main_track_id).await?[0];
+
+    Ok(TrackViewId {
+        track_id,
+        arrangement_id,
+    })
+}
+
+fn assert_close(actual: RealTime, expected: f64) {
+    let expected = RealTime::from_secs_f64(expected);
+    assert!(
+        actual.approx_eq(expected, RealTime::from_secs_f64(0.001)),
+        "{actual:?} isn't close to {expected:?}"
+    );
+}
+
+#[test]
+fn transients() -> Result<()> {
+    run_test(|client| async move {
+        let dir = tempfile::tempdir()?;
+        let dir = Utf8Path::from_path(dir.path()).unwrap();
+        let view_id = import_hits(&client, dir).await?;
+
+        let (track_item_id, view_item) = client.get_track_view_range(view_id, None, None).await?[0];
+        let ItemId::Audio(item_id) = view_item.inner else {
+            panic!("unexpected item {view_item:?}");
+        };
+
+        let transients = client.detect_audio_item_transients(item_id, 0.5).await?;
+        assert_eq!(transients.len(), 3);
+        for (&actual, expected) in transients.iter().zip([0.1, 0.4, 0.7]) {
+            assert_close(actual, expected);
+        }
+
+        assert_err!(
+            client.detect_audio_item_transients(item_id, 2.0).await,
+            ErrorKind::NotSupported
+        );
+
+        // the item starts after the first hit, which is left out
+        client
+            .set_audio_item_source_offset(item_id, RealTime::from_secs_f64(0.2))
+            .await?;
+
+        let ids = client
+            .slice_item_at_transients(view_id, track_item_id, 0.5, false)
+            .await?;
+        assert_eq!(ids.len(), 3);
+        assert_eq!(ids[0], track_item_id);
+
+        let mut expected = [(0.0, 0.2), (0.2, 0.4), (0.5, 0.7)].into_iter();
+        for &id in &ids {
+            let (start, offset) = expected.next().unwrap();
+            let item = client.get_track_view_item(view_id, id).await?;
+            assert_close(item.real_start, start);
+            assert_close(item.clip.unwrap().source_offset, offset);
+        }
+
+        let last = client.get_track_view_item(view_id, ids[2]).await?;
+        assert_close(last.real_end, 1.0);
+
+        Ok(())
+    })
+}
+
+#[test]
+fn split_and_trim() -> Result<()> {
+    run_test(|client| async move {
+        let dir = tempfile::tempdir()?;
+        let dir = Utf8Path::from_path(dir.path()).unwrap();
+        let view_id = import_hits(&client, dir).await?;
+        let (first_id, _) = client.get_track_view_range(view_id, None, None).await?[0];
+
+        let second_id = client
+            .split_track_item(view_id, first_id, Time::Real(RealTime::from_secs_f64(0.3)), false)
+            .await?;
+
+        let first = client.get_track_view_item(view_id, first_id).await?;
+        assert_close(first.real_end, 0.3);
+
+        let second = client.get_track_view_item(view_id, second_id).await?;
+        assert_close(second.real_start, 0.3);
+        assert_close(second.real_end, 1.0);
+        assert_close(second.clip.unwrap().source_offset, 0.3);
+
+        // the end of the first item isn't inside it anymore
+        assert_err!(
+            client
+                .split_track_item(view_id, first_id, Time::Real(first.real_end), false)
+                .await,
+            ErrorKind::NotSupported
+        );
+
+        let mut stream = client
+            .subscribe_track_view(view_id, TrackViewFilter::default())
+            .await?;
+
+        let new_start = Time::Real(RealTime::from_secs_f64(0.5));
+        let new_end = Time::Real(RealTime::from_secs_f64(0.8));
+        client
+            .trim_track_item(view_id, second_id, new_start, new_end, false)
+            .await?;
+
+        // the audio stays where it was on the timeline
+        let second = client.get_track_view_item(view_id, second_id).await?;
+        assert_close(second.real_start, 0.5);
+        assert_close(second.real_end, 0.8);
+        assert_close(second.clip.unwrap().source_offset, 0.5);
+
+        assert!(matches!(
+            stream.next().await,
+            Some(TrackViewEvent::ItemClipChanged { id, .. }) if id == second_id
+        ));
+        assert!(matches!(
+            stream.next().await,
+            Some(TrackViewEvent::ItemMoved { id, .. }) if id == second_id
+        ));
+        assert!(matches!(
+            stream.next().await,
+            Some(TrackViewEvent::ItemResized { id, .. }) if id == second_id
+        ));
+
+        assert_err!(
+            client
+                .trim_track_item(view_id, second_id, new_end, new_start, false)
+                .await,
+            ErrorKind::NotSupported
+        );
+
+        Ok(())
+    })
+}
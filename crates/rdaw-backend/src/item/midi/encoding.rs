@@ -0,0 +1,37 @@
+use rdaw_api::Result;
+use serde::{Deserialize, Serialize};
+
+use super::MidiItem;
+use crate::define_version_enum;
+use crate::document::encoding;
+use crate::object::{DeserializationContext, SerializationContext, Uuid};
+
+pub fn serialize(ctx: &mut SerializationContext<'_>, item: &MidiItem) -> Result<Vec<u8>> {
+    let raw = MidiItemLatest {
+        source: ctx.add_dep(item.source_id)?,
+    };
+
+    encoding::serialize(ctx.format(), Version::LATEST.as_u32(), &raw)
+}
+
+pub fn deserialize(ctx: &mut DeserializationContext<'_>, data: &[u8]) -> Result<MidiItem> {
+    let (version, data) = encoding::extract_version(ctx.format(), data)?;
+    let raw = match Version::from_u32(version)? {
+        Version::V1 => encoding::deserialize::<MidiItemV1>(ctx.format(), data)?,
+    };
+
+    Ok(MidiItem::new(ctx.add_dep(raw.source)?))
+}
+
+define_version_enum! {
+    enum Version {
+        V1 = 1,
+    }
+}
+
+type MidiItemLatest = MidiItemV1;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct MidiItemV1 {
+    source: Uuid,
+}
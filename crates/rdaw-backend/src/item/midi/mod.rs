@@ -0,0 +1,40 @@
+mod encoding;
+mod ops;
+#[cfg(test)]
+mod tests;
+
+use rdaw_api::item::MidiItemId;
+use rdaw_api::source::MidiSourceId;
+use rdaw_api::Result;
+
+use crate::object::{DeserializationContext, Object, ObjectId, ObjectType, SerializationContext};
+
+impl ObjectId for MidiItemId {
+    type Object = MidiItem;
+}
+
+/// Several items can play the same source, so that editing one clip changes all of its copies.
+#[derive(Debug, Clone)]
+pub struct MidiItem {
+    pub source_id: MidiSourceId,
+}
+
+impl MidiItem {
+    pub fn new(source_id: MidiSourceId) -> MidiItem {
+        MidiItem { source_id }
+    }
+}
+
+impl Object for MidiItem {
+    type Id = MidiItemId;
+
+    const TYPE: ObjectType = ObjectType::MidiItem;
+
+    fn serialize(&self, ctx: &mut SerializationContext<'_>) -> Result<Vec<u8>> {
+        self::encoding::serialize(ctx, self)
+    }
+
+    fn deserialize(ctx: &mut DeserializationContext<'_>, data: &[u8]) -> Result<Self> {
+        self::encoding::deserialize(ctx, data)
+    }
+}
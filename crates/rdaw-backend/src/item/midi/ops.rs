@@ -0,0 +1,65 @@
+use rdaw_api::item::{ItemId, MidiItemId, MidiItemOperations, MidiItemRequest, MidiItemResponse};
+use rdaw_api::source::MidiSourceId;
+use rdaw_api::time::Time;
+use rdaw_api::track::{TrackId, TrackItem, TrackItemId};
+use rdaw_api::{BackendProtocol, Result};
+use tracing::instrument;
+
+use super::MidiItem;
+use crate::object::ObjectKey;
+use crate::source::MidiSource;
+use crate::Backend;
+
+#[rdaw_rpc::handler(protocol = BackendProtocol, operations = MidiItemOperations)]
+impl Backend {
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn add_midi_clip(
+        &mut self,
+        track_id: TrackId,
+        start: Time,
+        duration: Time,
+    ) -> Result<TrackItemId> {
+        let document_id = self.hub.tracks.get_key_or_err(track_id)?.document_id;
+
+        let source_id = self
+            .hub
+            .midi_sources
+            .insert(ObjectKey::new_random(document_id), MidiSource::new());
+        let item_id = self
+            .hub
+            .midi_items
+            .insert(ObjectKey::new_random(document_id), MidiItem::new(source_id));
+
+        self.add_track_item(
+            track_id,
+            TrackItem {
+                inner: ItemId::Midi(item_id),
+                start,
+                duration,
+                lane: 0,
+                locked: false,
+            },
+        )
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn create_midi_item(&mut self, source_id: MidiSourceId) -> Result<MidiItemId> {
+        let document_id = self.hub.midi_sources.get_key_or_err(source_id)?.document_id;
+
+        let id = self
+            .hub
+            .midi_items
+            .insert(ObjectKey::new_random(document_id), MidiItem::new(source_id));
+
+        Ok(id)
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn get_midi_item_source(&self, id: MidiItemId) -> Result<MidiSourceId> {
+        let item = self.hub.midi_items.get_or_err(id)?;
+        Ok(item.source_id)
+    }
+}
@@ -0,0 +1,108 @@
+use rdaw_api::arrangement::ArrangementOperations;
+use rdaw_api::document::DocumentOperations;
+use rdaw_api::item::{ItemId, MidiItemOperations};
+use rdaw_api::source::{MidiCc, MidiNote, MidiSourceOperations};
+use rdaw_api::time::BeatTime;
+use rdaw_api::track::{TrackOperations, TrackViewId};
+use rdaw_api::{assert_err, ErrorKind, Result};
+use rdaw_core::path::Utf8Path;
+use tempfile::NamedTempFile;
+
+use crate::tests::{beats, invalid_track_id, run_test, ProjectBuilder};
+
+#[test]
+fn add_midi_clip() -> Result<()> {
+    run_test(|client| async move {
+        let project = ProjectBuilder::new()
+            .track("Keys", |t| t)
+            .build(&client)
+            .await?;
+
+        let track_id = project.track("Keys");
+        let item_id = client.add_midi_clip(track_id, beats(4), beats(8)).await?;
+
+        let item = client.get_track_item(track_id, item_id).await?;
+        assert_eq!(item.start, beats(4));
+        assert_eq!(item.duration, beats(8));
+
+        let ItemId::Midi(midi_item_id) = item.inner else {
+            panic!("unexpected item {:?}", item.inner);
+        };
+
+        // a linked copy plays the same source
+        let source_id = client.get_midi_item_source(midi_item_id).await?;
+        let copy_id = client.create_midi_item(source_id).await?;
+        assert_eq!(client.get_midi_item_source(copy_id).await?, source_id);
+
+        assert_err!(
+            client
+                .add_midi_clip(invalid_track_id(), beats(0), beats(1))
+                .await,
+            ErrorKind::InvalidId
+        );
+
+        Ok(())
+    })
+}
+
+#[test]
+fn midi_clip_roundtrip() -> Result<()> {
+    run_test(|client| async move {
+        let project = ProjectBuilder::new()
+            .track("Keys", |t| t)
+            .build(&client)
+            .await?;
+
+        let track_id = project.track("Keys");
+        let item_id = client.add_midi_clip(track_id, beats(0), beats(4)).await?;
+        let ItemId::Midi(midi_item_id) = client.get_track_item(track_id, item_id).await?.inner
+        else {
+            panic!("expected a midi item");
+        };
+
+        let source_id = client.get_midi_item_source(midi_item_id).await?;
+
+        let note = MidiNote {
+            start: BeatTime::from_beats(1),
+            duration: BeatTime::from_beats_f32(0.5),
+            key: 60,
+            velocity: 90,
+        };
+        client.add_midi_source_note(source_id, note).await?;
+
+        let cc = MidiCc {
+            position: BeatTime::from_beats(2),
+            controller: 64,
+            value: 127,
+        };
+        client.add_midi_source_cc(source_id, cc).await?;
+
+        let temp_file = NamedTempFile::with_prefix(".rdaw-test-")?;
+        let path = Utf8Path::from_path(temp_file.path()).unwrap();
+        client
+            .export_document_json(project.document_id, path.to_path_buf())
+            .await?;
+
+        let document_id = client.import_document_json(path.to_path_buf()).await?;
+        let arrangement_id = client.get_document_arrangement(document_id).await?;
+        let main_track_id = client.get_arrangement_main_track(arrangement_id).await?;
+        let track_id = client.get_track_children(main_track_id).await?[0];
+
+        let view_id = TrackViewId {
+            track_id,
+            arrangement_id,
+        };
+        let items = client.get_track_view_range(view_id, None, None).await?;
+        let ItemId::Midi(midi_item_id) = items[0].1.inner else {
+            panic!("expected a midi item");
+        };
+
+        let source_id = client.get_midi_item_source(midi_item_id).await?;
+        let notes = client.get_midi_source_notes(source_id).await?;
+        assert_eq!(notes.len(), 1);
+        assert_eq!(notes[0].1, note);
+        assert_eq!(client.get_midi_source_ccs(source_id).await?, vec![cc]);
+
+        Ok(())
+    })
+}
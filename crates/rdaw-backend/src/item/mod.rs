@@ -1,3 +1,5 @@
 mod audio;
+mod midi;
 
-pub use self::audio::AudioItem;
+pub use self::audio::{detect_transients, AudioItem};
+pub use self::midi::MidiItem;
@@ -0,0 +1,42 @@
+//! Clip launcher, a performance-oriented alternative to the arrangement's timeline.
+//!
+//! Scenes are saved with the arrangement, while what's launched is only kept in memory. Launches
+//! and stops are quantized against the transport position, converted to beats with the tempo map,
+//! so that clips loop in sync with it. The engine plays launched clips on top of the timeline.
+
+mod ops;
+#[cfg(test)]
+mod tests;
+
+use rdaw_api::launcher::LaunchedClip;
+use rdaw_api::time::BeatTime;
+use rdaw_api::track::TrackId;
+use rdaw_core::collections::HashMap;
+
+/// What's going on in the clip launcher of an arrangement.
+#[derive(Debug, Clone)]
+pub struct LauncherState {
+    pub quantization: BeatTime,
+    /// At most one clip per track.
+    pub launched: HashMap<TrackId, LaunchedClip>,
+}
+
+impl Default for LauncherState {
+    fn default() -> LauncherState {
+        LauncherState {
+            quantization: BeatTime::from_beats(4),
+            launched: HashMap::default(),
+        }
+    }
+}
+
+/// Returns the first multiple of `quantization` at or after `position`.
+pub fn quantize_launch(position: BeatTime, quantization: BeatTime) -> BeatTime {
+    let quantization = quantization.as_beats_f64();
+    if quantization <= 0.0 {
+        return position;
+    }
+
+    let steps = (position.as_beats_f64() / quantization).ceil();
+    BeatTime::from_beats_f64(steps * quantization)
+}
@@ -0,0 +1,314 @@
+use rdaw_api::arrangement::ArrangementId;
+use rdaw_api::item::ItemId;
+use rdaw_api::launcher::{
+    LaunchedClip, LauncherEvent, LauncherOperations, LauncherRequest, LauncherResponse, Scene,
+    SceneId,
+};
+use rdaw_api::time::BeatTime;
+use rdaw_api::track::TrackId;
+use rdaw_api::{bail, format_err, BackendProtocol, ErrorKind, Result};
+use rdaw_rpc::StreamId;
+use tracing::instrument;
+
+use super::{quantize_launch, LauncherState};
+use crate::Backend;
+
+#[rdaw_rpc::handler(protocol = BackendProtocol, operations = LauncherOperations)]
+impl Backend {
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn subscribe_launcher(&mut self, id: ArrangementId) -> Result<StreamId> {
+        self.hub.arrangements.ensure_has(id)?;
+        Ok(self.subscribers.launcher.subscribe(id))
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn add_scene(&mut self, id: ArrangementId, scene: Scene) -> Result<SceneId> {
+        self.ensure_scene_valid(id, &scene)?;
+
+        let arrangement = self.hub.arrangements.get_mut_or_err(id)?;
+        let scene_id = arrangement.scenes.insert(scene.clone());
+        arrangement.scene_order.push(scene_id);
+
+        self.subscribers.launcher.notify(
+            id,
+            LauncherEvent::SceneAdded {
+                id: scene_id,
+                scene,
+            },
+        );
+
+        Ok(scene_id)
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn get_scene(&self, id: ArrangementId, scene_id: SceneId) -> Result<Scene> {
+        let arrangement = self.hub.arrangements.get_or_err(id)?;
+        arrangement.scenes.get(scene_id).cloned().ok_or_else(|| {
+            format_err!(ErrorKind::InvalidId, "{scene_id:?} doesn't exist in {id:?}")
+        })
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn get_scenes(&self, id: ArrangementId) -> Result<Vec<(SceneId, Scene)>> {
+        let arrangement = self.hub.arrangements.get_or_err(id)?;
+
+        let scenes = arrangement
+            .scene_order
+            .iter()
+            .filter_map(|&scene_id| Some((scene_id, arrangement.scenes.get(scene_id)?.clone())))
+            .collect();
+
+        Ok(scenes)
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn set_scene(&mut self, id: ArrangementId, scene_id: SceneId, scene: Scene) -> Result<()> {
+        self.get_scene(id, scene_id)?;
+        self.ensure_scene_valid(id, &scene)?;
+
+        let arrangement = self.hub.arrangements.get_mut_or_err(id)?;
+        arrangement.scenes[scene_id] = scene.clone();
+
+        self.subscribers.launcher.notify(
+            id,
+            LauncherEvent::SceneChanged {
+                id: scene_id,
+                scene,
+            },
+        );
+
+        self.stop_orphaned_clips(id)
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn remove_scene(&mut self, id: ArrangementId, scene_id: SceneId) -> Result<()> {
+        let arrangement = self.hub.arrangements.get_mut_or_err(id)?;
+
+        if arrangement.scenes.remove(scene_id).is_none() {
+            return Ok(());
+        }
+
+        arrangement.scene_order.retain(|&v| v != scene_id);
+
+        self.subscribers
+            .launcher
+            .notify(id, LauncherEvent::SceneRemoved { id: scene_id });
+
+        self.stop_orphaned_clips(id)
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn get_launch_quantization(&self, id: ArrangementId) -> Result<BeatTime> {
+        self.hub.arrangements.ensure_has(id)?;
+
+        let quantization = match self.launchers.get(&id) {
+            Some(launcher) => launcher.quantization,
+            None => LauncherState::default().quantization,
+        };
+
+        Ok(quantization)
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn set_launch_quantization(
+        &mut self,
+        id: ArrangementId,
+        quantization: BeatTime,
+    ) -> Result<()> {
+        self.hub.arrangements.ensure_has(id)?;
+
+        if quantization < BeatTime::ZERO {
+            bail!(ErrorKind::NotSupported, "quantization can't be negative");
+        }
+
+        self.launchers.entry(id).or_default().quantization = quantization;
+        Ok(())
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn launch_clip(
+        &mut self,
+        id: ArrangementId,
+        scene_id: SceneId,
+        track_id: TrackId,
+    ) -> Result<BeatTime> {
+        let scene = self.get_scene(id, scene_id)?;
+
+        if !scene.clips.contains_key(&track_id) {
+            bail!(
+                ErrorKind::NotFound,
+                "{scene_id:?} doesn't have a clip for {track_id:?}"
+            );
+        }
+
+        let at = self.quantized_launch_position(id)?;
+        self.launch(id, scene_id, track_id, at);
+
+        Ok(at)
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn launch_scene(&mut self, id: ArrangementId, scene_id: SceneId) -> Result<BeatTime> {
+        let scene = self.get_scene(id, scene_id)?;
+        let at = self.quantized_launch_position(id)?;
+
+        for &track_id in scene.clips.keys() {
+            self.launch(id, scene_id, track_id, at);
+        }
+
+        Ok(at)
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn stop_clip(&mut self, id: ArrangementId, track_id: TrackId) -> Result<BeatTime> {
+        let at = self.quantized_launch_position(id)?;
+
+        let Some(launched) = self
+            .launchers
+            .get_mut(&id)
+            .and_then(|v| v.launched.get_mut(&track_id))
+        else {
+            bail!(ErrorKind::NotFound, "no clip is launched on {track_id:?}");
+        };
+
+        // a clip that's stopped before it has started doesn't play at all
+        let at = at.max(launched.start);
+        launched.stop = Some(at);
+
+        self.subscribers
+            .launcher
+            .notify(id, LauncherEvent::ClipStopped { track_id, at });
+
+        Ok(at)
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn get_launched_clips(&self, id: ArrangementId) -> Result<Vec<LaunchedClip>> {
+        self.hub.arrangements.ensure_has(id)?;
+
+        let mut clips = self
+            .launchers
+            .get(&id)
+            .map(|v| v.launched.values().copied().collect::<Vec<_>>())
+            .unwrap_or_default();
+        clips.sort_by_key(|v| v.track_id);
+
+        Ok(clips)
+    }
+
+    fn launch(&mut self, id: ArrangementId, scene_id: SceneId, track_id: TrackId, at: BeatTime) {
+        let clip = LaunchedClip {
+            track_id,
+            scene_id,
+            start: at,
+            stop: None,
+        };
+
+        // replaces whatever was launched on the track before
+        let launcher = self.launchers.entry(id).or_default();
+        launcher.launched.insert(track_id, clip);
+
+        self.subscribers
+            .launcher
+            .notify(id, LauncherEvent::ClipLaunched(clip));
+    }
+
+    /// Where a clip launched or stopped now would start or stop.
+    fn quantized_launch_position(&self, id: ArrangementId) -> Result<BeatTime> {
+        let arrangement = self.hub.arrangements.get_or_err(id)?;
+        let tempo_map = &self.hub.tempo_maps[arrangement.tempo_map_id];
+
+        let position = self
+            .transports
+            .get(&id)
+            .map(|v| v.position)
+            .unwrap_or_default();
+
+        let quantization = self.get_launch_quantization(id)?;
+        Ok(quantize_launch(
+            tempo_map.real_to_beat(position),
+            quantization,
+        ))
+    }
+
+    /// Stops launched clips whose scene no longer has them, right away.
+    fn stop_orphaned_clips(&mut self, id: ArrangementId) -> Result<()> {
+        let arrangement = self.hub.arrangements.get_or_err(id)?;
+        let tempo_map = &self.hub.tempo_maps[arrangement.tempo_map_id];
+
+        let Some(launcher) = self.launchers.get_mut(&id) else {
+            return Ok(());
+        };
+
+        let position = self
+            .transports
+            .get(&id)
+            .map(|v| v.position)
+            .unwrap_or_default();
+        let at = tempo_map.real_to_beat(position);
+
+        let mut stopped = Vec::new();
+
+        launcher.launched.retain(|&track_id, launched| {
+            let has_clip = arrangement
+                .scenes
+                .get(launched.scene_id)
+                .is_some_and(|scene| scene.clips.contains_key(&track_id));
+
+            if !has_clip {
+                stopped.push(track_id);
+            }
+
+            has_clip
+        });
+
+        for track_id in stopped {
+            self.subscribers
+                .launcher
+                .notify(id, LauncherEvent::ClipStopped { track_id, at });
+        }
+
+        Ok(())
+    }
+
+    fn ensure_scene_valid(&self, id: ArrangementId, scene: &Scene) -> Result<()> {
+        let document_id = self.hub.arrangements.get_key_or_err(id)?.document_id;
+        let tracks = self.arrangement_tracks(id);
+
+        for (track_id, clip) in &scene.clips {
+            if !tracks.contains(track_id) {
+                bail!(ErrorKind::NotFound, "{track_id:?} isn't in {id:?}");
+            }
+
+            if clip.length <= BeatTime::ZERO {
+                bail!(ErrorKind::NotSupported, "clip length must be positive");
+            }
+
+            let item_document_id = match clip.item {
+                ItemId::Audio(item_id) => self.hub.audio_items.get_key_or_err(item_id)?.document_id,
+                ItemId::Midi(item_id) => self.hub.midi_items.get_key_or_err(item_id)?.document_id,
+            };
+
+            if item_document_id != document_id {
+                bail!(
+                    ErrorKind::NotSupported,
+                    "clip item belongs to another document"
+                );
+            }
+        }
+
+        Ok(())
+    }
+}
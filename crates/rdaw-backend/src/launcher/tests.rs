@@ -0,0 +1,162 @@
+use std::collections::BTreeMap;
+
+use futures::StreamExt;
+use rdaw_api::document::DocumentId;
+use rdaw_api::item::{ItemId, MidiItemOperations};
+use rdaw_api::launcher::{Clip, LaunchedClip, LauncherEvent, LauncherOperations, Scene};
+use rdaw_api::source::MidiSourceOperations;
+use rdaw_api::time::BeatTime;
+use rdaw_api::transport::TransportOperations;
+use rdaw_api::{assert_err, ErrorKind, Result};
+
+use super::quantize_launch;
+use crate::tests::{beats, invalid_track_id, run_test, ProjectBuilder, TestClient};
+
+fn b(beats: f64) -> BeatTime {
+    BeatTime::from_beats_f64(beats)
+}
+
+async fn clip(client: &TestClient, document_id: DocumentId) -> Result<Clip> {
+    let source_id = client.create_midi_source(document_id).await?;
+    let item_id = client.create_midi_item(source_id).await?;
+
+    Ok(Clip {
+        item: ItemId::Midi(item_id),
+        length: BeatTime::from_beats(2),
+    })
+}
+
+#[test]
+fn quantization() {
+    assert_eq!(quantize_launch(b(0.0), b(4.0)), b(0.0));
+    assert_eq!(quantize_launch(b(0.5), b(4.0)), b(4.0));
+    assert_eq!(quantize_launch(b(4.0), b(4.0)), b(4.0));
+    assert_eq!(quantize_launch(b(5.0), b(1.0)), b(5.0));
+    assert_eq!(quantize_launch(b(5.25), b(0.0)), b(5.25));
+}
+
+#[test]
+fn clip_position() {
+    let clip = Clip {
+        item: ItemId::Midi(Default::default()),
+        length: b(2.0),
+    };
+
+    let launched = LaunchedClip {
+        track_id: Default::default(),
+        scene_id: Default::default(),
+        start: b(4.0),
+        stop: Some(b(10.0)),
+    };
+
+    assert_eq!(launched.position(&clip, b(3.0)), None);
+    assert_eq!(launched.position(&clip, b(4.0)), Some(b(0.0)));
+    assert_eq!(launched.position(&clip, b(7.5)), Some(b(1.5)));
+    assert_eq!(launched.position(&clip, b(10.0)), None);
+}
+
+#[test]
+fn scenes() -> Result<()> {
+    run_test(|client| async move {
+        let project = ProjectBuilder::new().tracks(2).build(&client).await?;
+        let id = project.arrangement_id;
+        let first_track = project.track("Track 0");
+
+        let mut stream = client.subscribe_launcher(id).await?;
+
+        let first = Scene {
+            name: "Verse".into(),
+            clips: BTreeMap::from([(first_track, clip(&client, project.document_id).await?)]),
+        };
+        let second = Scene {
+            name: "Chorus".into(),
+            clips: BTreeMap::new(),
+        };
+
+        let first_id = client.add_scene(id, first.clone()).await?;
+        let second_id = client.add_scene(id, second.clone()).await?;
+
+        assert_eq!(
+            stream.next().await,
+            Some(LauncherEvent::SceneAdded {
+                id: first_id,
+                scene: first.clone(),
+            })
+        );
+        assert_eq!(
+            client.get_scenes(id).await?,
+            vec![(first_id, first.clone()), (second_id, second.clone())]
+        );
+
+        let invalid = Scene {
+            name: "Outro".into(),
+            clips: BTreeMap::from([(
+                invalid_track_id(),
+                clip(&client, project.document_id).await?,
+            )]),
+        };
+        assert_err!(client.add_scene(id, invalid).await, ErrorKind::NotFound);
+
+        let mut zero_length = first.clone();
+        zero_length
+            .clips
+            .values_mut()
+            .for_each(|v| v.length = BeatTime::ZERO);
+        assert_err!(
+            client.set_scene(id, first_id, zero_length).await,
+            ErrorKind::NotSupported
+        );
+
+        client.remove_scene(id, first_id).await?;
+        assert_eq!(client.get_scenes(id).await?, vec![(second_id, second)]);
+        assert_err!(client.get_scene(id, first_id).await, ErrorKind::InvalidId);
+
+        Ok(())
+    })
+}
+
+#[test]
+fn launch() -> Result<()> {
+    run_test(|client| async move {
+        let project = ProjectBuilder::new().tracks(2).build(&client).await?;
+        let id = project.arrangement_id;
+        let first_track = project.track("Track 0");
+        let second_track = project.track("Track 1");
+
+        let scene = Scene {
+            name: "Verse".into(),
+            clips: BTreeMap::from([
+                (first_track, clip(&client, project.document_id).await?),
+                (second_track, clip(&client, project.document_id).await?),
+            ]),
+        };
+        let scene_id = client.add_scene(id, scene).await?;
+
+        // at 120 bpm, one beat is half a second
+        client.seek(id, beats(1)).await?;
+        assert_eq!(client.get_launch_quantization(id).await?, b(4.0));
+        assert_eq!(client.launch_clip(id, scene_id, first_track).await?, b(4.0));
+
+        client.set_launch_quantization(id, BeatTime::ZERO).await?;
+        assert_eq!(client.launch_scene(id, scene_id).await?, b(1.0));
+
+        assert_eq!(client.get_launched_clips(id).await?.len(), 2);
+
+        assert_eq!(client.stop_clip(id, second_track).await?, b(1.0));
+        assert_err!(
+            client.stop_clip(id, project.main_track_id).await,
+            ErrorKind::NotFound
+        );
+
+        // removing the scene stops its clips
+        client.remove_scene(id, scene_id).await?;
+        assert!(client.get_launched_clips(id).await?.is_empty());
+
+        assert_err!(
+            client.set_launch_quantization(id, b(-1.0)).await,
+            ErrorKind::NotSupported
+        );
+
+        Ok(())
+    })
+}
@@ -1,119 +1,501 @@
 pub mod arrangement;
 pub mod asset;
+pub mod automation;
+pub mod bus;
+pub mod cache;
+pub mod chord;
 pub mod document;
+pub mod engine;
+pub mod export;
+#[cfg(feature = "fuzzing")]
+pub mod fuzz;
+pub mod grid;
+pub mod interchange;
 pub mod item;
+pub mod launcher;
+pub mod log;
+pub mod meter;
+pub mod modulation;
+pub mod node;
 pub mod object;
+pub mod peaks;
+pub mod plugin;
+pub mod recording;
+pub mod render;
+pub mod settings;
 pub mod source;
+pub mod stats;
+pub mod task;
 pub mod tempo_map;
 #[cfg(test)]
 pub mod tests;
 pub mod track;
+pub mod transaction;
+pub mod transport;
+pub mod video;
 
 use std::future::Future;
 use std::pin::Pin;
 use std::sync::Arc;
+use std::thread;
+use std::time::Instant;
 
 use async_channel::{Receiver, Sender};
 use document::DocumentStorage;
-use futures::executor::ThreadPool;
 use futures::{select_biased, FutureExt};
-use rdaw_api::{BackendProtocol, BackendRequest, ErrorKind, Result};
-use rdaw_rpc::transport::{LocalServerTransport, ServerTransport};
-use rdaw_rpc::{ClientMessage, StreamIdAllocator};
+use rdaw_api::arrangement::ArrangementId;
+use rdaw_api::document::{BackupSettings, DocumentId, DocumentWarning};
+use rdaw_api::settings::MonitoringLimiter;
+use rdaw_api::source::AudioSourceId;
+use rdaw_api::stats::TaskQueue;
+use rdaw_api::{bail, BackendProtocol, BackendRequest, ErrorKind, Result};
+use rdaw_audio::nodes::LimiterParams;
+use rdaw_core::collections::{HashMap, HashSet};
+use rdaw_core::path::Utf8PathBuf;
+use rdaw_core::time::RealTime;
+use rdaw_rpc::transport::{
+    BatchingTransport, BoxedServerTransport, ConnectionId, Connections, Incoming, ServerTransport,
+    SocketListener,
+};
+use rdaw_rpc::{
+    CancellationToken, CancellationTokens, ClientMessage, RequestId, Role, ServerMessage, StreamId,
+    StreamIdAllocator,
+};
 
+use self::asset::PathVariables;
+use self::cache::FileCache;
+use self::engine::{DynDriver, Engine};
+use self::launcher::LauncherState;
+use self::log::LogBuffer;
+use self::meter::InputMonitor;
 use self::object::{Hub, SubscribersHub};
-use self::track::TrackViewCache;
+use self::peaks::PeaksState;
+use self::plugin::PluginHost;
+use self::recording::Recording;
+use self::settings::Autosave;
+use self::stats::HandlerProfiler;
+use self::task::TaskPool;
+use self::track::{Clipboard, TrackViewCache};
+use self::transaction::Transaction;
+use self::transport::{Ticker, Transport};
+
+const MAX_BATCH_SIZE: usize = 64;
 
 #[derive(Debug)]
 pub struct Backend {
-    transport: LocalServerTransport<BackendProtocol>,
+    connections: Connections<BackendProtocol>,
+    /// Connection of the message being handled.
+    connection: ConnectionId,
+    accepted_sender: Sender<BoxedServerTransport<BackendProtocol>>,
+    accepted_receiver: Receiver<BoxedServerTransport<BackendProtocol>>,
+    is_listening: bool,
 
-    thread_pool: ThreadPool,
+    tasks: TaskPool,
     queue: DeferredQueue,
+    cancellation_tokens: HashMap<ConnectionId, CancellationTokens>,
 
     documents: DocumentStorage,
+    path_variables: PathVariables,
     hub: Hub,
     subscribers: SubscribersHub,
 
     track_view_cache: TrackViewCache,
+    waveforms: HashMap<AudioSourceId, PeaksState>,
+    user_preset_dir: Option<Utf8PathBuf>,
+    cache: Option<Arc<FileCache>>,
+    cache_budget: u64,
+    author: Option<String>,
+    backups: BackupSettings,
+    autosave: Option<Autosave>,
+    unsaved_documents: HashSet<DocumentId>,
+    /// Problems found by validation when the documents were opened.
+    document_warnings: HashMap<DocumentId, Vec<DocumentWarning>>,
+    plugins: PluginHost,
+    safe_mode: bool,
+    settings_path: Option<Utf8PathBuf>,
+    profiler: HandlerProfiler,
+    log_buffer: LogBuffer,
+    transaction: Option<Transaction>,
+    transports: HashMap<ArrangementId, Transport>,
+    ticker: Option<Ticker>,
+    audio_driver: Option<Arc<dyn DynDriver>>,
+    engine: Option<Engine>,
+    engine_dirty: bool,
+    monitoring_limiter: LimiterParams,
+    recordings: HashMap<ArrangementId, Recording>,
+    input_monitor: Option<InputMonitor>,
+    recording_latency: RealTime,
+    launchers: HashMap<ArrangementId, LauncherState>,
+    clipboard: Option<Clipboard>,
 }
 
 impl Backend {
-    pub fn new(transport: LocalServerTransport<BackendProtocol>) -> Backend {
+    pub fn new(transport: impl ServerTransport<BackendProtocol>) -> Backend {
         let stream_id_allocator = Arc::new(StreamIdAllocator::new());
+        let limiter = MonitoringLimiter::default();
+
+        let mut connections = Connections::new();
+        let connection = connections.add(transport);
+        let (accepted_sender, accepted_receiver) = async_channel::unbounded();
 
         Backend {
-            transport,
+            connections,
+            connection,
+            accepted_sender,
+            accepted_receiver,
+            is_listening: false,
 
-            thread_pool: ThreadPool::new().unwrap(),
+            tasks: TaskPool::with_default_config(),
             queue: DeferredQueue::new(),
+            cancellation_tokens: HashMap::default(),
 
             documents: DocumentStorage::default(),
+            path_variables: PathVariables::default(),
             hub: Hub::default(),
             subscribers: SubscribersHub::new(stream_id_allocator.clone()),
 
             track_view_cache: TrackViewCache::default(),
+            waveforms: HashMap::default(),
+            user_preset_dir: None,
+            cache: None,
+            cache_budget: cache::DEFAULT_BUDGET,
+            author: None,
+            backups: BackupSettings::default(),
+            autosave: None,
+            unsaved_documents: HashSet::default(),
+            document_warnings: HashMap::default(),
+            plugins: PluginHost::default(),
+            safe_mode: false,
+            settings_path: None,
+            profiler: HandlerProfiler::default(),
+            log_buffer: LogBuffer::default(),
+            transaction: None,
+            transports: HashMap::default(),
+            ticker: None,
+            audio_driver: None,
+            engine: None,
+            engine_dirty: false,
+            monitoring_limiter: LimiterParams::new(limiter.enabled, limiter.ceiling),
+            recordings: HashMap::default(),
+            input_monitor: None,
+            recording_latency: RealTime::ZERO,
+            launchers: HashMap::default(),
+            clipboard: None,
         }
     }
 
+    /// Serves clients of the listener next to the one given to [`Backend::new`]. The backend keeps
+    /// running when all of them go away.
+    pub fn listen(&mut self, listener: SocketListener) {
+        let sender = self.accepted_sender.clone();
+        self.is_listening = true;
+
+        thread::Builder::new()
+            .name("rdaw-listener".into())
+            .spawn(move || {
+                let res = listener.serve::<BackendProtocol>(move |transport| {
+                    let transport = BoxedServerTransport::new(transport);
+                    sender.send_blocking(transport).is_ok()
+                });
+
+                if let Err(error) = res {
+                    tracing::error!(?error, "failed to accept clients");
+                }
+            })
+            .expect("failed to spawn listener thread");
+    }
+
+    /// In safe mode, nothing user-provided is loaded from outside of the opened documents, so that
+    /// a broken preset or config file can't get in the way of opening a project.
+    pub fn set_safe_mode(&mut self, safe_mode: bool) {
+        self.safe_mode = safe_mode;
+    }
+
+    /// Log records are only collected if the buffer's layer is installed in the subscriber.
+    pub fn set_log_buffer(&mut self, buffer: LogBuffer) {
+        self.log_buffer = buffer;
+    }
+
+    /// Without a driver, the transport still runs, but nothing is rendered.
+    pub fn set_audio_driver(&mut self, driver: Arc<dyn DynDriver>) {
+        self.engine = None;
+        self.input_monitor = None;
+        self.audio_driver = Some(driver);
+        self.update_input_monitor();
+    }
+
     pub async fn update(&mut self) -> Result<()> {
-        self.subscribers.deliver(&self.transport).await?;
+        // events of an open transaction are delivered all at once after it's committed
+        if self.transaction.is_some() {
+            return Ok(());
+        }
+
+        for (document_id, event) in self.hub.take_changes() {
+            self.unsaved_documents.insert(document_id);
+            self.subscribers.document_changes.notify(document_id, event);
+        }
+
+        self.subscribers.deliver(&self.connections.events()).await?;
         Ok(())
     }
 
     pub async fn handle(&mut self) -> Result<()> {
         loop {
-            let msg = select_biased! {
+            let (connection, incoming) = select_biased! {
                 task = self.queue.receiver.recv().fuse() => {
                     if let Ok(task) = task {
                         task(self).await?;
+                        self.update_engine();
+                        self.update().await?;
                     }
                     continue
                 }
 
-                msg = self.transport.recv().fuse() => match msg {
-                    Ok(v) => v,
-                    Err(e) if e.kind() == ErrorKind::Disconnected => return Ok(()),
-                    Err(e) => return Err(e),
+                transport = self.accepted_receiver.recv().fuse() => {
+                    if let Ok(transport) = transport {
+                        self.connections.add(transport);
+                    }
+                    continue
                 }
+
+                res = self.connections.recv().fuse() => res?,
             };
 
-            match msg {
-                ClientMessage::Request { id, payload } => match payload {
-                    BackendRequest::Arrangement(req) => {
-                        self.handle_arrangement_request(self.transport.clone(), id, req)
-                            .await?
-                    }
-                    BackendRequest::Asset(req) => {
-                        self.handle_asset_request(self.transport.clone(), id, req)
-                            .await?
-                    }
-                    BackendRequest::AudioSource(req) => {
-                        self.handle_audio_source_request(self.transport.clone(), id, req)
-                            .await?
-                    }
-                    BackendRequest::Document(req) => {
-                        self.handle_document_request(self.transport.clone(), id, req)
-                            .await?
-                    }
-                    BackendRequest::Track(req) => {
-                        self.handle_track_request(self.transport.clone(), id, req)
-                            .await?
-                    }
-                },
-                ClientMessage::CloseStream { id } => self.subscribers.close_one(id),
+            self.handle_incoming(connection, incoming).await?;
+
+            // handle everything that's already queued before delivering events, so that bursts
+            // of requests (e.g. during drags) get coalesced
+            for _ in 1..MAX_BATCH_SIZE {
+                let Some(res) = self.connections.recv().now_or_never() else {
+                    break;
+                };
+
+                let (connection, incoming) = res?;
+                self.handle_incoming(connection, incoming).await?;
+            }
+
+            if self.connections.is_empty() && !self.is_listening {
+                return Ok(());
             }
 
+            self.update_engine();
             self.update().await?;
         }
     }
 
-    fn spawn(&self, fut: impl Future<Output = Result<()>> + Send + 'static) {
-        self.thread_pool.spawn_ok(async move {
-            if let Err(error) = fut.await {
-                tracing::error!(?error);
+    async fn handle_incoming(
+        &mut self,
+        connection: ConnectionId,
+        incoming: Incoming<BackendProtocol>,
+    ) -> Result<()> {
+        match incoming {
+            Incoming::Message(msg) => self.handle_message(connection, msg).await,
+            Incoming::Closed { streams } => {
+                self.close_connection(connection, streams);
+                Ok(())
+            }
+        }
+    }
+
+    fn close_connection(&mut self, connection: ConnectionId, streams: Vec<StreamId>) {
+        for stream in streams {
+            self.subscribers.close_one(stream);
+        }
+
+        // nobody is waiting for the responses anymore
+        if let Some(tokens) = self.cancellation_tokens.remove(&connection) {
+            tokens.cancel_all();
+        }
+
+        self.abandon_transaction(connection);
+    }
+
+    async fn handle_message(
+        &mut self,
+        connection: ConnectionId,
+        msg: ClientMessage<BackendProtocol>,
+    ) -> Result<()> {
+        let Some(transport) = self.connections.get(connection) else {
+            return Ok(());
+        };
+
+        self.connection = connection;
+
+        match msg {
+            ClientMessage::Request { id, payload } => {
+                self.handle_authorized_request(transport, id, payload)
+                    .await?
+            }
+            ClientMessage::Batch { requests } => {
+                let transport = BatchingTransport::new(transport);
+
+                for (id, payload) in requests {
+                    self.handle_authorized_request(transport.clone(), id, payload)
+                        .await?;
+                }
+
+                transport.finish().await?;
+            }
+            ClientMessage::CancelRequest { id } => {
+                if let Some(tokens) = self.cancellation_tokens.get_mut(&connection) {
+                    tokens.cancel(id);
+                }
+            }
+            ClientMessage::CloseStream { id } => {
+                // streams of other clients are left alone
+                if self.connections.stream_owner(id) == Some(connection) {
+                    self.subscribers.close_one(id);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn handle_authorized_request<T: ServerTransport<BackendProtocol>>(
+        &mut self,
+        transport: T,
+        id: RequestId,
+        payload: BackendRequest,
+    ) -> Result<()> {
+        match self.authorize(transport.role(), &payload) {
+            Ok(()) => {
+                // deliver what happened before the transaction, so that a rollback only
+                // discards events of the transaction itself
+                if transaction::is_begin(&payload) {
+                    self.update().await?;
+                }
+
+                if payload.required_role() != Role::ReadOnly {
+                    self.engine_dirty = true;
+                }
+
+                self.handle_profiled_request(transport, id, payload).await
+            }
+            Err(error) => {
+                transport
+                    .send(ServerMessage::Response {
+                        id,
+                        payload: Err(error),
+                    })
+                    .await
+            }
+        }
+    }
+
+    fn authorize(&self, role: Role, request: &BackendRequest) -> Result<()> {
+        let required_role = request.required_role();
+
+        if !role.permits(required_role) {
+            bail!(
+                ErrorKind::PermissionDenied,
+                "request requires {required_role:?} role, but client has {role:?}",
+            );
+        }
+
+        if let Some(transaction) = &self.transaction {
+            transaction.ensure_allowed(self.connection, request)?;
+        }
+
+        Ok(())
+    }
+
+    async fn handle_profiled_request<T: ServerTransport<BackendProtocol>>(
+        &mut self,
+        transport: T,
+        id: RequestId,
+        payload: BackendRequest,
+    ) -> Result<()> {
+        let name = payload.name();
+
+        // handlers consume the request, so keep a copy around for the slow request log
+        let copy = self.profiler.slow_threshold().map(|_| payload.clone());
+
+        let start = Instant::now();
+        self.handle_request(transport, id, payload).await?;
+        let elapsed = start.elapsed();
+
+        if self.profiler.record(name, elapsed) {
+            if let Some(request) = copy {
+                tracing::warn!(?elapsed, ?request, "slow request");
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn handle_request<T: ServerTransport<BackendProtocol>>(
+        &mut self,
+        transport: T,
+        id: RequestId,
+        payload: BackendRequest,
+    ) -> Result<()> {
+        match payload {
+            BackendRequest::Arrangement(req) => {
+                self.handle_arrangement_request(transport, id, req).await
+            }
+            BackendRequest::Asset(req) => self.handle_asset_request(transport, id, req).await,
+            BackendRequest::AudioItem(req) => {
+                self.handle_audio_item_request(transport, id, req).await
+            }
+            BackendRequest::AudioSource(req) => {
+                self.handle_audio_source_request(transport, id, req).await
+            }
+            BackendRequest::Automation(req) => {
+                self.handle_automation_request(transport, id, req).await
+            }
+            BackendRequest::Bus(req) => self.handle_bus_request(transport, id, req).await,
+            BackendRequest::Cache(req) => self.handle_cache_request(transport, id, req).await,
+            BackendRequest::Chord(req) => self.handle_chord_request(transport, id, req).await,
+            BackendRequest::Document(req) => self.handle_document_request(transport, id, req).await,
+            BackendRequest::Engine(req) => self.handle_engine_request(transport, id, req).await,
+            BackendRequest::Export(req) => self.handle_export_request(transport, id, req).await,
+            BackendRequest::Grid(req) => self.handle_grid_request(transport, id, req).await,
+            BackendRequest::Interchange(req) => {
+                self.handle_interchange_request(transport, id, req).await
+            }
+            BackendRequest::Launcher(req) => self.handle_launcher_request(transport, id, req).await,
+            BackendRequest::Log(req) => self.handle_log_request(transport, id, req).await,
+            BackendRequest::Meter(req) => self.handle_meter_request(transport, id, req).await,
+            BackendRequest::MidiItem(req) => {
+                self.handle_midi_item_request(transport, id, req).await
+            }
+            BackendRequest::MidiSource(req) => {
+                self.handle_midi_source_request(transport, id, req).await
+            }
+            BackendRequest::Modulation(req) => {
+                self.handle_modulation_request(transport, id, req).await
             }
-        })
+            BackendRequest::Node(req) => self.handle_node_request(transport, id, req).await,
+            BackendRequest::Plugin(req) => self.handle_plugin_request(transport, id, req).await,
+            BackendRequest::Recording(req) => {
+                self.handle_recording_request(transport, id, req).await
+            }
+            BackendRequest::Render(req) => self.handle_render_request(transport, id, req).await,
+            BackendRequest::Settings(req) => self.handle_settings_request(transport, id, req).await,
+            BackendRequest::Stats(req) => self.handle_stats_request(transport, id, req).await,
+            BackendRequest::Track(req) => self.handle_track_request(transport, id, req).await,
+            BackendRequest::Transaction(req) => {
+                self.handle_transaction_request(transport, id, req).await
+            }
+            BackendRequest::Transport(req) => {
+                self.handle_transport_request(transport, id, req).await
+            }
+            BackendRequest::Video(req) => self.handle_video_request(transport, id, req).await,
+            BackendRequest::Waveform(req) => self.handle_waveform_request(transport, id, req).await,
+        }
+    }
+
+    fn spawn(&self, queue: TaskQueue, fut: impl Future<Output = Result<()>> + Send + 'static) {
+        self.tasks.spawn(queue, fut)
+    }
+
+    /// Used by handlers which take a `cancel` token after the responder.
+    fn cancellation_token(&mut self, id: RequestId) -> CancellationToken {
+        self.cancellation_tokens
+            .entry(self.connection)
+            .or_default()
+            .register(id)
     }
 }
 
@@ -155,3 +537,12 @@ impl DeferredQueue {
             .unwrap();
     }
 }
+
+/// Called by handlers taking a `cancel` token between steps of their work.
+pub(crate) fn ensure_not_cancelled(cancel: &CancellationToken) -> Result<()> {
+    if cancel.is_cancelled() {
+        bail!(ErrorKind::Cancelled, "request was cancelled");
+    }
+
+    Ok(())
+}
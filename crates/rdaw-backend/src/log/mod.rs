@@ -0,0 +1,124 @@
+mod ops;
+#[cfg(test)]
+mod tests;
+
+use std::collections::VecDeque;
+use std::fmt::{self, Write};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+use rdaw_api::log::{LogFilter, LogRecord};
+use tracing::field::{Field, Visit};
+use tracing::{Event, Subscriber};
+use tracing_subscriber::layer::Context;
+use tracing_subscriber::Layer;
+
+const DEFAULT_CAPACITY: usize = 4096;
+
+/// Bounded ring of the most recent log records, filled by [`LogLayer`].
+#[derive(Debug, Clone)]
+pub struct LogBuffer {
+    inner: Arc<Mutex<LogBufferInner>>,
+}
+
+#[derive(Debug)]
+struct LogBufferInner {
+    records: VecDeque<LogRecord>,
+    capacity: usize,
+}
+
+impl LogBuffer {
+    pub fn new(capacity: usize) -> LogBuffer {
+        let inner = LogBufferInner {
+            records: VecDeque::with_capacity(capacity),
+            capacity,
+        };
+
+        LogBuffer {
+            inner: Arc::new(Mutex::new(inner)),
+        }
+    }
+
+    pub fn layer(&self) -> LogLayer {
+        LogLayer {
+            buffer: self.clone(),
+        }
+    }
+
+    pub fn push(&self, record: LogRecord) {
+        let mut inner = self.inner.lock().unwrap();
+        if inner.capacity == 0 {
+            return;
+        }
+
+        if inner.records.len() == inner.capacity {
+            inner.records.pop_front();
+        }
+
+        inner.records.push_back(record);
+    }
+
+    pub fn records(&self, filter: &LogFilter) -> Vec<LogRecord> {
+        let inner = self.inner.lock().unwrap();
+        let limit = filter.limit.unwrap_or(usize::MAX);
+
+        let mut records = inner
+            .records
+            .iter()
+            .rev()
+            .filter(|v| filter.matches(v))
+            .take(limit)
+            .cloned()
+            .collect::<Vec<_>>();
+
+        records.reverse();
+        records
+    }
+}
+
+impl Default for LogBuffer {
+    fn default() -> LogBuffer {
+        LogBuffer::new(DEFAULT_CAPACITY)
+    }
+}
+
+/// Tracing layer recording every event it sees into a [`LogBuffer`].
+#[derive(Debug)]
+pub struct LogLayer {
+    buffer: LogBuffer,
+}
+
+impl<S: Subscriber> Layer<S> for LogLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let metadata = event.metadata();
+
+        let mut visitor = MessageVisitor::default();
+        event.record(&mut visitor);
+
+        self.buffer.push(LogRecord {
+            timestamp: SystemTime::now(),
+            level: (*metadata.level()).into(),
+            target: metadata.target().to_owned(),
+            message: visitor.message,
+        });
+    }
+}
+
+#[derive(Debug, Default)]
+struct MessageVisitor {
+    message: String,
+}
+
+impl Visit for MessageVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if !self.message.is_empty() {
+            self.message.push(' ');
+        }
+
+        let _ = if field.name() == "message" {
+            write!(self.message, "{value:?}")
+        } else {
+            write!(self.message, "{}={value:?}", field.name())
+        };
+    }
+}
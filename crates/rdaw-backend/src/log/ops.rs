@@ -0,0 +1,14 @@
+use rdaw_api::log::{LogFilter, LogOperations, LogRecord, LogRequest, LogResponse};
+use rdaw_api::{BackendProtocol, Result};
+use tracing::instrument;
+
+use crate::Backend;
+
+#[rdaw_rpc::handler(protocol = BackendProtocol, operations = LogOperations)]
+impl Backend {
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn get_log_records(&self, filter: LogFilter) -> Result<Vec<LogRecord>> {
+        Ok(self.log_buffer.records(&filter))
+    }
+}
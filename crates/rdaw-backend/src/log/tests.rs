@@ -0,0 +1,81 @@
+use std::time::SystemTime;
+
+use rdaw_api::log::{LogFilter, LogLevel, LogOperations, LogRecord};
+use rdaw_api::Result;
+use tracing_subscriber::layer::SubscriberExt;
+
+use super::LogBuffer;
+use crate::tests::run_test_with;
+
+fn record(message: &str) -> LogRecord {
+    LogRecord {
+        timestamp: SystemTime::now(),
+        level: LogLevel::Info,
+        target: "rdaw::test".into(),
+        message: message.into(),
+    }
+}
+
+#[test]
+fn bounded_ring() {
+    let buffer = LogBuffer::new(2);
+
+    for message in ["a", "b", "c"] {
+        buffer.push(record(message));
+    }
+
+    let messages = buffer
+        .records(&LogFilter::default())
+        .into_iter()
+        .map(|v| v.message)
+        .collect::<Vec<_>>();
+
+    assert_eq!(messages, ["b", "c"]);
+}
+
+#[test]
+fn log_records() -> Result<()> {
+    let buffer = LogBuffer::new(16);
+    let subscriber = tracing_subscriber::registry().with(buffer.layer());
+    let _guard = tracing::subscriber::set_default(subscriber);
+
+    tracing::info!(target: "rdaw::test", answer = 42, "hello");
+    tracing::debug!(target: "rdaw::other", "world");
+
+    run_test_with(
+        |backend| backend.set_log_buffer(buffer.clone()),
+        |client| async move {
+            let records = client
+                .get_log_records(LogFilter {
+                    target: Some("rdaw::test".into()),
+                    ..LogFilter::default()
+                })
+                .await?;
+
+            assert_eq!(records.len(), 1);
+            assert_eq!(records[0].level, LogLevel::Info);
+            assert_eq!(records[0].message, "hello answer=42");
+
+            let records = client
+                .get_log_records(LogFilter {
+                    max_level: LogLevel::Info,
+                    target: Some("rdaw::".into()),
+                    limit: None,
+                })
+                .await?;
+            assert_eq!(records.len(), 1);
+
+            let records = client
+                .get_log_records(LogFilter {
+                    max_level: LogLevel::Trace,
+                    target: Some("rdaw::".into()),
+                    limit: Some(1),
+                })
+                .await?;
+            assert_eq!(records.len(), 1);
+            assert_eq!(records[0].message, "world");
+
+            Ok(())
+        },
+    )
+}
@@ -0,0 +1,85 @@
+use std::fmt;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use rdaw_api::meter::{MeterFrame, METER_RATE};
+use rdaw_api::Result;
+use rdaw_audio::driver::InStreamDesc;
+use rdaw_core::sync::spsc::TryRecvError;
+
+use crate::engine::{DynDriver, DynInStream, BUFFER_SIZE, SAMPLE_RATE};
+use crate::recording::{ring, CHANNELS};
+use crate::{Backend, DeferredQueue};
+
+/// Input stream kept open while any track is armed, so that input meters work without
+/// recording. The metering thread stops once the monitor is dropped.
+pub struct InputMonitor {
+    _stream: Box<dyn DynInStream>,
+    _thread: JoinHandle<()>,
+}
+
+impl fmt::Debug for InputMonitor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("InputMonitor").finish_non_exhaustive()
+    }
+}
+
+impl InputMonitor {
+    pub fn start(driver: &dyn DynDriver, queue: DeferredQueue) -> Result<InputMonitor> {
+        let (sender, mut receiver) = ring()?;
+        let stream = driver.create_in_stream(InStreamDesc {
+            name: "rdaw-input-meter".into(),
+            sample_rate: SAMPLE_RATE,
+            buffer_size: BUFFER_SIZE,
+            channels: CHANNELS.to_vec(),
+            sender,
+        })?;
+
+        stream.set_active(true)?;
+
+        let num_channels = CHANNELS.len();
+        let interval = (SAMPLE_RATE / METER_RATE) as usize * num_channels;
+
+        let thread = thread::Builder::new()
+            .name("input-meter".into())
+            .spawn(move || {
+                let mut samples = Vec::with_capacity(interval * 2);
+
+                loop {
+                    match receiver.recv_timeout(Duration::from_millis(100)) {
+                        Ok(sample) => samples.push(sample),
+                        Err(TryRecvError::Empty) => continue,
+                        Err(TryRecvError::Closed) => break,
+                    }
+
+                    while let Ok(sample) = receiver.try_recv() {
+                        samples.push(sample);
+                    }
+
+                    // everything captured since the last frame goes into one, so that a late
+                    // thread doesn't send more than the meter rate
+                    let end = samples.len() / interval * interval;
+                    if end == 0 {
+                        continue;
+                    }
+
+                    let (left, right): (Vec<f32>, Vec<f32>) = samples[..end]
+                        .chunks_exact(num_channels)
+                        .map(|frame| (frame[0], frame[1]))
+                        .unzip();
+                    let frame = MeterFrame::measure(&left, &right);
+                    samples.drain(..end);
+
+                    queue.defer(move |this: &mut Backend| {
+                        this.notify_input_meter(frame);
+                        std::future::ready(Ok(()))
+                    });
+                }
+            })?;
+
+        Ok(InputMonitor {
+            _stream: stream,
+            _thread: thread,
+        })
+    }
+}
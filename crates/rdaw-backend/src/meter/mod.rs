@@ -0,0 +1,83 @@
+mod input;
+mod ops;
+#[cfg(test)]
+mod tests;
+
+use rdaw_api::meter::{MeterFrame, StereoFrame};
+
+pub use self::input::InputMonitor;
+use crate::Backend;
+
+impl Backend {
+    pub(crate) fn poll_meters(&mut self) {
+        let Some(engine) = &mut self.engine else {
+            return;
+        };
+
+        let Some(frames) = engine.poll_meters() else {
+            return;
+        };
+
+        for (track_id, frame) in frames {
+            self.subscribers.track_meter.notify(track_id, frame);
+        }
+
+        let Some(samples) = engine.poll_stereo_meter() else {
+            return;
+        };
+
+        let arrangement_id = engine.arrangement_id();
+        let subscribers = &mut self.subscribers.stereo_meter;
+
+        let mut decimations = subscribers
+            .filters(arrangement_id)
+            .copied()
+            .collect::<Vec<_>>();
+        decimations.sort_unstable();
+        decimations.dedup();
+
+        for decimation in decimations {
+            let frame = StereoFrame::measure(&samples, decimation as usize);
+            subscribers.notify_filtered(arrangement_id, frame, |&v| v == decimation);
+        }
+    }
+
+    /// Opens the default input for input meters while any track is armed, and closes it once
+    /// none is.
+    pub(crate) fn update_input_monitor(&mut self) {
+        let is_armed = self.hub.tracks.iter().any(|(_, _, track)| track.armed);
+
+        if !is_armed {
+            self.input_monitor = None;
+            return;
+        }
+
+        let Some(driver) = &self.audio_driver else {
+            return;
+        };
+
+        if self.input_monitor.is_none() {
+            match InputMonitor::start(driver.as_ref(), self.queue.clone()) {
+                Ok(monitor) => self.input_monitor = Some(monitor),
+                Err(error) => tracing::warn!(?error, "failed to open input for metering"),
+            }
+        }
+    }
+
+    /// Tells input meter subscribers of armed tracks about the levels of the input.
+    fn notify_input_meter(&mut self, frame: MeterFrame) {
+        let mut is_armed = false;
+
+        for (track_id, _, track) in self.hub.tracks.iter() {
+            if track.armed {
+                is_armed = true;
+                self.subscribers.input_meter.notify(track_id, frame);
+            }
+        }
+
+        // armed tracks could have been removed along with their document
+        if !is_armed {
+            self.update_input_monitor();
+        }
+    }
+}
@@ -0,0 +1,47 @@
+use rdaw_api::arrangement::ArrangementId;
+use rdaw_api::meter::{MeterOperations, MeterRequest, MeterResponse};
+use rdaw_api::track::TrackId;
+use rdaw_api::{bail, BackendProtocol, ErrorKind, Result};
+use rdaw_rpc::StreamId;
+use tracing::instrument;
+
+use crate::Backend;
+
+#[rdaw_rpc::handler(protocol = BackendProtocol, operations = MeterOperations)]
+impl Backend {
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn subscribe_track_meter(&mut self, track_id: TrackId) -> Result<StreamId> {
+        self.hub.tracks.ensure_has(track_id)?;
+        Ok(self.subscribers.track_meter.subscribe(track_id))
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn subscribe_stereo_meter(
+        &mut self,
+        arrangement_id: ArrangementId,
+        decimation: u32,
+    ) -> Result<StreamId> {
+        self.hub.arrangements.ensure_has(arrangement_id)?;
+
+        if decimation == 0 {
+            bail!(
+                ErrorKind::NotSupported,
+                "stereo meter decimation must be at least 1"
+            );
+        }
+
+        Ok(self
+            .subscribers
+            .stereo_meter
+            .subscribe_filtered(arrangement_id, decimation))
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn subscribe_input_meter(&mut self, track_id: TrackId) -> Result<StreamId> {
+        self.hub.tracks.ensure_has(track_id)?;
+        Ok(self.subscribers.input_meter.subscribe(track_id))
+    }
+}
@@ -0,0 +1,80 @@
+use std::sync::Arc;
+
+use futures::StreamExt;
+use rdaw_api::arrangement::ArrangementId;
+use rdaw_api::document::DocumentOperations;
+use rdaw_api::meter::MeterOperations;
+use rdaw_api::recording::RecordingOperations;
+use rdaw_api::track::{TrackId, TrackOperations};
+use rdaw_api::{assert_err, ErrorKind, Result};
+use rdaw_audio::driver::NullDriver;
+
+use crate::tests::{run_test, run_test_with};
+
+#[test]
+fn subscribe_track_meter() -> Result<()> {
+    run_test(|client| async move {
+        let document_id = client.create_document().await?;
+        let track_id = client.create_track(document_id).await?;
+
+        client.subscribe_track_meter(track_id).await?;
+
+        assert_err!(
+            client.subscribe_track_meter(TrackId::default()).await,
+            ErrorKind::InvalidId
+        );
+
+        Ok(())
+    })
+}
+
+#[test]
+fn subscribe_stereo_meter() -> Result<()> {
+    run_test(|client| async move {
+        let document_id = client.create_document().await?;
+        let arrangement_id = client.get_document_arrangement(document_id).await?;
+
+        client.subscribe_stereo_meter(arrangement_id, 1).await?;
+        client.subscribe_stereo_meter(arrangement_id, 16).await?;
+
+        assert_err!(
+            client.subscribe_stereo_meter(arrangement_id, 0).await,
+            ErrorKind::NotSupported
+        );
+        assert_err!(
+            client
+                .subscribe_stereo_meter(ArrangementId::default(), 1)
+                .await,
+            ErrorKind::InvalidId
+        );
+
+        Ok(())
+    })
+}
+
+#[test]
+fn input_meter_while_stopped() -> Result<()> {
+    run_test_with(
+        |backend| backend.set_audio_driver(Arc::new(NullDriver::new())),
+        |client| async move {
+            let document_id = client.create_document().await?;
+            let track_id = client.create_track(document_id).await?;
+
+            let mut stream = client.subscribe_input_meter(track_id).await?;
+            client.arm_track(track_id, true).await?;
+
+            // the null driver captures silence, without the transport playing
+            let frame = stream.next().await.expect("no input meter frame");
+            assert!(frame.is_silent());
+
+            assert_err!(
+                client.subscribe_input_meter(TrackId::default()).await,
+                ErrorKind::InvalidId
+            );
+
+            client.arm_track(track_id, false).await?;
+
+            Ok(())
+        },
+    )
+}
@@ -0,0 +1,250 @@
+use rdaw_api::arrangement::ArrangementId;
+use rdaw_api::modulation::{
+    ModulationOperations, ModulationRequest, ModulationResponse, ModulationSource, Modulator,
+    ModulatorEvent, ModulatorId,
+};
+use rdaw_api::node::{NodeId, NodeParam};
+use rdaw_api::{bail, format_err, BackendProtocol, ErrorKind, Result};
+use rdaw_core::time::RealTime;
+use rdaw_rpc::StreamId;
+use tracing::instrument;
+
+use crate::Backend;
+
+#[rdaw_rpc::handler(protocol = BackendProtocol, operations = ModulationOperations)]
+impl Backend {
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn subscribe_arrangement_modulators(&mut self, id: ArrangementId) -> Result<StreamId> {
+        self.hub.arrangements.ensure_has(id)?;
+        Ok(self.subscribers.arrangement_modulators.subscribe(id))
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn add_arrangement_modulator(
+        &mut self,
+        id: ArrangementId,
+        modulator: Modulator,
+    ) -> Result<ModulatorId> {
+        self.ensure_modulator_fits(id, &modulator)?;
+
+        let arrangement = self.hub.arrangements.get_mut_or_err(id)?;
+        let modulator_id = arrangement.modulators.insert(modulator.clone());
+
+        self.subscribers.arrangement_modulators.notify(
+            id,
+            ModulatorEvent::Added {
+                id: modulator_id,
+                modulator,
+            },
+        );
+
+        Ok(modulator_id)
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn get_arrangement_modulator(
+        &self,
+        id: ArrangementId,
+        modulator_id: ModulatorId,
+    ) -> Result<Modulator> {
+        let arrangement = self.hub.arrangements.get_or_err(id)?;
+        arrangement
+            .modulators
+            .get(modulator_id)
+            .cloned()
+            .ok_or_else(|| {
+                format_err!(
+                    ErrorKind::InvalidId,
+                    "{modulator_id:?} doesn't exist in {id:?}"
+                )
+            })
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn get_arrangement_modulators(
+        &self,
+        id: ArrangementId,
+    ) -> Result<Vec<(ModulatorId, Modulator)>> {
+        let arrangement = self.hub.arrangements.get_or_err(id)?;
+        Ok(arrangement
+            .modulators
+            .iter()
+            .map(|(modulator_id, modulator)| (modulator_id, modulator.clone()))
+            .collect())
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn set_arrangement_modulator(
+        &mut self,
+        id: ArrangementId,
+        modulator_id: ModulatorId,
+        modulator: Modulator,
+    ) -> Result<()> {
+        self.get_arrangement_modulator(id, modulator_id)?;
+        self.ensure_modulator_fits(id, &modulator)?;
+
+        let arrangement = self.hub.arrangements.get_mut_or_err(id)?;
+        arrangement.modulators[modulator_id] = modulator.clone();
+
+        self.subscribers.arrangement_modulators.notify(
+            id,
+            ModulatorEvent::Changed {
+                id: modulator_id,
+                modulator,
+            },
+        );
+
+        Ok(())
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn remove_arrangement_modulator(
+        &mut self,
+        id: ArrangementId,
+        modulator_id: ModulatorId,
+    ) -> Result<()> {
+        let arrangement = self.hub.arrangements.get_mut_or_err(id)?;
+
+        if arrangement.modulators.remove(modulator_id).is_some() {
+            self.subscribers
+                .arrangement_modulators
+                .notify(id, ModulatorEvent::Removed { id: modulator_id });
+        }
+
+        Ok(())
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn set_arrangement_macro_value(
+        &mut self,
+        id: ArrangementId,
+        modulator_id: ModulatorId,
+        value: f32,
+    ) -> Result<()> {
+        let mut modulator = self.get_arrangement_modulator(id, modulator_id)?;
+
+        let ModulationSource::Macro { value: old_value } = &mut modulator.source else {
+            bail!(ErrorKind::NotSupported, "{modulator_id:?} isn't a macro");
+        };
+
+        if !(0.0..=1.0).contains(&value) {
+            bail!(
+                ErrorKind::NotSupported,
+                "macro value {value} is out of range"
+            );
+        }
+
+        *old_value = value;
+
+        self.set_arrangement_modulator(id, modulator_id, modulator)
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn get_modulated_node_params(&self, id: NodeId) -> Result<Vec<NodeParam>> {
+        let node = self.hub.nodes.get_or_err(id)?;
+        let document_id = self.hub.nodes.get_key_or_err(id)?.document_id;
+        let arrangement_id = self.get_document_arrangement(document_id)?;
+        let arrangement = &self.hub.arrangements[arrangement_id];
+
+        let mut params = node.params.clone();
+        let mut offsets = vec![0.0; params.len()];
+
+        for (modulator_id, modulator) in &arrangement.modulators {
+            let value = self.modulation_value(arrangement_id, modulator_id, modulator);
+
+            for target in modulator.targets.iter().filter(|v| v.node_id == id) {
+                let Some(idx) = params.iter().position(|v| v.name == target.param) else {
+                    continue;
+                };
+
+                offsets[idx] += target.offset(&params[idx], value);
+            }
+        }
+
+        // offsets are summed up before clamping, so that opposite modulators cancel out
+        for (param, offset) in params.iter_mut().zip(offsets) {
+            param.value = param.clamp(param.value + offset);
+        }
+
+        Ok(params)
+    }
+
+    /// Taken from the engine while it's playing the arrangement.
+    fn modulation_value(
+        &self,
+        id: ArrangementId,
+        modulator_id: ModulatorId,
+        modulator: &Modulator,
+    ) -> f32 {
+        let engine_value = self
+            .engine
+            .as_ref()
+            .filter(|v| v.arrangement_id() == id && v.playhead().is_playing())
+            .and_then(|v| v.modulation_value(modulator_id));
+
+        match (modulator.source, engine_value) {
+            (ModulationSource::Macro { value }, _) => value,
+            (_, Some(value)) => value,
+            (ModulationSource::Lfo(lfo), None) => {
+                let position = self
+                    .transports
+                    .get(&id)
+                    .map_or(RealTime::ZERO, |v| v.position);
+                lfo.value_at(position)
+            }
+            // there's nothing to follow when the track isn't rendered
+            (ModulationSource::EnvelopeFollower(_), None) => 0.0,
+        }
+    }
+
+    fn ensure_modulator_fits(&self, id: ArrangementId, modulator: &Modulator) -> Result<()> {
+        if !modulator.is_valid() {
+            bail!(ErrorKind::NotSupported, "invalid modulator {modulator:?}");
+        }
+
+        let document_id = self.hub.arrangements.get_key_or_err(id)?.document_id;
+
+        if let ModulationSource::EnvelopeFollower(follower) = modulator.source {
+            let key = self.hub.tracks.get_key_or_err(follower.track_id)?;
+            if key.document_id != document_id {
+                bail!(
+                    ErrorKind::NotSupported,
+                    "{:?} belongs to another document",
+                    follower.track_id
+                );
+            }
+        }
+
+        // everything is persisted with the arrangement, so targets must be in the same document
+        for target in &modulator.targets {
+            let key = self.hub.nodes.get_key_or_err(target.node_id)?;
+            if key.document_id != document_id {
+                bail!(
+                    ErrorKind::NotSupported,
+                    "{:?} belongs to another document",
+                    target.node_id
+                );
+            }
+
+            let node = self.hub.nodes.get_or_err(target.node_id)?;
+            if node.param(&target.param).is_none() {
+                bail!(
+                    ErrorKind::NotFound,
+                    "{:?} has no parameter `{}`",
+                    target.node_id,
+                    target.param
+                );
+            }
+        }
+
+        Ok(())
+    }
+}
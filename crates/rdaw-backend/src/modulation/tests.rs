@@ -0,0 +1,170 @@
+use futures::StreamExt;
+use rdaw_api::document::DocumentOperations;
+use rdaw_api::modulation::{
+    Lfo, LfoShape, ModulationOperations, ModulationSource, ModulationTarget, Modulator,
+    ModulatorEvent, Polarity,
+};
+use rdaw_api::node::{NodeId, NodeOperations, NodeParam};
+use rdaw_api::{assert_err, ErrorKind, Result};
+
+use crate::tests::run_test;
+
+fn macro_knob(value: f32, targets: Vec<ModulationTarget>) -> Modulator {
+    Modulator {
+        name: "Macro".into(),
+        source: ModulationSource::Macro { value },
+        targets,
+    }
+}
+
+fn target(node_id: NodeId, depth: f32, polarity: Polarity) -> ModulationTarget {
+    ModulationTarget {
+        node_id,
+        param: "cutoff".into(),
+        depth,
+        polarity,
+    }
+}
+
+#[test]
+fn modulators() -> Result<()> {
+    run_test(|client| async move {
+        let document_id = client.create_document().await?;
+        let arrangement_id = client.get_document_arrangement(document_id).await?;
+        let node_id = client
+            .create_node(
+                document_id,
+                "filter".into(),
+                vec![NodeParam::new("cutoff", 0.0, 100.0, 50.0)],
+            )
+            .await?;
+
+        let mut stream = client
+            .subscribe_arrangement_modulators(arrangement_id)
+            .await?;
+
+        let knob = macro_knob(0.0, vec![target(node_id, 0.5, Polarity::Unipolar)]);
+        let modulator_id = client
+            .add_arrangement_modulator(arrangement_id, knob.clone())
+            .await?;
+        assert_eq!(
+            stream.next().await,
+            Some(ModulatorEvent::Added {
+                id: modulator_id,
+                modulator: knob.clone(),
+            })
+        );
+
+        client
+            .set_arrangement_macro_value(arrangement_id, modulator_id, 0.75)
+            .await?;
+
+        let turned = macro_knob(0.75, knob.targets.clone());
+        assert_eq!(
+            stream.next().await,
+            Some(ModulatorEvent::Changed {
+                id: modulator_id,
+                modulator: turned.clone(),
+            })
+        );
+        assert_eq!(
+            client.get_arrangement_modulators(arrangement_id).await?,
+            vec![(modulator_id, turned)]
+        );
+
+        let res = client
+            .set_arrangement_macro_value(arrangement_id, modulator_id, 2.0)
+            .await;
+        assert_err!(res, ErrorKind::NotSupported);
+
+        let res = client
+            .add_arrangement_modulator(
+                arrangement_id,
+                macro_knob(0.0, vec![target(node_id, 1.5, Polarity::Unipolar)]),
+            )
+            .await;
+        assert_err!(res, ErrorKind::NotSupported);
+
+        let mut unknown_param = target(node_id, 0.5, Polarity::Unipolar);
+        unknown_param.param = "resonance".into();
+        let res = client
+            .add_arrangement_modulator(arrangement_id, macro_knob(0.0, vec![unknown_param]))
+            .await;
+        assert_err!(res, ErrorKind::NotFound);
+
+        client
+            .remove_arrangement_modulator(arrangement_id, modulator_id)
+            .await?;
+        assert_eq!(
+            stream.next().await,
+            Some(ModulatorEvent::Removed { id: modulator_id })
+        );
+
+        let res = client
+            .get_arrangement_modulator(arrangement_id, modulator_id)
+            .await;
+        assert_err!(res, ErrorKind::InvalidId);
+
+        Ok(())
+    })
+}
+
+#[test]
+fn modulated_params() -> Result<()> {
+    run_test(|client| async move {
+        let document_id = client.create_document().await?;
+        let arrangement_id = client.get_document_arrangement(document_id).await?;
+        let node_id = client
+            .create_node(
+                document_id,
+                "filter".into(),
+                vec![NodeParam::new("cutoff", 0.0, 100.0, 50.0)],
+            )
+            .await?;
+
+        let cutoff = |params: Vec<NodeParam>| params[0].value;
+
+        let lfo = Modulator {
+            name: "LFO".into(),
+            source: ModulationSource::Lfo(Lfo {
+                shape: LfoShape::Saw,
+                rate: 1.0,
+                phase: 0.5,
+            }),
+            targets: vec![target(node_id, 0.2, Polarity::Unipolar)],
+        };
+        let lfo_id = client
+            .add_arrangement_modulator(arrangement_id, lfo)
+            .await?;
+
+        // the transport is stopped at the start, where the saw is halfway up
+        let params = client.get_modulated_node_params(node_id).await?;
+        assert_eq!(cutoff(params), 60.0);
+
+        let knob = macro_knob(0.5, vec![target(node_id, 1.0, Polarity::Bipolar)]);
+        let knob_id = client
+            .add_arrangement_modulator(arrangement_id, knob)
+            .await?;
+
+        // a bipolar macro in the middle doesn't move the parameter
+        let params = client.get_modulated_node_params(node_id).await?;
+        assert_eq!(cutoff(params), 60.0);
+
+        client
+            .set_arrangement_macro_value(arrangement_id, knob_id, 1.0)
+            .await?;
+        let params = client.get_modulated_node_params(node_id).await?;
+        assert_eq!(cutoff(params), 100.0);
+
+        // the unmodulated value stays where it was
+        let params = client.get_node_params(node_id).await?;
+        assert_eq!(cutoff(params), 50.0);
+
+        let res = client
+            .set_arrangement_macro_value(arrangement_id, lfo_id, 1.0)
+            .await;
+        assert_err!(res, ErrorKind::NotSupported);
+
+        Ok(())
+    })
+}
@@ -0,0 +1,79 @@
+use std::borrow::Cow;
+
+use rdaw_api::node::NodeParam;
+use rdaw_api::Result;
+use serde::{Deserialize, Serialize};
+
+use super::Node;
+use crate::define_version_enum;
+use crate::document::encoding;
+use crate::object::{DeserializationContext, SerializationContext};
+
+pub fn serialize(ctx: &mut SerializationContext<'_>, node: &Node) -> Result<Vec<u8>> {
+    let params = node
+        .params
+        .iter()
+        .map(|param| NodeParamLatest {
+            name: Cow::Borrowed(&param.name),
+            value: param.value,
+            min: param.min,
+            max: param.max,
+            default: param.default,
+        })
+        .collect();
+
+    let raw = NodeLatest {
+        kind: Cow::Borrowed(&node.kind),
+        params,
+    };
+
+    encoding::serialize(ctx.format(), Version::LATEST.as_u32(), &raw)
+}
+
+pub fn deserialize(ctx: &mut DeserializationContext<'_>, data: &[u8]) -> Result<Node> {
+    let (version, data) = encoding::extract_version(ctx.format(), data)?;
+    let raw = match Version::from_u32(version)? {
+        Version::V1 => encoding::deserialize::<NodeV1>(ctx.format(), data)?,
+    };
+
+    let params = raw
+        .params
+        .into_iter()
+        .map(|param| NodeParam {
+            name: param.name.into_owned(),
+            value: param.value,
+            min: param.min,
+            max: param.max,
+            default: param.default,
+        })
+        .collect();
+
+    Ok(Node::new(raw.kind.into_owned(), params))
+}
+
+define_version_enum! {
+    enum Version {
+        V1 = 1,
+    }
+}
+
+type NodeLatest<'a> = NodeV1<'a>;
+type NodeParamLatest<'a> = NodeParamV1<'a>;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct NodeV1<'a> {
+    #[serde(borrow)]
+    kind: Cow<'a, str>,
+    #[serde(borrow)]
+    params: Vec<NodeParamV1<'a>>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct NodeParamV1<'a> {
+    #[serde(borrow)]
+    name: Cow<'a, str>,
+    value: f32,
+    min: f32,
+    max: f32,
+    default: f32,
+}
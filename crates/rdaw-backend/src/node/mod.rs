@@ -0,0 +1,107 @@
+mod encoding;
+mod ops;
+pub mod presets;
+#[cfg(test)]
+mod tests;
+
+use std::collections::BTreeMap;
+use std::mem;
+
+use rdaw_api::node::{CompareSlot, NodeId, NodeParam};
+use rdaw_api::Result;
+
+use crate::object::{DeserializationContext, Object, ObjectId, ObjectType, SerializationContext};
+
+impl ObjectId for NodeId {
+    type Object = Node;
+}
+
+#[derive(Debug, Clone)]
+pub struct Node {
+    pub kind: String,
+    pub params: Vec<NodeParam>,
+    pub compare: CompareState,
+}
+
+impl Node {
+    pub fn new(kind: String, params: Vec<NodeParam>) -> Node {
+        Node {
+            kind,
+            params,
+            compare: CompareState::default(),
+        }
+    }
+
+    pub fn param_values(&self) -> BTreeMap<String, f32> {
+        self.params
+            .iter()
+            .map(|param| (param.name.clone(), param.value))
+            .collect()
+    }
+
+    pub fn param(&self, name: &str) -> Option<&NodeParam> {
+        self.params.iter().find(|param| param.name == name)
+    }
+
+    pub fn param_mut(&mut self, name: &str) -> Option<&mut NodeParam> {
+        self.params.iter_mut().find(|param| param.name == name)
+    }
+}
+
+impl Object for Node {
+    type Id = NodeId;
+
+    const TYPE: ObjectType = ObjectType::Node;
+
+    fn serialize(&self, ctx: &mut SerializationContext<'_>) -> Result<Vec<u8>> {
+        self::encoding::serialize(ctx, self)
+    }
+
+    fn deserialize(ctx: &mut DeserializationContext<'_>, data: &[u8]) -> Result<Self> {
+        self::encoding::deserialize(ctx, data)
+    }
+
+    fn heap_size(&self) -> usize {
+        let params = self
+            .params
+            .iter()
+            .map(|param| param.name.capacity())
+            .sum::<usize>();
+
+        let compare = [&self.compare.a, &self.compare.b]
+            .into_iter()
+            .flatten()
+            .flat_map(|values| values.keys())
+            .map(|name| name.capacity() + mem::size_of::<(String, f32)>())
+            .sum::<usize>();
+
+        self.kind.capacity()
+            + self.params.capacity() * mem::size_of::<NodeParam>()
+            + params
+            + compare
+    }
+}
+
+// A/B slots only live for the session, they aren't saved with the document
+#[derive(Debug, Clone, Default)]
+pub struct CompareState {
+    pub active: CompareSlot,
+    pub a: Option<BTreeMap<String, f32>>,
+    pub b: Option<BTreeMap<String, f32>>,
+}
+
+impl CompareState {
+    pub fn slot(&self, slot: CompareSlot) -> Option<&BTreeMap<String, f32>> {
+        match slot {
+            CompareSlot::A => self.a.as_ref(),
+            CompareSlot::B => self.b.as_ref(),
+        }
+    }
+
+    pub fn slot_mut(&mut self, slot: CompareSlot) -> &mut Option<BTreeMap<String, f32>> {
+        match slot {
+            CompareSlot::A => &mut self.a,
+            CompareSlot::B => &mut self.b,
+        }
+    }
+}
@@ -0,0 +1,359 @@
+use std::collections::BTreeMap;
+
+use rdaw_api::document::DocumentId;
+use rdaw_api::node::{
+    CompareSlot, NodeId, NodeOperations, NodeParam, NodeParamEvent, NodePreset, NodeRequest,
+    NodeResponse, PresetLocation,
+};
+use rdaw_api::{bail, format_err, BackendProtocol, ErrorKind, Result};
+use rdaw_core::collections::HashSet;
+use rdaw_core::path::{Utf8Path, Utf8PathBuf};
+use rdaw_rpc::StreamId;
+use tracing::instrument;
+
+use super::presets::{self, Preset};
+use super::Node;
+use crate::object::ObjectKey;
+use crate::Backend;
+
+#[rdaw_rpc::handler(protocol = BackendProtocol, operations = NodeOperations)]
+impl Backend {
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn create_node(
+        &mut self,
+        document_id: DocumentId,
+        kind: String,
+        params: Vec<NodeParam>,
+    ) -> Result<NodeId> {
+        self.documents.ensure_has(document_id)?;
+        presets::validate_name("node kind", &kind)?;
+
+        let mut names = HashSet::default();
+
+        for param in &params {
+            if !names.insert(param.name.as_str()) {
+                bail!(ErrorKind::NotSupported, "duplicate parameter `{}`", param.name);
+            }
+
+            let is_valid_range = param.min <= param.max
+                && (param.min..=param.max).contains(&param.default)
+                && (param.min..=param.max).contains(&param.value);
+
+            if !is_valid_range {
+                bail!(ErrorKind::NotSupported, "invalid range of parameter `{}`", param.name);
+            }
+        }
+
+        let node = Node::new(kind, params);
+        let id = self
+            .hub
+            .nodes
+            .insert(ObjectKey::new_random(document_id), node);
+
+        Ok(id)
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn subscribe_node_params(&mut self, id: NodeId) -> Result<StreamId> {
+        self.hub.nodes.ensure_has(id)?;
+        Ok(self.subscribers.node_params.subscribe(id))
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn get_node_kind(&self, id: NodeId) -> Result<String> {
+        let node = self.hub.nodes.get_or_err(id)?;
+        Ok(node.kind.clone())
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn get_node_params(&self, id: NodeId) -> Result<Vec<NodeParam>> {
+        let node = self.hub.nodes.get_or_err(id)?;
+        Ok(node.params.clone())
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn set_node_param(&mut self, id: NodeId, name: String, value: f32) -> Result<()> {
+        let node = self.hub.nodes.get_mut_or_err(id)?;
+        let param = node
+            .param_mut(&name)
+            .ok_or_else(|| format_err!(ErrorKind::NotFound, "{id:?} has no parameter `{name}`"))?;
+
+        param.value = param.clamp(value);
+        self.plugins.set_node_param(id, &name, param.value);
+
+        let event = NodeParamEvent {
+            name,
+            value: param.value,
+        };
+
+        self.subscribers.node_params.notify(id, event);
+
+        Ok(())
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn list_node_presets(
+        &self,
+        document_id: DocumentId,
+        kind: String,
+    ) -> Result<Vec<NodePreset>> {
+        presets::validate_name("node kind", &kind)?;
+
+        let mut list = Vec::new();
+
+        if let Some(dir) = &self.user_preset_dir {
+            for name in presets::list_user_presets(dir, &kind)? {
+                list.push(NodePreset {
+                    name,
+                    location: PresetLocation::User,
+                });
+            }
+        }
+
+        let arrangement_id = self.get_document_arrangement(document_id)?;
+        let arrangement = &self.hub.arrangements[arrangement_id];
+
+        for preset in &arrangement.presets {
+            if preset.kind == kind {
+                list.push(NodePreset {
+                    name: preset.name.clone(),
+                    location: PresetLocation::Document,
+                });
+            }
+        }
+
+        list.sort();
+
+        Ok(list)
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn save_node_preset(
+        &mut self,
+        id: NodeId,
+        name: String,
+        location: PresetLocation,
+    ) -> Result<()> {
+        presets::validate_name("preset", &name)?;
+
+        let node = self.hub.nodes.get_or_err(id)?;
+        let preset = Preset::from_node(name, node);
+
+        match location {
+            PresetLocation::User => {
+                let dir = self.user_preset_dir_or_err()?;
+                presets::write_user_preset(dir, &preset)?;
+            }
+            PresetLocation::Document => {
+                let document_id = self.hub.nodes.get_key_or_err(id)?.document_id;
+                let arrangement_id = self.get_document_arrangement(document_id)?;
+                let arrangement = &mut self.hub.arrangements[arrangement_id];
+
+                let existing = arrangement
+                    .presets
+                    .iter_mut()
+                    .find(|v| v.kind == preset.kind && v.name == preset.name);
+
+                match existing {
+                    Some(existing) => *existing = preset,
+                    None => arrangement.presets.push(preset),
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn apply_node_preset(
+        &mut self,
+        id: NodeId,
+        name: String,
+        location: PresetLocation,
+    ) -> Result<()> {
+        presets::validate_name("preset", &name)?;
+
+        let node = self.hub.nodes.get_or_err(id)?;
+        let document_id = self.hub.nodes.get_key_or_err(id)?.document_id;
+        let preset = self.find_preset(document_id, &node.kind, &name, location)?;
+
+        self.apply_node_param_values(id, &preset.params);
+
+        Ok(())
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn remove_node_preset(
+        &mut self,
+        document_id: DocumentId,
+        kind: String,
+        name: String,
+        location: PresetLocation,
+    ) -> Result<()> {
+        presets::validate_name("node kind", &kind)?;
+        presets::validate_name("preset", &name)?;
+
+        match location {
+            PresetLocation::User => {
+                let dir = self.user_preset_dir_or_err()?;
+                presets::remove_user_preset(dir, &kind, &name)?;
+            }
+            PresetLocation::Document => {
+                let arrangement_id = self.get_document_arrangement(document_id)?;
+                let arrangement = &mut self.hub.arrangements[arrangement_id];
+
+                let Some(idx) = arrangement
+                    .presets
+                    .iter()
+                    .position(|v| v.kind == kind && v.name == name)
+                else {
+                    bail!(ErrorKind::NotFound, "preset `{name}` doesn't exist");
+                };
+
+                arrangement.presets.remove(idx);
+            }
+        }
+
+        Ok(())
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn get_node_compare_slot(&self, id: NodeId) -> Result<CompareSlot> {
+        let node = self.hub.nodes.get_or_err(id)?;
+        Ok(node.compare.active)
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn store_node_compare_slot(&mut self, id: NodeId, slot: CompareSlot) -> Result<()> {
+        let node = self.hub.nodes.get_mut_or_err(id)?;
+        *node.compare.slot_mut(slot) = Some(node.param_values());
+        Ok(())
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn copy_node_compare_slot(&mut self, id: NodeId, from: CompareSlot) -> Result<()> {
+        let node = self.hub.nodes.get_mut_or_err(id)?;
+
+        // the active slot always reflects the live parameters
+        let values = match node.compare.slot(from) {
+            Some(values) if from != node.compare.active => values.clone(),
+            _ => node.param_values(),
+        };
+
+        let to = from.other();
+        *node.compare.slot_mut(to) = Some(values.clone());
+
+        if to == node.compare.active {
+            self.apply_node_param_values(id, &values);
+        }
+
+        Ok(())
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn toggle_node_compare(&mut self, id: NodeId) -> Result<CompareSlot> {
+        let node = self.hub.nodes.get_mut_or_err(id)?;
+
+        let current = node.param_values();
+        let active = node.compare.active;
+        let next = active.other();
+
+        *node.compare.slot_mut(active) = Some(current.clone());
+        node.compare.active = next;
+
+        // an empty slot starts out as a copy of the other one
+        let values = node.compare.slot_mut(next).get_or_insert(current).clone();
+        self.apply_node_param_values(id, &values);
+
+        Ok(next)
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn set_user_preset_dir(&mut self, path: Option<Utf8PathBuf>) -> Result<()> {
+        if self.safe_mode && path.is_some() {
+            tracing::warn!("safe mode is on, ignoring user presets");
+            return Ok(());
+        }
+
+        self.user_preset_dir = path;
+        Ok(())
+    }
+
+    fn apply_node_param_values(&mut self, id: NodeId, values: &BTreeMap<String, f32>) {
+        let Some(node) = self.hub.nodes.get_mut(id) else {
+            return;
+        };
+
+        // parameters the node doesn't know about are ignored, so that presets survive changes
+        // to the parameter set of a node kind
+        for (name, &value) in values {
+            let Some(param) = node.param_mut(name) else {
+                continue;
+            };
+
+            let value = param.clamp(value);
+            if param.value == value {
+                continue;
+            }
+
+            param.value = value;
+            self.plugins.set_node_param(id, name, value);
+
+            let event = NodeParamEvent {
+                name: name.clone(),
+                value,
+            };
+
+            self.subscribers.node_params.notify(id, event);
+        }
+    }
+
+    pub(crate) fn user_preset_dir_or_err(&self) -> Result<&Utf8Path> {
+        self.user_preset_dir
+            .as_deref()
+            .ok_or_else(|| format_err!(ErrorKind::NotFound, "user preset directory isn't set"))
+    }
+
+    fn find_preset(
+        &self,
+        document_id: DocumentId,
+        kind: &str,
+        name: &str,
+        location: PresetLocation,
+    ) -> Result<Preset> {
+        match location {
+            PresetLocation::User => {
+                let dir = self.user_preset_dir_or_err()?;
+                presets::read_user_preset(dir, kind, name)
+            }
+            PresetLocation::Document => {
+                let arrangement_id = self.get_document_arrangement(document_id)?;
+                let arrangement = &self.hub.arrangements[arrangement_id];
+
+                let preset = arrangement
+                    .presets
+                    .iter()
+                    .find(|v| v.kind == kind && v.name == name)
+                    .ok_or_else(|| {
+                        format_err!(ErrorKind::NotFound, "preset `{name}` doesn't exist")
+                    })?;
+
+                Ok(preset.clone())
+            }
+        }
+    }
+}
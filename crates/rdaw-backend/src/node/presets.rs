@@ -0,0 +1,156 @@
+//! User presets are stored as JSON files in `<dir>/<kind>/<name>.json`:
+//!
+//! ```json
+//! { "version": 1, "kind": "gain", "name": "Quiet", "params": { "gain": 0.25 } }
+//! ```
+//!
+//! Document presets are stored in the root arrangement.
+
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Write};
+
+use rdaw_api::error::ResultExt;
+use rdaw_api::{bail, ErrorKind, Result};
+use rdaw_core::path::{Utf8Path, Utf8PathBuf};
+use serde::{Deserialize, Serialize};
+
+use super::Node;
+use crate::define_version_enum;
+
+const EXTENSION: &str = "json";
+
+define_version_enum! {
+    enum Version {
+        V1 = 1,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Preset {
+    pub kind: String,
+    pub name: String,
+    pub params: BTreeMap<String, f32>,
+}
+
+impl Preset {
+    pub fn from_node(name: String, node: &Node) -> Preset {
+        Preset {
+            kind: node.kind.clone(),
+            name,
+            params: node.param_values(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct PresetFile {
+    version: u32,
+    #[serde(flatten)]
+    preset: Preset,
+}
+
+pub fn validate_name(what: &str, name: &str) -> Result<()> {
+    let is_invalid = name.is_empty()
+        || name.starts_with('.')
+        || name.trim() != name
+        || name.chars().any(|c| c.is_control() || matches!(c, '/' | '\\'));
+
+    if is_invalid {
+        bail!(ErrorKind::NotSupported, "invalid {what} name `{name}`");
+    }
+
+    Ok(())
+}
+
+pub fn list_user_presets(dir: &Utf8Path, kind: &str) -> Result<Vec<String>> {
+    let dir = dir.join(kind);
+
+    let entries = match fs::read_dir(&dir) {
+        Ok(v) => v,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e).with_context(|| format!("failed to read `{dir}`")),
+    };
+
+    let mut names = Vec::new();
+
+    for entry in entries {
+        let path = entry?.path();
+        let Some(path) = Utf8Path::from_path(&path) else {
+            continue;
+        };
+
+        if path.extension() != Some(EXTENSION) {
+            continue;
+        }
+
+        if let Some(name) = path.file_stem() {
+            if validate_name("preset", name).is_ok() {
+                names.push(name.to_owned());
+            }
+        }
+    }
+
+    names.sort();
+
+    Ok(names)
+}
+
+pub fn read_user_preset(dir: &Utf8Path, kind: &str, name: &str) -> Result<Preset> {
+    let path = preset_path(dir, kind, name);
+
+    let file = File::open(&path).with_context(|| format!("failed to open `{path}`"))?;
+    let raw = serde_json::from_reader::<_, PresetFile>(BufReader::new(file))
+        .convert_err(ErrorKind::Deserialization)?;
+
+    Version::from_u32(raw.version)?;
+
+    if raw.preset.kind != kind {
+        bail!(
+            ErrorKind::InvalidType,
+            "preset `{path}` is for `{}`, not `{kind}`",
+            raw.preset.kind,
+        );
+    }
+
+    Ok(Preset {
+        name: name.to_owned(),
+        ..raw.preset
+    })
+}
+
+pub fn write_user_preset(dir: &Utf8Path, preset: &Preset) -> Result<()> {
+    let path = preset_path(dir, &preset.kind, &preset.name);
+    let kind_dir = dir.join(&preset.kind);
+
+    fs::create_dir_all(&kind_dir).with_context(|| format!("failed to create `{kind_dir}`"))?;
+
+    let raw = PresetFile {
+        version: Version::LATEST.as_u32(),
+        preset: preset.clone(),
+    };
+
+    // write to a hidden temporary file first, so that a crash never leaves a truncated preset
+    let tmp_path = kind_dir.join(format!(".{}.{EXTENSION}.tmp", preset.name));
+
+    let mut writer = BufWriter::new(
+        File::create(&tmp_path).with_context(|| format!("failed to create `{tmp_path}`"))?,
+    );
+    serde_json::to_writer_pretty(&mut writer, &raw).convert_err(ErrorKind::Serialization)?;
+    writer.flush()?;
+    drop(writer);
+
+    fs::rename(&tmp_path, &path).with_context(|| format!("failed to write `{path}`"))?;
+
+    Ok(())
+}
+
+pub fn remove_user_preset(dir: &Utf8Path, kind: &str, name: &str) -> Result<()> {
+    let path = preset_path(dir, kind, name);
+    fs::remove_file(&path).with_context(|| format!("failed to remove `{path}`"))?;
+    Ok(())
+}
+
+fn preset_path(dir: &Utf8Path, kind: &str, name: &str) -> Utf8PathBuf {
+    dir.join(kind).join(format!("{name}.{EXTENSION}"))
+}
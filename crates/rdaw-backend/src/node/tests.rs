@@ -0,0 +1,320 @@
+use futures::StreamExt;
+use rdaw_api::document::DocumentOperations;
+use rdaw_api::node::{
+    CompareSlot, NodeOperations, NodeParam, NodeParamEvent, NodePreset, PresetLocation,
+};
+use rdaw_api::track::TrackOperations;
+use rdaw_api::{assert_err, ErrorKind, Result};
+use rdaw_core::path::Utf8Path;
+
+use crate::tests::{run_test, run_test_with};
+
+fn gain_params() -> Vec<NodeParam> {
+    vec![
+        NodeParam::new("gain", 0.0, 2.0, 1.0),
+        NodeParam::new("pan", -1.0, 1.0, 0.0),
+    ]
+}
+
+#[test]
+fn node_params() -> Result<()> {
+    run_test(|client| async move {
+        let document_id = client.create_document().await?;
+        let node_id = client
+            .create_node(document_id, "gain".into(), gain_params())
+            .await?;
+
+        assert_eq!(client.get_node_kind(node_id).await?, "gain");
+
+        let mut stream = client.subscribe_node_params(node_id).await?;
+
+        client.set_node_param(node_id, "gain".into(), 5.0).await?;
+        assert_eq!(
+            stream.next().await,
+            Some(NodeParamEvent {
+                name: "gain".into(),
+                value: 2.0,
+            })
+        );
+
+        let params = client.get_node_params(node_id).await?;
+        assert_eq!(params[0].value, 2.0);
+        assert_eq!(params[1].value, 0.0);
+
+        assert_err!(
+            client.set_node_param(node_id, "width".into(), 1.0).await,
+            ErrorKind::NotFound
+        );
+
+        let duplicate = vec![NodeParam::new("gain", 0.0, 1.0, 0.5); 2];
+        assert_err!(
+            client.create_node(document_id, "gain".into(), duplicate).await,
+            ErrorKind::NotSupported
+        );
+
+        Ok(())
+    })
+}
+
+#[test]
+fn compare_slots() -> Result<()> {
+    run_test(|client| async move {
+        let document_id = client.create_document().await?;
+        let node_id = client
+            .create_node(document_id, "gain".into(), gain_params())
+            .await?;
+
+        let gain = |params: Vec<NodeParam>| params[0].value;
+
+        assert_eq!(client.get_node_compare_slot(node_id).await?, CompareSlot::A);
+
+        client.set_node_param(node_id, "gain".into(), 0.5).await?;
+        assert_eq!(client.toggle_node_compare(node_id).await?, CompareSlot::B);
+        assert_eq!(gain(client.get_node_params(node_id).await?), 0.5);
+
+        client.set_node_param(node_id, "gain".into(), 1.5).await?;
+        assert_eq!(client.toggle_node_compare(node_id).await?, CompareSlot::A);
+        assert_eq!(gain(client.get_node_params(node_id).await?), 0.5);
+
+        assert_eq!(client.toggle_node_compare(node_id).await?, CompareSlot::B);
+        assert_eq!(gain(client.get_node_params(node_id).await?), 1.5);
+
+        client
+            .copy_node_compare_slot(node_id, CompareSlot::A)
+            .await?;
+        assert_eq!(gain(client.get_node_params(node_id).await?), 0.5);
+
+        client.set_node_param(node_id, "gain".into(), 0.25).await?;
+        client
+            .store_node_compare_slot(node_id, CompareSlot::A)
+            .await?;
+        client.set_node_param(node_id, "gain".into(), 2.0).await?;
+        assert_eq!(client.toggle_node_compare(node_id).await?, CompareSlot::A);
+        assert_eq!(gain(client.get_node_params(node_id).await?), 0.25);
+
+        Ok(())
+    })
+}
+
+#[test]
+fn track_nodes() -> Result<()> {
+    run_test(|client| async move {
+        let document_id = client.create_document().await?;
+        let track_id = client.create_track(document_id).await?;
+
+        let first = client
+            .create_node(document_id, "gain".into(), gain_params())
+            .await?;
+        let second = client
+            .create_node(document_id, "gain".into(), gain_params())
+            .await?;
+
+        client.insert_track_node(track_id, first, 0).await?;
+        client.insert_track_node(track_id, second, 0).await?;
+        assert_eq!(client.get_track_nodes(track_id).await?, vec![second, first]);
+
+        assert_err!(
+            client.insert_track_node(track_id, first, 0).await,
+            ErrorKind::NotSupported
+        );
+        assert_err!(
+            client.remove_track_node(track_id, 2).await,
+            ErrorKind::IndexOutOfBounds
+        );
+
+        client.remove_track_node(track_id, 0).await?;
+        assert_eq!(client.get_track_nodes(track_id).await?, vec![first]);
+
+        Ok(())
+    })
+}
+
+#[test]
+fn document_presets() -> Result<()> {
+    run_test(|client| async move {
+        let document_id = client.create_document().await?;
+        let node_id = client
+            .create_node(document_id, "gain".into(), gain_params())
+            .await?;
+
+        client.set_node_param(node_id, "gain".into(), 0.5).await?;
+        client
+            .save_node_preset(node_id, "Quiet".into(), PresetLocation::Document)
+            .await?;
+
+        assert_eq!(
+            client
+                .list_node_presets(document_id, "gain".into())
+                .await?,
+            vec![NodePreset {
+                name: "Quiet".into(),
+                location: PresetLocation::Document,
+            }]
+        );
+        assert!(client
+            .list_node_presets(document_id, "reverb".into())
+            .await?
+            .is_empty());
+
+        client.set_node_param(node_id, "gain".into(), 1.5).await?;
+        client
+            .apply_node_preset(node_id, "Quiet".into(), PresetLocation::Document)
+            .await?;
+        assert_eq!(client.get_node_params(node_id).await?[0].value, 0.5);
+
+        client
+            .remove_node_preset(
+                document_id,
+                "gain".into(),
+                "Quiet".into(),
+                PresetLocation::Document,
+            )
+            .await?;
+        assert_err!(
+            client
+                .apply_node_preset(node_id, "Quiet".into(), PresetLocation::Document)
+                .await,
+            ErrorKind::NotFound
+        );
+
+        Ok(())
+    })
+}
+
+#[test]
+fn user_presets() -> Result<()> {
+    run_test(|client| async move {
+        let document_id = client.create_document().await?;
+        let node_id = client
+            .create_node(document_id, "gain".into(), gain_params())
+            .await?;
+
+        assert_err!(
+            client
+                .save_node_preset(node_id, "Loud".into(), PresetLocation::User)
+                .await,
+            ErrorKind::NotFound
+        );
+
+        let dir = tempfile::tempdir()?;
+        let dir = Utf8Path::from_path(dir.path()).unwrap();
+        client.set_user_preset_dir(Some(dir.to_owned())).await?;
+
+        client.set_node_param(node_id, "pan".into(), -0.5).await?;
+        client
+            .save_node_preset(node_id, "Left".into(), PresetLocation::User)
+            .await?;
+
+        assert!(dir.join("gain").join("Left.json").exists());
+        assert_eq!(
+            client
+                .list_node_presets(document_id, "gain".into())
+                .await?,
+            vec![NodePreset {
+                name: "Left".into(),
+                location: PresetLocation::User,
+            }]
+        );
+
+        // unknown parameters are ignored, known ones are clamped
+        std::fs::write(
+            dir.join("gain").join("Wide.json"),
+            r#"{"version":1,"kind":"gain","name":"Wide","params":{"pan":3.0,"width":1.0}}"#,
+        )?;
+
+        let mut stream = client.subscribe_node_params(node_id).await?;
+        client
+            .apply_node_preset(node_id, "Wide".into(), PresetLocation::User)
+            .await?;
+        assert_eq!(
+            stream.next().await,
+            Some(NodeParamEvent {
+                name: "pan".into(),
+                value: 1.0,
+            })
+        );
+
+        client
+            .apply_node_preset(node_id, "Left".into(), PresetLocation::User)
+            .await?;
+        assert_eq!(client.get_node_params(node_id).await?[1].value, -0.5);
+
+        client
+            .remove_node_preset(
+                document_id,
+                "gain".into(),
+                "Wide".into(),
+                PresetLocation::User,
+            )
+            .await?;
+        assert_eq!(
+            client
+                .list_node_presets(document_id, "gain".into())
+                .await?
+                .len(),
+            1
+        );
+
+        Ok(())
+    })
+}
+
+#[test]
+fn safe_mode_skips_user_presets() -> Result<()> {
+    run_test_with(
+        |backend| backend.set_safe_mode(true),
+        |client| async move {
+            let document_id = client.create_document().await?;
+            let node_id = client
+                .create_node(document_id, "gain".into(), gain_params())
+                .await?;
+
+            let dir = tempfile::tempdir()?;
+            let dir = Utf8Path::from_path(dir.path()).unwrap();
+            std::fs::create_dir(dir.join("gain"))?;
+            std::fs::write(dir.join("gain").join("Broken.json"), "{")?;
+
+            client.set_user_preset_dir(Some(dir.to_owned())).await?;
+            assert!(client
+                .list_node_presets(document_id, "gain".into())
+                .await?
+                .is_empty());
+            assert_err!(
+                client
+                    .save_node_preset(node_id, "Loud".into(), PresetLocation::User)
+                    .await,
+                ErrorKind::NotFound
+            );
+
+            Ok(())
+        },
+    )
+}
+
+#[test]
+fn invalid_preset_names() -> Result<()> {
+    run_test(|client| async move {
+        let document_id = client.create_document().await?;
+        let node_id = client
+            .create_node(document_id, "gain".into(), gain_params())
+            .await?;
+
+        for name in ["", ".hidden", "../escape", "a/b", " padded"] {
+            assert_err!(
+                client
+                    .save_node_preset(node_id, name.into(), PresetLocation::Document)
+                    .await,
+                ErrorKind::NotSupported
+            );
+        }
+
+        assert_err!(
+            client
+                .create_node(document_id, "../gain".into(), Vec::new())
+                .await,
+            ErrorKind::NotSupported
+        );
+
+        Ok(())
+    })
+}
@@ -1,16 +1,23 @@
+use std::collections::BTreeMap;
 use std::io::{Read, Write};
 
 use rdaw_api::document::DocumentId;
 use rdaw_api::{bail, ErrorKind, Result};
+use rdaw_core::collections::{HashMap, HashSet};
+use rdaw_core::path::{Utf8Path, Utf8PathBuf};
 use rdaw_core::Uuid;
 use slotmap::KeyData;
 
 use super::{Hub, Object, ObjectId, ObjectKey, ObjectType, StorageRef};
 use crate::arrangement::Arrangement;
-use crate::asset::Asset;
+use crate::asset::{Asset, PathVariables};
+use crate::automation::AutomationLane;
+use crate::bus::Bus;
+use crate::document::encoding::Format;
 use crate::document::{Compression, DocumentStorage};
-use crate::item::AudioItem;
-use crate::source::AudioSource;
+use crate::item::{AudioItem, MidiItem};
+use crate::node::Node;
+use crate::source::{AudioSource, MidiSource};
 use crate::tempo_map::TempoMap;
 use crate::track::Track;
 
@@ -18,16 +25,71 @@ use crate::track::Track;
 pub struct SerializationContext<'a> {
     hub: &'a Hub,
     documents: &'a DocumentStorage,
+    path_variables: &'a PathVariables,
+    base_dir: Option<&'a Utf8Path>,
     document_id: DocumentId,
     deps: Vec<(ObjectType, Uuid, KeyData)>,
+    format: Format,
+    exported: Option<BTreeMap<Uuid, Vec<u8>>>,
 }
 
 impl SerializationContext<'_> {
     pub fn serialize<I: ObjectId>(
         hub: &mut Hub,
         documents: &DocumentStorage,
+        path_variables: &PathVariables,
+        base_dir: Option<&Utf8Path>,
         root_id: I,
     ) -> Result<Uuid>
+    where
+        I::Object: StorageRef,
+    {
+        let (root_uuid, _) = SerializationContext::serialize_inner(
+            hub,
+            documents,
+            path_variables,
+            base_dir,
+            root_id,
+            Format::Binary,
+            None,
+        )?;
+
+        Ok(root_uuid)
+    }
+
+    pub fn export<I: ObjectId>(
+        hub: &mut Hub,
+        documents: &DocumentStorage,
+        path_variables: &PathVariables,
+        base_dir: Option<&Utf8Path>,
+        root_id: I,
+        format: Format,
+    ) -> Result<(Uuid, BTreeMap<Uuid, Vec<u8>>)>
+    where
+        I::Object: StorageRef,
+    {
+        let (root_uuid, exported) = SerializationContext::serialize_inner(
+            hub,
+            documents,
+            path_variables,
+            base_dir,
+            root_id,
+            format,
+            Some(BTreeMap::new()),
+        )?;
+
+        Ok((root_uuid, exported.unwrap_or_default()))
+    }
+
+    fn serialize_inner<I: ObjectId>(
+        hub: &mut Hub,
+        documents: &DocumentStorage,
+        path_variables: &PathVariables,
+        base_dir: Option<&Utf8Path>,
+        root_id: I,
+        format: Format,
+        exported: Option<BTreeMap<Uuid, Vec<u8>>>,
+    ) -> Result<(Uuid, Option<BTreeMap<Uuid, Vec<u8>>>)>
     where
         I::Object: StorageRef,
     {
@@ -39,14 +101,22 @@ impl SerializationContext<'_> {
         let mut ctx = SerializationContext {
             hub,
             documents,
+            path_variables,
+            base_dir,
             document_id,
             deps: Vec::new(),
+            format,
+            exported,
         };
 
         let root_uuid = ctx.add_dep(root_id)?;
         ctx.serialize_loop()?;
 
-        Ok(root_uuid)
+        Ok((root_uuid, ctx.exported))
+    }
+
+    pub fn format(&self) -> Format {
+        self.format
     }
 
     pub fn add_dep<I: ObjectId>(&mut self, id: I) -> Result<Uuid>
@@ -59,6 +129,10 @@ impl SerializationContext<'_> {
         Ok(key.uuid)
     }
 
+    pub fn portable_path(&self, path: &Utf8Path) -> String {
+        self.path_variables.shorten(self.base_dir, path)
+    }
+
     fn serialize_loop(&mut self) -> Result<()> {
         while let Some((ty, uuid, id)) = self.deps.pop() {
             match ty {
@@ -66,6 +140,13 @@ impl SerializationContext<'_> {
                 ObjectType::Asset => self.serialize_obj::<Asset>(uuid, id.into())?,
                 ObjectType::AudioItem => self.serialize_obj::<AudioItem>(uuid, id.into())?,
                 ObjectType::AudioSource => self.serialize_obj::<AudioSource>(uuid, id.into())?,
+                ObjectType::AutomationLane => {
+                    self.serialize_obj::<AutomationLane>(uuid, id.into())?
+                }
+                ObjectType::Bus => self.serialize_obj::<Bus>(uuid, id.into())?,
+                ObjectType::MidiItem => self.serialize_obj::<MidiItem>(uuid, id.into())?,
+                ObjectType::MidiSource => self.serialize_obj::<MidiSource>(uuid, id.into())?,
+                ObjectType::Node => self.serialize_obj::<Node>(uuid, id.into())?,
                 ObjectType::Track => self.serialize_obj::<Track>(uuid, id.into())?,
                 ObjectType::TempoMap => self.serialize_obj::<TempoMap>(uuid, id.into())?,
             }
@@ -79,6 +160,11 @@ impl SerializationContext<'_> {
         let object = storage.get_or_err(id)?;
         let data = object.serialize(self)?;
 
+        if let Some(exported) = &mut self.exported {
+            exported.insert(uuid, data);
+            return Ok(());
+        }
+
         let document = self.documents.get_or_err(self.document_id)?;
 
         let mut blob = document.create_blob(Compression::None)?;
@@ -95,16 +181,141 @@ impl SerializationContext<'_> {
 pub struct DeserializationContext<'a> {
     hub: &'a mut Hub,
     documents: &'a DocumentStorage,
+    path_variables: &'a PathVariables,
+    base_dir: Option<Utf8PathBuf>,
     document_id: DocumentId,
     deps: Vec<(ObjectType, Uuid, KeyData)>,
+    format: Format,
+    imported: Option<&'a HashMap<Uuid, Vec<u8>>>,
+    /// When reloading, uuids of objects which were already queued for deserialization.
+    reloaded: Option<HashSet<Uuid>>,
+    /// Whether objects missing from the document are left out instead of failing.
+    skip_missing: bool,
 }
 
 impl DeserializationContext<'_> {
     pub fn deserialize<I: ObjectId>(
         hub: &mut Hub,
         documents: &DocumentStorage,
+        path_variables: &PathVariables,
+        document_id: DocumentId,
+        root_uuid: Uuid,
+    ) -> Result<I>
+    where
+        I::Object: StorageRef,
+    {
+        DeserializationContext::deserialize_inner(
+            hub,
+            documents,
+            path_variables,
+            document_id,
+            root_uuid,
+            None,
+            false,
+        )
+    }
+
+    /// Like [`DeserializationContext::deserialize`], but objects missing from the document,
+    /// other than the root, are left out. Their ids stay reserved without an object, so that
+    /// references to them can be found by [`super::validate`].
+    pub fn deserialize_partial<I: ObjectId>(
+        hub: &mut Hub,
+        documents: &DocumentStorage,
+        path_variables: &PathVariables,
+        document_id: DocumentId,
+        root_uuid: Uuid,
+    ) -> Result<I>
+    where
+        I::Object: StorageRef,
+    {
+        DeserializationContext::deserialize_inner(
+            hub,
+            documents,
+            path_variables,
+            document_id,
+            root_uuid,
+            None,
+            true,
+        )
+    }
+
+    /// Like [`DeserializationContext::deserialize`], but objects which are already loaded are
+    /// replaced with their stored versions, keeping their ids.
+    pub fn reload<I: ObjectId>(
+        hub: &mut Hub,
+        documents: &DocumentStorage,
+        path_variables: &PathVariables,
+        document_id: DocumentId,
+        root_uuid: Uuid,
+    ) -> Result<I>
+    where
+        I::Object: StorageRef,
+    {
+        DeserializationContext::deserialize_inner(
+            hub,
+            documents,
+            path_variables,
+            document_id,
+            root_uuid,
+            Some(HashSet::default()),
+            false,
+        )
+    }
+
+    fn deserialize_inner<I: ObjectId>(
+        hub: &mut Hub,
+        documents: &DocumentStorage,
+        path_variables: &PathVariables,
+        document_id: DocumentId,
+        root_uuid: Uuid,
+        reloaded: Option<HashSet<Uuid>>,
+        skip_missing: bool,
+    ) -> Result<I>
+    where
+        I::Object: StorageRef,
+    {
+        let base_dir = documents
+            .get_or_err(document_id)?
+            .path()
+            .and_then(|path| path.parent())
+            .map(|path| path.to_path_buf());
+
+        let mut ctx = DeserializationContext {
+            hub,
+            documents,
+            path_variables,
+            base_dir,
+            document_id,
+            deps: Vec::new(),
+            format: Format::Binary,
+            imported: None,
+            reloaded,
+            skip_missing,
+        };
+
+        let root_id = ctx.add_dep::<I>(root_uuid)?;
+        ctx.deserialize_loop()?;
+
+        if !ctx.hub.storage::<I::Object>().has(root_id) {
+            bail!(
+                ErrorKind::InvalidUuid,
+                "object {root_uuid} doesn't exist in the document"
+            );
+        }
+
+        Ok(root_id)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn import<I: ObjectId>(
+        hub: &mut Hub,
+        documents: &DocumentStorage,
+        path_variables: &PathVariables,
+        base_dir: Option<&Utf8Path>,
         document_id: DocumentId,
         root_uuid: Uuid,
+        objects: &HashMap<Uuid, Vec<u8>>,
+        format: Format,
     ) -> Result<I>
     where
         I::Object: StorageRef,
@@ -112,8 +323,14 @@ impl DeserializationContext<'_> {
         let mut ctx = DeserializationContext {
             hub,
             documents,
+            path_variables,
+            base_dir: base_dir.map(|path| path.to_path_buf()),
             document_id,
             deps: Vec::new(),
+            format,
+            imported: Some(objects),
+            reloaded: None,
+            skip_missing: false,
         };
 
         let root_id = ctx.add_dep::<I>(root_uuid)?;
@@ -122,6 +339,10 @@ impl DeserializationContext<'_> {
         Ok(root_id)
     }
 
+    pub fn format(&self) -> Format {
+        self.format
+    }
+
     pub fn add_dep<I: ObjectId>(&mut self, uuid: Uuid) -> Result<I>
     where
         I::Object: StorageRef,
@@ -130,15 +351,29 @@ impl DeserializationContext<'_> {
         let key = ObjectKey::new(self.document_id, uuid);
 
         if let Some(id) = storage.get_id(key) {
+            if let Some(reloaded) = &mut self.reloaded {
+                if reloaded.insert(uuid) {
+                    self.deps.push((I::Object::TYPE, uuid, id.data()));
+                }
+            }
+
             return Ok(id);
         }
 
         let id = storage.prepare_insert(key);
         self.deps.push((I::Object::TYPE, uuid, id.data()));
 
+        if let Some(reloaded) = &mut self.reloaded {
+            reloaded.insert(uuid);
+        }
+
         Ok(id)
     }
 
+    pub fn resolve_path(&self, path: &str) -> Result<Utf8PathBuf> {
+        self.path_variables.expand(self.base_dir.as_deref(), path)
+    }
+
     fn deserialize_loop(&mut self) -> Result<()> {
         while let Some((ty, uuid, id)) = self.deps.pop() {
             match ty {
@@ -146,6 +381,13 @@ impl DeserializationContext<'_> {
                 ObjectType::Asset => self.deserialize_obj::<Asset>(uuid, id.into())?,
                 ObjectType::AudioItem => self.deserialize_obj::<AudioItem>(uuid, id.into())?,
                 ObjectType::AudioSource => self.deserialize_obj::<AudioSource>(uuid, id.into())?,
+                ObjectType::AutomationLane => {
+                    self.deserialize_obj::<AutomationLane>(uuid, id.into())?
+                }
+                ObjectType::Bus => self.deserialize_obj::<Bus>(uuid, id.into())?,
+                ObjectType::MidiItem => self.deserialize_obj::<MidiItem>(uuid, id.into())?,
+                ObjectType::MidiSource => self.deserialize_obj::<MidiSource>(uuid, id.into())?,
+                ObjectType::Node => self.deserialize_obj::<Node>(uuid, id.into())?,
                 ObjectType::Track => self.deserialize_obj::<Track>(uuid, id.into())?,
                 ObjectType::TempoMap => self.deserialize_obj::<TempoMap>(uuid, id.into())?,
             }
@@ -155,9 +397,29 @@ impl DeserializationContext<'_> {
     }
 
     fn deserialize_obj<T: Object + StorageRef>(&mut self, uuid: Uuid, id: T::Id) -> Result<()> {
+        if let Some(objects) = self.imported {
+            let Some(data) = objects.get(&uuid) else {
+                bail!(
+                    ErrorKind::InvalidUuid,
+                    "object {uuid} doesn't exist in the imported data"
+                );
+            };
+
+            let object = T::deserialize(self, data)?;
+
+            let storage = self.hub.storage_mut::<T>();
+            storage.finish_insert(id, object);
+
+            return Ok(());
+        }
+
         let document = self.documents.get_or_err(self.document_id)?;
 
         let Some(revision) = document.read_object(uuid)? else {
+            if self.skip_missing {
+                return Ok(());
+            }
+
             bail!(
                 ErrorKind::InvalidUuid,
                 "object {uuid} doesn't exist in the document"
@@ -165,6 +427,10 @@ impl DeserializationContext<'_> {
         };
 
         let Some(mut blob) = document.open_blob(revision.hash)? else {
+            if self.skip_missing {
+                return Ok(());
+            }
+
             bail!(
                 ErrorKind::InvalidUuid,
                 "object {uuid} doesn't have a valid blob"
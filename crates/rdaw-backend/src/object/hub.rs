@@ -1,25 +1,52 @@
 use std::sync::Arc;
 
 use rdaw_api::arrangement::{ArrangementEvents, ArrangementId};
-use rdaw_api::track::{TrackEvents, TrackHierarchyEvent, TrackId, TrackViewEvent, TrackViewId};
-use rdaw_api::{BackendProtocol, Result};
+use rdaw_api::asset::{AssetEvents, AssetImportProgress};
+use rdaw_api::automation::{AutomationEvent, AutomationEvents, AutomationLaneId};
+use rdaw_api::bus::{BusEvents, BusId, SendEvent};
+use rdaw_api::chord::{ChordEvent, ChordEvents};
+use rdaw_api::document::{DocumentChangeEvent, DocumentEvents, DocumentId};
+use rdaw_api::engine::{EngineEvents, EngineStatus};
+use rdaw_api::item::{AudioItemEvents, AudioItemId, GainEnvelope};
+use rdaw_api::launcher::{LauncherEvent, LauncherEvents};
+use rdaw_api::meter::{MeterEvents, MeterFrame, StereoFrame};
+use rdaw_api::modulation::{ModulationEvents, ModulatorEvent};
+use rdaw_api::node::{NodeEvents, NodeId, NodeParamEvent};
+use rdaw_api::recording::{RecordingEvents, RecordingMeter};
+use rdaw_api::render::{RenderEvents, RenderProgress};
+use rdaw_api::source::{MidiCc, MidiNoteEvent, MidiSourceEvents, MidiSourceId};
+use rdaw_api::track::{
+    TrackEvents, TrackHierarchyEvent, TrackId, TrackMix, TrackStatus, TrackViewEvent,
+    TrackViewFilter, TrackViewId,
+};
+use rdaw_api::transport::{PlayheadEvent, TransportEvents};
+use rdaw_api::waveform::{WaveformEvent, WaveformEvents};
+use rdaw_api::{format_err, BackendProtocol, ErrorKind, Result};
 use rdaw_rpc::transport::ServerTransport;
 use rdaw_rpc::{StreamId, StreamIdAllocator, Subscribers};
 
-use super::{Object, Storage};
+use super::{MemoryUsage, Object, ObjectId, ObjectType, Storage};
 use crate::arrangement::Arrangement;
 use crate::asset::Asset;
-use crate::item::AudioItem;
-use crate::source::AudioSource;
+use crate::automation::AutomationLane;
+use crate::bus::Bus;
+use crate::item::{AudioItem, MidiItem};
+use crate::node::Node;
+use crate::source::{AudioSource, MidiSource};
 use crate::tempo_map::TempoMap;
 use crate::track::Track;
 
-#[derive(Debug, Default)]
+#[derive(Debug, Clone, Default)]
 pub struct Hub {
     pub arrangements: Storage<Arrangement>,
     pub assets: Storage<Asset>,
     pub audio_items: Storage<AudioItem>,
     pub audio_sources: Storage<AudioSource>,
+    pub automation_lanes: Storage<AutomationLane>,
+    pub buses: Storage<Bus>,
+    pub midi_items: Storage<MidiItem>,
+    pub midi_sources: Storage<MidiSource>,
+    pub nodes: Storage<Node>,
     pub tempo_maps: Storage<TempoMap>,
     pub tracks: Storage<Track>,
 }
@@ -32,14 +59,166 @@ impl Hub {
     pub fn storage_mut<T: StorageRef>(&mut self) -> &mut Storage<T> {
         T::storage_ref_mut(self)
     }
+
+    /// Mutably borrows several objects at once, possibly from different storages.
+    ///
+    /// `ids` is an id, an array of ids of the same type, or a tuple of those. Each storage can
+    /// only appear once, so several objects of the same type must be passed as an array.
+    pub fn get_many_mut<B: HubBorrow>(&mut self, ids: B) -> Result<B::Output<'_>> {
+        let mut parts = HubParts {
+            arrangements: Some(&mut self.arrangements),
+            assets: Some(&mut self.assets),
+            audio_items: Some(&mut self.audio_items),
+            audio_sources: Some(&mut self.audio_sources),
+            automation_lanes: Some(&mut self.automation_lanes),
+            buses: Some(&mut self.buses),
+            midi_items: Some(&mut self.midi_items),
+            midi_sources: Some(&mut self.midi_sources),
+            nodes: Some(&mut self.nodes),
+            tempo_maps: Some(&mut self.tempo_maps),
+            tracks: Some(&mut self.tracks),
+        };
+
+        ids.borrow_from(&mut parts)
+    }
+
+    /// Changes of objects since the last call, see [`Storage::take_changes`].
+    pub fn take_changes(&mut self) -> Vec<(DocumentId, DocumentChangeEvent)> {
+        let mut changes = Vec::new();
+        changes.extend(self.arrangements.take_changes());
+        changes.extend(self.assets.take_changes());
+        changes.extend(self.audio_items.take_changes());
+        changes.extend(self.audio_sources.take_changes());
+        changes.extend(self.automation_lanes.take_changes());
+        changes.extend(self.buses.take_changes());
+        changes.extend(self.midi_items.take_changes());
+        changes.extend(self.midi_sources.take_changes());
+        changes.extend(self.nodes.take_changes());
+        changes.extend(self.tempo_maps.take_changes());
+        changes.extend(self.tracks.take_changes());
+        changes
+    }
+
+    pub fn remove_document(&mut self, document_id: DocumentId) {
+        self.arrangements.remove_document(document_id);
+        self.assets.remove_document(document_id);
+        self.audio_items.remove_document(document_id);
+        self.audio_sources.remove_document(document_id);
+        self.automation_lanes.remove_document(document_id);
+        self.buses.remove_document(document_id);
+        self.midi_items.remove_document(document_id);
+        self.midi_sources.remove_document(document_id);
+        self.nodes.remove_document(document_id);
+        self.tempo_maps.remove_document(document_id);
+        self.tracks.remove_document(document_id);
+    }
+
+    pub fn memory_usage(&self, document_id: DocumentId) -> Vec<(ObjectType, MemoryUsage)> {
+        vec![
+            self.storage_memory_usage::<Arrangement>(document_id),
+            self.storage_memory_usage::<Asset>(document_id),
+            self.storage_memory_usage::<AudioItem>(document_id),
+            self.storage_memory_usage::<AudioSource>(document_id),
+            self.storage_memory_usage::<AutomationLane>(document_id),
+            self.storage_memory_usage::<Bus>(document_id),
+            self.storage_memory_usage::<MidiItem>(document_id),
+            self.storage_memory_usage::<MidiSource>(document_id),
+            self.storage_memory_usage::<Node>(document_id),
+            self.storage_memory_usage::<TempoMap>(document_id),
+            self.storage_memory_usage::<Track>(document_id),
+        ]
+    }
+
+    fn storage_memory_usage<T: StorageRef>(
+        &self,
+        document_id: DocumentId,
+    ) -> (ObjectType, MemoryUsage) {
+        (T::TYPE, self.storage::<T>().memory_usage(document_id))
+    }
 }
 
 pub trait StorageRef: Object + Sized {
     fn storage_ref(hub: &Hub) -> &Storage<Self>;
 
     fn storage_ref_mut(hub: &mut Hub) -> &mut Storage<Self>;
+
+    fn take_storage<'a>(parts: &mut HubParts<'a>) -> Result<&'a mut Storage<Self>> {
+        Self::take_storage_part(parts).ok_or_else(|| {
+            format_err!(
+                ErrorKind::Conflict,
+                "{:?} storage is borrowed more than once",
+                Self::TYPE,
+            )
+        })
+    }
+
+    fn take_storage_part<'a>(parts: &mut HubParts<'a>) -> Option<&'a mut Storage<Self>>;
+}
+
+/// Storages of a [`Hub`] which haven't been borrowed yet by [`Hub::get_many_mut`].
+pub struct HubParts<'a> {
+    arrangements: Option<&'a mut Storage<Arrangement>>,
+    assets: Option<&'a mut Storage<Asset>>,
+    audio_items: Option<&'a mut Storage<AudioItem>>,
+    audio_sources: Option<&'a mut Storage<AudioSource>>,
+    automation_lanes: Option<&'a mut Storage<AutomationLane>>,
+    buses: Option<&'a mut Storage<Bus>>,
+    midi_items: Option<&'a mut Storage<MidiItem>>,
+    midi_sources: Option<&'a mut Storage<MidiSource>>,
+    nodes: Option<&'a mut Storage<Node>>,
+    tempo_maps: Option<&'a mut Storage<TempoMap>>,
+    tracks: Option<&'a mut Storage<Track>>,
+}
+
+pub trait HubBorrow {
+    type Output<'a>;
+
+    fn borrow_from<'a>(self, parts: &mut HubParts<'a>) -> Result<Self::Output<'a>>;
+}
+
+impl<I> HubBorrow for I
+where
+    I: ObjectId,
+    I::Object: StorageRef + 'static,
+{
+    type Output<'a> = &'a mut I::Object;
+
+    fn borrow_from<'a>(self, parts: &mut HubParts<'a>) -> Result<Self::Output<'a>> {
+        I::Object::take_storage(parts)?.get_mut_or_err(self)
+    }
+}
+
+impl<I, const N: usize> HubBorrow for [I; N]
+where
+    I: ObjectId,
+    I::Object: StorageRef + 'static,
+{
+    type Output<'a> = [&'a mut I::Object; N];
+
+    fn borrow_from<'a>(self, parts: &mut HubParts<'a>) -> Result<Self::Output<'a>> {
+        I::Object::take_storage(parts)?.get_disjoint_mut_or_err(self)
+    }
 }
 
+macro_rules! impl_hub_borrow_for_tuple {
+    ($($name:ident),*) => {
+        impl<$($name: HubBorrow),*> HubBorrow for ($($name,)*) {
+            type Output<'a> = ($($name::Output<'a>,)*);
+
+            #[allow(non_snake_case)]
+            fn borrow_from<'a>(self, parts: &mut HubParts<'a>) -> Result<Self::Output<'a>> {
+                let ($($name,)*) = self;
+                Ok(($($name.borrow_from(parts)?,)*))
+            }
+        }
+    };
+}
+
+impl_hub_borrow_for_tuple!(A);
+impl_hub_borrow_for_tuple!(A, B);
+impl_hub_borrow_for_tuple!(A, B, C);
+impl_hub_borrow_for_tuple!(A, B, C, D);
+
 macro_rules! impl_storage_ref {
     ($field:ident: $ty:ty) => {
         impl StorageRef for $ty {
@@ -50,6 +229,10 @@ macro_rules! impl_storage_ref {
             fn storage_ref_mut(hub: &mut Hub) -> &mut Storage<Self> {
                 &mut hub.$field
             }
+
+            fn take_storage_part<'a>(parts: &mut HubParts<'a>) -> Option<&'a mut Storage<Self>> {
+                parts.$field.take()
+            }
         }
     };
 }
@@ -58,24 +241,91 @@ impl_storage_ref!(arrangements: Arrangement);
 impl_storage_ref!(assets: Asset);
 impl_storage_ref!(audio_items: AudioItem);
 impl_storage_ref!(audio_sources: AudioSource);
+impl_storage_ref!(automation_lanes: AutomationLane);
+impl_storage_ref!(buses: Bus);
+impl_storage_ref!(midi_items: MidiItem);
+impl_storage_ref!(midi_sources: MidiSource);
+impl_storage_ref!(nodes: Node);
 impl_storage_ref!(tempo_maps: TempoMap);
 impl_storage_ref!(tracks: Track);
 
 #[derive(Debug)]
 pub struct SubscribersHub {
     pub arrangement_name: Subscribers<ArrangementId, String>,
+    pub arrangement_chords: Subscribers<ArrangementId, ChordEvent>,
+    pub arrangement_modulators: Subscribers<ArrangementId, ModulatorEvent>,
+    pub asset_import_progress: Subscribers<DocumentId, AssetImportProgress>,
+    pub audio_item_gain_envelope: Subscribers<AudioItemId, GainEnvelope>,
+    pub automation_lane: Subscribers<AutomationLaneId, AutomationEvent>,
+    pub bus_mix: Subscribers<BusId, TrackMix>,
+    pub document_changes: Subscribers<DocumentId, DocumentChangeEvent>,
+    pub engine_status: Subscribers<ArrangementId, EngineStatus>,
+    pub input_meter: Subscribers<TrackId, MeterFrame>,
+    pub launcher: Subscribers<ArrangementId, LauncherEvent>,
+    pub midi_source_notes: Subscribers<MidiSourceId, MidiNoteEvent>,
+    pub midi_source_ccs: Subscribers<MidiSourceId, Vec<MidiCc>>,
+    pub node_params: Subscribers<NodeId, NodeParamEvent>,
+    pub playhead: Subscribers<ArrangementId, PlayheadEvent>,
+    pub recording_meter: Subscribers<ArrangementId, RecordingMeter>,
+    pub render_progress: Subscribers<ArrangementId, RenderProgress>,
+    /// Filtered by decimation.
+    pub stereo_meter: Subscribers<ArrangementId, StereoFrame, u32>,
     pub track_name: Subscribers<TrackId, String>,
     pub track_hierarchy: Subscribers<TrackId, TrackHierarchyEvent>,
-    pub track_view: Subscribers<TrackViewId, TrackViewEvent>,
+    pub track_locked: Subscribers<TrackId, bool>,
+    pub track_status: Subscribers<TrackId, TrackStatus>,
+    pub track_meter: Subscribers<TrackId, MeterFrame>,
+    pub track_mix: Subscribers<TrackId, TrackMix>,
+    pub track_sends: Subscribers<TrackId, SendEvent>,
+    pub track_view: Subscribers<TrackViewId, TrackViewEvent, TrackViewFilter>,
+    pub waveform_progress: Subscribers<TrackViewId, WaveformEvent>,
 }
 
 impl SubscribersHub {
     pub fn new(id_allocator: Arc<StreamIdAllocator>) -> SubscribersHub {
         SubscribersHub {
-            arrangement_name: Subscribers::new(id_allocator.clone()),
-            track_name: Subscribers::new(id_allocator.clone()),
+            arrangement_name: Subscribers::with_coalescing(id_allocator.clone(), |_, _| true),
+            arrangement_chords: Subscribers::new(id_allocator.clone()),
+            arrangement_modulators: Subscribers::new(id_allocator.clone()),
+            asset_import_progress: Subscribers::with_coalescing(id_allocator.clone(), |a, b| {
+                a.path == b.path
+            }),
+            audio_item_gain_envelope: Subscribers::with_coalescing(
+                id_allocator.clone(),
+                |_, _| true,
+            ),
+            bus_mix: Subscribers::with_coalescing(id_allocator.clone(), |_, _| true),
+            automation_lane: Subscribers::new(id_allocator.clone()),
+            document_changes: Subscribers::with_coalescing(id_allocator.clone(), |a, b| a == b),
+            engine_status: Subscribers::with_coalescing(id_allocator.clone(), |_, _| true),
+            input_meter: Subscribers::with_coalescing(id_allocator.clone(), |_, _| true),
+            launcher: Subscribers::new(id_allocator.clone()),
+            midi_source_notes: Subscribers::new(id_allocator.clone()),
+            midi_source_ccs: Subscribers::with_coalescing(id_allocator.clone(), |_, _| true),
+            node_params: Subscribers::with_coalescing(id_allocator.clone(), |a, b| {
+                a.name == b.name
+            }),
+            playhead: Subscribers::with_coalescing(id_allocator.clone(), |_, _| true),
+            recording_meter: Subscribers::with_coalescing(id_allocator.clone(), |_, _| true),
+            render_progress: Subscribers::with_coalescing(id_allocator.clone(), |a, b| {
+                a.path == b.path && a.stage == b.stage
+            }),
+            stereo_meter: Subscribers::with_coalescing(id_allocator.clone(), |_, _| true),
+            track_name: Subscribers::with_coalescing(id_allocator.clone(), |_, _| true),
             track_hierarchy: Subscribers::new(id_allocator.clone()),
-            track_view: Subscribers::new(id_allocator.clone()),
+            track_locked: Subscribers::with_coalescing(id_allocator.clone(), |_, _| true),
+            track_status: Subscribers::with_coalescing(id_allocator.clone(), |_, _| true),
+            track_sends: Subscribers::new(id_allocator.clone()),
+            track_meter: Subscribers::with_coalescing(id_allocator.clone(), |_, _| true),
+            track_mix: Subscribers::with_coalescing(id_allocator.clone(), |_, _| true),
+            track_view: Subscribers::with_coalescing(
+                id_allocator.clone(),
+                coalesce_track_view_events,
+            ),
+            waveform_progress: Subscribers::with_coalescing(
+                id_allocator.clone(),
+                coalesce_waveform_events,
+            ),
         }
     }
 
@@ -84,6 +334,74 @@ impl SubscribersHub {
             self.arrangement_name.close_one(key, stream);
         }
 
+        if let Some(key) = self.arrangement_chords.find_key(stream) {
+            self.arrangement_chords.close_one(key, stream);
+        }
+
+        if let Some(key) = self.arrangement_modulators.find_key(stream) {
+            self.arrangement_modulators.close_one(key, stream);
+        }
+
+        if let Some(key) = self.asset_import_progress.find_key(stream) {
+            self.asset_import_progress.close_one(key, stream);
+        }
+
+        if let Some(key) = self.audio_item_gain_envelope.find_key(stream) {
+            self.audio_item_gain_envelope.close_one(key, stream);
+        }
+
+        if let Some(key) = self.automation_lane.find_key(stream) {
+            self.automation_lane.close_one(key, stream);
+        }
+        if let Some(key) = self.bus_mix.find_key(stream) {
+            self.bus_mix.close_one(key, stream);
+        }
+
+
+        if let Some(key) = self.document_changes.find_key(stream) {
+            self.document_changes.close_one(key, stream);
+        }
+
+        if let Some(key) = self.engine_status.find_key(stream) {
+            self.engine_status.close_one(key, stream);
+        }
+
+        if let Some(key) = self.input_meter.find_key(stream) {
+            self.input_meter.close_one(key, stream);
+        }
+
+        if let Some(key) = self.launcher.find_key(stream) {
+            self.launcher.close_one(key, stream);
+        }
+
+        if let Some(key) = self.midi_source_notes.find_key(stream) {
+            self.midi_source_notes.close_one(key, stream);
+        }
+
+        if let Some(key) = self.midi_source_ccs.find_key(stream) {
+            self.midi_source_ccs.close_one(key, stream);
+        }
+
+        if let Some(key) = self.node_params.find_key(stream) {
+            self.node_params.close_one(key, stream);
+        }
+
+        if let Some(key) = self.playhead.find_key(stream) {
+            self.playhead.close_one(key, stream);
+        }
+
+        if let Some(key) = self.recording_meter.find_key(stream) {
+            self.recording_meter.close_one(key, stream);
+        }
+
+        if let Some(key) = self.render_progress.find_key(stream) {
+            self.render_progress.close_one(key, stream);
+        }
+
+        if let Some(key) = self.stereo_meter.find_key(stream) {
+            self.stereo_meter.close_one(key, stream);
+        }
+
         if let Some(key) = self.track_name.find_key(stream) {
             self.track_name.close_one(key, stream);
         }
@@ -92,9 +410,63 @@ impl SubscribersHub {
             self.track_hierarchy.close_one(key, stream);
         }
 
+        if let Some(key) = self.track_locked.find_key(stream) {
+            self.track_locked.close_one(key, stream);
+        }
+
+        if let Some(key) = self.track_status.find_key(stream) {
+            self.track_status.close_one(key, stream);
+        }
+
+        if let Some(key) = self.track_meter.find_key(stream) {
+            self.track_meter.close_one(key, stream);
+        }
+
+        if let Some(key) = self.track_mix.find_key(stream) {
+            self.track_mix.close_one(key, stream);
+        }
+        if let Some(key) = self.track_sends.find_key(stream) {
+            self.track_sends.close_one(key, stream);
+        }
+
+
         if let Some(key) = self.track_view.find_key(stream) {
             self.track_view.close_one(key, stream);
         }
+
+        if let Some(key) = self.waveform_progress.find_key(stream) {
+            self.waveform_progress.close_one(key, stream);
+        }
+    }
+
+    pub fn discard_queued(&mut self) {
+        self.arrangement_name.discard_queued();
+        self.arrangement_chords.discard_queued();
+        self.arrangement_modulators.discard_queued();
+        self.asset_import_progress.discard_queued();
+        self.audio_item_gain_envelope.discard_queued();
+        self.bus_mix.discard_queued();
+        self.automation_lane.discard_queued();
+        self.document_changes.discard_queued();
+        self.engine_status.discard_queued();
+        self.input_meter.discard_queued();
+        self.launcher.discard_queued();
+        self.midi_source_notes.discard_queued();
+        self.midi_source_ccs.discard_queued();
+        self.node_params.discard_queued();
+        self.playhead.discard_queued();
+        self.recording_meter.discard_queued();
+        self.render_progress.discard_queued();
+        self.stereo_meter.discard_queued();
+        self.track_name.discard_queued();
+        self.track_hierarchy.discard_queued();
+        self.track_locked.discard_queued();
+        self.track_status.discard_queued();
+        self.track_sends.discard_queued();
+        self.track_meter.discard_queued();
+        self.track_mix.discard_queued();
+        self.track_view.discard_queued();
+        self.waveform_progress.discard_queued();
     }
 
     pub async fn deliver<T>(&mut self, t: &T) -> Result<()>
@@ -107,6 +479,78 @@ impl SubscribersHub {
             })
             .await?;
 
+        self.arrangement_chords
+            .deliver(t, |ev| ChordEvents::SubscribeArrangementChords(ev).into())
+            .await?;
+
+        self.arrangement_modulators
+            .deliver(t, |ev| ModulationEvents::SubscribeArrangementModulators(ev).into())
+            .await?;
+
+        self.asset_import_progress
+            .deliver(t, |ev| AssetEvents::SubscribeAssetImportProgress(ev).into())
+            .await?;
+
+        self.audio_item_gain_envelope
+            .deliver(t, |ev| {
+                AudioItemEvents::SubscribeAudioItemGainEnvelope(ev).into()
+            })
+            .await?;
+
+        self.automation_lane
+            .deliver(t, |ev| AutomationEvents::SubscribeAutomationLane(ev).into())
+            .await?;
+
+        self.bus_mix
+            .deliver(t, |ev| BusEvents::SubscribeBusMix(ev).into())
+            .await?;
+
+        self.document_changes
+            .deliver(t, |ev| DocumentEvents::SubscribeDocumentChanges(ev).into())
+            .await?;
+
+        self.engine_status
+            .deliver(t, |ev| EngineEvents::SubscribeEngineStatus(ev).into())
+            .await?;
+
+        self.input_meter
+            .deliver(t, |ev| MeterEvents::SubscribeInputMeter(ev).into())
+            .await?;
+
+        self.launcher
+            .deliver(t, |ev| LauncherEvents::SubscribeLauncher(ev).into())
+            .await?;
+
+        self.midi_source_notes
+            .deliver(t, |ev| {
+                MidiSourceEvents::SubscribeMidiSourceNotes(ev).into()
+            })
+            .await?;
+
+        self.midi_source_ccs
+            .deliver(t, |ev| MidiSourceEvents::SubscribeMidiSourceCcs(ev).into())
+            .await?;
+
+        self.node_params
+            .deliver(t, |ev| NodeEvents::SubscribeNodeParams(ev).into())
+            .await?;
+
+        self.playhead
+            .deliver(t, |ev| TransportEvents::SubscribePlayhead(ev).into())
+            .await?;
+
+        self.recording_meter
+            .deliver(t, |ev| RecordingEvents::SubscribeRecordingMeter(ev).into())
+            .await?;
+
+        self.render_progress
+            .deliver(t, |ev| RenderEvents::SubscribeRenderProgress(ev).into())
+            .await?;
+
+        self.stereo_meter
+            .deliver(t, |ev| MeterEvents::SubscribeStereoMeter(ev).into())
+            .await?;
+
         self.track_name
             .deliver(t, |ev| TrackEvents::SubscribeTrackName(ev).into())
             .await?;
@@ -115,10 +559,61 @@ impl SubscribersHub {
             .deliver(t, |ev| TrackEvents::SubscribeTrackHierarchy(ev).into())
             .await?;
 
+        self.track_locked
+            .deliver(t, |ev| TrackEvents::SubscribeTrackLocked(ev).into())
+            .await?;
+
+        self.track_status
+            .deliver(t, |ev| TrackEvents::SubscribeTrackStatus(ev).into())
+            .await?;
+
+        self.track_meter
+            .deliver(t, |ev| MeterEvents::SubscribeTrackMeter(ev).into())
+            .await?;
+
+        self.track_mix
+            .deliver(t, |ev| TrackEvents::SubscribeTrackMix(ev).into())
+            .await?;
+
+        self.track_sends
+            .deliver(t, |ev| BusEvents::SubscribeTrackSends(ev).into())
+            .await?;
+
         self.track_view
             .deliver(t, |ev| TrackEvents::SubscribeTrackView(ev).into())
             .await?;
 
+        self.waveform_progress
+            .deliver(t, |ev| WaveformEvents::SubscribeWaveformProgress(ev).into())
+            .await?;
+
         Ok(())
     }
 }
+
+fn coalesce_track_view_events(old: &TrackViewEvent, new: &TrackViewEvent) -> bool {
+    match (old, new) {
+        (TrackViewEvent::ItemMoved { id: a, .. }, TrackViewEvent::ItemMoved { id: b, .. }) => a == b,
+        (TrackViewEvent::ItemResized { id: a, .. }, TrackViewEvent::ItemResized { id: b, .. }) => {
+            a == b
+        }
+        (TrackViewEvent::ItemLocked { id: a, .. }, TrackViewEvent::ItemLocked { id: b, .. }) => {
+            a == b
+        }
+        (
+            TrackViewEvent::ItemClipChanged { id: a, .. },
+            TrackViewEvent::ItemClipChanged { id: b, .. },
+        ) => a == b,
+        _ => false,
+    }
+}
+
+fn coalesce_waveform_events(old: &WaveformEvent, new: &WaveformEvent) -> bool {
+    match (old, new) {
+        (
+            WaveformEvent::Progress { source_id: a, .. },
+            WaveformEvent::Progress { source_id: b, .. },
+        ) => a == b,
+        _ => false,
+    }
+}
@@ -1,24 +1,19 @@
 mod encoding;
 mod hub;
 mod storage;
+#[cfg(test)]
+mod tests;
+mod validation;
 
 use rdaw_api::document::DocumentId;
+pub use rdaw_api::document::ObjectType;
 use rdaw_api::Result;
 pub use rdaw_core::Uuid;
 
 pub use self::encoding::{DeserializationContext, SerializationContext};
-pub use self::hub::{Hub, StorageRef, SubscribersHub};
+pub use self::hub::{Hub, HubBorrow, HubParts, StorageRef, SubscribersHub};
 pub use self::storage::Storage;
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-pub enum ObjectType {
-    Arrangement,
-    Asset,
-    AudioItem,
-    AudioSource,
-    TempoMap,
-    Track,
-}
+pub use self::validation::validate;
 
 pub trait Object: Sized {
     type Id: ObjectId<Object = Self>;
@@ -28,12 +23,23 @@ pub trait Object: Sized {
     fn serialize(&self, ctx: &mut SerializationContext<'_>) -> Result<Vec<u8>>;
 
     fn deserialize(ctx: &mut DeserializationContext<'_>, data: &[u8]) -> Result<Self>;
+
+    /// Approximate size of heap allocations owned by the object, for memory accounting.
+    fn heap_size(&self) -> usize {
+        0
+    }
 }
 
 pub trait ObjectId: slotmap::Key {
     type Object: Object<Id = Self>;
 }
 
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MemoryUsage {
+    pub count: usize,
+    pub bytes: usize,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct ObjectKey {
     pub document_id: DocumentId,
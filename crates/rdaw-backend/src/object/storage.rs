@@ -1,19 +1,31 @@
+use std::collections::BTreeMap;
+use std::mem;
 use std::ops::{Index, IndexMut};
 
-use rdaw_api::{bail, format_err, Error, ErrorKind, Result};
+use rdaw_api::document::{DocumentChangeEvent, DocumentId};
+use rdaw_api::{bail, format_err, Error, ErrorKind, Page, PageRequest, Result};
 use rdaw_core::collections::{HashMap, HashSet};
 use slotmap::SlotMap;
 
-use super::{Object, ObjectId, ObjectKey};
+use super::{MemoryUsage, Object, ObjectId, ObjectKey};
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Storage<T: Object> {
     map: SlotMap<T::Id, Entry<T>>,
     dirty_set: HashSet<T::Id>,
     key_to_id: HashMap<ObjectKey, T::Id>,
+    /// Changes since the last [`Storage::take_changes`].
+    changes: BTreeMap<T::Id, (ObjectKey, ObjectChange)>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ObjectChange {
+    Added,
+    Removed,
+    Modified,
+}
+
+#[derive(Debug, Clone)]
 struct Entry<T> {
     key: ObjectKey,
     object: Option<T>,
@@ -25,6 +37,7 @@ impl<T: Object> Storage<T> {
             map: SlotMap::default(),
             dirty_set: HashSet::default(),
             key_to_id: HashMap::default(),
+            changes: BTreeMap::new(),
         }
     }
 
@@ -34,9 +47,17 @@ impl<T: Object> Storage<T> {
         id
     }
 
+    /// Reloading an object that already exists counts as a modification.
     pub fn finish_insert(&mut self, id: T::Id, object: T) {
-        self.map[id].object = Some(object);
+        let entry = &mut self.map[id];
+        let change = match entry.object {
+            Some(_) => ObjectChange::Modified,
+            None => ObjectChange::Added,
+        };
+
+        entry.object = Some(object);
         self.dirty_set.insert(id);
+        record_change(&mut self.changes, id, entry.key, change);
     }
 
     pub fn insert(&mut self, key: ObjectKey, object: T) -> T::Id {
@@ -47,10 +68,64 @@ impl<T: Object> Storage<T> {
 
         self.dirty_set.insert(id);
         self.key_to_id.insert(key, id);
+        record_change(&mut self.changes, id, key, ObjectChange::Added);
 
         id
     }
 
+    pub fn remove(&mut self, id: T::Id) -> Option<T> {
+        let entry = self.map.remove(id)?;
+        self.dirty_set.remove(&id);
+        self.key_to_id.remove(&entry.key);
+
+        if entry.object.is_some() {
+            record_change(&mut self.changes, id, entry.key, ObjectChange::Removed);
+        }
+
+        entry.object
+    }
+
+    /// Removes objects of the document which were prepared for insertion, but never inserted,
+    /// e.g. because they're missing from the document file. Returns their keys.
+    pub fn discard_unfinished(&mut self, document_id: DocumentId) -> Vec<ObjectKey> {
+        let mut keys = Vec::new();
+
+        self.map.retain(|_, entry| {
+            if entry.object.is_none() && entry.key.document_id == document_id {
+                keys.push(entry.key);
+                return false;
+            }
+
+            true
+        });
+
+        for key in &keys {
+            self.key_to_id.remove(key);
+        }
+
+        keys
+    }
+
+    /// Removes all objects of the document without recording any changes.
+    pub fn remove_document(&mut self, document_id: DocumentId) {
+        let mut ids = Vec::new();
+
+        self.map.retain(|id, entry| {
+            if entry.key.document_id == document_id {
+                ids.push((id, entry.key));
+                return false;
+            }
+
+            true
+        });
+
+        for (id, key) in ids {
+            self.dirty_set.remove(&id);
+            self.key_to_id.remove(&key);
+            self.changes.remove(&id);
+        }
+    }
+
     pub fn has(&self, id: T::Id) -> bool {
         self.map.get(id).is_some_and(|v| v.object.is_some())
     }
@@ -76,8 +151,12 @@ impl<T: Object> Storage<T> {
         }
     }
 
+    /// Borrowing an object mutably counts as a modification.
     pub fn get_mut(&mut self, id: T::Id) -> Option<&mut T> {
-        self.map.get_mut(id).and_then(|v| v.object.as_mut())
+        let entry = self.map.get_mut(id)?;
+        let object = entry.object.as_mut()?;
+        record_change(&mut self.changes, id, entry.key, ObjectChange::Modified);
+        Some(object)
     }
 
     #[track_caller]
@@ -93,6 +172,11 @@ impl<T: Object> Storage<T> {
             if arr.iter().any(|v| v.object.is_none()) {
                 return None;
             }
+
+            for (&id, entry) in ids.iter().zip(&arr) {
+                record_change(&mut self.changes, id, entry.key, ObjectChange::Modified);
+            }
+
             Some(arr.map(|v| v.object.as_mut().unwrap()))
         })
     }
@@ -107,9 +191,13 @@ impl<T: Object> Storage<T> {
         }
 
         let Some(arr) = self.map.get_disjoint_mut(ids) else {
-            bail!(ErrorKind::Other, "duplicate ids in get_disjoint_mut");
+            bail!(ErrorKind::Conflict, "duplicate ids in get_disjoint_mut");
         };
 
+        for (&id, entry) in ids.iter().zip(&arr) {
+            record_change(&mut self.changes, id, entry.key, ObjectChange::Modified);
+        }
+
         Ok(arr.map(|v| v.object.as_mut().unwrap()))
     }
 
@@ -143,6 +231,35 @@ impl<T: Object> Storage<T> {
             .flat_map(|(id, entry)| entry.object.as_ref().map(|obj| (id, &entry.key, obj)))
     }
 
+    pub fn ids_in_document(&self, document_id: DocumentId) -> Vec<T::Id> {
+        self.iter()
+            .filter(|(_, key, _)| key.document_id == document_id)
+            .map(|(id, _, _)| id)
+            .collect()
+    }
+
+    pub fn page_in_document(&self, document_id: DocumentId, page: PageRequest) -> Page<T::Id> {
+        page.collect(
+            self.iter()
+                .filter(|(_, key, _)| key.document_id == document_id)
+                .map(|(id, _, _)| id),
+        )
+    }
+
+    pub fn memory_usage(&self, document_id: DocumentId) -> MemoryUsage {
+        let entry_size = mem::size_of::<Entry<T>>() + mem::size_of::<(ObjectKey, T::Id)>();
+        let mut usage = MemoryUsage::default();
+
+        for (_, key, object) in self.iter() {
+            if key.document_id == document_id {
+                usage.count += 1;
+                usage.bytes += entry_size + object.heap_size();
+            }
+        }
+
+        usage
+    }
+
     pub fn mark_dirty(&mut self, id: T::Id) {
         if self.has(id) {
             self.dirty_set.insert(id);
@@ -156,6 +273,46 @@ impl<T: Object> Storage<T> {
     pub fn clear_all_dirty(&mut self) {
         self.dirty_set.clear()
     }
+
+    /// Returns what happened to objects since the last call, at most one event per object.
+    pub fn take_changes(&mut self) -> impl Iterator<Item = (DocumentId, DocumentChangeEvent)> {
+        mem::take(&mut self.changes)
+            .into_values()
+            .map(|(key, change)| {
+                let (ty, uuid) = (T::TYPE, key.uuid);
+                let event = match change {
+                    ObjectChange::Added => DocumentChangeEvent::ObjectAdded { ty, uuid },
+                    ObjectChange::Removed => DocumentChangeEvent::ObjectRemoved { ty, uuid },
+                    ObjectChange::Modified => DocumentChangeEvent::ObjectModified { ty, uuid },
+                };
+
+                (key.document_id, event)
+            })
+    }
+}
+
+/// An object added and modified since the last call is only reported as added, and one that was
+/// added and then removed isn't reported at all.
+fn record_change<I: Ord>(
+    changes: &mut BTreeMap<I, (ObjectKey, ObjectChange)>,
+    id: I,
+    key: ObjectKey,
+    change: ObjectChange,
+) {
+    let Some((_, old)) = changes.get(&id) else {
+        changes.insert(id, (key, change));
+        return;
+    };
+
+    match (*old, change) {
+        (ObjectChange::Added, ObjectChange::Removed) => {
+            changes.remove(&id);
+        }
+        (ObjectChange::Added, _) => {}
+        (_, change) => {
+            changes.insert(id, (key, change));
+        }
+    }
 }
 
 impl<T: Object> Index<T::Id> for Storage<T> {
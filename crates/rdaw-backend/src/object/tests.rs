@@ -0,0 +1,549 @@
+use std::io::{Read, Write};
+
+use blake3::Hash;
+use rdaw_api::arrangement::ArrangementId;
+use rdaw_api::asset::AssetId;
+use rdaw_api::chord::{Chord, ChordQuality, PitchClass, Scale, ScaleMode};
+use rdaw_api::document::DocumentWarningKind::{DanglingReference, DuplicateUuid, OutOfRange};
+use rdaw_api::document::{DocumentChangeEvent, DocumentId};
+use rdaw_api::modulation::{
+    EnvelopeFollower, ModulationSource, ModulationTarget, Modulator, Polarity,
+};
+use rdaw_api::node::NodeParam;
+use rdaw_api::time::BeatTime;
+use rdaw_api::track::TrackId;
+use rdaw_api::{assert_err, ErrorKind, Result};
+use rdaw_core::path::Utf8Path;
+use rdaw_core::time::RealTime;
+use slotmap::{KeyData, SlotMap};
+
+use super::{
+    validate, DeserializationContext, Hub, ObjectId, ObjectKey, ObjectType, SerializationContext,
+    StorageRef, Uuid,
+};
+use crate::arrangement::Arrangement;
+use crate::asset::{Asset, EmbeddedAsset, ExternalAsset, PathVariables};
+use crate::bus::Bus;
+use crate::document::{Compression, Document, DocumentStorage};
+use crate::node::Node;
+use crate::tempo_map::TempoMap;
+use crate::tests::{assert_golden, from_hex, read_golden, to_hex};
+use crate::track::Track;
+
+const TEMPO_MAP_UUID: Uuid = Uuid::from_u128(1);
+const MAIN_TRACK_UUID: Uuid = Uuid::from_u128(2);
+const DRUMS_TRACK_UUID: Uuid = Uuid::from_u128(3);
+const ARRANGEMENT_UUID: Uuid = Uuid::from_u128(4);
+const EXTERNAL_ASSET_UUID: Uuid = Uuid::from_u128(5);
+const EMBEDDED_ASSET_UUID: Uuid = Uuid::from_u128(6);
+
+const OBJECTS: [(&str, Uuid); 6] = [
+    ("tempo_map", TEMPO_MAP_UUID),
+    ("main_track", MAIN_TRACK_UUID),
+    ("drums_track", DRUMS_TRACK_UUID),
+    ("arrangement", ARRANGEMENT_UUID),
+    ("external_asset", EXTERNAL_ASSET_UUID),
+    ("embedded_asset", EMBEDDED_ASSET_UUID),
+];
+
+struct Fixture {
+    hub: Hub,
+    documents: DocumentStorage,
+    path_variables: PathVariables,
+    document_id: DocumentId,
+}
+
+impl Fixture {
+    fn new() -> Result<Fixture> {
+        let mut documents = DocumentStorage::default();
+        let document_id = documents.insert(Document::new()?);
+
+        let mut path_variables = PathVariables::new();
+        path_variables.set("SAMPLES".into(), "/samples".into())?;
+
+        Ok(Fixture {
+            hub: Hub::default(),
+            documents,
+            path_variables,
+            document_id,
+        })
+    }
+
+    fn populate(&mut self) -> (ArrangementId, AssetId, AssetId) {
+        let document_id = self.document_id;
+        let hub = &mut self.hub;
+
+        let tempo_map_id = hub.tempo_maps.insert(
+            ObjectKey::new(document_id, TEMPO_MAP_UUID),
+            TempoMap::new(120.0),
+        );
+
+        let drums_track_id = hub.tracks.insert(
+            ObjectKey::new(document_id, DRUMS_TRACK_UUID),
+            Track::new("Drums".into()),
+        );
+
+        let mut main_track = Track::new("Main Track".into());
+        main_track.links.children.push(drums_track_id);
+
+        let main_track_id = hub
+            .tracks
+            .insert(ObjectKey::new(document_id, MAIN_TRACK_UUID), main_track);
+
+        let arrangement_id = hub.arrangements.insert(
+            ObjectKey::new(document_id, ARRANGEMENT_UUID),
+            Arrangement {
+                tempo_map_id,
+                main_track_id,
+                name: "Arrangement".into(),
+                markers: SlotMap::default(),
+                video: None,
+                presets: Vec::new(),
+                chords: SlotMap::default(),
+                modulators: SlotMap::default(),
+                scenes: SlotMap::default(),
+                scene_order: Vec::new(),
+                buses: Vec::new(),
+                seed: 42,
+            },
+        );
+
+        let external_asset_id = hub.assets.insert(
+            ObjectKey::new(document_id, EXTERNAL_ASSET_UUID),
+            Asset::External(ExternalAsset {
+                path: "/samples/drums/kick.wav".into(),
+                hash: Hash::from_bytes([0x11; 32]),
+                size: 1024,
+            }),
+        );
+
+        let embedded_asset_id = hub.assets.insert(
+            ObjectKey::new(document_id, EMBEDDED_ASSET_UUID),
+            Asset::Embedded(EmbeddedAsset {
+                hash: Hash::from_bytes([0x22; 32]),
+                size: 300,
+            }),
+        );
+
+        (arrangement_id, external_asset_id, embedded_asset_id)
+    }
+
+    fn read_object(&self, uuid: Uuid) -> Result<Vec<u8>> {
+        let document = &self.documents[self.document_id];
+        let revision = document.read_object(uuid)?.unwrap();
+        let mut blob = document.open_blob(revision.hash)?.unwrap();
+
+        let mut data = Vec::new();
+        blob.read_to_end(&mut data)?;
+        Ok(data)
+    }
+
+    fn write_object(&self, uuid: Uuid, data: &[u8]) -> Result<()> {
+        let document = &self.documents[self.document_id];
+
+        let mut blob = document.create_blob(Compression::None)?;
+        blob.write_all(data)?;
+        let hash = blob.save()?;
+
+        document.write_object(uuid, hash)
+    }
+
+    fn serialize<I: ObjectId>(&mut self, id: I) -> Result<Uuid>
+    where
+        I::Object: StorageRef,
+    {
+        SerializationContext::serialize(
+            &mut self.hub,
+            &self.documents,
+            &self.path_variables,
+            None,
+            id,
+        )
+    }
+
+    fn deserialize<I: ObjectId>(&mut self, uuid: Uuid) -> Result<I>
+    where
+        I::Object: StorageRef,
+    {
+        DeserializationContext::deserialize(
+            &mut self.hub,
+            &self.documents,
+            &self.path_variables,
+            self.document_id,
+            uuid,
+        )
+    }
+}
+
+fn golden_name(name: &str) -> String {
+    format!("objects/{name}.hex")
+}
+
+#[test]
+fn serialize_golden() -> Result<()> {
+    let mut fixture = Fixture::new()?;
+    let (arrangement_id, external_asset_id, embedded_asset_id) = fixture.populate();
+
+    fixture.serialize(arrangement_id)?;
+    fixture.serialize(external_asset_id)?;
+    fixture.serialize(embedded_asset_id)?;
+
+    for (name, uuid) in OBJECTS {
+        let data = fixture.read_object(uuid)?;
+        assert_golden(&golden_name(name), &to_hex(&data));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn deserialize_golden() -> Result<()> {
+    let mut fixture = Fixture::new()?;
+
+    for (name, uuid) in OBJECTS {
+        let data = from_hex(&read_golden(&golden_name(name)));
+        fixture.write_object(uuid, &data)?;
+    }
+
+    let arrangement_id = fixture.deserialize::<ArrangementId>(ARRANGEMENT_UUID)?;
+    let arrangement = fixture.hub.arrangements.get_or_err(arrangement_id)?;
+    assert_eq!(arrangement.name, "Arrangement");
+    assert_eq!(arrangement.seed, 42);
+
+    let main_track = fixture.hub.tracks.get_or_err(arrangement.main_track_id)?;
+    assert_eq!(main_track.name, "Main Track");
+    assert_eq!(main_track.links.children.len(), 1);
+
+    let drums_track = fixture.hub.tracks.get_or_err(main_track.links.children[0])?;
+    assert_eq!(drums_track.name, "Drums");
+
+    let external_asset_id = fixture.deserialize::<AssetId>(EXTERNAL_ASSET_UUID)?;
+    let external_asset = fixture.hub.assets.get_or_err(external_asset_id)?;
+    assert_eq!(external_asset.path(), Some(Utf8Path::new("/samples/drums/kick.wav")));
+    assert_eq!(external_asset.hash(), Hash::from_bytes([0x11; 32]));
+    assert_eq!(external_asset.size(), 1024);
+
+    let embedded_asset_id = fixture.deserialize::<AssetId>(EMBEDDED_ASSET_UUID)?;
+    let embedded_asset = fixture.hub.assets.get_or_err(embedded_asset_id)?;
+    assert_eq!(embedded_asset.path(), None);
+    assert_eq!(embedded_asset.hash(), Hash::from_bytes([0x22; 32]));
+    assert_eq!(embedded_asset.size(), 300);
+
+    Ok(())
+}
+
+#[test]
+fn deserialize_truncated() -> Result<()> {
+    let mut fixture = Fixture::new()?;
+
+    fixture.write_object(ARRANGEMENT_UUID, &[1, 0])?;
+    assert_err!(
+        fixture.deserialize::<ArrangementId>(ARRANGEMENT_UUID),
+        ErrorKind::Deserialization
+    );
+
+    Ok(())
+}
+
+#[test]
+fn deserialize_track_v1() -> Result<()> {
+    let mut fixture = Fixture::new()?;
+
+    let data = from_hex("01 00 00 00 05 44 72 75 6d 73 00 00");
+    fixture.write_object(DRUMS_TRACK_UUID, &data)?;
+
+    let track_id = fixture.deserialize::<TrackId>(DRUMS_TRACK_UUID)?;
+    let track = fixture.hub.tracks.get_or_err(track_id)?;
+    assert_eq!(track.name, "Drums");
+    assert!(!track.locked);
+
+    Ok(())
+}
+
+#[test]
+fn deserialize_arrangement_v1() -> Result<()> {
+    let mut fixture = Fixture::new()?;
+
+    for (name, uuid) in OBJECTS {
+        let data = from_hex(&read_golden(&golden_name(name)));
+        fixture.write_object(uuid, &data)?;
+    }
+
+    let data = from_hex(
+        "01 00 00 00 10 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 01 10 00 00 00 00 00 00 00 \
+         00 00 00 00 00 00 00 00 02 0b 41 72 72 61 6e 67 65 6d 65 6e 74",
+    );
+    fixture.write_object(ARRANGEMENT_UUID, &data)?;
+
+    let arrangement_id = fixture.deserialize::<ArrangementId>(ARRANGEMENT_UUID)?;
+    let arrangement = fixture.hub.arrangements.get_or_err(arrangement_id)?;
+    assert_eq!(arrangement.name, "Arrangement");
+    assert!(arrangement.markers.is_empty());
+    assert_eq!(arrangement.seed, 0);
+
+    Ok(())
+}
+
+#[test]
+fn chords_roundtrip() -> Result<()> {
+    let mut fixture = Fixture::new()?;
+    let (arrangement_id, _, _) = fixture.populate();
+
+    let chord = Chord {
+        start: BeatTime::from_beats(4),
+        duration: BeatTime::from_beats(2),
+        root: PitchClass(9),
+        quality: ChordQuality::Minor7,
+        bass: Some(PitchClass(7)),
+        scale: Scale::new(PitchClass::C, ScaleMode::Major),
+    };
+    fixture.hub.arrangements[arrangement_id].chords.insert(chord);
+
+    fixture.serialize(arrangement_id)?;
+    fixture.hub = Hub::default();
+
+    let arrangement_id = fixture.deserialize::<ArrangementId>(ARRANGEMENT_UUID)?;
+    let arrangement = fixture.hub.arrangements.get_or_err(arrangement_id)?;
+    assert_eq!(arrangement.chords.values().copied().collect::<Vec<_>>(), vec![chord]);
+
+    Ok(())
+}
+
+#[test]
+fn modulators_roundtrip() -> Result<()> {
+    let mut fixture = Fixture::new()?;
+    let (arrangement_id, _, _) = fixture.populate();
+
+    let node_id = fixture.hub.nodes.insert(
+        ObjectKey::new(fixture.document_id, Uuid::from_u128(7)),
+        Node::new("gain".into(), vec![NodeParam::new("gain", 0.0, 2.0, 1.0)]),
+    );
+
+    let main_track_id = fixture.hub.arrangements[arrangement_id].main_track_id;
+    let drums_track_id = fixture.hub.tracks[main_track_id].links.children[0];
+
+    let modulator = Modulator {
+        name: "Sidechain".into(),
+        source: ModulationSource::EnvelopeFollower(EnvelopeFollower {
+            track_id: drums_track_id,
+            attack: RealTime::from_secs_f64(0.01),
+            release: RealTime::from_secs_f64(0.2),
+        }),
+        targets: vec![ModulationTarget {
+            node_id,
+            param: "gain".into(),
+            depth: -0.5,
+            polarity: Polarity::Unipolar,
+        }],
+    };
+    fixture.hub.arrangements[arrangement_id].modulators.insert(modulator.clone());
+
+    fixture.serialize(arrangement_id)?;
+    fixture.hub = Hub::default();
+
+    let arrangement_id = fixture.deserialize::<ArrangementId>(ARRANGEMENT_UUID)?;
+    let arrangement = fixture.hub.arrangements.get_or_err(arrangement_id)?;
+    let loaded = arrangement.modulators.values().next().unwrap();
+    assert_eq!(loaded.name, modulator.name);
+    assert_eq!(loaded.targets[0].depth, -0.5);
+
+    let ModulationSource::EnvelopeFollower(follower) = loaded.source else {
+        panic!("expected an envelope follower");
+    };
+    let drums_track = fixture.hub.tracks.get_or_err(follower.track_id)?;
+    assert_eq!(drums_track.name, "Drums");
+
+    let node = fixture.hub.nodes.get_or_err(loaded.targets[0].node_id)?;
+    assert_eq!(node.kind, "gain");
+
+    Ok(())
+}
+
+#[test]
+fn get_many_mut() -> Result<()> {
+    let mut fixture = Fixture::new()?;
+    let (arrangement_id, _, _) = fixture.populate();
+
+    let hub = &mut fixture.hub;
+    let arrangement = &hub.arrangements[arrangement_id];
+    let (tempo_map_id, main_track_id) = (arrangement.tempo_map_id, arrangement.main_track_id);
+    let drums_track_id = hub.tracks[main_track_id].links.children[0];
+
+    let (arrangement, [main_track, drums_track], tempo_map) =
+        hub.get_many_mut((arrangement_id, [main_track_id, drums_track_id], tempo_map_id))?;
+    arrangement.name = "Renamed".into();
+    main_track.name = drums_track.name.clone();
+    *tempo_map = TempoMap::new(90.0);
+
+    assert_eq!(hub.arrangements[arrangement_id].name, "Renamed");
+    assert_eq!(hub.tracks[main_track_id].name, "Drums");
+
+    assert_err!(
+        hub.get_many_mut([main_track_id, main_track_id]),
+        ErrorKind::Conflict
+    );
+    assert_err!(
+        hub.get_many_mut((main_track_id, drums_track_id)),
+        ErrorKind::Conflict
+    );
+    assert_err!(
+        hub.get_many_mut((arrangement_id, TrackId::default())),
+        ErrorKind::InvalidId
+    );
+
+    Ok(())
+}
+
+#[test]
+fn storage_changes() {
+    let document_id = DocumentId::from(KeyData::from_ffi(1));
+    let mut hub = Hub::default();
+
+    let node = || Node::new("gain".into(), Vec::new());
+    let a_key = ObjectKey::new(document_id, Uuid::from_u128(1));
+    let b_key = ObjectKey::new(document_id, Uuid::from_u128(2));
+    let c_key = ObjectKey::new(document_id, Uuid::from_u128(3));
+
+    let a = hub.nodes.insert(a_key, node());
+    hub.nodes[a].kind = "pan".into();
+    let b = hub.nodes.insert(b_key, node());
+    hub.nodes.remove(b);
+
+    let ty = ObjectType::Node;
+    assert_eq!(
+        hub.take_changes(),
+        [(
+            document_id,
+            DocumentChangeEvent::ObjectAdded {
+                ty,
+                uuid: a_key.uuid
+            }
+        )]
+    );
+
+    let c = hub.nodes.insert(c_key, node());
+    hub.nodes.get_mut(a).unwrap();
+    hub.nodes.remove(a);
+    hub.nodes.get_mut(c).unwrap();
+
+    let mut changes = hub.take_changes();
+    changes.sort_by_key(|(_, event)| match *event {
+        DocumentChangeEvent::ObjectAdded { uuid, .. }
+        | DocumentChangeEvent::ObjectRemoved { uuid, .. }
+        | DocumentChangeEvent::ObjectModified { uuid, .. } => uuid,
+    });
+
+    assert_eq!(
+        changes,
+        [
+            (
+                document_id,
+                DocumentChangeEvent::ObjectRemoved {
+                    ty,
+                    uuid: a_key.uuid
+                }
+            ),
+            (
+                document_id,
+                DocumentChangeEvent::ObjectAdded {
+                    ty,
+                    uuid: c_key.uuid
+                }
+            ),
+        ]
+    );
+
+    assert!(hub.take_changes().is_empty());
+}
+
+#[test]
+fn validate_missing_objects() -> Result<()> {
+    let mut fixture = Fixture::new()?;
+    let (arrangement_id, _, _) = fixture.populate();
+
+    let main_track_id = fixture.hub.arrangements[arrangement_id].main_track_id;
+    let drums_track_id = fixture.hub.tracks[main_track_id].links.children[0];
+    fixture.hub.tracks[main_track_id].mix.pan = 3.0;
+
+    fixture.hub.arrangements[arrangement_id].modulators.insert(Modulator {
+        name: "Sidechain".into(),
+        source: ModulationSource::EnvelopeFollower(EnvelopeFollower {
+            track_id: drums_track_id,
+            attack: RealTime::from_secs_f64(0.01),
+            release: RealTime::from_secs_f64(0.2),
+        }),
+        targets: Vec::new(),
+    });
+
+    fixture.serialize(arrangement_id)?;
+
+    // a copy of the document without the drums track
+    let mut copy = Fixture::new()?;
+    for uuid in [TEMPO_MAP_UUID, MAIN_TRACK_UUID, ARRANGEMENT_UUID] {
+        copy.write_object(uuid, &fixture.read_object(uuid)?)?;
+    }
+
+    assert_err!(
+        copy.deserialize::<ArrangementId>(ARRANGEMENT_UUID),
+        ErrorKind::InvalidUuid
+    );
+    copy.hub = Hub::default();
+
+    let arrangement_id = DeserializationContext::deserialize_partial::<ArrangementId>(
+        &mut copy.hub,
+        &copy.documents,
+        &copy.path_variables,
+        copy.document_id,
+        ARRANGEMENT_UUID,
+    )?;
+    let warnings = validate(&mut copy.hub, copy.document_id);
+
+    let summary = warnings
+        .iter()
+        .map(|v| (v.kind, v.object_type, v.uuid, v.repaired))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        summary,
+        [
+            (DanglingReference, ObjectType::Track, MAIN_TRACK_UUID, true),
+            (OutOfRange, ObjectType::Track, MAIN_TRACK_UUID, true),
+            (DanglingReference, ObjectType::Arrangement, ARRANGEMENT_UUID, true),
+        ]
+    );
+
+    let arrangement = &copy.hub.arrangements[arrangement_id];
+    assert!(arrangement.modulators.is_empty());
+
+    let main_track = &copy.hub.tracks[arrangement.main_track_id];
+    assert!(main_track.links.children.is_empty());
+    assert_eq!(main_track.mix.pan, 1.0);
+
+    let drums_key = ObjectKey::new(copy.document_id, DRUMS_TRACK_UUID);
+    assert_eq!(copy.hub.tracks.get_id(drums_key), None);
+
+    // nothing is left to repair
+    assert!(validate(&mut copy.hub, copy.document_id).is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn validate_duplicate_uuids() -> Result<()> {
+    let mut fixture = Fixture::new()?;
+    fixture.populate();
+    assert!(validate(&mut fixture.hub, fixture.document_id).is_empty());
+
+    fixture.hub.buses.insert(
+        ObjectKey::new(fixture.document_id, TEMPO_MAP_UUID),
+        Bus::new("Bus".into()),
+    );
+
+    let warnings = validate(&mut fixture.hub, fixture.document_id);
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].kind, DuplicateUuid);
+    assert_eq!(warnings[0].object_type, ObjectType::TempoMap);
+    assert_eq!(warnings[0].uuid, TEMPO_MAP_UUID);
+    assert!(!warnings[0].repaired);
+
+    Ok(())
+}
@@ -0,0 +1,457 @@
+use rdaw_api::document::{DocumentId, DocumentWarning, DocumentWarningKind};
+use rdaw_api::item::ItemId;
+use rdaw_api::modulation::ModulationSource;
+use rdaw_api::time::{BeatTime, Time};
+use rdaw_api::track::TrackMix;
+use rdaw_core::collections::{HashMap, HashSet};
+use rdaw_core::time::RealTime;
+
+use super::{Hub, Object, ObjectKey, ObjectType, Storage, Uuid};
+use crate::tempo_map::TempoMap;
+use crate::track::Track;
+
+type Problems = Vec<(DocumentWarningKind, String)>;
+
+/// Checks the objects of a freshly deserialized document. References to missing objects are
+/// removed and invalid values are reset, other problems are only reported. Objects which were
+/// referenced but missing from the document are discarded afterwards.
+pub fn validate(hub: &mut Hub, document_id: DocumentId) -> Vec<DocumentWarning> {
+    let mut validator = Validator {
+        hub,
+        document_id,
+        warnings: Vec::new(),
+    };
+
+    validator.check_uuids();
+    // unusable items are removed first, so that tracks and scenes drop them like missing ones
+    validator.check_sources_and_items();
+    validator.check_automation_lanes();
+    validator.check_tempo_maps();
+    validator.check_tracks();
+    validator.check_buses();
+    validator.check_arrangements();
+    validator.discard_missing();
+
+    validator.warnings
+}
+
+struct Validator<'a> {
+    hub: &'a mut Hub,
+    document_id: DocumentId,
+    warnings: Vec<DocumentWarning>,
+}
+
+impl Validator<'_> {
+    fn report(&mut self, object_type: ObjectType, uuid: Uuid, problems: Problems, repaired: bool) {
+        self.warnings
+            .extend(problems.into_iter().map(|(kind, message)| DocumentWarning {
+                kind,
+                object_type,
+                uuid,
+                message,
+                repaired,
+            }));
+    }
+
+    fn check_uuids(&mut self) {
+        let hub = &*self.hub;
+        let document_id = self.document_id;
+
+        let all_uuids = [
+            uuids(&hub.arrangements, document_id),
+            uuids(&hub.assets, document_id),
+            uuids(&hub.audio_items, document_id),
+            uuids(&hub.audio_sources, document_id),
+            uuids(&hub.automation_lanes, document_id),
+            uuids(&hub.buses, document_id),
+            uuids(&hub.midi_items, document_id),
+            uuids(&hub.midi_sources, document_id),
+            uuids(&hub.nodes, document_id),
+            uuids(&hub.tempo_maps, document_id),
+            uuids(&hub.tracks, document_id),
+        ];
+
+        // uuids are unique within a storage, so only objects of different types can collide
+        let mut seen = HashMap::default();
+        let mut duplicates = Vec::new();
+
+        for (object_type, uuid) in all_uuids.into_iter().flatten() {
+            match seen.get(&uuid) {
+                Some(&other_type) => duplicates.push((object_type, uuid, other_type)),
+                None => {
+                    seen.insert(uuid, object_type);
+                }
+            }
+        }
+
+        for (object_type, uuid, other_type) in duplicates {
+            let message = format!("the UUID is used by a {other_type:?} too");
+            let problems = vec![(DocumentWarningKind::DuplicateUuid, message)];
+            self.report(object_type, uuid, problems, false);
+        }
+    }
+
+    fn check_sources_and_items(&mut self) {
+        for (id, uuid) in objects(&self.hub.audio_sources, self.document_id) {
+            let asset_id = self.hub.audio_sources[id].asset_id;
+            if let Some(missing) = missing_object(&self.hub.assets, asset_id) {
+                self.hub.audio_sources.remove(id);
+                let message = format!("removed the source, its {missing} is missing");
+                let problems = vec![(DocumentWarningKind::DanglingReference, message)];
+                self.report(ObjectType::AudioSource, uuid, problems, true);
+            }
+        }
+
+        for (id, uuid) in objects(&self.hub.audio_items, self.document_id) {
+            let item = &self.hub.audio_items[id];
+            if let Some(missing) = missing_object(&self.hub.audio_sources, item.source_id) {
+                self.hub.audio_items.remove(id);
+                let message = format!("removed the item, its {missing} is missing");
+                let problems = vec![(DocumentWarningKind::DanglingReference, message)];
+                self.report(ObjectType::AudioItem, uuid, problems, true);
+                continue;
+            }
+
+            let mut clip = item.clip;
+            let mut problems = Problems::new();
+
+            if !clip.gain.is_finite() || clip.gain < 0.0 {
+                clip.gain = 1.0;
+                let message = "reset the invalid gain to 1".into();
+                problems.push((DocumentWarningKind::OutOfRange, message));
+            }
+
+            if clip.source_offset < RealTime::ZERO {
+                clip.source_offset = RealTime::ZERO;
+                let message = "reset the negative source offset to 0".into();
+                problems.push((DocumentWarningKind::OutOfRange, message));
+            }
+
+            if !problems.is_empty() {
+                self.hub.audio_items[id].clip = clip;
+                self.report(ObjectType::AudioItem, uuid, problems, true);
+            }
+        }
+
+        for (id, uuid) in objects(&self.hub.midi_items, self.document_id) {
+            let source_id = self.hub.midi_items[id].source_id;
+            if let Some(missing) = missing_object(&self.hub.midi_sources, source_id) {
+                self.hub.midi_items.remove(id);
+                let message = format!("removed the item, its {missing} is missing");
+                let problems = vec![(DocumentWarningKind::DanglingReference, message)];
+                self.report(ObjectType::MidiItem, uuid, problems, true);
+            }
+        }
+    }
+
+    fn check_automation_lanes(&mut self) {
+        for (id, uuid) in objects(&self.hub.automation_lanes, self.document_id) {
+            let node_id = self.hub.automation_lanes[id].target.node_id;
+            if let Some(missing) = missing_object(&self.hub.nodes, node_id) {
+                self.hub.automation_lanes.remove(id);
+                let message = format!("removed the lane, its target {missing} is missing");
+                let problems = vec![(DocumentWarningKind::DanglingReference, message)];
+                self.report(ObjectType::AutomationLane, uuid, problems, true);
+            }
+        }
+    }
+
+    fn check_tempo_maps(&mut self) {
+        for (id, uuid) in objects(&self.hub.tempo_maps, self.document_id) {
+            let bpm = self.hub.tempo_maps[id].beats_per_minute();
+            if !bpm.is_finite() || bpm <= 0.0 {
+                self.hub.tempo_maps[id] = TempoMap::new(DEFAULT_BPM);
+                let message = format!("reset the invalid tempo of {bpm} BPM to {DEFAULT_BPM}");
+                let problems = vec![(DocumentWarningKind::OutOfRange, message)];
+                self.report(ObjectType::TempoMap, uuid, problems, true);
+            }
+        }
+    }
+
+    fn check_tracks(&mut self) {
+        for (id, uuid) in objects(&self.hub.tracks, self.document_id) {
+            let hub = &*self.hub;
+            let mut track = hub.tracks[id].clone();
+            let mut problems = Problems::new();
+
+            let mut children = HashSet::default();
+            track.links.children.retain(|&child_id| {
+                if let Some(missing) = missing_object(&hub.tracks, child_id) {
+                    let message = format!("removed the missing child {missing}");
+                    problems.push((DocumentWarningKind::DanglingReference, message));
+                    false
+                } else if !children.insert(child_id) {
+                    let message = "removed a child track listed twice".into();
+                    problems.push((DocumentWarningKind::DuplicateUuid, message));
+                    false
+                } else {
+                    true
+                }
+            });
+
+            track.items.retain(|_, item| {
+                if let Some(missing) = missing_item(hub, item.inner) {
+                    let message = format!("removed the item of the missing {missing}");
+                    problems.push((DocumentWarningKind::DanglingReference, message));
+                    return false;
+                }
+
+                if !is_positive(item.duration) {
+                    let message = format!("removed an item of duration {:?}", item.duration);
+                    problems.push((DocumentWarningKind::OutOfRange, message));
+                    return false;
+                }
+
+                if is_negative(item.start) {
+                    item.start = match item.start {
+                        Time::Real(_) => Time::Real(RealTime::ZERO),
+                        Time::Beat(_) => Time::Beat(BeatTime::ZERO),
+                    };
+
+                    let message = "moved an item which started before zero".into();
+                    problems.push((DocumentWarningKind::OutOfRange, message));
+                }
+
+                true
+            });
+
+            track.nodes.retain(|&node_id| {
+                let Some(missing) = missing_object(&hub.nodes, node_id) else {
+                    return true;
+                };
+
+                let message = format!("removed the missing {missing}");
+                problems.push((DocumentWarningKind::DanglingReference, message));
+                false
+            });
+
+            track.automation_lanes.retain(|&lane_id| {
+                let Some(missing) = missing_object(&hub.automation_lanes, lane_id) else {
+                    return true;
+                };
+
+                let message = format!("removed the missing {missing}");
+                problems.push((DocumentWarningKind::DanglingReference, message));
+                false
+            });
+
+            track.sends.retain(|_, send| {
+                if let Some(missing) = missing_object(&hub.buses, send.bus_id) {
+                    let message = format!("removed the send to the missing {missing}");
+                    problems.push((DocumentWarningKind::DanglingReference, message));
+                    return false;
+                }
+
+                if !send.is_valid() {
+                    send.level = 1.0;
+                    let message = "reset the invalid send level to 1".into();
+                    problems.push((DocumentWarningKind::OutOfRange, message));
+                }
+
+                true
+            });
+
+            check_mix(&mut track.mix, &mut problems);
+
+            if !problems.is_empty() {
+                self.hub.tracks[id] = track;
+                self.report(ObjectType::Track, uuid, problems, true);
+            }
+        }
+    }
+
+    fn check_buses(&mut self) {
+        for (id, uuid) in objects(&self.hub.buses, self.document_id) {
+            let mut mix = self.hub.buses[id].mix;
+            let mut problems = Problems::new();
+
+            check_mix(&mut mix, &mut problems);
+
+            if !problems.is_empty() {
+                self.hub.buses[id].mix = mix;
+                self.report(ObjectType::Bus, uuid, problems, true);
+            }
+        }
+    }
+
+    fn check_arrangements(&mut self) {
+        for (id, uuid) in objects(&self.hub.arrangements, self.document_id) {
+            let mut arrangement = self.hub.arrangements[id].clone();
+            let mut problems = Problems::new();
+
+            if let Some(missing) = missing_object(&self.hub.tempo_maps, arrangement.tempo_map_id) {
+                let key = ObjectKey::new_random(self.document_id);
+                let tempo_map = TempoMap::new(DEFAULT_BPM);
+                arrangement.tempo_map_id = self.hub.tempo_maps.insert(key, tempo_map);
+
+                let message = format!("replaced the missing {missing} with a new one");
+                problems.push((DocumentWarningKind::DanglingReference, message));
+            }
+
+            if let Some(missing) = missing_object(&self.hub.tracks, arrangement.main_track_id) {
+                let key = ObjectKey::new_random(self.document_id);
+                let main_track = Track::new("Main Track".into());
+                arrangement.main_track_id = self.hub.tracks.insert(key, main_track);
+
+                let message = format!("replaced the missing main {missing} with an empty one");
+                problems.push((DocumentWarningKind::DanglingReference, message));
+            }
+
+            let hub = &*self.hub;
+
+            if let Some(video) = &arrangement.video {
+                if let Some(missing) = missing_object(&hub.assets, video.asset_id) {
+                    arrangement.video = None;
+                    let message = format!("removed the video, its {missing} is missing");
+                    problems.push((DocumentWarningKind::DanglingReference, message));
+                }
+            }
+
+            arrangement.buses.retain(|&bus_id| {
+                let Some(missing) = missing_object(&hub.buses, bus_id) else {
+                    return true;
+                };
+
+                let message = format!("removed the missing {missing}");
+                problems.push((DocumentWarningKind::DanglingReference, message));
+                false
+            });
+
+            arrangement.modulators.retain(|_, modulator| {
+                if let ModulationSource::EnvelopeFollower(follower) = &modulator.source {
+                    if let Some(missing) = missing_object(&hub.tracks, follower.track_id) {
+                        let message = format!(
+                            "removed the modulator \"{}\", its {missing} is missing",
+                            modulator.name
+                        );
+                        problems.push((DocumentWarningKind::DanglingReference, message));
+                        return false;
+                    }
+                }
+
+                modulator.targets.retain(|target| {
+                    let Some(missing) = missing_object(&hub.nodes, target.node_id) else {
+                        return true;
+                    };
+
+                    let message = format!("removed a modulation target of the missing {missing}");
+                    problems.push((DocumentWarningKind::DanglingReference, message));
+                    false
+                });
+
+                true
+            });
+
+            for scene in arrangement.scenes.values_mut() {
+                scene.clips.retain(|&track_id, clip| {
+                    let missing = missing_object(&hub.tracks, track_id)
+                        .or_else(|| missing_item(hub, clip.item));
+
+                    let Some(missing) = missing else {
+                        return true;
+                    };
+
+                    let message = format!(
+                        "removed a clip of the scene \"{}\", its {missing} is missing",
+                        scene.name
+                    );
+                    problems.push((DocumentWarningKind::DanglingReference, message));
+                    false
+                });
+            }
+
+            if !problems.is_empty() {
+                self.hub.arrangements[id] = arrangement;
+                self.report(ObjectType::Arrangement, uuid, problems, true);
+            }
+        }
+    }
+
+    fn discard_missing(&mut self) {
+        let hub = &mut *self.hub;
+        hub.arrangements.discard_unfinished(self.document_id);
+        hub.assets.discard_unfinished(self.document_id);
+        hub.audio_items.discard_unfinished(self.document_id);
+        hub.audio_sources.discard_unfinished(self.document_id);
+        hub.automation_lanes.discard_unfinished(self.document_id);
+        hub.buses.discard_unfinished(self.document_id);
+        hub.midi_items.discard_unfinished(self.document_id);
+        hub.midi_sources.discard_unfinished(self.document_id);
+        hub.nodes.discard_unfinished(self.document_id);
+        hub.tempo_maps.discard_unfinished(self.document_id);
+        hub.tracks.discard_unfinished(self.document_id);
+    }
+}
+
+const DEFAULT_BPM: f32 = 120.0;
+
+fn objects<T: Object>(storage: &Storage<T>, document_id: DocumentId) -> Vec<(T::Id, Uuid)> {
+    storage
+        .iter()
+        .filter(|(_, key, _)| key.document_id == document_id)
+        .map(|(id, key, _)| (id, key.uuid))
+        .collect()
+}
+
+/// Describes the object if it's missing, e.g. `AudioSource 67e55044-...`.
+fn missing_object<T: Object>(storage: &Storage<T>, id: T::Id) -> Option<String> {
+    if storage.has(id) {
+        return None;
+    }
+
+    Some(match storage.get_key(id) {
+        Some(key) => format!("{:?} {}", T::TYPE, key.uuid),
+        None => format!("{:?}", T::TYPE),
+    })
+}
+
+fn missing_item(hub: &Hub, id: ItemId) -> Option<String> {
+    match id {
+        ItemId::Audio(id) => missing_object(&hub.audio_items, id),
+        ItemId::Midi(id) => missing_object(&hub.midi_items, id),
+    }
+}
+
+fn uuids<T: Object>(storage: &Storage<T>, document_id: DocumentId) -> Vec<(ObjectType, Uuid)> {
+    objects(storage, document_id)
+        .into_iter()
+        .map(|(_, uuid)| (T::TYPE, uuid))
+        .collect()
+}
+
+fn check_mix(mix: &mut TrackMix, problems: &mut Problems) {
+    let default = TrackMix::default();
+
+    if !mix.volume.is_finite() || mix.volume < 0.0 {
+        let message = format!(
+            "reset the invalid volume {} to {}",
+            mix.volume, default.volume
+        );
+        problems.push((DocumentWarningKind::OutOfRange, message));
+        mix.volume = default.volume;
+    }
+
+    if !mix.pan.is_finite() {
+        let message = format!("reset the invalid pan {} to {}", mix.pan, default.pan);
+        problems.push((DocumentWarningKind::OutOfRange, message));
+        mix.pan = default.pan;
+    } else if !(-1.0..=1.0).contains(&mix.pan) {
+        let message = format!("clamped the pan {} to the range from -1 to 1", mix.pan);
+        problems.push((DocumentWarningKind::OutOfRange, message));
+        mix.pan = mix.pan.clamp(-1.0, 1.0);
+    }
+}
+
+fn is_positive(time: Time) -> bool {
+    match time {
+        Time::Real(v) => v > RealTime::ZERO,
+        Time::Beat(v) => v > BeatTime::ZERO,
+    }
+}
+
+fn is_negative(time: Time) -> bool {
+    match time {
+        Time::Real(v) => v < RealTime::ZERO,
+        Time::Beat(v) => v < BeatTime::ZERO,
+    }
+}
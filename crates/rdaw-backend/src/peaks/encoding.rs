@@ -0,0 +1,74 @@
+use rdaw_api::waveform::Peak;
+use rdaw_api::{bail, ErrorKind, Result};
+use serde::{Deserialize, Serialize};
+
+use super::PeakPyramid;
+use crate::define_version_enum;
+use crate::document::encoding::{self, Format};
+
+/// Only the finest level is stored, the rest are cheap to rebuild.
+pub fn serialize(pyramid: &PeakPyramid) -> Result<Vec<u8>> {
+    let raw = PeaksLatest {
+        sample_rate: pyramid.sample_rate,
+        num_channels: pyramid.num_channels as u32,
+        peaks: pyramid
+            .base()
+            .iter()
+            .map(|peak| PeakLatest {
+                min: peak.min,
+                max: peak.max,
+            })
+            .collect(),
+    };
+
+    encoding::serialize(Format::Binary, Version::LATEST.as_u32(), &raw)
+}
+
+pub fn deserialize(data: &[u8]) -> Result<PeakPyramid> {
+    let (version, data) = encoding::extract_version(Format::Binary, data)?;
+    let raw = match Version::from_u32(version)? {
+        Version::V1 => encoding::deserialize::<PeaksV1>(Format::Binary, data)?,
+    };
+
+    let num_channels = raw.num_channels as usize;
+    if num_channels == 0 || raw.peaks.len() % num_channels != 0 {
+        bail!(
+            ErrorKind::Deserialization,
+            "{} peaks can't have {num_channels} channels",
+            raw.peaks.len()
+        );
+    }
+
+    let base = raw
+        .peaks
+        .into_iter()
+        .map(|peak| Peak {
+            min: peak.min,
+            max: peak.max,
+        })
+        .collect();
+
+    Ok(PeakPyramid::new(raw.sample_rate, num_channels, base))
+}
+
+define_version_enum! {
+    enum Version {
+        V1 = 1,
+    }
+}
+
+type PeaksLatest = PeaksV1;
+type PeakLatest = PeakV1;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct PeaksV1 {
+    sample_rate: u32,
+    num_channels: u32,
+    peaks: Vec<PeakV1>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct PeakV1 {
+    min: f32,
+    max: f32,
+}
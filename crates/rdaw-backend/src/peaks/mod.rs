@@ -0,0 +1,199 @@
+mod encoding;
+mod ops;
+#[cfg(test)]
+mod tests;
+
+use std::sync::Arc;
+
+use rdaw_api::audio::AudioInputStream as _;
+use rdaw_api::media::{MediaInput as _, OpenMediaInput as _};
+use rdaw_api::track::TrackViewId;
+use rdaw_api::waveform::{Peak, Waveform};
+use rdaw_api::{bail, ErrorKind, Result};
+use rdaw_ffmpeg::MediaInput;
+use rdaw_rpc::CancellationToken;
+
+use crate::asset::AssetReader;
+use crate::ensure_not_cancelled;
+
+/// Number of frames summarized by a peak of the finest level.
+pub const BASE_FRAMES_PER_PEAK: u32 = 256;
+
+pub fn frames_per_peak(level: usize) -> u32 {
+    let frames = u64::from(BASE_FRAMES_PER_PEAK) << level.min(32);
+    frames.min(u64::from(u32::MAX)) as u32
+}
+
+/// Min/max peaks of an audio source at several resolutions. Every level has half as many peaks
+/// as the one before it, down to a single peak per channel.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PeakPyramid {
+    pub sample_rate: u32,
+    pub num_channels: usize,
+    levels: Vec<Arc<[Peak]>>,
+}
+
+impl PeakPyramid {
+    /// Builds coarser levels from the finest one, which has interleaved channels.
+    pub fn new(sample_rate: u32, num_channels: usize, base: Vec<Peak>) -> PeakPyramid {
+        let num_channels = num_channels.max(1);
+        let mut levels: Vec<Arc<[Peak]>> = vec![base.into()];
+
+        while levels[levels.len() - 1].len() > num_channels {
+            let level = levels[levels.len() - 1]
+                .chunks(num_channels * 2)
+                .flat_map(|pair| {
+                    let (left, right) = pair.split_at(num_channels);
+                    (0..num_channels).map(move |channel| match right.get(channel) {
+                        Some(&peak) => left[channel].merge(peak),
+                        None => left[channel],
+                    })
+                })
+                .collect::<Vec<_>>();
+
+            levels.push(level.into());
+        }
+
+        PeakPyramid {
+            sample_rate,
+            num_channels,
+            levels,
+        }
+    }
+
+    pub fn num_levels(&self) -> usize {
+        self.levels.len()
+    }
+
+    pub fn base(&self) -> &[Peak] {
+        &self.levels[0]
+    }
+
+    /// Coarsest level with at most `resolution` frames per peak, or the finest one.
+    pub fn level_for(&self, resolution: u32) -> usize {
+        let mut level = 0;
+        while level + 1 < self.levels.len() && frames_per_peak(level + 1) <= resolution {
+            level += 1;
+        }
+        level
+    }
+
+    pub fn waveform(&self, level: usize) -> Waveform {
+        let level = level.min(self.levels.len() - 1);
+        Waveform {
+            sample_rate: self.sample_rate,
+            frames_per_peak: frames_per_peak(level),
+            num_channels: self.num_channels,
+            peaks: self.levels[level].clone(),
+        }
+    }
+}
+
+/// Accumulates interleaved frames into the finest level of a pyramid.
+#[derive(Debug)]
+pub struct PeakBuilder {
+    num_channels: usize,
+    peaks: Vec<Peak>,
+    current: Vec<Peak>,
+    current_frames: u32,
+}
+
+impl PeakBuilder {
+    pub fn new(num_channels: usize) -> PeakBuilder {
+        let num_channels = num_channels.max(1);
+        PeakBuilder {
+            num_channels,
+            peaks: Vec::new(),
+            current: vec![Peak::default(); num_channels],
+            current_frames: 0,
+        }
+    }
+
+    /// Incomplete frames at the end of `samples` are ignored.
+    pub fn push(&mut self, samples: &[f32]) {
+        for frame in samples.chunks_exact(self.num_channels) {
+            for (peak, &sample) in self.current.iter_mut().zip(frame) {
+                let sample = Peak {
+                    min: sample,
+                    max: sample,
+                };
+
+                *peak = match self.current_frames {
+                    0 => sample,
+                    _ => peak.merge(sample),
+                };
+            }
+
+            self.current_frames += 1;
+
+            if self.current_frames == BASE_FRAMES_PER_PEAK {
+                self.peaks.extend_from_slice(&self.current);
+                self.current_frames = 0;
+            }
+        }
+    }
+
+    pub fn finish(mut self, sample_rate: u32) -> PeakPyramid {
+        if self.current_frames > 0 {
+            self.peaks.extend_from_slice(&self.current);
+        }
+
+        PeakPyramid::new(sample_rate, self.num_channels, self.peaks)
+    }
+}
+
+#[derive(Debug)]
+pub enum PeaksState {
+    /// Views which have asked for the peaks, they are told about the progress.
+    Computing {
+        views: Vec<TrackViewId>,
+        /// Tokens of the requests waiting for the peaks. The job stops once the first one is
+        /// cancelled, and is started again for the rest.
+        requests: Vec<CancellationToken>,
+    },
+    Ready(Arc<PeakPyramid>),
+    /// Computed from the asset again when they're loaded next time.
+    Failed,
+}
+
+/// Decodes the whole asset, reporting the analyzed fraction every percent.
+pub fn compute_peaks(
+    reader: AssetReader,
+    cancel: &CancellationToken,
+    mut progress: impl FnMut(f32),
+) -> Result<PeakPyramid> {
+    let mut media = MediaInput::open(reader)?;
+    let Some(mut stream) = media.get_audio_stream()? else {
+        bail!(ErrorKind::NotFound, "asset doesn't have an audio stream");
+    };
+
+    let num_channels = stream.metadata().channels.len().max(1);
+    let sample_rate = stream.metadata().sample_rate;
+    let total_frames = stream.metadata().duration.as_secs_f64() * f64::from(sample_rate);
+
+    let mut builder = PeakBuilder::new(num_channels);
+    let mut num_frames = 0;
+    let mut reported = 0.0;
+
+    loop {
+        ensure_not_cancelled(cancel)?;
+
+        let frame = stream.next_frame()?;
+        if frame.is_empty() {
+            break;
+        }
+
+        builder.push(frame);
+        num_frames += frame.len() / num_channels;
+
+        if total_frames > 0.0 {
+            let fraction = (num_frames as f64 / total_frames).min(1.0) as f32;
+            if fraction - reported >= 0.01 {
+                reported = fraction;
+                progress(fraction);
+            }
+        }
+    }
+
+    Ok(builder.finish(sample_rate))
+}
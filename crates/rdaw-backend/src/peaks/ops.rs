@@ -0,0 +1,362 @@
+use std::io::{Read, Write};
+use std::sync::Arc;
+
+use blake3::Hash;
+use rdaw_api::asset::AssetId;
+use rdaw_api::cache::CacheKind;
+use rdaw_api::document::DocumentId;
+use rdaw_api::item::ItemId;
+use rdaw_api::source::AudioSourceId;
+use rdaw_api::stats::TaskQueue;
+use rdaw_api::track::TrackViewId;
+use rdaw_api::waveform::{
+    ItemWaveform, WaveformEvent, WaveformOperations, WaveformRequest, WaveformResponse,
+};
+use rdaw_api::{BackendProtocol, Error, ErrorKind, Result};
+use rdaw_rpc::{CancellationToken, Responder, StreamId};
+use tracing::instrument;
+
+use super::{compute_peaks, encoding, PeakPyramid, PeaksState};
+use crate::asset::AssetReader;
+use crate::cache::FileCache;
+use crate::document::{BlobReader, BlobWriter, Compression};
+use crate::Backend;
+
+#[rdaw_rpc::handler(protocol = BackendProtocol, operations = WaveformOperations)]
+impl Backend {
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn get_waveform(
+        &mut self,
+        view_id: TrackViewId,
+        resolution: u32,
+    ) -> Result<Vec<ItemWaveform>> {
+        let items = self.get_track_view_range(view_id, None, None)?;
+        let mut waveforms = Vec::new();
+
+        for (item_id, item) in items {
+            let ItemId::Audio(audio_item_id) = item.inner else {
+                continue;
+            };
+
+            let source_id = self.hub.audio_items.get_or_err(audio_item_id)?.source_id;
+
+            if let Some(PeaksState::Ready(pyramid)) = self.waveforms.get(&source_id) {
+                let level = pyramid.level_for(resolution);
+                waveforms.push(ItemWaveform {
+                    item_id,
+                    source_id,
+                    waveform: pyramid.waveform(level),
+                });
+            }
+        }
+
+        Ok(waveforms)
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn load_waveform(
+        &mut self,
+        responder: impl Responder<(), Error>,
+        cancel: CancellationToken,
+        view_id: TrackViewId,
+    ) -> Result<()> {
+        let items = self.get_track_view_range(view_id, None, None)?;
+
+        for (_, item) in items {
+            let ItemId::Audio(audio_item_id) = item.inner else {
+                continue;
+            };
+
+            let source_id = self.hub.audio_items.get_or_err(audio_item_id)?.source_id;
+            self.load_waveform_peaks(view_id, source_id, &cancel)?;
+        }
+
+        // the peaks keep loading after the response, until the token is cancelled
+        self.queue
+            .defer(move |_: &mut Backend| responder.respond(Ok(())));
+
+        Ok(())
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn subscribe_waveform_progress(&mut self, view_id: TrackViewId) -> Result<StreamId> {
+        self.hub.arrangements.ensure_has(view_id.arrangement_id)?;
+        self.hub.tracks.ensure_has(view_id.track_id)?;
+        Ok(self.subscribers.waveform_progress.subscribe(view_id))
+    }
+
+    /// Starts loading the peaks of a source from the document or computing them in the
+    /// background, unless they're ready. The view is told once they are.
+    fn load_waveform_peaks(
+        &mut self,
+        view_id: TrackViewId,
+        source_id: AudioSourceId,
+        cancel: &CancellationToken,
+    ) -> Result<()> {
+        let from_asset = match self.waveforms.get_mut(&source_id) {
+            Some(PeaksState::Ready(_)) => return Ok(()),
+            Some(PeaksState::Computing { views, requests }) => {
+                if !views.contains(&view_id) {
+                    views.push(view_id);
+                }
+                requests.push(cancel.clone());
+                return Ok(());
+            }
+            // the peaks stored in the document might be what's broken
+            Some(PeaksState::Failed) => true,
+            None => false,
+        };
+
+        self.start_waveform_peaks(source_id, vec![view_id], vec![cancel.clone()], from_asset)
+    }
+
+    fn start_waveform_peaks(
+        &mut self,
+        source_id: AudioSourceId,
+        views: Vec<TrackViewId>,
+        requests: Vec<CancellationToken>,
+        from_asset: bool,
+    ) -> Result<()> {
+        let asset_id = self.hub.audio_sources.get_or_err(source_id)?.asset_id;
+        let asset_hash = self.hub.assets.get_or_err(asset_id)?.hash();
+        let document_id = self
+            .hub
+            .audio_sources
+            .get_key_or_err(source_id)?
+            .document_id;
+
+        let job = match self.peaks_job(document_id, asset_id, asset_hash, from_asset) {
+            Ok(v) => v,
+            Err(error) => {
+                tracing::warn!(?source_id, ?error, "can't compute waveform peaks");
+                self.waveforms.insert(source_id, PeaksState::Failed);
+                return Ok(());
+            }
+        };
+
+        let from_asset = matches!(job, PeaksJob::Compute { .. });
+        let cancel = requests[0].clone();
+        self.waveforms
+            .insert(source_id, PeaksState::Computing { views, requests });
+
+        let queue = self.queue.clone();
+        self.spawn(TaskQueue::Analysis, async move {
+            let res = match job {
+                PeaksJob::Load(blob) => load_peaks(blob).map(|v| (v, None)),
+                PeaksJob::Compute {
+                    reader,
+                    blob,
+                    cache,
+                } => {
+                    let progress_queue = queue.clone();
+                    let progress = move |progress| {
+                        progress_queue.defer(move |this: &mut Backend| {
+                            this.notify_waveform_progress(
+                                source_id,
+                                WaveformEvent::Progress {
+                                    source_id,
+                                    progress,
+                                },
+                            );
+                            std::future::ready(Ok(()))
+                        });
+                    };
+
+                    store_peaks(
+                        reader,
+                        blob,
+                        cache.as_deref(),
+                        asset_hash,
+                        &cancel,
+                        progress,
+                    )
+                    .map(|(v, hash)| (v, Some(hash)))
+                }
+            };
+
+            queue.defer(move |this: &mut Backend| {
+                this.finish_waveform_peaks(document_id, asset_hash, source_id, from_asset, res);
+                std::future::ready(Ok(()))
+            });
+
+            Ok(())
+        });
+
+        Ok(())
+    }
+
+    fn peaks_job(
+        &self,
+        document_id: DocumentId,
+        asset_id: AssetId,
+        asset_hash: Hash,
+        from_asset: bool,
+    ) -> Result<PeaksJob> {
+        let document = self.documents.get_or_err(document_id)?;
+
+        let stored = match from_asset {
+            true => None,
+            false => document.find_waveform_peaks(asset_hash)?,
+        };
+
+        if let Some(hash) = stored {
+            if let Some(blob) = document.open_blob(hash)? {
+                return Ok(PeaksJob::Load(blob));
+            }
+        }
+
+        Ok(PeaksJob::Compute {
+            reader: self.open_asset(asset_id)?,
+            blob: document.create_blob(Compression::Zstd)?,
+            cache: self.cache.clone(),
+        })
+    }
+
+    fn finish_waveform_peaks(
+        &mut self,
+        document_id: DocumentId,
+        asset_hash: Hash,
+        source_id: AudioSourceId,
+        from_asset: bool,
+        res: Result<(PeakPyramid, Option<Hash>)>,
+    ) {
+        let (state, event) = match res {
+            Ok((pyramid, hash)) => {
+                if let (Some(hash), Some(document)) = (hash, self.documents.get(document_id)) {
+                    if let Err(error) = document.write_waveform_peaks(asset_hash, hash) {
+                        tracing::warn!(?source_id, ?error, "failed to cache waveform peaks");
+                    }
+                }
+
+                (
+                    PeaksState::Ready(Arc::new(pyramid)),
+                    WaveformEvent::Ready { source_id },
+                )
+            }
+            Err(error) if error.kind() == ErrorKind::Cancelled => {
+                let Some(PeaksState::Computing {
+                    views,
+                    mut requests,
+                }) = self.waveforms.remove(&source_id)
+                else {
+                    return;
+                };
+
+                // other requests might still be waiting for the peaks
+                requests.retain(|cancel| !cancel.is_cancelled());
+                if requests.is_empty() {
+                    return;
+                }
+
+                if let Err(error) =
+                    self.start_waveform_peaks(source_id, views, requests, from_asset)
+                {
+                    tracing::warn!(?source_id, ?error, "can't compute waveform peaks");
+                }
+
+                return;
+            }
+            Err(error) if !from_asset => {
+                tracing::warn!(?source_id, ?error, "failed to load waveform peaks");
+
+                let Some(PeaksState::Computing { views, requests }) =
+                    self.waveforms.remove(&source_id)
+                else {
+                    return;
+                };
+
+                if let Err(error) =
+                    self.start_waveform_peaks(source_id, views.clone(), requests, true)
+                {
+                    tracing::warn!(?source_id, ?error, "can't compute waveform peaks");
+                    for view_id in views {
+                        let event = WaveformEvent::Failed { source_id };
+                        self.subscribers.waveform_progress.notify(view_id, event);
+                    }
+                }
+
+                return;
+            }
+            Err(error) => {
+                tracing::warn!(?source_id, ?error, "failed to compute waveform peaks");
+                (PeaksState::Failed, WaveformEvent::Failed { source_id })
+            }
+        };
+
+        self.notify_waveform_progress(source_id, event);
+        self.waveforms.insert(source_id, state);
+    }
+
+    fn notify_waveform_progress(&mut self, source_id: AudioSourceId, event: WaveformEvent) {
+        let Some(PeaksState::Computing { views, .. }) = self.waveforms.get(&source_id) else {
+            return;
+        };
+
+        for &view_id in views {
+            self.subscribers.waveform_progress.notify(view_id, event);
+        }
+    }
+}
+
+enum PeaksJob {
+    Load(BlobReader),
+    Compute {
+        reader: AssetReader,
+        blob: BlobWriter,
+        cache: Option<Arc<FileCache>>,
+    },
+}
+
+fn load_peaks(mut blob: BlobReader) -> Result<PeakPyramid> {
+    let mut data = Vec::new();
+    blob.read_to_end(&mut data)?;
+    encoding::deserialize(&data)
+}
+
+/// Computes the peaks, unless another document already did and they're in the cache, and
+/// stores them in the document.
+fn store_peaks(
+    reader: AssetReader,
+    mut blob: BlobWriter,
+    cache: Option<&FileCache>,
+    asset_hash: Hash,
+    cancel: &CancellationToken,
+    progress: impl FnMut(f32),
+) -> Result<(PeakPyramid, Hash)> {
+    let (pyramid, data) = match cache.and_then(|cache| load_cached_peaks(cache, asset_hash)) {
+        Some(v) => v,
+        None => {
+            let pyramid = compute_peaks(reader, cancel, progress)?;
+            let data = encoding::serialize(&pyramid)?;
+
+            if let Some(cache) = cache {
+                if let Err(error) = cache.write(CacheKind::Waveform, asset_hash, &data) {
+                    tracing::warn!(?asset_hash, ?error, "failed to write waveform peaks to cache");
+                }
+            }
+
+            (pyramid, data)
+        }
+    };
+
+    blob.write_all(&data)?;
+    let hash = blob.save()?;
+    Ok((pyramid, hash))
+}
+
+fn load_cached_peaks(cache: &FileCache, asset_hash: Hash) -> Option<(PeakPyramid, Vec<u8>)> {
+    let res = cache
+        .read(CacheKind::Waveform, asset_hash)
+        .and_then(|data| match data {
+            Some(data) => Ok(Some((encoding::deserialize(&data)?, data))),
+            None => Ok(None),
+        });
+
+    res.unwrap_or_else(|error| {
+        tracing::warn!(?asset_hash, ?error, "failed to load cached waveform peaks");
+        None
+    })
+}
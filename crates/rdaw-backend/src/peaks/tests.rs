@@ -0,0 +1,217 @@
+use std::fs::{self, File};
+
+use futures::StreamExt;
+use rdaw_api::arrangement::ArrangementOperations;
+use rdaw_api::cache::{CacheKind, CacheOperations};
+use rdaw_api::document::DocumentOperations;
+use rdaw_api::interchange::InterchangeOperations;
+use rdaw_api::track::{TrackOperations, TrackViewId};
+use rdaw_api::waveform::{Peak, WaveformEvent, WaveformOperations};
+use rdaw_api::{assert_err, ErrorKind, Result};
+use rdaw_core::path::Utf8Path;
+use rdaw_rpc::CancellationToken;
+
+use super::{
+    compute_peaks, encoding, frames_per_peak, PeakBuilder, PeakPyramid, BASE_FRAMES_PER_PEAK,
+};
+use crate::asset::AssetReader;
+use crate::tests::{run_test, TestClient};
+
+fn peak(min: f32, max: f32) -> Peak {
+    Peak { min, max }
+}
+
+async fn import_view(client: &TestClient, dir: &Utf8Path, file: &str) -> Result<TrackViewId> {
+    let session = format!(
+        r#"{{
+            "version": 1,
+            "sources": [
+                {{ "id": "a", "path": "{file}", "sample_rate": 44100, "channels": 1, "duration": 1.0 }}
+            ],
+            "tracks": [
+                {{ "name": "A", "clips": [{{ "source": "a", "start": 0.0, "duration": 1.0 }}] }}
+            ]
+        }}"#
+    );
+    fs::write(dir.join("session.json"), session)?;
+
+    let document_id = client.import_session(dir.join("session.json")).await?;
+    let arrangement_id = client.get_document_arrangement(document_id).await?;
+    let main_track_id = client.get_arrangement_main_track(arrangement_id).await?;
+    let track_id = client.get_track_children(main_track_id).await?[0];
+
+    Ok(TrackViewId {
+        track_id,
+        arrangement_id,
+    })
+}
+
+#[test]
+fn builder() {
+    let mut builder = PeakBuilder::new(2);
+
+    let frames = (0..BASE_FRAMES_PER_PEAK + 1)
+        .flat_map(|i| [i as f32, -(i as f32)])
+        .collect::<Vec<_>>();
+    builder.push(&frames);
+
+    let pyramid = builder.finish(48000);
+    let last = BASE_FRAMES_PER_PEAK as f32;
+    assert_eq!(
+        pyramid.base(),
+        [
+            peak(0.0, last - 1.0),
+            peak(-(last - 1.0), 0.0),
+            peak(last, last),
+            peak(-last, -last),
+        ]
+    );
+}
+
+#[test]
+fn levels() {
+    let base = (0..5).map(|i| peak(-(i as f32), i as f32)).collect();
+    let pyramid = PeakPyramid::new(48000, 1, base);
+
+    assert_eq!(pyramid.num_levels(), 4);
+    assert_eq!(
+        pyramid.waveform(1).peaks[..],
+        [peak(-1.0, 1.0), peak(-3.0, 3.0), peak(-4.0, 4.0)]
+    );
+    assert_eq!(pyramid.waveform(3).peaks[..], [peak(-4.0, 4.0)]);
+
+    assert_eq!(pyramid.level_for(0), 0);
+    assert_eq!(pyramid.level_for(frames_per_peak(1)), 1);
+    assert_eq!(pyramid.level_for(frames_per_peak(2) - 1), 1);
+    assert_eq!(pyramid.level_for(u32::MAX), 3);
+    assert_eq!(
+        pyramid.waveform(2).frames_per_peak,
+        4 * BASE_FRAMES_PER_PEAK
+    );
+}
+
+#[test]
+fn roundtrip() -> Result<()> {
+    let base = (0..6).map(|i| peak(-(i as f32), 0.5)).collect();
+    let pyramid = PeakPyramid::new(44100, 2, base);
+
+    let data = encoding::serialize(&pyramid)?;
+    assert_eq!(encoding::deserialize(&data)?, pyramid);
+
+    Ok(())
+}
+
+#[test]
+fn cancelled_peaks() -> Result<()> {
+    let sample = Utf8Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("../rdaw-ffmpeg/tests/samples/220_Hz_sine_wave.ogg");
+    let reader = AssetReader::from_file(File::open(sample)?);
+
+    let cancel = CancellationToken::new();
+    cancel.cancel();
+    assert_err!(compute_peaks(reader, &cancel, |_| {}), ErrorKind::Cancelled);
+
+    Ok(())
+}
+
+#[test]
+fn get_waveform() -> Result<()> {
+    run_test(|client| async move {
+        let dir = tempfile::tempdir()?;
+        let dir = Utf8Path::from_path(dir.path()).unwrap();
+
+        let sample = Utf8Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("../rdaw-ffmpeg/tests/samples/220_Hz_sine_wave.ogg");
+        fs::copy(sample, dir.join("sine.ogg"))?;
+
+        let view_id = import_view(&client, dir, "sine.ogg").await?;
+        let mut stream = client.subscribe_waveform_progress(view_id).await?;
+
+        client.load_waveform(view_id).await?;
+        assert!(client.get_waveform(view_id, 1024).await?.is_empty());
+
+        loop {
+            match stream.next().await {
+                Some(WaveformEvent::Progress { progress, .. }) => assert!(progress <= 1.0),
+                Some(WaveformEvent::Ready { .. }) => break,
+                event => panic!("unexpected event {event:?}"),
+            }
+        }
+
+        let waveforms = client.get_waveform(view_id, 1024).await?;
+        assert_eq!(waveforms.len(), 1);
+
+        let waveform = &waveforms[0].waveform;
+        assert_eq!(waveform.frames_per_peak, 1024);
+        assert!(waveform.num_peaks() > 0);
+
+        let peak = waveform.channel_peak(0, 0).unwrap();
+        assert!(peak.min < 0.0 && peak.max > 0.0);
+
+        Ok(())
+    })
+}
+
+#[test]
+fn cached_waveform() -> Result<()> {
+    run_test(|client| async move {
+        let dir = tempfile::tempdir()?;
+        let dir = Utf8Path::from_path(dir.path()).unwrap();
+
+        let sample = Utf8Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("../rdaw-ffmpeg/tests/samples/220_Hz_sine_wave.ogg");
+        fs::copy(sample, dir.join("sine.ogg"))?;
+
+        client.set_cache_dir(Some(dir.join("cache"))).await?;
+
+        let view_id = import_view(&client, dir, "sine.ogg").await?;
+        let mut stream = client.subscribe_waveform_progress(view_id).await?;
+        client.load_waveform(view_id).await?;
+
+        loop {
+            match stream.next().await {
+                Some(WaveformEvent::Progress { .. }) => {}
+                Some(WaveformEvent::Ready { .. }) => break,
+                event => panic!("unexpected event {event:?}"),
+            }
+        }
+
+        let usage = client.get_cache_usage().await?;
+        let waveform = usage
+            .iter()
+            .find(|v| v.kind == CacheKind::Waveform)
+            .unwrap();
+        assert_eq!(waveform.num_entries, 1);
+        assert!(waveform.size > 0);
+
+        Ok(())
+    })
+}
+
+#[test]
+fn broken_asset() -> Result<()> {
+    run_test(|client| async move {
+        let dir = tempfile::tempdir()?;
+        let dir = Utf8Path::from_path(dir.path()).unwrap();
+        fs::write(dir.join("broken.wav"), [1, 2, 3])?;
+
+        let view_id = import_view(&client, dir, "broken.wav").await?;
+        let mut stream = client.subscribe_waveform_progress(view_id).await?;
+
+        client.load_waveform(view_id).await?;
+        assert!(matches!(
+            stream.next().await,
+            Some(WaveformEvent::Failed { .. })
+        ));
+        assert!(client.get_waveform(view_id, 1024).await?.is_empty());
+
+        // failed peaks are computed again
+        client.load_waveform(view_id).await?;
+        assert!(matches!(
+            stream.next().await,
+            Some(WaveformEvent::Failed { .. })
+        ));
+
+        Ok(())
+    })
+}
@@ -0,0 +1,122 @@
+mod ops;
+#[cfg(test)]
+mod tests;
+
+use std::sync::Arc;
+
+use rdaw_api::node::{NodeId, NodeParam};
+use rdaw_api::plugin::{PluginDescriptor, PluginParam};
+use rdaw_clap::{PluginLibrary, PluginNode};
+use rdaw_core::collections::{HashMap, HashSet};
+use rdaw_core::path::Utf8PathBuf;
+
+#[derive(Debug, Default)]
+pub struct PluginHost {
+    libraries: Vec<Arc<PluginLibrary>>,
+    nodes: HashMap<NodeId, PluginNodeEntry>,
+}
+
+#[derive(Debug)]
+struct PluginNodeEntry {
+    node: PluginNode,
+    params: Vec<PluginParam>,
+}
+
+impl PluginHost {
+    /// Libraries of a previous scan are kept, they can't be safely loaded twice.
+    pub fn scan(&mut self, paths: &[Utf8PathBuf]) {
+        for path in rdaw_clap::find_plugin_files(paths) {
+            if self.libraries.iter().any(|v| v.path() == path) {
+                continue;
+            }
+
+            match PluginLibrary::load(&path) {
+                Ok(library) => self.libraries.push(library),
+                Err(error) => tracing::warn!(%path, ?error, "skipping plugin"),
+            }
+        }
+    }
+
+    pub fn descriptors(&self) -> impl Iterator<Item = &PluginDescriptor> + '_ {
+        self.libraries.iter().flat_map(|v| v.descriptors())
+    }
+
+    pub fn find_library(&self, plugin_id: &str) -> Option<&Arc<PluginLibrary>> {
+        self.libraries
+            .iter()
+            .find(|v| v.descriptor(plugin_id).is_some())
+    }
+
+    pub fn insert_node(&mut self, id: NodeId, node: PluginNode, params: Vec<PluginParam>) {
+        self.nodes.insert(id, PluginNodeEntry { node, params });
+    }
+
+    pub fn remove_node(&mut self, id: NodeId) {
+        self.nodes.remove(&id);
+    }
+
+    pub fn get_node(&self, id: NodeId) -> Option<&PluginNode> {
+        self.nodes.get(&id).map(|v| &v.node)
+    }
+
+    pub fn node_params(&self, id: NodeId) -> Option<&[PluginParam]> {
+        self.nodes.get(&id).map(|v| &v.params[..])
+    }
+
+    /// Does nothing if the node isn't running a plugin.
+    pub fn set_node_param(&self, id: NodeId, name: &str, value: f32) {
+        let Some(entry) = self.nodes.get(&id) else {
+            return;
+        };
+
+        if let Some(param) = entry.params.iter().find(|v| v.name == name) {
+            entry.node.set_param(param.id, value.into());
+        }
+    }
+}
+
+/// The id is appended to repeated names.
+pub fn plugin_params(node: &PluginNode) -> Vec<PluginParam> {
+    let mut names = HashSet::default();
+
+    node.instance()
+        .params()
+        .iter()
+        .filter(|param| {
+            let (min, max, default) = (param.min as f32, param.max as f32, param.default as f32);
+            let is_valid = min <= max && (min..=max).contains(&default);
+            if !is_valid {
+                tracing::warn!(name = %param.name, "skipping plugin parameter with invalid range");
+            }
+
+            is_valid
+        })
+        .map(|param| {
+            let mut param = param.clone();
+            param.name = unique_name(&mut names, &param.name, param.id);
+            param
+        })
+        .collect()
+}
+
+fn unique_name(names: &mut HashSet<String>, name: &str, id: u32) -> String {
+    let name = if name.trim().is_empty() {
+        format!("#{id}")
+    } else if names.contains(name) {
+        format!("{name} #{id}")
+    } else {
+        name.to_owned()
+    };
+
+    names.insert(name.clone());
+    name
+}
+
+pub fn node_param(param: &PluginParam) -> NodeParam {
+    NodeParam::new(
+        param.name.clone(),
+        param.min as f32,
+        param.max as f32,
+        param.default as f32,
+    )
+}
@@ -0,0 +1,76 @@
+use rdaw_api::node::NodeId;
+use rdaw_api::plugin::{
+    plugin_node_kind, PluginDescriptor, PluginOperations, PluginParam, PluginRequest,
+    PluginResponse,
+};
+use rdaw_api::track::TrackId;
+use rdaw_api::{format_err, BackendProtocol, ErrorKind, Result};
+use rdaw_clap::PluginNode;
+use rdaw_core::path::Utf8PathBuf;
+use tracing::instrument;
+
+use super::{node_param, plugin_params};
+use crate::Backend;
+
+#[rdaw_rpc::handler(protocol = BackendProtocol, operations = PluginOperations)]
+impl Backend {
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn scan_plugins(&mut self, extra_paths: Vec<Utf8PathBuf>) -> Result<Vec<PluginDescriptor>> {
+        if self.safe_mode {
+            tracing::warn!("safe mode is on, not loading plugins");
+            return Ok(Vec::new());
+        }
+
+        let mut paths = extra_paths;
+        paths.extend(rdaw_clap::default_search_paths());
+
+        self.plugins.scan(&paths);
+        self.get_plugins()
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn get_plugins(&self) -> Result<Vec<PluginDescriptor>> {
+        Ok(self.plugins.descriptors().cloned().collect())
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn add_track_plugin(&mut self, track_id: TrackId, plugin_id: String) -> Result<NodeId> {
+        let document_id = self.hub.tracks.get_key_or_err(track_id)?.document_id;
+
+        let library = self
+            .plugins
+            .find_library(&plugin_id)
+            .ok_or_else(|| format_err!(ErrorKind::NotFound, "plugin `{plugin_id}` not found"))?;
+
+        let node = PluginNode::new(library.instantiate(&plugin_id)?);
+        let params = plugin_params(&node);
+
+        let kind = plugin_node_kind(&plugin_id);
+        let node_id =
+            self.create_node(document_id, kind, params.iter().map(node_param).collect())?;
+        self.plugins.insert_node(node_id, node, params);
+
+        let index = self.hub.tracks.get_or_err(track_id)?.nodes.len();
+        self.insert_track_node(track_id, node_id, index)?;
+
+        Ok(node_id)
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn get_plugin_params(&self, node_id: NodeId) -> Result<Vec<PluginParam>> {
+        self.hub.nodes.ensure_has(node_id)?;
+
+        let params = self.plugins.node_params(node_id).ok_or_else(|| {
+            format_err!(
+                ErrorKind::NotSupported,
+                "{node_id:?} isn't running a plugin"
+            )
+        })?;
+
+        Ok(params.to_vec())
+    }
+}
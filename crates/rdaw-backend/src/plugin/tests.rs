@@ -0,0 +1,73 @@
+use rdaw_api::document::DocumentOperations;
+use rdaw_api::node::NodeOperations;
+use rdaw_api::plugin::PluginOperations;
+use rdaw_api::track::TrackOperations;
+use rdaw_api::{assert_err, ErrorKind, Result};
+use rdaw_core::collections::HashSet;
+use rdaw_core::path::Utf8Path;
+
+use super::unique_name;
+use crate::tests::{run_test, run_test_with};
+
+#[test]
+fn broken_plugins_are_skipped() -> Result<()> {
+    run_test(|client| async move {
+        let dir = tempfile::tempdir()?;
+        let dir = Utf8Path::from_path(dir.path()).unwrap();
+        std::fs::write(dir.join("Broken.clap"), "not a library")?;
+
+        // plugins installed on the machine may be found as well
+        let plugins = client.scan_plugins(vec![dir.to_owned()]).await?;
+        assert!(plugins.iter().all(|v| !v.path.starts_with(dir)));
+        assert_eq!(client.get_plugins().await?, plugins);
+
+        Ok(())
+    })
+}
+
+#[test]
+fn safe_mode_skips_plugins() -> Result<()> {
+    run_test_with(
+        |backend| backend.set_safe_mode(true),
+        |client| async move {
+            assert!(client.scan_plugins(Vec::new()).await?.is_empty());
+            assert!(client.get_plugins().await?.is_empty());
+            Ok(())
+        },
+    )
+}
+
+#[test]
+fn unknown_plugin() -> Result<()> {
+    run_test(|client| async move {
+        let document_id = client.create_document().await?;
+        let track_id = client.create_track(document_id).await?;
+
+        assert_err!(
+            client
+                .add_track_plugin(track_id, "com.example.missing".into())
+                .await,
+            ErrorKind::NotFound
+        );
+        assert!(client.get_track_nodes(track_id).await?.is_empty());
+
+        let node_id = client
+            .create_node(document_id, "gain".into(), Vec::new())
+            .await?;
+        assert_err!(
+            client.get_plugin_params(node_id).await,
+            ErrorKind::NotSupported
+        );
+
+        Ok(())
+    })
+}
+
+#[test]
+fn duplicate_param_names() {
+    let mut names = HashSet::default();
+    let names = [(1, "Cutoff"), (2, "Cutoff"), (3, ""), (4, "Resonance")]
+        .map(|(id, name)| unique_name(&mut names, name, id));
+
+    assert_eq!(names, ["Cutoff", "Cutoff #2", "#3", "Resonance"]);
+}
@@ -0,0 +1,172 @@
+mod ops;
+#[cfg(test)]
+mod tests;
+
+use std::fmt;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use rdaw_api::arrangement::ArrangementId;
+use rdaw_api::audio::AudioChannel;
+use rdaw_api::recording::RecordingMeter;
+use rdaw_api::track::TrackId;
+use rdaw_api::{format_err, ErrorKind, Result};
+use rdaw_core::sync::spsc::{self, TryRecvError};
+use rdaw_core::time::RealTime;
+
+use crate::engine::{frames_to_time, DynInStream};
+use crate::{Backend, DeferredQueue};
+
+/// Capacity of the ring between the input stream and the capture thread, in samples.
+const RING_CAPACITY: usize = 1 << 16;
+
+/// Meter events are sent every time this many frames have been captured.
+const METER_INTERVAL: usize = 2048;
+
+pub const CHANNELS: [AudioChannel; 2] = [AudioChannel::FrontLeft, AudioChannel::FrontRight];
+
+/// Audio being captured into an arrangement.
+pub struct Recording {
+    pub tracks: Vec<TrackId>,
+    /// Where the captured audio goes in the arrangement.
+    pub start: RealTime,
+    pub sample_rate: u32,
+    stream: Box<dyn DynInStream>,
+    capture: JoinHandle<Vec<f32>>,
+}
+
+impl fmt::Debug for Recording {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Recording")
+            .field("tracks", &self.tracks)
+            .field("start", &self.start)
+            .field("sample_rate", &self.sample_rate)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Recording {
+    /// Stops the input stream and waits for the captured samples, interleaved.
+    pub fn finish(self) -> Result<Vec<f32>> {
+        if let Ok(dropped) = self.stream.num_dropped_frames() {
+            if dropped > 0 {
+                tracing::warn!(dropped, "recording has dropped frames");
+            }
+        }
+
+        // closes the ring, which stops the capture thread
+        drop(self.stream);
+
+        self.capture
+            .join()
+            .map_err(|_| format_err!(ErrorKind::Other, "capture thread panicked"))
+    }
+}
+
+/// Starts draining `receiver` on a separate thread, telling meter subscribers of the arrangement
+/// about the levels.
+pub fn start_capture(
+    queue: DeferredQueue,
+    arrangement_id: ArrangementId,
+    sample_rate: u32,
+    mut receiver: spsc::Receiver<f32>,
+) -> Result<JoinHandle<Vec<f32>>> {
+    let num_channels = CHANNELS.len();
+
+    let capture = thread::Builder::new()
+        .name("recording".into())
+        .spawn(move || {
+            let mut samples = Vec::new();
+            let mut metered = 0;
+
+            loop {
+                match receiver.recv_timeout(Duration::from_millis(100)) {
+                    Ok(sample) => samples.push(sample),
+                    Err(TryRecvError::Empty) => continue,
+                    Err(TryRecvError::Closed) => break,
+                }
+
+                while let Ok(sample) = receiver.try_recv() {
+                    samples.push(sample);
+                }
+
+                while samples.len() - metered >= METER_INTERVAL * num_channels {
+                    let end = metered + METER_INTERVAL * num_channels;
+                    let meter = RecordingMeter {
+                        peaks: channel_peaks(&samples[metered..end], num_channels),
+                        duration: frames_to_time((end / num_channels) as i64, sample_rate),
+                    };
+
+                    queue.defer(move |this: &mut Backend| {
+                        this.subscribers
+                            .recording_meter
+                            .notify(arrangement_id, meter);
+                        std::future::ready(Ok(()))
+                    });
+
+                    metered = end;
+                }
+            }
+
+            samples
+        })?;
+
+    Ok(capture)
+}
+
+pub fn channel_peaks(samples: &[f32], num_channels: usize) -> Vec<f32> {
+    let mut peaks = vec![0.0f32; num_channels];
+
+    for frame in samples.chunks_exact(num_channels.max(1)) {
+        for (peak, &sample) in peaks.iter_mut().zip(frame) {
+            *peak = peak.max(sample.abs());
+        }
+    }
+
+    peaks
+}
+
+/// Encodes interleaved samples as a 32-bit float WAV file.
+pub fn encode_wav(samples: &[f32], num_channels: u16, sample_rate: u32) -> Result<Vec<u8>> {
+    const FORMAT_IEEE_FLOAT: u16 = 3;
+
+    let data_len = wav_data_len(samples.len())?;
+    let block_align = num_channels * 4;
+
+    let mut wav = Vec::with_capacity(44 + samples.len() * 4);
+    wav.extend_from_slice(b"RIFF");
+    wav.extend_from_slice(&(36 + data_len).to_le_bytes());
+    wav.extend_from_slice(b"WAVE");
+
+    wav.extend_from_slice(b"fmt ");
+    wav.extend_from_slice(&16u32.to_le_bytes());
+    wav.extend_from_slice(&FORMAT_IEEE_FLOAT.to_le_bytes());
+    wav.extend_from_slice(&num_channels.to_le_bytes());
+    wav.extend_from_slice(&sample_rate.to_le_bytes());
+    wav.extend_from_slice(&(sample_rate * u32::from(block_align)).to_le_bytes());
+    wav.extend_from_slice(&block_align.to_le_bytes());
+    wav.extend_from_slice(&32u16.to_le_bytes());
+
+    wav.extend_from_slice(b"data");
+    wav.extend_from_slice(&data_len.to_le_bytes());
+    for sample in samples {
+        wav.extend_from_slice(&sample.to_le_bytes());
+    }
+
+    Ok(wav)
+}
+
+/// Size of the data chunk holding this many samples, which together with the rest of the header
+/// has to fit in the 32-bit RIFF chunk size.
+fn wav_data_len(num_samples: usize) -> Result<u32> {
+    num_samples
+        .checked_mul(4)
+        .and_then(|len| u32::try_from(len).ok())
+        .filter(|&len| len <= u32::MAX - 36)
+        .ok_or_else(|| format_err!(ErrorKind::NotSupported, "audio is too long for a WAV file"))
+}
+
+pub fn ring() -> Result<(spsc::Sender<f32>, spsc::Receiver<f32>)> {
+    spsc::try_channel(RING_CAPACITY)
+        .map_err(|e| format_err!(ErrorKind::Other, "failed to create capture ring: {e}"))
+}
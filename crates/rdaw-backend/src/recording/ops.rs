@@ -0,0 +1,278 @@
+use std::io::Write;
+
+use blake3::Hash;
+use rdaw_api::arrangement::ArrangementId;
+use rdaw_api::audio::{AudioMetadata, ResampleQuality, SampleFormat};
+use rdaw_api::document::DocumentId;
+use rdaw_api::item::ItemId;
+use rdaw_api::recording::{RecordingOperations, RecordingRequest, RecordingResponse};
+use rdaw_api::stats::TaskQueue;
+use rdaw_api::time::Time;
+use rdaw_api::track::{TrackId, TrackItem, TrackItemId};
+use rdaw_api::{bail, BackendProtocol, Error, ErrorKind, Result};
+use rdaw_audio::driver::InStreamDesc;
+use rdaw_core::time::RealTime;
+use rdaw_rpc::{Responder, StreamId};
+use tracing::instrument;
+
+use super::{encode_wav, ring, start_capture, Recording, CHANNELS};
+use crate::asset::{Asset, EmbeddedAsset};
+use crate::document::Compression;
+use crate::engine::{frames_to_time, time_to_frames, BUFFER_SIZE, SAMPLE_RATE};
+use crate::item::AudioItem;
+use crate::object::ObjectKey;
+use crate::source::AudioSource;
+use crate::transport::Transport;
+use crate::Backend;
+
+#[rdaw_rpc::handler(protocol = BackendProtocol, operations = RecordingOperations)]
+impl Backend {
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn arm_track(&mut self, id: TrackId, armed: bool) -> Result<()> {
+        let track = self.hub.tracks.get_mut_or_err(id)?;
+        track.armed = armed;
+        self.notify_track_status(id);
+        self.update_input_monitor();
+        Ok(())
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn is_track_armed(&self, id: TrackId) -> Result<bool> {
+        let track = self.hub.tracks.get_or_err(id)?;
+        Ok(track.armed)
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn start_recording(&mut self, id: ArrangementId) -> Result<()> {
+        self.hub.arrangements.ensure_has(id)?;
+
+        if self.recordings.contains_key(&id) {
+            bail!(ErrorKind::Conflict, "arrangement is already being recorded");
+        }
+
+        let tracks = self
+            .arrangement_tracks(id)
+            .into_iter()
+            .filter(|&track_id| self.hub.tracks.get(track_id).is_some_and(|v| v.armed))
+            .collect::<Vec<_>>();
+
+        if tracks.is_empty() {
+            bail!(ErrorKind::NotSupported, "no tracks are armed");
+        }
+
+        let Some(driver) = self.audio_driver.clone() else {
+            bail!(ErrorKind::NotSupported, "recording needs an audio driver");
+        };
+
+        let (sender, receiver) = ring()?;
+        let stream = driver.create_in_stream(InStreamDesc {
+            name: "rdaw-recording".into(),
+            sample_rate: SAMPLE_RATE,
+            buffer_size: BUFFER_SIZE,
+            channels: CHANNELS.to_vec(),
+            sender,
+        })?;
+
+        stream.set_active(true)?;
+
+        let capture = start_capture(self.queue.clone(), id, SAMPLE_RATE, receiver)?;
+
+        self.update_transport(id, Transport::play)?;
+        self.start_playback(id);
+
+        let start = self
+            .transports
+            .get(&id)
+            .map(|v| v.position)
+            .unwrap_or_default();
+
+        self.recordings.insert(
+            id,
+            Recording {
+                tracks: tracks.clone(),
+                start,
+                sample_rate: SAMPLE_RATE,
+                stream,
+                capture,
+            },
+        );
+
+        for track_id in tracks {
+            self.notify_track_status(track_id);
+        }
+
+        Ok(())
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn stop_recording(
+        &mut self,
+        responder: impl Responder<Vec<(TrackId, TrackItemId)>, Error>,
+        id: ArrangementId,
+    ) -> Result<()> {
+        let Some(recording) = self.recordings.remove(&id) else {
+            bail!(ErrorKind::NotFound, "arrangement isn't being recorded");
+        };
+
+        for &track_id in &recording.tracks {
+            self.notify_track_status(track_id);
+        }
+
+        self.stop(id)?;
+
+        let document_id = self.hub.arrangements.get_key_or_err(id)?.document_id;
+        let document = self.documents.get_or_err(document_id)?;
+        let mut blob = document.create_blob(Compression::Zstd)?;
+
+        let latency = self.recording_latency;
+        let queue = self.queue.clone();
+        self.spawn(TaskQueue::Io, async move {
+            let tracks = recording.tracks.clone();
+            let start = recording.start;
+            let sample_rate = recording.sample_rate;
+            let num_latency_samples =
+                time_to_frames(latency, sample_rate).max(0) as usize * CHANNELS.len();
+
+            let res = recording.finish().and_then(|mut samples| {
+                // the first captured samples were played before recording was started
+                samples.drain(..num_latency_samples.min(samples.len()));
+
+                let wav = encode_wav(&samples, CHANNELS.len() as u16, sample_rate)?;
+                blob.write_all(&wav)?;
+                let hash = blob.save()?;
+                let num_frames = samples.len() / CHANNELS.len();
+                Ok((hash, wav.len() as u64, num_frames))
+            });
+
+            queue.defer(move |this: &mut Backend| {
+                let res = res.and_then(|(hash, size, num_frames)| {
+                    let recorded = RecordedAudio {
+                        hash,
+                        size,
+                        num_frames,
+                        sample_rate,
+                    };
+
+                    this.add_recorded_items(document_id, &tracks, start, recorded)
+                });
+
+                responder.respond(res)
+            });
+
+            Ok(())
+        });
+
+        Ok(())
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn get_recording_latency_compensation(&self) -> Result<RealTime> {
+        Ok(self.recording_latency)
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn set_recording_latency_compensation(&mut self, latency: RealTime) -> Result<()> {
+        if latency < RealTime::ZERO {
+            bail!(ErrorKind::NotSupported, "latency can't be negative");
+        }
+
+        self.recording_latency = latency;
+        Ok(())
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn subscribe_recording_meter(&mut self, id: ArrangementId) -> Result<StreamId> {
+        self.hub.arrangements.ensure_has(id)?;
+        Ok(self.subscribers.recording_meter.subscribe(id))
+    }
+
+    fn add_recorded_items(
+        &mut self,
+        document_id: DocumentId,
+        tracks: &[TrackId],
+        start: RealTime,
+        recorded: RecordedAudio,
+    ) -> Result<Vec<(TrackId, TrackItemId)>> {
+        if recorded.num_frames == 0 {
+            return Ok(Vec::new());
+        }
+
+        // the tracks could have been deleted while recording
+        let tracks = tracks
+            .iter()
+            .copied()
+            .filter(|&track_id| self.hub.tracks.has(track_id))
+            .collect::<Vec<_>>();
+
+        if tracks.is_empty() {
+            bail!(
+                ErrorKind::NotFound,
+                "armed tracks were removed while recording"
+            );
+        }
+
+        let asset_id = self.hub.assets.insert(
+            ObjectKey::new_random(document_id),
+            Asset::Embedded(EmbeddedAsset {
+                hash: recorded.hash,
+                size: recorded.size,
+            }),
+        );
+
+        let duration = frames_to_time(recorded.num_frames as i64, recorded.sample_rate);
+
+        let metadata = AudioMetadata {
+            channels: CHANNELS.to_vec(),
+            sample_rate: recorded.sample_rate,
+            sample_format: SampleFormat::F32,
+            duration,
+        };
+
+        let source_id = self.hub.audio_sources.insert(
+            ObjectKey::new_random(document_id),
+            AudioSource {
+                asset_id,
+                metadata,
+                resample_quality: ResampleQuality::default(),
+            },
+        );
+
+        let mut items = Vec::new();
+
+        for track_id in tracks {
+            let item_id = self.hub.audio_items.insert(
+                ObjectKey::new_random(document_id),
+                AudioItem::new(source_id),
+            );
+
+            let track_item_id = self.add_track_item(
+                track_id,
+                TrackItem {
+                    inner: ItemId::Audio(item_id),
+                    start: Time::Real(start),
+                    duration: Time::Real(duration),
+                    lane: 0,
+                    locked: false,
+                },
+            )?;
+
+            items.push((track_id, track_item_id));
+        }
+
+        Ok(items)
+    }
+}
+
+struct RecordedAudio {
+    hash: Hash,
+    size: u64,
+    num_frames: usize,
+    sample_rate: u32,
+}
@@ -0,0 +1,138 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use rdaw_api::arrangement::ArrangementOperations;
+use rdaw_api::document::DocumentOperations;
+use rdaw_api::item::ItemId;
+use rdaw_api::recording::RecordingOperations;
+use rdaw_api::time::Time;
+use rdaw_api::track::TrackOperations;
+use rdaw_api::{assert_err, ErrorKind, Result};
+use rdaw_audio::driver::NullDriver;
+use rdaw_core::time::RealTime;
+
+use super::{channel_peaks, encode_wav, wav_data_len};
+use crate::tests::{run_test, run_test_with};
+
+#[test]
+fn wav_header() -> Result<()> {
+    let wav = encode_wav(&[0.5, -0.5, 1.0, -1.0], 2, 48000)?;
+
+    assert_eq!(wav.len(), 44 + 16);
+    assert_eq!(&wav[0..4], b"RIFF");
+    assert_eq!(&wav[8..16], b"WAVEfmt ");
+    assert_eq!(u16::from_le_bytes([wav[22], wav[23]]), 2);
+    assert_eq!(
+        u32::from_le_bytes([wav[24], wav[25], wav[26], wav[27]]),
+        48000
+    );
+    assert_eq!(&wav[36..40], b"data");
+    assert_eq!(u32::from_le_bytes([wav[40], wav[41], wav[42], wav[43]]), 16);
+    assert_eq!(&wav[44..48], &0.5f32.to_le_bytes());
+
+    Ok(())
+}
+
+#[test]
+fn wav_too_long() {
+    let max_samples = (u32::MAX as usize - 36) / 4;
+    assert_eq!(wav_data_len(max_samples).ok(), Some(max_samples as u32 * 4));
+    assert_err!(wav_data_len(max_samples + 1), ErrorKind::NotSupported);
+    assert_err!(wav_data_len(usize::MAX), ErrorKind::NotSupported);
+}
+
+#[test]
+fn peaks() {
+    let peaks = channel_peaks(&[0.25, -0.5, -0.75, 0.125], 2);
+    assert_eq!(peaks, [0.75, 0.5]);
+}
+
+#[test]
+fn recording_errors() -> Result<()> {
+    run_test(|client| async move {
+        let document_id = client.create_document().await?;
+        let arrangement_id = client.get_document_arrangement(document_id).await?;
+        let main_track_id = client.get_arrangement_main_track(arrangement_id).await?;
+
+        assert_err!(
+            client.start_recording(arrangement_id).await,
+            ErrorKind::NotSupported
+        );
+
+        client.arm_track(main_track_id, true).await?;
+        assert!(client.is_track_armed(main_track_id).await?);
+
+        // there's no audio driver
+        assert_err!(
+            client.start_recording(arrangement_id).await,
+            ErrorKind::NotSupported
+        );
+        assert_err!(
+            client.stop_recording(arrangement_id).await,
+            ErrorKind::NotFound
+        );
+
+        Ok(())
+    })
+}
+
+#[test]
+fn record_into_armed_tracks() -> Result<()> {
+    run_test_with(
+        |backend| backend.set_audio_driver(Arc::new(NullDriver::new())),
+        |client| async move {
+            let document_id = client.create_document().await?;
+            let arrangement_id = client.get_document_arrangement(document_id).await?;
+            let main_track_id = client.get_arrangement_main_track(arrangement_id).await?;
+
+            let armed = client.create_track(document_id).await?;
+            let unarmed = client.create_track(document_id).await?;
+            client.append_track_child(main_track_id, armed).await?;
+            client.append_track_child(main_track_id, unarmed).await?;
+
+            client.arm_track(armed, true).await?;
+            client.start_recording(arrangement_id).await?;
+            assert_err!(
+                client.start_recording(arrangement_id).await,
+                ErrorKind::Conflict
+            );
+
+            // the null driver captures silence in the background
+            std::thread::sleep(Duration::from_millis(100));
+
+            let items = client.stop_recording(arrangement_id).await?;
+            assert_eq!(items.len(), 1);
+            assert_eq!(items[0].0, armed);
+
+            let item = client.get_track_item(armed, items[0].1).await?;
+            assert!(matches!(item.inner, ItemId::Audio(_)));
+            assert_eq!(item.start, Time::Real(RealTime::ZERO));
+            assert!(item.duration > Time::Real(RealTime::ZERO));
+
+            Ok(())
+        },
+    )
+}
+
+#[test]
+fn latency_compensation() -> Result<()> {
+    run_test(|client| async move {
+        assert_eq!(
+            client.get_recording_latency_compensation().await?,
+            RealTime::ZERO
+        );
+
+        let latency = RealTime::from_nanos(5_000_000);
+        client.set_recording_latency_compensation(latency).await?;
+        assert_eq!(client.get_recording_latency_compensation().await?, latency);
+
+        assert_err!(
+            client
+                .set_recording_latency_compensation(RealTime::from_nanos(-1))
+                .await,
+            ErrorKind::NotSupported
+        );
+
+        Ok(())
+    })
+}
@@ -0,0 +1,284 @@
+//! Offline renders. The whole render is kept in memory, since loudness normalization needs it.
+//! CD images skip ffmpeg, their audio is written as is.
+
+mod ops;
+#[cfg(test)]
+mod tests;
+
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::ops::Range;
+
+use blake3::Hash;
+use rdaw_api::arrangement::ArrangementId;
+use rdaw_api::error::ResultExt;
+use rdaw_api::export::{ExportFormat, ExportSampleFormat, ExportSettings};
+use rdaw_api::source::AudioSourceId;
+use rdaw_api::Result;
+use rdaw_audio::graph::GraphParams;
+use rdaw_audio::playhead::Playhead;
+use rdaw_core::collections::HashMap;
+use rdaw_core::path::{Utf8Path, Utf8PathBuf};
+use rdaw_core::time::RealTime;
+use rdaw_ffmpeg::{AudioFileWriter, WriterSample};
+use rdaw_rpc::CancellationToken;
+
+use crate::asset::AssetReader;
+use crate::cache::FileCache;
+use crate::engine::{
+    decode_cached_source, frames_to_time, time_to_frames, DecodedSource, EngineGraph, GraphDesc,
+    BUFFER_SIZE, SAMPLE_RATE,
+};
+use crate::ensure_not_cancelled;
+use crate::export::{process_render, quantize_render};
+
+/// Red Book audio, dithered down from the render.
+pub const CD_SETTINGS: ExportSettings = ExportSettings {
+    format: ExportFormat::Wav,
+    sample_rate: 44100,
+    sample_format: ExportSampleFormat::I16,
+    loudness: None,
+    dither: true,
+    noise_shaping: true,
+};
+
+/// Frames of a CD sector, images are padded with silence to a whole number of them.
+const CD_SECTOR_FRAMES: usize = 588;
+
+#[derive(Debug)]
+pub struct RenderJob {
+    pub arrangement_id: ArrangementId,
+    pub range: Range<RealTime>,
+    pub settings: ExportSettings,
+    pub path: Utf8PathBuf,
+    /// Path and contents of a cue sheet. The render is written as a raw CD image if set.
+    pub cue_sheet: Option<(Utf8PathBuf, String)>,
+}
+
+impl RenderJob {
+    pub fn frames(&self) -> Range<i64> {
+        let sample_rate = self.settings.sample_rate;
+        time_to_frames(self.range.start, sample_rate)..time_to_frames(self.range.end, sample_rate)
+    }
+}
+
+pub fn decode_sources(
+    readers: Vec<(AudioSourceId, AssetReader, Hash)>,
+    cache: Option<&FileCache>,
+    cancel: &CancellationToken,
+    mut progress: impl FnMut(f32),
+) -> Result<HashMap<AudioSourceId, DecodedSource>> {
+    let num_sources = readers.len();
+    let mut sources = HashMap::default();
+
+    for (idx, (id, reader, asset_hash)) in readers.into_iter().enumerate() {
+        ensure_not_cancelled(cancel)?;
+        sources.insert(id, decode_cached_source(reader, cache, asset_hash)?);
+        progress((idx + 1) as f32 / num_sources as f32);
+    }
+
+    Ok(sources)
+}
+
+pub fn convert_sample_rate(desc: &mut GraphDesc, sample_rate: u32) {
+    let convert = |frames| time_to_frames(frames_to_time(frames, SAMPLE_RATE), sample_rate);
+
+    for track in &mut desc.tracks {
+        for item in &mut track.items {
+            let end = convert(item.start + item.duration);
+            item.start = convert(item.start);
+            item.duration = end - item.start;
+            item.loop_length = item.loop_length.map(convert);
+        }
+    }
+}
+
+pub fn render_graph(
+    desc: &GraphDesc,
+    sample_rate: u32,
+    frames: Range<i64>,
+    cancel: &CancellationToken,
+    mut progress: impl FnMut(f32),
+) -> Result<Vec<Vec<f32>>> {
+    let params = GraphParams {
+        sample_rate,
+        buffer_size: BUFFER_SIZE,
+    };
+
+    let num_frames = frames.end.saturating_sub(frames.start).max(0) as usize;
+    let mut channels = vec![Vec::with_capacity(num_frames); 2];
+
+    let playhead = Playhead::new();
+    let mut graph = EngineGraph::new(params, playhead.clone());
+    graph.update(desc);
+
+    let Some((mut compiled, master)) = graph.compile() else {
+        channels.iter_mut().for_each(|v| v.resize(num_frames, 0.0));
+        return Ok(channels);
+    };
+
+    playhead.seek(frames.start);
+
+    let mut reported = 0.0;
+
+    while channels[0].len() < num_frames {
+        ensure_not_cancelled(cancel)?;
+
+        compiled.process();
+
+        let len = (num_frames - channels[0].len()).min(params.buffer_size);
+        for (port, channel) in channels.iter_mut().enumerate() {
+            match compiled.audio_output(master, port) {
+                Some(output) => channel.extend_from_slice(&output[..len]),
+                None => channel.resize(channel.len() + len, 0.0),
+            }
+        }
+
+        playhead.advance(params.buffer_size);
+
+        let fraction = channels[0].len() as f32 / num_frames as f32;
+        if fraction - reported >= 0.01 {
+            reported = fraction;
+            progress(fraction);
+        }
+    }
+
+    Ok(channels)
+}
+
+/// Nothing is left at the path if it fails.
+pub fn write_render(
+    mut channels: Vec<Vec<f32>>,
+    job: &RenderJob,
+    cancel: &CancellationToken,
+    mut progress: impl FnMut(f32),
+) -> Result<()> {
+    let settings = &job.settings;
+    process_render(&mut channels, settings);
+
+    // vorbis is always floating point, whatever the sample format says
+    let quantized = match settings.format {
+        ExportFormat::Ogg => None,
+        _ => quantize_render(&channels, settings),
+    };
+
+    let res = AudioFileWriter::create(
+        &job.path,
+        settings.format,
+        settings.sample_format,
+        settings.sample_rate,
+        channels.len(),
+    )
+    .and_then(|mut writer| {
+        match &quantized {
+            Some(quantized) => write_blocks(&mut writer, quantized, cancel, &mut progress)?,
+            None => write_blocks(&mut writer, &channels, cancel, &mut progress)?,
+        }
+
+        writer.finish()
+    });
+
+    if res.is_err() {
+        if let Err(error) = fs::remove_file(&job.path) {
+            tracing::debug!(?error, path = ?job.path, "failed to remove unfinished render");
+        }
+    }
+
+    res
+}
+
+fn write_blocks<T: WriterSample>(
+    writer: &mut AudioFileWriter,
+    channels: &[Vec<T>],
+    cancel: &CancellationToken,
+    progress: &mut impl FnMut(f32),
+) -> Result<()> {
+    let num_frames = channels.first().map_or(0, |v| v.len());
+    let frame_size = writer.frame_size();
+    let mut reported = 0.0;
+
+    for start in (0..num_frames).step_by(frame_size) {
+        ensure_not_cancelled(cancel)?;
+
+        let end = (start + frame_size).min(num_frames);
+        let block = channels.iter().map(|v| &v[start..end]).collect::<Vec<_>>();
+        writer.write(&block)?;
+
+        let fraction = end as f32 / num_frames as f32;
+        if fraction - reported >= 0.01 {
+            reported = fraction;
+            progress(fraction);
+        }
+    }
+
+    Ok(())
+}
+
+/// Writes a finished render as a raw CD image and its cue sheet, like [`write_render`] does.
+pub fn write_cd_image(
+    mut channels: Vec<Vec<f32>>,
+    job: &RenderJob,
+    cancel: &CancellationToken,
+    mut progress: impl FnMut(f32),
+) -> Result<()> {
+    process_render(&mut channels, &job.settings);
+    let quantized = quantize_render(&channels, &job.settings).unwrap_or_default();
+
+    let res = write_raw_pcm(&job.path, &quantized, cancel, &mut progress).and_then(|()| {
+        let Some((path, sheet)) = &job.cue_sheet else {
+            return Ok(());
+        };
+
+        fs::write(path, sheet).with_context(|| format!("failed to write `{path}`"))
+    });
+
+    if res.is_err() {
+        let cue_path = job.cue_sheet.as_ref().map(|(path, _)| path);
+        for path in [Some(&job.path), cue_path].into_iter().flatten() {
+            if let Err(error) = fs::remove_file(path) {
+                tracing::debug!(?error, ?path, "failed to remove unfinished CD image");
+            }
+        }
+    }
+
+    res
+}
+
+/// Interleaved little-endian 16-bit samples, padded to whole sectors.
+fn write_raw_pcm(
+    path: &Utf8Path,
+    channels: &[Vec<i32>],
+    cancel: &CancellationToken,
+    progress: &mut impl FnMut(f32),
+) -> Result<()> {
+    let file = File::create(path).with_context(|| format!("failed to create `{path}`"))?;
+    let mut writer = BufWriter::new(file);
+
+    let num_frames = channels.first().map_or(0, |v| v.len());
+    let num_sectors = num_frames.div_ceil(CD_SECTOR_FRAMES);
+    let mut reported = 0.0;
+
+    for sector in 0..num_sectors {
+        ensure_not_cancelled(cancel)?;
+
+        let mut bytes = Vec::with_capacity(CD_SECTOR_FRAMES * channels.len() * 2);
+        for frame in sector * CD_SECTOR_FRAMES..(sector + 1) * CD_SECTOR_FRAMES {
+            for channel in channels {
+                let sample = channel.get(frame).copied().unwrap_or(0) as i16;
+                bytes.extend_from_slice(&sample.to_le_bytes());
+            }
+        }
+
+        writer.write_all(&bytes)?;
+
+        let fraction = (sector + 1) as f32 / num_sectors as f32;
+        if fraction - reported >= 0.01 {
+            reported = fraction;
+            progress(fraction);
+        }
+    }
+
+    writer.flush()?;
+
+    Ok(())
+}
@@ -0,0 +1,204 @@
+use rdaw_api::arrangement::ArrangementId;
+use rdaw_api::export::ExportSettings;
+use rdaw_api::render::{
+    RenderOperations, RenderProgress, RenderRange, RenderRequest, RenderResponse, RenderStage,
+};
+use rdaw_api::source::AudioSourceId;
+use rdaw_api::stats::TaskQueue;
+use rdaw_api::time::Time;
+use rdaw_api::{bail, BackendProtocol, Error, ErrorKind, Result};
+use rdaw_core::collections::HashMap;
+use rdaw_core::path::{Utf8Path, Utf8PathBuf};
+use rdaw_core::time::RealTime;
+use rdaw_rpc::{CancellationToken, Responder, StreamId};
+use tracing::instrument;
+
+use super::{
+    convert_sample_rate, decode_sources, render_graph, write_cd_image, write_render, RenderJob,
+    CD_SETTINGS,
+};
+use crate::engine::DecodedSource;
+use crate::export::validate_settings;
+use crate::{Backend, DeferredQueue};
+
+#[rdaw_rpc::handler(protocol = BackendProtocol, operations = RenderOperations)]
+impl Backend {
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn render_arrangement(
+        &mut self,
+        responder: impl Responder<(), Error>,
+        cancel: CancellationToken,
+        arrangement_id: ArrangementId,
+        range: RenderRange,
+        settings: ExportSettings,
+        path: Utf8PathBuf,
+    ) -> Result<()> {
+        validate_settings(&settings)?;
+
+        let range = self.resolve_time(arrangement_id, range.start)?
+            ..self.resolve_time(arrangement_id, range.end)?;
+        if range.start < RealTime::ZERO || range.start >= range.end {
+            bail!(ErrorKind::NotSupported, "invalid render range");
+        }
+
+        let job = RenderJob {
+            arrangement_id,
+            range,
+            settings,
+            path,
+            cue_sheet: None,
+        };
+
+        self.spawn_render(responder, cancel, job)
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn render_arrangement_cd(
+        &mut self,
+        responder: impl Responder<(), Error>,
+        cancel: CancellationToken,
+        arrangement_id: ArrangementId,
+        end: Time,
+        path: Utf8PathBuf,
+    ) -> Result<()> {
+        let end = self.resolve_time(arrangement_id, end)?;
+        if end <= RealTime::ZERO {
+            bail!(ErrorKind::NotSupported, "invalid render range");
+        }
+
+        let bin_path = path.with_extension("bin");
+        let bin_name = Utf8Path::new(bin_path.file_name().unwrap_or_default());
+        let sheet = self.cd_cue_sheet(arrangement_id, bin_name)?;
+
+        let job = RenderJob {
+            arrangement_id,
+            range: RealTime::ZERO..end,
+            settings: CD_SETTINGS,
+            path: bin_path,
+            cue_sheet: Some((path, sheet)),
+        };
+
+        self.spawn_render(responder, cancel, job)
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn subscribe_render_progress(&mut self, arrangement_id: ArrangementId) -> Result<StreamId> {
+        self.hub.arrangements.ensure_has(arrangement_id)?;
+        Ok(self.subscribers.render_progress.subscribe(arrangement_id))
+    }
+
+    fn spawn_render(
+        &mut self,
+        responder: impl Responder<(), Error>,
+        cancel: CancellationToken,
+        job: RenderJob,
+    ) -> Result<()> {
+        let arrangement_id = job.arrangement_id;
+
+        // the description is built again once the sources are decoded
+        let mut source_ids = Vec::new();
+        self.build_graph_desc(arrangement_id, |id| {
+            if !source_ids.contains(&id) {
+                source_ids.push(id);
+            }
+            None
+        });
+
+        let readers = source_ids
+            .into_iter()
+            .map(|id| {
+                let source = self.hub.audio_sources.get_or_err(id)?;
+                let hash = self.hub.assets.get_or_err(source.asset_id)?.hash();
+                Ok((id, self.open_asset(source.asset_id)?, hash))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let queue = self.queue.clone();
+        let cache = self.cache.clone();
+        self.spawn(TaskQueue::Decode, async move {
+            let progress = progress_reporter(&queue, &job, RenderStage::Decoding);
+            let res = decode_sources(readers, cache.as_deref(), &cancel, progress);
+
+            queue.defer(move |this: &mut Backend| {
+                this.start_render(responder, cancel, job, res);
+                std::future::ready(Ok(()))
+            });
+
+            Ok(())
+        });
+
+        Ok(())
+    }
+
+    fn start_render(
+        &mut self,
+        responder: impl Responder<(), Error>,
+        cancel: CancellationToken,
+        job: RenderJob,
+        sources: Result<HashMap<AudioSourceId, DecodedSource>>,
+    ) {
+        let desc = sources.map(|sources| {
+            let mut desc =
+                self.build_graph_desc(job.arrangement_id, |id| sources.get(&id).cloned());
+            // plugin instances are running in the engine, and can't be in two graphs at once
+            for track in &mut desc.tracks {
+                track.plugins.clear();
+            }
+            convert_sample_rate(&mut desc, job.settings.sample_rate);
+            desc
+        });
+
+        let queue = self.queue.clone();
+        self.spawn(TaskQueue::Render, async move {
+            let render_progress = progress_reporter(&queue, &job, RenderStage::Rendering);
+            let encode_progress = progress_reporter(&queue, &job, RenderStage::Encoding);
+
+            let res = desc
+                .and_then(|desc| {
+                    render_graph(
+                        &desc,
+                        job.settings.sample_rate,
+                        job.frames(),
+                        &cancel,
+                        render_progress,
+                    )
+                })
+                .and_then(|channels| match job.cue_sheet {
+                    Some(_) => write_cd_image(channels, &job, &cancel, encode_progress),
+                    None => write_render(channels, &job, &cancel, encode_progress),
+                });
+
+            queue.defer(move |_: &mut Backend| responder.respond(res));
+
+            Ok(())
+        });
+    }
+}
+
+fn progress_reporter(
+    queue: &DeferredQueue,
+    job: &RenderJob,
+    stage: RenderStage,
+) -> impl FnMut(f32) + Send + 'static {
+    let queue = queue.clone();
+    let arrangement_id = job.arrangement_id;
+    let path = job.path.clone();
+
+    move |progress| {
+        let path = path.clone();
+        queue.defer(move |this: &mut Backend| {
+            this.subscribers.render_progress.notify(
+                arrangement_id,
+                RenderProgress {
+                    path,
+                    stage,
+                    progress,
+                },
+            );
+            std::future::ready(Ok(()))
+        });
+    }
+}
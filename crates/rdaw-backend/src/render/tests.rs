@@ -0,0 +1,155 @@
+use std::fs;
+
+use futures::StreamExt;
+use rdaw_api::arrangement::{ArrangementId, ArrangementOperations, Marker, MarkerKind};
+use rdaw_api::document::DocumentOperations;
+use rdaw_api::export::{ExportFormat, ExportPreset, ExportSampleFormat};
+use rdaw_api::interchange::InterchangeOperations;
+use rdaw_api::render::{RenderOperations, RenderRange, RenderStage};
+use rdaw_api::time::Time;
+use rdaw_api::{assert_err, ErrorKind, Result};
+use rdaw_core::path::Utf8Path;
+use rdaw_core::time::RealTime;
+
+use crate::tests::{run_test, TestClient};
+
+async fn import_sine(client: &TestClient, dir: &Utf8Path) -> Result<ArrangementId> {
+    let sample = Utf8Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("../rdaw-ffmpeg/tests/samples/220_Hz_sine_wave.ogg");
+    fs::copy(sample, dir.join("sine.ogg"))?;
+
+    let session = r#"{
+        "version": 1,
+        "sources": [
+            { "id": "a", "path": "sine.ogg", "sample_rate": 44100, "channels": 1, "duration": 1.0 }
+        ],
+        "tracks": [
+            { "name": "A", "clips": [{ "source": "a", "start": 0.0, "duration": 1.0 }] }
+        ]
+    }"#;
+    fs::write(dir.join("session.json"), session)?;
+
+    let document_id = client.import_session(dir.join("session.json")).await?;
+    client.get_document_arrangement(document_id).await
+}
+
+fn seconds(start: f64, end: f64) -> RenderRange {
+    RenderRange {
+        start: Time::Real(RealTime::from_secs_f64(start)),
+        end: Time::Real(RealTime::from_secs_f64(end)),
+    }
+}
+
+#[test]
+fn render_arrangement() -> Result<()> {
+    run_test(|client| async move {
+        let dir = tempfile::tempdir()?;
+        let dir = Utf8Path::from_path(dir.path()).unwrap();
+
+        let arrangement_id = import_sine(&client, dir).await?;
+        let mut stream = client.subscribe_render_progress(arrangement_id).await?;
+
+        let settings = ExportPreset::built_in()[0].settings.clone();
+        let path = dir.join("render.wav");
+        client
+            .render_arrangement(arrangement_id, seconds(0.0, 0.5), settings, path.clone())
+            .await?;
+
+        // 24-bit stereo at 48 kHz, after the header
+        let len = fs::metadata(&path)?.len();
+        assert!(len >= 24000 * 2 * 3);
+
+        let mut stages = Vec::new();
+        while stages.last() != Some(&RenderStage::Encoding) {
+            let progress = stream.next().await.unwrap();
+            assert_eq!(progress.path, path);
+            assert!((0.0..=1.0).contains(&progress.progress));
+            if !stages.contains(&progress.stage) {
+                stages.push(progress.stage);
+            }
+        }
+
+        assert_eq!(
+            stages,
+            [
+                RenderStage::Decoding,
+                RenderStage::Rendering,
+                RenderStage::Encoding
+            ]
+        );
+
+        Ok(())
+    })
+}
+
+#[test]
+fn invalid_render() -> Result<()> {
+    run_test(|client| async move {
+        let dir = tempfile::tempdir()?;
+        let dir = Utf8Path::from_path(dir.path()).unwrap();
+
+        let arrangement_id = import_sine(&client, dir).await?;
+        let settings = ExportPreset::built_in()[0].settings.clone();
+        let path = dir.join("render.wav");
+
+        assert_err!(
+            client
+                .render_arrangement(
+                    arrangement_id,
+                    seconds(0.5, 0.5),
+                    settings.clone(),
+                    path.clone()
+                )
+                .await,
+            ErrorKind::NotSupported
+        );
+
+        let mut flac = settings;
+        flac.format = ExportFormat::Flac;
+        flac.sample_format = ExportSampleFormat::F32;
+        assert_err!(
+            client
+                .render_arrangement(arrangement_id, seconds(0.0, 0.5), flac, path.clone())
+                .await,
+            ErrorKind::NotSupported
+        );
+
+        assert!(!path.exists());
+
+        Ok(())
+    })
+}
+
+#[test]
+fn render_cd() -> Result<()> {
+    run_test(|client| async move {
+        let dir = tempfile::tempdir()?;
+        let dir = Utf8Path::from_path(dir.path()).unwrap();
+
+        let arrangement_id = import_sine(&client, dir).await?;
+        let marker = Marker {
+            position: Time::Real(RealTime::ZERO),
+            name: "Sine".into(),
+            kind: MarkerKind::CdTrack {
+                pregap: RealTime::ZERO,
+            },
+        };
+        client
+            .add_arrangement_marker(arrangement_id, marker)
+            .await?;
+
+        let end = Time::Real(RealTime::from_secs_f64(0.5));
+        let path = dir.join("disc.cue");
+        client
+            .render_arrangement_cd(arrangement_id, end, path.clone())
+            .await?;
+
+        let sheet = fs::read_to_string(&path)?;
+        assert!(sheet.contains("FILE \"disc.bin\" BINARY"));
+
+        // 22050 stereo 16-bit frames, padded to 38 sectors
+        assert_eq!(fs::metadata(dir.join("disc.bin"))?.len(), 38 * 2352);
+
+        Ok(())
+    })
+}
@@ -0,0 +1,76 @@
+//! Settings kept between sessions are stored as a JSON file:
+//!
+//! ```json
+//! { "version": 1, "path_variables": { "SAMPLES": "/home/user/samples" } }
+//! ```
+
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Write};
+
+use rdaw_api::error::ResultExt;
+use rdaw_api::{ErrorKind, Result};
+use rdaw_core::path::{Utf8Path, Utf8PathBuf};
+use serde::{Deserialize, Serialize};
+
+use crate::define_version_enum;
+
+define_version_enum! {
+    enum Version {
+        V1 = 1,
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct StoredSettings {
+    #[serde(default)]
+    pub path_variables: BTreeMap<String, Utf8PathBuf>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SettingsFile {
+    version: u32,
+    #[serde(flatten)]
+    settings: StoredSettings,
+}
+
+/// A missing file has the default settings.
+pub fn read(path: &Utf8Path) -> Result<StoredSettings> {
+    let file = match File::open(path) {
+        Ok(v) => v,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(StoredSettings::default()),
+        Err(e) => return Err(e).with_context(|| format!("failed to open `{path}`")),
+    };
+
+    let raw = serde_json::from_reader::<_, SettingsFile>(BufReader::new(file))
+        .convert_err(ErrorKind::Deserialization)?;
+
+    Version::from_u32(raw.version)?;
+
+    Ok(raw.settings)
+}
+
+pub fn write(path: &Utf8Path, settings: &StoredSettings) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).with_context(|| format!("failed to create `{dir}`"))?;
+    }
+
+    let raw = SettingsFile {
+        version: Version::LATEST.as_u32(),
+        settings: settings.clone(),
+    };
+
+    // a crash while writing mustn't leave the settings truncated
+    let tmp_path = Utf8PathBuf::from(format!("{path}.tmp"));
+
+    let mut writer = BufWriter::new(
+        File::create(&tmp_path).with_context(|| format!("failed to create `{tmp_path}`"))?,
+    );
+    serde_json::to_writer_pretty(&mut writer, &raw).convert_err(ErrorKind::Serialization)?;
+    writer.flush()?;
+    drop(writer);
+
+    fs::rename(&tmp_path, path).with_context(|| format!("failed to write `{path}`"))?;
+
+    Ok(())
+}
@@ -0,0 +1,88 @@
+mod file;
+mod ops;
+#[cfg(test)]
+mod tests;
+
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread;
+use std::time::Duration;
+
+use rdaw_api::Result;
+
+use self::file::StoredSettings;
+use crate::{Backend, DeferredQueue};
+
+/// Periodically saves documents with unsaved changes. The thread stops once the autosave is
+/// dropped.
+#[derive(Debug)]
+pub struct Autosave {
+    interval: Duration,
+    _stop: mpsc::Sender<()>,
+}
+
+impl Autosave {
+    pub fn start(queue: DeferredQueue, interval: Duration) -> Autosave {
+        let (stop, stopped) = mpsc::channel::<()>();
+
+        let res = thread::Builder::new()
+            .name("autosave".into())
+            .spawn(move || {
+                while let Err(RecvTimeoutError::Timeout) = stopped.recv_timeout(interval) {
+                    queue.defer(|this: &mut Backend| {
+                        this.autosave_documents();
+                        std::future::ready(Ok(()))
+                    });
+                }
+            });
+
+        if let Err(error) = res {
+            tracing::error!(?error, "failed to spawn autosave thread");
+        }
+
+        Autosave {
+            interval,
+            _stop: stop,
+        }
+    }
+
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+}
+
+impl Backend {
+    pub fn autosave_documents(&mut self) {
+        // a transaction can still be rolled back
+        if self.transaction.is_some() {
+            return;
+        }
+
+        let ids = self.unsaved_documents.iter().copied().collect::<Vec<_>>();
+
+        for id in ids {
+            // documents which were never saved don't have a place to be saved to
+            if self.documents.get(id).and_then(|v| v.path()).is_none() {
+                continue;
+            }
+
+            if let Err(error) = self.write_document(id, false) {
+                tracing::warn!(?id, ?error, "failed to autosave document");
+            }
+        }
+    }
+
+    /// Writes the settings kept between sessions, if there's a place for them.
+    pub fn store_settings(&self) -> Result<()> {
+        let Some(path) = &self.settings_path else {
+            return Ok(());
+        };
+
+        let path_variables = self
+            .path_variables
+            .iter()
+            .map(|(name, path)| (name.to_owned(), path.to_path_buf()))
+            .collect();
+
+        file::write(path, &StoredSettings { path_variables })
+    }
+}
@@ -0,0 +1,76 @@
+use std::time::Duration;
+
+use rdaw_api::settings::{
+    MonitoringLimiter, SettingsOperations, SettingsRequest, SettingsResponse,
+};
+use rdaw_api::{bail, BackendProtocol, ErrorKind, Result};
+use rdaw_core::path::Utf8PathBuf;
+use tracing::instrument;
+
+use super::{file, Autosave};
+use crate::Backend;
+
+#[rdaw_rpc::handler(protocol = BackendProtocol, operations = SettingsOperations)]
+impl Backend {
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn get_autosave_interval(&self) -> Result<Option<Duration>> {
+        Ok(self.autosave.as_ref().map(|v| v.interval()))
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn set_autosave_interval(&mut self, interval: Option<Duration>) -> Result<()> {
+        if interval.is_some_and(|v| v.is_zero()) {
+            bail!(ErrorKind::NotSupported, "autosave interval can't be zero");
+        }
+
+        self.autosave = interval.map(|v| Autosave::start(self.queue.clone(), v));
+        Ok(())
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn set_settings_path(&mut self, path: Option<Utf8PathBuf>) -> Result<()> {
+        if self.safe_mode && path.is_some() {
+            tracing::warn!("safe mode is on, ignoring stored settings");
+            return Ok(());
+        }
+
+        if let Some(path) = &path {
+            let settings = file::read(path)?;
+
+            for (name, var) in settings.path_variables {
+                if let Err(error) = self.path_variables.set(name.clone(), var) {
+                    tracing::warn!(%name, ?error, "skipping stored path variable");
+                }
+            }
+        }
+
+        self.settings_path = path;
+        Ok(())
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn get_monitoring_limiter(&self) -> Result<MonitoringLimiter> {
+        let (enabled, ceiling) = self.monitoring_limiter.get();
+        Ok(MonitoringLimiter { enabled, ceiling })
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn set_monitoring_limiter(&mut self, limiter: MonitoringLimiter) -> Result<()> {
+        if !limiter.ceiling.is_finite() || limiter.ceiling > 0.0 {
+            bail!(
+                ErrorKind::NotSupported,
+                "limiter ceiling must be at most 0 dBTP"
+            );
+        }
+
+        // shared with the engine, which picks it up without a rebuild
+        self.monitoring_limiter
+            .set(limiter.enabled, limiter.ceiling);
+        Ok(())
+    }
+}
@@ -0,0 +1,131 @@
+use std::time::Duration;
+
+use rdaw_api::arrangement::ArrangementOperations;
+use rdaw_api::asset::AssetOperations;
+use rdaw_api::document::DocumentOperations;
+use rdaw_api::settings::{MonitoringLimiter, SettingsOperations};
+use rdaw_api::track::TrackOperations;
+use rdaw_api::{assert_err, ErrorKind, Result};
+use rdaw_core::path::{Utf8Path, Utf8PathBuf};
+
+use crate::document::Document;
+use crate::tests::run_test;
+
+#[test]
+fn autosave_interval() -> Result<()> {
+    run_test(|client| async move {
+        assert_eq!(client.get_autosave_interval().await?, None);
+
+        let interval = Duration::from_secs(60);
+        client.set_autosave_interval(Some(interval)).await?;
+        assert_eq!(client.get_autosave_interval().await?, Some(interval));
+
+        assert_err!(
+            client.set_autosave_interval(Some(Duration::ZERO)).await,
+            ErrorKind::NotSupported
+        );
+
+        client.set_autosave_interval(None).await?;
+        assert_eq!(client.get_autosave_interval().await?, None);
+
+        Ok(())
+    })
+}
+
+#[test]
+fn monitoring_limiter() -> Result<()> {
+    run_test(|client| async move {
+        assert_eq!(
+            client.get_monitoring_limiter().await?,
+            MonitoringLimiter::default()
+        );
+
+        let limiter = MonitoringLimiter {
+            enabled: false,
+            ceiling: -3.0,
+        };
+        client.set_monitoring_limiter(limiter).await?;
+        assert_eq!(client.get_monitoring_limiter().await?, limiter);
+
+        for ceiling in [0.5, f32::NAN] {
+            assert_err!(
+                client
+                    .set_monitoring_limiter(MonitoringLimiter {
+                        enabled: true,
+                        ceiling,
+                    })
+                    .await,
+                ErrorKind::NotSupported
+            );
+        }
+        assert_eq!(client.get_monitoring_limiter().await?, limiter);
+
+        Ok(())
+    })
+}
+
+#[test]
+fn autosave() -> Result<()> {
+    run_test(|client| async move {
+        let dir = tempfile::tempdir()?;
+        let dir = Utf8Path::from_path(dir.path()).unwrap();
+        let path = dir.join("project.rdaw");
+
+        let document_id = client.create_document().await?;
+        client.save_document_as(document_id, path.clone()).await?;
+        let num_revisions = Document::open(&path)?.revisions()?.len();
+
+        client
+            .set_autosave_interval(Some(Duration::from_millis(10)))
+            .await?;
+
+        let arrangement_id = client.get_document_arrangement(document_id).await?;
+        let main_track_id = client.get_arrangement_main_track(arrangement_id).await?;
+        client
+            .set_track_name(main_track_id, "Renamed".into())
+            .await?;
+
+        // the autosave runs before the next request is handled
+        std::thread::sleep(Duration::from_millis(50));
+        client.get_track_name(main_track_id).await?;
+
+        let num_autosaved = Document::open(&path)?.revisions()?.len();
+        assert!(num_autosaved > num_revisions);
+
+        // nothing changed since then
+        std::thread::sleep(Duration::from_millis(50));
+        client.get_track_name(main_track_id).await?;
+        assert_eq!(Document::open(&path)?.revisions()?.len(), num_autosaved);
+
+        Ok(())
+    })
+}
+
+#[test]
+fn stored_path_variables() -> Result<()> {
+    run_test(|client| async move {
+        let dir = tempfile::tempdir()?;
+        let dir = Utf8Path::from_path(dir.path()).unwrap();
+        let path = dir.join("config").join("settings.json");
+
+        client.set_settings_path(Some(path.clone())).await?;
+        client
+            .set_asset_path_variable("SAMPLES".into(), Some("/mnt/samples".into()))
+            .await?;
+
+        // forgotten, but still in the file
+        client.set_settings_path(None).await?;
+        client
+            .set_asset_path_variable("SAMPLES".into(), None)
+            .await?;
+        assert!(client.get_asset_path_variables().await?.is_empty());
+
+        client.set_settings_path(Some(path)).await?;
+        assert_eq!(
+            client.get_asset_path_variables().await?,
+            vec![(String::from("SAMPLES"), Utf8PathBuf::from("/mnt/samples"))]
+        );
+
+        Ok(())
+    })
+}
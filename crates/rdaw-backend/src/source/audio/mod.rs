@@ -1,9 +1,9 @@
 mod ops;
 
 use rdaw_api::asset::AssetId;
-use rdaw_api::audio::AudioMetadata;
+use rdaw_api::audio::{AudioMetadata, ResampleQuality};
 use rdaw_api::source::AudioSourceId;
-use rdaw_api::Result;
+use rdaw_api::{bail, ErrorKind, Result};
 
 use crate::object::{DeserializationContext, Object, ObjectId, ObjectType, SerializationContext};
 
@@ -15,6 +15,7 @@ impl ObjectId for AudioSourceId {
 pub struct AudioSource {
     pub asset_id: AssetId,
     pub metadata: AudioMetadata,
+    pub resample_quality: ResampleQuality,
 }
 
 impl Object for AudioSource {
@@ -23,10 +24,10 @@ impl Object for AudioSource {
     const TYPE: ObjectType = ObjectType::AudioSource;
 
     fn serialize(&self, _ctx: &mut SerializationContext<'_>) -> Result<Vec<u8>> {
-        todo!()
+        bail!(ErrorKind::NotSupported, "audio source serialization is not implemented");
     }
 
     fn deserialize(_ctx: &mut DeserializationContext<'_>, _data: &[u8]) -> Result<Self> {
-        todo!()
+        bail!(ErrorKind::NotSupported, "audio source deserialization is not implemented");
     }
 }
@@ -1,9 +1,10 @@
 use rdaw_api::asset::AssetId;
-use rdaw_api::audio::AudioMetadata;
+use rdaw_api::audio::{AudioMetadata, ResampleQuality};
+use rdaw_api::document::DocumentId;
 use rdaw_api::source::{
     AudioSourceId, AudioSourceOperations, AudioSourceRequest, AudioSourceResponse,
 };
-use rdaw_api::{BackendProtocol, Result};
+use rdaw_api::{BackendProtocol, Page, PageRequest, Result};
 use rdaw_rpc::StreamId;
 use tracing::instrument;
 
@@ -18,6 +19,17 @@ impl Backend {
         todo!()
     }
 
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn list_audio_sources(
+        &self,
+        document_id: DocumentId,
+        page: PageRequest,
+    ) -> Result<Page<AudioSourceId>> {
+        self.documents.ensure_has(document_id)?;
+        Ok(self.hub.audio_sources.page_in_document(document_id, page))
+    }
+
     #[instrument(level = "trace", skip_all, err)]
     #[handler]
     pub fn subscribe_audio_source_name(&mut self, id: AudioSourceId) -> Result<StreamId> {
@@ -45,4 +57,23 @@ impl Backend {
         let _ = id;
         todo!()
     }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn get_audio_source_resample_quality(&self, id: AudioSourceId) -> Result<ResampleQuality> {
+        let source = self.hub.audio_sources.get_or_err(id)?;
+        Ok(source.resample_quality)
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn set_audio_source_resample_quality(
+        &mut self,
+        id: AudioSourceId,
+        quality: ResampleQuality,
+    ) -> Result<()> {
+        let source = self.hub.audio_sources.get_mut_or_err(id)?;
+        source.resample_quality = quality;
+        Ok(())
+    }
 }
@@ -0,0 +1,114 @@
+use rdaw_api::source::{MidiCc, MidiNote};
+use rdaw_api::time::BeatTime;
+use rdaw_api::{bail, ErrorKind, Result};
+use serde::{Deserialize, Serialize};
+use slotmap::SlotMap;
+
+use super::MidiSource;
+use crate::define_version_enum;
+use crate::document::encoding;
+use crate::object::{DeserializationContext, SerializationContext};
+
+pub fn serialize(ctx: &mut SerializationContext<'_>, source: &MidiSource) -> Result<Vec<u8>> {
+    let mut notes = source
+        .notes
+        .values()
+        .map(|note| MidiNoteLatest {
+            start: note.start,
+            duration: note.duration,
+            key: note.key,
+            velocity: note.velocity,
+        })
+        .collect::<Vec<_>>();
+    notes.sort_by_key(|note| (note.start, note.key));
+
+    let ccs = source
+        .ccs
+        .iter()
+        .map(|cc| MidiCcLatest {
+            position: cc.position,
+            controller: cc.controller,
+            value: cc.value,
+        })
+        .collect();
+
+    let raw = MidiSourceLatest { notes, ccs };
+
+    encoding::serialize(ctx.format(), Version::LATEST.as_u32(), &raw)
+}
+
+pub fn deserialize(ctx: &mut DeserializationContext<'_>, data: &[u8]) -> Result<MidiSource> {
+    let (version, data) = encoding::extract_version(ctx.format(), data)?;
+    let raw = match Version::from_u32(version)? {
+        Version::V1 => encoding::deserialize::<MidiSourceV1>(ctx.format(), data)?,
+    };
+
+    let mut notes = SlotMap::with_capacity_and_key(raw.notes.len());
+
+    for note in raw.notes {
+        let note = MidiNote {
+            start: note.start,
+            duration: note.duration,
+            key: note.key,
+            velocity: note.velocity,
+        };
+
+        if !note.is_valid() {
+            bail!(ErrorKind::Deserialization, "invalid note {note:?}");
+        }
+
+        notes.insert(note);
+    }
+
+    let mut source = MidiSource {
+        notes,
+        ccs: Vec::with_capacity(raw.ccs.len()),
+    };
+
+    for cc in raw.ccs {
+        let cc = MidiCc {
+            position: cc.position,
+            controller: cc.controller,
+            value: cc.value,
+        };
+
+        if !cc.is_valid() {
+            bail!(ErrorKind::Deserialization, "invalid control change {cc:?}");
+        }
+
+        source.insert_cc(cc);
+    }
+
+    Ok(source)
+}
+
+define_version_enum! {
+    enum Version {
+        V1 = 1,
+    }
+}
+
+type MidiSourceLatest = MidiSourceV1;
+type MidiNoteLatest = MidiNoteV1;
+type MidiCcLatest = MidiCcV1;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct MidiSourceV1 {
+    notes: Vec<MidiNoteV1>,
+    ccs: Vec<MidiCcV1>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct MidiNoteV1 {
+    start: BeatTime,
+    duration: BeatTime,
+    key: u8,
+    velocity: u8,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct MidiCcV1 {
+    position: BeatTime,
+    controller: u8,
+    value: u8,
+}
@@ -0,0 +1,55 @@
+mod encoding;
+mod ops;
+#[cfg(test)]
+mod tests;
+
+use std::mem;
+
+use rdaw_api::source::{MidiCc, MidiNote, MidiNoteId, MidiSourceId};
+use rdaw_api::Result;
+use slotmap::SlotMap;
+
+use crate::object::{DeserializationContext, Object, ObjectId, ObjectType, SerializationContext};
+
+impl ObjectId for MidiSourceId {
+    type Object = MidiSource;
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct MidiSource {
+    pub notes: SlotMap<MidiNoteId, MidiNote>,
+    /// Sorted by position.
+    pub ccs: Vec<MidiCc>,
+}
+
+impl MidiSource {
+    pub fn new() -> MidiSource {
+        MidiSource::default()
+    }
+
+    /// Inserts the event after all events with the same position, returns its index.
+    pub fn insert_cc(&mut self, cc: MidiCc) -> usize {
+        let idx = self.ccs.partition_point(|v| v.position <= cc.position);
+        self.ccs.insert(idx, cc);
+        idx
+    }
+}
+
+impl Object for MidiSource {
+    type Id = MidiSourceId;
+
+    const TYPE: ObjectType = ObjectType::MidiSource;
+
+    fn serialize(&self, ctx: &mut SerializationContext<'_>) -> Result<Vec<u8>> {
+        self::encoding::serialize(ctx, self)
+    }
+
+    fn deserialize(ctx: &mut DeserializationContext<'_>, data: &[u8]) -> Result<Self> {
+        self::encoding::deserialize(ctx, data)
+    }
+
+    fn heap_size(&self) -> usize {
+        self.notes.capacity() * mem::size_of::<(MidiNoteId, MidiNote)>()
+            + self.ccs.capacity() * mem::size_of::<MidiCc>()
+    }
+}
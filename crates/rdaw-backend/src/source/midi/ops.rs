@@ -0,0 +1,178 @@
+use rdaw_api::document::DocumentId;
+use rdaw_api::source::{
+    MidiCc, MidiNote, MidiNoteEvent, MidiNoteId, MidiSourceId, MidiSourceOperations,
+    MidiSourceRequest, MidiSourceResponse,
+};
+use rdaw_api::{bail, format_err, BackendProtocol, ErrorKind, Page, PageRequest, Result};
+use rdaw_rpc::StreamId;
+use tracing::instrument;
+
+use super::MidiSource;
+use crate::object::ObjectKey;
+use crate::Backend;
+
+#[rdaw_rpc::handler(protocol = BackendProtocol, operations = MidiSourceOperations)]
+impl Backend {
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn create_midi_source(&mut self, document_id: DocumentId) -> Result<MidiSourceId> {
+        self.documents.ensure_has(document_id)?;
+
+        let id = self
+            .hub
+            .midi_sources
+            .insert(ObjectKey::new_random(document_id), MidiSource::new());
+
+        Ok(id)
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn list_midi_sources(
+        &self,
+        document_id: DocumentId,
+        page: PageRequest,
+    ) -> Result<Page<MidiSourceId>> {
+        self.documents.ensure_has(document_id)?;
+        Ok(self.hub.midi_sources.page_in_document(document_id, page))
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn subscribe_midi_source_notes(&mut self, id: MidiSourceId) -> Result<StreamId> {
+        self.hub.midi_sources.ensure_has(id)?;
+        Ok(self.subscribers.midi_source_notes.subscribe(id))
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn subscribe_midi_source_ccs(&mut self, id: MidiSourceId) -> Result<StreamId> {
+        self.hub.midi_sources.ensure_has(id)?;
+        Ok(self.subscribers.midi_source_ccs.subscribe(id))
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn add_midi_source_note(&mut self, id: MidiSourceId, note: MidiNote) -> Result<MidiNoteId> {
+        if !note.is_valid() {
+            bail!(ErrorKind::NotSupported, "invalid note {note:?}");
+        }
+
+        let source = self.hub.midi_sources.get_mut_or_err(id)?;
+        let note_id = source.notes.insert(note);
+
+        self.subscribers
+            .midi_source_notes
+            .notify(id, MidiNoteEvent::Added { id: note_id, note });
+
+        Ok(note_id)
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn get_midi_source_note(&self, id: MidiSourceId, note_id: MidiNoteId) -> Result<MidiNote> {
+        let source = self.hub.midi_sources.get_or_err(id)?;
+        source
+            .notes
+            .get(note_id)
+            .copied()
+            .ok_or_else(|| format_err!(ErrorKind::InvalidId, "{note_id:?} doesn't exist in {id:?}"))
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn get_midi_source_notes(&self, id: MidiSourceId) -> Result<Vec<(MidiNoteId, MidiNote)>> {
+        let source = self.hub.midi_sources.get_or_err(id)?;
+
+        let mut notes = source
+            .notes
+            .iter()
+            .map(|(note_id, note)| (note_id, *note))
+            .collect::<Vec<_>>();
+        notes.sort_by_key(|(note_id, note)| (note.start, note.key, *note_id));
+
+        Ok(notes)
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn set_midi_source_note(
+        &mut self,
+        id: MidiSourceId,
+        note_id: MidiNoteId,
+        note: MidiNote,
+    ) -> Result<()> {
+        self.get_midi_source_note(id, note_id)?;
+
+        if !note.is_valid() {
+            bail!(ErrorKind::NotSupported, "invalid note {note:?}");
+        }
+
+        let source = self.hub.midi_sources.get_mut_or_err(id)?;
+        source.notes[note_id] = note;
+
+        self.subscribers
+            .midi_source_notes
+            .notify(id, MidiNoteEvent::Changed { id: note_id, note });
+
+        Ok(())
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn remove_midi_source_note(&mut self, id: MidiSourceId, note_id: MidiNoteId) -> Result<()> {
+        let source = self.hub.midi_sources.get_mut_or_err(id)?;
+
+        if source.notes.remove(note_id).is_some() {
+            self.subscribers
+                .midi_source_notes
+                .notify(id, MidiNoteEvent::Removed { id: note_id });
+        }
+
+        Ok(())
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn get_midi_source_ccs(&self, id: MidiSourceId) -> Result<Vec<MidiCc>> {
+        let source = self.hub.midi_sources.get_or_err(id)?;
+        Ok(source.ccs.clone())
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn add_midi_source_cc(&mut self, id: MidiSourceId, cc: MidiCc) -> Result<usize> {
+        if !cc.is_valid() {
+            bail!(ErrorKind::NotSupported, "invalid control change {cc:?}");
+        }
+
+        let source = self.hub.midi_sources.get_mut_or_err(id)?;
+        let index = source.insert_cc(cc);
+        self.notify_midi_source_ccs(id);
+
+        Ok(index)
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn remove_midi_source_cc(&mut self, id: MidiSourceId, index: usize) -> Result<()> {
+        let source = self.hub.midi_sources.get_mut_or_err(id)?;
+
+        if index >= source.ccs.len() {
+            bail!(
+                ErrorKind::IndexOutOfBounds,
+                "index out of bounds passed to remove_midi_source_cc",
+            );
+        }
+
+        source.ccs.remove(index);
+        self.notify_midi_source_ccs(id);
+
+        Ok(())
+    }
+
+    fn notify_midi_source_ccs(&mut self, id: MidiSourceId) {
+        let ccs = self.hub.midi_sources[id].ccs.clone();
+        self.subscribers.midi_source_ccs.notify(id, ccs);
+    }
+}
@@ -0,0 +1,128 @@
+use futures::StreamExt;
+use rdaw_api::document::DocumentOperations;
+use rdaw_api::source::{MidiCc, MidiNote, MidiNoteEvent, MidiSourceOperations};
+use rdaw_api::time::BeatTime;
+use rdaw_api::{assert_err, ErrorKind, Result};
+
+use crate::tests::run_test;
+
+fn note(start: i32, key: u8) -> MidiNote {
+    MidiNote {
+        start: BeatTime::from_beats(start),
+        duration: BeatTime::from_beats(1),
+        key,
+        velocity: 100,
+    }
+}
+
+fn cc(position: i32, value: u8) -> MidiCc {
+    MidiCc {
+        position: BeatTime::from_beats(position),
+        controller: 1,
+        value,
+    }
+}
+
+#[test]
+fn midi_notes() -> Result<()> {
+    run_test(|client| async move {
+        let document_id = client.create_document().await?;
+        let source_id = client.create_midi_source(document_id).await?;
+        let mut stream = client.subscribe_midi_source_notes(source_id).await?;
+
+        let c = client.add_midi_source_note(source_id, note(2, 60)).await?;
+        let e = client.add_midi_source_note(source_id, note(0, 64)).await?;
+
+        assert_eq!(
+            stream.next().await,
+            Some(MidiNoteEvent::Added {
+                id: c,
+                note: note(2, 60),
+            })
+        );
+        assert_eq!(
+            stream.next().await,
+            Some(MidiNoteEvent::Added {
+                id: e,
+                note: note(0, 64),
+            })
+        );
+
+        assert_eq!(
+            client.get_midi_source_notes(source_id).await?,
+            vec![(e, note(0, 64)), (c, note(2, 60))]
+        );
+
+        client
+            .set_midi_source_note(source_id, c, note(4, 62))
+            .await?;
+        assert_eq!(
+            stream.next().await,
+            Some(MidiNoteEvent::Changed {
+                id: c,
+                note: note(4, 62),
+            })
+        );
+        assert_eq!(
+            client.get_midi_source_note(source_id, c).await?,
+            note(4, 62)
+        );
+
+        client.remove_midi_source_note(source_id, e).await?;
+        assert_eq!(stream.next().await, Some(MidiNoteEvent::Removed { id: e }));
+        assert_err!(
+            client.get_midi_source_note(source_id, e).await,
+            ErrorKind::InvalidId
+        );
+
+        let silent = MidiNote {
+            velocity: 0,
+            ..note(0, 60)
+        };
+        assert_err!(
+            client.add_midi_source_note(source_id, silent).await,
+            ErrorKind::NotSupported
+        );
+        assert_err!(
+            client.add_midi_source_note(source_id, note(0, 128)).await,
+            ErrorKind::NotSupported
+        );
+
+        Ok(())
+    })
+}
+
+#[test]
+fn midi_ccs() -> Result<()> {
+    run_test(|client| async move {
+        let document_id = client.create_document().await?;
+        let source_id = client.create_midi_source(document_id).await?;
+        let mut stream = client.subscribe_midi_source_ccs(source_id).await?;
+
+        assert_eq!(client.add_midi_source_cc(source_id, cc(4, 10)).await?, 0);
+        assert_eq!(client.add_midi_source_cc(source_id, cc(0, 20)).await?, 0);
+        assert_eq!(client.add_midi_source_cc(source_id, cc(4, 30)).await?, 2);
+
+        assert_eq!(
+            stream.next().await,
+            Some(vec![cc(0, 20), cc(4, 10), cc(4, 30)])
+        );
+
+        client.remove_midi_source_cc(source_id, 1).await?;
+        assert_eq!(
+            client.get_midi_source_ccs(source_id).await?,
+            vec![cc(0, 20), cc(4, 30)]
+        );
+
+        assert_err!(
+            client.remove_midi_source_cc(source_id, 2).await,
+            ErrorKind::IndexOutOfBounds
+        );
+        assert_err!(
+            client.add_midi_source_cc(source_id, cc(0, 128)).await,
+            ErrorKind::NotSupported
+        );
+
+        Ok(())
+    })
+}
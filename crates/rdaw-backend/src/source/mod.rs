@@ -1,3 +1,5 @@
 mod audio;
+mod midi;
 
 pub use self::audio::AudioSource;
+pub use self::midi::MidiSource;
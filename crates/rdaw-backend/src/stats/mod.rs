@@ -0,0 +1,102 @@
+mod ops;
+#[cfg(test)]
+mod tests;
+
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use rdaw_api::stats::HandlerStats;
+use rdaw_core::collections::HashMap;
+
+const MAX_RECENT_SAMPLES: usize = 1024;
+const DEFAULT_SLOW_THRESHOLD: Duration = Duration::from_millis(20);
+
+/// Collects execution times of request handlers.
+#[derive(Debug)]
+pub struct HandlerProfiler {
+    handlers: HashMap<&'static str, HandlerSamples>,
+    slow_threshold: Option<Duration>,
+}
+
+#[derive(Debug, Default)]
+struct HandlerSamples {
+    count: u64,
+    total: Duration,
+    max: Duration,
+    recent: VecDeque<Duration>,
+}
+
+impl HandlerProfiler {
+    pub fn slow_threshold(&self) -> Option<Duration> {
+        self.slow_threshold
+    }
+
+    pub fn set_slow_threshold(&mut self, threshold: Option<Duration>) {
+        self.slow_threshold = threshold;
+    }
+
+    /// Returns whether the request exceeded the slow request threshold.
+    pub fn record(&mut self, name: &'static str, duration: Duration) -> bool {
+        let samples = self.handlers.entry(name).or_default();
+        samples.count += 1;
+        samples.total += duration;
+        samples.max = samples.max.max(duration);
+
+        if samples.recent.len() == MAX_RECENT_SAMPLES {
+            samples.recent.pop_front();
+        }
+
+        samples.recent.push_back(duration);
+
+        self.slow_threshold
+            .is_some_and(|threshold| duration >= threshold)
+    }
+
+    pub fn stats(&self) -> Vec<HandlerStats> {
+        let mut stats = self
+            .handlers
+            .iter()
+            .map(|(&name, samples)| {
+                let mut recent = Vec::from(samples.recent.clone());
+                recent.sort_unstable();
+
+                HandlerStats {
+                    name: name.into(),
+                    count: samples.count,
+                    total: samples.total,
+                    max: samples.max,
+                    p50: percentile(&recent, 0.5),
+                    p90: percentile(&recent, 0.9),
+                    p99: percentile(&recent, 0.99),
+                }
+            })
+            .collect::<Vec<_>>();
+
+        stats.sort_by(|a, b| b.total.cmp(&a.total).then_with(|| a.name.cmp(&b.name)));
+
+        stats
+    }
+
+    pub fn reset(&mut self) {
+        self.handlers.clear();
+    }
+}
+
+impl Default for HandlerProfiler {
+    fn default() -> HandlerProfiler {
+        HandlerProfiler {
+            handlers: HashMap::default(),
+            slow_threshold: Some(DEFAULT_SLOW_THRESHOLD),
+        }
+    }
+}
+
+/// Nearest-rank percentile of sorted samples.
+fn percentile(sorted: &[Duration], p: f64) -> Duration {
+    if sorted.is_empty() {
+        return Duration::ZERO;
+    }
+
+    let rank = (p * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
@@ -0,0 +1,86 @@
+use std::time::Duration;
+
+use rdaw_api::document::DocumentId;
+use rdaw_api::stats::{
+    DocumentStats, HandlerStats, MemoryStats, StatsOperations, StatsRequest, StatsResponse,
+    TaskQueueStats, TrackViewCacheStats,
+};
+use rdaw_api::{BackendProtocol, Result};
+use tracing::instrument;
+
+use crate::Backend;
+
+#[rdaw_rpc::handler(protocol = BackendProtocol, operations = StatsOperations)]
+impl Backend {
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn get_handler_stats(&self) -> Result<Vec<HandlerStats>> {
+        Ok(self.profiler.stats())
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn reset_handler_stats(&mut self) -> Result<()> {
+        self.profiler.reset();
+        Ok(())
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn set_slow_request_threshold(&mut self, threshold: Option<Duration>) -> Result<()> {
+        self.profiler.set_slow_threshold(threshold);
+        Ok(())
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn get_document_stats(&self, id: DocumentId) -> Result<DocumentStats> {
+        let document = self.documents.get_or_err(id)?;
+        let storage = document.storage_stats()?;
+
+        let objects = self
+            .hub
+            .memory_usage(id)
+            .into_iter()
+            .map(|(ty, usage)| MemoryStats {
+                name: format!("{ty:?}"),
+                count: usage.count,
+                estimated_bytes: usage.bytes,
+            })
+            .collect();
+
+        let track_views = self.track_view_cache.memory_usage(&self.hub, id);
+        let caches = vec![MemoryStats {
+            name: "TrackView".into(),
+            count: track_views.count,
+            estimated_bytes: track_views.bytes,
+        }];
+
+        Ok(DocumentStats {
+            objects,
+            caches,
+            blob_count: storage.blob_count,
+            blob_bytes: storage.blob_bytes,
+            file_size: storage.file_size,
+        })
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn get_task_queue_stats(&self) -> Result<Vec<TaskQueueStats>> {
+        Ok(self.tasks.stats())
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn get_track_view_cache_stats(&self) -> Result<TrackViewCacheStats> {
+        Ok(self.track_view_cache.stats())
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn set_track_view_cache_capacity(&mut self, capacity: usize) -> Result<()> {
+        self.track_view_cache.set_capacity(capacity);
+        Ok(())
+    }
+}
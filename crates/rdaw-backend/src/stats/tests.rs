@@ -0,0 +1,152 @@
+use std::time::Duration;
+
+use rdaw_api::document::DocumentOperations;
+use rdaw_api::stats::{MemoryStats, StatsOperations, TaskQueue};
+use rdaw_api::track::{TrackOperations, TrackViewFilter, TrackViewId};
+use rdaw_api::Result;
+
+use super::HandlerProfiler;
+use crate::tests::run_test;
+
+fn count_of(stats: &[MemoryStats], name: &str) -> usize {
+    stats.iter().find(|v| v.name == name).map_or(0, |v| v.count)
+}
+
+#[test]
+fn percentiles() {
+    let mut profiler = HandlerProfiler::default();
+    profiler.set_slow_threshold(Some(Duration::from_millis(100)));
+
+    for ms in (1..=100).rev() {
+        let is_slow = profiler.record("get_foo", Duration::from_millis(ms));
+        assert_eq!(is_slow, ms == 100);
+    }
+
+    profiler.record("set_foo", Duration::from_secs(1));
+
+    let stats = profiler.stats();
+    assert_eq!(stats.len(), 2);
+    assert_eq!(stats[0].name, "set_foo");
+
+    let stats = &stats[1];
+    assert_eq!(stats.count, 100);
+    assert_eq!(stats.total, Duration::from_millis(5050));
+    assert_eq!(stats.max, Duration::from_millis(100));
+    assert_eq!(stats.p50, Duration::from_millis(50));
+    assert_eq!(stats.p90, Duration::from_millis(90));
+    assert_eq!(stats.p99, Duration::from_millis(99));
+
+    profiler.reset();
+    assert!(profiler.stats().is_empty());
+}
+
+#[test]
+fn handler_stats() -> Result<()> {
+    run_test(|client| async move {
+        client.create_document().await?;
+        client.create_document().await?;
+
+        let stats = client.get_handler_stats().await?;
+        let create = stats.iter().find(|v| v.name == "create_document").unwrap();
+        assert_eq!(create.count, 2);
+        assert!(create.max <= create.total);
+
+        client.reset_handler_stats().await?;
+
+        // the reset itself is recorded after it has been handled
+        let stats = client.get_handler_stats().await?;
+        assert_eq!(stats.len(), 1);
+        assert_eq!(stats[0].name, "reset_handler_stats");
+
+        Ok(())
+    })
+}
+
+#[test]
+fn document_stats() -> Result<()> {
+    run_test(|client| async move {
+        let document_id = client.create_document().await?;
+        let other_document_id = client.create_document().await?;
+
+        let arrangement_id = client.get_document_arrangement(document_id).await?;
+        let track_id = client.create_track(document_id).await?;
+        client
+            .set_track_name(track_id, "A rather long track name".into())
+            .await?;
+
+        let view_id = TrackViewId {
+            track_id,
+            arrangement_id,
+        };
+        client.get_track_view_range(view_id, None, None).await?;
+
+        let stats = client.get_document_stats(document_id).await?;
+        assert_eq!(count_of(&stats.objects, "Arrangement"), 1);
+        assert_eq!(count_of(&stats.objects, "Track"), 2);
+        assert_eq!(count_of(&stats.caches, "TrackView"), 1);
+        assert!(stats.file_size > 0);
+
+        let other_stats = client.get_document_stats(other_document_id).await?;
+        assert_eq!(count_of(&other_stats.objects, "Track"), 1);
+        assert_eq!(count_of(&other_stats.caches, "TrackView"), 0);
+        assert!(other_stats.estimated_bytes() < stats.estimated_bytes());
+
+        Ok(())
+    })
+}
+
+#[test]
+fn task_queue_stats() -> Result<()> {
+    run_test(|client| async move {
+        let stats = client.get_task_queue_stats().await?;
+        let queues = stats.iter().map(|v| v.queue).collect::<Vec<_>>();
+        assert_eq!(queues, TaskQueue::ALL);
+        assert!(stats.iter().all(|v| v.concurrency > 0));
+
+        Ok(())
+    })
+}
+
+#[test]
+fn track_view_cache_eviction() -> Result<()> {
+    run_test(|client| async move {
+        let document_id = client.create_document().await?;
+        let arrangement_id = client.get_document_arrangement(document_id).await?;
+
+        let mut view_ids = Vec::new();
+        for _ in 0..3 {
+            let track_id = client.create_track(document_id).await?;
+            view_ids.push(TrackViewId {
+                track_id,
+                arrangement_id,
+            });
+        }
+
+        client.set_track_view_cache_capacity(1).await?;
+
+        // the subscribed view is pinned, so only the other ones get evicted
+        let _stream = client
+            .subscribe_track_view(view_ids[0], TrackViewFilter::default())
+            .await?;
+        client.get_track_view_range(view_ids[1], None, None).await?;
+        client.get_track_view_range(view_ids[2], None, None).await?;
+        client.get_track_view_range(view_ids[2], None, None).await?;
+        client.get_track_view_range(view_ids[0], None, None).await?;
+
+        let stats = client.get_track_view_cache_stats().await?;
+        assert_eq!(stats.capacity, 1);
+        assert_eq!(stats.len, 2);
+        assert_eq!(stats.hits, 2);
+        assert_eq!(stats.misses, 3);
+        assert_eq!(stats.evictions, 1);
+
+        client.get_track_view_range(view_ids[1], None, None).await?;
+
+        let stats = client.get_track_view_cache_stats().await?;
+        assert_eq!(stats.len, 2);
+        assert_eq!(stats.misses, 4);
+        assert_eq!(stats.evictions, 2);
+
+        Ok(())
+    })
+}
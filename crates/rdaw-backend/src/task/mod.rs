@@ -0,0 +1,252 @@
+#[cfg(test)]
+mod tests;
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::future::Future;
+use std::panic::{self, AssertUnwindSafe};
+use std::pin::Pin;
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use futures::executor::block_on;
+use rdaw_api::stats::{TaskQueue, TaskQueueStats};
+use rdaw_api::Result;
+
+type Task = Pin<Box<dyn Future<Output = Result<()>> + Send>>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QueueConfig {
+    /// How many tasks of the queue may run at the same time.
+    pub concurrency: usize,
+    /// How many tasks are taken from the queue in a row while others are waiting.
+    pub weight: usize,
+}
+
+impl QueueConfig {
+    pub fn new(concurrency: usize, weight: usize) -> QueueConfig {
+        QueueConfig {
+            concurrency: concurrency.max(1),
+            weight: weight.max(1),
+        }
+    }
+}
+
+/// Runs background work on a fixed set of threads. Tasks are taken from the queues in weighted
+/// round-robin order, and no queue can occupy more threads than its concurrency limit.
+#[derive(Clone)]
+pub struct TaskPool {
+    inner: Arc<TaskPoolInner>,
+}
+
+/// Workers only hold the shared state, so this is dropped along with the last handle.
+struct TaskPoolInner {
+    shared: Arc<Shared>,
+}
+
+struct Shared {
+    state: Mutex<State>,
+    condvar: Condvar,
+}
+
+struct State {
+    queues: Vec<QueueState>,
+    /// Every queue appears as many times as its weight.
+    schedule: Vec<usize>,
+    cursor: usize,
+    is_shutdown: bool,
+}
+
+struct QueueState {
+    queue: TaskQueue,
+    config: QueueConfig,
+    tasks: VecDeque<Task>,
+    running: usize,
+    completed: u64,
+    failed: u64,
+    busy: Duration,
+}
+
+impl TaskPool {
+    pub fn new(
+        num_threads: usize,
+        configs: impl IntoIterator<Item = (TaskQueue, QueueConfig)>,
+    ) -> TaskPool {
+        let mut queues = Vec::new();
+        let mut schedule = Vec::new();
+
+        for (queue, config) in configs {
+            schedule.extend(std::iter::repeat(queues.len()).take(config.weight));
+            queues.push(QueueState {
+                queue,
+                config,
+                tasks: VecDeque::new(),
+                running: 0,
+                completed: 0,
+                failed: 0,
+                busy: Duration::ZERO,
+            });
+        }
+
+        let shared = Arc::new(Shared {
+            state: Mutex::new(State {
+                queues,
+                schedule,
+                cursor: 0,
+                is_shutdown: false,
+            }),
+            condvar: Condvar::new(),
+        });
+
+        for idx in 0..num_threads.max(1) {
+            let shared = shared.clone();
+            thread::Builder::new()
+                .name(format!("rdaw-task-{idx}"))
+                .spawn(move || shared.run_worker())
+                .expect("failed to spawn task thread");
+        }
+
+        TaskPool {
+            inner: Arc::new(TaskPoolInner { shared }),
+        }
+    }
+
+    /// One thread per core, every queue may use at most half of them.
+    pub fn with_default_config() -> TaskPool {
+        let num_threads = thread::available_parallelism()
+            .map_or(4, |v| v.get())
+            .max(2);
+        let limit = num_threads / 2;
+
+        TaskPool::new(
+            num_threads,
+            [
+                (TaskQueue::Io, QueueConfig::new(limit, 1)),
+                (TaskQueue::Decode, QueueConfig::new(limit, 2)),
+                (TaskQueue::Analysis, QueueConfig::new(limit, 1)),
+                (TaskQueue::Render, QueueConfig::new(1, 1)),
+            ],
+        )
+    }
+
+    /// Errors returned by the task are logged.
+    pub fn spawn(&self, queue: TaskQueue, fut: impl Future<Output = Result<()>> + Send + 'static) {
+        let shared = &self.inner.shared;
+        let mut state = shared.state.lock().unwrap();
+
+        let Some(idx) = state.queues.iter().position(|v| v.queue == queue) else {
+            tracing::error!(?queue, "task queue doesn't exist");
+            return;
+        };
+
+        state.queues[idx].tasks.push_back(Box::pin(fut));
+        drop(state);
+
+        shared.condvar.notify_one();
+    }
+
+    pub fn stats(&self) -> Vec<TaskQueueStats> {
+        let state = self.inner.shared.state.lock().unwrap();
+        state
+            .queues
+            .iter()
+            .map(|v| TaskQueueStats {
+                queue: v.queue,
+                concurrency: v.config.concurrency,
+                queued: v.tasks.len(),
+                running: v.running,
+                completed: v.completed,
+                failed: v.failed,
+                busy: v.busy,
+            })
+            .collect()
+    }
+}
+
+impl fmt::Debug for TaskPool {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TaskPool").finish_non_exhaustive()
+    }
+}
+
+impl Drop for TaskPoolInner {
+    fn drop(&mut self) {
+        // queued tasks are dropped, running ones are finished
+        self.shared.state.lock().unwrap().is_shutdown = true;
+        self.shared.condvar.notify_all();
+    }
+}
+
+impl State {
+    /// Takes the next task in the schedule, skipping queues that are empty or at their limit.
+    fn take_task(&mut self) -> Option<(usize, Task)> {
+        for offset in 0..self.schedule.len() {
+            let slot = (self.cursor + offset) % self.schedule.len();
+            let idx = self.schedule[slot];
+            let queue = &mut self.queues[idx];
+
+            if queue.running >= queue.config.concurrency {
+                continue;
+            }
+
+            if let Some(task) = queue.tasks.pop_front() {
+                queue.running += 1;
+                self.cursor = slot + 1;
+                return Some((idx, task));
+            }
+        }
+
+        None
+    }
+}
+
+impl Shared {
+    fn run_worker(&self) {
+        let mut state = self.state.lock().unwrap();
+
+        loop {
+            if state.is_shutdown {
+                return;
+            }
+
+            let Some((idx, task)) = state.take_task() else {
+                state = self.condvar.wait(state).unwrap();
+                continue;
+            };
+
+            let queue = state.queues[idx].queue;
+            drop(state);
+
+            let start = Instant::now();
+            let res = panic::catch_unwind(AssertUnwindSafe(|| block_on(task)));
+            let elapsed = start.elapsed();
+
+            let is_ok = match res {
+                Ok(Ok(())) => true,
+                Ok(Err(error)) => {
+                    tracing::error!(?queue, ?error);
+                    false
+                }
+                Err(_) => {
+                    tracing::error!(?queue, "task has panicked");
+                    false
+                }
+            };
+
+            state = self.state.lock().unwrap();
+
+            let queue = &mut state.queues[idx];
+            queue.running -= 1;
+            queue.busy += elapsed;
+            if is_ok {
+                queue.completed += 1;
+            } else {
+                queue.failed += 1;
+            }
+
+            // a slot of the queue has freed up, which another worker may be waiting for
+            self.condvar.notify_one();
+        }
+    }
+}
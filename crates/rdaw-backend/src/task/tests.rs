@@ -0,0 +1,114 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc};
+use std::time::Duration;
+
+use rdaw_api::stats::TaskQueue;
+use rdaw_api::{format_err, ErrorKind};
+
+use super::{QueueConfig, TaskPool};
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+fn pool(io_concurrency: usize) -> TaskPool {
+    TaskPool::new(
+        4,
+        [
+            (TaskQueue::Io, QueueConfig::new(io_concurrency, 1)),
+            (TaskQueue::Analysis, QueueConfig::new(1, 1)),
+        ],
+    )
+}
+
+#[test]
+fn concurrency_limit() {
+    let pool = pool(2);
+    let running = Arc::new(AtomicUsize::new(0));
+    let max_running = Arc::new(AtomicUsize::new(0));
+    let (sender, receiver) = mpsc::channel();
+
+    for _ in 0..16 {
+        let running = running.clone();
+        let max_running = max_running.clone();
+        let sender = sender.clone();
+
+        pool.spawn(TaskQueue::Io, async move {
+            let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+            max_running.fetch_max(now, Ordering::SeqCst);
+            std::thread::sleep(Duration::from_millis(5));
+            running.fetch_sub(1, Ordering::SeqCst);
+            sender.send(()).unwrap();
+            Ok(())
+        });
+    }
+
+    for _ in 0..16 {
+        receiver.recv_timeout(TIMEOUT).unwrap();
+    }
+
+    assert_eq!(max_running.load(Ordering::SeqCst), 2);
+}
+
+#[test]
+fn busy_queue_doesnt_starve_others() {
+    let pool = pool(1);
+    let (unblock_sender, unblock_receiver) = mpsc::channel::<()>();
+    let (done_sender, done_receiver) = mpsc::channel();
+
+    pool.spawn(TaskQueue::Io, async move {
+        unblock_receiver.recv().unwrap();
+        Ok(())
+    });
+
+    // waits behind the blocked task, but doesn't take another thread
+    pool.spawn(TaskQueue::Io, async { Ok(()) });
+
+    pool.spawn(TaskQueue::Analysis, async move {
+        done_sender.send(()).unwrap();
+        Ok(())
+    });
+
+    done_receiver.recv_timeout(TIMEOUT).unwrap();
+
+    let stats = pool.stats();
+    assert_eq!(
+        (stats[0].queue, stats[0].running, stats[0].queued),
+        (TaskQueue::Io, 1, 1)
+    );
+
+    unblock_sender.send(()).unwrap();
+}
+
+#[test]
+fn stats() {
+    let pool = pool(1);
+    let (sender, receiver) = mpsc::channel();
+
+    let ok_sender = sender.clone();
+    pool.spawn(TaskQueue::Analysis, async move {
+        ok_sender.send(()).unwrap();
+        Ok(())
+    });
+
+    pool.spawn(TaskQueue::Analysis, async move {
+        let _sender = sender;
+        Err(format_err!(ErrorKind::Other, "oops"))
+    });
+
+    receiver.recv_timeout(TIMEOUT).unwrap();
+    // the second sender is dropped once the failed task is done
+    assert!(receiver.recv_timeout(TIMEOUT).is_err());
+
+    // counters are updated right after the task returns, give the worker a moment
+    let deadline = std::time::Instant::now() + TIMEOUT;
+    let stats = loop {
+        let stats = pool.stats();
+        if stats[1].completed + stats[1].failed == 2 || std::time::Instant::now() > deadline {
+            break stats;
+        }
+        std::thread::yield_now();
+    };
+
+    assert_eq!(stats[1].queue, TaskQueue::Analysis);
+    assert_eq!((stats[1].completed, stats[1].failed), (1, 1));
+    assert_eq!((stats[1].queued, stats[1].running), (0, 0));
+}
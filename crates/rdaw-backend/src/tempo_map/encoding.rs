@@ -6,18 +6,18 @@ use crate::define_version_enum;
 use crate::document::encoding;
 use crate::object::{DeserializationContext, SerializationContext};
 
-pub fn serialize(_ctx: &mut SerializationContext<'_>, tempo_map: &TempoMap) -> Result<Vec<u8>> {
+pub fn serialize(ctx: &mut SerializationContext<'_>, tempo_map: &TempoMap) -> Result<Vec<u8>> {
     let raw = TempoMapLatest {
         beats_per_minute: tempo_map.beats_per_minute,
     };
 
-    encoding::serialize(Version::LATEST.as_u32(), &raw)
+    encoding::serialize(ctx.format(), Version::LATEST.as_u32(), &raw)
 }
 
-pub fn deserialize(_ctx: &mut DeserializationContext<'_>, data: &[u8]) -> Result<TempoMap> {
-    let (version, data) = encoding::extract_version(data)?;
+pub fn deserialize(ctx: &mut DeserializationContext<'_>, data: &[u8]) -> Result<TempoMap> {
+    let (version, data) = encoding::extract_version(ctx.format(), data)?;
     let raw = match Version::from_u32(version)? {
-        Version::V1 => encoding::deserialize::<TempoMapV1>(data)?,
+        Version::V1 => encoding::deserialize::<TempoMapV1>(ctx.format(), data)?,
     };
 
     Ok(TempoMap::new(raw.beats_per_minute))
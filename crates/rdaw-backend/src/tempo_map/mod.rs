@@ -1,7 +1,7 @@
 mod encoding;
 
 use rdaw_api::tempo_map::TempoMapId;
-use rdaw_api::time::{BeatTime, Time};
+use rdaw_api::time::{BeatTime, Time, TimeBase};
 use rdaw_api::Result;
 use rdaw_core::time::RealTime;
 
@@ -21,6 +21,10 @@ impl TempoMap {
         TempoMap { beats_per_minute }
     }
 
+    pub fn beats_per_minute(&self) -> f32 {
+        self.beats_per_minute
+    }
+
     pub fn to_real(&self, time: Time) -> RealTime {
         match time {
             Time::Real(t) => t,
@@ -35,6 +39,14 @@ impl TempoMap {
         }
     }
 
+    /// Converts the time to the given time base, keeping its position at the current tempo.
+    pub fn convert(&self, time: Time, time_base: TimeBase) -> Time {
+        match time_base {
+            TimeBase::Absolute => Time::Real(self.to_real(time)),
+            TimeBase::Musical => Time::Beat(self.to_beat(time)),
+        }
+    }
+
     pub fn real_to_beat(&self, real: RealTime) -> BeatTime {
         let beats = real.as_secs_f64() / 60.0 * f64::from(self.beats_per_minute);
         BeatTime::from_beats_f64(beats)
@@ -1,28 +1,65 @@
+use std::collections::VecDeque;
 use std::future::Future;
+use std::path::PathBuf;
+use std::{env, fs};
 
 use futures::executor::LocalPool;
 use futures::task::SpawnExt;
 use futures::FutureExt;
-use rdaw_api::track::TrackId;
+use rdaw_api::arrangement::{ArrangementId, ArrangementOperations};
+use rdaw_api::document::{DocumentId, DocumentOperations};
+use rdaw_api::item::{AudioItemId, ItemId};
+use rdaw_api::time::{BeatTime, Time};
+use rdaw_api::track::{TrackId, TrackItem, TrackItemId, TrackOperations, TrackViewId};
 use rdaw_api::{BackendProtocol, Result};
+use rdaw_core::collections::HashMap;
 use rdaw_rpc::transport::{self, LocalClientTransport};
-use rdaw_rpc::Client;
+use rdaw_rpc::{Client, Role};
 use slotmap::KeyData;
 
 use crate::Backend;
 
+pub type TestClient = Client<BackendProtocol, LocalClientTransport<BackendProtocol>>;
+
 pub fn run_test<Fn, Fut>(f: Fn) -> Result<()>
 where
-    Fn: FnOnce(Client<BackendProtocol, LocalClientTransport<BackendProtocol>>) -> Fut,
+    Fn: FnOnce(TestClient) -> Fut,
+    Fut: Future<Output = Result<()>>,
+{
+    run_test_with(|_| {}, f)
+}
+
+pub fn run_test_with<Fn, Fut>(configure: impl FnOnce(&mut Backend), f: Fn) -> Result<()>
+where
+    Fn: FnOnce(TestClient) -> Fut,
+    Fut: Future<Output = Result<()>>,
+{
+    run_test_impl(Role::Admin, configure, f)
+}
+
+/// Runs the test with a client which only has the given role.
+pub fn run_test_as<Fn, Fut>(role: Role, f: Fn) -> Result<()>
+where
+    Fn: FnOnce(TestClient) -> Fut,
+    Fut: Future<Output = Result<()>>,
+{
+    run_test_impl(role, |_| {}, f)
+}
+
+fn run_test_impl<Fn, Fut>(role: Role, configure: impl FnOnce(&mut Backend), f: Fn) -> Result<()>
+where
+    Fn: FnOnce(TestClient) -> Fut,
     Fut: Future<Output = Result<()>>,
 {
     let mut executor = LocalPool::new();
     let spawner = executor.spawner();
 
     let (client_transport, server_transport) = transport::local(None);
+    let server_transport = server_transport.with_role(role);
 
     let client = Client::new(client_transport);
     let mut backend = Backend::new(server_transport);
+    configure(&mut backend);
 
     spawner
         .spawn(client.clone().handle().map(|v| v.unwrap()))
@@ -38,3 +75,244 @@ where
 pub fn invalid_track_id() -> TrackId {
     TrackId::from(KeyData::from_ffi(u64::MAX))
 }
+
+pub fn beats(beats: i32) -> Time {
+    Time::Beat(BeatTime::from_beats(beats))
+}
+
+/// Creates a document with tracks and items in one go:
+///
+/// ```ignore
+/// let project = ProjectBuilder::new()
+///     .tempo(90.0)
+///     .track("Drums", |t| t.item(beats(0), beats(4)).item(beats(4), beats(4)))
+///     .track("Group", |t| t.child("Bass", |t| t.item(beats(0), beats(8))))
+///     .build(&client)
+///     .await?;
+///
+/// let drums_id = project.track("Drums");
+/// ```
+///
+/// Top-level tracks become children of the main track. Track names must be unique.
+#[derive(Debug, Default)]
+pub struct ProjectBuilder {
+    tempo: Option<f32>,
+    tracks: Vec<TrackBuilder>,
+}
+
+impl ProjectBuilder {
+    pub fn new() -> ProjectBuilder {
+        ProjectBuilder::default()
+    }
+
+    pub fn tempo(mut self, beats_per_minute: f32) -> ProjectBuilder {
+        self.tempo = Some(beats_per_minute);
+        self
+    }
+
+    pub fn track(
+        mut self,
+        name: &str,
+        build: impl FnOnce(TrackBuilder) -> TrackBuilder,
+    ) -> ProjectBuilder {
+        self.tracks.push(build(TrackBuilder::new(name)));
+        self
+    }
+
+    /// Adds `count` empty top-level tracks named after their position, e.g. `Track 2`.
+    pub fn tracks(mut self, count: usize) -> ProjectBuilder {
+        let start = self.tracks.len();
+        for i in start..start + count {
+            self.tracks.push(TrackBuilder::new(&format!("Track {i}")));
+        }
+        self
+    }
+
+    pub async fn build(self, client: &TestClient) -> Result<Project> {
+        let document_id = client.create_document().await?;
+        let arrangement_id = client.get_document_arrangement(document_id).await?;
+        let main_track_id = client.get_arrangement_main_track(arrangement_id).await?;
+
+        if let Some(tempo) = self.tempo {
+            client.set_arrangement_tempo(arrangement_id, tempo).await?;
+        }
+
+        let mut project = Project {
+            document_id,
+            arrangement_id,
+            main_track_id,
+            tracks: HashMap::default(),
+            items: HashMap::default(),
+        };
+
+        let mut queue = self
+            .tracks
+            .into_iter()
+            .map(|track| (main_track_id, track))
+            .collect::<VecDeque<_>>();
+
+        while let Some((parent_id, track)) = queue.pop_front() {
+            let track_id = client.create_track(document_id).await?;
+            client.set_track_name(track_id, track.name.clone()).await?;
+            client.append_track_child(parent_id, track_id).await?;
+
+            let mut item_ids = Vec::with_capacity(track.items.len());
+            for item in track.items {
+                item_ids.push(client.add_track_item(track_id, item).await?);
+            }
+
+            if track.locked {
+                client.set_track_locked(track_id, true).await?;
+            }
+
+            queue.extend(track.children.into_iter().map(|child| (track_id, child)));
+
+            assert!(
+                project.tracks.insert(track.name.clone(), track_id).is_none(),
+                "duplicate track name `{}`",
+                track.name,
+            );
+            project.items.insert(track.name, item_ids);
+        }
+
+        Ok(project)
+    }
+}
+
+#[derive(Debug)]
+pub struct TrackBuilder {
+    name: String,
+    items: Vec<TrackItem>,
+    children: Vec<TrackBuilder>,
+    locked: bool,
+}
+
+impl TrackBuilder {
+    fn new(name: &str) -> TrackBuilder {
+        TrackBuilder {
+            name: name.into(),
+            items: Vec::new(),
+            children: Vec::new(),
+            locked: false,
+        }
+    }
+
+    pub fn item(self, start: Time, duration: Time) -> TrackBuilder {
+        self.item_in_lane(start, duration, 0)
+    }
+
+    pub fn item_in_lane(mut self, start: Time, duration: Time, lane: u32) -> TrackBuilder {
+        self.items.push(TrackItem {
+            inner: ItemId::Audio(AudioItemId::default()),
+            start,
+            duration,
+            lane,
+            locked: false,
+        });
+        self
+    }
+
+    pub fn child(
+        mut self,
+        name: &str,
+        build: impl FnOnce(TrackBuilder) -> TrackBuilder,
+    ) -> TrackBuilder {
+        self.children.push(build(TrackBuilder::new(name)));
+        self
+    }
+
+    pub fn locked(mut self) -> TrackBuilder {
+        self.locked = true;
+        self
+    }
+}
+
+#[derive(Debug)]
+pub struct Project {
+    pub document_id: DocumentId,
+    pub arrangement_id: ArrangementId,
+    pub main_track_id: TrackId,
+    tracks: HashMap<String, TrackId>,
+    items: HashMap<String, Vec<TrackItemId>>,
+}
+
+impl Project {
+    #[track_caller]
+    pub fn track(&self, name: &str) -> TrackId {
+        match self.tracks.get(name) {
+            Some(&id) => id,
+            None => panic!("no track named `{name}`"),
+        }
+    }
+
+    /// Items of the track, in the order they were added to the builder.
+    #[track_caller]
+    pub fn items(&self, name: &str) -> &[TrackItemId] {
+        match self.items.get(name) {
+            Some(ids) => ids,
+            None => panic!("no track named `{name}`"),
+        }
+    }
+
+    #[track_caller]
+    pub fn view(&self, name: &str) -> TrackViewId {
+        TrackViewId {
+            track_id: self.track(name),
+            arrangement_id: self.arrangement_id,
+        }
+    }
+}
+
+const BLESS_VAR: &str = "RDAW_BLESS";
+
+fn golden_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("fixtures")
+        .join(name)
+}
+
+pub fn read_golden(name: &str) -> String {
+    let path = golden_path(name);
+    fs::read_to_string(&path)
+        .unwrap_or_else(|e| panic!("failed to read golden file {}: {e}", path.display()))
+}
+
+#[track_caller]
+pub fn assert_golden(name: &str, actual: &str) {
+    if env::var_os(BLESS_VAR).is_some() {
+        let path = golden_path(name);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, actual).unwrap();
+        return;
+    }
+
+    let expected = read_golden(name);
+
+    assert!(
+        expected == actual,
+        "golden file {name} doesn't match, rerun with {BLESS_VAR}=1 if the format change is \
+         intentional\n\nexpected:\n{expected}\nactual:\n{actual}",
+    );
+}
+
+pub fn to_hex(data: &[u8]) -> String {
+    let mut hex = String::with_capacity(data.len() * 3);
+
+    for line in data.chunks(16) {
+        let line = line
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect::<Vec<_>>()
+            .join(" ");
+        hex.push_str(&line);
+        hex.push('\n');
+    }
+
+    hex
+}
+
+pub fn from_hex(hex: &str) -> Vec<u8> {
+    hex.split_whitespace()
+        .map(|byte| u8::from_str_radix(byte, 16).unwrap())
+        .collect()
+}
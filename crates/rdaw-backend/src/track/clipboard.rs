@@ -0,0 +1,34 @@
+use rdaw_api::document::DocumentId;
+use rdaw_api::time::BeatTime;
+use rdaw_api::track::TrackItem;
+use rdaw_core::time::RealTime;
+
+use crate::item::{AudioItem, MidiItem};
+
+/// Track items copied by `copy_track_items`. Inner items are copied along with them, so that
+/// pasting works after the originals are edited or removed.
+#[derive(Debug, Clone)]
+pub struct Clipboard {
+    /// Items can only be pasted into the document they were copied from, since they refer to
+    /// its sources.
+    pub document_id: DocumentId,
+    pub items: Vec<ClipboardItem>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ClipboardItem {
+    pub item: TrackItem,
+    pub inner: ClipboardInner,
+    /// Position of the track among the arrangement tracks, relative to the topmost copied one.
+    pub track_offset: usize,
+    /// Start of the item relative to the earliest copied one, in both time bases, so that the
+    /// item keeps its distance in whichever one it's positioned in.
+    pub real_offset: RealTime,
+    pub beat_offset: BeatTime,
+}
+
+#[derive(Debug, Clone)]
+pub enum ClipboardInner {
+    Audio(AudioItem),
+    Midi(MidiItem),
+}
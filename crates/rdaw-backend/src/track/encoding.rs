@@ -1,7 +1,10 @@
+use std::borrow::Cow;
+
+use rdaw_api::bus::{SendPosition, TrackSend};
 use rdaw_api::item::{ItemId, ItemKind};
 use rdaw_api::time::Time;
-use rdaw_api::track::TrackItem;
-use rdaw_api::Result;
+use rdaw_api::track::{TrackItem, TrackMix};
+use rdaw_api::{bail, ErrorKind, Result};
 use serde::{Deserialize, Serialize};
 use slotmap::SlotMap;
 
@@ -24,6 +27,7 @@ pub fn serialize(ctx: &mut SerializationContext<'_>, track: &Track) -> Result<Ve
         .map(|(_, item)| {
             let (kind, uuid) = match item.inner {
                 ItemId::Audio(id) => (ItemKind::Audio, ctx.add_dep(id)?),
+                ItemId::Midi(id) => (ItemKind::Midi, ctx.add_dep(id)?),
             };
 
             Ok(TrackItemLatest {
@@ -31,26 +35,84 @@ pub fn serialize(ctx: &mut SerializationContext<'_>, track: &Track) -> Result<Ve
                 uuid,
                 start: item.start,
                 duration: item.duration,
+                lane: item.lane,
+                locked: item.locked,
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let nodes = track
+        .nodes
+        .iter()
+        .map(|&id| ctx.add_dep(id))
+        .collect::<Result<Vec<_>>>()?;
+
+    let automation_lanes = track
+        .automation_lanes
+        .iter()
+        .map(|&id| ctx.add_dep(id))
+        .collect::<Result<Vec<_>>>()?;
+
+    let sends = track
+        .sends
+        .values()
+        .map(|send| {
+            Ok(TrackSendLatest {
+                bus_uuid: ctx.add_dep(send.bus_id)?,
+                level: send.level,
+                position: send.position,
             })
         })
         .collect::<Result<Vec<_>>>()?;
 
     let raw = TrackLatest {
-        name: &track.name,
+        name: Cow::Borrowed(&track.name),
+        locked: track.locked,
         children,
         items,
+        nodes,
+        automation_lanes,
+        muted: track.muted,
+        soloed: track.soloed,
+        volume: track.mix.volume,
+        pan: track.mix.pan,
+        sends,
     };
 
-    encoding::serialize(Version::LATEST.as_u32(), &raw)
+    encoding::serialize(ctx.format(), Version::LATEST.as_u32(), &raw)
 }
 
 pub fn deserialize(ctx: &mut DeserializationContext<'_>, data: &[u8]) -> Result<Track> {
-    let (version, data) = encoding::extract_version(data)?;
+    let (version, data) = encoding::extract_version(ctx.format(), data)?;
     let raw = match Version::from_u32(version)? {
-        Version::V1 => encoding::deserialize::<TrackV1>(data)?,
+        Version::V1 => {
+            let raw = TrackV2::from(encoding::deserialize::<TrackV1>(ctx.format(), data)?);
+            let raw = TrackV5::from(TrackV4::from(TrackV3::from(raw)));
+            TrackV7::from(TrackV6::from(raw)).into()
+        }
+        Version::V2 => {
+            let raw = encoding::deserialize::<TrackV2>(ctx.format(), data)?;
+            let raw = TrackV5::from(TrackV4::from(TrackV3::from(raw)));
+            TrackV7::from(TrackV6::from(raw)).into()
+        }
+        Version::V3 => {
+            let raw = encoding::deserialize::<TrackV3>(ctx.format(), data)?;
+            TrackV7::from(TrackV6::from(TrackV5::from(TrackV4::from(raw)))).into()
+        }
+        Version::V4 => {
+            let raw = encoding::deserialize::<TrackV4>(ctx.format(), data)?;
+            TrackV7::from(TrackV6::from(TrackV5::from(raw))).into()
+        }
+        Version::V5 => {
+            let raw = encoding::deserialize::<TrackV5>(ctx.format(), data)?;
+            TrackV7::from(TrackV6::from(raw)).into()
+        }
+        Version::V6 => TrackV7::from(encoding::deserialize::<TrackV6>(ctx.format(), data)?).into(),
+        Version::V7 => encoding::deserialize::<TrackV7>(ctx.format(), data)?.into(),
+        Version::V8 => encoding::deserialize::<TrackV8>(ctx.format(), data)?,
     };
 
-    let name = raw.name.to_owned();
+    let name = raw.name.into_owned();
 
     let children = raw
         .children
@@ -63,37 +125,88 @@ pub fn deserialize(ctx: &mut DeserializationContext<'_>, data: &[u8]) -> Result<
     for item in raw.items {
         let inner = match item.kind {
             ItemKind::Audio => ItemId::Audio(ctx.add_dep(item.uuid)?),
+            ItemKind::Midi => ItemId::Midi(ctx.add_dep(item.uuid)?),
         };
 
         items.insert(TrackItem {
             inner,
             start: item.start,
             duration: item.duration,
+            lane: item.lane,
+            locked: item.locked,
         });
     }
 
+    let nodes = raw
+        .nodes
+        .into_iter()
+        .map(|uuid| ctx.add_dep(uuid))
+        .collect::<Result<Vec<_>>>()?;
+
+    let automation_lanes = raw
+        .automation_lanes
+        .into_iter()
+        .map(|uuid| ctx.add_dep(uuid))
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut sends = SlotMap::with_capacity_and_key(raw.sends.len());
+
+    for send in raw.sends {
+        let send = TrackSend {
+            bus_id: ctx.add_dep(send.bus_uuid)?,
+            level: send.level,
+            position: send.position,
+        };
+
+        if !send.is_valid() {
+            bail!(ErrorKind::Deserialization, "invalid send {send:?}");
+        }
+
+        sends.insert(send);
+    }
+
     Ok(Track {
         name,
+        locked: raw.locked,
+        muted: raw.muted,
+        soloed: raw.soloed,
+        mix: TrackMix {
+            volume: raw.volume,
+            pan: raw.pan,
+        },
+        armed: false,
         links: TrackLinks {
             children,
             ..Default::default()
         },
         items,
+        nodes,
+        automation_lanes,
+        sends,
     })
 }
 
 define_version_enum! {
     enum Version {
         V1 = 1,
+        V2 = 2,
+        V3 = 3,
+        V4 = 4,
+        V5 = 5,
+        V6 = 6,
+        V7 = 7,
+        V8 = 8,
     }
 }
 
-type TrackLatest<'a> = TrackV1<'a>;
-type TrackItemLatest = TrackItemV1;
+type TrackLatest<'a> = TrackV8<'a>;
+type TrackItemLatest = TrackItemV3;
+type TrackSendLatest = TrackSendV8;
 
 #[derive(Debug, Serialize, Deserialize)]
 struct TrackV1<'a> {
-    name: &'a str,
+    #[serde(borrow)]
+    name: Cow<'a, str>,
     children: Vec<Uuid>,
     items: Vec<TrackItemV1>,
 }
@@ -105,3 +218,234 @@ struct TrackItemV1 {
     start: Time,
     duration: Time,
 }
+
+#[derive(Debug, Serialize, Deserialize)]
+struct TrackV2<'a> {
+    #[serde(borrow)]
+    name: Cow<'a, str>,
+    locked: bool,
+    children: Vec<Uuid>,
+    items: Vec<TrackItemV2>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct TrackItemV2 {
+    kind: ItemKind,
+    uuid: Uuid,
+    start: Time,
+    duration: Time,
+    locked: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct TrackV3<'a> {
+    #[serde(borrow)]
+    name: Cow<'a, str>,
+    locked: bool,
+    children: Vec<Uuid>,
+    items: Vec<TrackItemV3>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct TrackItemV3 {
+    kind: ItemKind,
+    uuid: Uuid,
+    start: Time,
+    duration: Time,
+    lane: u32,
+    locked: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct TrackV4<'a> {
+    #[serde(borrow)]
+    name: Cow<'a, str>,
+    locked: bool,
+    children: Vec<Uuid>,
+    items: Vec<TrackItemV3>,
+    nodes: Vec<Uuid>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct TrackV5<'a> {
+    #[serde(borrow)]
+    name: Cow<'a, str>,
+    locked: bool,
+    children: Vec<Uuid>,
+    items: Vec<TrackItemV3>,
+    nodes: Vec<Uuid>,
+    automation_lanes: Vec<Uuid>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct TrackV6<'a> {
+    #[serde(borrow)]
+    name: Cow<'a, str>,
+    locked: bool,
+    children: Vec<Uuid>,
+    items: Vec<TrackItemV3>,
+    nodes: Vec<Uuid>,
+    automation_lanes: Vec<Uuid>,
+    muted: bool,
+    soloed: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct TrackV7<'a> {
+    #[serde(borrow)]
+    name: Cow<'a, str>,
+    locked: bool,
+    children: Vec<Uuid>,
+    items: Vec<TrackItemV3>,
+    nodes: Vec<Uuid>,
+    automation_lanes: Vec<Uuid>,
+    muted: bool,
+    soloed: bool,
+    volume: f32,
+    pan: f32,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct TrackV8<'a> {
+    #[serde(borrow)]
+    name: Cow<'a, str>,
+    locked: bool,
+    children: Vec<Uuid>,
+    items: Vec<TrackItemV3>,
+    nodes: Vec<Uuid>,
+    automation_lanes: Vec<Uuid>,
+    muted: bool,
+    soloed: bool,
+    volume: f32,
+    pan: f32,
+    sends: Vec<TrackSendV8>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct TrackSendV8 {
+    bus_uuid: Uuid,
+    level: f32,
+    position: SendPosition,
+}
+
+impl<'a> From<TrackV1<'a>> for TrackV2<'a> {
+    fn from(v: TrackV1<'a>) -> TrackV2<'a> {
+        TrackV2 {
+            name: v.name,
+            locked: false,
+            children: v.children,
+            items: v.items.into_iter().map(TrackItemV2::from).collect(),
+        }
+    }
+}
+
+impl From<TrackItemV1> for TrackItemV2 {
+    fn from(v: TrackItemV1) -> TrackItemV2 {
+        TrackItemV2 {
+            kind: v.kind,
+            uuid: v.uuid,
+            start: v.start,
+            duration: v.duration,
+            locked: false,
+        }
+    }
+}
+
+impl<'a> From<TrackV2<'a>> for TrackV3<'a> {
+    fn from(v: TrackV2<'a>) -> TrackV3<'a> {
+        TrackV3 {
+            name: v.name,
+            locked: v.locked,
+            children: v.children,
+            items: v.items.into_iter().map(TrackItemV3::from).collect(),
+        }
+    }
+}
+
+impl From<TrackItemV2> for TrackItemV3 {
+    fn from(v: TrackItemV2) -> TrackItemV3 {
+        TrackItemV3 {
+            kind: v.kind,
+            uuid: v.uuid,
+            start: v.start,
+            duration: v.duration,
+            lane: 0,
+            locked: v.locked,
+        }
+    }
+}
+
+impl<'a> From<TrackV3<'a>> for TrackV4<'a> {
+    fn from(v: TrackV3<'a>) -> TrackV4<'a> {
+        TrackV4 {
+            name: v.name,
+            locked: v.locked,
+            children: v.children,
+            items: v.items,
+            nodes: Vec::new(),
+        }
+    }
+}
+
+impl<'a> From<TrackV4<'a>> for TrackV5<'a> {
+    fn from(v: TrackV4<'a>) -> TrackV5<'a> {
+        TrackV5 {
+            name: v.name,
+            locked: v.locked,
+            children: v.children,
+            items: v.items,
+            nodes: v.nodes,
+            automation_lanes: Vec::new(),
+        }
+    }
+}
+
+impl<'a> From<TrackV5<'a>> for TrackV6<'a> {
+    fn from(v: TrackV5<'a>) -> TrackV6<'a> {
+        TrackV6 {
+            name: v.name,
+            locked: v.locked,
+            children: v.children,
+            items: v.items,
+            nodes: v.nodes,
+            automation_lanes: v.automation_lanes,
+            muted: false,
+            soloed: false,
+        }
+    }
+}
+
+impl<'a> From<TrackV6<'a>> for TrackV7<'a> {
+    fn from(v: TrackV6<'a>) -> TrackV7<'a> {
+        TrackV7 {
+            name: v.name,
+            locked: v.locked,
+            children: v.children,
+            items: v.items,
+            nodes: v.nodes,
+            automation_lanes: v.automation_lanes,
+            muted: v.muted,
+            soloed: v.soloed,
+            volume: 1.0,
+            pan: 0.0,
+        }
+    }
+}
+
+impl<'a> From<TrackV7<'a>> for TrackV8<'a> {
+    fn from(v: TrackV7<'a>) -> TrackV8<'a> {
+        TrackV8 {
+            name: v.name,
+            locked: v.locked,
+            children: v.children,
+            items: v.items,
+            nodes: v.nodes,
+            automation_lanes: v.automation_lanes,
+            muted: v.muted,
+            soloed: v.soloed,
+            volume: v.volume,
+            pan: v.pan,
+            sends: Vec::new(),
+        }
+    }
+}
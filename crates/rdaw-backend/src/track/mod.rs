@@ -1,15 +1,22 @@
+mod clipboard;
 mod encoding;
 mod ops;
 #[cfg(test)]
 mod tests;
 mod view;
 
-use rdaw_api::track::{TrackId, TrackItem, TrackItemId};
+use std::mem;
+
+use rdaw_api::automation::AutomationLaneId;
+use rdaw_api::bus::{SendId, TrackSend};
+use rdaw_api::node::NodeId;
+use rdaw_api::track::{TrackId, TrackItem, TrackItemId, TrackMix};
 use rdaw_api::Result;
 use rdaw_core::collections::HashSet;
 use slotmap::SlotMap;
 
-pub use self::view::{TrackView, TrackViewCache};
+pub use self::clipboard::{Clipboard, ClipboardInner, ClipboardItem};
+pub use self::view::{filter_intersects, view_item, TrackView, TrackViewCache};
 use crate::object::{DeserializationContext, Object, ObjectId, ObjectType, SerializationContext};
 
 impl ObjectId for TrackId {
@@ -19,16 +26,33 @@ impl ObjectId for TrackId {
 #[derive(Debug, Clone)]
 pub struct Track {
     pub name: String,
+    pub locked: bool,
+    pub muted: bool,
+    pub soloed: bool,
+    pub mix: TrackMix,
+    /// Not saved, documents are always opened with nothing armed.
+    pub armed: bool,
     pub links: TrackLinks,
     pub items: SlotMap<TrackItemId, TrackItem>,
+    pub nodes: Vec<NodeId>,
+    pub automation_lanes: Vec<AutomationLaneId>,
+    pub sends: SlotMap<SendId, TrackSend>,
 }
 
 impl Track {
     pub fn new(name: String) -> Track {
         Track {
             name,
+            locked: false,
+            muted: false,
+            soloed: false,
+            mix: TrackMix::default(),
+            armed: false,
             links: TrackLinks::default(),
             items: SlotMap::default(),
+            nodes: Vec::new(),
+            automation_lanes: Vec::new(),
+            sends: SlotMap::default(),
         }
     }
 }
@@ -45,6 +69,20 @@ impl Object for Track {
     fn deserialize(ctx: &mut DeserializationContext<'_>, data: &[u8]) -> Result<Self> {
         self::encoding::deserialize(ctx, data)
     }
+
+    fn heap_size(&self) -> usize {
+        let links = &self.links;
+        let track_ids = links.children.capacity()
+            + links.ancestors.capacity()
+            + links.direct_ancestors.capacity();
+
+        self.name.capacity()
+            + track_ids * mem::size_of::<TrackId>()
+            + self.items.capacity() * mem::size_of::<TrackItem>()
+            + self.nodes.capacity() * mem::size_of::<NodeId>()
+            + self.automation_lanes.capacity() * mem::size_of::<AutomationLaneId>()
+            + self.sends.capacity() * mem::size_of::<TrackSend>()
+    }
 }
 
 #[derive(Debug, Clone, Default)]
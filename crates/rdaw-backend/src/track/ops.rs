@@ -1,16 +1,26 @@
+use rdaw_api::arrangement::ArrangementId;
 use rdaw_api::document::DocumentId;
-use rdaw_api::time::Time;
+use rdaw_api::item::{AudioClip, Fade, ItemId};
+use rdaw_api::node::NodeId;
+use rdaw_api::stats::TaskQueue;
+use rdaw_api::time::{BeatTime, Time, TimeBase};
 use rdaw_api::track::{
-    TrackHierarchy, TrackHierarchyEvent, TrackId, TrackItem, TrackItemId, TrackOperations,
-    TrackRequest, TrackResponse, TrackViewEvent, TrackViewId, TrackViewItem,
+    PasteTarget, RippleMode, TrackHierarchy, TrackHierarchyEvent, TrackId, TrackItem, TrackItemId,
+    TrackMix, TrackOperations, TrackRequest, TrackResponse, TrackStatus, TrackSummary,
+    TrackViewEvent, TrackViewFilter, TrackViewId, TrackViewItem, MAX_TRACK_LANE,
 };
-use rdaw_api::{bail, format_err, BackendProtocol, ErrorKind, Result};
-use rdaw_rpc::StreamId;
+use rdaw_api::{bail, format_err, BackendProtocol, Error, ErrorKind, Page, PageRequest, Result};
+use rdaw_core::collections::HashSet;
+use rdaw_core::time::RealTime;
+use rdaw_rpc::{CancellationToken, Responder, StreamId};
 use slotmap::Key;
 use tracing::instrument;
 
-use super::Track;
-use crate::object::ObjectKey;
+use super::view::{filter_intersects, item_clip, view_item};
+use super::{Clipboard, ClipboardInner, ClipboardItem, Track};
+use crate::item::{detect_transients, AudioItem};
+use crate::object::{ObjectKey, SubscribersHub};
+use crate::tempo_map::TempoMap;
 use crate::Backend;
 
 #[rdaw_rpc::handler(protocol = BackendProtocol, operations = TrackOperations)]
@@ -34,6 +44,17 @@ impl Backend {
         Ok(id)
     }
 
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn list_tracks(
+        &self,
+        document_id: DocumentId,
+        page: PageRequest,
+    ) -> Result<Page<TrackId>> {
+        self.documents.ensure_has(document_id)?;
+        Ok(self.hub.tracks.page_in_document(document_id, page))
+    }
+
     #[instrument(level = "trace", skip_all, err)]
     #[handler]
     pub fn subscribe_track_name(&mut self, id: TrackId) -> Result<StreamId> {
@@ -50,10 +71,36 @@ impl Backend {
 
     #[instrument(level = "trace", skip_all, err)]
     #[handler]
-    pub fn subscribe_track_view(&mut self, id: TrackViewId) -> Result<StreamId> {
+    pub fn subscribe_track_locked(&mut self, id: TrackId) -> Result<StreamId> {
+        self.hub.tracks.ensure_has(id)?;
+        Ok(self.subscribers.track_locked.subscribe(id))
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn subscribe_track_status(&mut self, id: TrackId) -> Result<StreamId> {
+        self.hub.tracks.ensure_has(id)?;
+        Ok(self.subscribers.track_status.subscribe(id))
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn subscribe_track_mix(&mut self, id: TrackId) -> Result<StreamId> {
+        self.hub.tracks.ensure_has(id)?;
+        Ok(self.subscribers.track_mix.subscribe(id))
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn subscribe_track_view(
+        &mut self,
+        id: TrackViewId,
+        filter: TrackViewFilter,
+    ) -> Result<StreamId> {
         self.hub.arrangements.ensure_has(id.arrangement_id)?;
         self.hub.tracks.ensure_has(id.track_id)?;
-        Ok(self.subscribers.track_view.subscribe(id))
+        self.track_view_cache.get_or_insert(&self.hub, &self.subscribers, id);
+        Ok(self.subscribers.track_view.subscribe_filtered(id, filter))
     }
 
     #[instrument(level = "trace", skip_all, err)]
@@ -72,6 +119,103 @@ impl Backend {
         Ok(())
     }
 
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn get_track_locked(&self, id: TrackId) -> Result<bool> {
+        let track = self.hub.tracks.get_or_err(id)?;
+        Ok(track.locked)
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn set_track_locked(&mut self, id: TrackId, locked: bool) -> Result<()> {
+        let track = self.hub.tracks.get_mut_or_err(id)?;
+        track.locked = locked;
+        self.subscribers.track_locked.notify(id, locked);
+        self.notify_track_status(id);
+        Ok(())
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn get_track_status(&self, id: TrackId) -> Result<TrackStatus> {
+        let track = self.hub.tracks.get_or_err(id)?;
+        Ok(TrackStatus {
+            frozen: false,
+            locked: track.locked,
+            muted: track.muted,
+            soloed: track.soloed,
+            armed: track.armed,
+            monitoring: self.recordings.values().any(|v| v.tracks.contains(&id)),
+        })
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn set_track_mute(&mut self, id: TrackId, muted: bool) -> Result<()> {
+        let track = self.hub.tracks.get_mut_or_err(id)?;
+        track.muted = muted;
+        self.notify_track_status(id);
+        Ok(())
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn set_track_solo(&mut self, id: TrackId, soloed: bool) -> Result<()> {
+        let track = self.hub.tracks.get_mut_or_err(id)?;
+        track.soloed = soloed;
+        self.notify_track_status(id);
+        Ok(())
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn get_track_mix(&self, id: TrackId) -> Result<TrackMix> {
+        let track = self.hub.tracks.get_or_err(id)?;
+        Ok(track.mix)
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn set_track_volume(&mut self, id: TrackId, volume: f32) -> Result<()> {
+        if !volume.is_finite() || volume < 0.0 {
+            bail!(ErrorKind::NotSupported, "invalid volume {volume}");
+        }
+
+        let track = self.hub.tracks.get_mut_or_err(id)?;
+        track.mix.volume = volume;
+        self.subscribers.track_mix.notify(id, track.mix);
+        Ok(())
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn set_track_pan(&mut self, id: TrackId, pan: f32) -> Result<()> {
+        if !(-1.0..=1.0).contains(&pan) {
+            bail!(ErrorKind::NotSupported, "invalid pan {pan}");
+        }
+
+        let track = self.hub.tracks.get_mut_or_err(id)?;
+        track.mix.pan = pan;
+        self.subscribers.track_mix.notify(id, track.mix);
+        Ok(())
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn get_muted_tracks(&self, arrangement_id: ArrangementId) -> Result<Vec<TrackId>> {
+        let arrangement = self.hub.arrangements.get_or_err(arrangement_id)?;
+        let hierarchy = self.get_track_hierarchy(arrangement.main_track_id)?;
+
+        let muted = hierarchy.effectively_muted(|id| self.get_track_status(id).unwrap_or_default());
+
+        Ok(self
+            .arrangement_tracks(arrangement_id)
+            .into_iter()
+            .filter(|id| muted.contains(id))
+            .collect())
+    }
+
     #[instrument(level = "trace", skip_all, err)]
     #[handler]
     pub fn get_track_children(&self, id: TrackId) -> Result<Vec<TrackId>> {
@@ -79,6 +223,24 @@ impl Backend {
         Ok(track.links.children.clone())
     }
 
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn get_tracks_summary(&self, ids: Vec<TrackId>) -> Result<Vec<TrackSummary>> {
+        ids.into_iter().map(|id| self.track_summary(id)).collect()
+    }
+
+    pub fn track_summary(&self, id: TrackId) -> Result<TrackSummary> {
+        let track = self.hub.tracks.get_or_err(id)?;
+
+        Ok(TrackSummary {
+            id,
+            name: track.name.clone(),
+            locked: track.locked,
+            children: track.links.children.to_vec(),
+            nodes: track.nodes.clone(),
+        })
+    }
+
     #[instrument(level = "trace", skip_all, err)]
     #[handler]
     pub fn get_track_hierarchy(&self, id: TrackId) -> Result<TrackHierarchy> {
@@ -98,14 +260,7 @@ impl Backend {
     }
 
     pub fn recompute_track_hierarchy(&mut self, root_id: TrackId) {
-        self.track_dfs(root_id, |this, track_id, _| {
-            let Some(track) = this.hub.tracks.get_mut(track_id) else {
-                return;
-            };
-
-            track.links.ancestors.clear();
-            track.links.direct_ancestors.clear();
-        });
+        self.clear_track_hierarchy(root_id);
 
         self.track_dfs(root_id, |this, track_id, parent_id| {
             let Some(track) = this.hub.tracks.get_mut(track_id) else {
@@ -120,11 +275,21 @@ impl Backend {
         self.recompute_track_ancestors(root_id);
     }
 
-    fn notify_track_child_change(&mut self, id: TrackId) {
-        let track = &self.hub.tracks[id];
-        let new_children = track.links.children.iter().copied().collect();
+    /// Clears ancestors of the track and all of its descendants.
+    pub fn clear_track_hierarchy(&mut self, root_id: TrackId) {
+        self.track_dfs(root_id, |this, track_id, _| {
+            let Some(track) = this.hub.tracks.get_mut(track_id) else {
+                return;
+            };
+
+            track.links.ancestors.clear();
+            track.links.direct_ancestors.clear();
+        });
+    }
 
-        let event = TrackHierarchyEvent::ChildrenChanged { id, new_children };
+    fn notify_track_hierarchy(&mut self, event: TrackHierarchyEvent) {
+        let id = event.id();
+        let track = &self.hub.tracks[id];
 
         for &ancestor in &track.links.ancestors {
             self.subscribers
@@ -151,40 +316,33 @@ impl Backend {
     ) {
         callback(self, root_id, parent_id);
 
-        let children = std::mem::take(&mut self.hub.tracks[root_id].links.children);
+        let Some(track) = self.hub.tracks.get(root_id) else {
+            return;
+        };
 
-        for &child_id in &children {
+        for child_id in track.links.children.clone() {
             self.track_dfs_inner(child_id, Some(root_id), callback);
         }
-
-        self.hub.tracks[root_id].links.children = children;
     }
 
     fn recompute_track_ancestors(&mut self, root_id: TrackId) {
         self.track_dfs(root_id, |this, track_id, _| {
-            if !this.hub.tracks.has(track_id) {
+            let Some(track) = this.hub.tracks.get(track_id) else {
                 return;
-            }
-
-            let mut ancestors = std::mem::take(&mut this.hub.tracks[track_id].links.ancestors);
-            let direct_ancestors =
-                std::mem::take(&mut this.hub.tracks[track_id].links.direct_ancestors);
+            };
 
-            ancestors.clear();
+            let mut ancestors = HashSet::default();
 
-            for &ancestor_id in &direct_ancestors {
+            for &ancestor_id in &track.links.direct_ancestors {
                 ancestors.insert(ancestor_id);
 
                 let Some(ancestor) = this.hub.tracks.get(ancestor_id) else {
                     continue;
                 };
 
-                for &transitive_ancestor in &ancestor.links.ancestors {
-                    ancestors.insert(transitive_ancestor);
-                }
+                ancestors.extend(ancestor.links.ancestors.iter().copied());
             }
 
-            this.hub.tracks[track_id].links.direct_ancestors = direct_ancestors;
             this.hub.tracks[track_id].links.ancestors = ancestors;
         });
     }
@@ -260,7 +418,11 @@ impl Backend {
 
         parent.links.children.insert(index, child_id);
         self.add_track_ancestor(child_id, parent_id);
-        self.notify_track_child_change(parent_id);
+        self.notify_track_hierarchy(TrackHierarchyEvent::ChildInserted {
+            id: parent_id,
+            index,
+            child: child_id,
+        });
 
         Ok(())
     }
@@ -299,7 +461,11 @@ impl Backend {
         let child_id = parent.links.children.remove(old_index);
         parent.links.children.insert(new_index, child_id);
 
-        self.notify_track_child_change(parent_id);
+        self.notify_track_hierarchy(TrackHierarchyEvent::ChildMoved {
+            id: parent_id,
+            from: old_index,
+            to: new_index,
+        });
 
         Ok(())
     }
@@ -326,10 +492,7 @@ impl Backend {
             );
         }
 
-        let [old_parent, new_parent] = self
-            .hub
-            .tracks
-            .get_disjoint_mut_or_err([old_parent_id, new_parent_id])?;
+        let [old_parent, new_parent] = self.hub.get_many_mut([old_parent_id, new_parent_id])?;
 
         if new_index > new_parent.links.children.len() {
             bail!(
@@ -354,8 +517,15 @@ impl Backend {
         }
 
         self.add_track_ancestor(child_id, new_parent_id);
-        self.notify_track_child_change(old_parent_id);
-        self.notify_track_child_change(new_parent_id);
+        self.notify_track_hierarchy(TrackHierarchyEvent::ChildRemoved {
+            id: old_parent_id,
+            index: old_index,
+        });
+        self.notify_track_hierarchy(TrackHierarchyEvent::ChildInserted {
+            id: new_parent_id,
+            index: new_index,
+            child: child_id,
+        });
 
         Ok(())
     }
@@ -378,7 +548,10 @@ impl Backend {
             self.remove_track_ancestor(child_id, parent_id);
         }
 
-        self.notify_track_child_change(parent_id);
+        self.notify_track_hierarchy(TrackHierarchyEvent::ChildRemoved {
+            id: parent_id,
+            index,
+        });
 
         Ok(())
     }
@@ -388,16 +561,19 @@ impl Backend {
     pub fn add_track_item(&mut self, track_id: TrackId, item: TrackItem) -> Result<TrackItemId> {
         let track = self.hub.tracks.get_mut_or_err(track_id)?;
         let item_id = track.items.insert(item);
+        let clip = item_clip(&self.hub, &item);
 
         for (view_id, view) in self.track_view_cache.iter_mut(track_id) {
             let arrangement = &self.hub.arrangements[view_id.arrangement_id];
             let tempo_map = &self.hub.tempo_maps[arrangement.tempo_map_id];
-            let view_item = view.add_item(tempo_map, item_id, item);
+            let view_item = view.add_item(tempo_map, item_id, item, clip);
             let event = TrackViewEvent::ItemAdded {
                 id: item_id,
                 item: view_item,
             };
-            self.subscribers.track_view.notify(view_id, event);
+            self.subscribers.track_view.notify_filtered(view_id, event, |filter| {
+                filter_intersects(tempo_map, filter, [&view_item])
+            });
         }
 
         Ok(item_id)
@@ -417,7 +593,14 @@ impl Backend {
 
     #[instrument(level = "trace", skip_all, err)]
     #[handler]
-    pub fn remove_track_item(&mut self, track_id: TrackId, item_id: TrackItemId) -> Result<()> {
+    pub fn remove_track_item(
+        &mut self,
+        track_id: TrackId,
+        item_id: TrackItemId,
+        force: bool,
+    ) -> Result<()> {
+        self.ensure_track_item_unlocked(track_id, item_id, force)?;
+
         let track = self.hub.tracks.get_mut_or_err(track_id)?;
 
         if track.items.remove(item_id).is_none() {
@@ -425,9 +608,17 @@ impl Backend {
         }
 
         for (view_id, view) in self.track_view_cache.iter_mut(track_id) {
+            let arrangement = &self.hub.arrangements[view_id.arrangement_id];
+            let tempo_map = &self.hub.tempo_maps[arrangement.tempo_map_id];
+            let Some(old_item) = view.get_item(item_id).copied() else {
+                continue;
+            };
+
             view.remove_item(item_id);
             let event = TrackViewEvent::ItemRemoved { id: item_id };
-            self.subscribers.track_view.notify(view_id, event);
+            self.subscribers.track_view.notify_filtered(view_id, event, |filter| {
+                filter_intersects(tempo_map, filter, [&old_item])
+            });
         }
 
         Ok(())
@@ -440,6 +631,17 @@ impl Backend {
         track_id: TrackId,
         item_id: TrackItemId,
         new_start: Time,
+        force: bool,
+    ) -> Result<()> {
+        self.ensure_track_item_unlocked(track_id, item_id, force)?;
+        self.apply_track_item_move(track_id, item_id, new_start)
+    }
+
+    fn apply_track_item_move(
+        &mut self,
+        track_id: TrackId,
+        item_id: TrackItemId,
+        new_start: Time,
     ) -> Result<()> {
         let track = self.hub.tracks.get_mut_or_err(track_id)?;
         let item = track.items.get_mut(item_id).ok_or_else(|| {
@@ -454,13 +656,26 @@ impl Backend {
         for (view_id, view) in self.track_view_cache.iter_mut(track_id) {
             let arrangement = &self.hub.arrangements[view_id.arrangement_id];
             let tempo_map = &self.hub.tempo_maps[arrangement.tempo_map_id];
+            let old_item = view.get_item(item_id).copied();
             let new_real_start = view.move_item(tempo_map, item_id, new_start);
+            let new_item = view.get_item(item_id).copied();
+            let (Some(old_item), Some(new_item)) = (old_item, new_item) else {
+                continue;
+            };
+
             let event = TrackViewEvent::ItemMoved {
                 id: item_id,
                 new_start,
                 new_real_start,
             };
-            self.subscribers.track_view.notify(view_id, event);
+            notify_item_change(
+                &mut self.subscribers,
+                tempo_map,
+                view_id,
+                item_id,
+                (&old_item, &new_item),
+                event,
+            );
         }
 
         Ok(())
@@ -473,6 +688,17 @@ impl Backend {
         track_id: TrackId,
         item_id: TrackItemId,
         new_duration: Time,
+        force: bool,
+    ) -> Result<()> {
+        self.ensure_track_item_unlocked(track_id, item_id, force)?;
+        self.apply_track_item_resize(track_id, item_id, new_duration)
+    }
+
+    fn apply_track_item_resize(
+        &mut self,
+        track_id: TrackId,
+        item_id: TrackItemId,
+        new_duration: Time,
     ) -> Result<()> {
         let track = self.hub.tracks.get_mut_or_err(track_id)?;
         let item = track.items.get_mut(item_id).ok_or_else(|| {
@@ -487,13 +713,26 @@ impl Backend {
         for (view_id, view) in self.track_view_cache.iter_mut(track_id) {
             let arrangement = &self.hub.arrangements[view_id.arrangement_id];
             let tempo_map = &self.hub.tempo_maps[arrangement.tempo_map_id];
+            let old_item = view.get_item(item_id).copied();
             let new_real_duration = view.resize_item(tempo_map, item_id, new_duration);
+            let new_item = view.get_item(item_id).copied();
+            let (Some(old_item), Some(new_item)) = (old_item, new_item) else {
+                continue;
+            };
+
             let event = TrackViewEvent::ItemResized {
                 id: item_id,
                 new_duration,
                 new_real_duration,
             };
-            self.subscribers.track_view.notify(view_id, event);
+            notify_item_change(
+                &mut self.subscribers,
+                tempo_map,
+                view_id,
+                item_id,
+                (&old_item, &new_item),
+                event,
+            );
         }
 
         Ok(())
@@ -501,42 +740,1031 @@ impl Backend {
 
     #[instrument(level = "trace", skip_all, err)]
     #[handler]
-    pub fn get_track_view_item(
+    pub fn ripple_move_track_item(
         &mut self,
         view_id: TrackViewId,
         item_id: TrackItemId,
-    ) -> Result<TrackViewItem> {
+        new_start: Time,
+        mode: RippleMode,
+        force: bool,
+    ) -> Result<()> {
         self.hub.arrangements.ensure_has(view_id.arrangement_id)?;
-        self.hub.tracks.ensure_has(view_id.track_id)?;
+        self.ensure_track_item_unlocked(view_id.track_id, item_id, force)?;
 
-        let view = self.track_view_cache.get_or_insert(&self.hub, view_id);
-        view.get_item(item_id).copied().ok_or_else(|| {
-            format_err!(
-                ErrorKind::InvalidId,
-                "{item_id:?} doesn't exist in {:?}",
-                view_id.track_id,
-            )
-        })
+        let item = self.get_track_item(view_id.track_id, item_id)?;
+        let tempo_map = self.arrangement_tempo_map(view_id.arrangement_id);
+        let pivot = tempo_map.to_real(item.start);
+
+        let shifts =
+            self.ripple_shifts(view_id, item_id, pivot, item.start, new_start, mode, force)?;
+
+        self.apply_track_item_move(view_id.track_id, item_id, new_start)?;
+
+        for (track_id, item_id, new_start) in shifts {
+            self.apply_track_item_move(track_id, item_id, new_start)?;
+        }
+
+        Ok(())
     }
 
     #[instrument(level = "trace", skip_all, err)]
     #[handler]
-    pub fn get_track_view_range(
+    pub fn ripple_resize_track_item(
         &mut self,
         view_id: TrackViewId,
-        start: Option<Time>,
-        end: Option<Time>,
-    ) -> Result<Vec<(TrackItemId, TrackViewItem)>> {
+        item_id: TrackItemId,
+        new_duration: Time,
+        mode: RippleMode,
+        force: bool,
+    ) -> Result<()> {
         self.hub.arrangements.ensure_has(view_id.arrangement_id)?;
-        self.hub.tracks.ensure_has(view_id.track_id)?;
+        self.ensure_track_item_unlocked(view_id.track_id, item_id, force)?;
 
-        let arrangement = &self.hub.arrangements[view_id.arrangement_id];
-        let tempo_map = &self.hub.tempo_maps[arrangement.tempo_map_id];
-        let view = self.track_view_cache.get_or_insert(&self.hub, view_id);
-        let range = view
-            .get_range(tempo_map, start, end)
-            .map(|(id, v)| (id, *v))
-            .collect();
-        Ok(range)
+        let item = self.get_track_item(view_id.track_id, item_id)?;
+        let tempo_map = self.arrangement_tempo_map(view_id.arrangement_id);
+        let pivot = tempo_map.to_real(item.start) + tempo_map.to_real(item.duration);
+
+        let shifts =
+            self.ripple_shifts(view_id, item_id, pivot, item.duration, new_duration, mode, force)?;
+
+        self.apply_track_item_resize(view_id.track_id, item_id, new_duration)?;
+
+        for (track_id, item_id, new_start) in shifts {
+            self.apply_track_item_move(track_id, item_id, new_start)?;
+        }
+
+        Ok(())
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn nudge_track_items(
+        &mut self,
+        arrangement_id: ArrangementId,
+        selection: Vec<(TrackId, TrackItemId)>,
+        delta: Time,
+        force: bool,
+    ) -> Result<()> {
+        self.hub.arrangements.ensure_has(arrangement_id)?;
+        let tempo_map = self.arrangement_tempo_map(arrangement_id);
+
+        // every item is checked before anything moves
+        let mut moves = Vec::with_capacity(selection.len());
+
+        for (track_id, item_id) in selection {
+            self.ensure_track_item_unlocked(track_id, item_id, force)?;
+
+            let item = self.get_track_item(track_id, item_id)?;
+            let new_start = shift_start(tempo_map, item.start, delta);
+
+            if tempo_map.to_real(new_start) < RealTime::ZERO {
+                bail!(
+                    ErrorKind::NotSupported,
+                    "nudge moves {item_id:?} before the start of the arrangement",
+                );
+            }
+
+            moves.push((track_id, item_id, new_start));
+        }
+
+        for (track_id, item_id, new_start) in moves {
+            self.apply_track_item_move(track_id, item_id, new_start)?;
+        }
+
+        Ok(())
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn move_track_items(
+        &mut self,
+        arrangement_id: ArrangementId,
+        selection: Vec<(TrackId, TrackItemId)>,
+        delta: Time,
+        track_delta: i32,
+        force: bool,
+    ) -> Result<Vec<(TrackId, TrackItemId)>> {
+        self.hub.arrangements.ensure_has(arrangement_id)?;
+        let track_ids = self.arrangement_tracks(arrangement_id);
+        let tempo_map = self.arrangement_tempo_map(arrangement_id);
+
+        // every item is checked before anything moves
+        let mut visited = HashSet::default();
+        let mut moves = Vec::with_capacity(selection.len());
+
+        for (track_id, item_id) in selection {
+            if !visited.insert((track_id, item_id)) {
+                continue;
+            }
+
+            self.ensure_track_item_unlocked(track_id, item_id, force)?;
+            let item = self.get_track_item(track_id, item_id)?;
+
+            let Some(track_idx) = track_ids.iter().position(|&id| id == track_id) else {
+                bail!(
+                    ErrorKind::InvalidId,
+                    "{track_id:?} isn't in {arrangement_id:?}"
+                );
+            };
+
+            let new_track_id = track_idx
+                .checked_add_signed(track_delta as isize)
+                .and_then(|idx| track_ids.get(idx).copied())
+                .ok_or_else(|| {
+                    format_err!(
+                        ErrorKind::NotSupported,
+                        "move puts {item_id:?} past the tracks of the arrangement",
+                    )
+                })?;
+
+            if !force && new_track_id != track_id && self.hub.tracks[new_track_id].locked {
+                bail!(ErrorKind::Locked, "{new_track_id:?} is locked");
+            }
+
+            let new_start = shift_start(tempo_map, item.start, delta);
+            if tempo_map.to_real(new_start) < RealTime::ZERO {
+                bail!(
+                    ErrorKind::NotSupported,
+                    "move puts {item_id:?} before the start of the arrangement",
+                );
+            }
+
+            moves.push((track_id, item_id, new_track_id, new_start));
+        }
+
+        let mut item_ids = Vec::with_capacity(moves.len());
+
+        for (track_id, item_id, new_track_id, new_start) in moves {
+            if new_track_id == track_id {
+                self.apply_track_item_move(track_id, item_id, new_start)?;
+                item_ids.push((track_id, item_id));
+                continue;
+            }
+
+            let item = self.get_track_item(track_id, item_id)?;
+            self.remove_track_item(track_id, item_id, true)?;
+
+            let new_item = TrackItem {
+                start: new_start,
+                ..item
+            };
+            item_ids.push((new_track_id, self.add_track_item(new_track_id, new_item)?));
+        }
+
+        Ok(item_ids)
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn delete_track_items(
+        &mut self,
+        selection: Vec<(TrackId, TrackItemId)>,
+        force: bool,
+    ) -> Result<()> {
+        // every item is checked before anything is removed
+        for &(track_id, item_id) in &selection {
+            self.ensure_track_item_unlocked(track_id, item_id, force)?;
+            self.get_track_item(track_id, item_id)?;
+        }
+
+        for (track_id, item_id) in selection {
+            self.remove_track_item(track_id, item_id, true)?;
+        }
+
+        Ok(())
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn copy_track_items(
+        &mut self,
+        arrangement_id: ArrangementId,
+        selection: Vec<(TrackId, TrackItemId)>,
+    ) -> Result<()> {
+        self.clipboard = Some(self.copy_selection(arrangement_id, &selection)?);
+        Ok(())
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn paste_track_items(
+        &mut self,
+        arrangement_id: ArrangementId,
+        target: PasteTarget,
+        force: bool,
+    ) -> Result<Vec<(TrackId, TrackItemId)>> {
+        let Some(clipboard) = self.clipboard.clone() else {
+            bail!(ErrorKind::NotSupported, "nothing to paste");
+        };
+
+        self.paste_clipboard(arrangement_id, clipboard, target, force)
     }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn duplicate_track_items(
+        &mut self,
+        arrangement_id: ArrangementId,
+        selection: Vec<(TrackId, TrackItemId)>,
+        force: bool,
+    ) -> Result<Vec<(TrackId, TrackItemId)>> {
+        let clipboard = self.copy_selection(arrangement_id, &selection)?;
+
+        let tempo_map = self.arrangement_tempo_map(arrangement_id);
+        let mut end = RealTime::ZERO;
+
+        for &(track_id, item_id) in &selection {
+            let item = self.get_track_item(track_id, item_id)?;
+            end = end.max(view_item(tempo_map, &item, None).real_end);
+        }
+
+        // copies go to the same tracks, so the target is the topmost selected one
+        let track_id = self
+            .arrangement_tracks(arrangement_id)
+            .into_iter()
+            .find(|&id| selection.iter().any(|&(track_id, _)| track_id == id))
+            .ok_or_else(|| format_err!(ErrorKind::NotSupported, "nothing is selected"))?;
+
+        let target = PasteTarget {
+            track_id,
+            offset: Time::Real(end),
+        };
+
+        self.paste_clipboard(arrangement_id, clipboard, target, force)
+    }
+
+    /// Copies the selected items along with their inner items, see `copy_track_items`.
+    fn copy_selection(
+        &self,
+        arrangement_id: ArrangementId,
+        selection: &[(TrackId, TrackItemId)],
+    ) -> Result<Clipboard> {
+        let document_id = self.hub.arrangements.get_key_or_err(arrangement_id)?.document_id;
+        let track_ids = self.arrangement_tracks(arrangement_id);
+        let tempo_map = self.arrangement_tempo_map(arrangement_id);
+
+        let mut copied = Vec::with_capacity(selection.len());
+
+        for &(track_id, item_id) in selection {
+            let Some(track_idx) = track_ids.iter().position(|&id| id == track_id) else {
+                bail!(
+                    ErrorKind::InvalidId,
+                    "{track_id:?} isn't in {arrangement_id:?}"
+                );
+            };
+
+            let item = self.get_track_item(track_id, item_id)?;
+            let inner = match item.inner {
+                ItemId::Audio(id) => {
+                    ClipboardInner::Audio(self.hub.audio_items.get_or_err(id)?.clone())
+                }
+                ItemId::Midi(id) => {
+                    ClipboardInner::Midi(self.hub.midi_items.get_or_err(id)?.clone())
+                }
+            };
+
+            copied.push((track_idx, item, inner, view_item(tempo_map, &item, None)));
+        }
+
+        let Some(top_idx) = copied.iter().map(|v| v.0).min() else {
+            bail!(ErrorKind::NotSupported, "nothing is selected");
+        };
+        let real_start = copied.iter().map(|v| v.3.real_start).min().unwrap_or(RealTime::ZERO);
+        let beat_start = copied.iter().map(|v| v.3.beat_start).min().unwrap_or(BeatTime::ZERO);
+
+        let items = copied
+            .into_iter()
+            .map(|(track_idx, item, inner, view_item)| ClipboardItem {
+                item,
+                inner,
+                track_offset: track_idx - top_idx,
+                real_offset: view_item.real_start - real_start,
+                beat_offset: view_item.beat_start - beat_start,
+            })
+            .collect();
+
+        Ok(Clipboard { document_id, items })
+    }
+
+    /// Adds copies of the clipboard items, see `paste_track_items`. Every target track is
+    /// checked before anything is added.
+    fn paste_clipboard(
+        &mut self,
+        arrangement_id: ArrangementId,
+        clipboard: Clipboard,
+        target: PasteTarget,
+        force: bool,
+    ) -> Result<Vec<(TrackId, TrackItemId)>> {
+        let document_id = self.hub.arrangements.get_key_or_err(arrangement_id)?.document_id;
+        if document_id != clipboard.document_id {
+            bail!(
+                ErrorKind::NotSupported,
+                "items can't be pasted into another document"
+            );
+        }
+
+        let track_ids = self.arrangement_tracks(arrangement_id);
+        let Some(target_idx) = track_ids.iter().position(|&id| id == target.track_id) else {
+            bail!(
+                ErrorKind::InvalidId,
+                "{:?} isn't in {arrangement_id:?}",
+                target.track_id,
+            );
+        };
+
+        let tempo_map = self.arrangement_tempo_map(arrangement_id);
+        let real_start = tempo_map.to_real(target.offset);
+        let beat_start = tempo_map.to_beat(target.offset);
+
+        if real_start < RealTime::ZERO {
+            bail!(
+                ErrorKind::NotSupported,
+                "paste starts before the start of the arrangement"
+            );
+        }
+
+        let mut pastes = Vec::with_capacity(clipboard.items.len());
+
+        for copied in clipboard.items {
+            let Some(&track_id) = track_ids.get(target_idx + copied.track_offset) else {
+                bail!(
+                    ErrorKind::NotSupported,
+                    "not enough tracks below {:?} to paste into",
+                    target.track_id,
+                );
+            };
+
+            if !force && self.hub.tracks.get_or_err(track_id)?.locked {
+                bail!(ErrorKind::Locked, "{track_id:?} is locked");
+            }
+
+            let start = match copied.item.start {
+                Time::Real(_) => Time::Real(real_start + copied.real_offset),
+                Time::Beat(_) => Time::Beat(beat_start + copied.beat_offset),
+            };
+
+            pastes.push((track_id, start, copied));
+        }
+
+        let mut item_ids = Vec::with_capacity(pastes.len());
+
+        for (track_id, start, copied) in pastes {
+            let key = ObjectKey::new_random(document_id);
+            let inner = match copied.inner {
+                ClipboardInner::Audio(item) => {
+                    ItemId::Audio(self.hub.audio_items.insert(key, item))
+                }
+                ClipboardInner::Midi(item) => {
+                    ItemId::Midi(self.hub.midi_items.insert(key, item))
+                }
+            };
+
+            let item = TrackItem {
+                inner,
+                start,
+                ..copied.item
+            };
+
+            item_ids.push((track_id, self.add_track_item(track_id, item)?));
+        }
+
+        Ok(item_ids)
+    }
+
+    /// Computes new starts of the items following a ripple edit. Items starting at or after
+    /// `pivot` are shifted by the difference between `old` and `new`. Nothing is changed, so
+    /// that a failing edit leaves every item in place.
+    #[allow(clippy::too_many_arguments)]
+    fn ripple_shifts(
+        &self,
+        view_id: TrackViewId,
+        item_id: TrackItemId,
+        pivot: RealTime,
+        old: Time,
+        new: Time,
+        mode: RippleMode,
+        force: bool,
+    ) -> Result<Vec<(TrackId, TrackItemId, Time)>> {
+        let track_ids = match mode {
+            RippleMode::Off => return Ok(Vec::new()),
+            RippleMode::Track => vec![view_id.track_id],
+            RippleMode::AllTracks => self.arrangement_tracks(view_id.arrangement_id),
+        };
+
+        let tempo_map = self.arrangement_tempo_map(view_id.arrangement_id);
+        let real_delta = tempo_map.to_real(new) - tempo_map.to_real(old);
+        let beat_delta = tempo_map.to_beat(new) - tempo_map.to_beat(old);
+
+        let mut shifts = Vec::new();
+
+        for track_id in track_ids {
+            let track = self.hub.tracks.get_or_err(track_id)?;
+
+            for (other_id, other) in &track.items {
+                let is_edited = track_id == view_id.track_id && other_id == item_id;
+                if is_edited || tempo_map.to_real(other.start) < pivot {
+                    continue;
+                }
+
+                if !force && (track.locked || other.locked) {
+                    bail!(ErrorKind::Locked, "{other_id:?} is locked in {track_id:?}");
+                }
+
+                let new_start = match other.start {
+                    Time::Real(t) => Time::Real(t + real_delta),
+                    Time::Beat(t) => Time::Beat(t + beat_delta),
+                };
+
+                if tempo_map.to_real(new_start) < RealTime::ZERO {
+                    bail!(
+                        ErrorKind::NotSupported,
+                        "ripple edit moves {other_id:?} before the start of the arrangement",
+                    );
+                }
+
+                shifts.push((track_id, other_id, new_start));
+            }
+        }
+
+        Ok(shifts)
+    }
+
+    fn arrangement_tempo_map(&self, arrangement_id: ArrangementId) -> &TempoMap {
+        let arrangement = &self.hub.arrangements[arrangement_id];
+        &self.hub.tempo_maps[arrangement.tempo_map_id]
+    }
+
+    /// Tells subscribers about the status of a track after any of its flags changed.
+    pub fn notify_track_status(&mut self, id: TrackId) {
+        if let Ok(status) = self.get_track_status(id) {
+            self.subscribers.track_status.notify(id, status);
+        }
+    }
+
+    /// Tracks reachable from the main track of the arrangement, in depth-first order.
+    pub fn arrangement_tracks(&self, arrangement_id: ArrangementId) -> Vec<TrackId> {
+        let main_track_id = self.hub.arrangements[arrangement_id].main_track_id;
+
+        let mut visited = HashSet::default();
+        let mut track_ids = Vec::new();
+        let mut stack = vec![main_track_id];
+
+        while let Some(track_id) = stack.pop() {
+            if !visited.insert(track_id) {
+                continue;
+            }
+
+            track_ids.push(track_id);
+
+            if let Some(track) = self.hub.tracks.get(track_id) {
+                stack.extend(track.links.children.iter().rev().copied());
+            }
+        }
+
+        track_ids
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn set_track_item_time_base(
+        &mut self,
+        view_id: TrackViewId,
+        item_id: TrackItemId,
+        time_base: TimeBase,
+        force: bool,
+    ) -> Result<()> {
+        self.hub.arrangements.ensure_has(view_id.arrangement_id)?;
+        self.ensure_track_item_unlocked(view_id.track_id, item_id, force)?;
+        self.convert_track_item_time_base(view_id, item_id, time_base)
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn set_track_time_base(
+        &mut self,
+        view_id: TrackViewId,
+        time_base: TimeBase,
+        force: bool,
+    ) -> Result<()> {
+        self.hub.arrangements.ensure_has(view_id.arrangement_id)?;
+
+        let track = self.hub.tracks.get_or_err(view_id.track_id)?;
+        let item_ids = track.items.keys().collect::<Vec<_>>();
+
+        for &item_id in &item_ids {
+            self.ensure_track_item_unlocked(view_id.track_id, item_id, force)?;
+        }
+
+        for item_id in item_ids {
+            self.convert_track_item_time_base(view_id, item_id, time_base)?;
+        }
+
+        Ok(())
+    }
+
+    fn convert_track_item_time_base(
+        &mut self,
+        view_id: TrackViewId,
+        item_id: TrackItemId,
+        time_base: TimeBase,
+    ) -> Result<()> {
+        let item = self.get_track_item(view_id.track_id, item_id)?;
+        let tempo_map = self.arrangement_tempo_map(view_id.arrangement_id);
+        let new_start = tempo_map.convert(item.start, time_base);
+        let new_duration = tempo_map.convert(item.duration, time_base);
+
+        if new_start != item.start {
+            self.apply_track_item_move(view_id.track_id, item_id, new_start)?;
+        }
+
+        if new_duration != item.duration {
+            self.apply_track_item_resize(view_id.track_id, item_id, new_duration)?;
+        }
+
+        Ok(())
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn split_track_item(
+        &mut self,
+        view_id: TrackViewId,
+        item_id: TrackItemId,
+        at: Time,
+        force: bool,
+    ) -> Result<TrackItemId> {
+        self.hub.arrangements.ensure_has(view_id.arrangement_id)?;
+        self.ensure_track_item_unlocked(view_id.track_id, item_id, force)?;
+
+        let item = self.get_track_item(view_id.track_id, item_id)?;
+        let ItemId::Audio(audio_item_id) = item.inner else {
+            bail!(ErrorKind::NotSupported, "only audio items can be split");
+        };
+
+        let tempo_map = self.arrangement_tempo_map(view_id.arrangement_id);
+        let cut = tempo_map.to_real(at) - tempo_map.to_real(item.start);
+        if cut <= RealTime::ZERO || cut >= tempo_map.to_real(item.duration) {
+            bail!(ErrorKind::NotSupported, "{at:?} isn't inside {item_id:?}");
+        }
+
+        let source_offset = self.hub.audio_items.get_or_err(audio_item_id)?.clip.source_offset;
+        let item_ids = self.slice_track_item(view_id, item_id, &[source_offset + cut])?;
+        Ok(item_ids[1])
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn trim_track_item(
+        &mut self,
+        view_id: TrackViewId,
+        item_id: TrackItemId,
+        new_start: Time,
+        new_end: Time,
+        force: bool,
+    ) -> Result<()> {
+        self.hub.arrangements.ensure_has(view_id.arrangement_id)?;
+        self.ensure_track_item_unlocked(view_id.track_id, item_id, force)?;
+
+        let item = self.get_track_item(view_id.track_id, item_id)?;
+        let tempo_map = self.arrangement_tempo_map(view_id.arrangement_id);
+        let new_real_start = tempo_map.to_real(new_start);
+        let new_real_end = tempo_map.to_real(new_end);
+
+        if new_real_start < RealTime::ZERO || new_real_end <= new_real_start {
+            bail!(
+                ErrorKind::NotSupported,
+                "invalid item range {new_start:?}..{new_end:?}"
+            );
+        }
+
+        // how far the start moves, which the source has to move by as well
+        let shift = new_real_start - tempo_map.to_real(item.start);
+        let new_real_duration = new_real_end - new_real_start;
+        let new_start = tempo_map.convert(new_start, item.start.time_base());
+        let new_duration =
+            tempo_map.convert(Time::Real(new_real_duration), item.duration.time_base());
+
+        if shift != RealTime::ZERO {
+            let ItemId::Audio(audio_item_id) = item.inner else {
+                bail!(ErrorKind::NotSupported, "only the end of MIDI items can be trimmed");
+            };
+
+            let audio_item = self.hub.audio_items.get_or_err(audio_item_id)?;
+            let source_offset = audio_item.clip.source_offset + shift;
+            if source_offset < RealTime::ZERO {
+                bail!(
+                    ErrorKind::NotSupported,
+                    "{item_id:?} can't start before its source"
+                );
+            }
+
+            let envelope = audio_item.gain_envelope.slice(shift, shift + new_real_duration);
+            self.set_audio_item_gain_envelope(audio_item_id, envelope)?;
+            self.update_audio_item_clip(audio_item_id, |clip| clip.source_offset = source_offset)?;
+        }
+
+        if new_start != item.start {
+            self.apply_track_item_move(view_id.track_id, item_id, new_start)?;
+        }
+
+        if new_duration != item.duration {
+            self.apply_track_item_resize(view_id.track_id, item_id, new_duration)?;
+        }
+
+        Ok(())
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn slice_item_at_transients(
+        &mut self,
+        responder: impl Responder<Vec<TrackItemId>, Error>,
+        cancel: CancellationToken,
+        view_id: TrackViewId,
+        item_id: TrackItemId,
+        sensitivity: f32,
+        force: bool,
+    ) -> Result<()> {
+        if !(0.0..=1.0).contains(&sensitivity) {
+            bail!(ErrorKind::NotSupported, "invalid sensitivity {sensitivity}");
+        }
+
+        self.hub.arrangements.ensure_has(view_id.arrangement_id)?;
+        self.ensure_track_item_unlocked(view_id.track_id, item_id, force)?;
+
+        let item = self.get_track_item(view_id.track_id, item_id)?;
+        let ItemId::Audio(audio_item_id) = item.inner else {
+            bail!(ErrorKind::NotSupported, "only audio items can be sliced");
+        };
+
+        let reader = self.open_audio_item_source(audio_item_id)?;
+
+        let queue = self.queue.clone();
+        self.spawn(TaskQueue::Analysis, async move {
+            let transients = detect_transients(reader, sensitivity, &cancel);
+
+            queue.defer(move |this: &mut Backend| {
+                // the item could have been changed while the source was analyzed
+                let res = transients.and_then(|transients| {
+                    this.hub.arrangements.ensure_has(view_id.arrangement_id)?;
+                    this.ensure_track_item_unlocked(view_id.track_id, item_id, force)?;
+                    this.slice_track_item(view_id, item_id, &transients)
+                });
+
+                this.engine_dirty = true;
+                responder.respond(res)
+            });
+
+            Ok(())
+        });
+
+        Ok(())
+    }
+
+    /// Splits an audio item at positions in its source, see `slice_item_at_transients`.
+    fn slice_track_item(
+        &mut self,
+        view_id: TrackViewId,
+        item_id: TrackItemId,
+        positions: &[RealTime],
+    ) -> Result<Vec<TrackItemId>> {
+        let item = self.get_track_item(view_id.track_id, item_id)?;
+        let ItemId::Audio(audio_item_id) = item.inner else {
+            bail!(ErrorKind::NotSupported, "only audio items can be sliced");
+        };
+
+        let audio_item = self.hub.audio_items.get_or_err(audio_item_id)?.clone();
+        let document_id = self.hub.audio_items.get_key_or_err(audio_item_id)?.document_id;
+
+        let tempo_map = self.arrangement_tempo_map(view_id.arrangement_id);
+        let real_start = tempo_map.to_real(item.start);
+        let real_duration = tempo_map.to_real(item.duration);
+
+        // cuts relative to the start of the item
+        let mut cuts = positions
+            .iter()
+            .map(|&v| v - audio_item.clip.source_offset)
+            .filter(|&v| v > RealTime::ZERO && v < real_duration)
+            .collect::<Vec<_>>();
+        cuts.sort();
+        cuts.dedup();
+
+        if cuts.is_empty() {
+            return Ok(vec![item_id]);
+        }
+
+        let starts = std::iter::once(RealTime::ZERO).chain(cuts.iter().copied());
+        let ends = cuts.iter().copied().chain(std::iter::once(real_duration));
+        let slices = starts
+            .zip(ends)
+            .map(|(start, end)| {
+                let new_start =
+                    tempo_map.convert(Time::Real(real_start + start), item.start.time_base());
+                let new_duration =
+                    tempo_map.convert(Time::Real(end - start), item.duration.time_base());
+                (start, end, new_start, new_duration)
+            })
+            .collect::<Vec<_>>();
+
+        let mut item_ids = vec![item_id];
+
+        for (i, &(start, end, new_start, new_duration)) in slices.iter().enumerate().skip(1) {
+            let is_last = i + 1 == slices.len();
+            let clip = AudioClip {
+                source_offset: audio_item.clip.source_offset + start,
+                fade_in: Fade::default(),
+                fade_out: if is_last {
+                    audio_item.clip.fade_out
+                } else {
+                    Fade::default()
+                },
+                ..audio_item.clip
+            };
+
+            let slice = AudioItem {
+                source_id: audio_item.source_id,
+                gain_envelope: audio_item.gain_envelope.slice(start, end),
+                clip,
+            };
+
+            let slice_id = self
+                .hub
+                .audio_items
+                .insert(ObjectKey::new_random(document_id), slice);
+
+            let new_item = TrackItem {
+                inner: ItemId::Audio(slice_id),
+                start: new_start,
+                duration: new_duration,
+                ..item
+            };
+
+            item_ids.push(self.add_track_item(view_id.track_id, new_item)?);
+        }
+
+        // the original item becomes the first slice
+        let (_, first_end, _, first_duration) = slices[0];
+        let envelope = audio_item.gain_envelope.slice(RealTime::ZERO, first_end);
+        self.set_audio_item_gain_envelope(audio_item_id, envelope)?;
+        self.update_audio_item_clip(audio_item_id, |clip| clip.fade_out = Fade::default())?;
+        self.apply_track_item_resize(view_id.track_id, item_id, first_duration)?;
+
+        Ok(item_ids)
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn move_track_item_to_lane(
+        &mut self,
+        track_id: TrackId,
+        item_id: TrackItemId,
+        new_lane: u32,
+        force: bool,
+    ) -> Result<()> {
+        if new_lane > MAX_TRACK_LANE {
+            bail!(
+                ErrorKind::NotSupported,
+                "lane {new_lane} is above the maximum of {MAX_TRACK_LANE}",
+            );
+        }
+
+        self.ensure_track_item_unlocked(track_id, item_id, force)?;
+
+        let track = self.hub.tracks.get_mut_or_err(track_id)?;
+        let item = track.items.get_mut(item_id).ok_or_else(|| {
+            format_err!(
+                ErrorKind::InvalidId,
+                "{item_id:?} doesn't exist in {track_id:?}",
+            )
+        })?;
+
+        item.lane = new_lane;
+
+        for (view_id, view) in self.track_view_cache.iter_mut(track_id) {
+            let arrangement = &self.hub.arrangements[view_id.arrangement_id];
+            let tempo_map = &self.hub.tempo_maps[arrangement.tempo_map_id];
+            view.move_item_to_lane(item_id, new_lane);
+            let item = view.get_item(item_id).copied();
+            let event = TrackViewEvent::ItemLaneChanged {
+                id: item_id,
+                new_lane,
+            };
+            self.subscribers.track_view.notify_filtered(view_id, event, |filter| {
+                filter_intersects(tempo_map, filter, &item)
+            });
+        }
+
+        Ok(())
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn set_track_item_locked(
+        &mut self,
+        track_id: TrackId,
+        item_id: TrackItemId,
+        locked: bool,
+    ) -> Result<()> {
+        let track = self.hub.tracks.get_mut_or_err(track_id)?;
+        let item = track.items.get_mut(item_id).ok_or_else(|| {
+            format_err!(
+                ErrorKind::InvalidId,
+                "{item_id:?} doesn't exist in {track_id:?}",
+            )
+        })?;
+
+        item.locked = locked;
+
+        for (view_id, view) in self.track_view_cache.iter_mut(track_id) {
+            let arrangement = &self.hub.arrangements[view_id.arrangement_id];
+            let tempo_map = &self.hub.tempo_maps[arrangement.tempo_map_id];
+            view.lock_item(item_id, locked);
+            let item = view.get_item(item_id).copied();
+            let event = TrackViewEvent::ItemLocked {
+                id: item_id,
+                locked,
+            };
+            self.subscribers.track_view.notify_filtered(view_id, event, |filter| {
+                filter_intersects(tempo_map, filter, &item)
+            });
+        }
+
+        Ok(())
+    }
+
+    fn ensure_track_item_unlocked(
+        &self,
+        track_id: TrackId,
+        item_id: TrackItemId,
+        force: bool,
+    ) -> Result<()> {
+        if force {
+            return Ok(());
+        }
+
+        let track = self.hub.tracks.get_or_err(track_id)?;
+
+        if track.locked {
+            bail!(ErrorKind::Locked, "{track_id:?} is locked");
+        }
+
+        if track.items.get(item_id).is_some_and(|item| item.locked) {
+            bail!(ErrorKind::Locked, "{item_id:?} is locked in {track_id:?}");
+        }
+
+        Ok(())
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn get_track_view_item(
+        &mut self,
+        view_id: TrackViewId,
+        item_id: TrackItemId,
+    ) -> Result<TrackViewItem> {
+        self.hub.arrangements.ensure_has(view_id.arrangement_id)?;
+        self.hub.tracks.ensure_has(view_id.track_id)?;
+
+        let view = self.track_view_cache.get_or_insert(&self.hub, &self.subscribers, view_id);
+        view.get_item(item_id).copied().ok_or_else(|| {
+            format_err!(
+                ErrorKind::InvalidId,
+                "{item_id:?} doesn't exist in {:?}",
+                view_id.track_id,
+            )
+        })
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn get_track_view_lane_count(&mut self, view_id: TrackViewId) -> Result<u32> {
+        self.hub.arrangements.ensure_has(view_id.arrangement_id)?;
+        self.hub.tracks.ensure_has(view_id.track_id)?;
+
+        let view = self.track_view_cache.get_or_insert(&self.hub, &self.subscribers, view_id);
+        Ok(view.lane_count())
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn get_track_view_range(
+        &mut self,
+        view_id: TrackViewId,
+        start: Option<Time>,
+        end: Option<Time>,
+    ) -> Result<Vec<(TrackItemId, TrackViewItem)>> {
+        self.hub.arrangements.ensure_has(view_id.arrangement_id)?;
+        self.hub.tracks.ensure_has(view_id.track_id)?;
+
+        let arrangement = &self.hub.arrangements[view_id.arrangement_id];
+        let tempo_map = &self.hub.tempo_maps[arrangement.tempo_map_id];
+        let view = self.track_view_cache.get_or_insert(&self.hub, &self.subscribers, view_id);
+        let range = view
+            .get_range(tempo_map, start, end)
+            .map(|(id, v)| (id, *v))
+            .collect();
+        Ok(range)
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn get_track_nodes(&self, id: TrackId) -> Result<Vec<NodeId>> {
+        let track = self.hub.tracks.get_or_err(id)?;
+        Ok(track.nodes.clone())
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn insert_track_node(&mut self, id: TrackId, node_id: NodeId, index: usize) -> Result<()> {
+        self.hub.nodes.ensure_has(node_id)?;
+
+        let track = self.hub.tracks.get_mut_or_err(id)?;
+
+        if index > track.nodes.len() {
+            bail!(
+                ErrorKind::IndexOutOfBounds,
+                "index out of bounds passed to insert_track_node",
+            );
+        }
+
+        if track.nodes.contains(&node_id) {
+            bail!(
+                ErrorKind::NotSupported,
+                "{node_id:?} is already inserted into {id:?}",
+            );
+        }
+
+        track.nodes.insert(index, node_id);
+
+        Ok(())
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn remove_track_node(&mut self, id: TrackId, index: usize) -> Result<()> {
+        let track = self.hub.tracks.get_mut_or_err(id)?;
+
+        if index >= track.nodes.len() {
+            bail!(
+                ErrorKind::IndexOutOfBounds,
+                "index out of bounds passed to remove_track_node",
+            );
+        }
+
+        let node_id = track.nodes.remove(index);
+
+        // the plugin is shut down once its node isn't on any track
+        let is_used = self
+            .hub
+            .tracks
+            .iter()
+            .any(|(_, _, track)| track.nodes.contains(&node_id));
+        if !is_used {
+            self.plugins.remove_node(node_id);
+        }
+
+        Ok(())
+    }
+}
+
+/// Sends the event to filtered subscribers which see the item both before and after the change.
+/// The ones which only see one side of it get the item removed or added instead.
+fn notify_item_change(
+    subscribers: &mut SubscribersHub,
+    tempo_map: &TempoMap,
+    view_id: TrackViewId,
+    item_id: TrackItemId,
+    (old_item, new_item): (&TrackViewItem, &TrackViewItem),
+    event: TrackViewEvent,
+) {
+    let sees_old = |filter: &TrackViewFilter| filter_intersects(tempo_map, filter, [old_item]);
+    let sees_new = |filter: &TrackViewFilter| filter_intersects(tempo_map, filter, [new_item]);
+
+    subscribers.track_view.notify_filtered(view_id, event, |filter| {
+        sees_old(filter) && sees_new(filter)
+    });
+
+    let event = TrackViewEvent::ItemRemoved { id: item_id };
+    subscribers.track_view.notify_filtered(view_id, event, |filter| {
+        sees_old(filter) && !sees_new(filter)
+    });
+
+    let event = TrackViewEvent::ItemAdded {
+        id: item_id,
+        item: *new_item,
+    };
+    subscribers.track_view.notify_filtered(view_id, event, |filter| {
+        !sees_old(filter) && sees_new(filter)
+    });
+}
+
+/// Shifts the start of an item by `delta`, keeping the time base of the item. See
+/// `nudge_track_items` for how deltas in the other time base are applied.
+fn shift_start(tempo_map: &TempoMap, start: Time, delta: Time) -> Time {
+    let new_start = match delta {
+        Time::Real(delta) => Time::Real(tempo_map.to_real(start) + delta),
+        Time::Beat(delta) => Time::Beat(tempo_map.to_beat(start) + delta),
+    };
+
+    tempo_map.convert(new_start, start.time_base())
 }
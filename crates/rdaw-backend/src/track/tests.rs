@@ -2,11 +2,20 @@ use futures::StreamExt;
 use rand::rngs::SmallRng;
 use rand::seq::SliceRandom;
 use rand::{Rng, SeedableRng};
-use rdaw_api::document::DocumentOperations;
-use rdaw_api::track::{TrackHierarchyEvent, TrackNode, TrackOperations};
-use rdaw_api::{assert_err, ErrorKind, Result};
-
-use crate::tests::{invalid_track_id, run_test};
+use rdaw_api::arrangement::ArrangementOperations;
+use rdaw_api::document::{DocumentId, DocumentOperations};
+use rdaw_api::item::{AudioItemId, ItemId, MidiItemOperations};
+use rdaw_api::recording::RecordingOperations;
+use rdaw_api::time::{BeatTime, Time, TimeBase};
+use rdaw_api::track::{
+    PasteTarget, RippleMode, TrackHierarchy, TrackHierarchyEvent, TrackItem, TrackMix, TrackNode,
+    TrackOperations, TrackStatus, TrackSummary, TrackViewEvent, TrackViewFilter, TrackViewId,
+    MAX_TRACK_LANE,
+};
+use rdaw_api::{assert_err, Error, ErrorKind, PageRequest, Result};
+use rdaw_core::time::RealTime;
+
+use crate::tests::{beats, invalid_track_id, run_test, ProjectBuilder};
 
 #[test]
 fn subscribe_track_name() -> Result<()> {
@@ -52,28 +61,66 @@ fn subscribe_track_hierarchy() -> Result<()> {
 
         assert_eq!(
             stream.next().await,
-            Some(TrackHierarchyEvent::ChildrenChanged {
+            Some(TrackHierarchyEvent::ChildInserted {
+                id: root,
+                index: 0,
+                child: child1,
+            })
+        );
+
+        assert_eq!(
+            stream.next().await,
+            Some(TrackHierarchyEvent::ChildInserted {
                 id: root,
-                new_children: vec![child1].into()
+                index: 1,
+                child: child2,
+            })
+        );
+
+        assert_eq!(
+            stream.next().await,
+            Some(TrackHierarchyEvent::ChildInserted {
+                id: child1,
+                index: 0,
+                child: grandchild,
             })
         );
 
+        client.move_track(root, 0, root, 1).await?;
+        client.move_track(child1, 0, child2, 0).await?;
+        client.remove_track_child(root, 0).await?;
+
         assert_eq!(
             stream.next().await,
-            Some(TrackHierarchyEvent::ChildrenChanged {
+            Some(TrackHierarchyEvent::ChildMoved {
                 id: root,
-                new_children: vec![child1, child2].into()
+                from: 0,
+                to: 1,
             })
         );
 
         assert_eq!(
             stream.next().await,
-            Some(TrackHierarchyEvent::ChildrenChanged {
+            Some(TrackHierarchyEvent::ChildRemoved {
                 id: child1,
-                new_children: vec![grandchild].into()
+                index: 0,
+            })
+        );
+
+        assert_eq!(
+            stream.next().await,
+            Some(TrackHierarchyEvent::ChildInserted {
+                id: child2,
+                index: 0,
+                child: grandchild,
             })
         );
 
+        assert_eq!(
+            stream.next().await,
+            Some(TrackHierarchyEvent::ChildRemoved { id: root, index: 0 })
+        );
+
         Ok(())
     })
 }
@@ -84,6 +131,132 @@ fn subscribe_track_view() -> Result<()> {
     todo!()
 }
 
+#[test]
+fn subscribe_track_view_filtered() -> Result<()> {
+    run_test(|client| async move {
+        let document_id = client.create_document().await?;
+        let arrangement_id = client.get_document_arrangement(document_id).await?;
+        let track_id = client.create_track(document_id).await?;
+
+        let view_id = TrackViewId {
+            track_id,
+            arrangement_id,
+        };
+
+        let beats = |v| Time::Beat(BeatTime::from_beats(v));
+        let item = |start| TrackItem {
+            inner: ItemId::Audio(AudioItemId::default()),
+            start: beats(start),
+            duration: beats(1),
+            lane: 0,
+            locked: false,
+        };
+
+        let filter = TrackViewFilter::range(beats(0), beats(4));
+        let mut stream = client.subscribe_track_view(view_id, filter).await?;
+
+        client.add_track_item(track_id, item(8)).await?;
+        let item_id = client.add_track_item(track_id, item(1)).await?;
+
+        match stream.next().await {
+            Some(TrackViewEvent::ItemAdded { id, .. }) => assert_eq!(id, item_id),
+            event => panic!("unexpected event: {event:?}"),
+        }
+
+        Ok(())
+    })
+}
+
+#[test]
+fn subscribe_track_view_filtered_move() -> Result<()> {
+    run_test(|client| async move {
+        let project = ProjectBuilder::new()
+            .track("Drums", |t| t.item(beats(8), beats(1)))
+            .build(&client)
+            .await?;
+
+        let track_id = project.track("Drums");
+        let item_id = project.items("Drums")[0];
+
+        let filter = TrackViewFilter::range(beats(0), beats(4));
+        let mut stream = client
+            .subscribe_track_view(project.view("Drums"), filter)
+            .await?;
+
+        // the subscriber hasn't seen the item yet
+        client
+            .move_track_item(track_id, item_id, beats(2), false)
+            .await?;
+        match stream.next().await {
+            Some(TrackViewEvent::ItemAdded { id, item }) => {
+                assert_eq!(id, item_id);
+                assert_eq!(item.start, beats(2));
+            }
+            event => panic!("unexpected event: {event:?}"),
+        }
+
+        client
+            .move_track_item(track_id, item_id, beats(1), false)
+            .await?;
+        match stream.next().await {
+            Some(TrackViewEvent::ItemMoved { id, new_start, .. }) => {
+                assert_eq!(id, item_id);
+                assert_eq!(new_start, beats(1));
+            }
+            event => panic!("unexpected event: {event:?}"),
+        }
+
+        client
+            .move_track_item(track_id, item_id, beats(12), false)
+            .await?;
+        match stream.next().await {
+            Some(TrackViewEvent::ItemRemoved { id }) => assert_eq!(id, item_id),
+            event => panic!("unexpected event: {event:?}"),
+        }
+
+        Ok(())
+    })
+}
+
+#[test]
+fn subscribe_track_view_filtered_resize() -> Result<()> {
+    run_test(|client| async move {
+        let project = ProjectBuilder::new()
+            .track("Drums", |t| t.item(beats(0), beats(1)))
+            .build(&client)
+            .await?;
+
+        let track_id = project.track("Drums");
+        let item_id = project.items("Drums")[0];
+
+        let filter = TrackViewFilter::range(beats(4), beats(8));
+        let mut stream = client
+            .subscribe_track_view(project.view("Drums"), filter)
+            .await?;
+
+        client
+            .resize_track_item(track_id, item_id, beats(6), false)
+            .await?;
+        match stream.next().await {
+            Some(TrackViewEvent::ItemAdded { id, item }) => {
+                assert_eq!(id, item_id);
+                assert_eq!(item.duration, beats(6));
+            }
+            event => panic!("unexpected event: {event:?}"),
+        }
+
+        client
+            .resize_track_item(track_id, item_id, beats(1), false)
+            .await?;
+        match stream.next().await {
+            Some(TrackViewEvent::ItemRemoved { id }) => assert_eq!(id, item_id),
+            event => panic!("unexpected event: {event:?}"),
+        }
+
+        Ok(())
+    })
+}
+
 #[test]
 fn get_set_track_name() -> Result<()> {
     run_test(|client| async move {
@@ -343,6 +516,10 @@ fn move_track_randomized() -> Result<()> {
             client.append_track_child(root, child).await?;
         }
 
+        // keep a local copy of the hierarchy up to date using the incremental events
+        let mut local = client.get_track_hierarchy(root).await?;
+        let mut stream = client.subscribe_track_hierarchy(root).await?;
+
         for _ in 0..NUM_ITERATIONS {
             let old_parent_id = *tracks.choose(&mut rng).unwrap();
             let old_parent_children = client.get_track_children(old_parent_id).await?;
@@ -381,11 +558,25 @@ fn move_track_randomized() -> Result<()> {
 
             if is_recursive {
                 assert_err!(res, ErrorKind::NotSupported);
-            } else {
-                res?;
+                continue;
+            }
+
+            res?;
+
+            let num_events = if old_parent_id == new_parent_id { 1 } else { 2 };
+            for _ in 0..num_events {
+                local.apply_event(stream.next().await.unwrap());
             }
         }
 
+        let dfs = |hierarchy: &TrackHierarchy| {
+            let mut nodes = Vec::new();
+            hierarchy.dfs(root, |node| nodes.push(node));
+            nodes
+        };
+
+        assert_eq!(dfs(&local), dfs(&client.get_track_hierarchy(root).await?));
+
         Ok(())
     })
 }
@@ -432,13 +623,868 @@ fn resize_track_item() -> Result<()> {
     todo!()
 }
 
+#[test]
+fn locked_track_item() -> Result<()> {
+    run_test(|client| async move {
+        let document_id = client.create_document().await?;
+        let track_id = client.create_track(document_id).await?;
+
+        let beats = |v| Time::Beat(BeatTime::from_beats(v));
+        let item = TrackItem {
+            inner: ItemId::Audio(AudioItemId::default()),
+            start: beats(0),
+            duration: beats(1),
+            lane: 0,
+            locked: false,
+        };
+
+        let item_id = client.add_track_item(track_id, item).await?;
+        client.set_track_item_locked(track_id, item_id, true).await?;
+        assert!(client.get_track_item(track_id, item_id).await?.locked);
+
+        assert_err!(
+            client.move_track_item(track_id, item_id, beats(2), false).await,
+            ErrorKind::Locked,
+        );
+        assert_err!(
+            client.resize_track_item(track_id, item_id, beats(2), false).await,
+            ErrorKind::Locked,
+        );
+        assert_err!(
+            client.remove_track_item(track_id, item_id, false).await,
+            ErrorKind::Locked,
+        );
+
+        client.move_track_item(track_id, item_id, beats(2), true).await?;
+        assert_eq!(client.get_track_item(track_id, item_id).await?.start, beats(2));
+
+        client.set_track_item_locked(track_id, item_id, false).await?;
+        client.remove_track_item(track_id, item_id, false).await?;
+
+        Ok(())
+    })
+}
+
+#[test]
+fn ripple_move_track_item() -> Result<()> {
+    run_test(|client| async move {
+        let project = ProjectBuilder::new()
+            .track("First", |t| {
+                t.item(beats(0), beats(2))
+                    .item(beats(4), beats(2))
+                    .item(beats(8), beats(2))
+            })
+            .track("Second", |t| t.item(beats(1), beats(2)).item(beats(6), beats(2)))
+            .build(&client)
+            .await?;
+
+        let starts = |name: &'static str| {
+            let client = client.clone();
+            let items = project.items(name).to_vec();
+            let track_id = project.track(name);
+            async move {
+                let mut starts = Vec::new();
+                for item_id in items {
+                    starts.push(client.get_track_item(track_id, item_id).await?.start);
+                }
+                Ok::<_, Error>(starts)
+            }
+        };
+
+        let view_id = project.view("First");
+        let first = project.items("First");
+
+        client
+            .ripple_move_track_item(view_id, first[1], beats(5), RippleMode::Track, false)
+            .await?;
+        assert_eq!(starts("First").await?, [beats(0), beats(5), beats(9)]);
+        assert_eq!(starts("Second").await?, [beats(1), beats(6)]);
+
+        client
+            .ripple_move_track_item(view_id, first[1], beats(3), RippleMode::AllTracks, false)
+            .await?;
+        assert_eq!(starts("First").await?, [beats(0), beats(3), beats(7)]);
+        assert_eq!(starts("Second").await?, [beats(1), beats(4)]);
+
+        client
+            .ripple_move_track_item(view_id, first[1], beats(4), RippleMode::Off, false)
+            .await?;
+        assert_eq!(starts("First").await?, [beats(0), beats(4), beats(7)]);
+
+        assert_err!(
+            client
+                .ripple_move_track_item(view_id, first[1], beats(-4), RippleMode::Track, false)
+                .await,
+            ErrorKind::NotSupported,
+        );
+        assert_eq!(starts("First").await?, [beats(0), beats(4), beats(7)]);
+
+        Ok(())
+    })
+}
+
+#[test]
+fn ripple_resize_track_item() -> Result<()> {
+    run_test(|client| async move {
+        let project = ProjectBuilder::new()
+            .track("First", |t| t.item(beats(0), beats(2)).item(beats(2), beats(2)))
+            .track("Second", |t| t.item(beats(4), beats(2)).locked())
+            .build(&client)
+            .await?;
+
+        let view_id = project.view("First");
+        let first = project.items("First");
+        let second = project.items("Second");
+
+        client
+            .ripple_resize_track_item(view_id, first[0], beats(3), RippleMode::Track, false)
+            .await?;
+        assert_eq!(client.get_track_item(view_id.track_id, first[0]).await?.duration, beats(3));
+        assert_eq!(client.get_track_item(view_id.track_id, first[1]).await?.start, beats(3));
+
+        // nothing changes if any of the shifted items is locked
+        let mode = RippleMode::AllTracks;
+        assert_err!(
+            client
+                .ripple_resize_track_item(view_id, first[0], beats(4), mode, false)
+                .await,
+            ErrorKind::Locked,
+        );
+        assert_eq!(client.get_track_item(view_id.track_id, first[0]).await?.duration, beats(3));
+
+        client
+            .ripple_resize_track_item(view_id, first[0], beats(4), mode, true)
+            .await?;
+        assert_eq!(client.get_track_item(view_id.track_id, first[1]).await?.start, beats(4));
+        assert_eq!(
+            client.get_track_item(project.track("Second"), second[0]).await?.start,
+            beats(5)
+        );
+
+        Ok(())
+    })
+}
+
+#[test]
+fn nudge_track_items() -> Result<()> {
+    run_test(|client| async move {
+        let project = ProjectBuilder::new()
+            .tempo(60.0)
+            .track("First", |t| t.item(beats(0), beats(2)).item(beats(4), beats(2)))
+            .track("Second", |t| t.item(beats(1), beats(2)).locked())
+            .build(&client)
+            .await?;
+
+        let secs = RealTime::from_secs_f64;
+        let first_id = project.track("First");
+        let first = project.items("First");
+        let second = (project.track("Second"), project.items("Second")[0]);
+
+        client
+            .set_track_item_time_base(project.view("First"), first[1], TimeBase::Absolute, false)
+            .await?;
+
+        let selection = vec![(first_id, first[0]), (first_id, first[1])];
+        let start = |item_id| {
+            let client = client.clone();
+            async move { Ok::<_, Error>(client.get_track_item(first_id, item_id).await?.start) }
+        };
+
+        let arrangement_id = project.arrangement_id;
+        client
+            .nudge_track_items(arrangement_id, selection.clone(), beats(1), false)
+            .await?;
+        assert_eq!(start(first[0]).await?, beats(1));
+        assert_eq!(start(first[1]).await?, Time::Real(secs(5.0)));
+
+        let delta = Time::Real(secs(-0.5));
+        client
+            .nudge_track_items(arrangement_id, selection.clone(), delta, false)
+            .await?;
+        assert_eq!(start(first[0]).await?, Time::Beat(BeatTime::from_beats_f32(0.5)));
+        assert_eq!(start(first[1]).await?, Time::Real(secs(4.5)));
+
+        // nothing moves if any of the items can't
+        assert_err!(
+            client
+                .nudge_track_items(arrangement_id, selection.clone(), beats(-1), false)
+                .await,
+            ErrorKind::NotSupported,
+        );
+        assert_eq!(start(first[0]).await?, Time::Beat(BeatTime::from_beats_f32(0.5)));
+
+        let with_locked = vec![(first_id, first[0]), second];
+        assert_err!(
+            client
+                .nudge_track_items(arrangement_id, with_locked.clone(), beats(1), false)
+                .await,
+            ErrorKind::Locked,
+        );
+        assert_eq!(start(first[0]).await?, Time::Beat(BeatTime::from_beats_f32(0.5)));
+
+        client
+            .nudge_track_items(arrangement_id, with_locked, beats(1), true)
+            .await?;
+        assert_eq!(client.get_track_item(second.0, second.1).await?.start, beats(2));
+
+        Ok(())
+    })
+}
+
+#[test]
+fn move_track_items() -> Result<()> {
+    run_test(|client| async move {
+        let project = ProjectBuilder::new()
+            .tempo(60.0)
+            .track("First", |t| t.item(beats(0), beats(2)).item(beats(4), beats(2)))
+            .track("Second", |t| t.item(beats(1), beats(2)))
+            .track("Third", |t| t.locked())
+            .build(&client)
+            .await?;
+
+        let arrangement_id = project.arrangement_id;
+        let (first, second, third) = (
+            project.track("First"),
+            project.track("Second"),
+            project.track("Third"),
+        );
+        let first_items = project.items("First");
+        let second_item = project.items("Second")[0];
+
+        let selection = vec![(first, first_items[0]), (first, first_items[1])];
+        let moved = client
+            .move_track_items(arrangement_id, selection.clone(), beats(1), 0, false)
+            .await?;
+        assert_eq!(moved, selection);
+        assert_eq!(client.get_track_item(first, first_items[0]).await?.start, beats(1));
+        assert_eq!(client.get_track_item(first, first_items[1]).await?.start, beats(5));
+
+        // nothing moves if any of the items can't
+        let selection = vec![(first, first_items[0]), (second, second_item)];
+        assert_err!(
+            client
+                .move_track_items(arrangement_id, selection.clone(), beats(0), 1, false)
+                .await,
+            ErrorKind::Locked,
+        );
+        assert_err!(
+            client
+                .move_track_items(arrangement_id, selection.clone(), beats(0), 3, false)
+                .await,
+            ErrorKind::NotSupported,
+        );
+        assert_err!(
+            client
+                .move_track_items(arrangement_id, selection.clone(), beats(-2), 0, false)
+                .await,
+            ErrorKind::NotSupported,
+        );
+        assert_eq!(client.get_track_item(first, first_items[0]).await?.start, beats(1));
+
+        // items moved to other tracks get new ids there
+        let moved = client
+            .move_track_items(arrangement_id, selection, beats(1), 1, true)
+            .await?;
+        assert_eq!((moved[0].0, moved[1].0), (second, third));
+        assert_eq!(client.get_track_item(moved[0].0, moved[0].1).await?.start, beats(2));
+        assert_eq!(client.get_track_item(moved[1].0, moved[1].1).await?.start, beats(2));
+        assert_err!(
+            client.get_track_item(first, first_items[0]).await,
+            ErrorKind::InvalidId,
+        );
+
+        Ok(())
+    })
+}
+
+#[test]
+fn delete_track_items() -> Result<()> {
+    run_test(|client| async move {
+        let project = ProjectBuilder::new()
+            .track("First", |t| t.item(beats(0), beats(2)).item(beats(4), beats(2)))
+            .track("Second", |t| t.item(beats(1), beats(2)).locked())
+            .build(&client)
+            .await?;
+
+        let first = project.track("First");
+        let first_items = project.items("First");
+        let second = (project.track("Second"), project.items("Second")[0]);
+
+        // nothing is removed if any of the items can't be
+        let with_locked = vec![(first, first_items[0]), second];
+        assert_err!(
+            client.delete_track_items(with_locked, false).await,
+            ErrorKind::Locked,
+        );
+        client.get_track_item(first, first_items[0]).await?;
+
+        let selection = vec![(first, first_items[0]), (first, first_items[1])];
+        client.delete_track_items(selection.clone(), false).await?;
+        for (track_id, item_id) in selection.clone() {
+            assert_err!(
+                client.get_track_item(track_id, item_id).await,
+                ErrorKind::InvalidId,
+            );
+        }
+
+        assert_err!(
+            client.delete_track_items(selection, false).await,
+            ErrorKind::InvalidId,
+        );
+
+        client.delete_track_items(vec![second], true).await?;
+
+        Ok(())
+    })
+}
+
+#[test]
+fn clipboard() -> Result<()> {
+    run_test(|client| async move {
+        let project = ProjectBuilder::new()
+            .tempo(60.0)
+            .track("First", |t| t)
+            .track("Second", |t| t)
+            .track("Third", |t| t.locked())
+            .build(&client)
+            .await?;
+
+        let secs = RealTime::from_secs_f64;
+        let arrangement_id = project.arrangement_id;
+        let first = project.track("First");
+        let second = project.track("Second");
+        let third = project.track("Third");
+
+        let first_item = client.add_midi_clip(first, beats(2), beats(2)).await?;
+        let second_item = client.add_midi_clip(second, beats(3), beats(1)).await?;
+        let absolute = TimeBase::Absolute;
+        client
+            .set_track_item_time_base(project.view("Second"), second_item, absolute, false)
+            .await?;
+
+        let target = |track_id, offset| PasteTarget { track_id, offset };
+
+        assert_err!(
+            client
+                .paste_track_items(arrangement_id, target(first, beats(0)), false)
+                .await,
+            ErrorKind::NotSupported,
+        );
+        assert_err!(
+            client.copy_track_items(arrangement_id, Vec::new()).await,
+            ErrorKind::NotSupported,
+        );
+
+        let selection = vec![(first, first_item), (second, second_item)];
+        client
+            .copy_track_items(arrangement_id, selection.clone())
+            .await?;
+
+        // pasted items keep their distance, in the time base they're positioned in
+        let pasted = client
+            .paste_track_items(arrangement_id, target(first, beats(8)), false)
+            .await?;
+        assert_eq!(pasted.len(), 2);
+        assert_eq!((pasted[0].0, pasted[1].0), (first, second));
+
+        let item = client.get_track_item(pasted[0].0, pasted[0].1).await?;
+        assert_eq!((item.start, item.duration), (beats(8), beats(2)));
+        let copy = client.get_track_item(pasted[1].0, pasted[1].1).await?;
+        assert_eq!(copy.start, Time::Real(secs(9.0)));
+
+        // copies have items of their own, playing the same sources
+        let original = client.get_track_item(first, first_item).await?;
+        assert_ne!(item.inner, original.inner);
+        let (ItemId::Midi(item_id), ItemId::Midi(original_id)) = (item.inner, original.inner) else {
+            panic!("expected midi items");
+        };
+        assert_eq!(
+            client.get_midi_item_source(item_id).await?,
+            client.get_midi_item_source(original_id).await?,
+        );
+
+        // the clipboard outlives the originals
+        client.remove_track_item(first, first_item, false).await?;
+
+        assert_err!(
+            client
+                .paste_track_items(arrangement_id, target(second, beats(12)), false)
+                .await,
+            ErrorKind::Locked,
+        );
+        assert_err!(
+            client
+                .paste_track_items(arrangement_id, target(third, beats(12)), true)
+                .await,
+            ErrorKind::NotSupported,
+        );
+
+        let pasted = client
+            .paste_track_items(arrangement_id, target(second, beats(12)), true)
+            .await?;
+        assert_eq!((pasted[0].0, pasted[1].0), (second, third));
+
+        // duplicates go right after the selection, and leave the clipboard alone
+        let duplicated = client
+            .duplicate_track_items(arrangement_id, vec![(second, second_item)], false)
+            .await?;
+        let item = client.get_track_item(second, duplicated[0].1).await?;
+        assert_eq!(item.start, Time::Real(secs(4.0)));
+
+        let pasted = client
+            .paste_track_items(arrangement_id, target(first, beats(16)), false)
+            .await?;
+        assert_eq!((pasted[0].0, pasted[1].0), (first, second));
+
+        Ok(())
+    })
+}
+
+#[test]
+fn time_base() -> Result<()> {
+    run_test(|client| async move {
+        let project = ProjectBuilder::new()
+            .tempo(60.0)
+            .track("Musical", |t| t.item(beats(4), beats(2)))
+            .track("Absolute", |t| t.item(beats(4), beats(2)).item(beats(8), beats(2)))
+            .build(&client)
+            .await?;
+
+        let secs = RealTime::from_secs_f64;
+        let musical = (project.view("Musical"), project.items("Musical")[0]);
+        let absolute = (project.view("Absolute"), project.items("Absolute")[0]);
+
+        client
+            .set_track_time_base(absolute.0, TimeBase::Absolute, false)
+            .await?;
+
+        let item = client.get_track_item(absolute.0.track_id, absolute.1).await?;
+        assert_eq!(item.start, Time::Real(secs(4.0)));
+        assert_eq!(item.duration, Time::Real(secs(2.0)));
+
+        client
+            .set_arrangement_tempo(project.arrangement_id, 120.0)
+            .await?;
+
+        let item = client.get_track_view_item(musical.0, musical.1).await?;
+        assert_eq!((item.real_start, item.real_end), (secs(2.0), secs(3.0)));
+        assert_eq!(
+            (item.beat_start, item.beat_end),
+            (BeatTime::from_beats(4), BeatTime::from_beats(6))
+        );
+        assert_eq!((item.frame_start, item.frame_end), (96000, 144000));
+
+        let item = client.get_track_view_item(absolute.0, absolute.1).await?;
+        assert_eq!((item.real_start, item.real_end), (secs(4.0), secs(6.0)));
+        assert_eq!(
+            (item.beat_start, item.beat_end),
+            (BeatTime::from_beats(8), BeatTime::from_beats(12))
+        );
+        assert_eq!((item.frame_start, item.frame_end), (192000, 288000));
+
+        // converting back keeps the item at its current position
+        client
+            .set_track_item_time_base(absolute.0, absolute.1, TimeBase::Musical, false)
+            .await?;
+        let item = client.get_track_item(absolute.0.track_id, absolute.1).await?;
+        assert_eq!(item.start, beats(8));
+        assert_eq!(item.duration, beats(4));
+
+        Ok(())
+    })
+}
+
+#[test]
+fn track_item_lanes() -> Result<()> {
+    run_test(|client| async move {
+        let project = ProjectBuilder::new()
+            .track("Track", |t| t.item(beats(0), beats(4)).item(beats(0), beats(4)))
+            .build(&client)
+            .await?;
+
+        let track_id = project.track("Track");
+        let view_id = project.view("Track");
+        let item_id = project.items("Track")[1];
+        assert_eq!(client.get_track_view_lane_count(view_id).await?, 1);
+
+        let mut stream = client
+            .subscribe_track_view(view_id, TrackViewFilter::default())
+            .await?;
+
+        client
+            .move_track_item_to_lane(track_id, item_id, 1, false)
+            .await?;
+
+        match stream.next().await {
+            Some(TrackViewEvent::ItemLaneChanged { id, new_lane }) => {
+                assert_eq!(id, item_id);
+                assert_eq!(new_lane, 1);
+            }
+            event => panic!("unexpected event: {event:?}"),
+        }
+
+        assert_eq!(client.get_track_item(track_id, item_id).await?.lane, 1);
+        assert_eq!(client.get_track_view_item(view_id, item_id).await?.lane, 1);
+        assert_eq!(client.get_track_view_lane_count(view_id).await?, 2);
+
+        client
+            .move_track_item_to_lane(track_id, item_id, MAX_TRACK_LANE, false)
+            .await?;
+        assert_eq!(
+            client.get_track_view_lane_count(view_id).await?,
+            MAX_TRACK_LANE + 1
+        );
+
+        assert_err!(
+            client
+                .move_track_item_to_lane(track_id, item_id, MAX_TRACK_LANE + 1, false)
+                .await,
+            ErrorKind::NotSupported
+        );
+        assert_err!(
+            client
+                .move_track_item_to_lane(track_id, item_id, u32::MAX, false)
+                .await,
+            ErrorKind::NotSupported
+        );
+        assert_eq!(
+            client.get_track_item(track_id, item_id).await?.lane,
+            MAX_TRACK_LANE
+        );
+
+        Ok(())
+    })
+}
+
+#[test]
+fn locked_track() -> Result<()> {
+    run_test(|client| async move {
+        let document_id = client.create_document().await?;
+        let track_id = client.create_track(document_id).await?;
+
+        let beats = |v| Time::Beat(BeatTime::from_beats(v));
+        let item = TrackItem {
+            inner: ItemId::Audio(AudioItemId::default()),
+            start: beats(0),
+            duration: beats(1),
+            lane: 0,
+            locked: false,
+        };
+
+        let item_id = client.add_track_item(track_id, item).await?;
+
+        let mut stream = client.subscribe_track_locked(track_id).await?;
+        client.set_track_locked(track_id, true).await?;
+        assert_eq!(stream.next().await, Some(true));
+        assert!(client.get_track_locked(track_id).await?);
+
+        assert_err!(
+            client.move_track_item(track_id, item_id, beats(2), false).await,
+            ErrorKind::Locked,
+        );
+
+        client.remove_track_item(track_id, item_id, true).await?;
+
+        Ok(())
+    })
+}
+
+#[test]
+fn list_tracks() -> Result<()> {
+    run_test(|client| async move {
+        let document_id = client.create_document().await?;
+        let arrangement_id = client.get_document_arrangement(document_id).await?;
+        let main_track_id = client.get_arrangement_main_track(arrangement_id).await?;
+
+        // detached tracks are listed too
+        let track_id = client.create_track(document_id).await?;
+        client.create_track(client.create_document().await?).await?;
+
+        let tracks = client.list_tracks(document_id, PageRequest::ALL).await?;
+        assert_eq!(tracks.total, 2);
+        assert!(tracks.items.contains(&main_track_id) && tracks.items.contains(&track_id));
+
+        // pages follow each other in the same order
+        let request = PageRequest::new(0, 1);
+        let first = client.list_tracks(document_id, request).await?;
+        assert_eq!(first.total, 2);
+        assert_eq!(first.items, tracks.items[..1]);
+
+        let request = first.next(request).unwrap();
+        let second = client.list_tracks(document_id, request).await?;
+        assert_eq!(second.items, tracks.items[1..]);
+        assert_eq!(second.next(request), None);
+
+        let past_end = client.list_tracks(document_id, PageRequest::new(5, 10)).await?;
+        assert!(past_end.items.is_empty());
+        assert_eq!(past_end.total, 2);
+
+        assert_err!(
+            client.list_tracks(DocumentId::default(), PageRequest::ALL).await,
+            ErrorKind::InvalidId,
+        );
+
+        Ok(())
+    })
+}
+
+#[test]
+fn track_status() -> Result<()> {
+    run_test(|client| async move {
+        let document_id = client.create_document().await?;
+        let track_id = client.create_track(document_id).await?;
+
+        assert_eq!(
+            client.get_track_status(track_id).await?,
+            TrackStatus::default()
+        );
+
+        let mut stream = client.subscribe_track_status(track_id).await?;
+
+        client.set_track_locked(track_id, true).await?;
+        let locked = TrackStatus {
+            locked: true,
+            ..TrackStatus::default()
+        };
+        assert_eq!(stream.next().await, Some(locked));
+
+        client.arm_track(track_id, true).await?;
+        let armed = TrackStatus {
+            armed: true,
+            ..locked
+        };
+        assert_eq!(stream.next().await, Some(armed));
+        assert_eq!(client.get_track_status(track_id).await?, armed);
+
+        Ok(())
+    })
+}
+
+#[test]
+fn effective_mute() -> Result<()> {
+    run_test(|client| async move {
+        let project = ProjectBuilder::new()
+            .track("Drums", |t| t)
+            .track("Group", |t| t.child("Bass", |t| t).child("Keys", |t| t))
+            .track("Vocals", |t| t)
+            .build(&client)
+            .await?;
+        let id = project.arrangement_id;
+        let [drums, group, bass, keys, vocals] =
+            ["Drums", "Group", "Bass", "Keys", "Vocals"].map(|v| project.track(v));
+
+        assert_eq!(client.get_muted_tracks(id).await?, vec![]);
+
+        let mut stream = client.subscribe_track_status(group).await?;
+
+        client.set_track_mute(group, true).await?;
+        let muted = TrackStatus {
+            muted: true,
+            ..TrackStatus::default()
+        };
+        assert_eq!(stream.next().await, Some(muted));
+        assert_eq!(client.get_muted_tracks(id).await?, vec![group, bass, keys]);
+
+        // soloing a child keeps the tracks it goes through audible
+        client.set_track_mute(group, false).await?;
+        client.set_track_solo(bass, true).await?;
+        assert_eq!(client.get_muted_tracks(id).await?, vec![drums, keys, vocals]);
+
+        // a muted track stays silent, even if it's soloed
+        client.set_track_mute(bass, true).await?;
+        assert_eq!(
+            client.get_muted_tracks(id).await?,
+            vec![drums, bass, keys, vocals]
+        );
+
+        client.set_track_solo(group, true).await?;
+        assert_eq!(client.get_muted_tracks(id).await?, vec![drums, bass, vocals]);
+
+        Ok(())
+    })
+}
+
+#[test]
+fn track_mix() -> Result<()> {
+    run_test(|client| async move {
+        let document_id = client.create_document().await?;
+        let track_id = client.create_track(document_id).await?;
+
+        assert_eq!(client.get_track_mix(track_id).await?, TrackMix::default());
+
+        let mut stream = client.subscribe_track_mix(track_id).await?;
+
+        client.set_track_volume(track_id, 0.5).await?;
+        let quieter = TrackMix {
+            volume: 0.5,
+            ..TrackMix::default()
+        };
+        assert_eq!(stream.next().await, Some(quieter));
+
+        client.set_track_pan(track_id, -0.25).await?;
+        let mix = TrackMix {
+            pan: -0.25,
+            ..quieter
+        };
+        assert_eq!(stream.next().await, Some(mix));
+        assert_eq!(client.get_track_mix(track_id).await?, mix);
+
+        assert_err!(
+            client.set_track_volume(track_id, f32::NAN).await,
+            ErrorKind::NotSupported
+        );
+        assert_err!(
+            client.set_track_pan(track_id, 1.5).await,
+            ErrorKind::NotSupported
+        );
+
+        Ok(())
+    })
+}
+
 #[test]
 #[ignore = "not yet implemented"]
 fn get_track_view_item() -> Result<()> {
     todo!()
 }
 #[test]
-#[ignore = "not yet implemented"]
 fn get_track_view_range() -> Result<()> {
-    todo!()
+    run_test(|client| async move {
+        let project = ProjectBuilder::new()
+            .tempo(60.0)
+            .track("Track", |t| {
+                t.item(beats(0), beats(2))
+                    .item(beats(4), beats(2))
+                    .item(beats(10), beats(2))
+            })
+            .build(&client)
+            .await?;
+
+        let view_id = project.view("Track");
+        let items = project.items("Track");
+
+        let range = client
+            .get_track_view_range(view_id, Some(beats(3)), Some(beats(7)))
+            .await?;
+        assert_eq!(range.iter().map(|(id, _)| *id).collect::<Vec<_>>(), [items[1]]);
+        assert_eq!(range[0].1.real_start, RealTime::from_secs_f64(4.0));
+
+        // real positions follow the tempo
+        client
+            .set_arrangement_tempo(project.arrangement_id, 120.0)
+            .await?;
+
+        let secs = |v| Some(Time::Real(RealTime::from_secs_f64(v)));
+        let range = client
+            .get_track_view_range(view_id, secs(4.0), secs(7.0))
+            .await?;
+        assert_eq!(range.iter().map(|(id, _)| *id).collect::<Vec<_>>(), [items[2]]);
+
+        assert_err!(
+            client
+                .set_arrangement_tempo(project.arrangement_id, 0.0)
+                .await,
+            ErrorKind::NotSupported,
+        );
+
+        Ok(())
+    })
+}
+
+#[test]
+fn project_builder() -> Result<()> {
+    run_test(|client| async move {
+        let project = ProjectBuilder::new()
+            .tempo(90.0)
+            .track("Drums", |t| t.item(beats(0), beats(4)).locked())
+            .track("Group", |t| {
+                t.child("Bass", |t| t.item(beats(0), beats(8)))
+                    .child("Keys", |t| t)
+            })
+            .tracks(2)
+            .build(&client)
+            .await?;
+
+        assert_eq!(
+            client.get_track_children(project.main_track_id).await?,
+            vec![
+                project.track("Drums"),
+                project.track("Group"),
+                project.track("Track 2"),
+                project.track("Track 3"),
+            ]
+        );
+        assert_eq!(
+            client.get_track_children(project.track("Group")).await?,
+            vec![project.track("Bass"), project.track("Keys")]
+        );
+
+        assert_eq!(client.get_track_name(project.track("Bass")).await?, "Bass");
+        assert!(client.get_track_locked(project.track("Drums")).await?);
+        assert_eq!(project.items("Bass").len(), 1);
+        assert!(project.items("Keys").is_empty());
+
+        assert_eq!(
+            client.get_arrangement_tempo(project.arrangement_id).await?,
+            90.0
+        );
+
+        Ok(())
+    })
+}
+
+#[test]
+fn get_tracks_summary() -> Result<()> {
+    run_test(|client| async move {
+        let project = ProjectBuilder::new()
+            .track("Drums", |t| t.locked())
+            .track("Group", |t| t.child("Bass", |t| t))
+            .build(&client)
+            .await?;
+
+        let ids = vec![project.track("Group"), project.track("Drums")];
+        let summaries = client.get_tracks_summary(ids).await?;
+
+        assert_eq!(
+            summaries,
+            vec![
+                TrackSummary {
+                    id: project.track("Group"),
+                    name: "Group".into(),
+                    locked: false,
+                    children: vec![project.track("Bass")],
+                    nodes: Vec::new(),
+                },
+                TrackSummary {
+                    id: project.track("Drums"),
+                    name: "Drums".into(),
+                    locked: true,
+                    children: Vec::new(),
+                    nodes: Vec::new(),
+                },
+            ]
+        );
+
+        let ids = vec![project.track("Drums"), invalid_track_id()];
+        assert_err!(client.get_tracks_summary(ids).await, ErrorKind::InvalidId);
+
+        Ok(())
+    })
+}
+
+#[test]
+fn error_operation() -> Result<()> {
+    run_test(|client| async move {
+        let track_id = invalid_track_id();
+        let error = client.get_track_name(track_id).await.unwrap_err();
+
+        let operation = error.operation().unwrap();
+        assert_eq!(operation.name, "get_track_name");
+        assert_eq!(
+            operation.objects,
+            vec![("id".to_string(), format!("{track_id:?}"))]
+        );
+
+        Ok(())
+    })
 }
@@ -1,18 +1,53 @@
+use std::collections::BTreeMap;
+use std::mem;
+
 use rdaw_api::arrangement::ArrangementId;
+use rdaw_api::document::DocumentId;
+use rdaw_api::item::{AudioClip, ItemId};
+use rdaw_api::stats::TrackViewCacheStats;
 use rdaw_api::time::Time;
-use rdaw_api::track::{TrackId, TrackItem, TrackItemId, TrackViewId, TrackViewItem};
+use rdaw_api::track::{
+    TrackId, TrackItem, TrackItemId, TrackViewFilter, TrackViewId, TrackViewItem,
+};
 use rdaw_core::collections::HashMap;
 use rdaw_core::time::RealTime;
 use rstar::{RTree, RTreeObject, AABB};
 use slotmap::SecondaryMap;
 
 use super::Track;
-use crate::object::Hub;
+use crate::engine::{time_to_frames, SAMPLE_RATE};
+use crate::object::{Hub, MemoryUsage, SubscribersHub};
 use crate::tempo_map::TempoMap;
 
-#[derive(Debug, Default)]
+/// How many views are kept by default. Views with subscribers don't count towards eviction.
+pub const DEFAULT_TRACK_VIEW_CACHE_CAPACITY: usize = 256;
+
+/// Computed views of tracks. Once the capacity is exceeded, the least recently used views are
+/// evicted, except for the ones that have subscribers, since those are kept up to date
+/// incrementally.
+#[derive(Debug, Clone)]
 pub struct TrackViewCache {
-    views: HashMap<TrackId, HashMap<ArrangementId, TrackView>>,
+    views: HashMap<TrackId, HashMap<ArrangementId, CachedView>>,
+    capacity: usize,
+    clock: u64,
+    stats: TrackViewCacheStats,
+}
+
+#[derive(Debug, Clone)]
+struct CachedView {
+    view: TrackView,
+    last_used: u64,
+}
+
+impl Default for TrackViewCache {
+    fn default() -> TrackViewCache {
+        TrackViewCache {
+            views: HashMap::default(),
+            capacity: DEFAULT_TRACK_VIEW_CACHE_CAPACITY,
+            clock: 0,
+            stats: TrackViewCacheStats::default(),
+        }
+    }
 }
 
 impl TrackViewCache {
@@ -24,7 +59,7 @@ impl TrackViewCache {
                         track_id,
                         arrangement_id: *k,
                     },
-                    v,
+                    &v.view,
                 )
             })
         })
@@ -44,14 +79,96 @@ impl TrackViewCache {
                             track_id,
                             arrangement_id: *k,
                         },
-                        v,
+                        &mut v.view,
                     )
                 })
             })
     }
 
-    pub fn get_or_insert(&mut self, hub: &Hub, view_id: TrackViewId) -> &mut TrackView {
-        self.views
+    fn len(&self) -> usize {
+        self.views.values().map(|v| v.len()).sum()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Takes effect on the next miss.
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+    }
+
+    pub fn stats(&self) -> TrackViewCacheStats {
+        TrackViewCacheStats {
+            capacity: self.capacity,
+            len: self.len(),
+            ..self.stats
+        }
+    }
+
+    pub fn memory_usage(&self, hub: &Hub, document_id: DocumentId) -> MemoryUsage {
+        let mut usage = MemoryUsage::default();
+
+        for (&track_id, views) in &self.views {
+            let is_in_document = hub
+                .tracks
+                .get_key(track_id)
+                .is_some_and(|key| key.document_id == document_id);
+
+            if !is_in_document {
+                continue;
+            }
+
+            for cached in views.values() {
+                usage.count += 1;
+                usage.bytes += mem::size_of::<CachedView>() + cached.view.heap_size();
+            }
+        }
+
+        usage
+    }
+
+    /// Recomputes views of an arrangement after its tempo map has changed.
+    pub fn recompute_arrangement(&mut self, hub: &Hub, arrangement_id: ArrangementId) {
+        let arrangement = &hub.arrangements[arrangement_id];
+        let tempo_map = &hub.tempo_maps[arrangement.tempo_map_id];
+
+        for (&track_id, views) in &mut self.views {
+            let Some(track) = hub.tracks.get(track_id) else {
+                continue;
+            };
+
+            if let Some(cached) = views.get_mut(&arrangement_id) {
+                cached.view.compute(hub, track, tempo_map);
+            }
+        }
+    }
+
+    /// Marks the view as used. On a miss, the view is computed, and views over the capacity are
+    /// evicted.
+    pub fn get_or_insert(
+        &mut self,
+        hub: &Hub,
+        subscribers: &SubscribersHub,
+        view_id: TrackViewId,
+    ) -> &mut TrackView {
+        self.clock += 1;
+
+        let is_cached = self
+            .views
+            .get(&view_id.track_id)
+            .is_some_and(|v| v.contains_key(&view_id.arrangement_id));
+
+        if is_cached {
+            self.stats.hits += 1;
+        } else {
+            self.stats.misses += 1;
+            self.evict(subscribers, self.capacity.saturating_sub(1));
+        }
+
+        let clock = self.clock;
+        let cached = self
+            .views
             .entry(view_id.track_id)
             .or_default()
             .entry(view_id.arrangement_id)
@@ -59,8 +176,52 @@ impl TrackViewCache {
                 let track = &hub.tracks[view_id.track_id];
                 let arrangement = &hub.arrangements[view_id.arrangement_id];
                 let tempo_map = &hub.tempo_maps[arrangement.tempo_map_id];
-                TrackView::new(track, tempo_map)
+                CachedView {
+                    view: TrackView::new(hub, track, tempo_map),
+                    last_used: clock,
+                }
+            });
+
+        cached.last_used = clock;
+        &mut cached.view
+    }
+
+    /// Evicts least recently used views until at most `max_len` are left, or only the ones with
+    /// subscribers remain.
+    fn evict(&mut self, subscribers: &SubscribersHub, max_len: usize) {
+        let mut candidates = self
+            .views
+            .iter()
+            .flat_map(|(&track_id, views)| {
+                views.iter().map(move |(&arrangement_id, cached)| {
+                    let view_id = TrackViewId {
+                        track_id,
+                        arrangement_id,
+                    };
+                    (cached.last_used, view_id)
+                })
             })
+            .collect::<Vec<_>>();
+
+        let Some(excess) = candidates.len().checked_sub(max_len).filter(|&v| v > 0) else {
+            return;
+        };
+
+        candidates.retain(|&(_, view_id)| !subscribers.track_view.has_subscribers(view_id));
+        candidates.sort_unstable_by_key(|&(last_used, _)| last_used);
+
+        for &(_, view_id) in candidates.iter().take(excess) {
+            let Some(views) = self.views.get_mut(&view_id.track_id) else {
+                continue;
+            };
+
+            views.remove(&view_id.arrangement_id);
+            if views.is_empty() {
+                self.views.remove(&view_id.track_id);
+            }
+
+            self.stats.evictions += 1;
+        }
     }
 }
 
@@ -68,33 +229,31 @@ impl TrackViewCache {
 pub struct TrackView {
     items: SecondaryMap<TrackItemId, TrackViewItem>,
     tree: RTree<TreeItem>,
+    lanes: BTreeMap<u32, usize>,
 }
 
 impl TrackView {
-    pub fn new(track: &Track, tempo_map: &TempoMap) -> TrackView {
+    pub fn new(hub: &Hub, track: &Track, tempo_map: &TempoMap) -> TrackView {
         let mut track_view = TrackView::default();
-        track_view.compute(track, tempo_map);
+        track_view.compute(hub, track, tempo_map);
         track_view
     }
 
-    pub fn compute(&mut self, track: &Track, tempo_map: &TempoMap) {
+    pub fn heap_size(&self) -> usize {
+        self.items.capacity() * mem::size_of::<TrackViewItem>()
+            + self.tree.size() * mem::size_of::<TreeItem>()
+            + self.lanes.len() * mem::size_of::<(u32, usize)>()
+    }
+
+    pub fn compute(&mut self, hub: &Hub, track: &Track, tempo_map: &TempoMap) {
         self.items.clear();
         self.items.set_capacity(track.items.capacity());
+        self.lanes.clear();
 
         for (item_id, item) in &track.items {
-            let real_start = tempo_map.to_real(item.start);
-            let real_duration = tempo_map.to_real(item.duration); // TODO: handle non-constant tempo
-            let real_end = real_start + real_duration;
-
-            let view_item = TrackViewItem {
-                inner: item.inner,
-                start: item.start,
-                duration: item.duration,
-                real_start,
-                real_end,
-            };
-
+            let view_item = view_item(tempo_map, item, item_clip(hub, item));
             self.items.insert(item_id, view_item);
+            *self.lanes.entry(item.lane).or_default() += 1;
         }
 
         let tree_items = self
@@ -115,25 +274,17 @@ impl TrackView {
         tempo_map: &TempoMap,
         item_id: TrackItemId,
         item: TrackItem,
+        clip: Option<AudioClip>,
     ) -> TrackViewItem {
-        let real_start = tempo_map.to_real(item.start);
-        let real_duration = tempo_map.to_real(item.duration); // TODO: handle non-constant tempo
-        let real_end = real_start + real_duration;
-
-        let view_item = TrackViewItem {
-            inner: item.inner,
-            start: item.start,
-            duration: item.duration,
-            real_start,
-            real_end,
-        };
+        let view_item = view_item(tempo_map, &item, clip);
 
         self.items.insert(item_id, view_item);
         self.tree.insert(TreeItem {
             id: item_id,
-            start: real_start,
-            end: real_end,
+            start: view_item.real_start,
+            end: view_item.real_end,
         });
+        *self.lanes.entry(item.lane).or_default() += 1;
 
         view_item
     }
@@ -150,6 +301,23 @@ impl TrackView {
         if let Some(item) = self.items.remove(item_id) {
             self.tree
                 .remove(&TreeItem::new(item_id, item.real_start, item.real_end));
+            self.remove_from_lane(item.lane);
+        }
+    }
+
+    pub fn lane_count(&self) -> u32 {
+        self.lanes.last_key_value().map_or(1, |(&lane, _)| lane + 1)
+    }
+
+    fn remove_from_lane(&mut self, lane: u32) {
+        let Some(count) = self.lanes.get_mut(&lane) else {
+            return;
+        };
+
+        *count -= 1;
+
+        if *count == 0 {
+            self.lanes.remove(&lane);
         }
     }
 
@@ -201,10 +369,14 @@ impl TrackView {
         new_start: Time,
     ) -> RealTime {
         self.update_item_envelope(id, |item| {
-            let duration = item.real_duration();
-            item.start = new_start;
-            item.real_start = tempo_map.to_real(new_start);
-            item.real_end = item.real_start + duration;
+            *item = view_item(
+                tempo_map,
+                &TrackItem {
+                    start: new_start,
+                    ..item.track_item()
+                },
+                item.clip,
+            );
             item.real_start
         })
     }
@@ -216,11 +388,39 @@ impl TrackView {
         new_duration: Time,
     ) -> RealTime {
         self.update_item_envelope(id, |item| {
-            item.duration = new_duration;
-            item.real_end = item.real_start + tempo_map.to_real(new_duration);
+            *item = view_item(
+                tempo_map,
+                &TrackItem {
+                    duration: new_duration,
+                    ..item.track_item()
+                },
+                item.clip,
+            );
             item.real_duration()
         })
     }
+
+    pub fn move_item_to_lane(&mut self, id: TrackItemId, new_lane: u32) {
+        let Some(item) = self.items.get_mut(id) else {
+            return;
+        };
+
+        let old_lane = std::mem::replace(&mut item.lane, new_lane);
+        self.remove_from_lane(old_lane);
+        *self.lanes.entry(new_lane).or_default() += 1;
+    }
+
+    pub fn lock_item(&mut self, id: TrackItemId, locked: bool) {
+        if let Some(item) = self.items.get_mut(id) {
+            item.locked = locked;
+        }
+    }
+
+    pub fn set_item_clip(&mut self, id: TrackItemId, clip: AudioClip) {
+        if let Some(item) = self.items.get_mut(id) {
+            item.clip = Some(clip);
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -243,11 +443,59 @@ impl RTreeObject for TreeItem {
         AABB::from_corners((self.start.as_nanos(), 0), (self.end.as_nanos(), 0))
     }
 }
+
+/// Computes positions of the item in every time base. Everything that needs them goes through
+/// here, so that they're rounded the same way everywhere.
+pub fn view_item(tempo_map: &TempoMap, item: &TrackItem, clip: Option<AudioClip>) -> TrackViewItem {
+    // TODO: handle non-constant tempo
+    let real_start = tempo_map.to_real(item.start);
+    let real_end = real_start + tempo_map.to_real(item.duration);
+    let beat_start = tempo_map.to_beat(item.start);
+    let beat_end = beat_start + tempo_map.to_beat(item.duration);
+
+    TrackViewItem {
+        inner: item.inner,
+        start: item.start,
+        duration: item.duration,
+        real_start,
+        real_end,
+        beat_start,
+        beat_end,
+        frame_start: time_to_frames(real_start, SAMPLE_RATE),
+        frame_end: time_to_frames(real_end, SAMPLE_RATE),
+        lane: item.lane,
+        locked: item.locked,
+        clip,
+    }
+}
+
+/// Clip of the item if it's an audio one.
+pub fn item_clip(hub: &Hub, item: &TrackItem) -> Option<AudioClip> {
+    match item.inner {
+        ItemId::Audio(id) => hub.audio_items.get(id).map(|v| v.clip),
+        ItemId::Midi(_) => None,
+    }
+}
+
+pub fn filter_intersects<'a>(
+    tempo_map: &TempoMap,
+    filter: &TrackViewFilter,
+    items: impl IntoIterator<Item = &'a TrackViewItem>,
+) -> bool {
+    let start = filter.start.map_or(RealTime::MIN, |t| tempo_map.to_real(t));
+    let end = filter.end.map_or(RealTime::MAX, |t| tempo_map.to_real(t));
+
+    items
+        .into_iter()
+        .any(|item| item.real_start <= end && item.real_end >= start)
+}
+
 #[cfg(test)]
 mod tests {
     use std::sync::atomic::{AtomicU64, Ordering};
 
     use rdaw_api::item::{AudioItemId, ItemId};
+    use rdaw_api::time::BeatTime;
     use slotmap::{KeyData, SlotMap};
 
     use super::*;
@@ -268,10 +516,12 @@ mod tests {
             inner: item_id(),
             start: Time::Real(RealTime::from_secs_f64(1.0)),
             duration: Time::Real(RealTime::from_secs_f64(2.0)),
+            lane: 0,
+            locked: false,
         };
         let id = items.insert(item);
 
-        let view_item = view.add_item(&tempo_map, id, item);
+        let view_item = view.add_item(&tempo_map, id, item, None);
 
         assert_eq!(
             view_item,
@@ -281,6 +531,13 @@ mod tests {
                 duration: item.duration,
                 real_start: RealTime::from_secs_f64(1.0),
                 real_end: RealTime::from_secs_f64(3.0),
+                beat_start: BeatTime::from_beats(2),
+                beat_end: BeatTime::from_beats(6),
+                frame_start: 48000,
+                frame_end: 144000,
+                lane: 0,
+                locked: false,
+                clip: None,
             }
         );
 
@@ -308,27 +565,33 @@ mod tests {
             inner: item_id(),
             start: real_0s,
             duration: real_2s,
+            lane: 0,
+            locked: false,
         };
 
         let item2 = TrackItem {
             inner: item_id(),
             start: real_1s,
             duration: real_3s,
+            lane: 0,
+            locked: false,
         };
 
         let item3 = TrackItem {
             inner: item_id(),
             start: real_2s,
             duration: real_3s,
+            lane: 0,
+            locked: false,
         };
 
         let id1 = items.insert(item1);
         let id2 = items.insert(item2);
         let id3 = items.insert(item3);
 
-        view.add_item(&tempo_map, id1, item1);
-        view.add_item(&tempo_map, id2, item2);
-        view.add_item(&tempo_map, id3, item3);
+        view.add_item(&tempo_map, id1, item1, None);
+        view.add_item(&tempo_map, id2, item2, None);
+        view.add_item(&tempo_map, id3, item3, None);
 
         let find = |start, end| {
             let mut items = view
@@ -346,4 +609,35 @@ mod tests {
         assert_eq!(find(Some(real_3s), Some(real_3s)), vec![id2, id3]);
         assert_eq!(find(Some(real_5s), Some(real_5s)), vec![id3]);
     }
+
+    #[test]
+    fn lanes() {
+        let tempo_map = TempoMap::new(120.0);
+        let mut items = SlotMap::default();
+        let mut view = TrackView::default();
+
+        let item = TrackItem {
+            inner: item_id(),
+            start: Time::Real(RealTime::from_secs_f64(0.0)),
+            duration: Time::Real(RealTime::from_secs_f64(1.0)),
+            lane: 0,
+            locked: false,
+        };
+
+        let id1 = items.insert(item);
+        let id2 = items.insert(TrackItem { lane: 2, ..item });
+
+        view.add_item(&tempo_map, id1, item, None);
+        assert_eq!(view.lane_count(), 1);
+
+        view.add_item(&tempo_map, id2, TrackItem { lane: 2, ..item }, None);
+        assert_eq!(view.lane_count(), 3);
+
+        view.move_item_to_lane(id2, 1);
+        assert_eq!(view.get_item(id2).map(|v| v.lane), Some(1));
+        assert_eq!(view.lane_count(), 2);
+
+        view.remove_item(id2);
+        assert_eq!(view.lane_count(), 1);
+    }
 }
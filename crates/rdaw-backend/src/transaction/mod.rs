@@ -0,0 +1,56 @@
+mod ops;
+#[cfg(test)]
+mod tests;
+
+use rdaw_api::transaction::TransactionRequest;
+use rdaw_api::{bail, BackendRequest, ErrorKind, Result};
+use rdaw_rpc::transport::ConnectionId;
+use rdaw_rpc::Role;
+
+use crate::object::Hub;
+use crate::track::TrackViewCache;
+
+/// State of the backend right before a transaction began, restored on rollback.
+#[derive(Debug)]
+pub struct Transaction {
+    /// Connection which began the transaction. It's rolled back when the client goes away.
+    owner: ConnectionId,
+    hub: Hub,
+    track_view_cache: TrackViewCache,
+}
+
+impl Transaction {
+    /// Checks whether the request may be handled while the transaction is open. Only its owner
+    /// may change anything, and documents live outside of the [`Hub`], so changes to them can't
+    /// be rolled back.
+    pub fn ensure_allowed(&self, connection: ConnectionId, request: &BackendRequest) -> Result<()> {
+        if request.required_role() == Role::ReadOnly {
+            return Ok(());
+        }
+
+        if connection != self.owner {
+            bail!(
+                ErrorKind::Conflict,
+                "`{}` isn't allowed while another client has a transaction open",
+                request.name(),
+            );
+        }
+
+        if matches!(request, BackendRequest::Document(_)) {
+            bail!(
+                ErrorKind::Conflict,
+                "`{}` isn't allowed inside a transaction",
+                request.name(),
+            );
+        }
+
+        Ok(())
+    }
+}
+
+pub fn is_begin(request: &BackendRequest) -> bool {
+    matches!(
+        request,
+        BackendRequest::Transaction(TransactionRequest::BeginTransaction { .. })
+    )
+}
@@ -0,0 +1,64 @@
+use rdaw_api::transaction::{TransactionOperations, TransactionRequest, TransactionResponse};
+use rdaw_api::{bail, format_err, BackendProtocol, ErrorKind, Result};
+use rdaw_rpc::transport::ConnectionId;
+use tracing::instrument;
+
+use super::Transaction;
+use crate::Backend;
+
+#[rdaw_rpc::handler(protocol = BackendProtocol, operations = TransactionOperations)]
+impl Backend {
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn begin_transaction(&mut self) -> Result<()> {
+        if self.transaction.is_some() {
+            bail!(ErrorKind::Conflict, "a transaction is already in progress");
+        }
+
+        self.transaction = Some(Transaction {
+            owner: self.connection,
+            hub: self.hub.clone(),
+            track_view_cache: self.track_view_cache.clone(),
+        });
+
+        Ok(())
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn commit_transaction(&mut self) -> Result<()> {
+        self.take_transaction()?;
+        Ok(())
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn rollback_transaction(&mut self) -> Result<()> {
+        let transaction = self.take_transaction()?;
+        self.restore(transaction);
+        Ok(())
+    }
+
+    /// Rolls back the transaction of a client which went away without finishing it.
+    pub(crate) fn abandon_transaction(&mut self, connection: ConnectionId) {
+        match self.transaction.take() {
+            Some(transaction) if transaction.owner == connection => {
+                tracing::warn!(?connection, "rolling back abandoned transaction");
+                self.restore(transaction);
+            }
+            transaction => self.transaction = transaction,
+        }
+    }
+
+    fn restore(&mut self, transaction: Transaction) {
+        self.hub = transaction.hub;
+        self.track_view_cache = transaction.track_view_cache;
+        self.subscribers.discard_queued();
+    }
+
+    fn take_transaction(&mut self) -> Result<Transaction> {
+        self.transaction
+            .take()
+            .ok_or_else(|| format_err!(ErrorKind::NotFound, "no transaction in progress"))
+    }
+}
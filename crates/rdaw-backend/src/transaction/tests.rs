@@ -0,0 +1,190 @@
+use futures::future::{self, Either};
+use futures::{FutureExt, StreamExt};
+use rdaw_api::document::DocumentOperations;
+use rdaw_api::track::TrackOperations;
+use rdaw_api::transaction::{Transaction, TransactionOperations};
+use rdaw_api::{assert_err, ErrorKind, Result};
+use rdaw_rpc::transport::{SocketClientTransport, SocketListener};
+use rdaw_rpc::{Authenticator, Client, Role, SharedSecret};
+
+use crate::tests::{beats, run_test, run_test_with, ProjectBuilder};
+
+#[test]
+fn commit_transaction() -> Result<()> {
+    run_test(|client| async move {
+        let project = ProjectBuilder::new()
+            .track("Drums", |t| t.item(beats(0), beats(4)))
+            .build(&client)
+            .await?;
+
+        let track_id = project.track("Drums");
+        let item_id = project.items("Drums")[0];
+        let mut stream = client.subscribe_track_name(track_id).await?;
+
+        client.begin_transaction().await?;
+        client.set_track_name(track_id, "Kick".into()).await?;
+        client.set_track_name(track_id, "Snare".into()).await?;
+        client
+            .move_track_item(track_id, item_id, beats(8), false)
+            .await?;
+
+        assert_eq!(client.get_track_name(track_id).await?, "Snare");
+        assert_eq!(stream.next().now_or_never(), None);
+
+        client.commit_transaction().await?;
+
+        // both renames are coalesced into a single event
+        assert_eq!(stream.next().await, Some("Snare".into()));
+
+        let item = client.get_track_item(track_id, item_id).await?;
+        assert_eq!(item.start, beats(8));
+
+        Ok(())
+    })
+}
+
+#[test]
+fn rollback_transaction() -> Result<()> {
+    run_test(|client| async move {
+        let project = ProjectBuilder::new()
+            .track("Drums", |t| t.item(beats(0), beats(4)))
+            .build(&client)
+            .await?;
+
+        let track_id = project.track("Drums");
+        let item_id = project.items("Drums")[0];
+        let mut stream = client.subscribe_track_name(track_id).await?;
+
+        client.set_track_name(track_id, "Kick".into()).await?;
+
+        client.begin_transaction().await?;
+        client.set_track_name(track_id, "Snare".into()).await?;
+        client
+            .move_track_item(track_id, item_id, beats(8), false)
+            .await?;
+        let new_track_id = client.create_track(project.document_id).await?;
+        client.rollback_transaction().await?;
+
+        assert_eq!(stream.next().await, Some("Kick".into()));
+        assert_eq!(client.get_track_name(track_id).await?, "Kick");
+
+        let item = client.get_track_item(track_id, item_id).await?;
+        assert_eq!(item.start, beats(0));
+
+        assert_err!(
+            client.get_track_name(new_track_id).await,
+            ErrorKind::InvalidId
+        );
+
+        // events of the rolled back changes are never delivered
+        client.set_track_name(track_id, "Hats".into()).await?;
+        assert_eq!(stream.next().await, Some("Hats".into()));
+
+        Ok(())
+    })
+}
+
+#[test]
+fn transaction_errors() -> Result<()> {
+    run_test(|client| async move {
+        assert_err!(client.commit_transaction().await, ErrorKind::NotFound);
+        assert_err!(client.rollback_transaction().await, ErrorKind::NotFound);
+
+        let document_id = client.create_document().await?;
+
+        client.begin_transaction().await?;
+        assert_err!(client.begin_transaction().await, ErrorKind::Conflict);
+        assert_err!(client.create_document().await, ErrorKind::Conflict);
+
+        client.get_document_arrangement(document_id).await?;
+        client.commit_transaction().await?;
+
+        client.create_document().await?;
+
+        Ok(())
+    })
+}
+
+#[test]
+fn transaction_guard() -> Result<()> {
+    run_test(|client| async move {
+        let project = ProjectBuilder::new()
+            .track("Drums", |t| t)
+            .build(&client)
+            .await?;
+
+        let track_id = project.track("Drums");
+
+        let transaction = Transaction::begin(&client).await?;
+        client.set_track_name(track_id, "Kick".into()).await?;
+        drop(transaction);
+
+        // the rollback is sent before the next request
+        assert_eq!(client.get_track_name(track_id).await?, "Drums");
+
+        let transaction = Transaction::begin(&client).await?;
+        client.set_track_name(track_id, "Snare".into()).await?;
+        transaction.commit().await?;
+
+        assert_eq!(client.get_track_name(track_id).await?, "Snare");
+
+        Ok(())
+    })
+}
+
+#[test]
+fn abandoned_transaction() -> Result<()> {
+    let secret = SharedSecret::new(b"secret");
+    let mut authenticator = Authenticator::new();
+    authenticator.add_secret(secret.clone(), Role::Editor);
+
+    let listener = SocketListener::bind_tcp("127.0.0.1:0", authenticator)?;
+    let addr = listener.tcp_addr().unwrap();
+
+    run_test_with(
+        |backend| backend.listen(listener),
+        |client| async move {
+            let project = ProjectBuilder::new()
+                .track("Drums", |t| t)
+                .build(&client)
+                .await?;
+
+            let track_id = project.track("Drums");
+
+            let remote = Client::new(SocketClientTransport::connect_tcp(addr, &secret)?);
+            let changes = async {
+                remote.begin_transaction().await?;
+                remote.set_track_name(track_id, "Kick".into()).await?;
+                remote.create_track(project.document_id).await
+            };
+
+            let new_track_id =
+                match future::select(remote.clone().handle().boxed(), changes.boxed()).await {
+                    Either::Left(_) => panic!("remote client has disconnected"),
+                    Either::Right((res, _)) => res?,
+                };
+
+            // other clients can only look until the transaction is finished
+            assert_eq!(client.get_track_name(track_id).await?, "Kick");
+            assert_err!(
+                client.set_track_name(track_id, "Snare".into()).await,
+                ErrorKind::Conflict
+            );
+            assert_err!(client.commit_transaction().await, ErrorKind::Conflict);
+
+            // the remote client goes away without committing, which the backend notices in a
+            // moment
+            drop(remote);
+            while client.begin_transaction().await.is_err() {}
+            client.rollback_transaction().await?;
+
+            assert_eq!(client.get_track_name(track_id).await?, "Drums");
+            assert_err!(
+                client.get_track_name(new_track_id).await,
+                ErrorKind::InvalidId
+            );
+
+            Ok(())
+        },
+    )
+}
@@ -0,0 +1,175 @@
+mod ops;
+#[cfg(test)]
+mod tests;
+
+use std::ops::Range;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use rdaw_api::transport::{LoopRegion, PlaybackState, PlayheadEvent};
+use rdaw_core::time::RealTime;
+
+use crate::{Backend, DeferredQueue};
+
+const TICK_INTERVAL: Duration = Duration::from_millis(20);
+
+/// Playback state of an arrangement.
+#[derive(Debug, Clone)]
+pub struct Transport {
+    pub playback: PlaybackState,
+    pub position: RealTime,
+    /// Where playback was started from, stopping returns the playhead here.
+    pub start_position: RealTime,
+    pub loop_region: Option<LoopRegion>,
+    /// Playback stops once the playhead gets here.
+    pub stop_position: Option<RealTime>,
+}
+
+impl Default for Transport {
+    fn default() -> Transport {
+        Transport {
+            playback: PlaybackState::Stopped,
+            position: RealTime::ZERO,
+            start_position: RealTime::ZERO,
+            loop_region: None,
+            stop_position: None,
+        }
+    }
+}
+
+impl Transport {
+    pub fn playhead(&self) -> PlayheadEvent {
+        PlayheadEvent {
+            playback: self.playback,
+            position: self.position,
+        }
+    }
+
+    pub fn play(&mut self) {
+        if self.playback == PlaybackState::Stopped {
+            self.start_position = self.position;
+        }
+
+        self.playback = PlaybackState::Playing;
+    }
+
+    pub fn play_from(&mut self, position: RealTime) {
+        self.position = position;
+        self.start_position = position;
+        self.stop_position = None;
+        self.playback = PlaybackState::Playing;
+    }
+
+    /// Plays `range`, and then moves the playhead to `return_position`.
+    pub fn play_range(&mut self, range: Range<RealTime>, return_position: RealTime) {
+        self.position = range.start;
+        self.start_position = return_position;
+        self.stop_position = Some(range.end);
+        self.playback = PlaybackState::Playing;
+    }
+
+    pub fn pause(&mut self) {
+        if self.playback == PlaybackState::Playing {
+            self.playback = PlaybackState::Paused;
+            self.stop_position = None;
+        }
+    }
+
+    pub fn stop(&mut self) {
+        if self.playback != PlaybackState::Stopped {
+            self.position = self.start_position;
+            self.playback = PlaybackState::Stopped;
+            self.stop_position = None;
+        }
+    }
+
+    pub fn seek(&mut self, position: RealTime) {
+        self.position = position;
+        self.stop_position = None;
+
+        if self.playback != PlaybackState::Playing {
+            self.start_position = position;
+        }
+    }
+
+    /// Stops playback if the playhead has passed the stop position. Returns whether it has.
+    pub fn stop_at_end(&mut self) -> bool {
+        match self.stop_position {
+            Some(end) if self.playback == PlaybackState::Playing && self.position >= end => {
+                self.stop();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Moves the playhead forward while playing. Returns whether it has moved.
+    pub fn advance(&mut self, elapsed: RealTime, loop_range: Option<Range<RealTime>>) -> bool {
+        if self.playback != PlaybackState::Playing || elapsed <= RealTime::ZERO {
+            return false;
+        }
+
+        let mut position = self.position + elapsed;
+
+        if let Some(range) = loop_range {
+            let len = (range.end - range.start).as_nanos();
+            if len > 0 && self.position < range.end && position >= range.end {
+                let overshoot = (position - range.end).as_nanos() % len;
+                position = range.start + RealTime::from_nanos(overshoot);
+            }
+        }
+
+        self.position = position;
+        self.stop_at_end();
+        true
+    }
+}
+
+/// Advances playing transports from the wall clock, so that the playhead moves without an audio
+/// stream. The thread stops once the ticker is dropped.
+#[derive(Debug)]
+pub struct Ticker {
+    running: Arc<AtomicBool>,
+}
+
+impl Ticker {
+    pub fn start(queue: DeferredQueue) -> Ticker {
+        let running = Arc::new(AtomicBool::new(true));
+
+        let thread_running = running.clone();
+        let res = thread::Builder::new()
+            .name("transport-ticker".into())
+            .spawn(move || {
+                let mut last = Instant::now();
+
+                while thread_running.load(Ordering::Relaxed) {
+                    thread::sleep(TICK_INTERVAL);
+
+                    let now = Instant::now();
+                    let elapsed = RealTime::from_secs_f64((now - last).as_secs_f64());
+                    last = now;
+
+                    queue.defer(move |this: &mut Backend| {
+                        this.advance_transports(elapsed);
+                        this.poll_engine_status();
+                        this.poll_meters();
+                        std::future::ready(Ok(()))
+                    });
+                }
+            });
+
+        if let Err(error) = res {
+            tracing::error!(?error, "failed to spawn transport ticker");
+        }
+
+        Ticker { running }
+    }
+}
+
+impl Drop for Ticker {
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::Relaxed);
+    }
+}
@@ -0,0 +1,220 @@
+use std::ops::Range;
+
+use rdaw_api::arrangement::ArrangementId;
+use rdaw_api::time::Time;
+use rdaw_api::transport::{
+    LoopRegion, PlaybackState, RangeEnd, TransportOperations, TransportRequest, TransportResponse,
+    TransportState,
+};
+use rdaw_api::{bail, BackendProtocol, ErrorKind, Result};
+use rdaw_core::time::RealTime;
+use rdaw_rpc::StreamId;
+use tracing::instrument;
+
+use super::{Ticker, Transport};
+use crate::Backend;
+
+#[rdaw_rpc::handler(protocol = BackendProtocol, operations = TransportOperations)]
+impl Backend {
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn subscribe_playhead(&mut self, id: ArrangementId) -> Result<StreamId> {
+        self.hub.arrangements.ensure_has(id)?;
+        Ok(self.subscribers.playhead.subscribe(id))
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn get_transport_state(&self, id: ArrangementId) -> Result<TransportState> {
+        self.hub.arrangements.ensure_has(id)?;
+
+        let transport = self.transports.get(&id).cloned().unwrap_or_default();
+
+        Ok(TransportState {
+            playback: transport.playback,
+            position: transport.position,
+            loop_region: transport.loop_region,
+        })
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn play(&mut self, id: ArrangementId) -> Result<()> {
+        self.update_transport(id, Transport::play)?;
+        self.start_playback(id);
+        Ok(())
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn pause(&mut self, id: ArrangementId) -> Result<()> {
+        self.update_transport(id, Transport::pause)
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn stop(&mut self, id: ArrangementId) -> Result<()> {
+        self.update_transport(id, Transport::stop)
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn seek(&mut self, id: ArrangementId, position: Time) -> Result<()> {
+        let position = self.resolve_time(id, position)?;
+
+        if position < RealTime::ZERO {
+            bail!(ErrorKind::NotSupported, "can't seek before the start");
+        }
+
+        self.update_transport(id, |transport| transport.seek(position))
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn play_from(&mut self, id: ArrangementId, position: Time) -> Result<()> {
+        let position = self.resolve_time(id, position)?;
+
+        if position < RealTime::ZERO {
+            bail!(ErrorKind::NotSupported, "can't play before the start");
+        }
+
+        self.update_transport(id, |transport| transport.play_from(position))?;
+        self.start_playback(id);
+        Ok(())
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn play_range(
+        &mut self,
+        id: ArrangementId,
+        start: Time,
+        end: Time,
+        after: RangeEnd,
+    ) -> Result<()> {
+        let range = self.resolve_time(id, start)?..self.resolve_time(id, end)?;
+
+        if range.start < RealTime::ZERO || range.start >= range.end {
+            bail!(ErrorKind::NotSupported, "invalid playback range");
+        }
+
+        self.update_transport(id, |transport| {
+            let return_position = match after {
+                RangeEnd::Start => range.start,
+                RangeEnd::PreviousPosition => transport.position,
+            };
+
+            transport.play_range(range, return_position);
+        })?;
+
+        self.start_playback(id);
+        Ok(())
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn set_loop_region(&mut self, id: ArrangementId, region: Option<LoopRegion>) -> Result<()> {
+        if let Some(region) = region {
+            let range = self.resolve_loop_range(id, region)?;
+            if range.start < RealTime::ZERO || range.start >= range.end {
+                bail!(ErrorKind::NotSupported, "invalid loop region");
+            }
+        }
+
+        self.hub.arrangements.ensure_has(id)?;
+        self.transports.entry(id).or_default().loop_region = region;
+        self.sync_engine_playhead(id);
+
+        Ok(())
+    }
+
+    pub(crate) fn advance_transports(&mut self, elapsed: RealTime) {
+        let mut is_playing = false;
+
+        let ids = self.transports.keys().copied().collect::<Vec<_>>();
+
+        for id in ids {
+            let loop_range = self.transports[&id]
+                .loop_region
+                .and_then(|region| self.resolve_loop_range(id, region).ok());
+
+            let engine_position = self.engine_position(id);
+
+            let transport = self.transports.get_mut(&id).unwrap();
+            let has_moved = match engine_position {
+                // the audio clock is the one that matters when there's sound
+                Some(position) if transport.playback == PlaybackState::Playing => {
+                    let has_moved = transport.position != position;
+                    transport.position = position;
+                    transport.stop_at_end();
+                    has_moved
+                }
+                _ => transport.advance(elapsed, loop_range),
+            };
+
+            let is_stopped = transport.playback == PlaybackState::Stopped;
+            is_playing |= transport.playback == PlaybackState::Playing;
+
+            if has_moved {
+                self.subscribers.playhead.notify(id, transport.playhead());
+
+                if is_stopped {
+                    self.sync_engine_playhead(id);
+                }
+            }
+        }
+
+        if !is_playing {
+            self.ticker = None;
+        }
+    }
+
+    pub(crate) fn start_playback(&mut self, id: ArrangementId) {
+        // playback goes on without sound, so that the UI still works without an audio device
+        if let Err(error) = self.ensure_engine(id) {
+            tracing::warn!(?error, "failed to start audio engine");
+        }
+
+        if self.ticker.is_none() {
+            self.ticker = Some(Ticker::start(self.queue.clone()));
+        }
+    }
+
+    pub(crate) fn update_transport(
+        &mut self,
+        id: ArrangementId,
+        f: impl FnOnce(&mut Transport),
+    ) -> Result<()> {
+        self.hub.arrangements.ensure_has(id)?;
+
+        let engine_position = self.engine_position(id);
+
+        let transport = self.transports.entry(id).or_default();
+        if let Some(position) = engine_position {
+            if transport.playback == PlaybackState::Playing {
+                transport.position = position;
+            }
+        }
+
+        let old = transport.playhead();
+        f(transport);
+
+        let new = transport.playhead();
+        if new != old {
+            self.subscribers.playhead.notify(id, new);
+            self.sync_engine_playhead(id);
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn resolve_time(&self, id: ArrangementId, time: Time) -> Result<RealTime> {
+        let arrangement = self.hub.arrangements.get_or_err(id)?;
+        let tempo_map = &self.hub.tempo_maps[arrangement.tempo_map_id];
+        Ok(tempo_map.to_real(time))
+    }
+
+    fn resolve_loop_range(&self, id: ArrangementId, region: LoopRegion) -> Result<Range<RealTime>> {
+        Ok(self.resolve_time(id, region.start)?..self.resolve_time(id, region.end)?)
+    }
+}
@@ -0,0 +1,180 @@
+use futures::StreamExt;
+use rdaw_api::document::DocumentOperations;
+use rdaw_api::time::Time;
+use rdaw_api::transport::{
+    LoopRegion, PlaybackState, PlayheadEvent, RangeEnd, TransportOperations, TransportState,
+};
+use rdaw_api::{assert_err, ErrorKind, Result};
+use rdaw_core::time::RealTime;
+
+use super::Transport;
+use crate::tests::run_test;
+
+fn secs(secs: i64) -> RealTime {
+    RealTime::from_secs(secs)
+}
+
+#[test]
+fn advance() {
+    let mut transport = Transport::default();
+    assert!(!transport.advance(secs(1), None));
+
+    transport.seek(secs(1));
+    transport.play();
+    assert!(transport.advance(secs(2), None));
+    assert_eq!(transport.position, secs(3));
+
+    transport.pause();
+    assert!(!transport.advance(secs(2), None));
+
+    transport.play();
+    transport.advance(secs(1), None);
+    transport.stop();
+    assert_eq!(transport.position, secs(1));
+}
+
+#[test]
+fn advance_loop() {
+    let mut transport = Transport::default();
+    transport.play();
+
+    transport.advance(secs(3), Some(secs(2)..secs(4)));
+    assert_eq!(transport.position, secs(3));
+
+    transport.advance(secs(6), Some(secs(2)..secs(4)));
+    assert_eq!(transport.position, secs(3));
+
+    // started after the loop region
+    transport.seek(secs(5));
+    transport.advance(secs(1), Some(secs(2)..secs(4)));
+    assert_eq!(transport.position, secs(6));
+}
+
+#[test]
+fn play_range() {
+    let mut transport = Transport::default();
+    transport.play_range(secs(2)..secs(4), secs(1));
+    assert_eq!(transport.position, secs(2));
+
+    transport.advance(secs(1), None);
+    assert_eq!(transport.playback, PlaybackState::Playing);
+
+    transport.advance(secs(2), None);
+    assert_eq!(transport.playback, PlaybackState::Stopped);
+    assert_eq!(transport.position, secs(1));
+
+    // pausing forgets the range
+    transport.play_range(secs(2)..secs(4), secs(1));
+    transport.pause();
+    transport.play();
+    transport.advance(secs(5), None);
+    assert_eq!(transport.position, secs(7));
+
+    transport.play_from(secs(3));
+    transport.advance(secs(1), None);
+    transport.stop();
+    assert_eq!(transport.position, secs(3));
+}
+
+#[test]
+fn transport_state() -> Result<()> {
+    run_test(|client| async move {
+        let document_id = client.create_document().await?;
+        let arrangement_id = client.get_document_arrangement(document_id).await?;
+
+        let mut stream = client.subscribe_playhead(arrangement_id).await?;
+
+        client.seek(arrangement_id, Time::Real(secs(2))).await?;
+        assert_eq!(
+            stream.next().await,
+            Some(PlayheadEvent {
+                playback: PlaybackState::Stopped,
+                position: secs(2),
+            })
+        );
+
+        client.play(arrangement_id).await?;
+        assert_eq!(
+            stream.next().await.map(|v| v.playback),
+            Some(PlaybackState::Playing)
+        );
+
+        client.stop(arrangement_id).await?;
+        assert_eq!(
+            client.get_transport_state(arrangement_id).await?,
+            TransportState {
+                playback: PlaybackState::Stopped,
+                position: secs(2),
+                loop_region: None,
+            }
+        );
+
+        let region = LoopRegion {
+            start: Time::Real(secs(1)),
+            end: Time::Real(secs(3)),
+        };
+        client.set_loop_region(arrangement_id, Some(region)).await?;
+        assert_eq!(
+            client
+                .get_transport_state(arrangement_id)
+                .await?
+                .loop_region,
+            Some(region)
+        );
+
+        Ok(())
+    })
+}
+
+#[test]
+fn transport_errors() -> Result<()> {
+    run_test(|client| async move {
+        let document_id = client.create_document().await?;
+        let arrangement_id = client.get_document_arrangement(document_id).await?;
+
+        assert_err!(
+            client.seek(arrangement_id, Time::Real(secs(-1))).await,
+            ErrorKind::NotSupported
+        );
+
+        let region = LoopRegion {
+            start: Time::Real(secs(3)),
+            end: Time::Real(secs(3)),
+        };
+        assert_err!(
+            client.set_loop_region(arrangement_id, Some(region)).await,
+            ErrorKind::NotSupported
+        );
+
+        assert_err!(
+            client.play_from(arrangement_id, Time::Real(secs(-1))).await,
+            ErrorKind::NotSupported
+        );
+        assert_err!(
+            client
+                .play_range(
+                    arrangement_id,
+                    Time::Real(secs(2)),
+                    Time::Real(secs(1)),
+                    RangeEnd::Start
+                )
+                .await,
+            ErrorKind::NotSupported
+        );
+
+        client.seek(arrangement_id, Time::Real(secs(5))).await?;
+        client
+            .play_range(
+                arrangement_id,
+                Time::Real(secs(1)),
+                Time::Real(secs(2)),
+                RangeEnd::PreviousPosition,
+            )
+            .await?;
+        client.stop(arrangement_id).await?;
+        let state = client.get_transport_state(arrangement_id).await?;
+        assert_eq!(state.position, secs(5));
+
+        Ok(())
+    })
+}
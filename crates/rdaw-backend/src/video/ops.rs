@@ -0,0 +1,213 @@
+use rdaw_api::arrangement::ArrangementId;
+use rdaw_api::asset::AssetId;
+use rdaw_api::audio::{AudioInputStream as _, AudioMetadata, ResampleQuality};
+use rdaw_api::item::ItemId;
+use rdaw_api::media::{MediaInput as _, OpenMediaInput as _};
+use rdaw_api::stats::TaskQueue;
+use rdaw_api::time::Time;
+use rdaw_api::track::{TrackId, TrackItem, TrackItemId};
+use rdaw_api::video::{
+    ArrangementVideo, VideoFrame, VideoInputStream as _, VideoMetadata, VideoOperations,
+    VideoRequest, VideoResponse,
+};
+use rdaw_api::{bail, format_err, BackendProtocol, Error, ErrorKind, Result};
+use rdaw_core::time::RealTime;
+use rdaw_ffmpeg::MediaInput;
+use rdaw_rpc::Responder;
+use tracing::instrument;
+
+use crate::asset::AssetReader;
+use crate::item::AudioItem;
+use crate::object::ObjectKey;
+use crate::source::AudioSource;
+use crate::Backend;
+
+#[rdaw_rpc::handler(protocol = BackendProtocol, operations = VideoOperations)]
+impl Backend {
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn attach_arrangement_video(
+        &mut self,
+        responder: impl Responder<VideoMetadata, Error>,
+        id: ArrangementId,
+        asset_id: AssetId,
+        offset: RealTime,
+    ) -> Result<()> {
+        self.hub.arrangements.ensure_has(id)?;
+        let reader = self.open_asset(asset_id)?;
+
+        let queue = self.queue.clone();
+        self.spawn(TaskQueue::Decode, async move {
+            let metadata = read_video_metadata(reader);
+
+            queue.defer(move |this: &mut Backend| {
+                let res = metadata.and_then(|metadata| {
+                    let arrangement = this.hub.arrangements.get_mut_or_err(id)?;
+                    arrangement.video = Some(ArrangementVideo {
+                        asset_id,
+                        offset,
+                        metadata,
+                    });
+                    Ok(metadata)
+                });
+
+                responder.respond(res)
+            });
+
+            Ok(())
+        });
+
+        Ok(())
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn detach_arrangement_video(&mut self, id: ArrangementId) -> Result<()> {
+        let arrangement = self.hub.arrangements.get_mut_or_err(id)?;
+        arrangement.video = None;
+        Ok(())
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn get_arrangement_video(&self, id: ArrangementId) -> Result<Option<ArrangementVideo>> {
+        let arrangement = self.hub.arrangements.get_or_err(id)?;
+        Ok(arrangement.video)
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn get_arrangement_video_frame(
+        &mut self,
+        responder: impl Responder<Option<VideoFrame>, Error>,
+        id: ArrangementId,
+        position: RealTime,
+        max_width: u32,
+        max_height: u32,
+    ) -> Result<()> {
+        let video = self.get_video_or_err(id)?;
+        let reader = self.open_asset(video.asset_id)?;
+
+        let queue = self.queue.clone();
+        self.spawn(TaskQueue::Decode, async move {
+            let position = position - video.offset;
+
+            let frame = if position < RealTime::ZERO || position >= video.metadata.duration {
+                Ok(None)
+            } else {
+                read_video_frame(reader, position, max_width, max_height)
+            };
+
+            queue.defer(move |_: &mut Backend| responder.respond(frame));
+
+            Ok(())
+        });
+
+        Ok(())
+    }
+
+    #[instrument(level = "trace", skip_all, err)]
+    #[handler]
+    pub fn extract_arrangement_video_audio(
+        &mut self,
+        responder: impl Responder<TrackItemId, Error>,
+        id: ArrangementId,
+        track_id: TrackId,
+    ) -> Result<()> {
+        let video = self.get_video_or_err(id)?;
+        self.hub.tracks.ensure_has(track_id)?;
+        let reader = self.open_asset(video.asset_id)?;
+
+        let queue = self.queue.clone();
+        self.spawn(TaskQueue::Decode, async move {
+            let metadata = read_audio_metadata(reader);
+
+            queue.defer(move |this: &mut Backend| {
+                let res = metadata.and_then(|metadata| {
+                    this.add_extracted_audio(id, track_id, video, metadata)
+                });
+
+                responder.respond(res)
+            });
+
+            Ok(())
+        });
+
+        Ok(())
+    }
+
+    fn get_video_or_err(&self, id: ArrangementId) -> Result<ArrangementVideo> {
+        let arrangement = self.hub.arrangements.get_or_err(id)?;
+        arrangement
+            .video
+            .ok_or_else(|| format_err!(ErrorKind::NotFound, "{id:?} doesn't have a video"))
+    }
+
+    fn add_extracted_audio(
+        &mut self,
+        id: ArrangementId,
+        track_id: TrackId,
+        video: ArrangementVideo,
+        metadata: AudioMetadata,
+    ) -> Result<TrackItemId> {
+        let document_id = self.hub.arrangements.get_key_or_err(id)?.document_id;
+        let duration = metadata.duration;
+
+        let source_id = self.hub.audio_sources.insert(
+            ObjectKey::new_random(document_id),
+            AudioSource {
+                asset_id: video.asset_id,
+                metadata,
+                resample_quality: ResampleQuality::default(),
+            },
+        );
+
+        let item_id = self
+            .hub
+            .audio_items
+            .insert(ObjectKey::new_random(document_id), AudioItem::new(source_id));
+
+        self.add_track_item(
+            track_id,
+            TrackItem {
+                inner: ItemId::Audio(item_id),
+                start: Time::Real(video.offset),
+                duration: Time::Real(duration),
+                lane: 0,
+                locked: false,
+            },
+        )
+    }
+}
+
+fn read_video_metadata(reader: AssetReader) -> Result<VideoMetadata> {
+    let mut media = MediaInput::open(reader)?;
+    let Some(stream) = media.get_video_stream(1, 1)? else {
+        bail!(ErrorKind::NotFound, "asset doesn't have a video stream");
+    };
+
+    Ok(*stream.metadata())
+}
+
+fn read_video_frame(
+    reader: AssetReader,
+    position: RealTime,
+    max_width: u32,
+    max_height: u32,
+) -> Result<Option<VideoFrame>> {
+    let mut media = MediaInput::open(reader)?;
+    let Some(mut stream) = media.get_video_stream(max_width, max_height)? else {
+        bail!(ErrorKind::NotFound, "asset doesn't have a video stream");
+    };
+
+    stream.frame_at(position)
+}
+
+fn read_audio_metadata(reader: AssetReader) -> Result<AudioMetadata> {
+    let mut media = MediaInput::open(reader)?;
+    let Some(stream) = media.get_audio_stream()? else {
+        bail!(ErrorKind::NotFound, "asset doesn't have an audio stream");
+    };
+
+    Ok(stream.metadata().clone())
+}
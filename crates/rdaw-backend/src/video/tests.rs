@@ -0,0 +1,52 @@
+use rdaw_api::asset::AssetOperations;
+use rdaw_api::document::DocumentOperations;
+use rdaw_api::video::VideoOperations;
+use rdaw_api::{assert_err, ErrorKind, Result};
+use rdaw_core::path::Utf8PathBuf;
+use rdaw_core::time::RealTime;
+
+use crate::tests::run_test;
+
+fn audio_sample_path() -> Utf8PathBuf {
+    let mut path = Utf8PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    path.push("../rdaw-ffmpeg/tests/samples/220_Hz_sine_wave.ogg");
+    path
+}
+
+#[test]
+fn attach_audio_only_asset() -> Result<()> {
+    run_test(|client| async move {
+        let document_id = client.create_document().await?;
+        let arrangement_id = client.get_document_arrangement(document_id).await?;
+        let asset_id = client
+            .create_external_asset(document_id, audio_sample_path())
+            .await?;
+
+        let res = client
+            .attach_arrangement_video(arrangement_id, asset_id, RealTime::ZERO)
+            .await;
+        assert_err!(res, ErrorKind::NotFound);
+
+        assert_eq!(client.get_arrangement_video(arrangement_id).await?, None);
+
+        Ok(())
+    })
+}
+
+#[test]
+fn no_video() -> Result<()> {
+    run_test(|client| async move {
+        let document_id = client.create_document().await?;
+        let arrangement_id = client.get_document_arrangement(document_id).await?;
+
+        client.detach_arrangement_video(arrangement_id).await?;
+        assert_eq!(client.get_arrangement_video(arrangement_id).await?, None);
+
+        let res = client
+            .get_arrangement_video_frame(arrangement_id, RealTime::ZERO, 160, 90)
+            .await;
+        assert_err!(res, ErrorKind::NotFound);
+
+        Ok(())
+    })
+}
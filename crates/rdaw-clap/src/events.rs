@@ -0,0 +1,125 @@
+use std::ffi::c_void;
+use std::{mem, ptr};
+
+use clap_sys::events::{
+    clap_event_header, clap_event_note, clap_event_param_value, clap_input_events,
+    clap_output_events, CLAP_CORE_EVENT_SPACE_ID, CLAP_EVENT_NOTE_OFF, CLAP_EVENT_NOTE_ON,
+    CLAP_EVENT_PARAM_VALUE,
+};
+use rdaw_audio::buffer::{Event, EventKind};
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+union RawEvent {
+    header: clap_event_header,
+    note: clap_event_note,
+    param: clap_event_param_value,
+}
+
+/// Fixed capacity, so that pushing never allocates on the realtime thread.
+pub(crate) struct InputEvents {
+    events: Vec<RawEvent>,
+}
+
+impl InputEvents {
+    pub fn new(capacity: usize) -> InputEvents {
+        InputEvents {
+            events: Vec::with_capacity(capacity),
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.events.clear();
+    }
+
+    pub fn push_param(&mut self, param_id: u32, value: f64) -> bool {
+        self.push(RawEvent {
+            param: clap_event_param_value {
+                header: header::<clap_event_param_value>(0, CLAP_EVENT_PARAM_VALUE),
+                param_id,
+                cookie: ptr::null_mut(),
+                note_id: -1,
+                port_index: -1,
+                channel: -1,
+                key: -1,
+                value,
+            },
+        })
+    }
+
+    pub fn push_note(&mut self, event: &Event) -> bool {
+        let (ty, key, velocity) = match event.kind {
+            EventKind::NoteOn { key, velocity } => (CLAP_EVENT_NOTE_ON, key, velocity),
+            EventKind::NoteOff { key } => (CLAP_EVENT_NOTE_OFF, key, 0),
+        };
+
+        self.push(RawEvent {
+            note: clap_event_note {
+                header: header::<clap_event_note>(event.frame, ty),
+                note_id: -1,
+                port_index: 0,
+                channel: 0,
+                key: key.into(),
+                velocity: f64::from(velocity) / 127.0,
+            },
+        })
+    }
+
+    fn push(&mut self, event: RawEvent) -> bool {
+        if self.events.len() == self.events.capacity() {
+            return false;
+        }
+
+        self.events.push(event);
+        true
+    }
+
+    /// The list borrows `self`, and must not outlive it.
+    pub fn as_raw(&self) -> clap_input_events {
+        clap_input_events {
+            ctx: (self as *const InputEvents).cast_mut().cast(),
+            size: Some(input_events_size),
+            get: Some(input_events_get),
+        }
+    }
+}
+
+fn header<T>(time: u32, ty: u16) -> clap_event_header {
+    clap_event_header {
+        size: mem::size_of::<T>() as u32,
+        time,
+        space_id: CLAP_CORE_EVENT_SPACE_ID,
+        type_: ty,
+        flags: 0,
+    }
+}
+
+unsafe extern "C" fn input_events_size(list: *const clap_input_events) -> u32 {
+    let events = &*(*list).ctx.cast::<InputEvents>();
+    events.events.len() as u32
+}
+
+unsafe extern "C" fn input_events_get(
+    list: *const clap_input_events,
+    index: u32,
+) -> *const clap_event_header {
+    let events = &*(*list).ctx.cast::<InputEvents>();
+    match events.events.get(index as usize) {
+        Some(event) => &event.header,
+        None => ptr::null(),
+    }
+}
+
+pub(crate) fn discarded_output_events() -> clap_output_events {
+    clap_output_events {
+        ctx: ptr::null_mut::<c_void>(),
+        try_push: Some(output_events_try_push),
+    }
+}
+
+unsafe extern "C" fn output_events_try_push(
+    _list: *const clap_output_events,
+    _event: *const clap_event_header,
+) -> bool {
+    true
+}
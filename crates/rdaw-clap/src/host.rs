@@ -0,0 +1,33 @@
+use std::ffi::{c_char, c_void};
+use std::ptr;
+
+use clap_sys::host::clap_host;
+use clap_sys::version::CLAP_VERSION;
+
+/// No host extensions are provided yet.
+pub(crate) fn new_host() -> Box<clap_host> {
+    Box::new(clap_host {
+        clap_version: CLAP_VERSION,
+        host_data: ptr::null_mut(),
+        name: c"rdaw".as_ptr(),
+        vendor: c"rdaw".as_ptr(),
+        url: c"https://github.com/LeshaInc/rdaw".as_ptr(),
+        version: c"0.1.0".as_ptr(),
+        get_extension: Some(get_extension),
+        request_restart: Some(request_restart),
+        request_process: Some(request_process),
+        request_callback: Some(request_callback),
+    })
+}
+
+unsafe extern "C" fn get_extension(_host: *const clap_host, _id: *const c_char) -> *const c_void {
+    ptr::null()
+}
+
+unsafe extern "C" fn request_restart(_host: *const clap_host) {
+    tracing::debug!("plugin requested a restart");
+}
+
+unsafe extern "C" fn request_process(_host: *const clap_host) {}
+
+unsafe extern "C" fn request_callback(_host: *const clap_host) {}
@@ -0,0 +1,299 @@
+use std::ffi::{c_char, CStr, CString};
+use std::fmt;
+use std::mem::MaybeUninit;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+use clap_sys::ext::audio_ports::{
+    clap_audio_port_info, clap_plugin_audio_ports, CLAP_EXT_AUDIO_PORTS,
+};
+use clap_sys::ext::note_ports::{clap_plugin_note_ports, CLAP_EXT_NOTE_PORTS};
+use clap_sys::ext::params::{
+    clap_param_info, clap_plugin_params, CLAP_EXT_PARAMS, CLAP_PARAM_IS_HIDDEN,
+    CLAP_PARAM_IS_READONLY,
+};
+use clap_sys::host::clap_host;
+use clap_sys::plugin::clap_plugin;
+use clap_sys::process::{clap_process, clap_process_status, CLAP_PROCESS_ERROR};
+use rdaw_api::plugin::PluginParam;
+use rdaw_api::{bail, format_err, ErrorKind, Result};
+use rdaw_audio::graph::GraphParams;
+
+use crate::host::new_host;
+use crate::library::PluginLibrary;
+
+/// Created, activated and dropped on the thread owning the graph, processed on the realtime one.
+pub struct PluginInstance {
+    plugin: *const clap_plugin,
+    plugin_id: String,
+    params: Vec<PluginParam>,
+    audio_inputs: Vec<u32>,
+    audio_outputs: Vec<u32>,
+    has_note_input: bool,
+    activation: Mutex<Option<GraphParams>>,
+    is_processing: AtomicBool,
+    // both must outlive the plugin
+    _host: Box<clap_host>,
+    _library: Arc<PluginLibrary>,
+}
+
+// See the note on the threads above.
+unsafe impl Send for PluginInstance {}
+unsafe impl Sync for PluginInstance {}
+
+impl PluginInstance {
+    pub(crate) fn new(library: Arc<PluginLibrary>, plugin_id: &str) -> Result<PluginInstance> {
+        let c_id = CString::new(plugin_id)
+            .map_err(|_| format_err!(ErrorKind::NotSupported, "invalid plugin id `{plugin_id}`"))?;
+
+        let factory = unsafe { &*library.factory };
+        let Some(create_plugin) = factory.create_plugin else {
+            bail!(
+                ErrorKind::NotSupported,
+                "`{}` can't create plugins",
+                library.path()
+            );
+        };
+
+        let host = new_host();
+        let plugin = unsafe { create_plugin(library.factory, &*host, c_id.as_ptr()) };
+        if plugin.is_null() {
+            bail!(ErrorKind::Other, "failed to create plugin `{plugin_id}`");
+        }
+
+        // from here on `drop` destroys the plugin
+        let mut instance = PluginInstance {
+            plugin,
+            plugin_id: plugin_id.into(),
+            params: Vec::new(),
+            audio_inputs: Vec::new(),
+            audio_outputs: Vec::new(),
+            has_note_input: false,
+            activation: Mutex::new(None),
+            is_processing: AtomicBool::new(false),
+            _host: host,
+            _library: library,
+        };
+
+        let is_initialized = unsafe { (*plugin).init.is_some_and(|init| init(plugin)) };
+        if !is_initialized {
+            bail!(
+                ErrorKind::Other,
+                "failed to initialize plugin `{plugin_id}`"
+            );
+        }
+
+        instance.params = unsafe { instance.read_params() };
+        instance.audio_inputs = unsafe { instance.read_audio_ports(true) };
+        instance.audio_outputs = unsafe { instance.read_audio_ports(false) };
+        instance.has_note_input = unsafe { instance.read_num_note_inputs() } > 0;
+
+        Ok(instance)
+    }
+
+    pub fn plugin_id(&self) -> &str {
+        &self.plugin_id
+    }
+
+    /// Hidden and read-only parameters are left out.
+    pub fn params(&self) -> &[PluginParam] {
+        &self.params
+    }
+
+    pub fn num_audio_inputs(&self) -> usize {
+        self.audio_inputs.iter().map(|&v| v as usize).sum()
+    }
+
+    pub fn num_audio_outputs(&self) -> usize {
+        self.audio_outputs.iter().map(|&v| v as usize).sum()
+    }
+
+    pub(crate) fn audio_input_ports(&self) -> &[u32] {
+        &self.audio_inputs
+    }
+
+    pub(crate) fn audio_output_ports(&self) -> &[u32] {
+        &self.audio_outputs
+    }
+
+    pub fn has_note_input(&self) -> bool {
+        self.has_note_input
+    }
+
+    /// Can't reactivate once the plugin has started processing.
+    pub(crate) fn activate(&self, params: &GraphParams) -> Result<()> {
+        let mut activation = self.activation.lock().unwrap();
+
+        if *activation == Some(*params) {
+            return Ok(());
+        }
+
+        if self.is_processing.load(Ordering::Acquire) {
+            bail!(
+                ErrorKind::Busy,
+                "plugin `{}` can't be reactivated while processing",
+                self.plugin_id,
+            );
+        }
+
+        unsafe {
+            if activation.take().is_some() {
+                self.deactivate();
+            }
+
+            let is_activated = (*self.plugin).activate.is_some_and(|activate| {
+                activate(
+                    self.plugin,
+                    params.sample_rate.into(),
+                    1,
+                    params.buffer_size as u32,
+                )
+            });
+
+            if !is_activated {
+                bail!(
+                    ErrorKind::Other,
+                    "failed to activate plugin `{}`",
+                    self.plugin_id
+                );
+            }
+        }
+
+        *activation = Some(*params);
+
+        Ok(())
+    }
+
+    /// Returns `false` if the plugin refused.
+    pub(crate) fn start_processing(&self) -> bool {
+        if self.is_processing.load(Ordering::Acquire) {
+            return true;
+        }
+
+        let is_started = unsafe {
+            (*self.plugin)
+                .start_processing
+                .is_some_and(|start| start(self.plugin))
+        };
+
+        self.is_processing.store(is_started, Ordering::Release);
+        is_started
+    }
+
+    /// Must be called from the audio thread, after [`PluginInstance::start_processing`].
+    pub(crate) unsafe fn process(&self, process: &clap_process) -> clap_process_status {
+        match (*self.plugin).process {
+            Some(process_fn) => process_fn(self.plugin, process),
+            None => CLAP_PROCESS_ERROR,
+        }
+    }
+
+    unsafe fn deactivate(&self) {
+        if let Some(deactivate) = (*self.plugin).deactivate {
+            deactivate(self.plugin);
+        }
+    }
+
+    unsafe fn extension<T>(&self, id: &CStr) -> Option<&T> {
+        let get_extension = (*self.plugin).get_extension?;
+        let ptr = get_extension(self.plugin, id.as_ptr());
+        (!ptr.is_null()).then(|| &*ptr.cast::<T>())
+    }
+
+    unsafe fn read_params(&self) -> Vec<PluginParam> {
+        let Some(ext) = self.extension::<clap_plugin_params>(CLAP_EXT_PARAMS) else {
+            return Vec::new();
+        };
+
+        let (Some(count), Some(get_info)) = (ext.count, ext.get_info) else {
+            return Vec::new();
+        };
+
+        let mut params = Vec::new();
+
+        for idx in 0..count(self.plugin) {
+            let mut info = MaybeUninit::<clap_param_info>::zeroed();
+            if !get_info(self.plugin, idx, info.as_mut_ptr()) {
+                continue;
+            }
+
+            let info = info.assume_init();
+            if info.flags & (CLAP_PARAM_IS_HIDDEN | CLAP_PARAM_IS_READONLY) != 0 {
+                continue;
+            }
+
+            params.push(PluginParam {
+                id: info.id,
+                name: fixed_str(&info.name),
+                module: fixed_str(&info.module),
+                min: info.min_value,
+                max: info.max_value,
+                default: info.default_value,
+            });
+        }
+
+        params
+    }
+
+    unsafe fn read_audio_ports(&self, is_input: bool) -> Vec<u32> {
+        let Some(ext) = self.extension::<clap_plugin_audio_ports>(CLAP_EXT_AUDIO_PORTS) else {
+            return Vec::new();
+        };
+
+        let (Some(count), Some(get)) = (ext.count, ext.get) else {
+            return Vec::new();
+        };
+
+        (0..count(self.plugin, is_input))
+            .map(|idx| {
+                let mut info = MaybeUninit::<clap_audio_port_info>::zeroed();
+                if get(self.plugin, idx, is_input, info.as_mut_ptr()) {
+                    info.assume_init().channel_count
+                } else {
+                    0
+                }
+            })
+            .collect()
+    }
+
+    unsafe fn read_num_note_inputs(&self) -> u32 {
+        self.extension::<clap_plugin_note_ports>(CLAP_EXT_NOTE_PORTS)
+            .and_then(|ext| ext.count)
+            .map_or(0, |count| count(self.plugin, true))
+    }
+}
+
+impl fmt::Debug for PluginInstance {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PluginInstance")
+            .field("plugin_id", &self.plugin_id)
+            .field("params", &self.params.len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl Drop for PluginInstance {
+    fn drop(&mut self) {
+        unsafe {
+            if *self.is_processing.get_mut() {
+                if let Some(stop) = (*self.plugin).stop_processing {
+                    stop(self.plugin);
+                }
+            }
+
+            if self.activation.get_mut().unwrap().is_some() {
+                self.deactivate();
+            }
+
+            if let Some(destroy) = (*self.plugin).destroy {
+                destroy(self.plugin);
+            }
+        }
+    }
+}
+
+fn fixed_str(chars: &[c_char]) -> String {
+    let len = chars.iter().position(|&c| c == 0).unwrap_or(chars.len());
+    let bytes = chars[..len].iter().map(|&c| c as u8).collect::<Vec<_>>();
+    String::from_utf8_lossy(&bytes).into_owned()
+}
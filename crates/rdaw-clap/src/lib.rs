@@ -0,0 +1,9 @@
+mod events;
+mod host;
+mod instance;
+mod library;
+mod node;
+
+pub use self::instance::PluginInstance;
+pub use self::library::{default_search_paths, find_plugin_files, PluginLibrary};
+pub use self::node::{collect_garbage, PluginNode};
@@ -0,0 +1,264 @@
+use std::env;
+use std::ffi::{c_char, CStr, CString};
+use std::fmt;
+use std::fs;
+use std::sync::Arc;
+
+use clap_sys::entry::clap_plugin_entry;
+use clap_sys::factory::plugin_factory::{clap_plugin_factory, CLAP_PLUGIN_FACTORY_ID};
+use clap_sys::plugin::clap_plugin_descriptor;
+use clap_sys::version::clap_version_is_compatible;
+use libloading::Library;
+use rdaw_api::plugin::PluginDescriptor;
+use rdaw_api::{bail, format_err, ErrorKind, Result};
+use rdaw_core::path::{Utf8Path, Utf8PathBuf};
+
+use crate::instance::PluginInstance;
+
+/// Stays loaded while any of its instances are alive.
+pub struct PluginLibrary {
+    path: Utf8PathBuf,
+    entry: *const clap_plugin_entry,
+    pub(crate) factory: *const clap_plugin_factory,
+    descriptors: Vec<PluginDescriptor>,
+    // unloaded after `deinit` is called in `drop`
+    _library: Library,
+}
+
+// Functions of the entry and of the plugin factory are thread-safe.
+unsafe impl Send for PluginLibrary {}
+unsafe impl Sync for PluginLibrary {}
+
+impl PluginLibrary {
+    pub fn load(path: &Utf8Path) -> Result<Arc<PluginLibrary>> {
+        let library = unsafe { Library::new(path) }
+            .map_err(|e| format_err!(ErrorKind::Io, "failed to load `{path}`: {e}"))?;
+
+        let entry = unsafe { library.get::<*const clap_plugin_entry>(b"clap_entry\0") }
+            .map_err(|e| format_err!(ErrorKind::NotSupported, "`{path}` isn't a plugin: {e}"))?;
+        let entry = *entry;
+
+        let entry_ref = unsafe { &*entry };
+        if !clap_version_is_compatible(entry_ref.clap_version) {
+            bail!(
+                ErrorKind::UnknownVersion,
+                "`{path}` uses an unsupported CLAP version"
+            );
+        }
+
+        let c_path = CString::new(path.as_str())
+            .map_err(|_| format_err!(ErrorKind::NotSupported, "invalid path `{path}`"))?;
+
+        let (Some(init), Some(get_factory)) = (entry_ref.init, entry_ref.get_factory) else {
+            bail!(ErrorKind::NotSupported, "`{path}` has an incomplete entry");
+        };
+
+        if !unsafe { init(c_path.as_ptr()) } {
+            bail!(ErrorKind::Other, "`{path}` failed to initialize");
+        }
+
+        // from here on `drop` calls `deinit`
+        let mut library = PluginLibrary {
+            path: path.to_owned(),
+            entry,
+            factory: std::ptr::null(),
+            descriptors: Vec::new(),
+            _library: library,
+        };
+
+        let factory = unsafe { get_factory(CLAP_PLUGIN_FACTORY_ID.as_ptr()) };
+        if factory.is_null() {
+            bail!(ErrorKind::NotSupported, "`{path}` has no plugin factory");
+        }
+
+        library.factory = factory.cast();
+        library.descriptors = library.read_descriptors();
+
+        Ok(Arc::new(library))
+    }
+
+    pub fn path(&self) -> &Utf8Path {
+        &self.path
+    }
+
+    pub fn descriptors(&self) -> &[PluginDescriptor] {
+        &self.descriptors
+    }
+
+    pub fn descriptor(&self, plugin_id: &str) -> Option<&PluginDescriptor> {
+        self.descriptors.iter().find(|v| v.id == plugin_id)
+    }
+
+    pub fn instantiate(self: &Arc<Self>, plugin_id: &str) -> Result<PluginInstance> {
+        if self.descriptor(plugin_id).is_none() {
+            bail!(
+                ErrorKind::NotFound,
+                "`{}` has no plugin `{plugin_id}`",
+                self.path
+            );
+        }
+
+        PluginInstance::new(self.clone(), plugin_id)
+    }
+
+    fn read_descriptors(&self) -> Vec<PluginDescriptor> {
+        let factory = unsafe { &*self.factory };
+        let (Some(get_plugin_count), Some(get_plugin_descriptor)) =
+            (factory.get_plugin_count, factory.get_plugin_descriptor)
+        else {
+            return Vec::new();
+        };
+
+        let count = unsafe { get_plugin_count(self.factory) };
+
+        (0..count)
+            .filter_map(|idx| {
+                let desc = unsafe { get_plugin_descriptor(self.factory, idx) };
+                if desc.is_null() {
+                    return None;
+                }
+
+                let desc = unsafe { self.convert_descriptor(&*desc) };
+                if desc.id.is_empty() {
+                    tracing::warn!(path = %self.path, "plugin without an id");
+                    return None;
+                }
+
+                Some(desc)
+            })
+            .collect()
+    }
+
+    unsafe fn convert_descriptor(&self, desc: &clap_plugin_descriptor) -> PluginDescriptor {
+        let mut features = Vec::new();
+
+        if !desc.features.is_null() {
+            let mut ptr = desc.features;
+            while !(*ptr).is_null() {
+                features.push(c_str(*ptr));
+                ptr = ptr.add(1);
+            }
+        }
+
+        PluginDescriptor {
+            id: c_str(desc.id),
+            name: c_str(desc.name),
+            vendor: c_str(desc.vendor),
+            version: c_str(desc.version),
+            description: c_str(desc.description),
+            features,
+            path: self.path.clone(),
+        }
+    }
+}
+
+impl fmt::Debug for PluginLibrary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PluginLibrary")
+            .field("path", &self.path)
+            .field("descriptors", &self.descriptors)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Drop for PluginLibrary {
+    fn drop(&mut self) {
+        if let Some(deinit) = unsafe { (*self.entry).deinit } {
+            unsafe { deinit() };
+        }
+    }
+}
+
+pub(crate) unsafe fn c_str(ptr: *const c_char) -> String {
+    if ptr.is_null() {
+        return String::new();
+    }
+
+    CStr::from_ptr(ptr).to_string_lossy().into_owned()
+}
+
+pub fn default_search_paths() -> Vec<Utf8PathBuf> {
+    let mut paths = Vec::new();
+
+    if let Some(var) = env::var_os("CLAP_PATH") {
+        paths.extend(env::split_paths(&var).filter_map(|v| Utf8PathBuf::from_path_buf(v).ok()));
+    }
+
+    let var = |name: &str| env::var(name).ok().map(Utf8PathBuf::from);
+
+    if cfg!(target_os = "windows") {
+        paths.extend(var("COMMONPROGRAMFILES").map(|v| v.join("CLAP")));
+        paths.extend(var("LOCALAPPDATA").map(|v| v.join("Programs/Common/CLAP")));
+    } else if cfg!(target_os = "macos") {
+        paths.extend(var("HOME").map(|v| v.join("Library/Audio/Plug-Ins/CLAP")));
+        paths.push("/Library/Audio/Plug-Ins/CLAP".into());
+    } else {
+        paths.extend(var("HOME").map(|v| v.join(".clap")));
+        paths.push("/usr/lib/clap".into());
+    }
+
+    paths
+}
+
+pub fn find_plugin_files(paths: &[Utf8PathBuf]) -> Vec<Utf8PathBuf> {
+    let mut files = Vec::new();
+    let mut stack = paths.to_vec();
+
+    while let Some(dir) = stack.pop() {
+        let entries = match fs::read_dir(&dir) {
+            Ok(v) => v,
+            Err(error) => {
+                tracing::debug!(%dir, %error, "skipping plugin directory");
+                continue;
+            }
+        };
+
+        for entry in entries.flatten() {
+            let Ok(path) = Utf8PathBuf::from_path_buf(entry.path()) else {
+                continue;
+            };
+
+            if path.extension() == Some("clap") {
+                files.push(path);
+            } else if entry.file_type().is_ok_and(|v| v.is_dir()) {
+                stack.push(path);
+            }
+        }
+    }
+
+    files.sort();
+    files
+}
+
+#[cfg(test)]
+mod tests {
+    use rdaw_api::{assert_err, ErrorKind};
+    use rdaw_core::path::Utf8PathBuf;
+
+    use super::{find_plugin_files, PluginLibrary};
+
+    #[test]
+    fn find_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let dir = Utf8PathBuf::from_path_buf(dir.path().to_owned()).unwrap();
+
+        std::fs::create_dir(dir.join("vendor")).unwrap();
+        std::fs::write(dir.join("a.clap"), "").unwrap();
+        std::fs::write(dir.join("vendor/b.clap"), "").unwrap();
+        std::fs::write(dir.join("readme.txt"), "").unwrap();
+
+        let files = find_plugin_files(&[dir.clone(), dir.join("missing")]);
+        assert_eq!(files, [dir.join("a.clap"), dir.join("vendor/b.clap")]);
+    }
+
+    #[test]
+    fn load_invalid() {
+        let dir = tempfile::tempdir().unwrap();
+        let dir = Utf8PathBuf::from_path_buf(dir.path().to_owned()).unwrap();
+
+        let path = dir.join("broken.clap");
+        std::fs::write(&path, "not a library").unwrap();
+
+        assert_err!(PluginLibrary::load(&path), ErrorKind::Io);
+    }
+}
@@ -0,0 +1,243 @@
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, ThreadId};
+use std::{mem, ptr};
+
+use clap_sys::audio_buffer::clap_audio_buffer;
+use clap_sys::process::{clap_process, CLAP_PROCESS_ERROR};
+use rdaw_audio::buffer::SilentHint;
+use rdaw_audio::graph::{CompiledNode, GraphParams, Inputs, Node, Outputs, MAX_EVENTS_PER_BLOCK};
+
+use crate::events::{discarded_output_events, InputEvents};
+use crate::instance::PluginInstance;
+
+/// Every channel of the plugin's audio ports is a separate graph port.
+#[derive(Debug, Clone)]
+pub struct PluginNode {
+    shared: Arc<Shared>,
+}
+
+/// Plugins dropped by compiled nodes on other threads, see [`collect_garbage`].
+static GRAVEYARD: Mutex<Vec<Arc<Shared>>> = Mutex::new(Vec::new());
+
+#[derive(Debug)]
+struct Shared {
+    instance: PluginInstance,
+    /// Thread the plugin was created on, the only one allowed to destroy it.
+    main_thread: ThreadId,
+    /// Bits of `f64`.
+    values: Box<[AtomicU64]>,
+    changed: Box<[AtomicBool]>,
+}
+
+impl PluginNode {
+    pub fn new(instance: PluginInstance) -> PluginNode {
+        let values = instance
+            .params()
+            .iter()
+            .map(|param| AtomicU64::new(param.default.to_bits()))
+            .collect();
+
+        let changed = instance
+            .params()
+            .iter()
+            .map(|_| AtomicBool::new(false))
+            .collect();
+
+        PluginNode {
+            shared: Arc::new(Shared {
+                instance,
+                main_thread: thread::current().id(),
+                values,
+                changed,
+            }),
+        }
+    }
+
+    pub fn instance(&self) -> &PluginInstance {
+        &self.shared.instance
+    }
+
+    pub fn set_param(&self, param_id: u32, value: f64) {
+        let params = self.shared.instance.params();
+        let Some(idx) = params.iter().position(|param| param.id == param_id) else {
+            return;
+        };
+
+        let value = value.clamp(params[idx].min, params[idx].max);
+        self.shared.values[idx].store(value.to_bits(), Ordering::Release);
+        self.shared.changed[idx].store(true, Ordering::Release);
+    }
+}
+
+impl Node for PluginNode {
+    fn num_audio_inputs(&self) -> usize {
+        self.shared.instance.num_audio_inputs()
+    }
+
+    fn num_audio_outputs(&self) -> usize {
+        self.shared.instance.num_audio_outputs()
+    }
+
+    fn num_event_inputs(&self) -> usize {
+        usize::from(self.shared.instance.has_note_input())
+    }
+
+    fn compile(&self, params: &GraphParams) -> Box<dyn CompiledNode> {
+        let is_active = match self.shared.instance.activate(params) {
+            Ok(()) => true,
+            Err(error) => {
+                tracing::error!(?error, "plugin will output silence");
+                false
+            }
+        };
+
+        Box::new(CompiledPluginNode::new(self.shared.clone(), is_active))
+    }
+}
+
+struct CompiledPluginNode {
+    shared: Arc<Shared>,
+    is_active: bool,
+    steady_time: i64,
+    events: InputEvents,
+    input_buffers: Vec<clap_audio_buffer>,
+    output_buffers: Vec<clap_audio_buffer>,
+    input_channels: Vec<*mut f32>,
+    output_channels: Vec<*mut f32>,
+}
+
+// The pointers are only set and used during `process`.
+unsafe impl Send for CompiledPluginNode {}
+
+impl CompiledPluginNode {
+    fn new(shared: Arc<Shared>, is_active: bool) -> CompiledPluginNode {
+        let instance = &shared.instance;
+        let empty_buffer = clap_audio_buffer {
+            data32: ptr::null_mut(),
+            data64: ptr::null_mut(),
+            channel_count: 0,
+            latency: 0,
+            constant_mask: 0,
+        };
+
+        CompiledPluginNode {
+            is_active,
+            steady_time: 0,
+            events: InputEvents::new(MAX_EVENTS_PER_BLOCK + instance.params().len()),
+            input_buffers: vec![empty_buffer; instance.audio_input_ports().len()],
+            output_buffers: vec![empty_buffer; instance.audio_output_ports().len()],
+            input_channels: Vec::with_capacity(instance.num_audio_inputs()),
+            output_channels: Vec::with_capacity(instance.num_audio_outputs()),
+            shared,
+        }
+    }
+
+    fn collect_events(&mut self, inputs: &Inputs<'_>) {
+        self.events.clear();
+
+        // parameter changes go first, all of them happen at the start of the block
+        let params = self.shared.instance.params();
+        for (idx, changed) in self.shared.changed.iter().enumerate() {
+            if changed.swap(false, Ordering::AcqRel) {
+                let value = f64::from_bits(self.shared.values[idx].load(Ordering::Acquire));
+                self.events.push_param(params[idx].id, value);
+            }
+        }
+
+        for event in inputs.events.iter().flat_map(|v| v.iter()) {
+            self.events.push_note(event);
+        }
+    }
+}
+
+impl CompiledNode for CompiledPluginNode {
+    fn process(&mut self, params: &GraphParams, inputs: Inputs<'_>, outputs: Outputs<'_>) {
+        if !self.is_active || !self.shared.instance.start_processing() {
+            for output in outputs.audio.iter_mut() {
+                output.clear();
+            }
+
+            return;
+        }
+
+        self.collect_events(&inputs);
+
+        // inputs are never written to, as the plugin isn't told that any of them are in-place
+        self.input_channels.clear();
+        self.input_channels
+            .extend(inputs.audio.iter().map(|v| v.as_ptr().cast_mut()));
+        fill_buffers(
+            &mut self.input_buffers,
+            self.shared.instance.audio_input_ports(),
+            &mut self.input_channels,
+        );
+
+        self.output_channels.clear();
+        self.output_channels
+            .extend(outputs.audio.iter_mut().map(|v| v.as_mut_ptr()));
+        fill_buffers(
+            &mut self.output_buffers,
+            self.shared.instance.audio_output_ports(),
+            &mut self.output_channels,
+        );
+
+        let in_events = self.events.as_raw();
+        let out_events = discarded_output_events();
+
+        let process = clap_process {
+            steady_time: self.steady_time,
+            frames_count: params.buffer_size as u32,
+            transport: ptr::null(),
+            audio_inputs: self.input_buffers.as_ptr(),
+            audio_outputs: self.output_buffers.as_mut_ptr(),
+            audio_inputs_count: self.input_buffers.len() as u32,
+            audio_outputs_count: self.output_buffers.len() as u32,
+            in_events: &in_events,
+            out_events: &out_events,
+        };
+
+        let status = unsafe { self.shared.instance.process(&process) };
+        self.steady_time += params.buffer_size as i64;
+
+        for output in outputs.audio.iter_mut() {
+            if status == CLAP_PROCESS_ERROR {
+                output.clear();
+            } else {
+                output.silent_hint = SilentHint::Unspecified;
+            }
+        }
+    }
+}
+
+impl Drop for CompiledPluginNode {
+    fn drop(&mut self) {
+        // this may be the last reference, and plugins can't be destroyed on the audio thread
+        if thread::current().id() != self.shared.main_thread {
+            GRAVEYARD.lock().unwrap().push(self.shared.clone());
+        }
+    }
+}
+
+/// Should be called regularly on the thread plugins are created on.
+pub fn collect_garbage() {
+    let current = thread::current().id();
+    let dropped = mem::take(&mut *GRAVEYARD.lock().unwrap());
+
+    let (own, other): (Vec<_>, Vec<_>) = dropped
+        .into_iter()
+        .partition(|shared| shared.main_thread == current);
+
+    GRAVEYARD.lock().unwrap().extend(other);
+    drop(own);
+}
+
+fn fill_buffers(buffers: &mut [clap_audio_buffer], ports: &[u32], channels: &mut [*mut f32]) {
+    let mut offset = 0;
+
+    for (buffer, &channel_count) in buffers.iter_mut().zip(ports) {
+        buffer.data32 = channels[offset..].as_mut_ptr();
+        buffer.channel_count = channel_count;
+        offset += channel_count as usize;
+    }
+}
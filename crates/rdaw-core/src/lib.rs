@@ -1,5 +1,6 @@
 pub mod collections;
 pub mod path;
+pub mod random;
 pub mod sync;
 pub mod time;
 
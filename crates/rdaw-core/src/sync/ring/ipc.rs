@@ -13,7 +13,7 @@ use loom::sync::atomic::Ordering::{AcqRel, Acquire, Relaxed};
 #[cfg(loom)]
 use loom::sync::atomic::{AtomicBool, AtomicU8, AtomicUsize};
 
-use super::{Buffer, Consumer, Producer};
+use super::{check_capacity, Buffer, CapacityError, Consumer, Producer};
 use crate::sync::{IpcSafe, SharedMemory};
 
 pub type IpcProducer<T, U = ()> = Producer<T, U, IpcBuffer<T, U>>;
@@ -27,7 +27,8 @@ pub struct IpcRing<T, U> {
 impl<T: IpcSafe, U: IpcSafe> IpcRing<T, U> {
     /// Creates an IPC ring buffer with a specified ID prefix.
     ///
-    /// The rest of the ID will be randomly generated.
+    /// The rest of the ID will be randomly generated. Fails with [`io::ErrorKind::InvalidInput`]
+    /// if `capacity` isn't valid, see [`check_capacity`].
     pub fn create(prefix: &str, capacity: usize, userdata: U) -> io::Result<Self> {
         let buffer = IpcBuffer::create(prefix, capacity, userdata)?;
         Ok(Self { buffer })
@@ -35,6 +36,9 @@ impl<T: IpcSafe, U: IpcSafe> IpcRing<T, U> {
 
     /// Opens an ring buffer by ID.
     ///
+    /// The handshake stored in shared memory is validated against `T` and `U`. On mismatch, fails
+    /// with [`io::ErrorKind::InvalidData`] wrapping a [`HandshakeError`].
+    ///
     /// # Safety
     ///
     /// ID must be obtained by [`IpcRing::id`]
@@ -84,9 +88,111 @@ impl<T: IpcSafe, U: IpcSafe> IpcRing<T, U> {
     }
 }
 
+/// Version of the shared memory layout, bumped on every incompatible change.
+const PROTOCOL_VERSION: u32 = 1;
+
+const MAGIC: [u8; 8] = *b"RDAWRING";
+
+/// Description of the buffer layout, stored at the very beginning of the shared segment.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+struct Handshake {
+    magic: [u8; 8],
+    version: u32,
+    elem_size: u32,
+    elem_align: u32,
+    userdata_size: u32,
+    type_hash: u64,
+    capacity: u64,
+}
+
+impl Handshake {
+    fn new<T, U>(capacity: usize) -> Handshake {
+        Handshake {
+            magic: MAGIC,
+            version: PROTOCOL_VERSION,
+            elem_size: std::mem::size_of::<T>() as u32,
+            elem_align: std::mem::align_of::<T>() as u32,
+            userdata_size: std::mem::size_of::<U>() as u32,
+            type_hash: type_hash::<T, U>(),
+            capacity: capacity as u64,
+        }
+    }
+
+    fn validate<T, U>(&self, shm_size: usize) -> Result<usize, HandshakeError> {
+        if self.magic != MAGIC {
+            return Err(HandshakeError::InvalidMagic);
+        }
+
+        if self.version != PROTOCOL_VERSION {
+            return Err(HandshakeError::VersionMismatch {
+                expected: PROTOCOL_VERSION,
+                found: self.version,
+            });
+        }
+
+        let expected = Handshake::new::<T, U>(0);
+        if self.elem_size != expected.elem_size
+            || self.elem_align != expected.elem_align
+            || self.userdata_size != expected.userdata_size
+            || self.type_hash != expected.type_hash
+        {
+            return Err(HandshakeError::TypeMismatch);
+        }
+
+        let capacity = usize::try_from(self.capacity)
+            .map_err(|_| CapacityError {
+                capacity: usize::MAX,
+            })
+            .and_then(check_capacity)?;
+
+        let required = Layout::array::<T>(capacity)
+            .ok()
+            .and_then(|array| Layout::new::<Header<U>>().extend(array).ok())
+            .map(|(layout, _)| layout.size())
+            .ok_or(CapacityError { capacity })?;
+
+        if shm_size < required {
+            return Err(HandshakeError::SegmentTooSmall {
+                size: shm_size,
+                required,
+            });
+        }
+
+        Ok(capacity)
+    }
+}
+
+/// FNV-1a hash of the element and userdata type names.
+fn type_hash<T, U>() -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    let names = [std::any::type_name::<T>(), std::any::type_name::<U>()];
+    for byte in names.iter().flat_map(|name| name.bytes().chain([0])) {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
+
+/// Error returned when an opened IPC ring buffer doesn't match the expected layout.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, thiserror::Error)]
+pub enum HandshakeError {
+    #[error("shared memory segment doesn't contain an IPC ring buffer")]
+    InvalidMagic,
+    #[error("IPC ring buffer protocol version mismatch: expected {expected}, found {found}")]
+    VersionMismatch { expected: u32, found: u32 },
+    #[error("IPC ring buffer element or userdata type mismatch")]
+    TypeMismatch,
+    #[error(transparent)]
+    InvalidCapacity(#[from] CapacityError),
+    #[error("shared memory segment is too small: {size} bytes, {required} required")]
+    SegmentTooSmall { size: usize, required: usize },
+}
+
+#[repr(C)]
 struct Header<U> {
+    handshake: Handshake,
     userdata: U,
-    capacity: usize,
     read_state: CachePadded<AtomicUsize>,
     write_state: CachePadded<AtomicUsize>,
     refcount: AtomicU8,
@@ -97,6 +203,7 @@ struct Header<U> {
 /// Ring buffer stored in shared memory
 pub struct IpcBuffer<T, U> {
     shm: SharedMemory,
+    capacity: usize,
     marker: PhantomData<(T, U)>,
 }
 
@@ -105,6 +212,8 @@ unsafe impl<T: Send + Sync, U: Send + Sync> Sync for IpcBuffer<T, U> {}
 
 impl<T: IpcSafe, U: IpcSafe> IpcBuffer<T, U> {
     fn create(prefix: &str, capacity: usize, userdata: U) -> io::Result<Self> {
+        check_capacity(capacity).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+
         let layout = Self::layout(capacity);
         let shm = SharedMemory::create(prefix, layout.size())?;
 
@@ -114,8 +223,8 @@ impl<T: IpcSafe, U: IpcSafe> IpcBuffer<T, U> {
         );
 
         let header = Header {
+            handshake: Handshake::new::<T, U>(capacity),
             userdata,
-            capacity,
             read_state: CachePadded::new(AtomicUsize::new(0)),
             write_state: CachePadded::new(AtomicUsize::new(0)),
             refcount: AtomicU8::new(0),
@@ -128,14 +237,31 @@ impl<T: IpcSafe, U: IpcSafe> IpcBuffer<T, U> {
 
         Ok(Self {
             shm,
+            capacity,
             marker: PhantomData,
         })
     }
 
     unsafe fn open(id: &str) -> io::Result<Self> {
         let shm = SharedMemory::open(id)?;
+
+        if shm.size() < std::mem::size_of::<Handshake>() {
+            let error = HandshakeError::SegmentTooSmall {
+                size: shm.size(),
+                required: std::mem::size_of::<Handshake>(),
+            };
+            return Err(io::Error::new(io::ErrorKind::InvalidData, error));
+        }
+
+        // SAFETY: `Handshake` is at offset 0 since `Header` is `repr(C)`, segment is big enough.
+        let handshake = unsafe { std::ptr::read(shm.as_ptr() as *const Handshake) };
+        let capacity = handshake
+            .validate::<T, U>(shm.size())
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
         Ok(Self {
             shm,
+            capacity,
             marker: PhantomData,
         })
     }
@@ -181,7 +307,7 @@ unsafe impl<T: IpcSafe, U: IpcSafe> Buffer<T, U> for IpcBuffer<T, U> {
     }
 
     fn capacity(&self) -> usize {
-        self.header().capacity
+        self.capacity
     }
 
     fn read_state(&self) -> &AtomicUsize {
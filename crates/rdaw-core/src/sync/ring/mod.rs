@@ -15,33 +15,47 @@ use loom::sync::atomic::Ordering::{Acquire, Relaxed, Release};
 #[cfg(loom)]
 use loom::sync::atomic::{fence, AtomicUsize};
 
-pub use self::ipc::{IpcBuffer, IpcConsumer, IpcProducer, IpcRing};
+pub use self::ipc::{HandshakeError, IpcBuffer, IpcConsumer, IpcProducer, IpcRing};
 pub use self::local::LocalBuffer;
 
 /// Creates a lock-free SPSC ring buffer.
 ///
-/// `capacity` must be a power of 2 between `1` and `usize::MAX / 2`.
+/// # Panics
+///
+/// Panics if `capacity` isn't a power of 2 between `1` and `usize::MAX / 2`. See [`try_buffer`].
 pub fn buffer<T>(capacity: usize) -> (Producer<T>, Consumer<T>) {
-    let buffer = ManuallyDrop::new(LocalBuffer::new(capacity, ()));
-
-    // SAFETY: pointer is valid since we got it from a reference, `ManuallyDrop` will prevent double
-    // drop. Inside, `ProcessLocalBuffer` is just a pointer, so it's fine to copy it.
-    let buffer_copy = unsafe { std::ptr::read(&buffer) };
-
-    // SAFETY: a newly created buffer will satisfy `read_state == write_state == 0`.
-    let producer = unsafe { Producer::new(buffer_copy) };
-    let consumer = unsafe { Consumer::new(buffer) };
-
-    (producer, consumer)
+    buffer_with_userdata(capacity, ())
 }
 
 /// Creates a lock-free SPSC ring buffer with the specified userdata.
 ///
-/// `capacity` must be a power of 2 between `1` and `usize::MAX / 2`.
+/// # Panics
+///
+/// Panics if `capacity` isn't a power of 2 between `1` and `usize::MAX / 2`. See
+/// [`try_buffer_with_userdata`].
 pub fn buffer_with_userdata<T, U>(
     capacity: usize,
     userdata: U,
 ) -> (Producer<T, U>, Consumer<T, U>) {
+    match try_buffer_with_userdata(capacity, userdata) {
+        Ok(v) => v,
+        Err(e) => panic!("{e}"),
+    }
+}
+
+/// Creates a lock-free SPSC ring buffer, or fails if `capacity` isn't valid.
+pub fn try_buffer<T>(capacity: usize) -> Result<(Producer<T>, Consumer<T>), CapacityError> {
+    try_buffer_with_userdata(capacity, ())
+}
+
+/// Creates a lock-free SPSC ring buffer with the specified userdata, or fails if `capacity` isn't
+/// valid.
+pub fn try_buffer_with_userdata<T, U>(
+    capacity: usize,
+    userdata: U,
+) -> Result<(Producer<T, U>, Consumer<T, U>), CapacityError> {
+    check_capacity(capacity)?;
+
     let buffer = ManuallyDrop::new(LocalBuffer::new(capacity, userdata));
 
     // SAFETY: pointer is valid since we got it from a reference, `ManuallyDrop` will prevent double
@@ -52,7 +66,32 @@ pub fn buffer_with_userdata<T, U>(
     let producer = unsafe { Producer::new(buffer_copy) };
     let consumer = unsafe { Consumer::new(buffer) };
 
-    (producer, consumer)
+    Ok((producer, consumer))
+}
+
+/// Returns `capacity` if a ring buffer can have it, which is a power of 2 between `1` and
+/// `usize::MAX / 2`.
+pub fn check_capacity(capacity: usize) -> Result<usize, CapacityError> {
+    if capacity > 0 && capacity <= INDEX_MASK && capacity.is_power_of_two() {
+        Ok(capacity)
+    } else {
+        Err(CapacityError { capacity })
+    }
+}
+
+/// Returns the smallest valid capacity which fits at least `len` items.
+pub fn capacity_at_least(len: usize) -> Result<usize, CapacityError> {
+    len.max(1)
+        .checked_next_power_of_two()
+        .ok_or(CapacityError { capacity: len })
+        .and_then(check_capacity)
+}
+
+/// Error returned when a ring buffer can't have the requested capacity.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, thiserror::Error)]
+#[error("ring buffer capacity {capacity} isn't a power of 2 between 1 and usize::MAX / 2")]
+pub struct CapacityError {
+    pub capacity: usize,
 }
 
 /// Underlying storage of the ring buffer.
@@ -589,6 +628,22 @@ mod tests {
 
     use super::*;
 
+    #[test]
+    #[cfg(not(loom))]
+    fn capacity() {
+        assert!(try_buffer::<u32>(0).is_err());
+        assert_eq!(
+            try_buffer::<u32>(12).err(),
+            Some(CapacityError { capacity: 12 })
+        );
+        assert!(try_buffer::<u32>(16).is_ok());
+
+        assert_eq!(capacity_at_least(0), Ok(1));
+        assert_eq!(capacity_at_least(12), Ok(16));
+        assert_eq!(capacity_at_least(16), Ok(16));
+        assert!(capacity_at_least(INDEX_MASK).is_err());
+    }
+
     #[test]
     #[cfg(not(loom))]
     fn sequential_copy() {
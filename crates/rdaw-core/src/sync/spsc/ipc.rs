@@ -23,7 +23,8 @@ pub struct IpcChannel<T> {
 impl<T: IpcSafe> IpcChannel<T> {
     /// Creates an IPC SPSC channel with a specified ID prefix.
     ///
-    /// The rest of the ID will be randomly generated.
+    /// The rest of the ID will be randomly generated. Fails with [`io::ErrorKind::InvalidInput`]
+    /// if `capacity` isn't valid, see [`check_capacity`](crate::sync::ring::check_capacity).
     pub fn create(prefix: &str, capacity: usize) -> io::Result<Self> {
         let state = SharedState {
             sender_waiting_len: CachePadded::new(AtomicUsize::new(0)),
@@ -37,6 +38,9 @@ impl<T: IpcSafe> IpcChannel<T> {
 
     /// Opens an IPC SPSC channel by ID.
     ///
+    /// Fails with [`io::ErrorKind::InvalidData`] if the channel was created with a different
+    /// element type or protocol version, see [`HandshakeError`](crate::sync::ring::HandshakeError).
+    ///
     /// # Safety
     ///
     /// ID must be obtained by [`IpcChannel::id`]
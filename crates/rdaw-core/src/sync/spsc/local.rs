@@ -17,11 +17,15 @@ use loom::sync::atomic::{fence, AtomicUsize};
 use loom::thread::{self, Thread};
 
 use super::{Closed, RawReceiver, RawSender, TryRecvError, TrySendError};
-use crate::sync::ring::{buffer_with_userdata, Consumer, PopError, Producer, PushError};
-
-pub fn channel<T>(capacity: usize) -> (RawLocalSender<T>, RawLocalReceiver<T>) {
-    let (producer, consumer) = buffer_with_userdata(capacity, SharedState::default());
-    (RawLocalSender { producer }, RawLocalReceiver { consumer })
+use crate::sync::ring::{
+    try_buffer_with_userdata, CapacityError, Consumer, PopError, Producer, PushError,
+};
+
+pub fn try_channel<T>(
+    capacity: usize,
+) -> Result<(RawLocalSender<T>, RawLocalReceiver<T>), CapacityError> {
+    let (producer, consumer) = try_buffer_with_userdata(capacity, SharedState::default())?;
+    Ok((RawLocalSender { producer }, RawLocalReceiver { consumer }))
 }
 
 #[derive(Default)]
@@ -13,10 +13,26 @@ use futures::Stream;
 
 pub use self::ipc::{IpcChannel, IpcReceiver, IpcSender, RawIpcReceiver, RawIpcSender};
 pub use self::local::{RawLocalReceiver, RawLocalSender};
+use crate::sync::ring::CapacityError;
 
+/// Creates a channel which can hold `capacity` values.
+///
+/// # Panics
+///
+/// Panics if `capacity` isn't a power of 2 between `1` and `usize::MAX / 2`. See [`try_channel`].
 pub fn channel<T>(capacity: usize) -> (Sender<T>, Receiver<T>) {
-    let (raw_sender, raw_receiver) = self::local::channel(capacity);
-    (
+    match try_channel(capacity) {
+        Ok(v) => v,
+        Err(e) => panic!("{e}"),
+    }
+}
+
+/// Creates a channel which can hold `capacity` values, or fails if `capacity` isn't valid.
+///
+/// Use [`ring::capacity_at_least`](crate::sync::ring::capacity_at_least) to round a length up.
+pub fn try_channel<T>(capacity: usize) -> Result<(Sender<T>, Receiver<T>), CapacityError> {
+    let (raw_sender, raw_receiver) = self::local::try_channel(capacity)?;
+    Ok((
         Sender {
             raw: raw_sender,
             marker: PhantomData,
@@ -25,7 +41,7 @@ pub fn channel<T>(capacity: usize) -> (Sender<T>, Receiver<T>) {
             raw: raw_receiver,
             marker: PhantomData,
         },
-    )
+    ))
 }
 
 pub trait RawSender<T> {
@@ -490,6 +506,16 @@ mod tests {
 
     use super::*;
 
+    #[test]
+    #[cfg(not(loom))]
+    fn capacity() {
+        assert_eq!(
+            try_channel::<u8>(3).err(),
+            Some(CapacityError { capacity: 3 })
+        );
+        assert!(try_channel::<u8>(4).is_ok());
+    }
+
     #[test]
     #[cfg(not(loom))]
     fn seq() {
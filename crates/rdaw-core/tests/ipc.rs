@@ -1,7 +1,10 @@
 use std::env;
+use std::io;
 use std::process::{Child, Command};
 
+use rdaw_core::sync::ring::HandshakeError;
 use rdaw_core::sync::spsc::IpcChannel;
+use rdaw_core::sync::SharedMemory;
 
 enum ProgramKind {
     SenderServer,
@@ -58,9 +61,32 @@ fn main_default() {
     println!("running receiver server");
     wait(spawn(ProgramKind::ReceiverServer));
 
+    println!("running handshake mismatch");
+    main_handshake_mismatch();
+
     println!();
 }
 
+fn assert_handshake_error(error: io::Error, expected: HandshakeError) {
+    assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    let inner = error.into_inner().unwrap();
+    assert_eq!(*inner.downcast::<HandshakeError>().unwrap(), expected);
+}
+
+fn main_handshake_mismatch() {
+    let channel = IpcChannel::<u8>::create("test", 128).unwrap();
+    let Err(error) = (unsafe { IpcChannel::<f32>::open(channel.id()) }) else {
+        panic!("opened a channel with a different element type");
+    };
+    assert_handshake_error(error, HandshakeError::TypeMismatch);
+
+    let shm = SharedMemory::create("test", 4096).unwrap();
+    let Err(error) = (unsafe { IpcChannel::<u8>::open(shm.id()) }) else {
+        panic!("opened a zeroed segment as a channel");
+    };
+    assert_handshake_error(error, HandshakeError::InvalidMagic);
+}
+
 fn main_sender_server() {
     let channel = IpcChannel::<u8>::create("test", 128).unwrap();
     let child = spawn_env(ProgramKind::ReceiverClient, "CHANNEL_ID", channel.id());
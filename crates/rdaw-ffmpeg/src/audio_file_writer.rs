@@ -0,0 +1,265 @@
+use std::ffi::CString;
+
+use ffmpeg_sys_next as ffi;
+use rdaw_api::export::{ExportFormat, ExportSampleFormat};
+use rdaw_api::{bail, format_err, ErrorKind, Result};
+use rdaw_core::path::Utf8Path;
+
+use crate::internal::encoder::{Encoder, EncoderConfig};
+use crate::internal::error::ErrorKind as FfmpegErrorKind;
+use crate::internal::frame::Frame;
+use crate::internal::init;
+use crate::internal::output::OutputContext;
+use crate::internal::packet::Packet;
+
+const DEFAULT_FRAME_SIZE: usize = 4096;
+
+/// The file is only complete after [`AudioFileWriter::finish`].
+#[derive(Debug)]
+pub struct AudioFileWriter {
+    context: OutputContext,
+    encoder: Encoder,
+    frame: Frame,
+    packet: Packet,
+    sample_format: ffi::AVSampleFormat,
+    sample_rate: i32,
+    num_channels: usize,
+    bits: u32,
+    frame_size: usize,
+    pts: i64,
+}
+
+impl AudioFileWriter {
+    pub fn create(
+        path: &Utf8Path,
+        format: ExportFormat,
+        sample_format: ExportSampleFormat,
+        sample_rate: u32,
+        num_channels: usize,
+    ) -> Result<AudioFileWriter> {
+        use ffi::AVCodecID::*;
+        use ffi::AVSampleFormat::*;
+
+        init();
+
+        let (format_name, codec_id, codec_sample_format) = match (format, sample_format) {
+            (ExportFormat::Wav, ExportSampleFormat::I16) => {
+                (c"wav", AV_CODEC_ID_PCM_S16LE, AV_SAMPLE_FMT_S16)
+            }
+            (ExportFormat::Wav, ExportSampleFormat::I24) => {
+                (c"wav", AV_CODEC_ID_PCM_S24LE, AV_SAMPLE_FMT_S32)
+            }
+            (ExportFormat::Wav, ExportSampleFormat::F32) => {
+                (c"wav", AV_CODEC_ID_PCM_F32LE, AV_SAMPLE_FMT_FLT)
+            }
+            (ExportFormat::Flac, ExportSampleFormat::I16) => {
+                (c"flac", AV_CODEC_ID_FLAC, AV_SAMPLE_FMT_S16)
+            }
+            (ExportFormat::Flac, ExportSampleFormat::I24) => {
+                (c"flac", AV_CODEC_ID_FLAC, AV_SAMPLE_FMT_S32)
+            }
+            (ExportFormat::Flac, ExportSampleFormat::F32) => {
+                bail!(
+                    ErrorKind::NotSupported,
+                    "FLAC can't store floating point samples"
+                );
+            }
+            (ExportFormat::Ogg, _) => (c"ogg", AV_CODEC_ID_VORBIS, AV_SAMPLE_FMT_FLTP),
+        };
+
+        let bits = match format {
+            ExportFormat::Ogg => 32,
+            _ => sample_format.integer_bits().unwrap_or(32),
+        };
+
+        let codec = unsafe { ffi::avcodec_find_encoder(codec_id) };
+        if codec.is_null() {
+            bail!(
+                ErrorKind::NotSupported,
+                "ffmpeg doesn't have an encoder for {format:?}"
+            );
+        }
+
+        let path = CString::new(path.as_str())
+            .map_err(|_| format_err!(ErrorKind::NotSupported, "path contains a nul byte"))?;
+        let sample_rate = i32::try_from(sample_rate)
+            .map_err(|_| format_err!(ErrorKind::NotSupported, "sample rate is too high"))?;
+        let num_channels_i32 = i32::try_from(num_channels)
+            .map_err(|_| format_err!(ErrorKind::NotSupported, "too many channels"))?;
+
+        let mut context = OutputContext::new(format_name, &path)?;
+
+        let encoder = Encoder::new(
+            codec,
+            EncoderConfig {
+                sample_format: codec_sample_format,
+                sample_rate,
+                num_channels: num_channels_i32,
+                // only 24 bits of the 32-bit samples are stored
+                bits_per_raw_sample: if bits == 24 { 24 } else { 0 },
+                global_header: context.needs_global_header(),
+            },
+        )?;
+
+        context.write_header(&encoder)?;
+
+        let frame_size = match encoder.frame_size() {
+            0 => DEFAULT_FRAME_SIZE,
+            v => v,
+        };
+
+        Ok(AudioFileWriter {
+            context,
+            encoder,
+            frame: Frame::new()?,
+            packet: Packet::new()?,
+            sample_format: codec_sample_format,
+            sample_rate,
+            num_channels,
+            bits,
+            frame_size,
+            pts: 0,
+        })
+    }
+
+    pub fn frame_size(&self) -> usize {
+        self.frame_size
+    }
+
+    /// Integer samples have as many bits as the sample format of the writer.
+    pub fn write<T: WriterSample>(&mut self, channels: &[&[T]]) -> Result<()> {
+        if channels.len() != self.num_channels {
+            bail!(
+                ErrorKind::NotSupported,
+                "expected {} channels, got {}",
+                self.num_channels,
+                channels.len()
+            );
+        }
+
+        let num_frames = channels.first().map_or(0, |v| v.len());
+        if num_frames == 0 {
+            return Ok(());
+        }
+
+        if num_frames > self.frame_size || channels.iter().any(|v| v.len() != num_frames) {
+            bail!(
+                ErrorKind::NotSupported,
+                "invalid block of {num_frames} frames"
+            );
+        }
+
+        self.frame.alloc_audio(
+            self.sample_format,
+            self.encoder.channel_layout(),
+            self.sample_rate,
+            num_frames as i32,
+        )?;
+        self.frame.set_pts(self.pts);
+        self.pts += num_frames as i64;
+
+        let num_channels = self.num_channels;
+        let bits = self.bits;
+        let len = num_frames * num_channels;
+
+        match self.sample_format {
+            ffi::AVSampleFormat::AV_SAMPLE_FMT_S16 => {
+                let plane = unsafe { self.frame.plane_mut::<i16>(0, len) };
+                interleave(channels, plane, |v| v.to_i16(bits));
+            }
+            ffi::AVSampleFormat::AV_SAMPLE_FMT_S32 => {
+                let plane = unsafe { self.frame.plane_mut::<i32>(0, len) };
+                interleave(channels, plane, |v| v.to_i32(bits));
+            }
+            ffi::AVSampleFormat::AV_SAMPLE_FMT_FLT => {
+                let plane = unsafe { self.frame.plane_mut::<f32>(0, len) };
+                interleave(channels, plane, |v| v.to_f32(bits));
+            }
+            _ => {
+                for (idx, channel) in channels.iter().enumerate() {
+                    let plane = unsafe { self.frame.plane_mut::<f32>(idx, num_frames) };
+                    for (dst, &src) in plane.iter_mut().zip(channel.iter()) {
+                        *dst = src.to_f32(bits);
+                    }
+                }
+            }
+        }
+
+        self.encoder.send_frame(&mut self.frame)?;
+        self.write_packets()
+    }
+
+    pub fn finish(mut self) -> Result<()> {
+        self.encoder.flush()?;
+        self.write_packets()?;
+        self.context.write_trailer()?;
+        Ok(())
+    }
+
+    fn write_packets(&mut self) -> Result<()> {
+        loop {
+            let packet = match self.encoder.recv_packet(&mut self.packet) {
+                Ok(v) => v,
+                Err(e) if matches!(e.kind(), FfmpegErrorKind::Again | FfmpegErrorKind::Eof) => {
+                    return Ok(());
+                }
+                Err(e) => return Err(e.into()),
+            };
+
+            self.context.write_packet(packet, &self.encoder)?;
+        }
+    }
+}
+
+fn interleave<T: Copy, U>(channels: &[&[T]], dst: &mut [U], convert: impl Fn(T) -> U) {
+    let num_channels = channels.len();
+
+    for (channel_idx, channel) in channels.iter().enumerate() {
+        for (frame_idx, &sample) in channel.iter().enumerate() {
+            dst[frame_idx * num_channels + channel_idx] = convert(sample);
+        }
+    }
+}
+
+pub trait WriterSample: Copy {
+    fn to_i16(self, bits: u32) -> i16;
+
+    /// Samples with fewer bits are in the most significant ones.
+    fn to_i32(self, bits: u32) -> i32;
+
+    fn to_f32(self, bits: u32) -> f32;
+}
+
+impl WriterSample for f32 {
+    fn to_i16(self, _bits: u32) -> i16 {
+        (self * 32768.0).round().clamp(-32768.0, 32767.0) as i16
+    }
+
+    fn to_i32(self, _bits: u32) -> i32 {
+        (f64::from(self) * 2147483648.0)
+            .round()
+            .clamp(-2147483648.0, 2147483647.0) as i32
+    }
+
+    fn to_f32(self, _bits: u32) -> f32 {
+        self
+    }
+}
+
+impl WriterSample for i32 {
+    fn to_i16(self, bits: u32) -> i16 {
+        if bits > 16 {
+            (self >> (bits - 16)) as i16
+        } else {
+            (self << (16 - bits)) as i16
+        }
+    }
+
+    fn to_i32(self, bits: u32) -> i32 {
+        self << (32 - bits.clamp(1, 32))
+    }
+
+    fn to_f32(self, bits: u32) -> f32 {
+        (f64::from(self) / f64::from(1u32 << (bits.clamp(1, 32) - 1))) as f32
+    }
+}
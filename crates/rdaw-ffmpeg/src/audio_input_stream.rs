@@ -2,7 +2,7 @@ use std::io::{Read, Seek};
 use std::mem::ManuallyDrop;
 
 use ffmpeg_sys_next as ffi;
-use rdaw_api::audio::AudioMetadata;
+use rdaw_api::audio::{AudioMetadata, ResampleQuality};
 use rdaw_api::Result;
 
 use crate::internal::decoder::Decoder;
@@ -45,6 +45,7 @@ impl<R: Read + Seek> AudioInputStream<'_, R> {
                 in_ch_layout: raw_metadata.channel_layout,
                 in_sample_format: raw_metadata.sample_format,
                 in_sample_rate: raw_metadata.sample_rate,
+                quality: ResampleQuality::default(),
             })?)
         };
 
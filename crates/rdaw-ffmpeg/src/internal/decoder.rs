@@ -55,6 +55,12 @@ impl Decoder {
         Ok(())
     }
 
+    pub fn flush_buffers(&mut self) {
+        unsafe {
+            ffi::avcodec_flush_buffers(self.raw);
+        }
+    }
+
     pub fn recv_frame<'a>(&mut self, frame: &'a mut Frame) -> Result<FilledFrame<'a>> {
         let res = unsafe { ffi::avcodec_receive_frame(self.raw, frame.as_raw()) };
         if res < 0 {
@@ -0,0 +1,114 @@
+use std::ptr::{self, null_mut};
+
+use ffmpeg_sys_next as ffi;
+
+use super::error::{Error, Result};
+use super::frame::Frame;
+use super::packet::{FilledPacket, Packet};
+
+pub struct EncoderConfig {
+    pub sample_format: ffi::AVSampleFormat,
+    pub sample_rate: i32,
+    pub num_channels: i32,
+    /// 0 if all of them are used.
+    pub bits_per_raw_sample: i32,
+    pub global_header: bool,
+}
+
+#[derive(Debug)]
+pub struct Encoder {
+    raw: *mut ffi::AVCodecContext,
+}
+
+impl Encoder {
+    pub fn new(codec: *const ffi::AVCodec, config: EncoderConfig) -> Result<Encoder> {
+        let raw = unsafe { ffi::avcodec_alloc_context3(codec) };
+        if raw.is_null() {
+            return Err(Error::new_oom("avcodec"));
+        }
+
+        unsafe {
+            (*raw).sample_fmt = config.sample_format;
+            (*raw).sample_rate = config.sample_rate;
+            (*raw).time_base = ffi::AVRational {
+                num: 1,
+                den: config.sample_rate,
+            };
+            (*raw).bits_per_raw_sample = config.bits_per_raw_sample;
+            // the native vorbis encoder is the only one ffmpeg may have been built with
+            (*raw).strict_std_compliance = ffi::FF_COMPLIANCE_EXPERIMENTAL;
+            ffi::av_channel_layout_default(&mut (*raw).ch_layout, config.num_channels);
+
+            if config.global_header {
+                (*raw).flags |= ffi::AV_CODEC_FLAG_GLOBAL_HEADER as i32;
+            }
+        }
+
+        let encoder = Encoder { raw };
+
+        let res = unsafe { ffi::avcodec_open2(raw, codec, null_mut()) };
+        if res < 0 {
+            return Err(Error::new(res, "avcodec_open2"));
+        }
+
+        Ok(encoder)
+    }
+
+    pub fn as_raw(&self) -> *const ffi::AVCodecContext {
+        self.raw
+    }
+
+    pub fn time_base(&self) -> ffi::AVRational {
+        unsafe { (*self.raw).time_base }
+    }
+
+    pub fn channel_layout(&self) -> &ffi::AVChannelLayout {
+        unsafe { &(*self.raw).ch_layout }
+    }
+
+    /// 0 if any size is accepted.
+    pub fn frame_size(&self) -> usize {
+        let raw = unsafe { &*self.raw };
+        let is_variable = unsafe {
+            ((*raw.codec).capabilities & ffi::AV_CODEC_CAP_VARIABLE_FRAME_SIZE as i32) != 0
+        };
+
+        if is_variable {
+            0
+        } else {
+            raw.frame_size.max(0) as usize
+        }
+    }
+
+    pub fn send_frame(&mut self, frame: &mut Frame) -> Result<()> {
+        let res = unsafe { ffi::avcodec_send_frame(self.raw, frame.as_raw()) };
+        if res < 0 {
+            return Err(Error::new(res, "avcodec_send_frame"));
+        }
+        Ok(())
+    }
+
+    pub fn flush(&mut self) -> Result<()> {
+        let res = unsafe { ffi::avcodec_send_frame(self.raw, ptr::null()) };
+        if res < 0 {
+            return Err(Error::new(res, "avcodec_send_frame"));
+        }
+        Ok(())
+    }
+
+    pub fn recv_packet<'a>(&mut self, packet: &'a mut Packet) -> Result<FilledPacket<'a>> {
+        let res = unsafe { ffi::avcodec_receive_packet(self.raw, packet.as_raw()) };
+        if res < 0 {
+            return Err(Error::new(res, "avcodec_receive_packet"));
+        }
+        Ok(unsafe { packet.assume_filled() })
+    }
+}
+
+impl Drop for Encoder {
+    fn drop(&mut self) {
+        unsafe {
+            ffi::avcodec_free_context(&mut self.raw);
+        }
+    }
+}
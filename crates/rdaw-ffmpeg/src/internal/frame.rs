@@ -23,6 +23,46 @@ impl Frame {
         self.raw
     }
 
+    /// The previous buffers may still be referenced by an encoder.
+    pub fn alloc_audio(
+        &mut self,
+        sample_format: ffi::AVSampleFormat,
+        ch_layout: &ffi::AVChannelLayout,
+        sample_rate: i32,
+        num_samples: i32,
+    ) -> Result<()> {
+        unsafe {
+            ffi::av_frame_unref(self.raw);
+            (*self.raw).format = sample_format as i32;
+            (*self.raw).sample_rate = sample_rate;
+            (*self.raw).nb_samples = num_samples;
+        }
+
+        let res = unsafe { ffi::av_channel_layout_copy(&mut (*self.raw).ch_layout, ch_layout) };
+        if res < 0 {
+            return Err(Error::new(res, "av_channel_layout_copy"));
+        }
+
+        let res = unsafe { ffi::av_frame_get_buffer(self.raw, 0) };
+        if res < 0 {
+            return Err(Error::new(res, "av_frame_get_buffer"));
+        }
+
+        Ok(())
+    }
+
+    pub fn set_pts(&mut self, pts: i64) {
+        unsafe {
+            (*self.raw).pts = pts;
+        }
+    }
+
+    /// Must be allocated by [`Frame::alloc_audio`].
+    pub unsafe fn plane_mut<T>(&mut self, idx: usize, len: usize) -> &mut [T] {
+        let plane = *(*self.raw).extended_data.add(idx);
+        std::slice::from_raw_parts_mut(plane as *mut T, len)
+    }
+
     pub unsafe fn assume_filled(&mut self) -> FilledFrame<'_> {
         FilledFrame {
             raw: self.raw,
@@ -46,6 +86,14 @@ pub struct FilledFrame<'a> {
 }
 
 impl FilledFrame<'_> {
+    pub fn as_raw(&self) -> *const ffi::AVFrame {
+        self.raw
+    }
+
+    pub fn timestamp(&self) -> i64 {
+        unsafe { (*self.raw).best_effort_timestamp }
+    }
+
     pub unsafe fn get_data(&self) -> &[u8] {
         let sample_format = std::mem::transmute::<i32, ffi::AVSampleFormat>((*self.raw).format);
         let bytes_per_sample = ffi::av_get_bytes_per_sample(sample_format) as usize;
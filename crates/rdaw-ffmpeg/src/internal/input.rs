@@ -3,6 +3,7 @@ use std::ptr;
 
 use ffmpeg_sys_next as ffi;
 use rdaw_api::audio::{AudioChannel, AudioMetadata, SampleFormat};
+use rdaw_api::video::{FrameRate, VideoMetadata};
 use rdaw_core::time::RealTime;
 
 use super::decoder::Decoder;
@@ -84,6 +85,54 @@ impl<R: Read + Seek> InputContext<R> {
         Ok(Some((stream_idx, decoder)))
     }
 
+    pub fn find_video_stream(&self) -> Result<Option<(StreamIdx, Decoder)>> {
+        let mut codec = ptr::null();
+
+        let res = unsafe {
+            ffi::av_find_best_stream(
+                self.raw,
+                ffi::AVMediaType::AVMEDIA_TYPE_VIDEO,
+                -1, // stream_nb: automatic selection
+                -1, // no related stream
+                &mut codec,
+                0, // no flags
+            )
+        };
+
+        if res == ffi::AVERROR_STREAM_NOT_FOUND {
+            return Ok(None);
+        }
+
+        if res < 0 {
+            return Err(Error::new(res, "av_find_best_stream"));
+        }
+
+        if codec.is_null() {
+            return Err(Error::new(ffi::AVERROR_BUG, "av_find_best_stream"));
+        }
+
+        let stream_idx = StreamIdx(res);
+        let stream = self.get_stream(stream_idx);
+
+        let decoder = Decoder::new(codec, stream.codecpar, stream.time_base)?;
+
+        Ok(Some((stream_idx, decoder)))
+    }
+
+    fn get_stream(&self, idx: StreamIdx) -> &ffi::AVStream {
+        let streams = unsafe {
+            std::slice::from_raw_parts((*self.raw).streams, (*self.raw).nb_streams as usize)
+        };
+
+        let stream = streams
+            .iter()
+            .copied()
+            .find(|&v| unsafe { (*v).index == idx.0 })
+            .expect("no such stream");
+
+        unsafe { &*stream }
+    }
+
     pub fn get_audio_stream_raw_metadata(&self, idx: StreamIdx) -> Result<RawAudioMetadata> {
         let streams = unsafe {
             std::slice::from_raw_parts((*self.raw).streams, (*self.raw).nb_streams as usize)
@@ -119,6 +168,46 @@ impl<R: Read + Seek> InputContext<R> {
         })
     }
 
+    pub fn get_video_stream_raw_metadata(&self, idx: StreamIdx) -> Result<RawVideoMetadata> {
+        let stream = self.get_stream(idx);
+        let codecpar = unsafe { &*stream.codecpar };
+
+        Ok(RawVideoMetadata {
+            width: codecpar.width,
+            height: codecpar.height,
+            pix_format: unsafe {
+                std::mem::transmute::<i32, ffi::AVPixelFormat>(codecpar.format)
+            },
+            time_base: stream.time_base,
+            frame_rate: stream.avg_frame_rate,
+            duration_ns: stream.duration * (stream.time_base.num as i64) * 1_000_000_000
+                / (stream.time_base.den as i64),
+        })
+    }
+
+    pub fn get_video_stream_metadata(&self, idx: StreamIdx) -> Result<VideoMetadata> {
+        let raw = self.get_video_stream_raw_metadata(idx)?;
+        Ok(VideoMetadata {
+            width: raw.width.max(0) as u32,
+            height: raw.height.max(0) as u32,
+            frame_rate: FrameRate {
+                num: raw.frame_rate.num.max(0) as u32,
+                den: raw.frame_rate.den.max(0) as u32,
+            },
+            duration: RealTime::from_nanos(raw.duration_ns),
+        })
+    }
+
+    pub fn seek(&mut self, idx: StreamIdx, timestamp: i64) -> Result<()> {
+        let res = unsafe {
+            ffi::av_seek_frame(self.raw, idx.0, timestamp, ffi::AVSEEK_FLAG_BACKWARD as i32)
+        };
+        if res < 0 {
+            return Err(Error::new(res, "av_seek_frame"));
+        }
+        Ok(())
+    }
+
     pub fn read_packet<'a>(&mut self, packet: &'a mut Packet) -> Result<FilledPacket<'a>, Error> {
         let res = unsafe { ffi::av_read_frame(self.raw, packet.as_raw()) };
         if res < 0 {
@@ -143,6 +232,15 @@ pub struct RawAudioMetadata<'a> {
     pub duration_ns: i64,
 }
 
+pub struct RawVideoMetadata {
+    pub width: i32,
+    pub height: i32,
+    pub pix_format: ffi::AVPixelFormat,
+    pub time_base: ffi::AVRational,
+    pub frame_rate: ffi::AVRational,
+    pub duration_ns: i64,
+}
+
 #[rustfmt::skip]
 const CHANNEL_MAPPING: [(ffi::AVChannel, AudioChannel); 30] = [
     (ffi::AVChannel::AV_CHAN_FRONT_LEFT, AudioChannel::FrontLeft),
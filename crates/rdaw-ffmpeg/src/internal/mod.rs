@@ -5,12 +5,15 @@ use ffmpeg_sys_next as ffi;
 use tracing::Level;
 
 pub mod decoder;
+pub mod encoder;
 pub mod error;
 pub mod frame;
 pub mod input;
+pub mod output;
 pub mod packet;
 pub mod reader;
 pub mod resample;
+pub mod scale;
 
 #[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
 pub struct StreamIdx(pub c_int);
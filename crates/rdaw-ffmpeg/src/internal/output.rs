@@ -0,0 +1,109 @@
+use std::ffi::CStr;
+use std::ptr;
+
+use ffmpeg_sys_next as ffi;
+
+use super::encoder::Encoder;
+use super::error::{Error, Result};
+use super::packet::FilledPacket;
+
+#[derive(Debug)]
+pub struct OutputContext {
+    raw: *mut ffi::AVFormatContext,
+    stream: *mut ffi::AVStream,
+}
+
+impl OutputContext {
+    pub fn new(format_name: &CStr, path: &CStr) -> Result<OutputContext> {
+        let mut raw = ptr::null_mut();
+
+        let res = unsafe {
+            ffi::avformat_alloc_output_context2(
+                &mut raw,
+                ptr::null(),
+                format_name.as_ptr(),
+                path.as_ptr(),
+            )
+        };
+        if res < 0 {
+            return Err(Error::new(res, "avformat_alloc_output_context2"));
+        }
+
+        let mut context = OutputContext {
+            raw,
+            stream: ptr::null_mut(),
+        };
+
+        let res =
+            unsafe { ffi::avio_open(&mut (*raw).pb, path.as_ptr(), ffi::AVIO_FLAG_WRITE as i32) };
+        if res < 0 {
+            return Err(Error::new(res, "avio_open"));
+        }
+
+        context.stream = unsafe { ffi::avformat_new_stream(raw, ptr::null()) };
+        if context.stream.is_null() {
+            return Err(Error::new_oom("avformat_new_stream"));
+        }
+
+        Ok(context)
+    }
+
+    pub fn needs_global_header(&self) -> bool {
+        unsafe { ((*(*self.raw).oformat).flags & ffi::AVFMT_GLOBALHEADER as i32) != 0 }
+    }
+
+    pub fn write_header(&mut self, encoder: &Encoder) -> Result<()> {
+        unsafe {
+            (*self.stream).time_base = encoder.time_base();
+        }
+
+        let res = unsafe {
+            ffi::avcodec_parameters_from_context((*self.stream).codecpar, encoder.as_raw())
+        };
+        if res < 0 {
+            return Err(Error::new(res, "avcodec_parameters_from_context"));
+        }
+
+        let res = unsafe { ffi::avformat_write_header(self.raw, ptr::null_mut()) };
+        if res < 0 {
+            return Err(Error::new(res, "avformat_write_header"));
+        }
+
+        Ok(())
+    }
+
+    pub fn write_packet(&mut self, mut packet: FilledPacket<'_>, encoder: &Encoder) -> Result<()> {
+        unsafe {
+            let raw = packet.as_raw();
+            (*raw).stream_index = (*self.stream).index;
+            ffi::av_packet_rescale_ts(raw, encoder.time_base(), (*self.stream).time_base);
+        }
+
+        let res = unsafe { ffi::av_interleaved_write_frame(self.raw, packet.as_raw()) };
+        if res < 0 {
+            return Err(Error::new(res, "av_interleaved_write_frame"));
+        }
+
+        Ok(())
+    }
+
+    pub fn write_trailer(&mut self) -> Result<()> {
+        let res = unsafe { ffi::av_write_trailer(self.raw) };
+        if res < 0 {
+            return Err(Error::new(res, "av_write_trailer"));
+        }
+
+        Ok(())
+    }
+}
+
+impl Drop for OutputContext {
+    fn drop(&mut self) {
+        unsafe {
+            if !(*self.raw).pb.is_null() {
+                ffi::avio_closep(&mut (*self.raw).pb);
+            }
+            ffi::avformat_free_context(self.raw);
+        }
+    }
+}
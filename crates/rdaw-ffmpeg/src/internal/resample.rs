@@ -1,4 +1,7 @@
+use std::ffi::CStr;
+
 use ffmpeg_sys_next as ffi;
+use rdaw_api::audio::ResampleQuality;
 
 use super::error::{Error, Result};
 
@@ -9,6 +12,7 @@ pub struct ResamplerConfig<'a> {
     pub out_ch_layout: &'a ffi::AVChannelLayout,
     pub out_sample_format: ffi::AVSampleFormat,
     pub out_sample_rate: i32,
+    pub quality: ResampleQuality,
 }
 
 #[derive(Debug)]
@@ -40,6 +44,22 @@ impl Resampler {
             return Err(Error::new(res, "swr_alloc_set_opts2"));
         }
 
+        // filter length, number of filter phases, and the cutoff relative to the nyquist frequency
+        let (filter_size, phase_shift, cutoff) = match config.quality {
+            ResampleQuality::Fast => (8, 6, 0.8),
+            ResampleQuality::Balanced => (32, 10, 0.97),
+            ResampleQuality::Best => (128, 14, 0.99),
+        };
+
+        set_int_option(raw, c"filter_size", filter_size)?;
+        set_int_option(raw, c"phase_shift", phase_shift)?;
+        set_int_option(raw, c"linear_interp", 1)?;
+
+        let res = unsafe { ffi::av_opt_set_double(raw.cast(), c"cutoff".as_ptr(), cutoff, 0) };
+        if res < 0 {
+            return Err(Error::new(res, "av_opt_set_double"));
+        }
+
         let res = unsafe { ffi::swr_init(raw) };
         if res < 0 {
             return Err(Error::new(res, "swr_init"));
@@ -133,6 +153,15 @@ impl Resampler {
     }
 }
 
+fn set_int_option(raw: *mut ffi::SwrContext, name: &CStr, value: i64) -> Result<()> {
+    let res = unsafe { ffi::av_opt_set_int(raw.cast(), name.as_ptr(), value, 0) };
+    if res < 0 {
+        return Err(Error::new(res, "av_opt_set_int"));
+    }
+
+    Ok(())
+}
+
 impl Drop for Resampler {
     fn drop(&mut self) {
         unsafe {
@@ -0,0 +1,85 @@
+use std::ptr;
+
+use ffmpeg_sys_next as ffi;
+
+use super::error::{Error, Result};
+use super::frame::FilledFrame;
+
+pub struct ScalerConfig {
+    pub in_width: u32,
+    pub in_height: u32,
+    pub in_pix_format: ffi::AVPixelFormat,
+    pub out_width: u32,
+    pub out_height: u32,
+}
+
+#[derive(Debug)]
+pub struct Scaler {
+    raw: *mut ffi::SwsContext,
+    in_height: u32,
+    out_stride: usize,
+    buf: Vec<u8>,
+}
+
+impl Scaler {
+    pub fn new(config: ScalerConfig) -> Result<Scaler> {
+        let raw = unsafe {
+            ffi::sws_getContext(
+                config.in_width as i32,
+                config.in_height as i32,
+                config.in_pix_format,
+                config.out_width as i32,
+                config.out_height as i32,
+                ffi::AVPixelFormat::AV_PIX_FMT_RGBA,
+                ffi::SWS_BILINEAR as i32,
+                ptr::null_mut(),
+                ptr::null_mut(),
+                ptr::null(),
+            )
+        };
+        if raw.is_null() {
+            return Err(Error::new(ffi::AVERROR(ffi::EINVAL), "sws_getContext"));
+        }
+
+        let out_stride = config.out_width as usize * 4;
+
+        Ok(Scaler {
+            raw,
+            in_height: config.in_height,
+            out_stride,
+            buf: vec![0; out_stride * config.out_height as usize],
+        })
+    }
+
+    pub fn convert(&mut self, frame: &FilledFrame<'_>) -> Result<&[u8]> {
+        let frame = unsafe { &*frame.as_raw() };
+
+        let out_buffers = [self.buf.as_mut_ptr()];
+        let out_strides = [self.out_stride as i32];
+
+        let res = unsafe {
+            ffi::sws_scale(
+                self.raw,
+                frame.data.as_ptr() as *const *const u8,
+                frame.linesize.as_ptr(),
+                0,
+                self.in_height as i32,
+                out_buffers.as_ptr(),
+                out_strides.as_ptr(),
+            )
+        };
+        if res < 0 {
+            return Err(Error::new(res, "sws_scale"));
+        }
+
+        Ok(&self.buf)
+    }
+}
+
+impl Drop for Scaler {
+    fn drop(&mut self) {
+        unsafe {
+            ffi::sws_freeContext(self.raw);
+        }
+    }
+}
@@ -1,6 +1,12 @@
+mod audio_file_writer;
 mod audio_input_stream;
 mod internal;
 mod media_input;
+mod resample_node;
+mod video_input_stream;
 
+pub use self::audio_file_writer::{AudioFileWriter, WriterSample};
 pub use self::audio_input_stream::AudioInputStream;
 pub use self::media_input::MediaInput;
+pub use self::resample_node::ResampleNode;
+pub use self::video_input_stream::VideoInputStream;
@@ -4,7 +4,7 @@ use rdaw_api::Result;
 
 use crate::internal::init;
 use crate::internal::input::InputContext;
-use crate::AudioInputStream;
+use crate::{AudioInputStream, VideoInputStream};
 
 #[derive(Debug)]
 pub struct MediaInput<R> {
@@ -24,6 +24,7 @@ impl<R: Read + Seek> rdaw_api::media::OpenMediaInput<R> for MediaInput<R> {
 
 impl<R: Read + Seek> rdaw_api::media::MediaInput for MediaInput<R> {
     type AudioInputStream<'a> = AudioInputStream<'a, R> where Self: 'a;
+    type VideoInputStream<'a> = VideoInputStream<'a, R> where Self: 'a;
 
     fn get_audio_stream(&mut self) -> Result<Option<AudioInputStream<'_, R>>> {
         let Some((stream_idx, decoder)) = self.context.find_audio_stream()? else {
@@ -33,4 +34,17 @@ impl<R: Read + Seek> rdaw_api::media::MediaInput for MediaInput<R> {
         let stream = AudioInputStream::new(self, stream_idx, decoder)?;
         Ok(Some(stream))
     }
+
+    fn get_video_stream(
+        &mut self,
+        max_width: u32,
+        max_height: u32,
+    ) -> Result<Option<VideoInputStream<'_, R>>> {
+        let Some((stream_idx, decoder)) = self.context.find_video_stream()? else {
+            return Ok(None);
+        };
+
+        let stream = VideoInputStream::new(self, stream_idx, decoder, max_width, max_height)?;
+        Ok(Some(stream))
+    }
 }
@@ -0,0 +1,204 @@
+use std::collections::VecDeque;
+use std::sync::Arc;
+
+use ffmpeg_sys_next as ffi;
+use rdaw_api::audio::ResampleQuality;
+use rdaw_api::item::AudioClip;
+use rdaw_audio::buffer::SilentHint;
+use rdaw_audio::graph::{CompiledNode, GraphParams, Inputs, Node, Outputs};
+use rdaw_audio::playhead::Playhead;
+use rdaw_core::time::RealTime;
+
+use crate::internal::error::Result;
+use crate::internal::resample::{Resampler, ResamplerConfig};
+
+/// Source frames converted at once.
+const CHUNK_FRAMES: usize = 256;
+
+/// Plays decoded samples at a fixed place on the timeline, like
+/// [`SampleNode`](rdaw_audio::nodes::SampleNode), converting them from their own sample rate to
+/// the one of the graph.
+#[derive(Debug, Clone)]
+pub struct ResampleNode {
+    pub samples: Arc<[f32]>,
+    pub sample_rate: u32,
+    /// Timeline frame where the item starts.
+    pub start: i64,
+    pub duration: i64,
+    /// The item starts over after this many timeline frames, until its duration is over.
+    pub loop_length: Option<i64>,
+    /// Where to start in the samples, and the gain and fades to apply.
+    pub clip: AudioClip,
+    pub quality: ResampleQuality,
+    pub playhead: Playhead,
+}
+
+impl Node for ResampleNode {
+    fn num_audio_inputs(&self) -> usize {
+        0
+    }
+
+    fn num_audio_outputs(&self) -> usize {
+        1
+    }
+
+    fn compile(&self, params: &GraphParams) -> Box<dyn CompiledNode> {
+        let sample_rate = f64::from(params.sample_rate);
+        let offset = (self.clip.source_offset.as_secs_f64() * sample_rate).round() as i64;
+
+        Box::new(CompiledResampleNode {
+            node: self.clone(),
+            offset,
+            resampler: None,
+            next_frame: None,
+            source_pos: 0,
+            flushed: false,
+            pending: VecDeque::with_capacity(params.buffer_size + CHUNK_FRAMES * 2),
+        })
+    }
+}
+
+struct CompiledResampleNode {
+    node: ResampleNode,
+    /// Source offset in timeline frames.
+    offset: i64,
+    /// Missing if it couldn't be created, the node is silent then.
+    resampler: Option<Resampler>,
+    /// Item frame the first pending sample belongs to. The resampler starts over when the playhead
+    /// jumps somewhere else.
+    next_frame: Option<i64>,
+    /// Next source frame to convert.
+    source_pos: usize,
+    flushed: bool,
+    /// Converted samples which weren't played yet.
+    pending: VecDeque<f32>,
+}
+
+impl CompiledResampleNode {
+    fn seek(&mut self, params: &GraphParams, frame: i64) {
+        let ratio = f64::from(self.node.sample_rate) / f64::from(params.sample_rate);
+        self.source_pos = ((frame + self.offset).max(0) as f64 * ratio) as usize;
+        self.flushed = false;
+        self.pending.clear();
+
+        self.resampler = match create_resampler(&self.node, params) {
+            Ok(v) => Some(v),
+            Err(error) => {
+                tracing::warn!(?error, "failed to create resampler");
+                None
+            }
+        };
+    }
+
+    /// Converts source frames until at least `len` samples are pending.
+    fn fill(&mut self, len: usize) {
+        while self.pending.len() < len {
+            let Some(resampler) = &mut self.resampler else {
+                break;
+            };
+
+            let res = if self.source_pos < self.node.samples.len() {
+                let end = (self.source_pos + CHUNK_FRAMES).min(self.node.samples.len());
+                let chunk = &self.node.samples[self.source_pos..end];
+                self.source_pos = end;
+                resampler.convert(as_bytes(chunk))
+            } else if !self.flushed {
+                self.flushed = true;
+                resampler.flush()
+            } else {
+                // past the end of the source
+                break;
+            };
+
+            match res {
+                Ok(data) => self.pending.extend(as_samples(data)),
+                Err(error) => {
+                    tracing::warn!(?error, "failed to resample audio");
+                    self.resampler = None;
+                }
+            }
+        }
+
+        if self.pending.len() < len {
+            self.pending.resize(len, 0.0);
+        }
+    }
+}
+
+impl CompiledNode for CompiledResampleNode {
+    fn process(&mut self, params: &GraphParams, _inputs: Inputs<'_>, outputs: Outputs<'_>) {
+        let output = &mut *outputs.audio[0];
+
+        let period_start = self.node.playhead.frame() - self.node.start;
+        let period_end = period_start + output.len() as i64;
+
+        if period_end <= 0 || period_start >= self.node.duration {
+            output.clear();
+            self.next_frame = None;
+            return;
+        }
+
+        let first = period_start.max(0);
+        let last = period_end.min(self.node.duration);
+
+        let frame_secs = 1.0 / f64::from(params.sample_rate);
+        // fades are applied to every repetition
+        let loop_length = self.node.loop_length.unwrap_or(self.node.duration).max(1);
+        let duration = RealTime::from_secs_f64(loop_length as f64 * frame_secs);
+
+        output.fill(0.0);
+
+        // the period is split where the item starts over, the resampler seeks back then
+        let mut frame = first;
+        while frame < last {
+            let item_frame = frame % loop_length;
+            let end = last.min(frame - item_frame + loop_length);
+
+            if self.next_frame != Some(item_frame) {
+                self.seek(params, item_frame);
+            }
+
+            let len = (end - frame) as usize;
+            self.fill(len);
+            self.next_frame = Some(item_frame + len as i64);
+
+            let offset = (frame - period_start) as usize;
+            for (i, out) in output[offset..offset + len].iter_mut().enumerate() {
+                let sample = self.pending.pop_front().unwrap_or(0.0);
+                let position = RealTime::from_secs_f64((item_frame + i as i64) as f64 * frame_secs);
+                *out = sample * self.node.clip.gain_at(position, duration);
+            }
+
+            frame = end;
+        }
+
+        output.silent_hint = SilentHint::Unspecified;
+    }
+}
+
+fn create_resampler(node: &ResampleNode, params: &GraphParams) -> Result<Resampler> {
+    // SAFETY: a zeroed layout is valid, and the default mono layout doesn't allocate, so it doesn't
+    // have to be uninitialized.
+    let mut layout = unsafe { std::mem::zeroed::<ffi::AVChannelLayout>() };
+    unsafe { ffi::av_channel_layout_default(&mut layout, 1) };
+
+    Resampler::new(ResamplerConfig {
+        in_ch_layout: &layout,
+        in_sample_format: ffi::AVSampleFormat::AV_SAMPLE_FMT_FLT,
+        in_sample_rate: node.sample_rate as i32,
+        out_ch_layout: &layout,
+        out_sample_format: ffi::AVSampleFormat::AV_SAMPLE_FMT_FLT,
+        out_sample_rate: params.sample_rate as i32,
+        quality: node.quality,
+    })
+}
+
+fn as_bytes(samples: &[f32]) -> &[u8] {
+    // SAFETY: any f32 is a valid sequence of bytes.
+    unsafe { std::slice::from_raw_parts(samples.as_ptr().cast(), std::mem::size_of_val(samples)) }
+}
+
+fn as_samples(data: &[u8]) -> impl Iterator<Item = f32> + '_ {
+    data.chunks_exact(4)
+        .map(|v| f32::from_ne_bytes([v[0], v[1], v[2], v[3]]))
+}
@@ -0,0 +1,154 @@
+use std::io::{Read, Seek};
+
+use ffmpeg_sys_next as ffi;
+use rdaw_api::video::{VideoFrame, VideoMetadata};
+use rdaw_api::Result;
+use rdaw_core::time::RealTime;
+
+use crate::internal::decoder::Decoder;
+use crate::internal::error::ErrorKind;
+use crate::internal::frame::Frame;
+use crate::internal::packet::Packet;
+use crate::internal::scale::{Scaler, ScalerConfig};
+use crate::internal::StreamIdx;
+use crate::MediaInput;
+
+#[derive(Debug)]
+pub struct VideoInputStream<'media, R> {
+    media: &'media mut MediaInput<R>,
+    metadata: VideoMetadata,
+    stream_idx: StreamIdx,
+    time_base: ffi::AVRational,
+    decoder: Decoder,
+    scaler: Scaler,
+    out_width: u32,
+    out_height: u32,
+    packet: Packet,
+    frame: Frame,
+    is_draining: bool,
+}
+
+impl<R: Read + Seek> VideoInputStream<'_, R> {
+    pub(crate) fn new(
+        media: &mut MediaInput<R>,
+        stream_idx: StreamIdx,
+        decoder: Decoder,
+        max_width: u32,
+        max_height: u32,
+    ) -> Result<VideoInputStream<'_, R>> {
+        let raw_metadata = media.context.get_video_stream_raw_metadata(stream_idx)?;
+        let metadata = media.context.get_video_stream_metadata(stream_idx)?;
+
+        let (out_width, out_height) =
+            fit_size(metadata.width, metadata.height, max_width, max_height);
+
+        let scaler = Scaler::new(ScalerConfig {
+            in_width: metadata.width,
+            in_height: metadata.height,
+            in_pix_format: raw_metadata.pix_format,
+            out_width,
+            out_height,
+        })?;
+
+        let packet = Packet::new()?;
+        let frame = Frame::new()?;
+
+        Ok(VideoInputStream {
+            media,
+            metadata,
+            stream_idx,
+            time_base: raw_metadata.time_base,
+            decoder,
+            scaler,
+            out_width,
+            out_height,
+            packet,
+            frame,
+            is_draining: false,
+        })
+    }
+}
+
+impl<'media, R: Read + Seek> rdaw_api::video::VideoInputStream<'media>
+    for VideoInputStream<'media, R>
+{
+    fn metadata(&self) -> &VideoMetadata {
+        &self.metadata
+    }
+
+    fn seek(&mut self, position: RealTime) -> Result<()> {
+        let timestamp = to_timestamp(self.time_base, position);
+        self.media.context.seek(self.stream_idx, timestamp)?;
+        self.decoder.flush_buffers();
+        self.is_draining = false;
+        Ok(())
+    }
+
+    fn next_frame(&mut self) -> Result<Option<VideoFrame>> {
+        let time_base = self.time_base;
+
+        loop {
+            match self.decoder.recv_frame(&mut self.frame) {
+                Ok(frame) => {
+                    let position = to_real_time(time_base, frame.timestamp());
+                    let data = self.scaler.convert(&frame)?.to_vec();
+                    return Ok(Some(VideoFrame {
+                        position,
+                        width: self.out_width,
+                        height: self.out_height,
+                        data,
+                    }));
+                }
+                Err(e) if e.kind() == ErrorKind::Eof => return Ok(None),
+                Err(e) if e.kind() == ErrorKind::Again => {}
+                Err(e) => return Err(e.into()),
+            }
+
+            if self.is_draining {
+                return Ok(None);
+            }
+
+            match self.media.context.read_packet(&mut self.packet) {
+                Ok(packet) => {
+                    if packet.stream_idx() == self.stream_idx {
+                        self.decoder.send_packet(packet)?;
+                    }
+                }
+                Err(e) if e.kind() == ErrorKind::Eof => {
+                    self.decoder.flush()?;
+                    self.is_draining = true;
+                }
+                Err(e) => return Err(e.into()),
+            }
+        }
+    }
+}
+
+fn to_timestamp(time_base: ffi::AVRational, time: RealTime) -> i64 {
+    let num = i128::from(time_base.num) * 1_000_000_000;
+    let den = i128::from(time_base.den);
+    (i128::from(time.as_nanos()) * den / num) as i64
+}
+
+fn to_real_time(time_base: ffi::AVRational, timestamp: i64) -> RealTime {
+    let num = i128::from(time_base.num) * 1_000_000_000;
+    let den = i128::from(time_base.den);
+    RealTime::from_nanos((i128::from(timestamp) * num / den) as i64)
+}
+
+fn fit_size(width: u32, height: u32, max_width: u32, max_height: u32) -> (u32, u32) {
+    if width == 0 || height == 0 {
+        return (max_width.max(1), max_height.max(1));
+    }
+
+    let scale = f64::min(
+        f64::from(max_width) / f64::from(width),
+        f64::from(max_height) / f64::from(height),
+    )
+    .min(1.0);
+
+    let width = ((f64::from(width) * scale).round() as u32).max(1);
+    let height = ((f64::from(height) * scale).round() as u32).max(1);
+
+    (width, height)
+}
@@ -0,0 +1,115 @@
+use std::fs::File;
+
+use rdaw_api::audio::AudioInputStream as _;
+use rdaw_api::export::{ExportFormat, ExportSampleFormat};
+use rdaw_api::media::{MediaInput as _, OpenMediaInput as _};
+use rdaw_api::{assert_err, ErrorKind, Result};
+use rdaw_core::path::Utf8Path;
+use rdaw_ffmpeg::{AudioFileWriter, MediaInput};
+
+fn sine(len: usize) -> Vec<f32> {
+    (0..len).map(|i| 0.5 * (i as f32 * 0.05).sin()).collect()
+}
+
+fn write(
+    path: &Utf8Path,
+    format: ExportFormat,
+    sample_format: ExportSampleFormat,
+    channels: &[Vec<f32>],
+) -> Result<()> {
+    let mut writer = AudioFileWriter::create(path, format, sample_format, 48000, channels.len())?;
+    let len = channels[0].len();
+
+    for start in (0..len).step_by(writer.frame_size()) {
+        let end = (start + writer.frame_size()).min(len);
+        let block = channels.iter().map(|v| &v[start..end]).collect::<Vec<_>>();
+        writer.write(&block)?;
+    }
+
+    writer.finish()
+}
+
+fn read(path: &Utf8Path) -> Result<(u32, Vec<f32>)> {
+    let mut media = MediaInput::open(File::open(path)?)?;
+    let mut stream = media.get_audio_stream()?.unwrap();
+    let sample_rate = stream.metadata().sample_rate;
+
+    let mut samples = Vec::new();
+
+    loop {
+        let frame = stream.next_frame()?;
+        if frame.is_empty() {
+            break;
+        }
+
+        samples.extend_from_slice(frame);
+    }
+
+    Ok((sample_rate, samples))
+}
+
+#[test]
+fn lossless_roundtrip() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    let dir = Utf8Path::from_path(dir.path()).unwrap();
+
+    let left = sine(10000);
+    let right = left.iter().map(|v| -v).collect::<Vec<_>>();
+    let channels = vec![left, right];
+
+    for (format, sample_format, extension) in [
+        (ExportFormat::Wav, ExportSampleFormat::I16, "wav"),
+        (ExportFormat::Wav, ExportSampleFormat::F32, "wav"),
+        (ExportFormat::Flac, ExportSampleFormat::I24, "flac"),
+    ] {
+        let path = dir.join(format!("{sample_format:?}.{extension}"));
+        write(&path, format, sample_format, &channels)?;
+
+        let (sample_rate, samples) = read(&path)?;
+        assert_eq!(sample_rate, 48000);
+        assert_eq!(samples.len(), 20000);
+
+        let interleaved = channels[0]
+            .iter()
+            .zip(&channels[1])
+            .flat_map(|(&l, &r)| [l, r]);
+        for (actual, expected) in samples.iter().zip(interleaved) {
+            assert!(
+                (actual - expected).abs() < 1e-4,
+                "{format:?} {sample_format:?}"
+            );
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn integer_samples() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    let path = Utf8Path::from_path(dir.path()).unwrap().join("int.wav");
+
+    let mut writer =
+        AudioFileWriter::create(&path, ExportFormat::Wav, ExportSampleFormat::I24, 44100, 1)?;
+    writer.write(&[&[4194304i32, -8388608][..]])?;
+    writer.finish()?;
+
+    let (sample_rate, samples) = read(&path)?;
+    assert_eq!(sample_rate, 44100);
+    assert_eq!(samples, vec![0.5, -1.0]);
+
+    Ok(())
+}
+
+#[test]
+fn unsupported() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    let path = Utf8Path::from_path(dir.path()).unwrap().join("float.flac");
+
+    assert_err!(
+        AudioFileWriter::create(&path, ExportFormat::Flac, ExportSampleFormat::F32, 48000, 2),
+        ErrorKind::NotSupported
+    );
+
+    Ok(())
+}
@@ -1,17 +1,39 @@
+use std::cell::RefCell;
+use std::collections::BTreeMap;
 use std::future::Future;
+use std::rc::Rc;
 use std::sync::Arc;
+use std::time::Duration;
 
-use floem::reactive::use_context;
-use rdaw_api::{Backend, Error, Result};
-use rdaw_ui::task::spawn;
+use floem::reactive::{provide_context, use_context, with_scope, RwSignal, Scope};
+use futures::Stream;
+use rdaw_api::{Backend, BoxStream, Error, Result};
+use rdaw_ui::task::{run_after, spawn, stream_for_each};
+
+use crate::debug::DebugStats;
 
 pub fn get_backend() -> Arc<dyn Backend> {
     use_context().expect("no backend in scope")
 }
 
+const MAX_RETRIES: u32 = 3;
+/// Doubled after every retry.
+pub const RETRY_DELAY: Duration = Duration::from_millis(50);
+/// How long a toast stays on screen.
+pub const TOAST_DURATION: Duration = Duration::from_secs(5);
+
+/// Reports the error to the user, if there's an [`ErrorReports`] in scope.
 #[cold]
 pub fn handle_error(error: Error) {
-    tracing::error!(?error);
+    if error.is_user_error() {
+        tracing::warn!(?error);
+    } else {
+        tracing::error!(?error);
+    }
+
+    if let Some(reports) = use_context::<ErrorReports>() {
+        reports.report(error);
+    }
 }
 
 pub fn call<Fac, Fut, Cb, Res>(fac: Fac, callback: Cb)
@@ -27,3 +49,216 @@ where
         Err(e) => handle_error(e),
     })
 }
+
+/// Like [`call`], but calls which have failed with a retryable error are made again after a
+/// growing delay.
+pub fn call_retry<Fac, Fut, Cb, Res>(fac: Fac, callback: Cb)
+where
+    Fac: Fn(Arc<dyn Backend>) -> Fut + 'static,
+    Fut: Future<Output = Result<Res>> + Send + 'static,
+    Cb: FnOnce(Res) + 'static,
+    Res: Send + 'static,
+{
+    retry_from(Rc::new(fac), callback, 0);
+}
+
+fn retry_from<Fac, Fut, Cb, Res>(fac: Rc<Fac>, callback: Cb, retries: u32)
+where
+    Fac: Fn(Arc<dyn Backend>) -> Fut + 'static,
+    Fut: Future<Output = Result<Res>> + Send + 'static,
+    Cb: FnOnce(Res) + 'static,
+    Res: Send + 'static,
+{
+    let backend = get_backend();
+
+    spawn(fac(backend), move |res| match res {
+        Err(e) if e.is_retryable() && retries < MAX_RETRIES => {
+            tracing::debug!(?e, retries, "retrying");
+            // whatever keeps the backend busy gets some time to finish
+            run_after(RETRY_DELAY * 2u32.pow(retries), move || {
+                retry_from(fac, callback, retries + 1)
+            });
+        }
+        Ok(v) => callback(v),
+        Err(e) => handle_error(e),
+    })
+}
+
+/// Errors shown to the user. User errors, e.g. a wrong passphrase, are shown as toasts which go
+/// away by themselves, everything else is shown in a dialog until it's dismissed.
+#[derive(Clone, Copy)]
+pub struct ErrorReports {
+    pub toasts: RwSignal<Vec<Toast>>,
+    pub dialog: RwSignal<Option<Error>>,
+    next_toast_id: RwSignal<u64>,
+    /// Scope of the reports, so that toasts go away even if the view which has made the call
+    /// is gone by then.
+    scope: Scope,
+}
+
+#[derive(Debug, Clone)]
+pub struct Toast {
+    pub id: u64,
+    pub message: String,
+}
+
+impl ErrorReports {
+    pub fn new() -> ErrorReports {
+        ErrorReports {
+            toasts: RwSignal::new(Vec::new()),
+            dialog: RwSignal::new(None),
+            next_toast_id: RwSignal::new(0),
+            scope: Scope::current(),
+        }
+    }
+
+    pub fn provide(&self) {
+        provide_context(*self);
+    }
+
+    pub fn report(&self, error: Error) {
+        if !error.is_user_error() {
+            // the first error is the most interesting one, the rest are often caused by it
+            if self.dialog.with_untracked(|v| v.is_none()) {
+                self.dialog.set(Some(error));
+            }
+
+            return;
+        }
+
+        let id = self.next_toast_id.get_untracked();
+        self.next_toast_id.set(id + 1);
+
+        self.toasts.update(|v| {
+            v.push(Toast {
+                id,
+                message: error.to_string(),
+            })
+        });
+
+        let toasts = self.toasts;
+        with_scope(self.scope, || {
+            run_after(TOAST_DURATION, move || {
+                toasts.update(|v| v.retain(|toast| toast.id != id));
+            })
+        });
+    }
+
+    pub fn dismiss_dialog(&self) {
+        self.dialog.set(None);
+    }
+}
+
+impl Default for ErrorReports {
+    fn default() -> ErrorReports {
+        ErrorReports::new()
+    }
+}
+
+/// Notifications from the client that cached state has to be fetched again, e.g. after it has
+/// connected to the backend again. Subscriptions are replayed by the client, so they keep working.
+#[derive(Clone)]
+pub struct Resync {
+    subscribe: Rc<dyn Fn() -> BoxStream<()>>,
+}
+
+impl Resync {
+    pub fn new(subscribe: impl Fn() -> BoxStream<()> + 'static) -> Resync {
+        Resync {
+            subscribe: Rc::new(subscribe),
+        }
+    }
+
+    pub fn provide(&self) {
+        provide_context(self.clone());
+    }
+}
+
+/// Calls `f` in the current scope whenever cached state has to be fetched again, until the scope
+/// is disposed. Does nothing if there's no [`Resync`] in scope.
+pub fn on_resync(f: impl Fn() + 'static) {
+    let Some(resync) = use_context::<Resync>() else {
+        return;
+    };
+
+    let scope = Scope::current();
+    stream_for_each((resync.subscribe)(), move |()| with_scope(scope, &f));
+}
+
+/// Handles events until the current scope is disposed, which also closes the stream. While it
+/// is open, the stream is counted in the [`SubscriptionRegistry`] under `name`, and every event
+/// is counted as an update in the [`DebugStats`].
+pub fn subscribe<T: Send + 'static>(
+    name: &'static str,
+    stream: impl Stream<Item = T> + Send + Unpin + 'static,
+    on_message: impl Fn(T) + 'static,
+) {
+    if let Some(registry) = use_context::<SubscriptionRegistry>() {
+        let guard = registry.register(name);
+        Scope::current().create_rw_signal(guard);
+    }
+
+    let stats = use_context::<DebugStats>();
+
+    stream_for_each(stream, move |message| {
+        if let Some(stats) = &stats {
+            stats.count_update(name);
+        }
+
+        on_message(message)
+    });
+}
+
+/// Keeps track of subscriptions made through [`subscribe`], to find views that leak them.
+#[derive(Debug, Clone, Default)]
+pub struct SubscriptionRegistry {
+    active: Rc<RefCell<BTreeMap<&'static str, usize>>>,
+}
+
+impl SubscriptionRegistry {
+    pub fn new() -> SubscriptionRegistry {
+        SubscriptionRegistry::default()
+    }
+
+    pub fn provide(&self) {
+        provide_context(self.clone());
+    }
+
+    /// Number of open subscriptions by name.
+    pub fn active(&self) -> Vec<(&'static str, usize)> {
+        let active = self.active.borrow();
+        active.iter().map(|(&name, &count)| (name, count)).collect()
+    }
+
+    pub fn total(&self) -> usize {
+        self.active.borrow().values().sum()
+    }
+
+    fn register(&self, name: &'static str) -> SubscriptionGuard {
+        *self.active.borrow_mut().entry(name).or_default() += 1;
+
+        SubscriptionGuard {
+            registry: self.clone(),
+            name,
+        }
+    }
+}
+
+struct SubscriptionGuard {
+    registry: SubscriptionRegistry,
+    name: &'static str,
+}
+
+impl Drop for SubscriptionGuard {
+    fn drop(&mut self) {
+        let mut active = self.registry.active.borrow_mut();
+
+        if let Some(count) = active.get_mut(self.name) {
+            *count -= 1;
+
+            if *count == 0 {
+                active.remove(self.name);
+            }
+        }
+    }
+}
@@ -0,0 +1,98 @@
+//! Counters behind the debug panel, for finding out why a view doesn't update, or updates too
+//! often. Nothing is counted unless [`DebugStats`] is provided.
+
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::rc::Rc;
+
+use floem::reactive::{provide_context, use_context, RwSignal, Scope};
+
+#[derive(Debug, Clone, Default)]
+pub struct DebugStats {
+    inner: Rc<RefCell<DebugStatsInner>>,
+}
+
+#[derive(Debug, Default)]
+struct DebugStatsInner {
+    signals: BTreeMap<&'static str, usize>,
+    updates: BTreeMap<&'static str, u64>,
+}
+
+impl DebugStats {
+    pub fn new() -> DebugStats {
+        DebugStats::default()
+    }
+
+    pub fn provide(&self) {
+        provide_context(self.clone());
+    }
+
+    /// Number of live signals created through [`object_signal`] by name.
+    pub fn signals(&self) -> Vec<(&'static str, usize)> {
+        let inner = self.inner.borrow();
+        inner
+            .signals
+            .iter()
+            .map(|(&name, &count)| (name, count))
+            .collect()
+    }
+
+    /// Number of updates of views by name since the last reset, e.g. events handled by
+    /// [`api::subscribe`](crate::api::subscribe).
+    pub fn updates(&self) -> Vec<(&'static str, u64)> {
+        let inner = self.inner.borrow();
+        inner
+            .updates
+            .iter()
+            .map(|(&name, &count)| (name, count))
+            .collect()
+    }
+
+    /// Counts an update of the view `name`.
+    pub fn count_update(&self, name: &'static str) {
+        *self.inner.borrow_mut().updates.entry(name).or_default() += 1;
+    }
+
+    pub fn reset_updates(&self) {
+        self.inner.borrow_mut().updates.clear();
+    }
+
+    fn register_signal(&self, name: &'static str) -> SignalGuard {
+        *self.inner.borrow_mut().signals.entry(name).or_default() += 1;
+
+        SignalGuard {
+            stats: self.clone(),
+            name,
+        }
+    }
+}
+
+/// Creates a signal caching the state of a backend object, counted under `name` until the
+/// current scope is disposed.
+pub fn object_signal<T: 'static>(name: &'static str, value: T) -> RwSignal<T> {
+    if let Some(stats) = use_context::<DebugStats>() {
+        let guard = stats.register_signal(name);
+        Scope::current().create_rw_signal(guard);
+    }
+
+    RwSignal::new(value)
+}
+
+struct SignalGuard {
+    stats: DebugStats,
+    name: &'static str,
+}
+
+impl Drop for SignalGuard {
+    fn drop(&mut self) {
+        let mut inner = self.stats.inner.borrow_mut();
+
+        if let Some(count) = inner.signals.get_mut(self.name) {
+            *count -= 1;
+
+            if *count == 0 {
+                inner.signals.remove(self.name);
+            }
+        }
+    }
+}
@@ -1,4 +1,7 @@
 pub mod api;
+pub mod debug;
+#[cfg(test)]
+pub mod tests;
 pub mod views;
 
 use std::sync::Arc;
@@ -6,16 +9,24 @@ use std::sync::Arc;
 use floem::keyboard::{Key, Modifiers, NamedKey};
 use floem::peniko::Color;
 use floem::reactive::{provide_context, use_context, RwSignal};
-use floem::views::{dyn_container, h_stack, scroll, Decorators};
+use floem::views::{dyn_container, empty, h_stack, scroll, v_stack, Decorators};
 use floem::{IntoView, View};
 use futures::executor::{block_on, ThreadPool};
 use rdaw_api::arrangement::ArrangementId;
-use rdaw_api::document::DocumentId;
-use rdaw_api::{Backend, Error};
+use rdaw_api::document::{DocumentId, DocumentWarning};
+use rdaw_api::{Backend, Error, ErrorKind};
+use rdaw_core::path::Utf8PathBuf;
 use rdaw_ui::task::provide_executor;
 use rdaw_ui::theme::Theme;
 use rdaw_ui::views::tree::{tree, FsTreeModel};
-use views::arrangement;
+use rdaw_ui::views::LiveLayerSettings;
+use views::{
+    arrangement, debug_panel, document_warnings, error_reports, log_panel, monitoring_panel,
+    passphrase_prompt,
+};
+
+/// Frame rate of meters and the playhead in performance mode.
+const PERFORMANCE_MODE_MAX_FPS: u32 = 15;
 
 pub fn app_view(document_id: DocumentId, main_arrangement: ArrangementId) -> impl IntoView {
     provide_document_id(document_id);
@@ -41,13 +52,29 @@ pub fn provide_document_id(id: DocumentId) {
     provide_context(id);
 }
 
-pub fn run(backend: Arc<dyn Backend>) {
+/// Points the backend running next to the frontend at the user's settings and presets. Backends
+/// of other users are left alone.
+pub async fn configure_local_backend(backend: &dyn Backend) -> Result<(), Error> {
+    backend.set_settings_path(settings_path()).await?;
+    backend.set_user_preset_dir(user_preset_dir()).await
+}
+
+pub fn run(backend: Arc<dyn Backend>, resync: api::Resync) {
     let executor = Arc::new(ThreadPool::builder().pool_size(1).create().unwrap());
 
     provide_executor(executor.clone());
     provide_context(backend.clone());
+    resync.provide();
+    api::SubscriptionRegistry::new().provide();
+    debug::DebugStats::new().provide();
+    let errors = api::ErrorReports::new();
+    errors.provide();
     Theme::light().provide();
 
+    let live_layers = LiveLayerSettings::default();
+    live_layers.provide();
+    let default_max_fps = live_layers.max_fps.get_untracked();
+
     let (document_id, main_arrangement) = block_on(async move {
         let document_id = backend.create_document().await?;
         let main_arrangement = backend.get_document_arrangement(document_id).await?;
@@ -57,17 +84,67 @@ pub fn run(backend: Arc<dyn Backend>) {
 
     floem::launch(move || {
         let state = RwSignal::new((document_id, main_arrangement));
+        let prompt = RwSignal::new(None);
+        let warnings = RwSignal::new(Vec::new());
+        let show_logs = RwSignal::new(false);
+        let show_debug = RwSignal::new(false);
+        let show_monitoring = RwSignal::new(false);
 
-        let view = dyn_container(move || state.get(), move |(doc, arr)| app_view(doc, arr))
-            .style(|s| s.width_full().height_full())
-            .keyboard_navigatable()
-            .into_view();
+        let view = v_stack((
+            error_reports(errors),
+            dyn_container(
+                move || prompt.get(),
+                move |path: Option<Utf8PathBuf>| match path {
+                    Some(path) => passphrase_prompt(move |passphrase| {
+                        open_encrypted_document(state, prompt, warnings, path.clone(), passphrase)
+                    })
+                    .into_any(),
+                    None => empty().into_any(),
+                },
+            ),
+            dyn_container(
+                move || warnings.with(|v| !v.is_empty()),
+                move |show| match show {
+                    true => document_warnings(warnings).into_any(),
+                    false => empty().into_any(),
+                },
+            ),
+            dyn_container(move || state.get(), move |(doc, arr)| app_view(doc, arr))
+                .style(|s| s.width_full().height_full()),
+            dyn_container(
+                move || show_logs.get(),
+                move |show| match show {
+                    true => log_panel().into_any(),
+                    false => empty().into_any(),
+                },
+            ),
+            dyn_container(
+                move || show_monitoring.get().then(|| state.get().1),
+                move |arrangement_id| match arrangement_id {
+                    Some(id) => monitoring_panel(id).into_any(),
+                    None => empty().into_any(),
+                },
+            ),
+            dyn_container(
+                move || show_debug.get(),
+                move |show| match show {
+                    true => debug_panel().into_any(),
+                    false => empty().into_any(),
+                },
+            ),
+        ))
+        .style(|s| s.width_full().height_full())
+        .keyboard_navigatable()
+        .into_view();
 
         let id = view.id();
 
         view.on_key_down(Key::Named(NamedKey::F11), Modifiers::empty(), move |_| {
             id.inspect()
         })
+        .on_key_down(Key::Named(NamedKey::F12), Modifiers::empty(), move |_| {
+            show_debug.update(|v| *v = !*v);
+        })
         .on_key_down(Key::Named(NamedKey::F1), Modifiers::empty(), move |_| {
             let document_id = state.get().0;
             api::call(
@@ -79,14 +156,96 @@ pub fn run(backend: Arc<dyn Backend>) {
             );
         })
         .on_key_down(Key::Named(NamedKey::F2), Modifiers::empty(), move |_| {
-            api::call(
-                move |api| async move {
-                    let document_id = api.open_document("/tmp/test.rdaw".into()).await?;
-                    let arrangement_id = api.get_document_arrangement(document_id).await?;
-                    Ok((document_id, arrangement_id))
-                },
-                move |new_state| state.set(new_state),
-            );
+            open_document(state, prompt, warnings, "/tmp/test.rdaw".into());
+        })
+        .on_key_down(Key::Named(NamedKey::F3), Modifiers::empty(), move |_| {
+            show_logs.update(|v| *v = !*v);
+        })
+        .on_key_down(Key::Named(NamedKey::F4), Modifiers::empty(), move |_| {
+            live_layers.max_fps.update(|v| {
+                *v = if *v == default_max_fps {
+                    PERFORMANCE_MODE_MAX_FPS
+                } else {
+                    default_max_fps
+                }
+            });
+        })
+        .on_key_down(Key::Named(NamedKey::F5), Modifiers::empty(), move |_| {
+            show_monitoring.update(|v| *v = !*v);
         })
     });
 }
+
+fn config_dir() -> Option<Utf8PathBuf> {
+    let config_dir = match std::env::var("XDG_CONFIG_HOME") {
+        Ok(v) if !v.is_empty() => Utf8PathBuf::from(v),
+        _ => Utf8PathBuf::from(std::env::var("HOME").ok()?).join(".config"),
+    };
+
+    Some(config_dir.join("rdaw"))
+}
+
+fn settings_path() -> Option<Utf8PathBuf> {
+    Some(config_dir()?.join("settings.json"))
+}
+
+fn user_preset_dir() -> Option<Utf8PathBuf> {
+    Some(config_dir()?.join("presets"))
+}
+
+type DocumentState = RwSignal<(DocumentId, ArrangementId)>;
+
+type WarningsState = RwSignal<Vec<DocumentWarning>>;
+
+fn open_document(
+    state: DocumentState,
+    prompt: RwSignal<Option<Utf8PathBuf>>,
+    warnings: WarningsState,
+    path: Utf8PathBuf,
+) {
+    api::call(
+        {
+            let path = path.clone();
+            move |api| async move {
+                let document_id = match api.open_document(path).await {
+                    Ok(v) => v,
+                    Err(e) if e.kind() == ErrorKind::InvalidPassphrase => return Ok(None),
+                    Err(e) => return Err(e),
+                };
+
+                let arrangement_id = api.get_document_arrangement(document_id).await?;
+                let new_warnings = api.get_document_warnings(document_id).await?;
+                Ok(Some(((document_id, arrangement_id), new_warnings)))
+            }
+        },
+        move |res| match res {
+            Some((new_state, new_warnings)) => {
+                state.set(new_state);
+                warnings.set(new_warnings);
+            }
+            None => prompt.set(Some(path)),
+        },
+    );
+}
+
+fn open_encrypted_document(
+    state: DocumentState,
+    prompt: RwSignal<Option<Utf8PathBuf>>,
+    warnings: WarningsState,
+    path: Utf8PathBuf,
+    passphrase: String,
+) {
+    api::call(
+        move |api| async move {
+            let document_id = api.open_encrypted_document(path, passphrase).await?;
+            let arrangement_id = api.get_document_arrangement(document_id).await?;
+            let new_warnings = api.get_document_warnings(document_id).await?;
+            Ok(((document_id, arrangement_id), new_warnings))
+        },
+        move |(new_state, new_warnings)| {
+            prompt.set(None);
+            state.set(new_state);
+            warnings.set(new_warnings);
+        },
+    );
+}
@@ -0,0 +1,358 @@
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+
+use floem::reactive::{provide_context, with_scope, RwSignal, Scope};
+use futures::channel::mpsc::{self, UnboundedSender};
+use futures::executor::LocalPool;
+use futures::task::LocalSpawnExt;
+use futures::{FutureExt, StreamExt};
+use rdaw_api::document::DocumentOperations;
+use rdaw_api::track::TrackOperations;
+use rdaw_api::{format_err, BackendProtocol, ErrorKind, Result};
+use rdaw_backend::Backend;
+use rdaw_core::path::Utf8Path;
+use rdaw_rpc::transport::{self, LocalClientTransport};
+use rdaw_rpc::Client;
+use rdaw_ui::task::{provide_manual_executor, ManualExecutor};
+use tempfile::NamedTempFile;
+
+use crate::api::{self, ErrorReports, Resync, SubscriptionRegistry, RETRY_DELAY, TOAST_DURATION};
+use crate::debug::DebugStats;
+use crate::views::{arrangement, track_locked};
+use crate::{open_document, open_encrypted_document, provide_document_id};
+
+pub type TestClient = Client<BackendProtocol, LocalClientTransport<BackendProtocol>>;
+
+pub struct TestContext {
+    pub executor: ManualExecutor,
+    pub scope: Scope,
+    pub client: TestClient,
+    pub subscriptions: SubscriptionRegistry,
+    pub debug: DebugStats,
+    pub errors: ErrorReports,
+    resync_senders: Arc<Mutex<Vec<UnboundedSender<()>>>>,
+}
+
+impl TestContext {
+    pub fn with_scope<T>(&self, f: impl FnOnce() -> T) -> T {
+        with_scope(self.scope, f)
+    }
+
+    pub fn settle(&self) {
+        self.executor.run_until_stalled();
+    }
+
+    /// Tells the views to fetch their cached state again, as the client does after reconnecting.
+    pub fn resync(&self) {
+        let mut senders = self.resync_senders.lock().unwrap();
+        senders.retain(|sender| sender.unbounded_send(()).is_ok());
+        drop(senders);
+
+        self.settle();
+    }
+}
+
+pub fn run_test(f: impl FnOnce(&TestContext)) {
+    let executor = ManualExecutor::new();
+    let spawner = executor.spawner();
+
+    let (client_transport, server_transport) = transport::local(None);
+
+    let client = Client::new(client_transport);
+    let mut backend = Backend::new(server_transport);
+
+    spawner
+        .spawn_local(client.clone().handle().map(|v| v.unwrap()))
+        .unwrap();
+
+    spawner
+        .spawn_local(async move { backend.handle().await.unwrap() })
+        .unwrap();
+
+    let scope = Scope::new();
+    let subscriptions = SubscriptionRegistry::new();
+    let debug = DebugStats::new();
+
+    let resync_senders = Arc::new(Mutex::new(Vec::new()));
+    let resync = Resync::new({
+        let resync_senders = resync_senders.clone();
+        move || {
+            let (sender, receiver) = mpsc::unbounded();
+            resync_senders.lock().unwrap().push(sender);
+            receiver.boxed()
+        }
+    });
+
+    let errors = with_scope(scope, || {
+        provide_manual_executor(executor.clone());
+        resync.provide();
+        provide_context::<Arc<dyn rdaw_api::Backend>>(Arc::new(client.clone()));
+        subscriptions.provide();
+        debug.provide();
+
+        let errors = ErrorReports::new();
+        errors.provide();
+        errors
+    });
+
+    let cx = TestContext {
+        executor,
+        scope,
+        client,
+        subscriptions,
+        debug,
+        errors,
+        resync_senders,
+    };
+
+    f(&cx);
+
+    cx.scope.dispose();
+}
+
+fn create_encrypted_document(path: &Utf8Path, passphrase: &str) -> Result<()> {
+    let mut executor = LocalPool::new();
+    let spawner = executor.spawner();
+
+    let (client_transport, server_transport) = transport::local(None);
+
+    let client: TestClient = Client::new(client_transport);
+    let mut backend = Backend::new(server_transport);
+
+    spawner
+        .spawn_local(client.clone().handle().map(|v| v.unwrap()))
+        .unwrap();
+
+    spawner
+        .spawn_local(async move { backend.handle().await.unwrap() })
+        .unwrap();
+
+    executor.run_until(async move {
+        let document_id = client.create_document().await?;
+
+        client
+            .set_document_passphrase(document_id, Some(passphrase.into()))
+            .await?;
+
+        client.save_document_as(document_id, path.into()).await
+    })
+}
+
+#[test]
+fn open_encrypted_document_prompts_for_passphrase() -> Result<()> {
+    let temp_file = NamedTempFile::with_prefix(".rdaw-test-")?;
+    let path = Utf8Path::from_path(temp_file.path()).unwrap().to_path_buf();
+
+    create_encrypted_document(&path, "secret")?;
+
+    run_test(|cx| {
+        let initial_state = cx
+            .executor
+            .run_until({
+                let client = cx.client.clone();
+                async move {
+                    let document_id = client.create_document().await?;
+                    let arrangement_id = client.get_document_arrangement(document_id).await?;
+                    Ok::<_, rdaw_api::Error>((document_id, arrangement_id))
+                }
+            })
+            .unwrap();
+
+        cx.with_scope(|| {
+            let state = RwSignal::new(initial_state);
+            let prompt = RwSignal::new(None);
+            let warnings = RwSignal::new(Vec::new());
+
+            open_document(state, prompt, warnings, path.clone());
+            cx.settle();
+
+            assert_eq!(prompt.get_untracked(), Some(path.clone()));
+            assert_eq!(state.get_untracked(), initial_state);
+
+            open_encrypted_document(state, prompt, warnings, path.clone(), "wrong".into());
+            cx.settle();
+
+            assert_eq!(prompt.get_untracked(), Some(path.clone()));
+            assert_eq!(state.get_untracked(), initial_state);
+
+            open_encrypted_document(state, prompt, warnings, path.clone(), "secret".into());
+            cx.settle();
+
+            assert_eq!(prompt.get_untracked(), None);
+            assert_ne!(state.get_untracked().0, initial_state.0);
+        });
+    });
+
+    Ok(())
+}
+
+#[test]
+fn unmounting_arrangement_closes_streams() {
+    run_test(|cx| {
+        let (document_id, arrangement_id) = cx
+            .executor
+            .run_until({
+                let client = cx.client.clone();
+                async move {
+                    let document_id = client.create_document().await?;
+                    let arrangement_id = client.get_document_arrangement(document_id).await?;
+                    Ok::<_, rdaw_api::Error>((document_id, arrangement_id))
+                }
+            })
+            .unwrap();
+
+        let view_scope = cx.scope.create_child();
+
+        with_scope(view_scope, || {
+            provide_document_id(document_id);
+            let _view = arrangement(arrangement_id);
+        });
+        cx.settle();
+
+        assert!(cx.subscriptions.total() > 0);
+        assert_eq!(cx.client.open_stream_count(), cx.subscriptions.total());
+
+        view_scope.dispose();
+        cx.settle();
+
+        assert_eq!(cx.subscriptions.active(), vec![]);
+        assert_eq!(cx.client.open_stream_count(), 0);
+    });
+}
+
+#[test]
+fn debug_stats_count_object_signals_and_updates() {
+    run_test(|cx| {
+        let track_id = cx
+            .executor
+            .run_until({
+                let client = cx.client.clone();
+                async move {
+                    let document_id = client.create_document().await?;
+                    client.create_track(document_id).await
+                }
+            })
+            .unwrap();
+
+        let view_scope = cx.scope.create_child();
+
+        let locked = with_scope(view_scope, || track_locked(track_id));
+        cx.settle();
+
+        assert_eq!(cx.debug.signals(), vec![("track_locked", 1)]);
+        assert_eq!(cx.debug.updates(), vec![]);
+
+        cx.executor
+            .run_until({
+                let client = cx.client.clone();
+                async move { client.set_track_locked(track_id, true).await }
+            })
+            .unwrap();
+        cx.settle();
+
+        assert!(locked.get_untracked());
+        assert_eq!(cx.debug.updates(), vec![("track_locked", 1)]);
+
+        cx.debug.reset_updates();
+        assert_eq!(cx.debug.updates(), vec![]);
+
+        view_scope.dispose();
+        assert_eq!(cx.debug.signals(), vec![]);
+    });
+}
+
+#[test]
+fn retries_wait_for_the_executor() {
+    run_test(|cx| {
+        let attempts = Arc::new(AtomicU32::new(0));
+
+        cx.with_scope(|| {
+            let attempts = attempts.clone();
+            api::call_retry(
+                move |_| {
+                    let attempts = attempts.clone();
+                    async move {
+                        attempts.fetch_add(1, Ordering::Relaxed);
+                        Err::<(), _>(format_err!(ErrorKind::Busy, "backend is busy"))
+                    }
+                },
+                drop,
+            );
+        });
+        cx.settle();
+
+        assert_eq!(attempts.load(Ordering::Relaxed), 1);
+
+        cx.executor.advance(RETRY_DELAY);
+        assert_eq!(attempts.load(Ordering::Relaxed), 2);
+
+        // the delay is doubled after every retry
+        cx.executor.advance(RETRY_DELAY);
+        assert_eq!(attempts.load(Ordering::Relaxed), 2);
+        cx.executor.advance(RETRY_DELAY);
+        assert_eq!(attempts.load(Ordering::Relaxed), 3);
+
+        cx.executor.advance(RETRY_DELAY * 4);
+        assert_eq!(attempts.load(Ordering::Relaxed), 4);
+
+        // out of retries
+        assert!(cx.errors.dialog.with_untracked(|v| v.is_some()));
+        cx.executor.advance(RETRY_DELAY * 8);
+        assert_eq!(attempts.load(Ordering::Relaxed), 4);
+    });
+}
+
+#[test]
+fn user_errors_are_shown_as_toasts() {
+    run_test(|cx| {
+        cx.with_scope(|| {
+            api::handle_error(format_err!(ErrorKind::NotFound, "file not found"));
+            api::handle_error(format_err!(ErrorKind::Sql, "disk I/O error"));
+            api::handle_error(format_err!(ErrorKind::Io, "broken pipe"));
+        });
+
+        let toasts = cx.errors.toasts.get_untracked();
+        assert_eq!(toasts.len(), 1);
+        assert_eq!(toasts[0].message, "file not found");
+
+        // only the first unexpected error is shown
+        let dialog = cx.errors.dialog.get_untracked().unwrap();
+        assert_eq!(dialog.kind(), ErrorKind::Sql);
+
+        cx.executor.advance(TOAST_DURATION);
+        assert!(cx.errors.toasts.with_untracked(|v| v.is_empty()));
+
+        cx.errors.dismiss_dialog();
+        assert!(cx.errors.dialog.with_untracked(|v| v.is_none()));
+    });
+}
+
+#[test]
+fn resync_refetches_cached_state() {
+    run_test(|cx| {
+        let track_id = cx
+            .executor
+            .run_until({
+                let client = cx.client.clone();
+                async move {
+                    let document_id = client.create_document().await?;
+                    client.create_track(document_id).await
+                }
+            })
+            .unwrap();
+
+        let view_scope = cx.scope.create_child();
+
+        let locked = with_scope(view_scope, || track_locked(track_id));
+        cx.settle();
+        assert!(!locked.get_untracked());
+
+        // as if an event got lost while the client was disconnected
+        locked.set(true);
+
+        cx.resync();
+        assert!(!locked.get_untracked());
+
+        view_scope.dispose();
+    });
+}
@@ -1,21 +1,26 @@
 use floem::event::{Event, EventListener, EventPropagation};
+use floem::keyboard::{Key, Modifiers, NamedKey};
 use floem::kurbo::Vec2;
 use floem::peniko::Color;
-use floem::reactive::{batch, create_memo, RwSignal};
+use floem::reactive::{batch, create_memo, provide_context, use_context, RwSignal};
 use floem::style::CursorStyle;
 use floem::taffy::{Display, Position};
 use floem::views::{
-    container, dyn_container, empty, h_stack, scroll, v_stack, virtual_stack, Decorators,
+    container, dyn_container, empty, h_stack, scroll, stack, v_stack, virtual_stack, Decorators,
     VirtualDirection, VirtualItemSize, VirtualVector,
 };
 use floem::{IntoView, View};
 use rdaw_api::arrangement::ArrangementId;
-use rdaw_api::track::{TrackHierarchy, TrackHierarchyEvent, TrackId, TrackNode};
+use rdaw_api::time::{BeatTime, Time};
+use rdaw_api::track::{PasteTarget, RippleMode, TrackHierarchy, TrackId, TrackItemId, TrackNode};
+use rdaw_api::transport::{PlaybackState, RangeEnd};
 use rdaw_core::collections::{HashMap, HashSet, ImVec};
-use rdaw_ui::task::stream_for_each;
+use rdaw_core::time::RealTime;
+use rdaw_ui::theme::{ColorKind, Level};
+use rdaw_ui::views::button;
 
 use crate::api;
-use crate::views::{track_control, track_items};
+use crate::views::{playhead, track_control, track_items};
 
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
 enum DropLocation {
@@ -36,27 +41,243 @@ struct State {
     track_heights: RwSignal<HashMap<TrackNode, RwSignal<f64>>>,
 }
 
+/// Ripple mode used by item edits in the current arrangement.
+pub fn get_ripple_mode() -> RwSignal<RippleMode> {
+    use_context().expect("no ripple mode in scope")
+}
+
+/// Where the edit cursor is and which time range is selected in the current arrangement.
+#[derive(Clone, Copy)]
+pub struct TimeSelection {
+    pub cursor: RwSignal<Time>,
+    pub range: RwSignal<Option<(Time, Time)>>,
+}
+
+pub fn get_time_selection() -> TimeSelection {
+    use_context().expect("no time selection in scope")
+}
+
+/// Items selected in the current arrangement, which edits like nudging apply to.
+#[derive(Clone, Copy)]
+pub struct ItemSelection(pub RwSignal<Vec<(TrackId, TrackItemId)>>);
+
+pub fn get_item_selection() -> ItemSelection {
+    use_context().expect("no item selection in scope")
+}
+
 pub fn arrangement(id: ArrangementId) -> impl IntoView {
     let main_track = RwSignal::new(None);
+    let ripple_mode = RwSignal::new(RippleMode::Off);
+    provide_context(ripple_mode);
 
-    api::call(
+    let selection = TimeSelection {
+        cursor: RwSignal::new(Time::Real(RealTime::ZERO)),
+        range: RwSignal::new(None),
+    };
+    provide_context(selection);
+
+    let item_selection = ItemSelection(RwSignal::new(Vec::new()));
+    provide_context(item_selection);
+
+    let range_end = RwSignal::new(RangeEnd::Start);
+
+    api::call_retry(
         move |api| async move { api.get_arrangement_main_track(id).await },
         move |id| {
             main_track.set(Some(id));
         },
     );
 
-    dyn_container(
+    let tracks = dyn_container(
         move || main_track.get(),
         move |main_track| match main_track {
-            Some(id) => track_tree(id).into_any(),
+            Some(main_track) => track_tree(id, main_track).into_any(),
             None => empty().into_any(),
         },
     )
+    .style(|s| s.width_full().height_full());
+
+    // space toggles playback from the cursor, shift+space auditions the selected range
+    let play_from_cursor = move |_: &Event| {
+        let cursor = selection.cursor.get_untracked();
+        api::call(
+            move |api| async move {
+                match api.get_transport_state(id).await?.playback {
+                    PlaybackState::Playing => api.stop(id).await,
+                    _ => api.play_from(id, cursor).await,
+                }
+            },
+            drop,
+        );
+    };
+
+    let play_selection = move |_: &Event| {
+        let Some((start, end)) = selection.range.get_untracked() else {
+            return;
+        };
+
+        let after = range_end.get_untracked();
+        api::call(
+            move |api| async move { api.play_range(id, start, end, after).await },
+            drop,
+        );
+    };
+
+    // plus and minus, on the numpad too, nudge the selected items by a beat, or by 10ms with alt
+    let nudge = move |delta: Time| {
+        move |_: &Event| {
+            let items = item_selection.0.get_untracked();
+            if items.is_empty() {
+                return;
+            }
+
+            api::call(
+                move |api| async move { api.nudge_track_items(id, items, delta, false).await },
+                drop,
+            );
+        }
+    };
+
+    // ctrl+c copies the selected items, ctrl+v pastes them at the cursor onto the track of the
+    // first selected item, ctrl+d duplicates them. Pasted items become the selection.
+    let copy = move |_: &Event| {
+        let items = item_selection.0.get_untracked();
+        if items.is_empty() {
+            return;
+        }
+
+        api::call(
+            move |api| async move { api.copy_track_items(id, items).await },
+            drop,
+        );
+    };
+
+    let paste = move |_: &Event| {
+        let Some(&(track_id, _)) = item_selection.0.get_untracked().first() else {
+            return;
+        };
+
+        let target = PasteTarget {
+            track_id,
+            offset: selection.cursor.get_untracked(),
+        };
+        api::call(
+            move |api| async move { api.paste_track_items(id, target, false).await },
+            move |items| item_selection.0.set(items),
+        );
+    };
+
+    let duplicate = move |_: &Event| {
+        let items = item_selection.0.get_untracked();
+        if items.is_empty() {
+            return;
+        }
+
+        api::call(
+            move |api| async move { api.duplicate_track_items(id, items, false).await },
+            move |items| item_selection.0.set(items),
+        );
+    };
+
+    // delete removes the selected items at once
+    let delete = move |_: &Event| {
+        let items = item_selection.0.get_untracked();
+        if items.is_empty() {
+            return;
+        }
+
+        api::call(
+            move |api| async move { api.delete_track_items(items, false).await },
+            move |()| item_selection.0.set(Vec::new()),
+        );
+    };
+
+    let beat = BeatTime::from_beats(1);
+    let fine = RealTime::from_secs_f64(0.01);
+
+    v_stack((
+        h_stack((ripple_toggle(ripple_mode), range_end_toggle(range_end))),
+        tracks,
+    ))
     .style(|s| s.width_full().height_full())
+    .keyboard_navigatable()
+    .on_key_down(
+        Key::Named(NamedKey::Space),
+        Modifiers::empty(),
+        play_from_cursor,
+    )
+    .on_key_down(
+        Key::Named(NamedKey::Space),
+        Modifiers::SHIFT,
+        play_selection,
+    )
+    .on_key_down(
+        Key::Character("+".into()),
+        Modifiers::empty(),
+        nudge(Time::Beat(beat)),
+    )
+    .on_key_down(
+        Key::Character("-".into()),
+        Modifiers::empty(),
+        nudge(Time::Beat(BeatTime::ZERO - beat)),
+    )
+    .on_key_down(
+        Key::Character("+".into()),
+        Modifiers::ALT,
+        nudge(Time::Real(fine)),
+    )
+    .on_key_down(
+        Key::Character("-".into()),
+        Modifiers::ALT,
+        nudge(Time::Real(RealTime::ZERO - fine)),
+    )
+    .on_key_down(Key::Character("c".into()), Modifiers::CONTROL, copy)
+    .on_key_down(Key::Character("v".into()), Modifiers::CONTROL, paste)
+    .on_key_down(Key::Character("d".into()), Modifiers::CONTROL, duplicate)
+    .on_key_down(Key::Named(NamedKey::Delete), Modifiers::empty(), delete)
+}
+
+fn range_end_toggle(range_end: RwSignal<RangeEnd>) -> impl IntoView {
+    let toggle = move |_ev: &Event| {
+        range_end.update(|v| {
+            *v = match v {
+                RangeEnd::Start => RangeEnd::PreviousPosition,
+                RangeEnd::PreviousPosition => RangeEnd::Start,
+            }
+        });
+    };
+
+    button(ColorKind::Surface, Level::Mid, move || {
+        match range_end.get() {
+            RangeEnd::Start => "After range: start",
+            RangeEnd::PreviousPosition => "After range: previous",
+        }
+    })
+    .on_click_stop(toggle)
+    .style(|s| s.width(200.0).margin(5.0))
+}
+
+fn ripple_toggle(mode: RwSignal<RippleMode>) -> impl IntoView {
+    let toggle = move |_ev: &Event| {
+        mode.update(|v| {
+            *v = match v {
+                RippleMode::Off => RippleMode::Track,
+                RippleMode::Track => RippleMode::AllTracks,
+                RippleMode::AllTracks => RippleMode::Off,
+            }
+        });
+    };
+
+    button(ColorKind::Surface, Level::Mid, move || match mode.get() {
+        RippleMode::Off => "Ripple: off",
+        RippleMode::Track => "Ripple: track",
+        RippleMode::AllTracks => "Ripple: all tracks",
+    })
+    .on_click_stop(toggle)
+    .style(|s| s.width(150.0).margin(5.0))
 }
 
-fn track_tree(root: TrackId) -> impl IntoView {
+fn track_tree(id: ArrangementId, root: TrackId) -> impl IntoView {
     let state = State {
         selection: RwSignal::new(None),
         transitive_selection: RwSignal::new(HashSet::default()),
@@ -76,15 +297,19 @@ fn track_tree(root: TrackId) -> impl IntoView {
         move |(new_hierarchy, stream)| {
             state.hierarchy.set(new_hierarchy);
 
-            stream_for_each(stream, move |event| {
-                let TrackHierarchyEvent::ChildrenChanged { id, new_children } = event;
-                state.hierarchy.update(|v| {
-                    v.set_children(id, new_children.into_iter().collect());
-                });
+            api::subscribe("track_hierarchy", stream, move |event| {
+                state.hierarchy.update(|v| v.apply_event(event));
             })
         },
     );
 
+    api::on_resync(move || {
+        api::call(
+            move |api| async move { api.get_track_hierarchy(root).await },
+            move |new_hierarchy| state.hierarchy.set(new_hierarchy),
+        )
+    });
+
     let order = create_memo(move |_| {
         let mut order = ImVec::new();
 
@@ -127,9 +352,9 @@ fn track_tree(root: TrackId) -> impl IntoView {
     scroll(
         h_stack((
             control_tree.style(|s| s.width(400.0)),
-            items_tree.style(|s| s.flex_grow(1.0)).on_event(
-                EventListener::PointerWheel,
-                move |ev| {
+            stack((items_tree.style(|s| s.width_full()), playhead(id)))
+                .style(|s| s.flex_grow(1.0).position(Position::Relative))
+                .on_event(EventListener::PointerWheel, move |ev| {
                     let Event::PointerWheel(ev) = ev else {
                         return EventPropagation::Continue;
                     };
@@ -140,8 +365,7 @@ fn track_tree(root: TrackId) -> impl IntoView {
                     }
 
                     EventPropagation::Continue
-                },
-            ),
+                }),
         ))
         .style(|s| s.width_full()),
     )
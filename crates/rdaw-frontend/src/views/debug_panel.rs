@@ -0,0 +1,94 @@
+use std::fmt::Display;
+
+use floem::reactive::{use_context, RwSignal};
+use floem::views::{dyn_stack, h_stack, label, scroll, v_stack, Decorators};
+use floem::IntoView;
+use rdaw_ui::theme::{ColorKind, Level};
+use rdaw_ui::views::button;
+
+use crate::api::SubscriptionRegistry;
+use crate::debug::DebugStats;
+
+/// Counters of the reactive wiring of rdaw's own views, complementing floem's inspector.
+pub fn debug_panel() -> impl IntoView {
+    let subscriptions = use_context::<SubscriptionRegistry>().unwrap_or_default();
+    let stats = use_context::<DebugStats>().unwrap_or_default();
+
+    let snapshot = RwSignal::new(Snapshot::default());
+
+    let refresh = {
+        let stats = stats.clone();
+        move || {
+            snapshot.set(Snapshot {
+                subscriptions: subscriptions.active(),
+                signals: stats.signals(),
+                updates: stats.updates(),
+            })
+        }
+    };
+
+    refresh();
+
+    let refresh_button = button(ColorKind::Surface, Level::Mid, || "Refresh")
+        .on_click_stop({
+            let refresh = refresh.clone();
+            move |_ev| refresh()
+        })
+        .style(|s| s.width(80.0));
+
+    let reset_button = button(ColorKind::Surface, Level::Mid, || "Reset updates")
+        .on_click_stop(move |_ev| {
+            stats.reset_updates();
+            refresh();
+        })
+        .style(|s| s.width(120.0));
+
+    let controls = h_stack((refresh_button, reset_button)).style(|s| s.column_gap(5.0));
+
+    let columns = h_stack((
+        counters("Subscriptions", move || {
+            snapshot.with(|v| format_counts(&v.subscriptions))
+        }),
+        counters("Object signals", move || {
+            snapshot.with(|v| format_counts(&v.signals))
+        }),
+        counters("Updates", move || {
+            snapshot.with(|v| format_counts(&v.updates))
+        }),
+    ))
+    .style(|s| s.column_gap(20.0));
+
+    v_stack((controls, scroll(columns).style(|s| s.height(200.0))))
+        .style(|s| s.padding(10).row_gap(5.0))
+}
+
+#[derive(Debug, Clone, Default)]
+struct Snapshot {
+    subscriptions: Vec<(&'static str, usize)>,
+    signals: Vec<(&'static str, usize)>,
+    updates: Vec<(&'static str, u64)>,
+}
+
+fn counters(title: &'static str, rows: impl Fn() -> Vec<String> + 'static) -> impl IntoView {
+    let list = dyn_stack(
+        move || rows().into_iter().enumerate(),
+        |(idx, row)| (*idx, row.clone()),
+        |(_, row)| label(move || row.clone()),
+    )
+    .style(|s| s.flex_col());
+
+    v_stack((label(move || title), list)).style(|s| s.min_width(200.0).row_gap(2.0))
+}
+
+fn format_counts<T: Display>(counts: &[(&'static str, T)]) -> Vec<String> {
+    let mut rows = counts
+        .iter()
+        .map(|(name, count)| format!("{name}: {count}"))
+        .collect::<Vec<_>>();
+
+    if rows.is_empty() {
+        rows.push("none".into());
+    }
+
+    rows
+}
@@ -0,0 +1,44 @@
+use floem::reactive::RwSignal;
+use floem::views::{dyn_stack, h_stack, label, scroll, v_stack, Decorators};
+use floem::IntoView;
+use rdaw_api::document::DocumentWarning;
+use rdaw_ui::theme::{ColorKind, Level};
+use rdaw_ui::views::button;
+
+/// Problems found when the document was opened, shown until they're dismissed.
+pub fn document_warnings(warnings: RwSignal<Vec<DocumentWarning>>) -> impl IntoView {
+    let dismiss_button = button(ColorKind::Surface, Level::Mid, || "Dismiss")
+        .on_click_stop(move |_| warnings.set(Vec::new()))
+        .style(|s| s.width(80.0));
+
+    let header = h_stack((
+        label(move || {
+            let count = warnings.with(|v| v.len());
+            format!("Found {count} problems when opening the document")
+        }),
+        dismiss_button,
+    ))
+    .style(|s| s.items_center().column_gap(5.0));
+
+    let list = dyn_stack(
+        move || warnings.get().into_iter().enumerate(),
+        |(idx, warning)| (*idx, warning.uuid),
+        |(_, warning)| label(move || format_warning(&warning)),
+    )
+    .style(|s| s.flex_col());
+
+    v_stack((header, scroll(list).style(|s| s.max_height(150.0))))
+        .style(|s| s.padding(10).row_gap(5.0))
+}
+
+fn format_warning(warning: &DocumentWarning) -> String {
+    let status = match warning.repaired {
+        true => "repaired",
+        false => "not repaired",
+    };
+
+    format!(
+        "{:?} {}: {} ({status})",
+        warning.object_type, warning.uuid, warning.message
+    )
+}
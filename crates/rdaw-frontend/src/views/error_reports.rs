@@ -0,0 +1,39 @@
+use floem::views::{dyn_container, dyn_stack, empty, h_stack, label, v_stack, Decorators};
+use floem::IntoView;
+use rdaw_ui::theme::{ColorKind, Level};
+use rdaw_ui::views::button;
+
+use crate::api::ErrorReports;
+
+/// Toasts of user errors, and a dialog with the first unexpected error until it's dismissed.
+pub fn error_reports(reports: ErrorReports) -> impl IntoView {
+    let dialog = dyn_container(
+        move || reports.dialog.with(|v| v.as_ref().map(|e| e.to_string())),
+        move |message| match message {
+            Some(message) => error_dialog(reports, message).into_any(),
+            None => empty().into_any(),
+        },
+    );
+
+    let toasts = dyn_stack(
+        move || reports.toasts.get(),
+        |toast| toast.id,
+        |toast| label(move || toast.message.clone()).style(|s| s.padding(5.0)),
+    )
+    .style(|s| s.flex_col().row_gap(5.0));
+
+    v_stack((dialog, toasts))
+}
+
+fn error_dialog(reports: ErrorReports, message: String) -> impl IntoView {
+    let dismiss_button = button(ColorKind::Surface, Level::Mid, || "Dismiss")
+        .on_click_stop(move |_| reports.dismiss_dialog())
+        .style(|s| s.width(80.0));
+
+    v_stack((
+        h_stack((label(|| "Something went wrong"), dismiss_button))
+            .style(|s| s.items_center().column_gap(5.0)),
+        label(move || message.clone()),
+    ))
+    .style(|s| s.padding(10).row_gap(5.0))
+}
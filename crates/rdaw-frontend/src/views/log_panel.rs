@@ -0,0 +1,90 @@
+use std::time::UNIX_EPOCH;
+
+use floem::event::Event;
+use floem::reactive::RwSignal;
+use floem::views::{dyn_stack, h_stack, label, scroll, text_input, v_stack, Decorators};
+use floem::IntoView;
+use rdaw_api::log::{LogFilter, LogLevel, LogRecord};
+use rdaw_ui::theme::{ColorKind, Level};
+use rdaw_ui::views::button;
+
+use crate::api;
+
+const MAX_RECORDS: usize = 500;
+
+pub fn log_panel() -> impl IntoView {
+    let records = RwSignal::new(Vec::<LogRecord>::new());
+    let max_level = RwSignal::new(LogLevel::Info);
+    let target = RwSignal::new(String::new());
+
+    let refresh = move || {
+        let target = target.get_untracked();
+        let filter = LogFilter {
+            max_level: max_level.get_untracked(),
+            target: (!target.is_empty()).then_some(target),
+            limit: Some(MAX_RECORDS),
+        };
+
+        api::call(
+            move |api| async move { api.get_log_records(filter).await },
+            move |new_records| records.set(new_records),
+        );
+    };
+
+    refresh();
+
+    let cycle_level = move |_ev: &Event| {
+        max_level.update(|level| {
+            let idx = LogLevel::ALL.iter().position(|v| v == level).unwrap_or(0);
+            *level = LogLevel::ALL[(idx + 1) % LogLevel::ALL.len()];
+        });
+        refresh();
+    };
+
+    let level_button = button(ColorKind::Surface, Level::Mid, move || {
+        max_level.get().name()
+    })
+    .on_click_stop(cycle_level)
+    .style(|s| s.width(80.0));
+
+    let refresh_button = button(ColorKind::Surface, Level::Mid, || "Refresh")
+        .on_click_stop(move |_ev| refresh())
+        .style(|s| s.width(80.0));
+
+    let controls = h_stack((
+        level_button,
+        text_input(target).placeholder("Target"),
+        refresh_button,
+    ))
+    .style(|s| s.items_center().column_gap(5.0));
+
+    let list = dyn_stack(
+        move || records.get().into_iter().enumerate(),
+        |(idx, record)| (*idx, record.timestamp),
+        |(_, record)| label(move || format_record(&record)),
+    )
+    .style(|s| s.flex_col());
+
+    v_stack((controls, scroll(list).style(|s| s.height(200.0))))
+        .style(|s| s.padding(10).row_gap(5.0))
+}
+
+fn format_record(record: &LogRecord) -> String {
+    let since_epoch = record
+        .timestamp
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+
+    let secs = since_epoch.as_secs() % 86400;
+    let millis = since_epoch.subsec_millis();
+
+    format!(
+        "{:02}:{:02}:{:02}.{millis:03} {:>5} {}: {}",
+        secs / 3600,
+        secs / 60 % 60,
+        secs % 60,
+        record.level.name(),
+        record.target,
+        record.message,
+    )
+}
@@ -1,7 +1,26 @@
 mod arrangement;
+mod debug_panel;
+mod document_warnings;
+mod error_reports;
+mod log_panel;
+mod monitoring_panel;
+mod node_editor;
+mod passphrase_prompt;
+mod playhead;
 mod track_control;
 mod track_items;
 
-pub use self::arrangement::arrangement;
-pub use self::track_control::track_control;
+pub use self::arrangement::{
+    arrangement, get_item_selection, get_ripple_mode, get_time_selection, ItemSelection,
+    TimeSelection,
+};
+pub use self::debug_panel::debug_panel;
+pub use self::document_warnings::document_warnings;
+pub use self::error_reports::error_reports;
+pub use self::log_panel::log_panel;
+pub use self::monitoring_panel::monitoring_panel;
+pub use self::node_editor::node_editor;
+pub use self::passphrase_prompt::passphrase_prompt;
+pub use self::playhead::{playhead, PIXELS_PER_SECOND};
+pub use self::track_control::{track_control, track_locked, track_status};
 pub use self::track_items::track_items;
@@ -0,0 +1,36 @@
+use floem::views::{h_stack, label, v_stack, Decorators};
+use floem::IntoView;
+use rdaw_api::arrangement::ArrangementId;
+use rdaw_api::meter::StereoFrame;
+use rdaw_ui::views::{correlation_meter, goniometer};
+
+use crate::{api, debug};
+
+const GONIOMETER_DECIMATION: u32 = 4;
+
+pub fn monitoring_panel(id: ArrangementId) -> impl IntoView {
+    let frame = debug::object_signal("stereo_meter", StereoFrame::default());
+
+    api::call(
+        move |api| async move { api.subscribe_stereo_meter(id, GONIOMETER_DECIMATION).await },
+        move |stream| {
+            api::subscribe("stereo_meter", stream, move |new_frame| {
+                frame.set(new_frame)
+            })
+        },
+    );
+
+    let correlation = h_stack((
+        label(|| "-1"),
+        correlation_meter(move || frame.with(|v| v.correlation)),
+        label(|| "+1"),
+    ))
+    .style(|s| s.items_center().column_gap(5.0));
+
+    v_stack((
+        goniometer(move || frame.with(|v| v.points.clone())),
+        correlation,
+    ))
+    .style(|s| s.padding(10).row_gap(5.0))
+    .debug_name("Monitoring")
+}
@@ -0,0 +1,220 @@
+use floem::event::Event;
+use floem::reactive::RwSignal;
+use floem::views::{dyn_stack, h_stack, label, text_input, v_stack, Decorators};
+use floem::IntoView;
+use rdaw_api::node::{CompareSlot, NodeId, NodeParam, NodePreset, PresetLocation};
+use rdaw_ui::theme::{ColorKind, Level};
+use rdaw_ui::views::button;
+
+use crate::{api, debug, get_document_id};
+
+const PARAM_STEPS: f32 = 100.0;
+
+pub fn node_editor(id: NodeId) -> impl IntoView {
+    let params = debug::object_signal("node_params", Vec::<NodeParam>::new());
+
+    api::call(
+        move |api| async move {
+            let params = api.get_node_params(id).await?;
+            let stream = api.subscribe_node_params(id).await?;
+            Ok((params, stream))
+        },
+        move |(new_params, stream)| {
+            params.set(new_params);
+
+            api::subscribe("node_params", stream, move |event| {
+                params.update(|params| {
+                    if let Some(param) = params.iter_mut().find(|v| v.name == event.name) {
+                        param.value = event.value;
+                    }
+                })
+            })
+        },
+    );
+
+    api::on_resync(move || {
+        api::call(
+            move |api| async move { api.get_node_params(id).await },
+            move |new_params| params.set(new_params),
+        )
+    });
+
+    let rows = dyn_stack(
+        move || params.get(),
+        |param| param.name.clone(),
+        move |param| param_row(id, param.name, params),
+    )
+    .style(|s| s.flex_col());
+
+    v_stack((preset_picker(id), compare_controls(id), rows))
+        .style(|s| s.padding(10).row_gap(5.0))
+}
+
+fn param_row(id: NodeId, name: String, params: RwSignal<Vec<NodeParam>>) -> impl IntoView {
+    let get_param = {
+        let name = name.clone();
+        move || params.with(|params| params.iter().find(|v| v.name == name).cloned())
+    };
+
+    let step = {
+        let get_param = get_param.clone();
+        move |direction: f32| {
+            let get_param = get_param.clone();
+            move |_ev: &Event| {
+                let Some(param) = get_param() else {
+                    return;
+                };
+
+                let value = param.value + direction * (param.max - param.min) / PARAM_STEPS;
+                api::call(
+                    move |api| async move { api.set_node_param(id, param.name, value).await },
+                    drop,
+                );
+            }
+        }
+    };
+
+    let value = label(move || match get_param() {
+        Some(param) => format!("{:.2}", param.value),
+        None => String::new(),
+    })
+    .style(|s| s.width(60.0));
+
+    h_stack((
+        label(move || name.clone()).style(|s| s.width(120.0)),
+        button(ColorKind::Surface, Level::Mid, || "-").on_click_stop(step(-1.0)),
+        value,
+        button(ColorKind::Surface, Level::Mid, || "+").on_click_stop(step(1.0)),
+    ))
+    .style(|s| s.items_center().column_gap(5.0))
+}
+
+fn compare_controls(id: NodeId) -> impl IntoView {
+    let active = RwSignal::new(CompareSlot::A);
+
+    api::call(
+        move |api| async move { api.get_node_compare_slot(id).await },
+        move |slot| active.set(slot),
+    );
+
+    let toggle = move |_ev: &Event| {
+        api::call(
+            move |api| async move { api.toggle_node_compare(id).await },
+            move |slot| active.set(slot),
+        );
+    };
+
+    let copy = move |_ev: &Event| {
+        let from = active.get_untracked();
+        api::call(
+            move |api| async move { api.copy_node_compare_slot(id, from).await },
+            drop,
+        );
+    };
+
+    let toggle_button = button(ColorKind::Surface, Level::Mid, move || match active.get() {
+        CompareSlot::A => "A",
+        CompareSlot::B => "B",
+    })
+    .on_click_stop(toggle)
+    .style(|s| s.width(50.0));
+
+    let copy_button = button(ColorKind::Surface, Level::Mid, move || match active.get() {
+        CompareSlot::A => "Copy A to B",
+        CompareSlot::B => "Copy B to A",
+    })
+    .on_click_stop(copy)
+    .style(|s| s.width(100.0));
+
+    h_stack((toggle_button, copy_button)).style(|s| s.column_gap(5.0))
+}
+
+fn preset_picker(id: NodeId) -> impl IntoView {
+    let document_id = get_document_id();
+    let presets = RwSignal::new(Vec::<NodePreset>::new());
+    let new_name = RwSignal::new(String::new());
+    let location = RwSignal::new(PresetLocation::Document);
+
+    let refresh = move || {
+        api::call(
+            move |api| async move {
+                let kind = api.get_node_kind(id).await?;
+                api.list_node_presets(document_id, kind).await
+            },
+            move |new_presets| presets.set(new_presets),
+        );
+    };
+
+    refresh();
+
+    let list = dyn_stack(
+        move || presets.get(),
+        |preset| preset.clone(),
+        move |preset| {
+            let text = match preset.location {
+                PresetLocation::User => preset.name.clone(),
+                PresetLocation::Document => format!("{} (document)", preset.name),
+            };
+
+            button(ColorKind::Surface, Level::Mid, move || text.clone()).on_click_stop(
+                move |_ev| {
+                    let preset = preset.clone();
+                    api::call(
+                        move |api| async move {
+                            api.apply_node_preset(id, preset.name, preset.location)
+                                .await
+                        },
+                        drop,
+                    );
+                },
+            )
+        },
+    )
+    .style(|s| s.column_gap(5.0));
+
+    let toggle_location = move |_ev: &Event| {
+        location.update(|v| {
+            *v = match v {
+                PresetLocation::User => PresetLocation::Document,
+                PresetLocation::Document => PresetLocation::User,
+            }
+        });
+    };
+
+    let location_button = button(ColorKind::Surface, Level::Mid, move || match location.get() {
+        PresetLocation::User => "User",
+        PresetLocation::Document => "Document",
+    })
+    .on_click_stop(toggle_location)
+    .style(|s| s.width(100.0));
+
+    let save = move |_ev: &Event| {
+        let name = new_name.get_untracked();
+        if name.is_empty() {
+            return;
+        }
+
+        let location = location.get_untracked();
+        api::call(
+            move |api| async move { api.save_node_preset(id, name, location).await },
+            move |()| {
+                new_name.set(String::new());
+                refresh();
+            },
+        );
+    };
+
+    let save_button = button(ColorKind::Surface, Level::Mid, || "Save preset")
+        .on_click_stop(save)
+        .style(|s| s.width(100.0));
+
+    v_stack((
+        list,
+        h_stack((
+            text_input(new_name).placeholder("Preset name"),
+            location_button,
+            save_button,
+        )),
+    ))
+    .style(|s| s.row_gap(5.0))
+}
@@ -0,0 +1,67 @@
+use floem::reactive::{create_effect, RwSignal};
+use floem::views::{h_stack, label, text_input, Decorators};
+use floem::IntoView;
+use rdaw_ui::theme::{ColorKind, Level};
+use rdaw_ui::views::button;
+
+/// Shown in place of every character of the passphrase.
+const MASK: char = '•';
+
+pub fn passphrase_prompt(on_submit: impl Fn(String) + 'static) -> impl IntoView {
+    let passphrase = RwSignal::new(String::new());
+
+    let submit_button = button(ColorKind::Surface, Level::Mid, || "Open")
+        .on_click_stop(move |_| on_submit(passphrase.get_untracked()))
+        .style(move |s| s.width(100.0));
+
+    h_stack((
+        label(|| "This document is encrypted"),
+        masked_input(passphrase),
+        submit_button,
+    ))
+    .style(move |s| s.padding(10).items_center())
+}
+
+/// Text input which edits `value`, but only shows a mask character for each of its characters.
+fn masked_input(value: RwSignal<String>) -> impl IntoView {
+    let masked = RwSignal::new(String::new());
+
+    create_effect(move |_| {
+        let typed = masked.get();
+        let new_value = unmask(&value.get_untracked(), &typed);
+
+        let new_masked = new_value.chars().map(|_| MASK).collect::<String>();
+        if new_masked != typed {
+            masked.set(new_masked);
+        }
+
+        value.set(new_value);
+    });
+
+    text_input(masked).placeholder("Passphrase")
+}
+
+/// Applies an edit of the masked text to the value. Mask characters kept at the start and the
+/// end stand for the characters of the value which were there, anything between them was typed.
+fn unmask(value: &str, masked: &str) -> String {
+    let old = value.chars().collect::<Vec<_>>();
+    let new = masked.chars().collect::<Vec<_>>();
+
+    let prefix = new
+        .iter()
+        .take(old.len())
+        .take_while(|&&c| c == MASK)
+        .count();
+    let suffix = new[prefix..]
+        .iter()
+        .rev()
+        .take(old.len() - prefix)
+        .take_while(|&&c| c == MASK)
+        .count();
+
+    old[..prefix]
+        .iter()
+        .chain(&new[prefix..new.len() - suffix])
+        .chain(&old[old.len() - suffix..])
+        .collect()
+}
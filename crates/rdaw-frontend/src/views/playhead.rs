@@ -0,0 +1,58 @@
+use floem::kurbo::Rect;
+use floem::peniko::Color;
+use floem::taffy::Position;
+use floem::views::Decorators;
+use floem::IntoView;
+use rdaw_api::arrangement::ArrangementId;
+use rdaw_core::time::RealTime;
+use rdaw_ui::views::live_layer;
+
+use crate::{api, debug};
+
+/// Horizontal zoom of the arrangement.
+pub const PIXELS_PER_SECOND: f64 = 100.0;
+
+/// Line at the playhead position, laid over the track items.
+pub fn playhead(id: ArrangementId) -> impl IntoView {
+    let position = debug::object_signal("playhead", RealTime::ZERO);
+
+    api::call(
+        move |api| async move {
+            let state = api.get_transport_state(id).await?;
+            let stream = api.subscribe_playhead(id).await?;
+            Ok((state, stream))
+        },
+        move |(state, stream)| {
+            position.set(state.position);
+
+            api::subscribe("playhead", stream, move |event| {
+                position.set(event.position);
+            })
+        },
+    );
+
+    api::on_resync(move || {
+        api::call(
+            move |api| async move { api.get_transport_state(id).await },
+            move |state| position.set(state.position),
+        )
+    });
+
+    live_layer(
+        move || position.get(),
+        |cx, size, position| {
+            let x = position.as_secs_f64() * PIXELS_PER_SECOND;
+            if x < 0.0 || x > size.width {
+                return;
+            }
+
+            cx.fill(
+                &Rect::new(x - 0.5, 0.0, x + 0.5, size.height),
+                Color::RED,
+                0.0,
+            );
+        },
+    )
+    .style(|s| s.position(Position::Absolute).inset(0.0).z_index(20))
+    .debug_name("Playhead")
+}
@@ -1,17 +1,16 @@
 use floem::event::Event;
 use floem::reactive::{create_effect, RwSignal};
-use floem::views::{h_stack, text_input, Decorators};
+use floem::views::{h_stack, label, text_input, Decorators};
 use floem::IntoView;
-use rdaw_api::track::TrackId;
-use rdaw_ui::task::stream_for_each;
-use rdaw_ui::theme::{ColorKind, Level};
+use rdaw_api::track::{TrackId, TrackStatus};
+use rdaw_ui::theme::{ColorKind, Level, Theme};
 use rdaw_ui::views::button;
 
-use crate::{api, get_document_id};
+use crate::{api, debug, get_document_id};
 
 pub fn track_control(id: TrackId) -> impl IntoView {
     let document_id = get_document_id();
-    let name = RwSignal::new(String::new());
+    let name = debug::object_signal("track_name", String::new());
     let editor_name = RwSignal::new(String::new());
 
     api::call(
@@ -23,10 +22,17 @@ pub fn track_control(id: TrackId) -> impl IntoView {
         move |(new_name, stream)| {
             name.set(new_name);
 
-            stream_for_each(stream, move |new_name| name.set(new_name))
+            api::subscribe("track_name", stream, move |new_name| name.set(new_name))
         },
     );
 
+    api::on_resync(move || {
+        api::call(
+            move |api| async move { api.get_track_name(id).await },
+            move |new_name| name.set(new_name),
+        )
+    });
+
     create_effect(move |old| {
         let editor_name = editor_name.get();
         let name = name.get();
@@ -62,9 +68,120 @@ pub fn track_control(id: TrackId) -> impl IntoView {
         .on_click_stop(add_child)
         .style(move |s| s.width(100.0));
 
+    let locked = track_locked(id);
+
+    let toggle_locked = move |_ev: &Event| {
+        let new_locked = !locked.get_untracked();
+        api::call(
+            move |api| async move { api.set_track_locked(id, new_locked).await },
+            drop,
+        );
+    };
+
+    let lock_button = button(ColorKind::Surface, Level::Mid, move || {
+        if locked.get() {
+            "\u{1f512}"
+        } else {
+            "\u{1f513}"
+        }
+    })
+    .on_click_stop(toggle_locked);
+
     h_stack((
         text_input(editor_name).placeholder("Name"),
         add_child_button,
+        lock_button,
+        status_badges(id),
     ))
     .style(move |s| s.padding(10))
 }
+
+fn status_badges(id: TrackId) -> impl IntoView {
+    let status = track_status(id);
+
+    h_stack((
+        status_badge(ColorKind::Accent, "Frozen", move || status.get().frozen),
+        status_badge(ColorKind::Surface, "Locked", move || status.get().locked),
+        status_badge(ColorKind::Surface, "Muted", move || status.get().muted),
+        status_badge(ColorKind::Accent, "Solo", move || status.get().soloed),
+        status_badge(ColorKind::Error, "Armed", move || status.get().armed),
+        status_badge(ColorKind::Warning, "Monitoring", move || {
+            status.get().monitoring
+        }),
+    ))
+    .style(|s| s.items_center())
+}
+
+fn status_badge(
+    color: ColorKind,
+    text: &'static str,
+    visible: impl Fn() -> bool + 'static,
+) -> impl IntoView {
+    label(move || text).style(move |s| {
+        let theme = Theme::get();
+        let colors = theme.colors[color][Level::High];
+        s.padding_horiz(6)
+            .margin(3)
+            .border_radius(4)
+            .background(colors.bg)
+            .color(colors.fg)
+            .font_size(theme.fonts.normal.s.size)
+            .apply_if(!visible(), |s| s.hide())
+    })
+}
+
+pub fn track_status(id: TrackId) -> RwSignal<TrackStatus> {
+    let status = debug::object_signal("track_status", TrackStatus::default());
+
+    api::call(
+        move |api| async move {
+            let status = api.get_track_status(id).await?;
+            let stream = api.subscribe_track_status(id).await?;
+            Ok((status, stream))
+        },
+        move |(new_status, stream)| {
+            status.set(new_status);
+
+            api::subscribe("track_status", stream, move |new_status| {
+                status.set(new_status)
+            })
+        },
+    );
+
+    api::on_resync(move || {
+        api::call(
+            move |api| async move { api.get_track_status(id).await },
+            move |new_status| status.set(new_status),
+        )
+    });
+
+    status
+}
+
+pub fn track_locked(id: TrackId) -> RwSignal<bool> {
+    let locked = debug::object_signal("track_locked", false);
+
+    api::call(
+        move |api| async move {
+            let locked = api.get_track_locked(id).await?;
+            let stream = api.subscribe_track_locked(id).await?;
+            Ok((locked, stream))
+        },
+        move |(new_locked, stream)| {
+            locked.set(new_locked);
+
+            api::subscribe("track_locked", stream, move |new_locked| {
+                locked.set(new_locked)
+            })
+        },
+    );
+
+    api::on_resync(move || {
+        api::call(
+            move |api| async move { api.get_track_locked(id).await },
+            move |new_locked| locked.set(new_locked),
+        )
+    });
+
+    locked
+}
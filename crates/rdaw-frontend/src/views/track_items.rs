@@ -3,9 +3,21 @@ use floem::views::{label, Decorators};
 use floem::IntoView;
 use rdaw_api::track::TrackId;
 
-pub fn track_items(_id: TrackId, is_even: bool) -> impl IntoView {
-    label(move || "Track items...").style(move |s| {
+use crate::views::track_locked;
+
+pub fn track_items(id: TrackId, is_even: bool) -> impl IntoView {
+    let locked = track_locked(id);
+
+    label(move || {
+        if locked.get() {
+            "\u{1f512} Track items..."
+        } else {
+            "Track items..."
+        }
+    })
+    .style(move |s| {
         s.width_full()
             .background(Color::BLACK.with_alpha_factor(if is_even { 0.03 } else { 0.1 }))
+            .apply_if(locked.get(), |s| s.color(Color::BLACK.with_alpha_factor(0.4)))
     })
 }
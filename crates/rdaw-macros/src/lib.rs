@@ -57,17 +57,36 @@ pub fn rpc_operations(args: TokenStream, item: TokenStream) -> TokenStream {
     let event_enum_ident = format_ident!("{ident_without_ops}Events");
 
     let mut req_enum_variants = Vec::new();
+    let mut req_roles = Vec::new();
+    let mut req_names = Vec::new();
     let mut res_enum_variants = Vec::new();
+    let mut res_stream_ids = Vec::new();
     let mut event_enum_variants = Vec::new();
     let mut func_impls = Vec::new();
 
     for func in &mut funcs {
         let mut is_sub = false;
+        let mut role = None;
+
         func.attrs.retain(|attr| {
-            is_sub = attr.path().is_ident("sub");
-            !is_sub
+            if attr.path().is_ident("sub") {
+                is_sub = true;
+                return false;
+            }
+
+            if attr.path().is_ident("role") {
+                match attr.parse_args::<syn::Ident>() {
+                    Ok(v) => role = Some(v),
+                    Err(e) => emit_error!(attr, "invalid `#[role]` attribute: {}", e),
+                }
+                return false;
+            }
+
+            true
         });
 
+        let role = role.unwrap_or_else(|| default_role(&func.sig.ident));
+
         let syn::ReturnType::Type(_, func_ret_ty_res) = &func.sig.output else {
             emit_error!(func.sig.output, "method must return `Result<T>`");
             continue;
@@ -135,12 +154,28 @@ pub fn rpc_operations(args: TokenStream, item: TokenStream) -> TokenStream {
             let res_variant = quote_spanned!(variant_span => #variant_ident(rdaw_rpc::StreamId));
             let event_variant = quote_spanned!(variant_span => #variant_ident(#event_ty));
 
+            res_stream_ids.push(quote_spanned! { variant_span =>
+                #res_enum_ident::#variant_ident(v) => Some(v)
+            });
+
             (res_variant, Some(event_variant))
         } else {
             let res_variant = quote!(#variant_ident(#func_ret_ty));
+
+            res_stream_ids.push(quote_spanned! { variant_span =>
+                #res_enum_ident::#variant_ident(_) => None
+            });
+
             (res_variant, None)
         };
 
+        req_roles.push(quote_spanned! { variant_span =>
+            #req_enum_ident::#variant_ident { .. } => rdaw_rpc::Role::#role
+        });
+        let func_name = func.sig.ident.to_string();
+        req_names.push(quote_spanned! { variant_span =>
+            #req_enum_ident::#variant_ident { .. } => #func_name
+        });
         req_enum_variants.push(req_variant);
         res_enum_variants.push(res_variant);
         event_enum_variants.extend(event_variant);
@@ -161,13 +196,19 @@ pub fn rpc_operations(args: TokenStream, item: TokenStream) -> TokenStream {
             Box::new(syn::Type::Verbatim(new_output_ty)),
         );
 
-        let func_body = if is_sub {
-            quote! {
+        // the request is made before the future is polled, so that batches queue it right away
+        let (func_request, func_body) = if is_sub {
+            let func_request = quote! {
+                let req: <#protocol_path as rdaw_rpc::Protocol>::Req =
+                    #req_enum_ident::#variant_ident { #(#param_names,)* }.into();
+
+                let res = self.request(req.clone());
+            };
+
+            let func_body = quote! {
                 use futures::StreamExt as _;
 
-                let res = self.request(
-                    #req_enum_ident::#variant_ident { #(#param_names,)* }.into()
-                ).await?;
+                let res = res.await?;
 
                 let res: #res_enum_ident = res
                     .try_into()
@@ -178,7 +219,7 @@ pub fn rpc_operations(args: TokenStream, item: TokenStream) -> TokenStream {
                     _ => return Err(#error_path_as::invalid_type()),
                 };
 
-                let stream = self.subscribe(id)
+                let stream = self.subscribe_replayable(id, req)
                     .map(|v| {
                         let ev: #event_enum_ident = v.try_into().ok().unwrap();
                         match ev {
@@ -188,12 +229,18 @@ pub fn rpc_operations(args: TokenStream, item: TokenStream) -> TokenStream {
                     })
                     .boxed();
                 Ok(stream)
-            }
+            };
+
+            (func_request, func_body)
         } else {
-            quote! {
+            let func_request = quote! {
                 let res = self.request(
                     #req_enum_ident::#variant_ident { #(#param_names,)* }.into()
-                ).await?;
+                );
+            };
+
+            let func_body = quote! {
+                let res = res.await?;
 
                 let res: #res_enum_ident = res
                     .try_into()
@@ -203,7 +250,9 @@ pub fn rpc_operations(args: TokenStream, item: TokenStream) -> TokenStream {
                     #res_enum_ident::#variant_ident(v) => Ok(v),
                     _ => Err(#error_path_as::invalid_type()),
                 }
-            }
+            };
+
+            (func_request, func_body)
         };
 
         let func_sig = &func.sig;
@@ -211,6 +260,8 @@ pub fn rpc_operations(args: TokenStream, item: TokenStream) -> TokenStream {
         let func_impl = quote! {
             #[allow(unused_variables)]
             #func_sig {
+                #func_request
+
                 Box::pin(async move {
                     #func_body
                 })
@@ -233,11 +284,35 @@ pub fn rpc_operations(args: TokenStream, item: TokenStream) -> TokenStream {
             #(#req_enum_variants,)*
         }
 
+        #[automatically_derived]
+        impl #req_enum_ident {
+            pub fn required_role(&self) -> rdaw_rpc::Role {
+                match *self {
+                    #(#req_roles,)*
+                }
+            }
+
+            pub fn name(&self) -> &'static str {
+                match *self {
+                    #(#req_names,)*
+                }
+            }
+        }
+
         #[derive(Debug, Clone)]
         #vis enum #res_enum_ident {
             #(#res_enum_variants,)*
         }
 
+        #[automatically_derived]
+        impl #res_enum_ident {
+            pub fn stream_id(&self) -> Option<rdaw_rpc::StreamId> {
+                match *self {
+                    #(#res_stream_ids,)*
+                }
+            }
+        }
+
         #[derive(Debug, Clone)]
         #vis enum #event_enum_ident {
             #(#event_enum_variants,)*
@@ -250,6 +325,14 @@ pub fn rpc_operations(args: TokenStream, item: TokenStream) -> TokenStream {
         {
             #(#func_impls)*
         }
+
+        #[automatically_derived]
+        impl<T> #ident for rdaw_rpc::Batch<#protocol_path, T>
+        where
+            T: rdaw_rpc::transport::ClientTransport<#protocol_path>
+        {
+            #(#func_impls)*
+        }
     };
 
     TokenStream::from(expanded)
@@ -303,6 +386,21 @@ pub fn rpc_protocol(args: TokenStream, item: TokenStream) -> TokenStream {
         #res_enum
         #event_enum
 
+        #[automatically_derived]
+        impl #req_enum_ident {
+            pub fn required_role(&self) -> rdaw_rpc::Role {
+                match self {
+                    #(#req_enum_ident::#ops_names(v) => v.required_role(),)*
+                }
+            }
+
+            pub fn name(&self) -> &'static str {
+                match self {
+                    #(#req_enum_ident::#ops_names(v) => v.name(),)*
+                }
+            }
+        }
+
         #vis trait #ident_prefix: 'static + Sync #(+ #ops_traits)* {}
 
         impl<T> #ident_prefix for T where T: 'static + Sync #(+ #ops_traits)* {}
@@ -313,6 +411,12 @@ pub fn rpc_protocol(args: TokenStream, item: TokenStream) -> TokenStream {
             type Res = #res_enum_ident;
             type Event = #event_enum_ident;
             type Error = #error_path;
+
+            fn response_stream_id(res: &Self::Res) -> Option<rdaw_rpc::StreamId> {
+                match res {
+                    #(#res_enum_ident::#ops_names(v) => v.stream_id(),)*
+                }
+            }
         }
     };
 
@@ -393,16 +497,56 @@ pub fn rpc_handler(args: TokenStream, item: TokenStream) -> TokenStream {
             })
             .collect::<Vec<_>>();
 
+        let object_args = func
+            .sig
+            .inputs
+            .iter()
+            .flat_map(|v| match v {
+                syn::FnArg::Typed(syn::PatType { pat, ty, .. }) if is_id_type(ty) => match **pat {
+                    syn::Pat::Ident(ref ident) => Some(ident.ident.clone()),
+                    _ => None,
+                },
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        let operation_name = func_name.to_string();
+        let object_names = object_args.iter().map(|v| v.to_string());
+
+        let with_operation = quote! {
+            |e: #error_path| rdaw_rpc::ProtocolError::with_operation(
+                e,
+                #operation_name,
+                &[#((#object_names, &#object_args as &dyn std::fmt::Debug),)*],
+            )
+        };
+
         let has_responder = args.first().is_some_and(|arg| arg == "responder");
 
+        // handlers taking a token right after the responder get it from the implementor, which
+        // has to provide `fn cancellation_token(&mut self, id: RequestId) -> CancellationToken`
+        let has_cancel = has_responder && args.get(1).is_some_and(|arg| arg == "cancel");
+
         let match_case = if has_responder {
             args.remove(0);
+
+            let (cancel_let, cancel_arg) = if has_cancel {
+                args.remove(0);
+                (
+                    quote!(let cancel = self.cancellation_token(req_id);),
+                    quote!(cancel,),
+                )
+            } else {
+                (quote!(), quote!())
+            };
+
             quote! {
                 #req_ident::#name { #(#args,)* } => {
                     let responder = rdaw_rpc::ClosureResponder::new(move |res: Result<_, #error_path>| {
                         let payload = res
                             .map(#res_ident::#name)
-                            .map(|v| v.into());
+                            .map(|v| v.into())
+                            .map_err(#with_operation);
                         async move {
                             transport
                                 .send(rdaw_rpc::ServerMessage::Response { id: req_id, payload })
@@ -410,7 +554,8 @@ pub fn rpc_handler(args: TokenStream, item: TokenStream) -> TokenStream {
                         }
                     });
 
-                    self.#func_name(responder, #(#args,)*)
+                    #cancel_let
+                    self.#func_name(responder, #cancel_arg #(#args,)*)
                 }
             }
         } else {
@@ -419,7 +564,8 @@ pub fn rpc_handler(args: TokenStream, item: TokenStream) -> TokenStream {
                     let payload = self
                         .#func_name(#(#args,)*)
                         .map(#res_ident::#name)
-                        .map(|v| v.into());
+                        .map(|v| v.into())
+                        .map_err(#with_operation);
                     transport
                         .send(rdaw_rpc::ServerMessage::Response { id: req_id, payload })
                         .await
@@ -451,6 +597,26 @@ pub fn rpc_handler(args: TokenStream, item: TokenStream) -> TokenStream {
     item.to_token_stream().into()
 }
 
+fn default_role(func_name: &syn::Ident) -> syn::Ident {
+    let name = func_name.to_string();
+    let is_read_only = ["get_", "list_", "subscribe_"]
+        .iter()
+        .any(|prefix| name.starts_with(prefix));
+
+    let role = if is_read_only { "ReadOnly" } else { "Editor" };
+    syn::Ident::new(role, func_name.span())
+}
+
+fn is_id_type(ty: &syn::Type) -> bool {
+    let syn::Type::Path(path) = ty else {
+        return false;
+    };
+
+    path.path.segments.last().is_some_and(|segment| {
+        segment.arguments.is_empty() && segment.ident.to_string().ends_with("Id")
+    })
+}
+
 fn parse_macro_args<T: FromMeta>(args: TokenStream) -> Result<T, darling::Error> {
     let attr_args = NestedMeta::parse_meta_list(args.into())?;
     T::from_list(&attr_args)
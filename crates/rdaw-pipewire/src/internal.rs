@@ -1,7 +1,9 @@
 use std::cell::RefCell;
-use std::mem::size_of;
+use std::mem::{size_of, MaybeUninit};
 use std::rc::Rc;
 use std::slice;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
 
 use pipewire::channel::{Receiver, Sender};
 use pipewire::context::Context;
@@ -16,16 +18,17 @@ use pipewire::spa::pod::{Object, Pod, Value};
 use pipewire::spa::sys::*;
 use pipewire::spa::utils::dict::DictRef;
 use pipewire::spa::utils::Direction;
-use pipewire::stream::{Stream, StreamFlags, StreamListener};
+use pipewire::stream::{Stream, StreamFlags, StreamListener, StreamRef};
 use pipewire::types::ObjectType;
-use rdaw_api::audio::AudioChannel;
-use rdaw_audio::driver::{OutCallbackData, OutStreamDesc};
+use rdaw_api::audio::{AudioChannel, StreamInfo};
+use rdaw_audio::driver::{send_captured, InStreamDesc, OutCallbackData, OutStreamDesc};
 use slotmap::SlotMap;
 
 use crate::{Error, Result};
 
 slotmap::new_key_type! {
     pub struct OutStreamId;
+    pub struct InStreamId;
 }
 
 pub enum Message {
@@ -42,9 +45,37 @@ pub enum Message {
         id: OutStreamId,
         active: bool,
     },
+    GetOutStreamInfo {
+        sender: oneshot::Sender<Result<StreamInfo>>,
+        id: OutStreamId,
+    },
     DestroyOutStream {
         id: OutStreamId,
     },
+    CreateInStream {
+        sender: oneshot::Sender<Result<InStreamId>>,
+        desc: InStreamDesc,
+    },
+    IsInStreamActive {
+        sender: oneshot::Sender<Result<bool>>,
+        id: InStreamId,
+    },
+    SetInStreamActive {
+        sender: oneshot::Sender<Result<()>>,
+        id: InStreamId,
+        active: bool,
+    },
+    GetInStreamInfo {
+        sender: oneshot::Sender<Result<StreamInfo>>,
+        id: InStreamId,
+    },
+    GetInStreamDroppedFrames {
+        sender: oneshot::Sender<Result<u64>>,
+        id: InStreamId,
+    },
+    DestroyInStream {
+        id: InStreamId,
+    },
     Terminate,
 }
 
@@ -87,9 +118,43 @@ impl Handle {
         self.send_recv(receiver, Message::SetOutStreamActive { sender, id, active })
     }
 
+    pub fn get_out_stream_info(&self, id: OutStreamId) -> Result<StreamInfo> {
+        let (sender, receiver) = oneshot::channel();
+        self.send_recv(receiver, Message::GetOutStreamInfo { sender, id })
+    }
+
     pub fn destroy_out_stream(&self, id: OutStreamId) -> Result<()> {
         self.send(Message::DestroyOutStream { id })
     }
+
+    pub fn create_in_stream(&self, desc: InStreamDesc) -> Result<InStreamId> {
+        let (sender, receiver) = oneshot::channel();
+        self.send_recv(receiver, Message::CreateInStream { sender, desc })
+    }
+
+    pub fn is_in_stream_active(&self, id: InStreamId) -> Result<bool> {
+        let (sender, receiver) = oneshot::channel();
+        self.send_recv(receiver, Message::IsInStreamActive { sender, id })
+    }
+
+    pub fn set_in_stream_active(&self, id: InStreamId, active: bool) -> Result<()> {
+        let (sender, receiver) = oneshot::channel();
+        self.send_recv(receiver, Message::SetInStreamActive { sender, id, active })
+    }
+
+    pub fn get_in_stream_info(&self, id: InStreamId) -> Result<StreamInfo> {
+        let (sender, receiver) = oneshot::channel();
+        self.send_recv(receiver, Message::GetInStreamInfo { sender, id })
+    }
+
+    pub fn get_in_stream_dropped_frames(&self, id: InStreamId) -> Result<u64> {
+        let (sender, receiver) = oneshot::channel();
+        self.send_recv(receiver, Message::GetInStreamDroppedFrames { sender, id })
+    }
+
+    pub fn destroy_in_stream(&self, id: InStreamId) -> Result<()> {
+        self.send(Message::DestroyInStream { id })
+    }
 }
 
 pub struct PwThread {
@@ -98,14 +163,42 @@ pub struct PwThread {
     registry: Registry,
 
     out_streams: RefCell<SlotMap<OutStreamId, OutStream>>,
+    in_streams: RefCell<SlotMap<InStreamId, InStream>>,
 }
 
 struct OutStream {
     active: bool,
+    info: Arc<SharedStreamInfo>,
+    stream: Stream,
+    _listener: StreamListener<()>,
+}
+
+struct InStream {
+    active: bool,
+    info: Arc<SharedStreamInfo>,
+    dropped_frames: Arc<AtomicU64>,
     stream: Stream,
     _listener: StreamListener<()>,
 }
 
+/// Updated from the realtime thread on every process call. The sample rate is fixed in the
+/// format of the stream, pipewire resamples if the graph runs at a different one.
+struct SharedStreamInfo {
+    sample_rate: u32,
+    quantum: AtomicUsize,
+    device_latency: AtomicUsize,
+}
+
+impl SharedStreamInfo {
+    fn load(&self) -> StreamInfo {
+        StreamInfo {
+            sample_rate: self.sample_rate,
+            quantum: self.quantum.load(Ordering::Relaxed),
+            device_latency: self.device_latency.load(Ordering::Relaxed),
+        }
+    }
+}
+
 impl PwThread {
     pub fn new() -> Result<PwThread> {
         let main_loop = MainLoop::new(None)?;
@@ -118,6 +211,7 @@ impl PwThread {
             core,
             registry,
             out_streams: Default::default(),
+            in_streams: Default::default(),
         })
     }
 
@@ -172,7 +266,26 @@ impl PwThread {
             Message::SetOutStreamActive { sender, id, active } => {
                 let _ = sender.send(self.set_out_stream_active(id, active));
             }
+            Message::GetOutStreamInfo { sender, id } => {
+                let _ = sender.send(self.get_out_stream_info(id));
+            }
             Message::DestroyOutStream { id } => self.destroy_out_stream(id),
+            Message::CreateInStream { sender, desc } => {
+                let _ = sender.send(self.create_in_stream(desc));
+            }
+            Message::IsInStreamActive { sender, id } => {
+                let _ = sender.send(self.is_in_stream_active(id));
+            }
+            Message::SetInStreamActive { sender, id, active } => {
+                let _ = sender.send(self.set_in_stream_active(id, active));
+            }
+            Message::GetInStreamInfo { sender, id } => {
+                let _ = sender.send(self.get_in_stream_info(id));
+            }
+            Message::GetInStreamDroppedFrames { sender, id } => {
+                let _ = sender.send(self.get_in_stream_dropped_frames(id));
+            }
+            Message::DestroyInStream { id } => self.destroy_in_stream(id),
             Message::Terminate => self.terminate(),
         }
     }
@@ -198,9 +311,21 @@ impl PwThread {
 
         let stream = Stream::new(&self.core, &name, props)?;
 
+        // until the first process call, assume the device gives us what we asked for
+        let info = Arc::new(SharedStreamInfo {
+            sample_rate,
+            quantum: AtomicUsize::new(buffer_size),
+            device_latency: AtomicUsize::new(0),
+        });
+
+        let shared_info = info.clone();
         let listener = stream
             .add_local_listener::<()>()
             .process(move |stream, _| {
+                if let Some(latency) = device_latency(stream, sample_rate) {
+                    shared_info.device_latency.store(latency, Ordering::Relaxed);
+                }
+
                 let Some(mut buffer) = stream.dequeue_buffer() else {
                     return;
                 };
@@ -210,7 +335,7 @@ impl PwThread {
                         continue;
                     };
 
-                    let samples = &mut transmute_out_buffer(samples);
+                    let samples = &mut transmute_buffer(samples);
                     let len = samples.len().min(buffer_size * num_channels);
                     let samples = &mut samples[..len];
 
@@ -218,6 +343,8 @@ impl PwThread {
                     let chunk_size = std::mem::size_of_val(samples) as u32;
                     let chunk_stride = (num_channels * size_of::<f32>()) as i32;
 
+                    shared_info.quantum.store(num_frames, Ordering::Relaxed);
+
                     (callback)(OutCallbackData {
                         samples,
                         num_channels,
@@ -244,6 +371,7 @@ impl PwThread {
 
         let out_stream = OutStream {
             active: true,
+            info,
             stream,
             _listener: listener,
         };
@@ -271,10 +399,141 @@ impl PwThread {
         Ok(())
     }
 
+    fn get_out_stream_info(&self, id: OutStreamId) -> Result<StreamInfo> {
+        let out_streams = self.out_streams.borrow();
+        let stream = out_streams.get(id).ok_or_else(|| Error::InvalidStreamId)?;
+        Ok(stream.info.load())
+    }
+
     fn destroy_out_stream(&self, id: OutStreamId) {
         self.out_streams.borrow_mut().remove(id);
     }
 
+    fn create_in_stream(&self, desc: InStreamDesc) -> Result<InStreamId> {
+        let InStreamDesc {
+            name,
+            sample_rate,
+            channels,
+            mut sender,
+            buffer_size,
+        } = desc;
+
+        let num_channels = channels.len();
+
+        let props = properties! {
+            *MEDIA_TYPE => "Audio",
+            *MEDIA_ROLE => "Production",
+            *MEDIA_CATEGORY => "Capture",
+            *AUDIO_CHANNELS => num_channels.to_string().as_bytes(),
+            *NODE_LATENCY => format!("{buffer_size}/{sample_rate}").as_bytes(),
+        };
+
+        let stream = Stream::new(&self.core, &name, props)?;
+
+        let info = Arc::new(SharedStreamInfo {
+            sample_rate,
+            quantum: AtomicUsize::new(buffer_size),
+            device_latency: AtomicUsize::new(0),
+        });
+
+        let dropped_frames = Arc::new(AtomicU64::new(0));
+
+        let shared_info = info.clone();
+        let shared_dropped_frames = dropped_frames.clone();
+        let listener = stream
+            .add_local_listener::<()>()
+            .process(move |stream, _| {
+                if let Some(latency) = device_latency(stream, sample_rate) {
+                    shared_info.device_latency.store(latency, Ordering::Relaxed);
+                }
+
+                let Some(mut buffer) = stream.dequeue_buffer() else {
+                    return;
+                };
+
+                for data in buffer.datas_mut() {
+                    let chunk = data.chunk();
+                    let offset = chunk.offset() as usize;
+                    let size = chunk.size() as usize;
+
+                    let Some(bytes) = data.data() else {
+                        continue;
+                    };
+
+                    let Some(bytes) = bytes.get_mut(offset..offset + size) else {
+                        continue;
+                    };
+
+                    let samples = transmute_buffer(bytes);
+                    let len = samples.len() - samples.len() % num_channels.max(1);
+                    let samples = &samples[..len];
+
+                    shared_info
+                        .quantum
+                        .store(samples.len() / num_channels.max(1), Ordering::Relaxed);
+
+                    send_captured(&mut sender, samples, num_channels, &shared_dropped_frames);
+                }
+            })
+            .register()?;
+
+        let audio_info = serialize_audio_info(sample_rate, &channels)?;
+        let mut params = [Pod::from_bytes(&audio_info).unwrap()];
+
+        stream.connect(
+            Direction::Input,
+            None,
+            StreamFlags::AUTOCONNECT | StreamFlags::MAP_BUFFERS | StreamFlags::RT_PROCESS,
+            &mut params,
+        )?;
+
+        let in_stream = InStream {
+            active: true,
+            info,
+            dropped_frames,
+            stream,
+            _listener: listener,
+        };
+
+        let id = self.in_streams.borrow_mut().insert(in_stream);
+
+        Ok(id)
+    }
+
+    fn is_in_stream_active(&self, id: InStreamId) -> Result<bool> {
+        let in_streams = self.in_streams.borrow();
+        let stream = in_streams.get(id).ok_or_else(|| Error::InvalidStreamId)?;
+        Ok(stream.active)
+    }
+
+    fn set_in_stream_active(&self, id: InStreamId, active: bool) -> Result<()> {
+        let mut in_streams = self.in_streams.borrow_mut();
+        let stream = in_streams
+            .get_mut(id)
+            .ok_or_else(|| Error::InvalidStreamId)?;
+
+        stream.stream.set_active(active)?;
+        stream.active = active;
+
+        Ok(())
+    }
+
+    fn get_in_stream_info(&self, id: InStreamId) -> Result<StreamInfo> {
+        let in_streams = self.in_streams.borrow();
+        let stream = in_streams.get(id).ok_or_else(|| Error::InvalidStreamId)?;
+        Ok(stream.info.load())
+    }
+
+    fn get_in_stream_dropped_frames(&self, id: InStreamId) -> Result<u64> {
+        let in_streams = self.in_streams.borrow();
+        let stream = in_streams.get(id).ok_or_else(|| Error::InvalidStreamId)?;
+        Ok(stream.dropped_frames.load(Ordering::Relaxed))
+    }
+
+    fn destroy_in_stream(&self, id: InStreamId) {
+        self.in_streams.borrow_mut().remove(id);
+    }
+
     fn terminate(&self) {
         self.main_loop.quit();
     }
@@ -353,7 +612,38 @@ fn serialize_audio_info(sample_rate: u32, channels: &[AudioChannel]) -> Result<V
     Ok(values.0.into_inner())
 }
 
-fn transmute_out_buffer(data: &mut [u8]) -> &mut [f32] {
+/// Delay between the stream and the device, in frames of the stream.
+fn device_latency(stream: &StreamRef, sample_rate: u32) -> Option<usize> {
+    let mut time = MaybeUninit::<pipewire::sys::pw_time>::zeroed();
+
+    // SAFETY: the stream pointer is valid for the duration of the process call, and
+    // `pw_stream_get_time_n` writes at most `size_of::<pw_time>()` bytes
+    let res = unsafe {
+        pipewire::sys::pw_stream_get_time_n(
+            stream.as_raw_ptr(),
+            time.as_mut_ptr(),
+            size_of::<pipewire::sys::pw_time>(),
+        )
+    };
+
+    if res < 0 {
+        return None;
+    }
+
+    // SAFETY: zeroed is a valid pw_time, and it was filled in on success
+    let time = unsafe { time.assume_init() };
+
+    // the delay is counted in ticks of the graph clock
+    let (num, denom) = (u64::from(time.rate.num), u64::from(time.rate.denom));
+    if num == 0 || denom == 0 || time.delay < 0 {
+        return None;
+    }
+
+    let frames = time.delay as u64 * num * u64::from(sample_rate) / denom;
+    usize::try_from(frames).ok()
+}
+
+fn transmute_buffer(data: &mut [u8]) -> &mut [f32] {
     assert!(data.len() % size_of::<f32>() == 0);
     let len = data.len() / size_of::<f32>();
     let ptr = data.as_mut_ptr() as *mut f32;
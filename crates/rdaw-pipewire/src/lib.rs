@@ -1,10 +1,11 @@
 mod error;
 mod internal;
 
-use rdaw_audio::driver::{self, OutStreamDesc};
+use rdaw_api::audio::StreamInfo;
+use rdaw_audio::driver::{self, InStreamDesc, OutStreamDesc};
 
 pub use crate::error::{Error, Result};
-use crate::internal::{Handle, OutStreamId, PwThread};
+use crate::internal::{Handle, InStreamId, OutStreamId, PwThread};
 
 pub struct Driver {
     handle: Handle,
@@ -46,6 +47,7 @@ impl Drop for Driver {
 impl driver::Driver for Driver {
     type Error = Error;
     type OutStream = OutStream;
+    type InStream = InStream;
 
     fn create_out_stream(&self, desc: OutStreamDesc) -> Result<OutStream> {
         let id = self.handle.create_out_stream(desc)?;
@@ -54,6 +56,14 @@ impl driver::Driver for Driver {
             handle: self.handle.clone(),
         })
     }
+
+    fn create_in_stream(&self, desc: InStreamDesc) -> Result<InStream> {
+        let id = self.handle.create_in_stream(desc)?;
+        Ok(InStream {
+            id,
+            handle: self.handle.clone(),
+        })
+    }
 }
 
 pub struct OutStream {
@@ -71,6 +81,10 @@ impl driver::OutStream for OutStream {
     fn set_active(&self, active: bool) -> Result<()> {
         self.handle.set_out_stream_active(self.id, active)
     }
+
+    fn info(&self) -> Result<StreamInfo> {
+        self.handle.get_out_stream_info(self.id)
+    }
 }
 
 impl Drop for OutStream {
@@ -78,3 +92,34 @@ impl Drop for OutStream {
         let _ = self.handle.destroy_out_stream(self.id);
     }
 }
+
+pub struct InStream {
+    id: InStreamId,
+    handle: Handle,
+}
+
+impl driver::InStream for InStream {
+    type Error = Error;
+
+    fn is_active(&self) -> Result<bool> {
+        self.handle.is_in_stream_active(self.id)
+    }
+
+    fn set_active(&self, active: bool) -> Result<()> {
+        self.handle.set_in_stream_active(self.id, active)
+    }
+
+    fn info(&self) -> Result<StreamInfo> {
+        self.handle.get_in_stream_info(self.id)
+    }
+
+    fn num_dropped_frames(&self) -> Result<u64> {
+        self.handle.get_in_stream_dropped_frames(self.id)
+    }
+}
+
+impl Drop for InStream {
+    fn drop(&mut self) {
+        let _ = self.handle.destroy_in_stream(self.id);
+    }
+}
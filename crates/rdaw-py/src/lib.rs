@@ -0,0 +1,299 @@
+//! Python bindings for scripting the backend, e.g. to generate projects or process them in
+//! batches.
+//!
+//! Ids are passed around as plain integers. Every call blocks until the backend responds, with
+//! the GIL released in the meantime.
+
+use std::future::Future;
+use std::thread;
+
+use futures::executor::block_on;
+use pyo3::create_exception;
+use pyo3::exceptions::PyException;
+use pyo3::prelude::*;
+use rdaw_api::arrangement::ArrangementOperations;
+use rdaw_api::asset::AssetOperations;
+use rdaw_api::document::DocumentOperations;
+use rdaw_api::item::{ItemId, MidiItemOperations};
+use rdaw_api::source::{AudioSourceOperations, MidiNote, MidiSourceOperations};
+use rdaw_api::time::{BeatTime, Time};
+use rdaw_api::track::{TrackItem, TrackOperations};
+use rdaw_api::BackendProtocol;
+use rdaw_backend::Backend;
+use rdaw_core::path::Utf8PathBuf;
+use rdaw_rpc::transport::{self, LocalClientTransport};
+use slotmap::{Key, KeyData};
+
+create_exception!(
+    rdaw_py,
+    RdawError,
+    PyException,
+    "Error returned by the backend."
+);
+
+type RpcClient = rdaw_rpc::Client<BackendProtocol, LocalClientTransport<BackendProtocol>>;
+
+fn to_py_err(error: rdaw_api::Error) -> PyErr {
+    RdawError::new_err(format!("{:?}: {error}", error.kind()))
+}
+
+fn from_raw<K: Key>(raw: u64) -> K {
+    KeyData::from_ffi(raw).into()
+}
+
+fn to_raw<K: Key>(id: K) -> u64 {
+    id.data().as_ffi()
+}
+
+fn beats(beats: f64) -> Time {
+    Time::Beat(BeatTime::from_beats_f64(beats))
+}
+
+#[pyclass(frozen)]
+struct Client {
+    inner: RpcClient,
+}
+
+impl Client {
+    fn call<T, F, Fut>(&self, py: Python<'_>, f: F) -> PyResult<T>
+    where
+        F: FnOnce(RpcClient) -> Fut + Send,
+        Fut: Future<Output = rdaw_api::Result<T>>,
+        T: Send,
+    {
+        let client = self.inner.clone();
+        py.allow_threads(move || block_on(f(client)))
+            .map_err(to_py_err)
+    }
+}
+
+#[pymethods]
+impl Client {
+    /// Starts a backend in this process. It stops once the client is garbage collected.
+    #[staticmethod]
+    #[pyo3(signature = (safe_mode = false))]
+    fn embedded(safe_mode: bool) -> Client {
+        let (client_transport, server_transport) = transport::local(None);
+
+        // there's no audio driver, so nothing is played back
+        let mut backend = Backend::new(server_transport);
+        backend.set_safe_mode(safe_mode);
+        thread::spawn(move || block_on(backend.handle()));
+
+        let client = RpcClient::new(client_transport);
+
+        let client_clone = client.clone();
+        thread::spawn(move || block_on(client_clone.handle()));
+
+        Client { inner: client }
+    }
+
+    fn create_document(&self, py: Python<'_>) -> PyResult<u64> {
+        let id = self.call(py, |c| async move { c.create_document().await })?;
+        Ok(to_raw(id))
+    }
+
+    fn open_document(&self, py: Python<'_>, path: String) -> PyResult<u64> {
+        let path = Utf8PathBuf::from(path);
+        let id = self.call(py, |c| async move { c.open_document(path).await })?;
+        Ok(to_raw(id))
+    }
+
+    fn save_document(&self, py: Python<'_>, id: u64) -> PyResult<()> {
+        self.call(py, |c| async move { c.save_document(from_raw(id)).await })
+    }
+
+    fn save_document_as(&self, py: Python<'_>, id: u64, path: String) -> PyResult<()> {
+        let path = Utf8PathBuf::from(path);
+        self.call(py, |c| async move {
+            c.save_document_as(from_raw(id), path).await
+        })
+    }
+
+    fn get_document_arrangement(&self, py: Python<'_>, id: u64) -> PyResult<u64> {
+        let id = self.call(py, |c| async move {
+            c.get_document_arrangement(from_raw(id)).await
+        })?;
+        Ok(to_raw(id))
+    }
+
+    fn get_arrangement_name(&self, py: Python<'_>, id: u64) -> PyResult<String> {
+        self.call(
+            py,
+            |c| async move { c.get_arrangement_name(from_raw(id)).await },
+        )
+    }
+
+    fn set_arrangement_name(&self, py: Python<'_>, id: u64, name: String) -> PyResult<()> {
+        self.call(py, |c| async move {
+            c.set_arrangement_name(from_raw(id), name).await
+        })
+    }
+
+    fn get_arrangement_main_track(&self, py: Python<'_>, id: u64) -> PyResult<u64> {
+        let id = self.call(py, |c| async move {
+            c.get_arrangement_main_track(from_raw(id)).await
+        })?;
+        Ok(to_raw(id))
+    }
+
+    fn get_arrangement_tempo(&self, py: Python<'_>, id: u64) -> PyResult<f32> {
+        self.call(py, |c| async move {
+            c.get_arrangement_tempo(from_raw(id)).await
+        })
+    }
+
+    fn set_arrangement_tempo(
+        &self,
+        py: Python<'_>,
+        id: u64,
+        beats_per_minute: f32,
+    ) -> PyResult<()> {
+        self.call(py, |c| async move {
+            c.set_arrangement_tempo(from_raw(id), beats_per_minute)
+                .await
+        })
+    }
+
+    fn create_track(&self, py: Python<'_>, document_id: u64) -> PyResult<u64> {
+        let id = self.call(py, |c| async move {
+            c.create_track(from_raw(document_id)).await
+        })?;
+        Ok(to_raw(id))
+    }
+
+    fn get_track_name(&self, py: Python<'_>, id: u64) -> PyResult<String> {
+        self.call(py, |c| async move { c.get_track_name(from_raw(id)).await })
+    }
+
+    fn set_track_name(&self, py: Python<'_>, id: u64, name: String) -> PyResult<()> {
+        self.call(
+            py,
+            |c| async move { c.set_track_name(from_raw(id), name).await },
+        )
+    }
+
+    fn get_track_children(&self, py: Python<'_>, id: u64) -> PyResult<Vec<u64>> {
+        let children = self.call(
+            py,
+            |c| async move { c.get_track_children(from_raw(id)).await },
+        )?;
+        Ok(children.into_iter().map(to_raw).collect())
+    }
+
+    fn append_track_child(&self, py: Python<'_>, parent_id: u64, child_id: u64) -> PyResult<()> {
+        self.call(py, |c| async move {
+            c.append_track_child(from_raw(parent_id), from_raw(child_id))
+                .await
+        })
+    }
+
+    fn remove_track_child(&self, py: Python<'_>, parent_id: u64, index: usize) -> PyResult<()> {
+        self.call(py, |c| async move {
+            c.remove_track_child(from_raw(parent_id), index).await
+        })
+    }
+
+    fn create_external_asset(
+        &self,
+        py: Python<'_>,
+        document_id: u64,
+        path: String,
+    ) -> PyResult<u64> {
+        let path = Utf8PathBuf::from(path);
+        let id = self.call(py, |c| async move {
+            c.create_external_asset(from_raw(document_id), path).await
+        })?;
+        Ok(to_raw(id))
+    }
+
+    fn create_audio_source(&self, py: Python<'_>, asset_id: u64) -> PyResult<u64> {
+        let id = self.call(py, |c| async move {
+            c.create_audio_source(from_raw(asset_id)).await
+        })?;
+        Ok(to_raw(id))
+    }
+
+    fn create_midi_source(&self, py: Python<'_>, document_id: u64) -> PyResult<u64> {
+        let id = self.call(py, |c| async move {
+            c.create_midi_source(from_raw(document_id)).await
+        })?;
+        Ok(to_raw(id))
+    }
+
+    /// Start and duration are in beats, relative to the start of the source.
+    fn add_midi_source_note(
+        &self,
+        py: Python<'_>,
+        id: u64,
+        start: f64,
+        duration: f64,
+        key: u8,
+        velocity: u8,
+    ) -> PyResult<u64> {
+        let note = MidiNote {
+            start: BeatTime::from_beats_f64(start),
+            duration: BeatTime::from_beats_f64(duration),
+            key,
+            velocity,
+        };
+
+        let id = self.call(py, |c| async move {
+            c.add_midi_source_note(from_raw(id), note).await
+        })?;
+        Ok(to_raw(id))
+    }
+
+    fn create_midi_item(&self, py: Python<'_>, source_id: u64) -> PyResult<u64> {
+        let id = self.call(py, |c| async move {
+            c.create_midi_item(from_raw(source_id)).await
+        })?;
+        Ok(to_raw(id))
+    }
+
+    /// Places a MIDI item on the track. Start and duration are in beats.
+    #[pyo3(signature = (track_id, item_id, start, duration, lane = 0))]
+    fn add_midi_track_item(
+        &self,
+        py: Python<'_>,
+        track_id: u64,
+        item_id: u64,
+        start: f64,
+        duration: f64,
+        lane: u32,
+    ) -> PyResult<u64> {
+        let item = TrackItem {
+            inner: ItemId::Midi(from_raw(item_id)),
+            start: beats(start),
+            duration: beats(duration),
+            lane,
+            locked: false,
+        };
+
+        let id = self.call(py, |c| async move {
+            c.add_track_item(from_raw(track_id), item).await
+        })?;
+        Ok(to_raw(id))
+    }
+
+    #[pyo3(signature = (track_id, item_id, force = false))]
+    fn remove_track_item(
+        &self,
+        py: Python<'_>,
+        track_id: u64,
+        item_id: u64,
+        force: bool,
+    ) -> PyResult<()> {
+        self.call(py, |c| async move {
+            c.remove_track_item(from_raw(track_id), from_raw(item_id), force)
+                .await
+        })
+    }
+}
+
+#[pymodule]
+fn rdaw_py(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<Client>()?;
+    m.add("RdawError", m.py().get_type_bound::<RdawError>())?;
+    Ok(())
+}
@@ -0,0 +1,137 @@
+use std::fmt;
+
+const KEY_DERIVATION_CONTEXT: &str = "rdaw-rpc shared secret v1";
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum Role {
+    ReadOnly,
+    Editor,
+    Admin,
+}
+
+impl Role {
+    pub fn permits(self, required: Role) -> bool {
+        self >= required
+    }
+}
+
+#[derive(Clone)]
+pub struct SharedSecret {
+    key: [u8; 32],
+}
+
+impl SharedSecret {
+    pub fn new(secret: &[u8]) -> SharedSecret {
+        SharedSecret {
+            key: blake3::derive_key(KEY_DERIVATION_CONTEXT, secret),
+        }
+    }
+
+    pub fn respond(&self, challenge: &Challenge) -> ChallengeResponse {
+        ChallengeResponse {
+            mac: *self.mac(challenge).as_bytes(),
+        }
+    }
+
+    fn verify(&self, challenge: &Challenge, response: &ChallengeResponse) -> bool {
+        // comparison of `blake3::Hash` is constant-time
+        self.mac(challenge) == blake3::Hash::from(response.mac)
+    }
+
+    fn mac(&self, challenge: &Challenge) -> blake3::Hash {
+        blake3::keyed_hash(&self.key, &challenge.nonce)
+    }
+}
+
+impl fmt::Debug for SharedSecret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SharedSecret").finish_non_exhaustive()
+    }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct Challenge {
+    pub nonce: [u8; 32],
+}
+
+impl Challenge {
+    pub fn new_random() -> Challenge {
+        Challenge {
+            nonce: rand::random(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct ChallengeResponse {
+    pub mac: [u8; 32],
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Authenticator {
+    secrets: Vec<(SharedSecret, Role)>,
+}
+
+impl Authenticator {
+    pub fn new() -> Authenticator {
+        Authenticator::default()
+    }
+
+    pub fn add_secret(&mut self, secret: SharedSecret, role: Role) {
+        self.secrets.push((secret, role));
+    }
+
+    pub fn authenticate(
+        &self,
+        challenge: &Challenge,
+        response: &ChallengeResponse,
+    ) -> Option<Role> {
+        self.secrets
+            .iter()
+            .filter(|(secret, _)| secret.verify(challenge, response))
+            .map(|&(_, role)| role)
+            .max()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn role_permits() {
+        assert!(Role::Admin.permits(Role::Editor));
+        assert!(Role::Editor.permits(Role::Editor));
+        assert!(Role::Editor.permits(Role::ReadOnly));
+        assert!(!Role::ReadOnly.permits(Role::Editor));
+        assert!(!Role::Editor.permits(Role::Admin));
+    }
+
+    #[test]
+    fn authenticate() {
+        let mut authenticator = Authenticator::new();
+        authenticator.add_secret(SharedSecret::new(b"viewer"), Role::ReadOnly);
+        authenticator.add_secret(SharedSecret::new(b"admin"), Role::Admin);
+
+        let challenge = Challenge::new_random();
+
+        let response = SharedSecret::new(b"viewer").respond(&challenge);
+        assert_eq!(
+            authenticator.authenticate(&challenge, &response),
+            Some(Role::ReadOnly)
+        );
+
+        let response = SharedSecret::new(b"admin").respond(&challenge);
+        assert_eq!(
+            authenticator.authenticate(&challenge, &response),
+            Some(Role::Admin)
+        );
+
+        let response = SharedSecret::new(b"wrong").respond(&challenge);
+        assert_eq!(authenticator.authenticate(&challenge, &response), None);
+
+        let other_challenge = Challenge::new_random();
+        let response = SharedSecret::new(b"admin").respond(&other_challenge);
+        assert_eq!(authenticator.authenticate(&challenge, &response), None);
+    }
+}
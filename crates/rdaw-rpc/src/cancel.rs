@@ -0,0 +1,86 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Weak};
+
+use rdaw_core::collections::HashMap;
+
+use crate::RequestId;
+
+/// Tells a handler which responds later that the client isn't waiting for the response anymore.
+/// Work is supposed to check it between steps and stop early.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    pub fn new() -> CancellationToken {
+        CancellationToken::default()
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+/// Tokens of requests still being handled, for [`ClientMessage::CancelRequest`].
+///
+/// Only weak references are kept, so a token is forgotten once the handler drops it.
+///
+/// [`ClientMessage::CancelRequest`]: crate::ClientMessage::CancelRequest
+#[derive(Debug, Default)]
+pub struct CancellationTokens {
+    tokens: HashMap<RequestId, Weak<AtomicBool>>,
+}
+
+impl CancellationTokens {
+    pub fn new() -> CancellationTokens {
+        CancellationTokens::default()
+    }
+
+    pub fn register(&mut self, id: RequestId) -> CancellationToken {
+        self.tokens.retain(|_, v| v.strong_count() > 0);
+
+        let token = CancellationToken::new();
+        self.tokens.insert(id, Arc::downgrade(&token.cancelled));
+        token
+    }
+
+    /// Does nothing if the request was already handled.
+    pub fn cancel(&mut self, id: RequestId) {
+        if let Some(cancelled) = self.tokens.remove(&id).and_then(|v| v.upgrade()) {
+            cancelled.store(true, Ordering::Relaxed);
+        }
+    }
+
+    /// Cancels all requests, e.g. because the client went away.
+    pub fn cancel_all(self) {
+        for cancelled in self.tokens.into_values().filter_map(|v| v.upgrade()) {
+            cancelled.store(true, Ordering::Relaxed);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cancel() {
+        let mut tokens = CancellationTokens::new();
+        let first = tokens.register(RequestId(0));
+        let second = tokens.register(RequestId(1));
+
+        tokens.cancel(RequestId(1));
+        assert!(!first.is_cancelled());
+        assert!(second.is_cancelled());
+
+        // dropped tokens are forgotten
+        drop(first);
+        tokens.register(RequestId(2));
+        assert_eq!(tokens.tokens.len(), 1);
+    }
+}
@@ -1,79 +1,344 @@
 use std::future::Future;
 use std::pin::Pin;
 use std::sync::atomic::{AtomicU64, Ordering};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex, RwLock};
 use std::task::{Context, Poll, Waker};
+use std::time::Duration;
 
 use async_channel::{Receiver, Sender};
-use crossbeam_queue::SegQueue;
-use futures::Stream;
+use futures::future::{self, Either};
+use futures::{pin_mut, Stream};
 use pin_project_lite::pin_project;
-use rdaw_core::collections::{dashmap, DashMap};
+use rdaw_core::collections::{dashmap, DashMap, DashSet};
 
 use crate::transport::ClientTransport;
 use crate::{ClientMessage, Protocol, ProtocolError, RequestId, ServerMessage, StreamId};
 
+const MIN_RECONNECT_DELAY: Duration = Duration::from_millis(100);
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(10);
+const MAX_CONNECT_ATTEMPTS: u32 = 10;
+
 pub struct Client<P: Protocol, T: ClientTransport<P>> {
     inner: Arc<Inner<P, T>>,
 }
 
 struct Inner<P: Protocol, T: ClientTransport<P>> {
-    transport: T,
+    transport: RwLock<T>,
     req_counter: AtomicU64,
+    stream_counter: AtomicU64,
     requests: DashMap<RequestId, RequestSlot<P>>,
-    streams: DashMap<StreamId, Sender<P::Event>>,
-    closed_streams: Arc<SegQueue<StreamId>>,
+    streams: DashMap<StreamId, StreamSlot<P>>,
+    server_streams: DashMap<StreamId, StreamId>,
+    /// Requests whose responses are discarded when they arrive.
+    cancelled_requests: DashSet<RequestId>,
+    dropped_sender: Sender<Dropped<P>>,
+    dropped_receiver: Receiver<Dropped<P>>,
+    resync_listeners: Mutex<Vec<Sender<()>>>,
 }
 
 impl<P: Protocol, T: ClientTransport<P>> Client<P, T> {
     pub fn new(transport: T) -> Client<P, T> {
+        let (dropped_sender, dropped_receiver) = async_channel::unbounded();
+
         Client {
             inner: Arc::new(Inner {
-                transport,
+                transport: RwLock::new(transport),
                 req_counter: AtomicU64::new(0),
+                stream_counter: AtomicU64::new(0),
                 requests: DashMap::default(),
                 streams: DashMap::default(),
-                closed_streams: Arc::new(SegQueue::new()),
+                server_streams: DashMap::default(),
+                cancelled_requests: DashSet::default(),
+                dropped_sender,
+                dropped_receiver,
+                resync_listeners: Mutex::new(Vec::new()),
             }),
         }
     }
 
+    fn transport(&self) -> T {
+        self.inner.transport.read().unwrap().clone()
+    }
+
     pub async fn handle(self) -> Result<(), P::Error> {
         loop {
-            let msg = match self.inner.transport.recv().await {
+            let transport = self.transport();
+
+            // dropped streams and requests are handled right away, not only once the next
+            // message arrives
+            let recv = transport.recv();
+            let dropped = self.inner.dropped_receiver.recv();
+            pin_mut!(recv, dropped);
+
+            match future::select(recv, dropped).await {
+                Either::Left((Ok(msg), _)) => self.handle_msg(msg).await,
+                Either::Left((Err(e), _)) if e.is_disconnected() => {
+                    self.fail_pending_requests();
+                    return Ok(());
+                }
+                Either::Left((Err(e), _)) => return Err(e),
+                Either::Right((Ok(dropped), _)) => self.handle_dropped(&transport, dropped).await?,
+                Either::Right((Err(_), _)) => {}
+            }
+
+            while let Ok(dropped) = self.inner.dropped_receiver.try_recv() {
+                self.handle_dropped(&transport, dropped).await?;
+            }
+        }
+    }
+
+    async fn handle_dropped(&self, transport: &T, dropped: Dropped<P>) -> Result<(), P::Error> {
+        match dropped {
+            Dropped::Stream(id) => self.close_stream(transport, id).await,
+            Dropped::Request(id) => transport.send(ClientMessage::CancelRequest { id }).await,
+            Dropped::Detached(payload) => {
+                let id = RequestId(self.inner.req_counter.fetch_add(1, Ordering::Relaxed));
+                self.inner.cancelled_requests.insert(id);
+                transport.send(ClientMessage::Request { id, payload }).await
+            }
+        }
+    }
+
+    /// Sends a message after everything queued by dropped streams and requests, so that the
+    /// server sees them in the order they happened.
+    async fn send_message(&self, msg: ClientMessage<P>) -> Result<(), P::Error> {
+        let transport = self.transport();
+
+        while let Ok(dropped) = self.inner.dropped_receiver.try_recv() {
+            self.handle_dropped(&transport, dropped).await?;
+        }
+
+        transport.send(msg).await
+    }
+
+    async fn close_stream(&self, transport: &T, id: StreamId) -> Result<(), P::Error> {
+        let Some((_, slot)) = self.inner.streams.remove(&id) else {
+            return Ok(());
+        };
+
+        self.inner.server_streams.remove(&slot.server_id);
+
+        transport
+            .send(ClientMessage::CloseStream { id: slot.server_id })
+            .await
+    }
+
+    /// Number of streams which haven't been closed yet, for debugging leaks.
+    pub fn open_stream_count(&self) -> usize {
+        self.inner.streams.len()
+    }
+
+    /// Keeps handling messages, connecting again whenever the server goes away. Failed connection
+    /// attempts are retried after `sleep` with a growing delay, the last error is returned once
+    /// they run out.
+    pub async fn handle_reconnecting<C, Fut, S, SFut>(
+        self,
+        mut connect: C,
+        mut sleep: S,
+    ) -> Result<(), P::Error>
+    where
+        C: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, P::Error>>,
+        S: FnMut(Duration) -> SFut,
+        SFut: Future<Output = ()>,
+    {
+        self.clone().handle().await?;
+
+        loop {
+            let transport = connect_with_backoff(&mut connect, &mut sleep).await?;
+            self.reconnect(transport);
+
+            let handle = self.clone().handle();
+            let resubscribe = self.resubscribe();
+            pin_mut!(handle, resubscribe);
+
+            match future::select(handle, resubscribe).await {
+                Either::Left((res, _)) => res?,
+                Either::Right((res, handle)) => {
+                    res?;
+                    self.notify_resync();
+                    handle.await?;
+                }
+            }
+        }
+    }
+
+    fn reconnect(&self, transport: T) {
+        *self.inner.transport.write().unwrap() = transport;
+        self.inner.server_streams.clear();
+
+        // streams that can't be replayed are closed, their ids are meaningless to the new server
+        self.inner.streams.retain(|_, slot| slot.replay.is_some());
+    }
+
+    async fn resubscribe(&self) -> Result<(), P::Error> {
+        let replays = self
+            .inner
+            .streams
+            .iter()
+            .filter_map(|slot| Some((*slot.key(), slot.replay.clone()?)))
+            .collect::<Vec<_>>();
+
+        for (id, req) in replays {
+            let res = match self.request(req).await {
                 Ok(v) => v,
-                Err(e) if e.is_disconnected() => return Ok(()),
-                Err(e) => return Err(e),
+                Err(e) if e.is_disconnected() => return Err(e),
+                Err(_) => {
+                    self.inner.streams.remove(&id);
+                    continue;
+                }
+            };
+
+            let Some(server_id) = P::response_stream_id(&res) else {
+                self.inner.streams.remove(&id);
+                continue;
             };
 
-            self.handle_msg(msg).await;
+            let is_alive = match self.inner.streams.get_mut(&id) {
+                Some(mut slot) => {
+                    slot.server_id = server_id;
+                    slot.next_seq = 0;
+                    true
+                }
+                None => false,
+            };
 
-            while let Some(id) = self.inner.closed_streams.pop() {
-                self.inner
-                    .transport
-                    .send(ClientMessage::CloseStream { id })
+            if is_alive {
+                self.inner.server_streams.insert(server_id, id);
+            } else {
+                self.transport()
+                    .send(ClientMessage::CloseStream { id: server_id })
                     .await?;
             }
         }
+
+        Ok(())
+    }
+
+    /// Notifies after reconnecting or noticing lost events, when cached state has to be refetched.
+    pub fn subscribe_resync(&self) -> impl Stream<Item = ()> {
+        let (sender, receiver) = async_channel::unbounded();
+        self.inner.resync_listeners.lock().unwrap().push(sender);
+        receiver
+    }
+
+    fn notify_resync(&self) {
+        let mut listeners = self.inner.resync_listeners.lock().unwrap();
+        listeners.retain(|sender| sender.try_send(()).is_ok());
+    }
+
+    fn fail_pending_requests(&self) {
+        // cancelled requests won't be answered by the next server
+        self.inner.cancelled_requests.clear();
+
+        // every request has a slot from the moment it's sent, polled or not
+        for mut slot in self.inner.requests.iter_mut() {
+            if slot.response.is_some() {
+                continue;
+            }
+
+            slot.response = Some(Err(P::Error::disconnected()));
+
+            if let Some(waker) = slot.waker.take() {
+                waker.wake();
+            }
+        }
     }
 
     pub async fn request(&self, payload: P::Req) -> Result<P::Res, P::Error> {
-        let id = RequestId(self.inner.req_counter.fetch_add(1, Ordering::Relaxed));
+        let id = self.start_request();
 
         let msg = ClientMessage::Request { id, payload };
 
-        self.inner.transport.send(msg).await?;
+        if let Err(e) = self.send_message(msg).await {
+            self.inner.requests.remove(&id);
+            return Err(e);
+        }
+
         self.wait_for_response(id).await
     }
 
+    /// Sends a request which is cancelled once the returned future is dropped before completing.
+    pub async fn request_with_cancel(
+        &self,
+        payload: P::Req,
+    ) -> Result<CancellableRequest<P, T>, P::Error> {
+        let id = self.start_request();
+
+        let res = self
+            .send_message(ClientMessage::Request { id, payload })
+            .await;
+        if let Err(e) = res {
+            self.inner.requests.remove(&id);
+            return Err(e);
+        }
+
+        Ok(CancellableRequest {
+            client: self.clone(),
+            id,
+            is_done: false,
+        })
+    }
+
+    /// Sends a request without waiting for its response, which is discarded. It can be called
+    /// from `Drop` implementations, the request is sent by [`Client::handle`] or before the next
+    /// request, whichever comes first.
+    pub fn request_detached(&self, payload: P::Req) {
+        let _ = self
+            .inner
+            .dropped_sender
+            .try_send(Dropped::Detached(payload));
+    }
+
+    /// Starts a batch of requests which are sent together, see [`Batch`].
+    pub fn batch(&self) -> Batch<P, T> {
+        Batch {
+            client: self.clone(),
+            requests: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Allocates an id for a request which is about to be sent, and a slot for its response.
+    fn start_request(&self) -> RequestId {
+        let id = RequestId(self.inner.req_counter.fetch_add(1, Ordering::Relaxed));
+        self.inner.requests.insert(
+            id,
+            RequestSlot {
+                response: None,
+                waker: None,
+            },
+        );
+        id
+    }
+
     pub fn subscribe(&self, id: StreamId) -> impl Stream<Item = P::Event> {
+        self.subscribe_inner(id, None)
+    }
+
+    pub fn subscribe_replayable(&self, id: StreamId, req: P::Req) -> impl Stream<Item = P::Event> {
+        self.subscribe_inner(id, Some(req))
+    }
+
+    fn subscribe_inner(&self, server_id: StreamId, replay: Option<P::Req>) -> EventStream<P> {
+        let id = StreamId(self.inner.stream_counter.fetch_add(1, Ordering::Relaxed));
+
         let (sender, receiver) = async_channel::unbounded();
-        self.inner.streams.insert(id, sender);
+
+        self.inner.streams.insert(
+            id,
+            StreamSlot {
+                server_id,
+                sender,
+                replay,
+                next_seq: 0,
+            },
+        );
+        self.inner.server_streams.insert(server_id, id);
 
         EventStream {
             cleaner: StreamCleaner {
                 id,
-                queue: self.inner.closed_streams.clone(),
+                sender: self.inner.dropped_sender.clone(),
             },
             receiver,
         }
@@ -83,63 +348,110 @@ impl<P: Protocol, T: ClientTransport<P>> Client<P, T> {
         &self,
         id: RequestId,
     ) -> impl Future<Output = Result<P::Res, P::Error>> + '_ {
-        std::future::poll_fn(move |ctx| {
-            let mut slot = self
-                .inner
-                .requests
-                .entry(id)
-                .or_insert_with(|| RequestSlot {
-                    response: None,
-                    waker: None,
-                });
-
-            if let Some(response) = slot.response.take() {
-                return Poll::Ready(response);
-            }
+        std::future::poll_fn(move |ctx| self.poll_response(id, ctx))
+    }
 
-            slot.waker = Some(ctx.waker().clone());
+    fn poll_response(
+        &self,
+        id: RequestId,
+        ctx: &mut Context<'_>,
+    ) -> Poll<Result<P::Res, P::Error>> {
+        let mut slot = self
+            .inner
+            .requests
+            .entry(id)
+            .or_insert_with(|| RequestSlot {
+                response: None,
+                waker: None,
+            });
 
-            Poll::Pending
-        })
+        if let Some(response) = slot.response.take() {
+            drop(slot);
+            self.inner.requests.remove(&id);
+            return Poll::Ready(response);
+        }
+
+        slot.waker = Some(ctx.waker().clone());
+
+        Poll::Pending
+    }
+
+    fn cancel_request(&self, id: RequestId) {
+        let slot = self.inner.requests.remove(&id);
+
+        // the response may have arrived already
+        if slot.is_some_and(|(_, v)| v.response.is_some()) {
+            return;
+        }
+
+        self.inner.cancelled_requests.insert(id);
+        let _ = self.inner.dropped_sender.try_send(Dropped::Request(id));
     }
 
     async fn handle_msg(&self, msg: ServerMessage<P>) {
         match msg {
-            ServerMessage::Response { id, payload } => {
-                let mut slot = self
-                    .inner
-                    .requests
-                    .entry(id)
-                    .or_insert_with(|| RequestSlot {
-                        response: None,
-                        waker: None,
-                    });
-
-                slot.response = Some(payload);
-
-                if let Some(waker) = slot.waker.take() {
-                    waker.wake();
+            ServerMessage::Response { id, payload } => self.complete_request(id, payload),
+
+            ServerMessage::Batch { responses } => {
+                for (id, payload) in responses {
+                    self.complete_request(id, payload);
                 }
             }
 
-            ServerMessage::Event { id, payload } => {
+            ServerMessage::Event { id, seq, payload } => {
+                let Some(id) = self.inner.server_streams.get(&id).map(|v| *v) else {
+                    return;
+                };
+
                 let dashmap::mapref::entry::Entry::Occupied(mut entry) =
                     self.inner.streams.entry(id)
                 else {
                     return;
                 };
 
-                let res = entry.get_mut().send_blocking(payload);
+                let slot = entry.get_mut();
+                let has_gap = seq != slot.next_seq;
+                slot.next_seq = seq + 1;
+
+                let res = slot.sender.send_blocking(payload);
                 if res.is_err() {
-                    entry.remove();
+                    let slot = entry.remove();
+                    self.inner.server_streams.remove(&slot.server_id);
+                }
+
+                if has_gap {
+                    self.notify_resync();
                 }
             }
 
             ServerMessage::CloseStream { id } => {
-                self.inner.streams.remove(&id);
+                if let Some((_, id)) = self.inner.server_streams.remove(&id) {
+                    self.inner.streams.remove(&id);
+                }
             }
         }
     }
+
+    fn complete_request(&self, id: RequestId, payload: Result<P::Res, P::Error>) {
+        if self.inner.cancelled_requests.remove(&id).is_some() {
+            return;
+        }
+
+        // the request was cancelled, or failed when the previous server went away
+        let Some(mut slot) = self.inner.requests.get_mut(&id) else {
+            return;
+        };
+
+        if slot.response.is_some() {
+            return;
+        }
+
+        slot.response = Some(payload);
+
+        if let Some(waker) = slot.waker.take() {
+            waker.wake();
+        }
+    }
 }
 
 impl<P: Protocol, T: ClientTransport<P>> Clone for Client<P, T> {
@@ -150,34 +462,148 @@ impl<P: Protocol, T: ClientTransport<P>> Clone for Client<P, T> {
     }
 }
 
+/// Requests which are sent to the server in a single message by [`Batch::send`], saving a round
+/// trip per request. The server handles them in order.
+///
+/// Operations implemented for the batch queue a request and return a future of its response,
+/// which only completes after the batch is sent.
+pub struct Batch<P: Protocol, T: ClientTransport<P>> {
+    client: Client<P, T>,
+    requests: Mutex<Vec<(RequestId, P::Req)>>,
+}
+
+impl<P: Protocol, T: ClientTransport<P>> Batch<P, T> {
+    pub fn request(&self, payload: P::Req) -> impl Future<Output = Result<P::Res, P::Error>> + '_ {
+        let id = self.client.start_request();
+        self.requests.lock().unwrap().push((id, payload));
+        self.client.wait_for_response(id)
+    }
+
+    pub fn subscribe_replayable(&self, id: StreamId, req: P::Req) -> impl Stream<Item = P::Event> {
+        self.client.subscribe_replayable(id, req)
+    }
+
+    /// Number of requests queued since the last send.
+    pub fn len(&self) -> usize {
+        self.requests.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Sends the queued requests, the batch can be reused afterwards.
+    pub async fn send(&self) -> Result<(), P::Error> {
+        let requests = std::mem::take(&mut *self.requests.lock().unwrap());
+        if requests.is_empty() {
+            return Ok(());
+        }
+
+        // if sending fails, the queued requests are failed once the disconnect is noticed
+        self.client
+            .send_message(ClientMessage::Batch { requests })
+            .await
+    }
+}
+
+/// Response to [`Client::request_with_cancel`]. Dropping it before the response arrives tells the
+/// server to cancel the request.
+pub struct CancellableRequest<P: Protocol, T: ClientTransport<P>> {
+    client: Client<P, T>,
+    id: RequestId,
+    is_done: bool,
+}
+
+impl<P: Protocol, T: ClientTransport<P>> CancellableRequest<P, T> {
+    pub fn id(&self) -> RequestId {
+        self.id
+    }
+
+    pub fn cancel(self) {}
+}
+
+impl<P: Protocol, T: ClientTransport<P>> Future for CancellableRequest<P, T> {
+    type Output = Result<P::Res, P::Error>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let res = self.client.poll_response(self.id, cx);
+        self.is_done = res.is_ready();
+        res
+    }
+}
+
+impl<P: Protocol, T: ClientTransport<P>> Drop for CancellableRequest<P, T> {
+    fn drop(&mut self) {
+        if !self.is_done {
+            self.client.cancel_request(self.id);
+        }
+    }
+}
+
+async fn connect_with_backoff<T, E, C, Fut, S, SFut>(connect: &mut C, sleep: &mut S) -> Result<T, E>
+where
+    C: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+    S: FnMut(Duration) -> SFut,
+    SFut: Future<Output = ()>,
+{
+    let mut delay = MIN_RECONNECT_DELAY;
+    let mut attempt = 1;
+
+    loop {
+        match connect().await {
+            Ok(transport) => return Ok(transport),
+            Err(e) if attempt >= MAX_CONNECT_ATTEMPTS => return Err(e),
+            Err(_) => {}
+        }
+
+        sleep(delay).await;
+        delay = (delay * 2).min(MAX_RECONNECT_DELAY);
+        attempt += 1;
+    }
+}
+
+enum Dropped<P: Protocol> {
+    Stream(StreamId),
+    Request(RequestId),
+    Detached(P::Req),
+}
+
 struct RequestSlot<P: Protocol> {
     response: Option<Result<P::Res, P::Error>>,
     waker: Option<Waker>,
 }
 
+struct StreamSlot<P: Protocol> {
+    server_id: StreamId,
+    sender: Sender<P::Event>,
+    replay: Option<P::Req>,
+    next_seq: u64,
+}
+
 pin_project! {
-    struct EventStream<T> {
-        cleaner: StreamCleaner,
+    struct EventStream<P: Protocol> {
+        cleaner: StreamCleaner<P>,
         #[pin]
-        receiver: Receiver<T>,
+        receiver: Receiver<P::Event>,
     }
 }
 
-impl<T> Stream for EventStream<T> {
-    type Item = T;
+impl<P: Protocol> Stream for EventStream<P> {
+    type Item = P::Event;
 
-    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<T>> {
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<P::Event>> {
         self.project().receiver.poll_next(cx)
     }
 }
 
-struct StreamCleaner {
+struct StreamCleaner<P: Protocol> {
     id: StreamId,
-    queue: Arc<SegQueue<StreamId>>,
+    sender: Sender<Dropped<P>>,
 }
 
-impl Drop for StreamCleaner {
+impl<P: Protocol> Drop for StreamCleaner<P> {
     fn drop(&mut self) {
-        self.queue.push(self.id);
+        let _ = self.sender.try_send(Dropped::Stream(self.id));
     }
 }
@@ -0,0 +1,18 @@
+use std::io;
+
+use serde::de::DeserializeOwned;
+
+use crate::transport::read_frame;
+
+/// Decodes length-prefixed frames from the data the way the reader thread of a socket transport
+/// does, until the data runs out or a frame is rejected.
+pub fn read_frames<T: DeserializeOwned>(mut data: &[u8]) -> io::Result<Vec<T>> {
+    let mut buf = Vec::new();
+    let mut messages = Vec::new();
+
+    while !data.is_empty() {
+        messages.push(read_frame(&mut data, &mut buf)?);
+    }
+
+    Ok(messages)
+}
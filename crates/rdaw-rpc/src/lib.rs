@@ -1,24 +1,34 @@
+mod auth;
+mod cancel;
 mod client;
+#[cfg(feature = "fuzzing")]
+pub mod fuzz;
 mod id_allocator;
 mod subscribers;
 pub mod transport;
 
+use std::fmt;
 use std::future::Future;
 use std::marker::PhantomData;
 
 pub use rdaw_macros::{
     rpc_handler as handler, rpc_operations as operations, rpc_protocol as protocol,
 };
+use serde::{Deserialize, Serialize};
 
-pub use self::client::Client;
+pub use self::auth::{Authenticator, Challenge, ChallengeResponse, Role, SharedSecret};
+pub use self::cancel::{CancellationToken, CancellationTokens};
+pub use self::client::{Batch, CancellableRequest, Client};
 pub use self::id_allocator::IdAllocator;
-pub use self::subscribers::Subscribers;
+pub use self::subscribers::{Subscribers, MAX_QUEUED_EVENTS};
 
 pub trait Protocol: Send + Sync + 'static {
-    type Req: Send + 'static;
+    type Req: Clone + Send + 'static;
     type Res: Send + 'static;
     type Event: Send + 'static;
     type Error: ProtocolError;
+
+    fn response_stream_id(res: &Self::Res) -> Option<StreamId>;
 }
 
 pub trait ProtocolError: std::error::Error + Send + 'static {
@@ -29,9 +39,17 @@ pub trait ProtocolError: std::error::Error + Send + 'static {
     fn is_disconnected(&self) -> bool;
 
     fn is_invalid_type(&self) -> bool;
+
+    fn with_operation(
+        self,
+        _name: &'static str,
+        _objects: &[(&'static str, &dyn fmt::Debug)],
+    ) -> Self {
+        self
+    }
 }
 
-#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Serialize, Deserialize)]
 #[repr(transparent)]
 pub struct RequestId(pub u64);
 
@@ -43,7 +61,7 @@ impl From<u64> for RequestId {
 
 pub type RequestIdAllocator = IdAllocator<RequestId>;
 
-#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Serialize, Deserialize)]
 #[repr(transparent)]
 pub struct StreamId(pub u64);
 
@@ -55,20 +73,49 @@ impl From<u64> for StreamId {
 
 pub type StreamIdAllocator = IdAllocator<StreamId>;
 
-#[derive(Debug, Clone, Eq, PartialEq)]
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(bound(
+    serialize = "P::Req: Serialize",
+    deserialize = "P::Req: Deserialize<'de>"
+))]
 pub enum ClientMessage<P: Protocol> {
-    Request { id: RequestId, payload: P::Req },
-    CloseStream { id: StreamId },
+    Request {
+        id: RequestId,
+        payload: P::Req,
+    },
+    /// Requests handled in order, saving a round trip per request. Responses come back in a
+    /// single [`ServerMessage::Batch`], except for deferred ones.
+    Batch {
+        requests: Vec<(RequestId, P::Req)>,
+    },
+    /// The client isn't interested in the response anymore. It's still sent, but handlers which
+    /// take a [`CancellationToken`] can stop early and respond with an error.
+    CancelRequest {
+        id: RequestId,
+    },
+    CloseStream {
+        id: StreamId,
+    },
 }
 
-#[derive(Debug, Clone, Eq, PartialEq)]
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(bound(
+    serialize = "P::Res: Serialize, P::Event: Serialize, P::Error: Serialize",
+    deserialize = "P::Res: Deserialize<'de>, P::Event: Deserialize<'de>, P::Error: Deserialize<'de>"
+))]
 pub enum ServerMessage<P: Protocol> {
     Response {
         id: RequestId,
         payload: Result<P::Res, P::Error>,
     },
+    Batch {
+        responses: Vec<(RequestId, Result<P::Res, P::Error>)>,
+    },
     Event {
         id: StreamId,
+        /// Counts events sent on the stream, starting from zero. A gap means that events were
+        /// lost, e.g. because the queue of the stream overflowed.
+        seq: u64,
         payload: P::Event,
     },
     CloseStream {
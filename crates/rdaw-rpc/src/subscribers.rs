@@ -7,34 +7,78 @@ use rdaw_core::collections::HashMap;
 use crate::transport::ServerTransport;
 use crate::{Protocol, ServerMessage, StreamId, StreamIdAllocator};
 
+pub type CoalesceFn<E> = fn(&E, &E) -> bool;
+
+/// Events queued per key before the oldest ones are dropped. Subscribers notice the gap in
+/// sequence numbers and refetch their state.
+pub const MAX_QUEUED_EVENTS: usize = 4096;
+
 #[derive(Debug)]
-pub struct Subscribers<K, E> {
+pub struct Subscribers<K, E, F = ()> {
     id_allocator: Arc<StreamIdAllocator>,
-    entries: HashMap<K, Entry<E>>,
-    closed_entries: Vec<Entry<E>>,
+    entries: HashMap<K, Entry<E, F>>,
+    closed_entries: Vec<Entry<E, F>>,
     streams: HashMap<StreamId, K>,
+    coalesce: Option<CoalesceFn<E>>,
 }
 
 #[derive(Debug)]
-struct Entry<E> {
-    streams: Vec<StreamId>,
+struct Entry<E, F> {
+    streams: Vec<Subscription<F>>,
     closed_streams: Vec<StreamId>,
-    queue: VecDeque<E>,
+    queue: VecDeque<QueuedEvent<E>>,
+}
+
+#[derive(Debug)]
+struct Subscription<F> {
+    id: StreamId,
+    filter: F,
+    next_seq: u64,
+}
+
+impl<F> Subscription<F> {
+    fn is_target(&self, targets: &Option<Vec<StreamId>>) -> bool {
+        targets.as_ref().is_none_or(|v| v.contains(&self.id))
+    }
+}
+
+#[derive(Debug)]
+struct QueuedEvent<E> {
+    event: E,
+    targets: Option<Vec<StreamId>>,
 }
 
-impl<K, E> Subscribers<K, E> {
-    pub fn new(id_allocator: Arc<StreamIdAllocator>) -> Subscribers<K, E> {
+impl<K, E, F> Subscribers<K, E, F> {
+    pub fn new(id_allocator: Arc<StreamIdAllocator>) -> Subscribers<K, E, F> {
         Subscribers {
             id_allocator,
             entries: HashMap::default(),
             closed_entries: Vec::new(),
             streams: HashMap::default(),
+            coalesce: None,
+        }
+    }
+
+    pub fn with_coalescing(
+        id_allocator: Arc<StreamIdAllocator>,
+        coalesce: CoalesceFn<E>,
+    ) -> Subscribers<K, E, F> {
+        Subscribers {
+            coalesce: Some(coalesce),
+            ..Subscribers::new(id_allocator)
         }
     }
 }
 
-impl<K: Copy + Eq + Hash, E: Clone> Subscribers<K, E> {
-    pub fn subscribe(&mut self, key: K) -> StreamId {
+impl<K: Copy + Eq + Hash, E: Clone, F> Subscribers<K, E, F> {
+    pub fn subscribe(&mut self, key: K) -> StreamId
+    where
+        F: Default,
+    {
+        self.subscribe_filtered(key, F::default())
+    }
+
+    pub fn subscribe_filtered(&mut self, key: K, filter: F) -> StreamId {
         let stream = self.id_allocator.next();
 
         let entry = self.entries.entry(key).or_insert_with(|| Entry {
@@ -43,13 +87,46 @@ impl<K: Copy + Eq + Hash, E: Clone> Subscribers<K, E> {
             queue: VecDeque::new(),
         });
 
-        entry.streams.push(stream);
+        entry.streams.push(Subscription {
+            id: stream,
+            filter,
+            next_seq: 0,
+        });
         self.streams.insert(stream, key);
 
         stream
     }
 
     pub fn notify(&mut self, key: K, event: E) {
+        self.push_event(key, event, None);
+    }
+
+    pub fn notify_filtered(&mut self, key: K, event: E, matches: impl Fn(&F) -> bool) {
+        let Some(entry) = self.entries.get(&key) else {
+            return;
+        };
+
+        let targets = entry
+            .streams
+            .iter()
+            .filter(|v| matches(&v.filter))
+            .map(|v| v.id)
+            .collect::<Vec<_>>();
+
+        if targets.is_empty() {
+            return;
+        }
+
+        let targets = if targets.len() == entry.streams.len() {
+            None
+        } else {
+            Some(targets)
+        };
+
+        self.push_event(key, event, targets);
+    }
+
+    fn push_event(&mut self, key: K, event: E, targets: Option<Vec<StreamId>>) {
         let Some(entry) = self.entries.get_mut(&key) else {
             return;
         };
@@ -58,7 +135,48 @@ impl<K: Copy + Eq + Hash, E: Clone> Subscribers<K, E> {
             return;
         }
 
-        entry.queue.push_back(event);
+        // only the last event is replaced, otherwise events would be reordered
+        if let Some(coalesce) = self.coalesce {
+            let queued = entry
+                .queue
+                .back_mut()
+                .filter(|v| v.targets == targets && coalesce(&v.event, &event));
+
+            if let Some(queued) = queued {
+                queued.event = event;
+                return;
+            }
+        }
+
+        if entry.queue.len() >= MAX_QUEUED_EVENTS {
+            if let Some(dropped) = entry.queue.pop_front() {
+                for stream in &mut entry.streams {
+                    if stream.is_target(&dropped.targets) {
+                        stream.next_seq += 1;
+                    }
+                }
+            }
+        }
+
+        entry.queue.push_back(QueuedEvent { event, targets });
+    }
+
+    /// Drops events that haven't been delivered yet.
+    pub fn discard_queued(&mut self) {
+        for entry in self.entries.values_mut() {
+            entry.queue.clear();
+        }
+    }
+
+    pub fn has_subscribers(&self, key: K) -> bool {
+        self.entries.get(&key).is_some_and(|v| !v.streams.is_empty())
+    }
+
+    pub fn filters(&self, key: K) -> impl Iterator<Item = &F> {
+        self.entries
+            .get(&key)
+            .into_iter()
+            .flat_map(|v| v.streams.iter().map(|v| &v.filter))
     }
 
     pub fn find_key(&mut self, stream: StreamId) -> Option<K> {
@@ -76,7 +194,7 @@ impl<K: Copy + Eq + Hash, E: Clone> Subscribers<K, E> {
             return;
         };
 
-        let Some(idx) = entry.streams.iter().position(|v| *v == stream) else {
+        let Some(idx) = entry.streams.iter().position(|v| v.id == stream) else {
             return;
         };
 
@@ -102,10 +220,20 @@ impl<K: Copy + Eq + Hash, E: Clone> Subscribers<K, E> {
                 continue;
             }
 
-            for event in entry.queue.drain(..) {
-                for &id in &entry.streams {
-                    let payload = converter(event.clone());
-                    transport.send(ServerMessage::Event { id, payload }).await?;
+            for queued in entry.queue.drain(..) {
+                for stream in &mut entry.streams {
+                    if !stream.is_target(&queued.targets) {
+                        continue;
+                    }
+
+                    let id = stream.id;
+                    let seq = stream.next_seq;
+                    stream.next_seq += 1;
+
+                    let payload = converter(queued.event.clone());
+                    transport
+                        .send(ServerMessage::Event { id, seq, payload })
+                        .await?;
                 }
             }
 
@@ -115,7 +243,7 @@ impl<K: Copy + Eq + Hash, E: Clone> Subscribers<K, E> {
         }
 
         for entry in self.closed_entries.drain(..) {
-            to_close.extend(entry.streams);
+            to_close.extend(entry.streams.into_iter().map(|v| v.id));
             to_close.extend(entry.closed_streams);
         }
 
@@ -130,3 +258,25 @@ impl<K: Copy + Eq + Hash, E: Clone> Subscribers<K, E> {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn coalesce_with_last() {
+        let allocator = Arc::new(StreamIdAllocator::new());
+        let mut subscribers =
+            Subscribers::<u32, (u32, u32)>::with_coalescing(allocator, |a, b| a.0 == b.0);
+        subscribers.subscribe(0);
+
+        subscribers.notify(0, (1, 0));
+        subscribers.notify(0, (2, 0));
+        subscribers.notify(0, (1, 1));
+        subscribers.notify(0, (1, 2));
+
+        let queue = &subscribers.entries[&0].queue;
+        let events = queue.iter().map(|v| v.event).collect::<Vec<_>>();
+        assert_eq!(events, [(1, 0), (2, 0), (1, 2)]);
+    }
+}
@@ -0,0 +1,71 @@
+use std::sync::{Arc, Mutex};
+
+use super::ServerTransport;
+use crate::{ClientMessage, Protocol, RequestId, Role, ServerMessage};
+
+type Responses<P> = Vec<(RequestId, Result<<P as Protocol>::Res, <P as Protocol>::Error>)>;
+
+/// Collects responses to a [`ClientMessage::Batch`], so that they're sent back in a single
+/// message by [`BatchingTransport::finish`].
+///
+/// Everything else goes through the inner transport right away, and so do responses sent after
+/// finishing, e.g. by handlers which respond later.
+pub struct BatchingTransport<P: Protocol, T> {
+    inner: T,
+    responses: Arc<Mutex<Option<Responses<P>>>>,
+}
+
+impl<P: Protocol, T: ServerTransport<P>> BatchingTransport<P, T> {
+    pub fn new(inner: T) -> BatchingTransport<P, T> {
+        BatchingTransport {
+            inner,
+            responses: Arc::new(Mutex::new(Some(Vec::new()))),
+        }
+    }
+
+    pub async fn finish(&self) -> Result<(), P::Error> {
+        let responses = self.responses.lock().unwrap().take().unwrap_or_default();
+        if responses.is_empty() {
+            return Ok(());
+        }
+
+        self.inner.send(ServerMessage::Batch { responses }).await
+    }
+}
+
+impl<P: Protocol, T: ServerTransport<P>> ServerTransport<P> for BatchingTransport<P, T> {
+    async fn send(&self, message: ServerMessage<P>) -> Result<(), P::Error> {
+        let message = match message {
+            ServerMessage::Response { id, payload } => {
+                let mut responses = self.responses.lock().unwrap();
+                match responses.as_mut() {
+                    Some(responses) => {
+                        responses.push((id, payload));
+                        return Ok(());
+                    }
+                    None => ServerMessage::Response { id, payload },
+                }
+            }
+            message => message,
+        };
+
+        self.inner.send(message).await
+    }
+
+    async fn recv(&self) -> Result<ClientMessage<P>, P::Error> {
+        self.inner.recv().await
+    }
+
+    fn role(&self) -> Role {
+        self.inner.role()
+    }
+}
+
+impl<P: Protocol, T: Clone> Clone for BatchingTransport<P, T> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            responses: self.responses.clone(),
+        }
+    }
+}
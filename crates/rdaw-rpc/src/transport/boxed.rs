@@ -0,0 +1,72 @@
+use std::fmt;
+use std::sync::Arc;
+
+use futures::future::BoxFuture;
+
+use super::ServerTransport;
+use crate::{ClientMessage, Protocol, Role, ServerMessage};
+
+trait DynServerTransport<P: Protocol>: Send + Sync {
+    fn send(&self, message: ServerMessage<P>) -> BoxFuture<'_, Result<(), P::Error>>;
+
+    fn recv(&self) -> BoxFuture<'_, Result<ClientMessage<P>, P::Error>>;
+
+    fn role(&self) -> Role;
+}
+
+impl<P: Protocol, T: ServerTransport<P>> DynServerTransport<P> for T {
+    fn send(&self, message: ServerMessage<P>) -> BoxFuture<'_, Result<(), P::Error>> {
+        Box::pin(ServerTransport::send(self, message))
+    }
+
+    fn recv(&self) -> BoxFuture<'_, Result<ClientMessage<P>, P::Error>> {
+        Box::pin(ServerTransport::recv(self))
+    }
+
+    fn role(&self) -> Role {
+        ServerTransport::role(self)
+    }
+}
+
+/// Any server transport, so that servers don't have to be generic over the kind of connection.
+pub struct BoxedServerTransport<P: Protocol> {
+    inner: Arc<dyn DynServerTransport<P>>,
+}
+
+impl<P: Protocol> BoxedServerTransport<P> {
+    pub fn new(transport: impl ServerTransport<P>) -> BoxedServerTransport<P> {
+        BoxedServerTransport {
+            inner: Arc::new(transport),
+        }
+    }
+}
+
+impl<P: Protocol> ServerTransport<P> for BoxedServerTransport<P> {
+    async fn send(&self, message: ServerMessage<P>) -> Result<(), P::Error> {
+        self.inner.send(message).await
+    }
+
+    async fn recv(&self) -> Result<ClientMessage<P>, P::Error> {
+        self.inner.recv().await
+    }
+
+    fn role(&self) -> Role {
+        self.inner.role()
+    }
+}
+
+impl<P: Protocol> Clone for BoxedServerTransport<P> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<P: Protocol> fmt::Debug for BoxedServerTransport<P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BoxedServerTransport")
+            .field("role", &self.role())
+            .finish_non_exhaustive()
+    }
+}
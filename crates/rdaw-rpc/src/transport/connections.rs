@@ -0,0 +1,317 @@
+use std::sync::{Arc, Mutex};
+
+use futures::future::{self, FutureExt};
+use rdaw_core::collections::HashMap;
+
+use super::{BoxedServerTransport, ServerTransport};
+use crate::{ClientMessage, Protocol, ProtocolError, Role, ServerMessage, StreamId};
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct ConnectionId(pub u64);
+
+type Routes = Arc<Mutex<HashMap<StreamId, ConnectionId>>>;
+
+/// Clients of a server. Responses go back through the connection the request came from, events
+/// go to the connection which has opened their stream.
+#[derive(Debug)]
+pub struct Connections<P: Protocol> {
+    next_id: u64,
+    connections: Vec<Connection<P>>,
+    routes: Routes,
+}
+
+pub enum Incoming<P: Protocol> {
+    Message(ClientMessage<P>),
+    /// The client went away, leaving these streams open. The connection is removed.
+    Closed {
+        streams: Vec<StreamId>,
+    },
+}
+
+impl<P: Protocol> Connections<P> {
+    pub fn new() -> Connections<P> {
+        Connections {
+            next_id: 0,
+            connections: Vec::new(),
+            routes: Routes::default(),
+        }
+    }
+
+    pub fn add(&mut self, transport: impl ServerTransport<P>) -> ConnectionId {
+        let id = ConnectionId(self.next_id);
+        self.next_id += 1;
+
+        self.connections.push(Connection {
+            id,
+            transport: BoxedServerTransport::new(transport),
+            routes: self.routes.clone(),
+        });
+
+        id
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.connections.is_empty()
+    }
+
+    pub fn get(&self, id: ConnectionId) -> Option<Connection<P>> {
+        self.connections.iter().find(|v| v.id == id).cloned()
+    }
+
+    pub fn stream_owner(&self, stream: StreamId) -> Option<ConnectionId> {
+        self.routes.lock().unwrap().get(&stream).copied()
+    }
+
+    /// Transport for delivering events of all connections.
+    pub fn events(&self) -> EventRouter<P> {
+        EventRouter {
+            transports: self
+                .connections
+                .iter()
+                .map(|v| (v.id, v.transport.clone()))
+                .collect(),
+            routes: self.routes.clone(),
+        }
+    }
+
+    /// Waits for a message from any of the connections. Never completes if there are none.
+    pub async fn recv(&mut self) -> Result<(ConnectionId, Incoming<P>), P::Error> {
+        if self.connections.is_empty() {
+            return future::pending().await;
+        }
+
+        let (res, idx, rest) =
+            future::select_all(self.connections.iter().map(|v| v.transport.recv().boxed())).await;
+        drop(rest);
+
+        let id = self.connections[idx].id;
+
+        match res {
+            Ok(message) => Ok((id, Incoming::Message(message))),
+            Err(e) if e.is_disconnected() => {
+                self.connections.remove(idx);
+
+                let mut streams = Vec::new();
+                self.routes.lock().unwrap().retain(|&stream, owner| {
+                    if *owner == id {
+                        streams.push(stream);
+                        return false;
+                    }
+
+                    true
+                });
+
+                Ok((id, Incoming::Closed { streams }))
+            }
+            Err(e) => Err(e),
+        }
+    }
+}
+
+impl<P: Protocol> Default for Connections<P> {
+    fn default() -> Self {
+        Connections::new()
+    }
+}
+
+/// Transport of a single client, which remembers the streams opened by responses sent through
+/// it.
+///
+/// Nothing is sent to a client which went away, its connection is removed once receiving from it
+/// fails as well.
+#[derive(Debug)]
+pub struct Connection<P: Protocol> {
+    id: ConnectionId,
+    transport: BoxedServerTransport<P>,
+    routes: Routes,
+}
+
+impl<P: Protocol> Connection<P> {
+    pub fn id(&self) -> ConnectionId {
+        self.id
+    }
+
+    fn route(&self, response: &P::Res) {
+        if let Some(stream) = P::response_stream_id(response) {
+            self.routes.lock().unwrap().insert(stream, self.id);
+        }
+    }
+}
+
+impl<P: Protocol> ServerTransport<P> for Connection<P> {
+    async fn send(&self, message: ServerMessage<P>) -> Result<(), P::Error> {
+        match &message {
+            ServerMessage::Response {
+                payload: Ok(res), ..
+            } => self.route(res),
+            ServerMessage::Batch { responses } => {
+                for res in responses.iter().flat_map(|(_, v)| v) {
+                    self.route(res);
+                }
+            }
+            _ => {}
+        }
+
+        match self.transport.send(message).await {
+            Err(e) if e.is_disconnected() => Ok(()),
+            res => res,
+        }
+    }
+
+    async fn recv(&self) -> Result<ClientMessage<P>, P::Error> {
+        self.transport.recv().await
+    }
+
+    fn role(&self) -> Role {
+        self.transport.role()
+    }
+}
+
+impl<P: Protocol> Clone for Connection<P> {
+    fn clone(&self) -> Self {
+        Self {
+            id: self.id,
+            transport: self.transport.clone(),
+            routes: self.routes.clone(),
+        }
+    }
+}
+
+/// Sends events and closed streams to the connection which has opened the stream. Events of
+/// streams without a connection are dropped, and so are responses.
+///
+/// Receiving never completes.
+#[derive(Debug)]
+pub struct EventRouter<P: Protocol> {
+    transports: HashMap<ConnectionId, BoxedServerTransport<P>>,
+    routes: Routes,
+}
+
+impl<P: Protocol> ServerTransport<P> for EventRouter<P> {
+    async fn send(&self, message: ServerMessage<P>) -> Result<(), P::Error> {
+        let owner = {
+            let mut routes = self.routes.lock().unwrap();
+            match &message {
+                ServerMessage::Event { id, .. } => routes.get(id).copied(),
+                ServerMessage::CloseStream { id } => routes.remove(id),
+                _ => None,
+            }
+        };
+
+        let Some(transport) = owner.and_then(|v| self.transports.get(&v)) else {
+            return Ok(());
+        };
+
+        match transport.send(message).await {
+            Err(e) if e.is_disconnected() => Ok(()),
+            res => res,
+        }
+    }
+
+    async fn recv(&self) -> Result<ClientMessage<P>, P::Error> {
+        future::pending().await
+    }
+
+    fn role(&self) -> Role {
+        Role::ReadOnly
+    }
+}
+
+impl<P: Protocol> Clone for EventRouter<P> {
+    fn clone(&self) -> Self {
+        Self {
+            transports: self.transports.clone(),
+            routes: self.routes.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::executor::block_on;
+
+    use super::*;
+    use crate::transport::{local, ClientTransport};
+    use crate::RequestId;
+
+    #[derive(Debug, Clone, Eq, PartialEq, thiserror::Error)]
+    #[error("disconnected")]
+    struct Disconnected;
+
+    impl ProtocolError for Disconnected {
+        fn disconnected() -> Self {
+            Disconnected
+        }
+
+        fn invalid_type() -> Self {
+            Disconnected
+        }
+
+        fn is_disconnected(&self) -> bool {
+            true
+        }
+
+        fn is_invalid_type(&self) -> bool {
+            false
+        }
+    }
+
+    /// Every response opens the stream with the same id.
+    #[derive(Debug, Clone, Eq, PartialEq)]
+    struct TestProtocol;
+
+    impl Protocol for TestProtocol {
+        type Req = ();
+        type Res = u64;
+        type Event = u32;
+        type Error = Disconnected;
+
+        fn response_stream_id(res: &u64) -> Option<StreamId> {
+            Some(StreamId(*res))
+        }
+    }
+
+    #[test]
+    fn route_events() {
+        block_on(async {
+            let mut connections = Connections::<TestProtocol>::new();
+
+            let (first_client, first) = local(None);
+            let (second_client, second) = local(None);
+            let first = connections.add(first);
+            let second = connections.add(second);
+
+            for (id, stream) in [(first, 1), (second, 2)] {
+                let response = ServerMessage::Response {
+                    id: RequestId(0),
+                    payload: Ok(stream),
+                };
+                connections.get(id).unwrap().send(response).await.unwrap();
+            }
+
+            first_client.recv().await.unwrap();
+            second_client.recv().await.unwrap();
+
+            let event = ServerMessage::Event {
+                id: StreamId(2),
+                seq: 0,
+                payload: 5,
+            };
+            connections.events().send(event.clone()).await.unwrap();
+            assert_eq!(second_client.recv().await.unwrap(), event);
+            assert!(first_client.recv().now_or_never().is_none());
+
+            drop(second_client);
+
+            let (id, incoming) = connections.recv().await.unwrap();
+            assert_eq!(id, second);
+            assert!(matches!(incoming, Incoming::Closed { streams } if streams == [StreamId(2)]));
+
+            assert_eq!(connections.stream_owner(StreamId(1)), Some(first));
+            assert_eq!(connections.stream_owner(StreamId(2)), None);
+
+            // events of the closed connection are dropped
+            connections.events().send(event).await.unwrap();
+        });
+    }
+}
@@ -1,7 +1,7 @@
 use async_channel::{Receiver, Sender};
 
 use super::{ClientTransport, ServerTransport};
-use crate::{ClientMessage, Protocol, ProtocolError, ServerMessage};
+use crate::{ClientMessage, Protocol, ProtocolError, Role, ServerMessage};
 
 pub fn local<P: Protocol>(
     cap: Option<usize>,
@@ -21,6 +21,7 @@ pub fn local<P: Protocol>(
         LocalServerTransport {
             sender: server_sender,
             receiver: server_receiver,
+            role: Role::Admin,
         },
     )
 }
@@ -60,6 +61,14 @@ impl<P: Protocol> Clone for LocalClientTransport<P> {
 pub struct LocalServerTransport<P: Protocol> {
     sender: Sender<ServerMessage<P>>,
     receiver: Receiver<ClientMessage<P>>,
+    role: Role,
+}
+
+impl<P: Protocol> LocalServerTransport<P> {
+    /// Local clients are trusted with [`Role::Admin`] unless told otherwise.
+    pub fn with_role(self, role: Role) -> LocalServerTransport<P> {
+        LocalServerTransport { role, ..self }
+    }
 }
 
 impl<P: Protocol> ServerTransport<P> for LocalServerTransport<P> {
@@ -76,6 +85,10 @@ impl<P: Protocol> ServerTransport<P> for LocalServerTransport<P> {
             .await
             .map_err(|_| P::Error::disconnected())
     }
+
+    fn role(&self) -> Role {
+        self.role
+    }
 }
 
 impl<P: Protocol> Clone for LocalServerTransport<P> {
@@ -83,6 +96,7 @@ impl<P: Protocol> Clone for LocalServerTransport<P> {
         Self {
             sender: self.sender.clone(),
             receiver: self.receiver.clone(),
+            role: self.role,
         }
     }
 }
@@ -1,7 +1,18 @@
+mod batch;
+mod boxed;
+mod connections;
 mod local;
+mod socket;
 
+pub use self::batch::BatchingTransport;
+pub use self::boxed::BoxedServerTransport;
+pub use self::connections::{Connection, ConnectionId, Connections, EventRouter, Incoming};
 pub use self::local::{local, LocalClientTransport, LocalServerTransport};
-use crate::{ClientMessage, Protocol, ServerMessage};
+#[cfg(feature = "fuzzing")]
+pub(crate) use self::socket::read_frame;
+pub use self::socket::{SocketClientTransport, SocketListener, SocketServerTransport};
+
+use crate::{ClientMessage, Protocol, Role, ServerMessage};
 
 #[trait_variant::make(Send)]
 pub trait ClientTransport<P: Protocol>: Clone + Send + Sync + 'static {
@@ -15,4 +26,6 @@ pub trait ServerTransport<P: Protocol>: Clone + Send + Sync + 'static {
     async fn send(&self, message: ServerMessage<P>) -> Result<(), P::Error>;
 
     async fn recv(&self) -> Result<ClientMessage<P>, P::Error>;
+
+    fn role(&self) -> Role;
 }
@@ -0,0 +1,629 @@
+//! Transport over TCP and Unix domain sockets, for running the backend in another process.
+//!
+//! Each message is encoded with postcard and prefixed with its length as a little-endian `u32`.
+//! Before any messages are exchanged, the server challenges the client to prove that it knows
+//! one of the shared secrets, which also decides the client's role.
+//!
+//! Sockets are blocking, every connection has a reader and a writer thread which exchange
+//! messages with the transport through channels.
+
+use std::io::{self, Read, Write};
+use std::net::{self, Shutdown, TcpListener, TcpStream, ToSocketAddrs};
+#[cfg(unix)]
+use std::os::unix::net::{UnixListener, UnixStream};
+#[cfg(unix)]
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use async_channel::{Receiver, Sender};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use super::{ClientTransport, ServerTransport};
+use crate::{
+    Authenticator, Challenge, ChallengeResponse, ClientMessage, Protocol, ProtocolError, Role,
+    ServerMessage, SharedSecret,
+};
+
+/// Larger frames are treated as corrupted, so that a bad length doesn't exhaust memory.
+const MAX_FRAME_SIZE: usize = 64 * 1024 * 1024;
+
+/// How long the other side has to complete the handshake.
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+const HANDSHAKE_REJECTED: u8 = 0;
+const HANDSHAKE_ACCEPTED: u8 = 1;
+
+#[derive(Debug)]
+enum Socket {
+    Tcp(TcpStream),
+    #[cfg(unix)]
+    Unix(UnixStream),
+}
+
+impl Socket {
+    fn try_clone(&self) -> io::Result<Socket> {
+        match self {
+            Socket::Tcp(v) => v.try_clone().map(Socket::Tcp),
+            #[cfg(unix)]
+            Socket::Unix(v) => v.try_clone().map(Socket::Unix),
+        }
+    }
+
+    fn set_read_timeout(&self, timeout: Option<Duration>) -> io::Result<()> {
+        match self {
+            Socket::Tcp(v) => v.set_read_timeout(timeout),
+            #[cfg(unix)]
+            Socket::Unix(v) => v.set_read_timeout(timeout),
+        }
+    }
+
+    fn shutdown(&self) {
+        let _ = match self {
+            Socket::Tcp(v) => v.shutdown(Shutdown::Both),
+            #[cfg(unix)]
+            Socket::Unix(v) => v.shutdown(Shutdown::Both),
+        };
+    }
+}
+
+impl Read for Socket {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            Socket::Tcp(v) => v.read(buf),
+            #[cfg(unix)]
+            Socket::Unix(v) => v.read(buf),
+        }
+    }
+}
+
+impl Write for Socket {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Socket::Tcp(v) => v.write(buf),
+            #[cfg(unix)]
+            Socket::Unix(v) => v.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Socket::Tcp(v) => v.flush(),
+            #[cfg(unix)]
+            Socket::Unix(v) => v.flush(),
+        }
+    }
+}
+
+fn write_frame<T: Serialize>(
+    socket: &mut Socket,
+    buf: &mut Vec<u8>,
+    message: &T,
+) -> io::Result<()> {
+    buf.clear();
+    buf.extend_from_slice(&[0; 4]);
+
+    let mut frame = postcard::to_extend(message, std::mem::take(buf))
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+    let len = frame.len() - 4;
+    if len > MAX_FRAME_SIZE {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "message is too large",
+        ));
+    }
+
+    frame[..4].copy_from_slice(&(len as u32).to_le_bytes());
+    socket.write_all(&frame)?;
+
+    *buf = frame;
+    Ok(())
+}
+
+pub(crate) fn read_frame<T: DeserializeOwned>(
+    reader: &mut impl Read,
+    buf: &mut Vec<u8>,
+) -> io::Result<T> {
+    let mut len = [0; 4];
+    reader.read_exact(&mut len)?;
+
+    let len = u32::from_le_bytes(len) as usize;
+    if len > MAX_FRAME_SIZE {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "message is too large",
+        ));
+    }
+
+    buf.resize(len, 0);
+    reader.read_exact(buf)?;
+
+    postcard::from_bytes(buf).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+/// Moves messages between the channels and the socket until either side goes away.
+fn spawn_io<Out, In>(
+    socket: Socket,
+    outgoing: Receiver<Out>,
+    incoming: Sender<In>,
+) -> io::Result<()>
+where
+    Out: Serialize + Send + 'static,
+    In: DeserializeOwned + Send + 'static,
+{
+    let mut reader = socket.try_clone()?;
+    let mut writer = socket;
+
+    let outgoing_clone = outgoing.clone();
+    thread::spawn(move || {
+        let mut buf = Vec::new();
+        while let Ok(message) = read_frame::<In>(&mut reader, &mut buf) {
+            if incoming.send_blocking(message).is_err() {
+                break;
+            }
+        }
+
+        // wakes up the writer, and makes further sends fail
+        outgoing_clone.close();
+        reader.shutdown();
+    });
+
+    thread::spawn(move || {
+        let mut buf = Vec::new();
+        while let Ok(message) = outgoing.recv_blocking() {
+            if write_frame(&mut writer, &mut buf, &message).is_err() {
+                break;
+            }
+        }
+
+        outgoing.close();
+        writer.shutdown();
+    });
+
+    Ok(())
+}
+
+fn client_handshake(socket: &mut Socket, secret: &SharedSecret) -> io::Result<()> {
+    socket.set_read_timeout(Some(HANDSHAKE_TIMEOUT))?;
+
+    let mut challenge = Challenge { nonce: [0; 32] };
+    socket.read_exact(&mut challenge.nonce)?;
+    socket.write_all(&secret.respond(&challenge).mac)?;
+
+    let mut status = [0];
+    socket.read_exact(&mut status)?;
+    if status[0] != HANDSHAKE_ACCEPTED {
+        return Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            "server has rejected the shared secret",
+        ));
+    }
+
+    socket.set_read_timeout(None)
+}
+
+fn server_handshake(socket: &mut Socket, authenticator: &Authenticator) -> io::Result<Role> {
+    socket.set_read_timeout(Some(HANDSHAKE_TIMEOUT))?;
+
+    let challenge = Challenge::new_random();
+    socket.write_all(&challenge.nonce)?;
+
+    let mut response = ChallengeResponse { mac: [0; 32] };
+    socket.read_exact(&mut response.mac)?;
+
+    let Some(role) = authenticator.authenticate(&challenge, &response) else {
+        let _ = socket.write_all(&[HANDSHAKE_REJECTED]);
+        return Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            "client has failed to authenticate",
+        ));
+    };
+
+    socket.write_all(&[HANDSHAKE_ACCEPTED])?;
+    socket.set_read_timeout(None)?;
+
+    Ok(role)
+}
+
+#[derive(Debug)]
+pub struct SocketClientTransport<P: Protocol> {
+    sender: Sender<ClientMessage<P>>,
+    receiver: Receiver<ServerMessage<P>>,
+}
+
+impl<P: Protocol> SocketClientTransport<P>
+where
+    ClientMessage<P>: Serialize,
+    ServerMessage<P>: DeserializeOwned,
+{
+    pub fn connect_tcp(
+        addr: impl ToSocketAddrs,
+        secret: &SharedSecret,
+    ) -> io::Result<SocketClientTransport<P>> {
+        let socket = TcpStream::connect(addr)?;
+        socket.set_nodelay(true)?;
+        SocketClientTransport::new(Socket::Tcp(socket), secret)
+    }
+
+    #[cfg(unix)]
+    pub fn connect_unix(
+        path: impl AsRef<Path>,
+        secret: &SharedSecret,
+    ) -> io::Result<SocketClientTransport<P>> {
+        let socket = UnixStream::connect(path)?;
+        SocketClientTransport::new(Socket::Unix(socket), secret)
+    }
+
+    fn new(mut socket: Socket, secret: &SharedSecret) -> io::Result<SocketClientTransport<P>> {
+        client_handshake(&mut socket, secret)?;
+
+        let (sender, outgoing) = async_channel::unbounded();
+        let (incoming, receiver) = async_channel::unbounded();
+        spawn_io(socket, outgoing, incoming)?;
+
+        Ok(SocketClientTransport { sender, receiver })
+    }
+}
+
+impl<P: Protocol> ClientTransport<P> for SocketClientTransport<P> {
+    async fn send(&self, message: ClientMessage<P>) -> Result<(), P::Error> {
+        self.sender
+            .send(message)
+            .await
+            .map_err(|_| P::Error::disconnected())
+    }
+
+    async fn recv(&self) -> Result<ServerMessage<P>, P::Error> {
+        self.receiver
+            .recv()
+            .await
+            .map_err(|_| P::Error::disconnected())
+    }
+}
+
+impl<P: Protocol> Clone for SocketClientTransport<P> {
+    fn clone(&self) -> Self {
+        Self {
+            sender: self.sender.clone(),
+            receiver: self.receiver.clone(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct SocketServerTransport<P: Protocol> {
+    sender: Sender<ServerMessage<P>>,
+    receiver: Receiver<ClientMessage<P>>,
+    role: Role,
+}
+
+impl<P: Protocol> ServerTransport<P> for SocketServerTransport<P> {
+    async fn send(&self, message: ServerMessage<P>) -> Result<(), P::Error> {
+        self.sender
+            .send(message)
+            .await
+            .map_err(|_| P::Error::disconnected())
+    }
+
+    async fn recv(&self) -> Result<ClientMessage<P>, P::Error> {
+        self.receiver
+            .recv()
+            .await
+            .map_err(|_| P::Error::disconnected())
+    }
+
+    fn role(&self) -> Role {
+        self.role
+    }
+}
+
+impl<P: Protocol> Clone for SocketServerTransport<P> {
+    fn clone(&self) -> Self {
+        Self {
+            sender: self.sender.clone(),
+            receiver: self.receiver.clone(),
+            role: self.role,
+        }
+    }
+}
+
+#[derive(Debug)]
+enum Listener {
+    Tcp(TcpListener),
+    #[cfg(unix)]
+    Unix(UnixListener),
+}
+
+/// Accepts connections of clients, one transport per client.
+#[derive(Debug)]
+pub struct SocketListener {
+    listener: Listener,
+    authenticator: Authenticator,
+}
+
+impl SocketListener {
+    pub fn bind_tcp(
+        addr: impl ToSocketAddrs,
+        authenticator: Authenticator,
+    ) -> io::Result<SocketListener> {
+        Ok(SocketListener {
+            listener: Listener::Tcp(TcpListener::bind(addr)?),
+            authenticator,
+        })
+    }
+
+    #[cfg(unix)]
+    pub fn bind_unix(
+        path: impl AsRef<Path>,
+        authenticator: Authenticator,
+    ) -> io::Result<SocketListener> {
+        Ok(SocketListener {
+            listener: Listener::Unix(UnixListener::bind(path)?),
+            authenticator,
+        })
+    }
+
+    /// Address of a TCP listener, e.g. to find out which port was picked.
+    pub fn tcp_addr(&self) -> Option<net::SocketAddr> {
+        match &self.listener {
+            Listener::Tcp(v) => v.local_addr().ok(),
+            #[cfg(unix)]
+            Listener::Unix(_) => None,
+        }
+    }
+
+    /// Waits for the next client. It still has to authenticate, see
+    /// [`PendingConnection::handshake`].
+    pub fn accept(&self) -> io::Result<PendingConnection> {
+        let socket = match &self.listener {
+            Listener::Tcp(v) => {
+                let (socket, _) = v.accept()?;
+                socket.set_nodelay(true)?;
+                Socket::Tcp(socket)
+            }
+            #[cfg(unix)]
+            Listener::Unix(v) => Socket::Unix(v.accept()?.0),
+        };
+
+        Ok(PendingConnection {
+            socket,
+            authenticator: self.authenticator.clone(),
+        })
+    }
+
+    /// Accepts clients until `on_connect` returns false. Every handshake runs on a thread of its
+    /// own, so that a client which stalls during it doesn't hold up the others.
+    pub fn serve<P: Protocol>(
+        &self,
+        on_connect: impl Fn(SocketServerTransport<P>) -> bool + Send + Sync + 'static,
+    ) -> io::Result<()>
+    where
+        ServerMessage<P>: Serialize,
+        ClientMessage<P>: DeserializeOwned,
+    {
+        let on_connect = Arc::new(on_connect);
+        let stopped = Arc::new(AtomicBool::new(false));
+
+        while !stopped.load(Ordering::Relaxed) {
+            let pending = match self.accept() {
+                Ok(v) => v,
+                Err(e) if is_transient(&e) => continue,
+                Err(e) => return Err(e),
+            };
+
+            let on_connect = on_connect.clone();
+            let stopped = stopped.clone();
+
+            thread::spawn(move || {
+                // clients which fail to authenticate are simply disconnected
+                if let Ok(transport) = pending.handshake() {
+                    if !on_connect(transport) {
+                        stopped.store(true, Ordering::Relaxed);
+                    }
+                }
+            });
+        }
+
+        Ok(())
+    }
+}
+
+/// Errors of a single client which shouldn't stop the listener.
+fn is_transient(error: &io::Error) -> bool {
+    matches!(
+        error.kind(),
+        io::ErrorKind::ConnectionAborted
+            | io::ErrorKind::ConnectionReset
+            | io::ErrorKind::Interrupted
+    )
+}
+
+/// Client which has connected, but hasn't authenticated yet.
+#[derive(Debug)]
+pub struct PendingConnection {
+    socket: Socket,
+    authenticator: Authenticator,
+}
+
+impl PendingConnection {
+    /// Blocks until the client completes the handshake, for up to 10 seconds.
+    pub fn handshake<P: Protocol>(mut self) -> io::Result<SocketServerTransport<P>>
+    where
+        ServerMessage<P>: Serialize,
+        ClientMessage<P>: DeserializeOwned,
+    {
+        let role = match server_handshake(&mut self.socket, &self.authenticator) {
+            Ok(v) => v,
+            Err(e) => {
+                self.socket.shutdown();
+                return Err(e);
+            }
+        };
+
+        let (sender, outgoing) = async_channel::unbounded();
+        let (incoming, receiver) = async_channel::unbounded();
+        spawn_io(self.socket, outgoing, incoming)?;
+
+        Ok(SocketServerTransport {
+            sender,
+            receiver,
+            role,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc;
+
+    use futures::executor::block_on;
+
+    use super::*;
+
+    #[derive(Debug, Clone, Eq, PartialEq, thiserror::Error, Serialize, serde::Deserialize)]
+    enum TestError {
+        #[error("disconnected")]
+        Disconnected,
+        #[error("invalid type")]
+        InvalidType,
+    }
+
+    impl ProtocolError for TestError {
+        fn disconnected() -> Self {
+            TestError::Disconnected
+        }
+
+        fn invalid_type() -> Self {
+            TestError::InvalidType
+        }
+
+        fn is_disconnected(&self) -> bool {
+            *self == TestError::Disconnected
+        }
+
+        fn is_invalid_type(&self) -> bool {
+            *self == TestError::InvalidType
+        }
+    }
+
+    #[derive(Debug, Clone, Eq, PartialEq)]
+    struct TestProtocol;
+
+    impl Protocol for TestProtocol {
+        type Req = u32;
+        type Res = u32;
+        type Event = u32;
+        type Error = TestError;
+
+        fn response_stream_id(_res: &u32) -> Option<crate::StreamId> {
+            None
+        }
+    }
+
+    fn authenticator(secret: &SharedSecret) -> Authenticator {
+        let mut authenticator = Authenticator::new();
+        authenticator.add_secret(secret.clone(), Role::Editor);
+        authenticator
+    }
+
+    fn roundtrip(
+        client: SocketClientTransport<TestProtocol>,
+        server: SocketServerTransport<TestProtocol>,
+    ) {
+        block_on(async {
+            assert_eq!(server.role(), Role::Editor);
+
+            let request = ClientMessage::Request {
+                id: crate::RequestId(1),
+                payload: 2,
+            };
+            client.send(request.clone()).await.unwrap();
+            assert_eq!(server.recv().await.unwrap(), request);
+
+            let response = ServerMessage::Response {
+                id: crate::RequestId(1),
+                payload: Err(TestError::InvalidType),
+            };
+            server.send(response.clone()).await.unwrap();
+            assert_eq!(client.recv().await.unwrap(), response);
+
+            drop(server);
+            assert_eq!(client.recv().await, Err(TestError::Disconnected));
+        });
+    }
+
+    #[test]
+    fn tcp() {
+        let secret = SharedSecret::new(b"secret");
+        let listener = SocketListener::bind_tcp("127.0.0.1:0", authenticator(&secret)).unwrap();
+        let addr = listener.tcp_addr().unwrap();
+
+        let client = thread::spawn(move || SocketClientTransport::connect_tcp(addr, &secret));
+        let server = listener.accept().unwrap().handshake().unwrap();
+
+        roundtrip(client.join().unwrap().unwrap(), server);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn unix() {
+        let path = std::env::temp_dir().join(format!("rdaw-rpc-{}.sock", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let secret = SharedSecret::new(b"secret");
+        let listener = SocketListener::bind_unix(&path, authenticator(&secret)).unwrap();
+
+        let client_path = path.clone();
+        let client =
+            thread::spawn(move || SocketClientTransport::connect_unix(client_path, &secret));
+        let server = listener.accept().unwrap().handshake().unwrap();
+
+        roundtrip(client.join().unwrap().unwrap(), server);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn wrong_secret() {
+        let secret = SharedSecret::new(b"secret");
+        let listener = SocketListener::bind_tcp("127.0.0.1:0", authenticator(&secret)).unwrap();
+        let addr = listener.tcp_addr().unwrap();
+
+        let client = thread::spawn(move || {
+            SocketClientTransport::<TestProtocol>::connect_tcp(addr, &SharedSecret::new(b"wrong"))
+        });
+
+        let err = listener
+            .accept()
+            .unwrap()
+            .handshake::<TestProtocol>()
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::PermissionDenied);
+
+        let err = client.join().unwrap().unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::PermissionDenied);
+    }
+
+    #[test]
+    fn stalled_handshake() {
+        let secret = SharedSecret::new(b"secret");
+        let listener = SocketListener::bind_tcp("127.0.0.1:0", authenticator(&secret)).unwrap();
+        let addr = listener.tcp_addr().unwrap();
+
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            listener.serve::<TestProtocol>(move |transport| sender.send(transport).is_ok())
+        });
+
+        // connects, but never responds to the challenge
+        let _stalled = TcpStream::connect(addr).unwrap();
+
+        let client = SocketClientTransport::connect_tcp(addr, &secret).unwrap();
+        let server = receiver.recv_timeout(HANDSHAKE_TIMEOUT / 2).unwrap();
+
+        roundtrip(client, server);
+    }
+}
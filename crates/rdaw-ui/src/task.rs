@@ -1,25 +1,51 @@
+use std::cell::{Cell, RefCell};
 use std::collections::VecDeque;
 use std::future::Future;
+use std::rc::Rc;
 use std::sync::{Arc, Mutex};
+use std::task::{Poll, Waker};
+use std::time::Duration;
 
+use floem::action::exec_after;
 use floem::ext_event::{create_ext_action, register_ext_trigger};
 use floem::reactive::{provide_context, use_context, with_scope, Scope};
-use futures::executor::ThreadPool;
-use futures::task::SpawnExt;
+use futures::executor::{LocalPool, LocalSpawner, ThreadPool};
+use futures::task::{LocalSpawnExt, SpawnExt};
 use futures::{Stream, StreamExt};
 
 pub fn provide_executor(executor: Arc<ThreadPool>) {
     provide_context(executor);
 }
 
+pub fn provide_manual_executor(executor: ManualExecutor) {
+    provide_context(executor);
+}
+
 pub fn spawn<T: Send + 'static>(
     future: impl Future<Output = T> + Send + 'static,
     on_completed: impl FnOnce(T) + 'static,
 ) {
     let scope = Scope::current();
+    let child = scope.create_child();
+
+    if let Some(executor) = use_context::<ManualExecutor>() {
+        let handle = executor
+            .inner
+            .spawner
+            .spawn_local_with_handle(async move {
+                let v = future.await;
+                with_scope(child, move || {
+                    on_completed(v);
+                });
+            })
+            .unwrap();
+
+        scope.create_rw_signal(handle);
+        return;
+    }
+
     let executor = use_context::<Arc<ThreadPool>>().unwrap();
 
-    let child = scope.create_child();
     let send = create_ext_action(scope, move |v| {
         with_scope(child, move || {
             on_completed(v);
@@ -35,12 +61,57 @@ pub fn spawn<T: Send + 'static>(
     scope.create_rw_signal(handle);
 }
 
+/// Runs `f` in the current scope once `duration` has passed, unless the scope is disposed before.
+/// With a [`ManualExecutor`], time only passes in [`ManualExecutor::advance`].
+pub fn run_after(duration: Duration, f: impl FnOnce() + 'static) {
+    let scope = Scope::current();
+    let child = scope.create_child();
+
+    if let Some(executor) = use_context::<ManualExecutor>() {
+        let sleep = executor.sleep(duration);
+        let handle = executor
+            .inner
+            .spawner
+            .spawn_local_with_handle(async move {
+                sleep.await;
+                with_scope(child, f);
+            })
+            .unwrap();
+
+        scope.create_rw_signal(handle);
+        return;
+    }
+
+    let is_cancelled = Rc::new(Cell::new(false));
+    scope.create_rw_signal(TimerGuard {
+        is_cancelled: is_cancelled.clone(),
+    });
+
+    exec_after(duration, move |_| {
+        if !is_cancelled.get() {
+            with_scope(child, f);
+        }
+    });
+}
+
+/// Cancels a timer of [`run_after`] when its scope is disposed.
+struct TimerGuard {
+    is_cancelled: Rc<Cell<bool>>,
+}
+
+impl Drop for TimerGuard {
+    fn drop(&mut self) {
+        self.is_cancelled.set(true);
+    }
+}
+
 pub fn stream_for_each<T: Send + 'static>(
     mut stream: impl Stream<Item = T> + Send + Unpin + 'static,
     on_message: impl Fn(T) + 'static,
 ) {
     let scope = Scope::current();
     let queue = Arc::new(Mutex::new(VecDeque::new()));
+    let is_manual = use_context::<ManualExecutor>().is_some();
 
     let trigger = scope.create_trigger();
     trigger.notify();
@@ -62,9 +133,88 @@ pub fn stream_for_each<T: Send + 'static>(
                     queue.push_back(value);
                 }
 
-                register_ext_trigger(trigger);
+                if is_manual {
+                    trigger.notify();
+                } else {
+                    register_ext_trigger(trigger);
+                }
             }
         },
         drop,
     );
 }
+
+#[derive(Clone)]
+pub struct ManualExecutor {
+    inner: Rc<ManualExecutorInner>,
+}
+
+struct ManualExecutorInner {
+    pool: RefCell<LocalPool>,
+    spawner: LocalSpawner,
+    now: Cell<Duration>,
+    timers: RefCell<Vec<Waker>>,
+}
+
+impl ManualExecutor {
+    pub fn new() -> ManualExecutor {
+        let pool = LocalPool::new();
+        let spawner = pool.spawner();
+
+        ManualExecutor {
+            inner: Rc::new(ManualExecutorInner {
+                pool: RefCell::new(pool),
+                spawner,
+                now: Cell::new(Duration::ZERO),
+                timers: RefCell::new(Vec::new()),
+            }),
+        }
+    }
+
+    pub fn spawner(&self) -> LocalSpawner {
+        self.inner.spawner.clone()
+    }
+
+    pub fn run_until_stalled(&self) {
+        self.inner.pool.borrow_mut().run_until_stalled();
+    }
+
+    pub fn run_until<F: Future>(&self, future: F) -> F::Output {
+        self.inner.pool.borrow_mut().run_until(future)
+    }
+
+    pub fn now(&self) -> Duration {
+        self.inner.now.get()
+    }
+
+    pub fn advance(&self, duration: Duration) {
+        self.inner.now.set(self.inner.now.get() + duration);
+
+        let timers = std::mem::take(&mut *self.inner.timers.borrow_mut());
+        for waker in timers {
+            waker.wake();
+        }
+
+        self.run_until_stalled();
+    }
+
+    pub fn sleep(&self, duration: Duration) -> impl Future<Output = ()> {
+        let inner = self.inner.clone();
+        let deadline = inner.now.get() + duration;
+
+        std::future::poll_fn(move |ctx| {
+            if inner.now.get() >= deadline {
+                return Poll::Ready(());
+            }
+
+            inner.timers.borrow_mut().push(ctx.waker().clone());
+            Poll::Pending
+        })
+    }
+}
+
+impl Default for ManualExecutor {
+    fn default() -> ManualExecutor {
+        ManualExecutor::new()
+    }
+}
@@ -0,0 +1,61 @@
+use std::f64::consts::FRAC_1_SQRT_2;
+
+use floem::kurbo::{Point, Rect};
+use floem::views::Decorators;
+use floem::IntoView;
+
+use super::live_layer;
+use crate::theme::{ColorKind, Level, Theme};
+
+/// Rotated by 45 degrees, so that mono signals are a vertical line.
+pub fn goniometer(points: impl Fn() -> Vec<[f32; 2]> + 'static) -> impl IntoView {
+    let theme = Theme::get();
+    let bg = theme.colors[ColorKind::Surface][Level::Low].bg;
+    let axes = theme.colors[ColorKind::Surface][Level::High].bg;
+    let fg = theme.colors[ColorKind::Success][Level::High].bg;
+
+    live_layer(points, move |cx, size, points| {
+        cx.fill(&size.to_rect(), bg, 0.0);
+
+        let center = size.to_rect().center();
+        let radius = size.width.min(size.height) / 2.0;
+
+        cx.fill(
+            &Rect::new(center.x - 0.5, 0.0, center.x + 0.5, size.height),
+            axes,
+            0.0,
+        );
+        cx.fill(
+            &Rect::new(0.0, center.y - 0.5, size.width, center.y + 0.5),
+            axes,
+            0.0,
+        );
+
+        for &[left, right] in points {
+            let (left, right) = (f64::from(left), f64::from(right));
+            let side = ((right - left) * FRAC_1_SQRT_2).clamp(-1.0, 1.0);
+            let mid = ((left + right) * FRAC_1_SQRT_2).clamp(-1.0, 1.0);
+
+            let point = Point::new(center.x + side * radius, center.y - mid * radius);
+            cx.fill(&Rect::from_center_size(point, (1.0, 1.0)), fg, 0.0);
+        }
+    })
+    .style(|s| s.width(160.0).height(160.0))
+}
+
+pub fn correlation_meter(correlation: impl Fn() -> f32 + 'static) -> impl IntoView {
+    let theme = Theme::get();
+    let bg = theme.colors[ColorKind::Surface][Level::Low].bg;
+    let fg = theme.colors[ColorKind::Success][Level::High].bg;
+    let warning = theme.colors[ColorKind::Warning][Level::High].bg;
+
+    live_layer(correlation, move |cx, size, &correlation| {
+        cx.fill(&size.to_rect(), bg, 0.0);
+
+        let middle = size.width / 2.0;
+        let x = middle + middle * f64::from(correlation.clamp(-1.0, 1.0));
+        let bar = Rect::new(middle.min(x), 0.0, middle.max(x), size.height);
+        cx.fill(&bar, if correlation < 0.0 { warning } else { fg }, 0.0);
+    })
+    .style(|s| s.width(160.0).height(8.0))
+}
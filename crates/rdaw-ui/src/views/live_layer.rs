@@ -0,0 +1,124 @@
+use std::borrow::Cow;
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+use floem::action::exec_after;
+use floem::context::PaintCx;
+use floem::kurbo::Size;
+use floem::reactive::{create_effect, provide_context, use_context, RwSignal};
+use floem::{View, ViewId};
+
+const DEFAULT_MAX_FPS: u32 = 60;
+
+/// How often live layers may repaint.
+#[derive(Debug, Clone, Copy)]
+pub struct LiveLayerSettings {
+    pub max_fps: RwSignal<u32>,
+}
+
+impl LiveLayerSettings {
+    pub fn new(max_fps: u32) -> LiveLayerSettings {
+        LiveLayerSettings {
+            max_fps: RwSignal::new(max_fps),
+        }
+    }
+
+    pub fn get() -> LiveLayerSettings {
+        use_context().unwrap_or_else(|| LiveLayerSettings::new(DEFAULT_MAX_FPS))
+    }
+
+    pub fn provide(self) {
+        provide_context(self);
+    }
+
+    fn frame_interval(&self) -> Duration {
+        Duration::from_secs_f64(1.0 / f64::from(self.max_fps.get_untracked().max(1)))
+    }
+}
+
+impl Default for LiveLayerSettings {
+    fn default() -> LiveLayerSettings {
+        LiveLayerSettings::new(DEFAULT_MAX_FPS)
+    }
+}
+
+/// Paints a value which changes all the time, like a meter level or the playhead position.
+///
+/// The value is only read by the layer, so its changes don't restyle or relayout any views.
+/// They request a paint instead, at most [`LiveLayerSettings::max_fps`] times per second.
+pub fn live_layer<T: 'static>(
+    value: impl Fn() -> T + 'static,
+    paint: impl Fn(&mut PaintCx, Size, &T) + 'static,
+) -> LiveLayer<T> {
+    let id = ViewId::new();
+    let settings = LiveLayerSettings::get();
+
+    let current = Rc::new(RefCell::new(None));
+    let last_paint = Rc::new(Cell::new(None::<Instant>));
+    let is_scheduled = Rc::new(Cell::new(false));
+
+    create_effect({
+        let current = current.clone();
+        let last_paint = last_paint.clone();
+        move |_| {
+            *current.borrow_mut() = Some(value());
+
+            if is_scheduled.get() {
+                return;
+            }
+
+            let interval = settings.frame_interval();
+            let elapsed = last_paint.get().map_or(interval, |v| v.elapsed());
+
+            if elapsed >= interval {
+                id.request_paint();
+                return;
+            }
+
+            is_scheduled.set(true);
+            let is_scheduled = is_scheduled.clone();
+            exec_after(interval - elapsed, move |_| {
+                is_scheduled.set(false);
+                id.request_paint();
+            });
+        }
+    });
+
+    LiveLayer {
+        id,
+        current,
+        last_paint,
+        paint: Box::new(paint),
+    }
+}
+
+pub struct LiveLayer<T> {
+    id: ViewId,
+    current: Rc<RefCell<Option<T>>>,
+    last_paint: Rc<Cell<Option<Instant>>>,
+    #[allow(clippy::type_complexity)]
+    paint: Box<dyn Fn(&mut PaintCx, Size, &T)>,
+}
+
+impl<T: 'static> View for LiveLayer<T> {
+    fn id(&self) -> ViewId {
+        self.id
+    }
+
+    fn debug_name(&self) -> Cow<'static, str> {
+        "LiveLayer".into()
+    }
+
+    fn paint(&mut self, cx: &mut PaintCx) {
+        let Some(size) = self.id.get_size() else {
+            return;
+        };
+
+        if let Some(value) = &*self.current.borrow() {
+            (self.paint)(cx, size, value);
+        }
+
+        self.last_paint.set(Some(Instant::now()));
+    }
+}
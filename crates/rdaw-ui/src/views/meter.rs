@@ -0,0 +1,23 @@
+use floem::kurbo::Rect;
+use floem::views::Decorators;
+use floem::IntoView;
+
+use super::live_layer;
+use crate::theme::{ColorKind, Level, Theme};
+
+/// Vertical bar showing a level from 0 to 1, which turns red above 1.
+pub fn meter(level: impl Fn() -> f32 + 'static) -> impl IntoView {
+    let theme = Theme::get();
+    let bg = theme.colors[ColorKind::Surface][Level::Low].bg;
+    let fg = theme.colors[ColorKind::Success][Level::High].bg;
+    let clip = theme.colors[ColorKind::Error][Level::High].bg;
+
+    live_layer(level, move |cx, size, &level| {
+        cx.fill(&size.to_rect(), bg, 0.0);
+
+        let height = size.height * f64::from(level.clamp(0.0, 1.0));
+        let bar = Rect::new(0.0, size.height - height, size.width, size.height);
+        cx.fill(&bar, if level > 1.0 { clip } else { fg }, 0.0);
+    })
+    .style(|s| s.width(8.0).height_full())
+}
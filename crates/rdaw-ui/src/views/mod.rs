@@ -1,5 +1,11 @@
 mod button;
+mod goniometer;
+mod live_layer;
+mod meter;
 pub mod tree;
 
 pub use self::button::button;
+pub use self::goniometer::{correlation_meter, goniometer};
+pub use self::live_layer::{live_layer, LiveLayer, LiveLayerSettings};
+pub use self::meter::meter;
 pub use self::tree::tree;
@@ -1,9 +1,16 @@
+use std::io;
 use std::sync::Arc;
 use std::thread;
 
 use futures::executor::block_on;
+use futures::{future, StreamExt};
+use rdaw_api::BackendProtocol;
+use rdaw_audio::driver::NullDriver;
+use rdaw_backend::log::LogBuffer;
 use rdaw_backend::Backend;
-use rdaw_rpc::{transport, Client};
+use rdaw_frontend::api::Resync;
+use rdaw_rpc::transport::{self, SocketClientTransport, SocketListener};
+use rdaw_rpc::{Authenticator, Client, Role, SharedSecret};
 use tracing_error::ErrorLayer;
 use tracing_subscriber::fmt::format::FmtSpan;
 use tracing_subscriber::layer::SubscriberExt;
@@ -15,14 +22,56 @@ fn main() {
         .with_span_events(FmtSpan::NEW | FmtSpan::CLOSE)
         .with_filter(EnvFilter::from_default_env());
 
+    let log_buffer = LogBuffer::default();
+
     tracing_subscriber::registry()
         .with(subscriber)
+        .with(log_buffer.layer())
         .with(ErrorLayer::default())
         .init();
 
+    let safe_mode = std::env::args().skip(1).any(|v| v == "--safe-mode");
+    if safe_mode {
+        tracing::info!("starting in safe mode");
+    }
+
+    let listen_addr = std::env::args()
+        .skip(1)
+        .find_map(|v| v.strip_prefix("--listen=").map(String::from));
+
+    let connect_addr = std::env::args()
+        .skip(1)
+        .find_map(|v| v.strip_prefix("--connect=").map(String::from));
+
+    if let Some(addr) = connect_addr {
+        run_remote(addr);
+        return;
+    }
+
     let (client_transport, server_transport) = transport::local(None);
 
     let mut backend = Backend::new(server_transport);
+    backend.set_safe_mode(safe_mode);
+    backend.set_log_buffer(log_buffer);
+
+    match rdaw_pipewire::Driver::new() {
+        Ok(driver) => backend.set_audio_driver(Arc::new(driver)),
+        Err(error) => {
+            tracing::warn!(
+                ?error,
+                "failed to connect to pipewire, audio output is disabled"
+            );
+            backend.set_audio_driver(Arc::new(NullDriver::new()));
+        }
+    }
+
+    if let Some(addr) = listen_addr {
+        match bind(&addr) {
+            Ok(listener) => backend.listen(listener),
+            Err(error) => tracing::error!(?error, %addr, "failed to listen for remote clients"),
+        }
+    }
+
     thread::spawn(move || block_on(backend.handle()).unwrap());
 
     let client = Client::new(client_transport);
@@ -30,5 +79,94 @@ fn main() {
     let client_clone = client.clone();
     thread::spawn(move || block_on(client_clone.handle()).unwrap());
 
-    rdaw_frontend::run(Arc::new(client));
+    block_on(rdaw_frontend::configure_local_backend(&client)).unwrap();
+
+    let resync = Resync::new({
+        let client = client.clone();
+        move || client.subscribe_resync().boxed()
+    });
+
+    rdaw_frontend::run(Arc::new(client), resync);
+}
+
+/// Runs the frontend for the backend at `unix:<path>` or a TCP address, authenticating with the
+/// secret from `RDAW_SECRET`. The client connects again whenever the backend goes away.
+fn run_remote(addr: String) {
+    let Some(secret) = std::env::var_os("RDAW_SECRET") else {
+        tracing::error!("no secret for connecting to the backend is set");
+        return;
+    };
+
+    let secret = SharedSecret::new(secret.as_encoded_bytes());
+
+    let transport = match connect(&addr, &secret) {
+        Ok(v) => v,
+        Err(error) => {
+            tracing::error!(?error, %addr, "failed to connect to the backend");
+            return;
+        }
+    };
+
+    let client = Client::new(transport);
+
+    let client_clone = client.clone();
+    thread::spawn(move || {
+        let reconnect = move || future::ready(connect(&addr, &secret).map_err(Into::into));
+
+        // nothing else runs on this thread while the client is disconnected
+        let sleep = |delay| {
+            thread::sleep(delay);
+            future::ready(())
+        };
+
+        if let Err(error) = block_on(client_clone.handle_reconnecting(reconnect, sleep)) {
+            tracing::error!(?error, "lost connection to the backend");
+        }
+    });
+
+    let resync = Resync::new({
+        let client = client.clone();
+        move || client.subscribe_resync().boxed()
+    });
+
+    rdaw_frontend::run(Arc::new(client), resync);
+}
+
+fn connect(
+    addr: &str,
+    secret: &SharedSecret,
+) -> io::Result<SocketClientTransport<BackendProtocol>> {
+    match addr.strip_prefix("unix:") {
+        Some(path) => SocketClientTransport::connect_unix(path, secret),
+        None => SocketClientTransport::connect_tcp(addr, secret),
+    }
+}
+
+/// Listens on `unix:<path>` or a TCP address. Remote clients authenticate with the secrets from
+/// `RDAW_SECRET` (editors) and `RDAW_READ_ONLY_SECRET`.
+fn bind(addr: &str) -> io::Result<SocketListener> {
+    let mut authenticator = Authenticator::new();
+    let mut has_secret = false;
+
+    for (var, role) in [
+        ("RDAW_SECRET", Role::Editor),
+        ("RDAW_READ_ONLY_SECRET", Role::ReadOnly),
+    ] {
+        if let Some(secret) = std::env::var_os(var) {
+            authenticator.add_secret(SharedSecret::new(secret.as_encoded_bytes()), role);
+            has_secret = true;
+        }
+    }
+
+    if !has_secret {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "no secret for remote clients is set",
+        ));
+    }
+
+    match addr.strip_prefix("unix:") {
+        Some(path) => SocketListener::bind_unix(path, authenticator),
+        None => SocketListener::bind_tcp(addr, authenticator),
+    }
 }
@@ -0,0 +1,22 @@
+#![no_main]
+
+use std::cell::RefCell;
+
+use libfuzzer_sys::fuzz_target;
+use rdaw_backend::fuzz::{ObjectFuzzer, OBJECT_TYPES};
+
+thread_local! {
+    static FUZZER: RefCell<ObjectFuzzer> = RefCell::new(ObjectFuzzer::new().unwrap());
+}
+
+fuzz_target!(|data: &[u8]| {
+    let Some((&ty, data)) = data.split_first() else {
+        return;
+    };
+
+    let ty = OBJECT_TYPES[usize::from(ty) % OBJECT_TYPES.len()];
+
+    FUZZER.with_borrow_mut(|fuzzer| {
+        let _ = fuzzer.deserialize(ty, data);
+    });
+});
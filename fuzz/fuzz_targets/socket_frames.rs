@@ -0,0 +1,10 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use rdaw_api::BackendProtocol;
+use rdaw_rpc::fuzz::read_frames;
+use rdaw_rpc::ClientMessage;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = read_frames::<ClientMessage<BackendProtocol>>(data);
+});